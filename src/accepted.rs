@@ -0,0 +1,522 @@
+//! Accepted findings: known differences that later comparisons suppress.
+//!
+//! `dpc review` writes `accepted.json` after a human signs off on findings;
+//! `dpc compare --accepted accepted.json` then masks accepted pixel regions
+//! before scoring and drops the other accepted findings from the output.
+//! Findings are matched by content rather than index, so an accepted diff stays
+//! accepted when unrelated diffs come and go between runs.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DpcError, Result};
+use crate::metrics::SeverityCalibration;
+use crate::types::{
+    ColorDiffKind, DiffSeverity, LayoutDiffKind, MetricScores, RegionBounds, TypographyIssue,
+};
+
+/// Minimum intersection-over-union for two regions to count as the same finding.
+pub const REGION_MATCH_IOU: f32 = 0.5;
+
+/// One finding from a compare result, reduced to what identifies it across runs.
+///
+/// Regions use the coordinates of the compare output: normalized (0.0 - 1.0)
+/// for pixel diffs, the source view's for layout diffs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "metric",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum Finding {
+    Pixel {
+        region: RegionBounds,
+    },
+    Layout {
+        kind: LayoutDiffKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        region: RegionBounds,
+    },
+    Typography {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        element_id_ref: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        element_id_impl: Option<String>,
+        /// Element name for display; not used for matching.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        issues: Vec<TypographyIssue>,
+    },
+    Color {
+        kind: ColorDiffKind,
+        ref_color: String,
+        impl_color: String,
+    },
+    MissingText {
+        text: String,
+    },
+    ExtraText {
+        text: String,
+    },
+}
+
+impl Finding {
+    /// List every finding in `scores`, metric by metric.
+    pub fn collect(scores: &MetricScores) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if let Some(pixel) = &scores.pixel {
+            findings.extend(pixel.diff_regions.iter().map(|r| Finding::Pixel {
+                region: bounds(r.x, r.y, r.width, r.height),
+            }));
+        }
+        if let Some(layout) = &scores.layout {
+            findings.extend(layout.diff_regions.iter().map(|r| Finding::Layout {
+                kind: r.kind,
+                label: r.label.clone(),
+                region: bounds(r.x, r.y, r.width, r.height),
+            }));
+        }
+        if let Some(typography) = &scores.typography {
+            findings.extend(typography.diffs.iter().map(|d| Finding::Typography {
+                element_id_ref: d.element_id_ref.clone(),
+                element_id_impl: d.element_id_impl.clone(),
+                label: d.label.clone(),
+                issues: d.issues.clone(),
+            }));
+        }
+        if let Some(color) = &scores.color {
+            findings.extend(color.diffs.iter().map(|d| Finding::Color {
+                kind: d.kind,
+                ref_color: d.ref_color.clone(),
+                impl_color: d.impl_color.clone(),
+            }));
+        }
+        if let Some(content) = &scores.content {
+            findings.extend(
+                content
+                    .missing_text
+                    .iter()
+                    .map(|text| Finding::MissingText { text: text.clone() }),
+            );
+            findings.extend(
+                content
+                    .extra_text
+                    .iter()
+                    .map(|text| Finding::ExtraText { text: text.clone() }),
+            );
+        }
+        findings
+    }
+
+    /// Whether `self` (an accepted finding) covers `other` (a fresh one).
+    ///
+    /// Regions match when they overlap by [`REGION_MATCH_IOU`]; a typography
+    /// finding is covered when its issues are a subset of the accepted ones.
+    /// A layout finding accepted without a label (before element names were
+    /// reported) covers the labelled one.
+    pub fn covers(&self, other: &Finding) -> bool {
+        match (self, other) {
+            (Finding::Pixel { region: a }, Finding::Pixel { region: b }) => {
+                iou(a, b) >= REGION_MATCH_IOU
+            }
+            (
+                Finding::Layout {
+                    kind: ka,
+                    label: la,
+                    region: ra,
+                },
+                Finding::Layout {
+                    kind: kb,
+                    label: lb,
+                    region: rb,
+                },
+            ) => ka == kb && (la.is_none() || la == lb) && iou(ra, rb) >= REGION_MATCH_IOU,
+            (
+                Finding::Typography {
+                    element_id_ref: ra,
+                    element_id_impl: ia,
+                    issues: accepted,
+                    ..
+                },
+                Finding::Typography {
+                    element_id_ref: rb,
+                    element_id_impl: ib,
+                    issues,
+                    ..
+                },
+            ) => ra == rb && ia == ib && issues.iter().all(|i| accepted.contains(i)),
+            (
+                Finding::Color {
+                    kind: ka,
+                    ref_color: ra,
+                    impl_color: ia,
+                },
+                Finding::Color {
+                    kind: kb,
+                    ref_color: rb,
+                    impl_color: ib,
+                },
+            ) => ka == kb && ra.eq_ignore_ascii_case(rb) && ia.eq_ignore_ascii_case(ib),
+            (Finding::MissingText { text: a }, Finding::MissingText { text: b })
+            | (Finding::ExtraText { text: a }, Finding::ExtraText { text: b }) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Normalized region the finding refers to, if it has one.
+    pub fn region(&self) -> Option<RegionBounds> {
+        match self {
+            Finding::Pixel { region } | Finding::Layout { region, .. } => Some(*region),
+            _ => None,
+        }
+    }
+
+    /// One-line human-readable description.
+    pub fn describe(&self) -> String {
+        let area = |r: &RegionBounds| {
+            format!(
+                "at ({:.0}%, {:.0}%) {:.0}%×{:.0}%",
+                r.x * 100.0,
+                r.y * 100.0,
+                r.width * 100.0,
+                r.height * 100.0
+            )
+        };
+        match self {
+            Finding::Pixel { region } => format!("pixel: changed region {}", area(region)),
+            Finding::Layout {
+                kind,
+                label,
+                region,
+            } => format!(
+                "layout: {} {} {}",
+                snake_case(kind),
+                label.as_deref().unwrap_or("element"),
+                area(region)
+            ),
+            Finding::Typography {
+                element_id_ref,
+                element_id_impl,
+                label,
+                issues,
+            } => format!(
+                "typography: {} ({})",
+                label
+                    .as_deref()
+                    .or(element_id_impl.as_deref())
+                    .or(element_id_ref.as_deref())
+                    .unwrap_or("text"),
+                issues.iter().map(snake_case).collect::<Vec<_>>().join(", ")
+            ),
+            Finding::Color {
+                kind,
+                ref_color,
+                impl_color,
+            } => format!("color: {} {} → {}", snake_case(kind), ref_color, impl_color),
+            Finding::MissingText { text } => format!("content: missing \"{}\"", text),
+            Finding::ExtraText { text } => format!("content: extra \"{}\"", text),
+        }
+    }
+}
+
+/// Contents of `accepted.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AcceptedFindings {
+    pub accepted: Vec<Finding>,
+}
+
+impl AcceptedFindings {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path).map_err(|err| {
+            DpcError::Config(format!(
+                "Failed to read accepted findings {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        serde_json::from_str(&data).map_err(|err| {
+            DpcError::Config(format!(
+                "Invalid accepted findings JSON in {}: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn is_accepted(&self, finding: &Finding) -> bool {
+        self.accepted
+            .iter()
+            .any(|accepted| accepted.covers(finding))
+    }
+
+    /// Accepted pixel regions, to be masked out before the metrics run.
+    pub fn pixel_regions(&self) -> Vec<RegionBounds> {
+        self.accepted
+            .iter()
+            .filter_map(|finding| match finding {
+                Finding::Pixel { region } => Some(*region),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Remove accepted findings from `scores`; returns how many were dropped.
+    ///
+    /// Scores are left as computed: only pixel acceptance changes the score,
+    /// through masking.
+    pub fn suppress(&self, scores: &mut MetricScores) -> usize {
+        if self.accepted.is_empty() {
+            return 0;
+        }
+        let mut removed = 0;
+        let mut keep = |finding: Finding| {
+            let accepted = self.is_accepted(&finding);
+            removed += accepted as usize;
+            !accepted
+        };
+        if let Some(pixel) = scores.pixel.as_mut() {
+            pixel.diff_regions.retain(|r| {
+                keep(Finding::Pixel {
+                    region: bounds(r.x, r.y, r.width, r.height),
+                })
+            });
+        }
+        if let Some(layout) = scores.layout.as_mut() {
+            layout.diff_regions.retain(|r| {
+                keep(Finding::Layout {
+                    kind: r.kind,
+                    label: r.label.clone(),
+                    region: bounds(r.x, r.y, r.width, r.height),
+                })
+            });
+        }
+        if let Some(typography) = scores.typography.as_mut() {
+            typography.diffs.retain(|d| {
+                keep(Finding::Typography {
+                    element_id_ref: d.element_id_ref.clone(),
+                    element_id_impl: d.element_id_impl.clone(),
+                    label: d.label.clone(),
+                    issues: d.issues.clone(),
+                })
+            });
+        }
+        if let Some(color) = scores.color.as_mut() {
+            color.diffs.retain(|d| {
+                keep(Finding::Color {
+                    kind: d.kind,
+                    ref_color: d.ref_color.clone(),
+                    impl_color: d.impl_color.clone(),
+                })
+            });
+        }
+        if let Some(content) = scores.content.as_mut() {
+            content
+                .missing_text
+                .retain(|text| keep(Finding::MissingText { text: text.clone() }));
+            content
+                .extra_text
+                .retain(|text| keep(Finding::ExtraText { text: text.clone() }));
+        }
+        removed
+    }
+}
+
+fn bounds(x: f32, y: f32, width: f32, height: f32) -> RegionBounds {
+    RegionBounds {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+fn iou(a: &RegionBounds, b: &RegionBounds) -> f32 {
+    let overlap_w = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let overlap_h = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    if overlap_w <= 0.0 || overlap_h <= 0.0 {
+        return 0.0;
+    }
+    let intersection = overlap_w * overlap_h;
+    let union = a.width * a.height + b.width * b.height - intersection;
+    if union <= 0.0 {
+        0.0
+    } else {
+        intersection / union
+    }
+}
+
+/// The serde name of a unit enum variant, e.g. `position_shift`.
+fn snake_case<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Severity and fingerprint of every finding, in [`Finding::collect`] order.
+pub(crate) fn finding_ratings(
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+) -> Vec<(DiffSeverity, Option<String>)> {
+    let mut ratings = Vec::new();
+    for region in scores.pixel.iter().flat_map(|m| &m.diff_regions) {
+        ratings.push((region.severity, region.fingerprint.clone()));
+    }
+    for region in scores.layout.iter().flat_map(|m| &m.diff_regions) {
+        ratings.push((calibration.layout(region.kind), region.fingerprint.clone()));
+    }
+    for diff in scores.typography.iter().flat_map(|m| &m.diffs) {
+        ratings.push((
+            calibration.typography(&diff.issues),
+            diff.fingerprint.clone(),
+        ));
+    }
+    for diff in scores.color.iter().flat_map(|m| &m.diffs) {
+        ratings.push((calibration.color(diff), diff.fingerprint.clone()));
+    }
+    if let Some(content) = &scores.content {
+        for index in 0..content.missing_text.len() {
+            let fingerprint = content.missing_text_fingerprints.get(index).cloned();
+            ratings.push((calibration.missing_text, fingerprint));
+        }
+        for index in 0..content.extra_text.len() {
+            let fingerprint = content.extra_text_fingerprints.get(index).cloned();
+            ratings.push((calibration.extra_text, fingerprint));
+        }
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        ContentMetric, DiffSeverity, PixelDiffReason, PixelDiffRegion, PixelMetric,
+    };
+
+    fn scores() -> MetricScores {
+        MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.9,
+                diff_regions: vec![
+                    PixelDiffRegion {
+                        fingerprint: None,
+                        x: 0.1,
+                        y: 0.1,
+                        width: 0.2,
+                        height: 0.2,
+                        severity: DiffSeverity::Major,
+                        reason: PixelDiffReason::PixelChange,
+                        intensity: None,
+                        max_intensity: None,
+                        pixel_bounds: None,
+                        css_bounds: None,
+                    },
+                    PixelDiffRegion {
+                        fingerprint: None,
+                        x: 0.6,
+                        y: 0.6,
+                        width: 0.1,
+                        height: 0.1,
+                        severity: DiffSeverity::Minor,
+                        reason: PixelDiffReason::PixelChange,
+                        intensity: None,
+                        max_intensity: None,
+                        pixel_bounds: None,
+                        css_bounds: None,
+                    },
+                ],
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: None,
+            typography: None,
+            color: None,
+            content: Some(ContentMetric {
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
+                score: 0.8,
+                missing_text: vec!["Sign up".to_string()],
+                extra_text: vec!["Beta".to_string()],
+                placeholder_text: Vec::new(),
+                text_labels: Default::default(),
+            }),
+            semantic: None,
+        }
+    }
+
+    #[test]
+    fn suppresses_accepted_findings_by_content() {
+        let mut scores = scores();
+        let accepted = AcceptedFindings {
+            accepted: vec![
+                // Slightly shifted from the detected region; still the same diff.
+                Finding::Pixel {
+                    region: bounds(0.12, 0.1, 0.2, 0.2),
+                },
+                Finding::ExtraText {
+                    text: "Beta".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(accepted.suppress(&mut scores), 2);
+        let pixel = scores.pixel.unwrap();
+        assert_eq!(pixel.diff_regions.len(), 1);
+        assert_eq!(pixel.diff_regions[0].x, 0.6);
+        let content = scores.content.unwrap();
+        assert_eq!(content.missing_text, vec!["Sign up".to_string()]);
+        assert!(content.extra_text.is_empty());
+    }
+
+    #[test]
+    fn typography_acceptance_covers_subsets_of_issues() {
+        let accepted = Finding::Typography {
+            element_id_ref: Some("h1".to_string()),
+            element_id_impl: Some("h1".to_string()),
+            label: None,
+            issues: vec![
+                TypographyIssue::FontSizeDiff,
+                TypographyIssue::LineHeightDiff,
+            ],
+        };
+        let fewer = Finding::Typography {
+            element_id_ref: Some("h1".to_string()),
+            element_id_impl: Some("h1".to_string()),
+            label: None,
+            issues: vec![TypographyIssue::FontSizeDiff],
+        };
+        let more = Finding::Typography {
+            element_id_ref: Some("h1".to_string()),
+            element_id_impl: Some("h1".to_string()),
+            label: None,
+            issues: vec![
+                TypographyIssue::FontSizeDiff,
+                TypographyIssue::FontWeightDiff,
+            ],
+        };
+        assert!(accepted.covers(&fewer));
+        assert!(!accepted.covers(&more));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let accepted = AcceptedFindings {
+            accepted: Finding::collect(&scores()),
+        };
+        let json = serde_json::to_string(&accepted).unwrap();
+        assert!(json.contains("\"metric\":\"missing_text\""));
+        assert_eq!(
+            serde_json::from_str::<AcceptedFindings>(&json).unwrap(),
+            accepted
+        );
+        assert_eq!(accepted.pixel_regions().len(), 2);
+    }
+}
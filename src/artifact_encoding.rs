@@ -0,0 +1,429 @@
+//! Encoding of kept artifacts.
+//!
+//! Screenshots and the diff heatmap are written as full-size PNGs while the
+//! metrics run. Once the result is final, [`encode_compare_artifacts`]
+//! re-encodes them per artifact type and optionally writes thumbnails, so tall
+//! pages do not cost tens of MB of CI storage per run. DOM snapshots of large
+//! pages run to several MB of JSON; [`write_json_snapshot`] streams them
+//! through gzip or zstd when configured.
+
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageOutputFormat};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DpcError, Result};
+use crate::output::CompareArtifacts;
+
+/// Container format for an image artifact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactImageFormat {
+    /// Lossless; optionally optimized with oxipng.
+    #[default]
+    Png,
+    /// Lossy; transparency is flattened.
+    Jpeg,
+    /// Lossy WebP at the configured quality.
+    Webp,
+}
+
+impl ArtifactImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArtifactImageFormat::Png => "png",
+            ArtifactImageFormat::Jpeg => "jpg",
+            ArtifactImageFormat::Webp => "webp",
+        }
+    }
+}
+
+/// How one kind of image artifact is written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageEncoding {
+    pub format: ArtifactImageFormat,
+    /// JPEG/WebP quality (1-100); ignored for PNG.
+    pub quality: u8,
+    /// Losslessly recompress PNG output with oxipng (slower, typically 20-40% smaller).
+    pub optimize: bool,
+    /// Downscale so neither side exceeds this many pixels.
+    pub max_dim: Option<u32>,
+}
+
+impl Default for ImageEncoding {
+    fn default() -> Self {
+        Self {
+            format: ArtifactImageFormat::Png,
+            quality: 85,
+            optimize: false,
+            max_dim: None,
+        }
+    }
+}
+
+impl ImageEncoding {
+    /// Whether the full-size PNG written during the run already matches.
+    pub fn is_passthrough(&self) -> bool {
+        self.format == ArtifactImageFormat::Png && !self.optimize && self.max_dim.is_none()
+    }
+
+    pub fn validate(&self, name: &str) -> std::result::Result<(), String> {
+        if !(1..=100).contains(&self.quality) {
+            return Err(format!(
+                "artifacts.{name}.quality must be between 1 and 100"
+            ));
+        }
+        if self.max_dim == Some(0) {
+            return Err(format!(
+                "artifacts.{name}.max_dim must be greater than zero"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encode `img`, downscaling first when it exceeds `max_dim`.
+    pub fn encode(&self, img: &DynamicImage) -> Result<Vec<u8>> {
+        let img = match self.max_dim {
+            Some(max_dim) if img.width() > max_dim || img.height() > max_dim => {
+                img.resize(max_dim, max_dim, FilterType::Lanczos3)
+            }
+            _ => img.clone(),
+        };
+
+        let mut bytes = Vec::new();
+        match self.format {
+            ArtifactImageFormat::Png => {
+                img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)?;
+                if self.optimize {
+                    // Best effort: an optimizer failure still leaves a valid PNG.
+                    if let Ok(optimized) =
+                        oxipng::optimize_from_memory(&bytes, &oxipng::Options::from_preset(2))
+                    {
+                        bytes = optimized;
+                    }
+                }
+            }
+            ArtifactImageFormat::Jpeg => {
+                DynamicImage::ImageRgb8(img.to_rgb8()).write_to(
+                    &mut Cursor::new(&mut bytes),
+                    ImageOutputFormat::Jpeg(self.quality),
+                )?;
+            }
+            ArtifactImageFormat::Webp => {
+                let rgba = img.to_rgba8();
+                let encoded = webp::Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height())
+                    .encode(f32::from(self.quality));
+                bytes.extend_from_slice(&encoded);
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Compression of JSON snapshot artifacts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotCompression {
+    /// Pretty-printed JSON.
+    #[default]
+    None,
+    /// Compact JSON, gzip-compressed (`.json.gz`).
+    Gzip,
+    /// Compact JSON, zstd-compressed (`.json.zst`); smaller and faster than gzip.
+    Zstd,
+}
+
+impl SnapshotCompression {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SnapshotCompression::None => "json",
+            SnapshotCompression::Gzip => "json.gz",
+            SnapshotCompression::Zstd => "json.zst",
+        }
+    }
+
+    /// The compression a snapshot file was written with, from its name.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => SnapshotCompression::Gzip,
+            Some("zst") => SnapshotCompression::Zstd,
+            _ => SnapshotCompression::None,
+        }
+    }
+}
+
+/// Stream `value` as JSON to `<directory>/<name>.<extension>` with
+/// `compression`, returning the written path.
+pub fn write_json_snapshot<T: Serialize>(
+    directory: &Path,
+    name: &str,
+    value: &T,
+    compression: SnapshotCompression,
+) -> Result<PathBuf> {
+    let path = directory.join(format!("{name}.{}", compression.extension()));
+    let file = BufWriter::new(File::create(&path)?);
+    match compression {
+        SnapshotCompression::None => {
+            let mut writer = file;
+            serde_json::to_writer_pretty(&mut writer, value)?;
+            writer.flush()?;
+        }
+        SnapshotCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            serde_json::to_writer(&mut encoder, value)?;
+            encoder.finish()?.flush()?;
+        }
+        SnapshotCompression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(file, 0)?;
+            serde_json::to_writer(&mut encoder, value)?;
+            encoder.finish()?.flush()?;
+        }
+    }
+    Ok(path)
+}
+
+/// Read a snapshot written by [`write_json_snapshot`], decompressing by file
+/// extension.
+pub fn read_json_snapshot<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match SnapshotCompression::from_path(path) {
+        SnapshotCompression::None => Box::new(file),
+        SnapshotCompression::Gzip => Box::new(flate2::read::GzDecoder::new(file)),
+        SnapshotCompression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+    };
+    serde_json::from_reader(reader)
+        .map_err(|e| DpcError::Config(format!("Invalid JSON snapshot {}: {e}", path.display())))
+}
+
+/// Per-artifact-type encoding, from the `[artifacts]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactEncoding {
+    /// Reference and implementation screenshots.
+    pub screenshots: ImageEncoding,
+    /// The diff heatmap and the color drift heatmap.
+    pub diff: ImageEncoding,
+    /// Thumbnails of both screenshots and the diff; written only when
+    /// `max_dim` is set.
+    pub thumbnails: ImageEncoding,
+    /// Compression of the DOM snapshots (`ref_dom.json`, `impl_dom.json`).
+    pub dom_compression: SnapshotCompression,
+}
+
+impl ArtifactEncoding {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        self.screenshots.validate("screenshots")?;
+        self.diff.validate("diff")?;
+        self.thumbnails.validate("thumbnails")
+    }
+}
+
+/// Re-encode the image artifacts in `artifacts.directory` and write thumbnails.
+///
+/// Screenshots that point outside the artifacts directory (the user's own
+/// input images) are left in place; their encoded copy is written next to the
+/// other artifacts instead.
+pub fn encode_compare_artifacts(
+    artifacts: &mut CompareArtifacts,
+    encoding: &ArtifactEncoding,
+) -> Result<()> {
+    let directory = artifacts.directory.clone();
+
+    if encoding.thumbnails.max_dim.is_some() {
+        let thumbnail = |source: &Option<PathBuf>, name: &str| -> Result<Option<PathBuf>> {
+            source
+                .as_deref()
+                .map(|source| write_encoded(source, &directory, name, &encoding.thumbnails))
+                .transpose()
+        };
+        artifacts.ref_thumbnail = thumbnail(&artifacts.ref_screenshot, "ref_thumbnail")?;
+        artifacts.impl_thumbnail = thumbnail(&artifacts.impl_screenshot, "impl_thumbnail")?;
+        artifacts.diff_thumbnail = thumbnail(&artifacts.diff_image, "diff_thumbnail")?;
+    }
+
+    let slots = [
+        (
+            &mut artifacts.ref_screenshot,
+            "ref_screenshot",
+            &encoding.screenshots,
+        ),
+        (
+            &mut artifacts.impl_screenshot,
+            "impl_screenshot",
+            &encoding.screenshots,
+        ),
+        (&mut artifacts.diff_image, "diff_heatmap", &encoding.diff),
+        (
+            &mut artifacts.color_drift_image,
+            "color_drift",
+            &encoding.diff,
+        ),
+    ];
+    for (slot, name, image_encoding) in slots {
+        if image_encoding.is_passthrough() {
+            continue;
+        }
+        let Some(source) = slot.clone() else {
+            continue;
+        };
+        let dest = write_encoded(&source, &directory, name, image_encoding)?;
+        if dest != source && source.starts_with(&directory) {
+            fs::remove_file(&source)?;
+        }
+        *slot = Some(dest);
+    }
+    Ok(())
+}
+
+fn write_encoded(
+    source: &Path,
+    directory: &Path,
+    name: &str,
+    encoding: &ImageEncoding,
+) -> Result<PathBuf> {
+    let img = image::open(source)?;
+    let bytes = encoding.encode(&img)?;
+    let dest = directory.join(format!("{name}.{}", encoding.format.extension()));
+    fs::write(&dest, bytes)?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+    use tempfile::TempDir;
+
+    fn artifacts_in(dir: &Path, ref_screenshot: PathBuf) -> CompareArtifacts {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_fn(40, 20, |x, y| {
+            Rgba([(x * 6) as u8, (y * 12) as u8, 128, 255])
+        }));
+        let impl_screenshot = dir.join("impl_screenshot.png");
+        let diff = dir.join("diff_heatmap.png");
+        for path in [&ref_screenshot, &impl_screenshot, &diff] {
+            img.save(path).unwrap();
+        }
+        CompareArtifacts {
+            directory: dir.to_path_buf(),
+            kept: true,
+            ref_screenshot: Some(ref_screenshot),
+            impl_screenshot: Some(impl_screenshot),
+            diff_image: Some(diff),
+            color_drift_image: None,
+            ref_thumbnail: None,
+            impl_thumbnail: None,
+            diff_thumbnail: None,
+            ref_dom_snapshot: None,
+            impl_dom_snapshot: None,
+            ref_figma_snapshot: None,
+            impl_figma_snapshot: None,
+            typography_report: None,
+            typography_report_csv: None,
+            typography_specimen: None,
+            run_manifest: None,
+            ref_network_har: None,
+            impl_network_har: None,
+            overlay: None,
+            artifact_index: None,
+        }
+    }
+
+    #[test]
+    fn default_encoding_leaves_artifacts_untouched() {
+        let dir = TempDir::new().unwrap();
+        let mut artifacts = artifacts_in(dir.path(), dir.path().join("ref_screenshot.png"));
+        let before = artifacts.clone();
+
+        encode_compare_artifacts(&mut artifacts, &ArtifactEncoding::default()).unwrap();
+
+        assert_eq!(artifacts.ref_screenshot, before.ref_screenshot);
+        assert_eq!(artifacts.diff_image, before.diff_image);
+        assert!(artifacts.ref_thumbnail.is_none());
+    }
+
+    #[test]
+    fn encodes_per_artifact_type_and_keeps_user_inputs() {
+        let dir = TempDir::new().unwrap();
+        let input_dir = TempDir::new().unwrap();
+        let user_input = input_dir.path().join("design.png");
+        let mut artifacts = artifacts_in(dir.path(), user_input.clone());
+        let encoding = ArtifactEncoding {
+            screenshots: ImageEncoding {
+                format: ArtifactImageFormat::Jpeg,
+                quality: 70,
+                max_dim: Some(20),
+                ..ImageEncoding::default()
+            },
+            diff: ImageEncoding {
+                optimize: true,
+                ..ImageEncoding::default()
+            },
+            thumbnails: ImageEncoding {
+                format: ArtifactImageFormat::Webp,
+                max_dim: Some(8),
+                ..ImageEncoding::default()
+            },
+            dom_compression: SnapshotCompression::None,
+        };
+
+        encode_compare_artifacts(&mut artifacts, &encoding).unwrap();
+
+        let ref_screenshot = artifacts.ref_screenshot.clone().unwrap();
+        assert_eq!(ref_screenshot, dir.path().join("ref_screenshot.jpg"));
+        assert_eq!(image::image_dimensions(&ref_screenshot).unwrap(), (20, 10));
+        assert!(user_input.is_file(), "user input must not be removed");
+        assert!(!dir.path().join("impl_screenshot.png").exists());
+        assert_eq!(
+            artifacts.diff_image,
+            Some(dir.path().join("diff_heatmap.png"))
+        );
+        let thumbnail = artifacts.diff_thumbnail.clone().unwrap();
+        assert_eq!(thumbnail, dir.path().join("diff_thumbnail.webp"));
+        assert_eq!(image::image_dimensions(&thumbnail).unwrap(), (8, 4));
+    }
+
+    #[test]
+    fn json_snapshots_round_trip_through_each_compression() {
+        let dir = TempDir::new().unwrap();
+        let value =
+            serde_json::json!({"nodes": (0..200).map(|i| format!("node-{i}")).collect::<Vec<_>>()});
+        let mut sizes = Vec::new();
+        for compression in [
+            SnapshotCompression::None,
+            SnapshotCompression::Gzip,
+            SnapshotCompression::Zstd,
+        ] {
+            let path = write_json_snapshot(dir.path(), "ref_dom", &value, compression).unwrap();
+            assert_eq!(
+                path.file_name().unwrap().to_str().unwrap(),
+                format!("ref_dom.{}", compression.extension())
+            );
+            let read: serde_json::Value = read_json_snapshot(&path).unwrap();
+            assert_eq!(read, value);
+            sizes.push(fs::metadata(&path).unwrap().len());
+        }
+        assert!(
+            sizes[1] < sizes[0] / 2 && sizes[2] < sizes[0] / 2,
+            "{sizes:?}"
+        );
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_quality() {
+        let encoding = ArtifactEncoding {
+            screenshots: ImageEncoding {
+                quality: 0,
+                ..ImageEncoding::default()
+            },
+            ..ArtifactEncoding::default()
+        };
+        assert!(encoding.validate().is_err());
+        assert!(ArtifactEncoding::default().validate().is_ok());
+    }
+}
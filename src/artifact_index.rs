@@ -0,0 +1,324 @@
+//! `index.json`: a listing of every file in a kept artifacts directory.
+//!
+//! Report viewers and upload steps read the index instead of guessing file
+//! names, which change with the configured image format, DOM compression and
+//! input kinds. Each entry gives the file's path relative to the artifacts
+//! directory, its type, the [`CompareArtifacts`] role it fills, its size, and
+//! the fingerprints and highest severity of the findings it illustrates, so a
+//! viewer can put the diff heatmap of a run with major pixel findings first.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::metrics::SeverityCalibration;
+use crate::output::{CompareArtifacts, DPC_OUTPUT_VERSION};
+use crate::types::{DiffSeverity, MetricScores};
+
+/// File name of the index inside the artifacts directory.
+pub const ARTIFACT_INDEX_FILE: &str = "index.json";
+
+/// Saved view bundles listed file by file (see [`crate::view_bundle`]).
+const VIEW_BUNDLE_DIRS: &[(&str, &str)] = &[("ref_view", "refView"), ("impl_view", "implView")];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactIndex {
+    pub version: String,
+    pub files: Vec<ArtifactEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactEntry {
+    /// `/`-separated path relative to the artifacts directory; absolute for
+    /// files outside it.
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: ArtifactType,
+    /// Key of the file in the result's `artifacts` object (`diffImage`,
+    /// `refDomSnapshot`, ...), or `refView`/`implView` for view bundle files.
+    pub role: String,
+    pub bytes: u64,
+    /// Highest severity among `findings`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<DiffSeverity>,
+    /// Fingerprints of the findings this file shows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactType {
+    Image,
+    Json,
+    Csv,
+    Html,
+    Har,
+    Other,
+}
+
+impl ArtifactType {
+    fn of(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let name = name
+            .strip_suffix(".gz")
+            .or_else(|| name.strip_suffix(".zst"))
+            .unwrap_or(&name);
+        match name.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("png" | "jpg" | "jpeg" | "webp" | "gif") => ArtifactType::Image,
+            Some("json") => ArtifactType::Json,
+            Some("csv") => ArtifactType::Csv,
+            Some("html") => ArtifactType::Html,
+            Some("har") => ArtifactType::Har,
+            _ => ArtifactType::Other,
+        }
+    }
+}
+
+/// Findings a group of artifacts illustrates.
+#[derive(Default)]
+struct Related {
+    severity: Option<DiffSeverity>,
+    findings: Vec<String>,
+}
+
+impl Related {
+    fn add(&mut self, fingerprint: Option<&String>, severity: DiffSeverity) {
+        self.severity = self.severity.max(Some(severity));
+        self.findings.extend(fingerprint.cloned());
+    }
+}
+
+/// Index the files of `artifacts`, relating them to the findings in
+/// `scores` rated with `calibration`. Missing files are left out.
+pub fn build_artifact_index(
+    artifacts: &CompareArtifacts,
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+) -> ArtifactIndex {
+    let mut pixel = Related::default();
+    for region in scores.pixel.iter().flat_map(|m| &m.diff_regions) {
+        pixel.add(region.fingerprint.as_ref(), region.severity);
+    }
+    let mut layout = Related::default();
+    for region in scores.layout.iter().flat_map(|m| &m.diff_regions) {
+        layout.add(region.fingerprint.as_ref(), calibration.layout(region.kind));
+    }
+    let mut typography = Related::default();
+    for diff in scores.typography.iter().flat_map(|m| &m.diffs) {
+        typography.add(
+            diff.fingerprint.as_ref(),
+            calibration.typography(&diff.issues),
+        );
+    }
+    let mut color = Related::default();
+    for diff in scores.color.iter().flat_map(|m| &m.diffs) {
+        color.add(diff.fingerprint.as_ref(), calibration.color(diff));
+    }
+    let none = Related::default();
+
+    let mut all = Related::default();
+    for related in [&pixel, &layout, &typography, &color] {
+        all.severity = all.severity.max(related.severity);
+        all.findings.extend(related.findings.iter().cloned());
+    }
+
+    let slots: [(&Option<PathBuf>, &str, &Related); 18] = [
+        (&artifacts.ref_screenshot, "refScreenshot", &none),
+        (&artifacts.impl_screenshot, "implScreenshot", &none),
+        (&artifacts.diff_image, "diffImage", &pixel),
+        (&artifacts.color_drift_image, "colorDriftImage", &color),
+        (&artifacts.ref_thumbnail, "refThumbnail", &none),
+        (&artifacts.impl_thumbnail, "implThumbnail", &none),
+        (&artifacts.diff_thumbnail, "diffThumbnail", &pixel),
+        (&artifacts.ref_dom_snapshot, "refDomSnapshot", &layout),
+        (&artifacts.impl_dom_snapshot, "implDomSnapshot", &layout),
+        (&artifacts.ref_figma_snapshot, "refFigmaSnapshot", &layout),
+        (&artifacts.impl_figma_snapshot, "implFigmaSnapshot", &layout),
+        (
+            &artifacts.typography_report,
+            "typographyReport",
+            &typography,
+        ),
+        (
+            &artifacts.typography_report_csv,
+            "typographyReportCsv",
+            &typography,
+        ),
+        (
+            &artifacts.typography_specimen,
+            "typographySpecimen",
+            &typography,
+        ),
+        (&artifacts.run_manifest, "runManifest", &none),
+        (&artifacts.ref_network_har, "refNetworkHar", &none),
+        (&artifacts.impl_network_har, "implNetworkHar", &none),
+        (&artifacts.overlay, "overlay", &all),
+    ];
+
+    let dir = &artifacts.directory;
+    let mut files: Vec<ArtifactEntry> = slots
+        .into_iter()
+        .filter_map(|(path, role, related)| entry(dir, path.as_ref()?, role, related))
+        .collect();
+    for (bundle, role) in VIEW_BUNDLE_DIRS {
+        let Ok(read) = fs::read_dir(dir.join(bundle)) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = read.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        files.extend(
+            paths
+                .iter()
+                .filter_map(|path| entry(dir, path, role, &none)),
+        );
+    }
+
+    ArtifactIndex {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        files,
+    }
+}
+
+fn entry(dir: &Path, path: &Path, role: &str, related: &Related) -> Option<ArtifactEntry> {
+    let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+    Some(ArtifactEntry {
+        path: relative_path(dir, path),
+        kind: ArtifactType::of(path),
+        role: role.to_string(),
+        bytes: metadata.len(),
+        severity: related.severity,
+        findings: related.findings.clone(),
+    })
+}
+
+/// `path` relative to the artifacts directory `dir`, `/`-separated; absolute
+/// for files outside it.
+pub(crate) fn relative_path(dir: &Path, path: &Path) -> String {
+    match path.strip_prefix(dir) {
+        Ok(relative) => relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Write [`ARTIFACT_INDEX_FILE`] into a kept artifacts directory and record
+/// it in `artifacts`. Does nothing when the artifacts are not kept.
+pub fn write_artifact_index(
+    artifacts: &mut CompareArtifacts,
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+) -> Result<()> {
+    if !artifacts.kept {
+        return Ok(());
+    }
+    let index = build_artifact_index(artifacts, scores, calibration);
+    let path = artifacts.directory.join(ARTIFACT_INDEX_FILE);
+    fs::write(&path, serde_json::to_vec_pretty(&index)?)?;
+    artifacts.artifact_index = Some(path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{PixelDiffReason, PixelDiffRegion, PixelMetric};
+
+    #[test]
+    fn index_lists_existing_files_with_their_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, bytes: &[u8]| {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, bytes).unwrap();
+            path
+        };
+        let mut artifacts = CompareArtifacts {
+            directory: dir.path().to_path_buf(),
+            kept: true,
+            ref_screenshot: Some(file("ref_screenshot.png", b"png")),
+            impl_screenshot: Some(dir.path().join("missing.png")),
+            diff_image: Some(file("diff_heatmap.png", b"heatmap")),
+            color_drift_image: None,
+            ref_thumbnail: None,
+            impl_thumbnail: None,
+            diff_thumbnail: None,
+            ref_dom_snapshot: Some(file("ref_dom.json.gz", b"gz")),
+            impl_dom_snapshot: None,
+            ref_figma_snapshot: None,
+            impl_figma_snapshot: None,
+            typography_report: None,
+            typography_report_csv: None,
+            typography_specimen: None,
+            run_manifest: None,
+            ref_network_har: None,
+            impl_network_har: None,
+            overlay: None,
+            artifact_index: None,
+        };
+        file("ref_view/view.json", b"{}");
+        let region = |severity, fingerprint: &str| PixelDiffRegion {
+            x: 0.0,
+            y: 0.0,
+            width: 0.1,
+            height: 0.1,
+            severity,
+            reason: PixelDiffReason::PixelChange,
+            intensity: None,
+            max_intensity: None,
+            pixel_bounds: None,
+            css_bounds: None,
+            fingerprint: Some(fingerprint.to_string()),
+        };
+        let scores = MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.8,
+                diff_regions: vec![
+                    region(DiffSeverity::Minor, "a"),
+                    region(DiffSeverity::Major, "b"),
+                ],
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: None,
+            typography: None,
+            color: None,
+            content: None,
+            semantic: None,
+        };
+
+        write_artifact_index(&mut artifacts, &scores, &SeverityCalibration::default()).unwrap();
+        let path = artifacts.artifact_index.clone().unwrap();
+        let index: ArtifactIndex = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+
+        let summary: Vec<(&str, &str, ArtifactType)> = index
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.role.as_str(), f.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("ref_screenshot.png", "refScreenshot", ArtifactType::Image),
+                ("diff_heatmap.png", "diffImage", ArtifactType::Image),
+                ("ref_dom.json.gz", "refDomSnapshot", ArtifactType::Json),
+                ("ref_view/view.json", "refView", ArtifactType::Json),
+            ]
+        );
+        let diff = &index.files[1];
+        assert_eq!(diff.bytes, 7);
+        assert_eq!(diff.severity, Some(DiffSeverity::Major));
+        assert_eq!(diff.findings, ["a", "b"]);
+        assert!(index.files[0].findings.is_empty());
+    }
+}
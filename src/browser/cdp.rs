@@ -0,0 +1,434 @@
+//! Chrome DevTools Protocol capture backend (`cdp` feature).
+//!
+//! Drives a local Chrome/Chromium directly from Rust via chromiumoxide, so URL
+//! inputs render without Node.js or the playwright npm package. Navigation,
+//! the network-idle wait, the viewport screenshot and DOM extraction mirror
+//! the Playwright script; the DOM walk is the same
+//! [`DOM_SNAPSHOT_FN`](super::playwright::DOM_SNAPSHOT_FN), evaluated through
+//! `Runtime.evaluate`, and interaction states are forced with
+//! `CSS.forcePseudoState` like the Playwright script does.
+
+use std::path::Path;
+
+use super::manager::UrlToViewOptions;
+use crate::types::DomSnapshot;
+use crate::{DpcError, Result};
+
+/// Render `url` with a CDP-driven browser, writing a viewport screenshot to
+/// `screenshot_path` when given, and return the DOM snapshot if `with_dom`.
+#[cfg(feature = "cdp")]
+pub(crate) async fn capture(
+    url: &str,
+    screenshot_path: Option<&Path>,
+    options: &UrlToViewOptions,
+    with_dom: bool,
+) -> Result<Option<DomSnapshot>> {
+    match tokio::time::timeout(
+        options.process_timeout,
+        imp::capture(url, screenshot_path, options, with_dom),
+    )
+    .await
+    {
+        Ok(result) => result,
+        // Dropping the browser kills the Chrome process.
+        Err(_) => Err(DpcError::Config(format!(
+            "CDP capture timed out after {:?}",
+            options.process_timeout
+        ))),
+    }
+}
+
+#[cfg(not(feature = "cdp"))]
+pub(crate) async fn capture(
+    _url: &str,
+    _screenshot_path: Option<&Path>,
+    _options: &UrlToViewOptions,
+    _with_dom: bool,
+) -> Result<Option<DomSnapshot>> {
+    Err(DpcError::Config(
+        "the cdp browser backend needs dpc built with the `cdp` feature (cargo install dpc --features cdp)"
+            .to_string(),
+    ))
+}
+
+#[cfg(feature = "cdp")]
+mod imp {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use chromiumoxide::cdp::browser_protocol::browser::{GrantPermissionsParams, PermissionType};
+    use chromiumoxide::cdp::browser_protocol::css::{self, ForcePseudoStateParams};
+    use chromiumoxide::cdp::browser_protocol::dom::{
+        self, GetDocumentParams, NodeId, RequestNodeParams,
+    };
+    use chromiumoxide::cdp::browser_protocol::emulation::{
+        MediaFeature, SetGeolocationOverrideParams, SetLocaleOverrideParams,
+    };
+    use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
+    use chromiumoxide::cdp::js_protocol::runtime::{
+        CallFunctionOnParams, EvaluateParams, RemoteObjectId,
+    };
+    use chromiumoxide::handler::viewport::Viewport as CdpViewport;
+    use chromiumoxide::page::ScreenshotParams;
+    use chromiumoxide::{Browser, BrowserConfig, Page};
+    use futures::StreamExt;
+
+    use super::super::dom::{convert_raw_dom, RawDomSnapshot, RawInteractionStates, StateStyle};
+    use super::super::emulation::Emulation;
+    use super::super::manager::UrlToViewOptions;
+    use super::super::playwright::{dom_snapshot_expression, STATE_STYLE_FN};
+    use crate::network::NetworkOptions;
+    use crate::types::{BrowserEngine, DomSnapshot};
+    use crate::{DpcError, Result};
+
+    /// Quiet period without finished requests that counts as network idle
+    /// (Playwright's `networkidle` uses the same 500ms).
+    const NETWORK_QUIET_MS: u128 = 500;
+
+    /// Interactive elements whose states are captured, as in the Playwright script.
+    const MAX_STATE_ELEMENTS: usize = 200;
+
+    static PROFILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    pub(super) async fn capture(
+        url: &str,
+        screenshot_path: Option<&Path>,
+        options: &UrlToViewOptions,
+        with_dom: bool,
+    ) -> Result<Option<DomSnapshot>> {
+        // The CDP backend drives Chromium only; other engines need Playwright.
+        if options.engine != BrowserEngine::Chromium {
+            return Err(DpcError::Config(format!(
+                "the cdp browser backend only renders with chromium, not {}; use the playwright backend for firefox/webkit",
+                options.engine
+            )));
+        }
+
+        // A manager session's own profile, else a fresh one per capture so
+        // concurrent captures don't fight over Chrome's profile lock.
+        let throwaway_profile = options.user_data_dir.is_none();
+        let profile_dir = options.user_data_dir.clone().unwrap_or_else(|| {
+            std::env::temp_dir().join(format!(
+                "dpc-cdp-{}-{}",
+                std::process::id(),
+                PROFILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ))
+        });
+
+        let mut builder = BrowserConfig::builder()
+            .viewport(CdpViewport {
+                width: options.viewport.width,
+                height: options.viewport.height,
+                device_scale_factor: Some(f64::from(options.viewport.device_scale_factor)),
+                emulating_mobile: false,
+                is_landscape: false,
+                has_touch: false,
+            })
+            .window_size(options.viewport.width, options.viewport.height)
+            .request_timeout(options.process_timeout)
+            .user_data_dir(&profile_dir)
+            .no_sandbox();
+        if !options.headless {
+            builder = builder.with_head();
+        }
+        // chromiumoxide ignores certificate errors unless told otherwise.
+        if !options.network.ignore_tls_errors {
+            builder = builder.respect_https_errors();
+        }
+        // Sets `navigator.language` and `Accept-Language`; the ICU locale
+        // and timezone are overridden per page.
+        if let Some(locale) = &options.emulation.locale {
+            builder = builder.arg(format!("--lang={locale}"));
+        }
+        if let Some(proxy) = options.network.effective_proxy() {
+            builder = builder.arg(format!("--proxy-server={proxy}"));
+            if let Some(bypass) = NetworkOptions::proxy_bypass() {
+                builder = builder.arg(format!("--proxy-bypass-list={}", bypass.replace(',', ";")));
+            }
+        }
+        let config = builder.build().map_err(launch_error)?;
+
+        let (mut browser, mut handler) = Browser::launch(config)
+            .await
+            .map_err(|err| launch_error(err.to_string()))?;
+        let handler_task = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let result = match grant_permissions(&browser, &options.emulation).await {
+            Err(err) => Err(err),
+            Ok(()) => match browser.new_page("about:blank").await {
+                Ok(page) => capture_page(&page, url, screenshot_path, options, with_dom).await,
+                Err(err) => Err(cdp_error(err)),
+            },
+        };
+
+        let _ = browser.close().await;
+        let _ = browser.wait().await;
+        handler_task.abort();
+        if throwaway_profile {
+            let _ = std::fs::remove_dir_all(&profile_dir);
+        }
+        result
+    }
+
+    /// Grant the emulated permissions to every origin of the default context.
+    async fn grant_permissions(browser: &Browser, emulation: &Emulation) -> Result<()> {
+        let permissions: Vec<PermissionType> = emulation
+            .granted_permissions()
+            .into_iter()
+            .filter_map(permission_type)
+            .collect();
+        if permissions.is_empty() {
+            return Ok(());
+        }
+        browser
+            .execute(GrantPermissionsParams::new(permissions))
+            .await
+            .map_err(cdp_error)?;
+        Ok(())
+    }
+
+    /// The CDP permission behind a Playwright permission name.
+    fn permission_type(name: &str) -> Option<PermissionType> {
+        Some(match name {
+            "geolocation" => PermissionType::Geolocation,
+            "notifications" => PermissionType::Notifications,
+            "clipboard-read" => PermissionType::ClipboardReadWrite,
+            "clipboard-write" => PermissionType::ClipboardSanitizedWrite,
+            "camera" => PermissionType::VideoCapture,
+            "microphone" => PermissionType::AudioCapture,
+            "midi" => PermissionType::Midi,
+            "midi-sysex" => PermissionType::MidiSysex,
+            "background-sync" => PermissionType::BackgroundSync,
+            "payment-handler" => PermissionType::PaymentHandler,
+            "storage-access" => PermissionType::StorageAccess,
+            "accessibility-events" => PermissionType::AccessibilityEvents,
+            "accelerometer" | "gyroscope" | "magnetometer" | "ambient-light-sensor" => {
+                PermissionType::Sensors
+            }
+            _ => return None,
+        })
+    }
+
+    async fn capture_page(
+        page: &Page,
+        url: &str,
+        screenshot_path: Option<&Path>,
+        options: &UrlToViewOptions,
+        with_dom: bool,
+    ) -> Result<Option<DomSnapshot>> {
+        if let Some(locale) = options.emulation.icu_locale() {
+            page.emulate_locale(SetLocaleOverrideParams::builder().locale(locale).build())
+                .await
+                .map_err(cdp_error)?;
+        }
+        if let Some(timezone) = &options.emulation.timezone {
+            page.emulate_timezone(timezone.as_str())
+                .await
+                .map_err(cdp_error)?;
+        }
+        if let Some(position) = options.emulation.geolocation {
+            let mut geolocation = SetGeolocationOverrideParams::builder()
+                .latitude(position.latitude)
+                .longitude(position.longitude);
+            if let Some(accuracy) = position.accuracy {
+                geolocation = geolocation.accuracy(accuracy);
+            }
+            page.emulate_geolocation(geolocation.build())
+                .await
+                .map_err(cdp_error)?;
+        }
+        let features: Vec<MediaFeature> = options
+            .emulation
+            .media_features()
+            .into_iter()
+            .map(|(name, value)| MediaFeature::new(name, value))
+            .collect();
+        if !features.is_empty() {
+            page.emulate_media_features(features)
+                .await
+                .map_err(cdp_error)?;
+        }
+        for script in &options.init_scripts {
+            let source = std::fs::read_to_string(script).map_err(|err| {
+                DpcError::Config(format!(
+                    "Cannot read init script {}: {err}",
+                    script.display()
+                ))
+            })?;
+            page.evaluate_on_new_document(source)
+                .await
+                .map_err(cdp_error)?;
+        }
+        match tokio::time::timeout(options.navigation_timeout, page.goto(url)).await {
+            Ok(result) => {
+                result.map_err(cdp_error)?;
+            }
+            Err(_) => {
+                return Err(DpcError::Config(format!(
+                    "CDP navigation to {url} timed out after {:?}; try increasing --nav-timeout",
+                    options.navigation_timeout
+                )))
+            }
+        }
+        page.evaluate_expression(network_idle_expression(options.network_idle_timeout))
+            .await
+            .map_err(cdp_error)?;
+
+        if let Some(path) = screenshot_path {
+            let png = page
+                .screenshot(
+                    ScreenshotParams::builder()
+                        .format(CaptureScreenshotFormat::Png)
+                        .build(),
+                )
+                .await
+                .map_err(cdp_error)?;
+            std::fs::write(path, png)?;
+        }
+
+        if !with_dom {
+            return Ok(None);
+        }
+        let mut raw: RawDomSnapshot = page
+            .evaluate_expression(dom_snapshot_expression())
+            .await
+            .map_err(cdp_error)?
+            .into_value()
+            .map_err(|err| DpcError::Config(format!("Failed to parse CDP DOM snapshot: {err}")))?;
+        if options.capture_states {
+            raw.states = capture_states(page).await?;
+        }
+        Ok(Some(convert_raw_dom(raw, BrowserEngine::Chromium)))
+    }
+
+    /// Styles of each interactive element the DOM walk registered, by
+    /// default and with `:hover` and `:focus-visible` forced.
+    async fn capture_states(page: &Page) -> Result<HashMap<String, RawInteractionStates>> {
+        page.execute(dom::EnableParams::default())
+            .await
+            .map_err(cdp_error)?;
+        page.execute(css::EnableParams::default())
+            .await
+            .map_err(cdp_error)?;
+        page.execute(GetDocumentParams::builder().depth(0).build())
+            .await
+            .map_err(cdp_error)?;
+        let ids: Vec<String> = page
+            .evaluate_expression(
+                "Array.from((window.__dpcInteractiveElements || new Map()).keys())",
+            )
+            .await
+            .map_err(cdp_error)?
+            .into_value()
+            .map_err(|err| {
+                DpcError::Config(format!("Failed to list interactive elements: {err}"))
+            })?;
+
+        let mut states = HashMap::new();
+        for id in ids.into_iter().take(MAX_STATE_ELEMENTS) {
+            let expression = format!(
+                "window.__dpcInteractiveElements.get({})",
+                serde_json::Value::String(id.clone())
+            );
+            let element = page
+                .execute(EvaluateParams::new(expression))
+                .await
+                .map_err(cdp_error)?;
+            let Some(object_id) = element.result.result.object_id.clone() else {
+                continue;
+            };
+            let node_id = page
+                .execute(RequestNodeParams::new(object_id.clone()))
+                .await
+                .map_err(cdp_error)?
+                .result
+                .node_id;
+            let element_states = RawInteractionStates {
+                default: style_with(page, node_id, &object_id, &[]).await?,
+                hover: style_with(page, node_id, &object_id, &["hover"]).await?,
+                focus_visible: style_with(page, node_id, &object_id, &["focus", "focus-visible"])
+                    .await?,
+            };
+            page.execute(ForcePseudoStateParams::new(node_id, Vec::new()))
+                .await
+                .map_err(cdp_error)?;
+            states.insert(id, element_states);
+        }
+        Ok(states)
+    }
+
+    /// [`STATE_STYLE_FN`] of an element with `pseudo_classes` forced.
+    async fn style_with(
+        page: &Page,
+        node_id: NodeId,
+        object_id: &RemoteObjectId,
+        pseudo_classes: &[&str],
+    ) -> Result<StateStyle> {
+        let forced = pseudo_classes
+            .iter()
+            .map(|class| class.to_string())
+            .collect();
+        page.execute(ForcePseudoStateParams::new(node_id, forced))
+            .await
+            .map_err(cdp_error)?;
+        let call = CallFunctionOnParams::builder()
+            .function_declaration(STATE_STYLE_FN)
+            .object_id(object_id.clone())
+            .return_by_value(true)
+            .build()
+            .map_err(DpcError::Config)?;
+        let value = page
+            .execute(call)
+            .await
+            .map_err(cdp_error)?
+            .result
+            .result
+            .value
+            .unwrap_or_default();
+        serde_json::from_value(value)
+            .map_err(|err| DpcError::Config(format!("Failed to parse element state styles: {err}")))
+    }
+
+    /// Promise that resolves once fonts are ready and no resource finished
+    /// loading for [`NETWORK_QUIET_MS`], or after `idle_timeout`.
+    fn network_idle_expression(idle_timeout: Duration) -> String {
+        format!(
+            r#"new Promise((resolve) => {{
+  const deadline = Date.now() + {idle};
+  let last = Date.now();
+  const observer = new PerformanceObserver(() => {{ last = Date.now(); }});
+  try {{ observer.observe({{ type: 'resource' }}); }} catch (e) {{}}
+  const tick = () => {{
+    const now = Date.now();
+    if (now - last >= {quiet} || now >= deadline) {{
+      observer.disconnect();
+      resolve(true);
+    }} else {{
+      setTimeout(tick, 100);
+    }}
+  }};
+  document.fonts.ready.then(tick, tick);
+}})"#,
+            idle = idle_timeout.as_millis(),
+            quiet = NETWORK_QUIET_MS
+        )
+    }
+
+    fn launch_error(err: impl std::fmt::Display) -> DpcError {
+        DpcError::Config(format!(
+            "Failed to launch Chrome for the cdp backend: {err}; install Chrome/Chromium or set CHROME to its executable path"
+        ))
+    }
+
+    fn cdp_error(err: chromiumoxide::error::CdpError) -> DpcError {
+        DpcError::Config(format!("CDP capture failed: {err}"))
+    }
+}
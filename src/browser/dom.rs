@@ -1,6 +1,9 @@
 //! DOM snapshot types and conversion from raw Playwright output.
 
-use crate::types::{BoundingBox, ComputedStyle, DomNode, DomSnapshot};
+use crate::types::{
+    BoundingBox, BrowserEngine, ComputedStyle, CustomProperties, DomNode, DomSnapshot, FlexLayout,
+    InteractionStates, PageDiagnostics,
+};
 use std::collections::HashMap;
 
 /// Raw script result with DOM snapshot from Playwright.
@@ -8,6 +11,9 @@ use std::collections::HashMap;
 pub(crate) struct ScriptResultWithDom {
     pub status: String,
     pub dom: Option<RawDomSnapshot>,
+    /// Present when a HAR was requested.
+    #[serde(default)]
+    pub diagnostics: Option<PageDiagnostics>,
 }
 
 /// Raw DOM snapshot as returned by the Playwright script.
@@ -18,6 +24,46 @@ pub(crate) struct RawDomSnapshot {
     pub title: Option<String>,
     #[serde(default)]
     pub nodes: Vec<RawDomNode>,
+    /// Interactive elements' styles by node id, when states were captured.
+    #[serde(default)]
+    pub states: HashMap<String, RawInteractionStates>,
+    #[serde(default)]
+    pub custom_properties: Option<CustomProperties>,
+}
+
+/// `(property, value)` pairs of an element and its first descendants, in
+/// the same order for every state.
+pub(crate) type StateStyle = Vec<(String, String)>;
+
+/// An interactive element's styles by default and with each state forced.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawInteractionStates {
+    pub default: StateStyle,
+    pub hover: StateStyle,
+    pub focus_visible: StateStyle,
+}
+
+impl RawInteractionStates {
+    fn changes(self) -> InteractionStates {
+        InteractionStates {
+            hover: changed_properties(&self.default, &self.hover),
+            focus_visible: changed_properties(&self.default, &self.focus_visible),
+        }
+    }
+}
+
+/// Properties whose value differs between two captures, sorted and deduplicated.
+fn changed_properties(default: &StateStyle, forced: &StateStyle) -> Vec<String> {
+    let mut changed: Vec<String> = default
+        .iter()
+        .zip(forced)
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((property, _), _)| property.clone())
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
 }
 
 /// Raw DOM node from Playwright output.
@@ -33,6 +79,8 @@ pub(crate) struct RawDomNode {
     pub attributes: HashMap<String, String>,
     pub text: Option<String>,
     pub bounding_box: RawBoundingBox,
+    #[serde(default)]
+    pub text_baseline: Option<f32>,
     pub computed_style: Option<RawComputedStyle>,
 }
 
@@ -50,23 +98,32 @@ pub(crate) struct RawBoundingBox {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct RawComputedStyle {
     pub font_family: Option<String>,
+    #[serde(default)]
+    pub rendered_font_family: Option<String>,
     pub font_size: Option<f32>,
     pub font_weight: Option<String>,
     pub line_height: Option<f32>,
     pub letter_spacing: Option<f32>,
     pub color: Option<String>,
     pub background_color: Option<String>,
+    #[serde(default)]
+    pub background_image: Option<String>,
     pub display: Option<String>,
     pub visibility: Option<String>,
     pub opacity: Option<f32>,
+    #[serde(default)]
+    pub flex: Option<FlexLayout>,
 }
 
-/// Converts raw DOM data from Playwright into the application's DomSnapshot type.
-pub(crate) fn convert_raw_dom(dom_data: RawDomSnapshot) -> DomSnapshot {
+/// Converts raw DOM data from the capture script into the application's DomSnapshot type,
+/// recording the engine that rendered it.
+pub(crate) fn convert_raw_dom(dom_data: RawDomSnapshot, engine: BrowserEngine) -> DomSnapshot {
+    let mut states = dom_data.states;
     let nodes: Vec<DomNode> = dom_data
         .nodes
         .into_iter()
         .map(|raw| DomNode {
+            interaction_states: states.remove(&raw.id).map(RawInteractionStates::changes),
             id: raw.id,
             tag: raw.tag,
             children: raw.children,
@@ -79,25 +136,35 @@ pub(crate) fn convert_raw_dom(dom_data: RawDomSnapshot) -> DomSnapshot {
                 width: raw.bounding_box.width,
                 height: raw.bounding_box.height,
             },
+            text_baseline: raw.text_baseline,
             computed_style: raw.computed_style.map(|s| ComputedStyle {
                 font_family: s.font_family,
+                rendered_font_family: s.rendered_font_family,
                 font_size: s.font_size,
                 font_weight: s.font_weight,
                 line_height: s.line_height,
                 letter_spacing: s.letter_spacing,
                 color: s.color,
                 background_color: s.background_color,
+                background_image: s.background_image,
                 display: s.display,
                 visibility: s.visibility,
                 opacity: s.opacity,
+                flex: s.flex,
             }),
         })
         .collect();
 
     DomSnapshot {
+        schema_version: DomSnapshot::SCHEMA_VERSION,
         url: dom_data.url,
         title: dom_data.title,
+        browser: Some(engine),
+        diagnostics: None,
         nodes,
+        custom_properties: dom_data
+            .custom_properties
+            .filter(|properties| !properties.is_empty()),
     }
 }
 
@@ -174,6 +241,34 @@ mod tests {
         assert!(dom.nodes.is_empty());
     }
 
+    #[test]
+    fn script_result_with_diagnostics_summarizes_failed_images() {
+        let json = r#"{
+            "status": "ok",
+            "dom": {"url": "https://test.com", "title": "Test", "nodes": []},
+            "diagnostics": {
+                "consoleErrors": ["Uncaught TypeError: x is undefined"],
+                "failedRequests": [
+                    {"url": "https://test.com/app.css", "resourceType": "stylesheet", "status": 500},
+                    {"url": "https://cdn.test.com/hero.png", "resourceType": "image", "status": 404},
+                    {"url": "https://fonts.test.com/a.woff2", "resourceType": "font", "error": "net::ERR_FAILED"}
+                ]
+            }
+        }"#;
+
+        let result: ScriptResultWithDom = serde_json::from_str(json).unwrap();
+        let diagnostics = result.diagnostics.unwrap();
+        assert_eq!(diagnostics.failed_requests.len(), 3);
+        assert_eq!(
+            diagnostics.summary().as_deref(),
+            Some("3 failed requests (1 image, e.g. https://cdn.test.com/hero.png 404); 1 console error")
+        );
+        assert_eq!(
+            diagnostics.failed_requests[2].to_string(),
+            "https://fonts.test.com/a.woff2 net::ERR_FAILED"
+        );
+    }
+
     #[test]
     fn convert_raw_dom_copies_style_fields() {
         let raw = RawDomSnapshot {
@@ -192,29 +287,55 @@ mod tests {
                     width: 3.0,
                     height: 4.0,
                 },
+                text_baseline: Some(3.0),
                 computed_style: Some(RawComputedStyle {
                     font_family: Some("Arial".into()),
+                    rendered_font_family: None,
                     font_size: Some(12.0),
                     font_weight: Some("700".into()),
                     line_height: Some(16.0),
                     letter_spacing: Some(0.5),
                     color: Some("rgb(0,0,0)".into()),
                     background_color: Some("rgb(255,255,255)".into()),
+                    background_image: None,
                     display: Some("block".into()),
                     visibility: Some("visible".into()),
                     opacity: Some(0.8),
+                    flex: None,
                 }),
             }],
+            states: HashMap::from([(
+                "n1".to_string(),
+                RawInteractionStates {
+                    default: style_entries(&[("color", "red"), ("outline-style", "none")]),
+                    hover: style_entries(&[("color", "blue"), ("outline-style", "none")]),
+                    focus_visible: style_entries(&[("color", "red"), ("outline-style", "none")]),
+                },
+            )]),
+            custom_properties: Some(CustomProperties::default()),
         };
 
-        let snapshot = convert_raw_dom(raw);
+        let snapshot = convert_raw_dom(raw, BrowserEngine::Firefox);
+        assert_eq!(snapshot.custom_properties, None);
+        assert_eq!(snapshot.browser, Some(BrowserEngine::Firefox));
         let node = snapshot.nodes.first().unwrap();
 
         assert_eq!(node.text.as_deref(), Some("hello"));
+        assert_eq!(node.text_baseline, Some(3.0));
+        let states = node.interaction_states.as_ref().unwrap();
+        assert_eq!(states.hover, vec!["color".to_string()]);
+        assert!(states.focus_visible.is_empty());
         let style = node.computed_style.as_ref().unwrap();
         assert_eq!(style.font_family.as_deref(), Some("Arial"));
         assert_eq!(style.display.as_deref(), Some("block"));
         assert_eq!(style.visibility.as_deref(), Some("visible"));
         assert_eq!(style.opacity, Some(0.8));
     }
+
+    fn style_entries(entries: &[(&str, &str)]) -> StateStyle {
+        entries
+            .iter()
+            .map(|(property, value)| (property.to_string(), value.to_string()))
+            .collect()
+    }
 }
@@ -0,0 +1,332 @@
+//! Locale, timezone, geolocation and permission emulation for URL captures.
+//!
+//! Localized implementations format dates, numbers and currencies with the
+//! browser's locale and timezone, which otherwise come from the machine that
+//! runs the capture. Pinning both (`--locale de-DE --timezone Europe/Berlin`)
+//! makes a German build render the same on a laptop and on a UTC CI runner.
+//! Pages that branch on permission state (a store locator asking for the
+//! position, a "copy" button probing the clipboard) likewise render the same
+//! every run with `--geolocation` and `--grant-permissions`.
+//! `--reduced-motion` stops well-behaved animations mid-flight, and
+//! `--forced-colors` renders the page as Windows high-contrast users see it,
+//! for comparison against a dedicated forced-colors design.
+
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Permission names accepted by `--grant-permissions` (Playwright's names).
+pub const PERMISSIONS: &[&str] = &[
+    "geolocation",
+    "notifications",
+    "clipboard-read",
+    "clipboard-write",
+    "camera",
+    "microphone",
+    "midi",
+    "midi-sysex",
+    "background-sync",
+    "payment-handler",
+    "storage-access",
+    "accelerometer",
+    "gyroscope",
+    "magnetometer",
+    "ambient-light-sensor",
+    "accessibility-events",
+];
+
+/// How a page is rendered beyond its viewport; unset fields keep the host's
+/// (or the browser's) defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Emulation {
+    /// BCP 47 language tag such as `de-DE`: `navigator.language`, the
+    /// `Accept-Language` header and `Intl` formatting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// IANA timezone such as `Europe/Berlin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Position reported by `navigator.geolocation`; implies granting the
+    /// `geolocation` permission.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geolocation: Option<Geolocation>,
+    /// Permissions granted to every page (see [`PERMISSIONS`]). Others are
+    /// left to the browser, which denies their prompts in headless runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    /// Match `prefers-reduced-motion: reduce`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reduced_motion: bool,
+    /// Match `forced-colors: active`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forced_colors: bool,
+}
+
+/// A fixed position, `--geolocation LAT,LON[,ACCURACY]`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy radius in meters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+}
+
+impl Geolocation {
+    fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!(
+                "--geolocation latitude {} must be between -90 and 90",
+                self.latitude
+            ));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!(
+                "--geolocation longitude {} must be between -180 and 180",
+                self.longitude
+            ));
+        }
+        if self
+            .accuracy
+            .is_some_and(|accuracy| !(0.0..).contains(&accuracy))
+        {
+            return Err("--geolocation accuracy must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Geolocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let number = |part: &str| {
+            part.parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| format!("'{part}' is not a number"))
+        };
+        let geolocation = match parts.as_slice() {
+            [latitude, longitude] => Geolocation {
+                latitude: number(latitude)?,
+                longitude: number(longitude)?,
+                accuracy: None,
+            },
+            [latitude, longitude, accuracy] => Geolocation {
+                latitude: number(latitude)?,
+                longitude: number(longitude)?,
+                accuracy: Some(number(accuracy)?),
+            },
+            _ => return Err("expected LAT,LON or LAT,LON,ACCURACY".to_string()),
+        };
+        geolocation.validate()?;
+        Ok(geolocation)
+    }
+}
+
+impl Emulation {
+    pub fn is_empty(&self) -> bool {
+        self.locale.is_none()
+            && self.timezone.is_none()
+            && self.geolocation.is_none()
+            && self.permissions.is_empty()
+            && !self.reduced_motion
+            && !self.forced_colors
+    }
+
+    /// Add comma-separated permission names (the `--grant-permissions` value).
+    pub fn grant_permissions(mut self, names: &str) -> Self {
+        self.permissions.extend(
+            names
+                .split(',')
+                .map(|name| name.trim().to_ascii_lowercase())
+                .filter(|name| !name.is_empty()),
+        );
+        self
+    }
+
+    /// Permissions to grant: [`Self::permissions`], plus `geolocation` when
+    /// a position is set.
+    pub fn granted_permissions(&self) -> Vec<&str> {
+        let mut granted: Vec<&str> = self.permissions.iter().map(String::as_str).collect();
+        if self.geolocation.is_some() && !granted.contains(&"geolocation") {
+            granted.push("geolocation");
+        }
+        granted
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(locale) = &self.locale {
+            let mut parts = locale.split(['-', '_']);
+            let language = parts.next().unwrap_or_default();
+            let valid = (2..=3).contains(&language.len())
+                && language.chars().all(|c| c.is_ascii_alphabetic())
+                && parts.all(|part| {
+                    (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+                });
+            if !valid {
+                return Err(format!(
+                    "--locale '{locale}' is not a BCP 47 language tag (e.g. de-DE)"
+                ));
+            }
+        }
+        if let Some(timezone) = &self.timezone {
+            let valid = !timezone.is_empty()
+                && !timezone.starts_with('/')
+                && !timezone.ends_with('/')
+                && timezone
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+            if !valid {
+                return Err(format!(
+                    "--timezone '{timezone}' is not an IANA timezone (e.g. Europe/Berlin)"
+                ));
+            }
+        }
+        if let Some(geolocation) = &self.geolocation {
+            geolocation.validate()?;
+        }
+        if let Some(unknown) = self
+            .permissions
+            .iter()
+            .find(|name| !PERMISSIONS.contains(&name.as_str()))
+        {
+            return Err(format!(
+                "--grant-permissions: unknown permission '{unknown}'; expected one of {}",
+                PERMISSIONS.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Context options for the Playwright script.
+    pub(crate) fn playwright_json(&self) -> String {
+        json!({
+            "locale": self.locale,
+            "timezoneId": self.timezone,
+            "geolocation": self.geolocation,
+            "permissions": self.granted_permissions(),
+            "reducedMotion": self.reduced_motion.then_some("reduce"),
+            "forcedColors": self.forced_colors.then_some("active"),
+        })
+        .to_string()
+    }
+
+    /// Media features to emulate over CDP, as `(name, value)` pairs.
+    #[cfg_attr(not(feature = "cdp"), allow(dead_code))]
+    pub(crate) fn media_features(&self) -> Vec<(&'static str, &'static str)> {
+        let mut features = Vec::new();
+        if self.reduced_motion {
+            features.push(("prefers-reduced-motion", "reduce"));
+        }
+        if self.forced_colors {
+            features.push(("forced-colors", "active"));
+        }
+        features
+    }
+
+    /// The locale as the ICU identifier CDP's `Emulation.setLocaleOverride`
+    /// expects (`de_DE`).
+    #[cfg_attr(not(feature = "cdp"), allow(dead_code))]
+    pub(crate) fn icu_locale(&self) -> Option<String> {
+        self.locale.as_ref().map(|locale| locale.replace('-', "_"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_accepts_tags_and_zones_and_rejects_junk() {
+        let emulation = Emulation {
+            locale: Some("de-DE".into()),
+            timezone: Some("America/Argentina/Buenos_Aires".into()),
+            ..Emulation::default()
+        };
+        assert!(emulation.validate().is_ok());
+        assert_eq!(emulation.icu_locale().as_deref(), Some("de_DE"));
+        let settings: serde_json::Value =
+            serde_json::from_str(&emulation.playwright_json()).unwrap();
+        assert_eq!(settings["timezoneId"], "America/Argentina/Buenos_Aires");
+
+        for locale in ["zh-Hant-TW", "en", "pt_BR"] {
+            let emulation = Emulation {
+                locale: Some(locale.into()),
+                timezone: Some("UTC".into()),
+                ..Emulation::default()
+            };
+            assert!(emulation.validate().is_ok(), "{locale}");
+        }
+        let bad_locale = Emulation {
+            locale: Some("german".into()),
+            timezone: None,
+            ..Emulation::default()
+        };
+        assert!(bad_locale.validate().unwrap_err().contains("--locale"));
+        let bad_zone = Emulation {
+            locale: None,
+            timezone: Some("Europe/Berlin; rm".into()),
+            ..Emulation::default()
+        };
+        assert!(bad_zone.validate().unwrap_err().contains("--timezone"));
+        assert!(Emulation::default().is_empty());
+    }
+
+    #[test]
+    fn geolocation_parses_and_implies_its_permission() {
+        let geolocation: Geolocation = "52.52, 13.405,25".parse().unwrap();
+        assert_eq!(geolocation.accuracy, Some(25.0));
+        assert!("52.52".parse::<Geolocation>().is_err());
+        assert!("91,0"
+            .parse::<Geolocation>()
+            .unwrap_err()
+            .contains("latitude"));
+        assert!("0,0,-1".parse::<Geolocation>().is_err());
+
+        let emulation = Emulation {
+            geolocation: Some(geolocation),
+            ..Emulation::default()
+        }
+        .grant_permissions("Notifications, clipboard-read");
+        assert!(emulation.validate().is_ok());
+        assert_eq!(
+            emulation.granted_permissions(),
+            ["notifications", "clipboard-read", "geolocation"]
+        );
+        let settings: serde_json::Value =
+            serde_json::from_str(&emulation.playwright_json()).unwrap();
+        assert_eq!(settings["geolocation"]["latitude"], 52.52);
+        assert_eq!(settings["permissions"][2], "geolocation");
+
+        let unknown = Emulation::default().grant_permissions("telepathy");
+        assert!(unknown.validate().unwrap_err().contains("'telepathy'"));
+    }
+
+    #[test]
+    fn media_toggles_reach_both_backends() {
+        let emulation = Emulation {
+            reduced_motion: true,
+            forced_colors: true,
+            ..Emulation::default()
+        };
+        assert!(!emulation.is_empty());
+        let settings: serde_json::Value =
+            serde_json::from_str(&emulation.playwright_json()).unwrap();
+        assert_eq!(settings["reducedMotion"], "reduce");
+        assert_eq!(settings["forcedColors"], "active");
+        assert_eq!(
+            emulation.media_features(),
+            [
+                ("prefers-reduced-motion", "reduce"),
+                ("forced-colors", "active")
+            ]
+        );
+        let json = serde_json::to_value(Emulation::default()).unwrap();
+        assert_eq!(json, serde_json::json!({}));
+    }
+}
@@ -0,0 +1,211 @@
+//! Managed Playwright installation (`dpc install-browser`).
+//!
+//! Installs the `playwright` npm package and its Chromium build into a
+//! directory dpc owns and remembers that location, so the Node helpers find it
+//! through `NODE_PATH` / `PLAYWRIGHT_BROWSERS_PATH` without a project-local
+//! `npm install`.
+
+use crate::types::BrowserEngine;
+use crate::{DpcError, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Overrides the remembered install location (useful in CI images).
+pub const PLAYWRIGHT_DIR_ENV: &str = "DPC_PLAYWRIGHT_DIR";
+
+/// A Playwright install made by `dpc install-browser`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedPlaywright {
+    pub dir: PathBuf,
+}
+
+impl ManagedPlaywright {
+    /// The install at `dir`, if the playwright package is present there.
+    pub fn at(dir: impl Into<PathBuf>) -> Option<Self> {
+        let install = Self { dir: dir.into() };
+        install
+            .node_modules()
+            .join("playwright")
+            .join("package.json")
+            .is_file()
+            .then_some(install)
+    }
+
+    pub fn node_modules(&self) -> PathBuf {
+        self.dir.join("node_modules")
+    }
+
+    pub fn browsers_dir(&self) -> PathBuf {
+        self.dir.join("browsers")
+    }
+
+    /// Point a Node process at this install. A `PLAYWRIGHT_BROWSERS_PATH`
+    /// set by the user wins.
+    pub(crate) fn apply_env(&self, cmd: &mut Command) {
+        cmd.env(
+            "NODE_PATH",
+            prepend_node_path(&self.node_modules(), std::env::var_os("NODE_PATH")),
+        );
+        if std::env::var_os("PLAYWRIGHT_BROWSERS_PATH").is_none() {
+            cmd.env("PLAYWRIGHT_BROWSERS_PATH", self.browsers_dir());
+        }
+    }
+}
+
+/// Where `dpc install-browser` installs by default (`<data dir>/dpc/playwright`).
+pub fn default_install_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("dpc").join("playwright"))
+}
+
+/// File recording the last install location (`<config dir>/dpc/playwright-dir`).
+fn location_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("dpc").join("playwright-dir"))
+}
+
+/// The managed install to use: `DPC_PLAYWRIGHT_DIR`, else the remembered
+/// location, else the default directory.
+pub fn managed_install() -> Option<ManagedPlaywright> {
+    if let Some(dir) = std::env::var_os(PLAYWRIGHT_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return ManagedPlaywright::at(PathBuf::from(dir));
+    }
+    location_file()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|dir| PathBuf::from(dir.trim()))
+        .and_then(ManagedPlaywright::at)
+        .or_else(|| default_install_dir().and_then(ManagedPlaywright::at))
+}
+
+/// Apply the managed install, if any, to a Node command.
+pub(crate) fn apply_managed_env(cmd: &mut Command) {
+    if let Some(install) = managed_install() {
+        install.apply_env(cmd);
+    }
+}
+
+/// Install playwright and the given browser engines into `dir` and remember
+/// the location.
+///
+/// Runs `npm install playwright` with `dir` as prefix, then Playwright's own
+/// `install <engines>`; their output is passed through so download progress
+/// stays visible.
+pub async fn install_playwright(
+    dir: &Path,
+    node_command: &str,
+    engines: &[BrowserEngine],
+) -> Result<ManagedPlaywright> {
+    fs::create_dir_all(dir)?;
+
+    let npm = if cfg!(windows) { "npm.cmd" } else { "npm" };
+    let status = Command::new(npm)
+        .args(["install", "--no-audit", "--no-fund", "--prefix"])
+        .arg(dir)
+        .arg("playwright")
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await
+        .map_err(|err| install_spawn_error(err, npm))?;
+    if !status.success() {
+        return Err(DpcError::Config(format!(
+            "`npm install playwright` failed ({status}); check network access to the npm registry"
+        )));
+    }
+
+    let install = ManagedPlaywright::at(dir).ok_or_else(|| {
+        DpcError::Config(format!(
+            "npm finished but {} has no playwright package",
+            dir.display()
+        ))
+    })?;
+
+    let engines = if engines.is_empty() {
+        &[BrowserEngine::Chromium][..]
+    } else {
+        engines
+    };
+    let mut cmd = Command::new(node_command);
+    cmd.arg(install.node_modules().join("playwright").join("cli.js"))
+        .arg("install")
+        .args(engines.iter().map(|engine| engine.as_str()))
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    install.apply_env(&mut cmd);
+    let status = cmd
+        .status()
+        .await
+        .map_err(|err| install_spawn_error(err, node_command))?;
+    if !status.success() {
+        return Err(DpcError::Config(format!(
+            "`playwright install` failed ({status}); on Linux, system libraries may be missing \
+             (`npx playwright install-deps`)"
+        )));
+    }
+
+    remember_install(&install)?;
+    Ok(install)
+}
+
+fn remember_install(install: &ManagedPlaywright) -> Result<()> {
+    let Some(file) = location_file() else {
+        return Ok(());
+    };
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let dir = fs::canonicalize(&install.dir).unwrap_or_else(|_| install.dir.clone());
+    fs::write(file, dir.to_string_lossy().as_bytes())?;
+    Ok(())
+}
+
+fn install_spawn_error(err: std::io::Error, command: &str) -> DpcError {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        DpcError::Config(format!(
+            "'{command}' was not found on PATH; install Node.js (which ships npm) first"
+        ))
+    } else {
+        DpcError::Io(err)
+    }
+}
+
+/// `NODE_PATH` with `node_modules` in front of any existing entries.
+fn prepend_node_path(node_modules: &Path, existing: Option<OsString>) -> OsString {
+    let mut paths = vec![node_modules.to_path_buf()];
+    if let Some(existing) = existing {
+        paths.extend(std::env::split_paths(&existing));
+    }
+    std::env::join_paths(paths).unwrap_or_else(|_| node_modules.as_os_str().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn at_requires_the_playwright_package() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(ManagedPlaywright::at(dir.path()), None);
+
+        let package = dir.path().join("node_modules/playwright");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("package.json"), "{}").unwrap();
+        let install = ManagedPlaywright::at(dir.path()).expect("installed");
+        assert_eq!(install.browsers_dir(), dir.path().join("browsers"));
+    }
+
+    #[test]
+    fn prepend_node_path_keeps_existing_entries() {
+        let managed = PathBuf::from("/opt/dpc/node_modules");
+        let existing = std::env::join_paths(["/usr/lib/node_modules"]).unwrap();
+        let joined = prepend_node_path(&managed, Some(existing));
+        let paths: Vec<_> = std::env::split_paths(&joined).collect();
+        assert_eq!(
+            paths,
+            vec![managed.clone(), PathBuf::from("/usr/lib/node_modules")]
+        );
+        assert_eq!(prepend_node_path(&managed, None), managed.into_os_string());
+    }
+}
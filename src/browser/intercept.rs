@@ -0,0 +1,159 @@
+//! Request interception rules for URL captures.
+//!
+//! Blocking analytics, ads and other third-party requests, or answering them
+//! with fixed responses (e.g. a deterministic avatar image), removes a common
+//! source of run-to-run differences. Rules use Playwright URL globs such as
+//! `**/analytics.js` or `https://*.doubleclick.net/**` and are applied through
+//! `BrowserContext.route`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Requests to block or mock while capturing, from `--block-urls` and the
+/// `[requests]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestRules {
+    /// URL globs whose requests are aborted.
+    pub block: Vec<String>,
+    /// URL globs answered with a fixed response; these win over `block`.
+    pub mock: Vec<MockRoute>,
+}
+
+/// A fixed response for requests matching `url`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockRoute {
+    /// URL glob to match.
+    pub url: String,
+    /// File whose contents become the response body.
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Inline response body, when no `file` is given.
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default = "default_mock_status")]
+    pub status: u16,
+    /// Defaults to a type guessed from the file extension.
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+fn default_mock_status() -> u16 {
+    200
+}
+
+impl RequestRules {
+    pub fn is_empty(&self) -> bool {
+        self.block.is_empty() && self.mock.is_empty()
+    }
+
+    /// Add comma-separated block patterns (the `--block-urls` value).
+    pub fn block_patterns(mut self, patterns: &str) -> Self {
+        self.block.extend(
+            patterns
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string),
+        );
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.block.iter().any(|pattern| pattern.trim().is_empty()) {
+            return Err("requests.block patterns must not be empty".to_string());
+        }
+        for mock in &self.mock {
+            if mock.url.trim().is_empty() {
+                return Err("requests.mock entries need a url pattern".to_string());
+            }
+            if !(100..=599).contains(&mock.status) {
+                return Err(format!(
+                    "requests.mock status for '{}' must be an HTTP status code",
+                    mock.url
+                ));
+            }
+            match (&mock.file, &mock.body) {
+                (Some(_), Some(_)) => {
+                    return Err(format!(
+                        "requests.mock for '{}' sets both file and body",
+                        mock.url
+                    ))
+                }
+                (Some(file), None) if !file.is_file() => {
+                    return Err(format!(
+                        "requests.mock file {} for '{}' does not exist",
+                        file.display(),
+                        mock.url
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Rules for the Playwright script, with mock files as absolute paths
+    /// since Node resolves them against its own working directory.
+    pub(crate) fn playwright_json(&self) -> String {
+        let mock: Vec<_> = self
+            .mock
+            .iter()
+            .map(|mock| {
+                let file = mock
+                    .file
+                    .as_ref()
+                    .map(|file| std::path::absolute(file).unwrap_or_else(|_| file.clone()));
+                json!({
+                    "url": mock.url,
+                    "file": file,
+                    "body": mock.body,
+                    "status": mock.status,
+                    "contentType": mock.content_type,
+                })
+            })
+            .collect();
+        json!({ "block": self.block, "mock": mock }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_patterns_split_and_trim() {
+        let rules =
+            RequestRules::default().block_patterns(" **/gtag/**, ,https://*.doubleclick.net/** ");
+        assert_eq!(
+            rules.block,
+            vec!["**/gtag/**", "https://*.doubleclick.net/**"]
+        );
+        assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_ambiguous_or_missing_mock_bodies() {
+        let mut rules = RequestRules {
+            block: vec![],
+            mock: vec![MockRoute {
+                url: "**/avatar/*.png".into(),
+                file: Some("does-not-exist.png".into()),
+                body: None,
+                status: 200,
+                content_type: None,
+            }],
+        };
+        assert!(rules.validate().unwrap_err().contains("does not exist"));
+
+        rules.mock[0].body = Some("x".into());
+        assert!(rules.validate().unwrap_err().contains("both file and body"));
+
+        rules.mock[0].file = None;
+        let settings: serde_json::Value = serde_json::from_str(&rules.playwright_json()).unwrap();
+        assert_eq!(settings["mock"][0]["body"], "x");
+        assert_eq!(settings["mock"][0]["status"], 200);
+    }
+}
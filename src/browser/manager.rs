@@ -1,9 +1,10 @@
 //! Browser manager for coordinating headless browser sessions.
 //!
-//! This module provides the `BrowserManager` struct for managing
-//! concurrent browser sessions with semaphore-based limiting.
+//! This module provides the `BrowserManager` struct for managing a pool of
+//! reusable browser sessions, each with its own profile directory.
 
-use crate::types::{NormalizedView, ResourceKind};
+use crate::network::NetworkOptions;
+use crate::types::{BrowserEngine, DomSnapshot, NormalizedView, ResourceKind};
 use crate::{DpcError, Result, Viewport};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,15 +13,19 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::io::AsyncReadExt;
 use tokio::process::Command;
-use tokio::sync::Semaphore;
 use tokio::time::timeout;
 
+use super::cdp;
 use super::dom::{convert_raw_dom, ScriptResultWithDom};
+use super::emulation::Emulation;
+use super::install::apply_managed_env;
+use super::intercept::RequestRules;
 use super::playwright::{
-    ensure_node_available, ensure_playwright_available, map_playwright_error,
-    map_playwright_status_error, map_spawn_error, ScriptError, ScriptResult, PLAYWRIGHT_SCRIPT,
-    PLAYWRIGHT_SCRIPT_WITH_DOM,
+    dom_snapshot_expression, ensure_node_available, ensure_playwright_available,
+    map_playwright_error, map_playwright_status_error, map_spawn_error, ScriptError, ScriptResult,
+    PLAYWRIGHT_SCRIPT, PLAYWRIGHT_SCRIPT_WITH_DOM, STATE_STYLE_FN,
 };
+use super::session::{SessionPool, SessionStats};
 
 pub type ProgressCallback = Arc<dyn Fn(&str) + Send + Sync>;
 
@@ -33,6 +38,44 @@ pub const DEFAULT_NETWORK_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default timeout for the entire Playwright process.
 pub const DEFAULT_PROCESS_TIMEOUT: Duration = Duration::from_secs(45);
 
+/// Environment variable selecting the capture backend (`playwright` or `cdp`).
+pub const BROWSER_BACKEND_ENV: &str = "DPC_BROWSER_BACKEND";
+
+/// How URL inputs are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptureBackend {
+    /// A Node.js helper script driving the playwright npm package.
+    #[default]
+    Playwright,
+    /// Chrome DevTools Protocol straight from Rust (`cdp` feature); needs only
+    /// a local Chrome/Chromium, no Node.js.
+    Cdp,
+}
+
+impl CaptureBackend {
+    /// The backend named by `DPC_BROWSER_BACKEND`, defaulting to Playwright.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(BROWSER_BACKEND_ENV) {
+            Ok(value) if !value.trim().is_empty() => value.parse(),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+impl std::str::FromStr for CaptureBackend {
+    type Err = DpcError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "playwright" => Ok(CaptureBackend::Playwright),
+            "cdp" => Ok(CaptureBackend::Cdp),
+            other => Err(DpcError::Config(format!(
+                "Unknown browser backend '{other}' (expected playwright or cdp)"
+            ))),
+        }
+    }
+}
+
 /// Configuration options for browser sessions.
 #[derive(Debug, Clone)]
 pub struct BrowserOptions {
@@ -48,8 +91,15 @@ pub struct BrowserOptions {
     pub network_idle_timeout: Duration,
     /// Timeout for the entire Playwright process.
     pub process_timeout: Duration,
-    /// Maximum number of concurrent browser sessions.
+    /// Number of browser sessions a [`BrowserManager`] runs renders on; more
+    /// renders than this queue until a session frees up.
     pub max_concurrent_sessions: usize,
+    /// Which backend renders pages.
+    pub backend: CaptureBackend,
+    /// Browser engine to render with (the cdp backend supports only Chromium).
+    pub engine: BrowserEngine,
+    /// Proxy and TLS settings for page loads.
+    pub network: NetworkOptions,
 }
 
 impl Default for BrowserOptions {
@@ -62,15 +112,23 @@ impl Default for BrowserOptions {
             network_idle_timeout: DEFAULT_NETWORK_IDLE_TIMEOUT,
             process_timeout: DEFAULT_PROCESS_TIMEOUT,
             max_concurrent_sessions: 1,
+            backend: CaptureBackend::Playwright,
+            engine: BrowserEngine::Chromium,
+            network: NetworkOptions::default(),
         }
     }
 }
 
-/// Manages concurrent browser sessions with semaphore-based limiting.
+/// Runs renders on a pool of `max_concurrent_sessions` browser sessions.
+///
+/// Each session has its own profile directory, used by one render at a time
+/// and kept for the session's later renders; renders beyond the pool size
+/// wait in a first-come, first-served queue. Clones share the pool, and the
+/// profiles are deleted when the last clone is dropped.
 #[derive(Debug, Clone)]
 pub struct BrowserManager {
     options: BrowserOptions,
-    semaphore: Arc<Semaphore>,
+    sessions: SessionPool,
 }
 
 /// Result of rendering a page.
@@ -82,16 +140,22 @@ pub struct PageRenderResult {
     pub viewport: Viewport,
     /// Time taken to render the page.
     pub elapsed: Duration,
+    /// Session that rendered the page (`0..max_concurrent_sessions`).
+    pub session_id: usize,
+    /// Time spent queued for a free session before rendering started.
+    pub queue_wait: Duration,
 }
 
 impl BrowserManager {
     /// Creates a new BrowserManager with the given options.
     pub fn new(options: BrowserOptions) -> Self {
-        let permits = options.max_concurrent_sessions.max(1);
-        Self {
-            options,
-            semaphore: Arc::new(Semaphore::new(permits)),
-        }
+        let sessions = SessionPool::new(options.max_concurrent_sessions);
+        Self { options, sessions }
+    }
+
+    /// Queue wait and usage counters across all renders so far.
+    pub fn stats(&self) -> SessionStats {
+        self.sessions.stats()
     }
 
     /// Renders a URL and optionally saves a screenshot.
@@ -100,14 +164,29 @@ impl BrowserManager {
         url: &str,
         screenshot_path: Option<&Path>,
     ) -> Result<PageRenderResult> {
-        self.ensure_node_available().await?;
-        let _permit = self
-            .semaphore
-            .acquire()
-            .await
-            .map_err(|_| DpcError::Config("Browser manager unavailable".to_string()))?;
+        if self.options.backend == CaptureBackend::Playwright {
+            self.ensure_node_available().await?;
+        }
+        let session = self.sessions.acquire().await?;
+        let start = Instant::now();
+        match self.options.backend {
+            CaptureBackend::Playwright => {
+                self.run_playwright(url, screenshot_path, &session.profile_dir)
+                    .await?
+            }
+            CaptureBackend::Cdp => {
+                self.run_cdp(url, screenshot_path, &session.profile_dir)
+                    .await?
+            }
+        }
 
-        self.run_playwright(url, screenshot_path).await
+        Ok(PageRenderResult {
+            screenshot_path: screenshot_path.map(|path| path.to_path_buf()),
+            viewport: self.options.viewport,
+            elapsed: start.elapsed(),
+            session_id: session.id,
+            queue_wait: session.queue_wait,
+        })
     }
 
     /// Render a URL to a full NormalizedView (screenshot + DOM snapshot) using the manager's settings.
@@ -116,14 +195,14 @@ impl BrowserManager {
         url: &str,
         screenshot_path: &Path,
     ) -> Result<NormalizedView> {
-        ensure_node_available(&self.options.node_command).await?;
-        let _permit = self
-            .semaphore
-            .acquire()
-            .await
-            .map_err(|_| DpcError::Config("Browser manager unavailable".to_string()))?;
-
-        let options: UrlToViewOptions = self.options.clone().into();
+        if self.options.backend == CaptureBackend::Playwright {
+            self.ensure_node_available().await?;
+        }
+        let session = self.sessions.acquire().await?;
+        let options = UrlToViewOptions {
+            user_data_dir: Some(session.profile_dir.clone()),
+            ..self.options.clone().into()
+        };
         url_to_normalized_view(url, screenshot_path, options).await
     }
 
@@ -131,7 +210,8 @@ impl BrowserManager {
         &self,
         url: &str,
         screenshot_path: Option<&Path>,
-    ) -> Result<PageRenderResult> {
+        profile_dir: &Path,
+    ) -> Result<()> {
         // Fail fast if Node is missing to avoid spawning Playwright unnecessarily.
         self.ensure_node_available().await?;
         ensure_playwright_available(&self.options.node_command).await?;
@@ -158,10 +238,15 @@ impl BrowserManager {
                     .unwrap_or_default(),
             )
             .arg(if self.options.headless { "1" } else { "0" })
+            .arg(self.options.viewport.device_scale_factor.to_string())
+            .arg(self.options.engine.as_str())
+            .arg(self.options.network.playwright_json())
+            .arg(profile_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        apply_managed_env(&mut cmd);
+        self.options.network.apply_node_env(&mut cmd);
 
-        let start = Instant::now();
         let mut child = cmd
             .spawn()
             .map_err(|err| map_spawn_error(err, &self.options.node_command))?;
@@ -224,12 +309,24 @@ impl BrowserManager {
                 stdout.trim()
             )));
         }
+        Ok(())
+    }
 
-        Ok(PageRenderResult {
-            screenshot_path: screenshot_path.map(|path| path.to_path_buf()),
-            viewport: self.options.viewport,
-            elapsed: start.elapsed(),
-        })
+    async fn run_cdp(
+        &self,
+        url: &str,
+        screenshot_path: Option<&Path>,
+        profile_dir: &Path,
+    ) -> Result<()> {
+        if let Some(parent) = screenshot_path.and_then(Path::parent) {
+            fs::create_dir_all(parent)?;
+        }
+        let options = UrlToViewOptions {
+            user_data_dir: Some(profile_dir.to_path_buf()),
+            ..self.options.clone().into()
+        };
+        cdp::capture(url, screenshot_path, &options, false).await?;
+        Ok(())
     }
 
     async fn ensure_node_available(&self) -> Result<()> {
@@ -254,6 +351,29 @@ pub struct UrlToViewOptions {
     pub process_timeout: Duration,
     /// Optional progress callback for logging.
     pub progress: Option<ProgressCallback>,
+    /// Which backend renders the page.
+    pub backend: CaptureBackend,
+    /// Browser engine to render with.
+    pub engine: BrowserEngine,
+    /// Proxy and TLS settings for page loads.
+    pub network: NetworkOptions,
+    /// Record a HAR of the page's network traffic here and collect console
+    /// errors and failed requests into the DOM snapshot (Playwright only).
+    pub har_path: Option<PathBuf>,
+    /// Requests to block or mock while capturing (Playwright only).
+    pub request_rules: RequestRules,
+    /// JavaScript files evaluated in every page before its own scripts, e.g.
+    /// to seed `Math.random`, freeze `Date` or set localStorage flags.
+    pub init_scripts: Vec<PathBuf>,
+    /// Locale and timezone to render with.
+    pub emulation: Emulation,
+    /// Browser profile directory to launch with and keep (a
+    /// [`BrowserManager`] session's); a throwaway profile when unset.
+    pub user_data_dir: Option<PathBuf>,
+    /// Also capture how interactive elements restyle with `:hover` and
+    /// `:focus-visible` forced ([`crate::types::DomNode::interaction_states`];
+    /// Chromium only).
+    pub capture_states: bool,
 }
 
 impl Default for UrlToViewOptions {
@@ -266,6 +386,15 @@ impl Default for UrlToViewOptions {
             network_idle_timeout: DEFAULT_NETWORK_IDLE_TIMEOUT,
             process_timeout: DEFAULT_PROCESS_TIMEOUT,
             progress: None,
+            backend: CaptureBackend::Playwright,
+            engine: BrowserEngine::Chromium,
+            network: NetworkOptions::default(),
+            har_path: None,
+            request_rules: RequestRules::default(),
+            init_scripts: Vec::new(),
+            emulation: Emulation::default(),
+            user_data_dir: None,
+            capture_states: false,
         }
     }
 }
@@ -280,10 +409,29 @@ impl From<BrowserOptions> for UrlToViewOptions {
             network_idle_timeout: opts.network_idle_timeout,
             process_timeout: opts.process_timeout,
             progress: None,
+            backend: opts.backend,
+            engine: opts.engine,
+            network: opts.network,
+            har_path: None,
+            request_rules: RequestRules::default(),
+            init_scripts: Vec::new(),
+            emulation: Emulation::default(),
+            user_data_dir: None,
+            capture_states: false,
         }
     }
 }
 
+/// Init script paths for the Playwright script, made absolute since Node
+/// resolves them against its own working directory.
+fn init_scripts_json(scripts: &[PathBuf]) -> String {
+    let paths: Vec<PathBuf> = scripts
+        .iter()
+        .map(|path| std::path::absolute(path).unwrap_or_else(|_| path.clone()))
+        .collect();
+    serde_json::to_string(&paths).unwrap_or_else(|_| "[]".to_string())
+}
+
 fn log_progress(progress: &Option<ProgressCallback>, message: &str) {
     if let Some(cb) = progress {
         cb(message);
@@ -315,18 +463,68 @@ pub async fn url_to_normalized_view(
     log_progress(
         &progress,
         &format!(
-            "Launching headless browser for {} ({}x{}, nav {}s, idle {}s)…",
-            url, options.viewport.width, options.viewport.height, nav_secs, idle_secs
+            "Launching headless {} for {} ({}, nav {}s, idle {}s)…",
+            options.engine, url, options.viewport, nav_secs, idle_secs
         ),
     );
-    ensure_node_available(&options.node_command).await?;
-    ensure_playwright_available(&options.node_command).await?;
+    if options.backend == CaptureBackend::Playwright {
+        ensure_node_available(&options.node_command).await?;
+        ensure_playwright_available(&options.node_command).await?;
+    }
 
     if let Some(parent) = screenshot_path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| DpcError::Config(format!("Failed to create screenshot dir: {}", e)))?;
     }
 
+    let start = Instant::now();
+    let dom_snapshot = match options.backend {
+        CaptureBackend::Playwright => {
+            capture_with_playwright(url, screenshot_path, &options).await?
+        }
+        CaptureBackend::Cdp => {
+            if options.har_path.is_some() {
+                return Err(DpcError::Config(
+                    "--record-network needs the playwright browser backend".to_string(),
+                ));
+            }
+            if !options.request_rules.is_empty() {
+                return Err(DpcError::Config(
+                    "--block-urls and [requests] rules need the playwright browser backend"
+                        .to_string(),
+                ));
+            }
+            log_progress(&progress, "Navigating and waiting for network idle (CDP)…");
+            cdp::capture(url, Some(screenshot_path), &options, true)
+                .await?
+                .ok_or_else(|| DpcError::Config("CDP capture returned no DOM data".to_string()))?
+        }
+    };
+
+    log_progress(
+        &progress,
+        &format!("Capture finished in {:.1}s", start.elapsed().as_secs_f32()),
+    );
+
+    Ok(NormalizedView {
+        kind: ResourceKind::Url,
+        screenshot_path: screenshot_path.to_path_buf(),
+        width: options.viewport.width,
+        height: options.viewport.height,
+        dom: Some(dom_snapshot),
+        figma_tree: None,
+        ocr_blocks: None,
+        content_area: None,
+    })
+}
+
+/// Run the Playwright helper script and return the converted DOM snapshot.
+async fn capture_with_playwright(
+    url: &str,
+    screenshot_path: &Path,
+    options: &UrlToViewOptions,
+) -> Result<DomSnapshot> {
+    let progress = options.progress.clone();
     let mut cmd = Command::new(&options.node_command);
     cmd.arg("-e")
         .arg(PLAYWRIGHT_SCRIPT_WITH_DOM)
@@ -337,14 +535,41 @@ pub async fn url_to_normalized_view(
         .arg(options.network_idle_timeout.as_millis().to_string())
         .arg(screenshot_path.to_string_lossy().to_string())
         .arg(if options.headless { "1" } else { "0" })
+        .arg(options.viewport.device_scale_factor.to_string())
+        .arg(options.engine.as_str())
+        .arg(options.network.playwright_json())
+        .arg(
+            options
+                .har_path
+                .as_deref()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        )
+        .arg(options.request_rules.playwright_json())
+        .arg(init_scripts_json(&options.init_scripts))
+        .arg(options.emulation.playwright_json())
+        .arg(
+            options
+                .user_data_dir
+                .as_deref()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        )
+        .arg(dom_snapshot_expression())
+        .arg(if options.capture_states {
+            STATE_STYLE_FN
+        } else {
+            ""
+        })
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
+    apply_managed_env(&mut cmd);
+    options.network.apply_node_env(&mut cmd);
 
     log_progress(
         &progress,
         "Navigating and waiting for network idle (Playwright)…",
     );
-    let start = Instant::now();
     let mut child = cmd
         .spawn()
         .map_err(|err| map_spawn_error(err, &options.node_command))?;
@@ -416,22 +641,12 @@ pub async fn url_to_normalized_view(
         DpcError::Config("Playwright returned ok status but no DOM data".to_string())
     })?;
 
-    let dom_snapshot = convert_raw_dom(dom_data);
-
-    log_progress(
-        &progress,
-        &format!("Capture finished in {:.1}s", start.elapsed().as_secs_f32()),
-    );
-
-    Ok(NormalizedView {
-        kind: ResourceKind::Url,
-        screenshot_path: screenshot_path.to_path_buf(),
-        width: options.viewport.width,
-        height: options.viewport.height,
-        dom: Some(dom_snapshot),
-        figma_tree: None,
-        ocr_blocks: None,
-    })
+    let mut dom = convert_raw_dom(dom_data, options.engine);
+    dom.diagnostics = result.diagnostics.map(|mut diagnostics| {
+        diagnostics.har = options.har_path.clone().filter(|path| path.is_file());
+        diagnostics
+    });
+    Ok(dom)
 }
 
 #[cfg(test)]
@@ -453,13 +668,13 @@ mod tests {
     }
 
     #[test]
-    fn semaphore_never_zero() {
+    fn sessions_never_zero() {
         let manager = BrowserManager::new(BrowserOptions {
             max_concurrent_sessions: 0,
             ..BrowserOptions::default()
         });
 
-        assert_eq!(manager.semaphore.available_permits(), 1);
+        assert_eq!(manager.stats().sessions, 1);
     }
 
     #[test]
@@ -479,15 +694,18 @@ mod tests {
     fn url_to_view_options_from_browser_options() {
         let browser_opts = BrowserOptions {
             node_command: "custom-node".to_string(),
-            viewport: Viewport {
-                width: 1920,
-                height: 1080,
-            },
+            viewport: Viewport::new(1920, 1080),
             headless: false,
             navigation_timeout: Duration::from_secs(30),
             network_idle_timeout: Duration::from_secs(10),
             process_timeout: Duration::from_secs(60),
             max_concurrent_sessions: 4,
+            backend: CaptureBackend::Cdp,
+            engine: BrowserEngine::Webkit,
+            network: NetworkOptions {
+                proxy: Some("http://proxy.corp:3128".to_string()),
+                ..NetworkOptions::default()
+            },
         };
 
         let view_opts: UrlToViewOptions = browser_opts.into();
@@ -499,6 +717,45 @@ mod tests {
         assert_eq!(view_opts.network_idle_timeout, Duration::from_secs(10));
         assert_eq!(view_opts.process_timeout, Duration::from_secs(60));
         assert!(view_opts.progress.is_none());
+        assert_eq!(view_opts.backend, CaptureBackend::Cdp);
+        assert_eq!(view_opts.engine, BrowserEngine::Webkit);
+        assert!(view_opts.har_path.is_none());
+        assert_eq!(
+            view_opts.network.proxy.as_deref(),
+            Some("http://proxy.corp:3128")
+        );
+    }
+
+    #[test]
+    fn capture_backend_parses_names() {
+        assert_eq!(
+            "playwright".parse::<CaptureBackend>().unwrap(),
+            CaptureBackend::Playwright
+        );
+        assert_eq!(
+            " CDP ".parse::<CaptureBackend>().unwrap(),
+            CaptureBackend::Cdp
+        );
+        assert!("puppeteer".parse::<CaptureBackend>().is_err());
+        assert_eq!(
+            BrowserOptions::default().backend,
+            CaptureBackend::Playwright
+        );
+    }
+
+    #[cfg(not(feature = "cdp"))]
+    #[tokio::test]
+    async fn cdp_backend_without_the_feature_is_a_config_error() {
+        let options = UrlToViewOptions {
+            backend: CaptureBackend::Cdp,
+            ..UrlToViewOptions::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let err =
+            url_to_normalized_view("https://example.com", &dir.path().join("shot.png"), options)
+                .await
+                .unwrap_err();
+        assert!(matches!(err, DpcError::Config(msg) if msg.contains("`cdp` feature")));
     }
 
     #[tokio::test]
@@ -512,6 +769,18 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn manager_runs_max_concurrent_sessions() {
+        let manager = BrowserManager::new(BrowserOptions {
+            max_concurrent_sessions: 3,
+            ..BrowserOptions::default()
+        });
+        let stats = manager.clone().stats();
+        assert_eq!(stats.sessions, 3);
+        assert_eq!((stats.busy, stats.renders), (0, 0));
+        assert_eq!(stats.average_wait(), Duration::ZERO);
+    }
+
     #[tokio::test]
     async fn render_url_to_normalized_view_checks_node() {
         let manager = BrowserManager::new(BrowserOptions {
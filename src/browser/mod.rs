@@ -6,8 +6,12 @@
 //! # Module Structure
 //!
 //! - [`manager`] - Browser session management with concurrency control
+//! - [`session`] - Reusable sessions with per-session profiles and queue metrics
 //! - [`playwright`] - Playwright scripts and availability checks
+//! - [`cdp`] - Chrome DevTools Protocol backend (`cdp` feature, no Node.js)
 //! - [`dom`] - DOM snapshot types and conversion
+//! - [`intercept`] - Request blocking/mocking rules for captures
+//! - [`emulation`] - Locale and timezone emulation for captures
 //!
 //! # Example
 //!
@@ -23,12 +27,26 @@
 //! # }
 //! ```
 
+mod cdp;
 mod dom;
+mod emulation;
+mod install;
+mod intercept;
 mod manager;
 mod playwright;
+mod session;
+
+pub use emulation::{Emulation, Geolocation, PERMISSIONS};
+pub use install::{
+    default_install_dir, install_playwright, managed_install, ManagedPlaywright, PLAYWRIGHT_DIR_ENV,
+};
+pub use intercept::{MockRoute, RequestRules};
+pub use playwright::{chromium_executable, ensure_playwright_available, node_version};
+pub use session::SessionStats;
 
 // Re-export public types from manager
 pub use manager::{
-    url_to_normalized_view, BrowserManager, BrowserOptions, PageRenderResult, UrlToViewOptions,
-    DEFAULT_NAVIGATION_TIMEOUT, DEFAULT_NETWORK_IDLE_TIMEOUT, DEFAULT_PROCESS_TIMEOUT,
+    url_to_normalized_view, BrowserManager, BrowserOptions, CaptureBackend, PageRenderResult,
+    UrlToViewOptions, BROWSER_BACKEND_ENV, DEFAULT_NAVIGATION_TIMEOUT,
+    DEFAULT_NETWORK_IDLE_TIMEOUT, DEFAULT_PROCESS_TIMEOUT,
 };
@@ -3,27 +3,45 @@
 //! This module contains the inline Playwright scripts, error mapping,
 //! and availability checks for Node.js and Playwright.
 
+use super::install::apply_managed_env;
 use crate::{DpcError, Result};
 use std::io;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::process::Command;
 
 /// Basic Playwright script for screenshot capture.
 pub(crate) const PLAYWRIGHT_SCRIPT: &str = r#"
-const [, url, width, height, navTimeout, idleTimeout, screenshotPath, headlessFlag] = process.argv;
+const [, url, width, height, navTimeout, idleTimeout, screenshotPath, headlessFlag, scale, browserName, networkJson, userDataDir] = process.argv;
 
 async function run() {
   let browser;
   try {
-    const { chromium } = require('playwright');
-    browser = await chromium.launch({ headless: headlessFlag !== '0' });
-    const context = await browser.newContext({
+    const network = JSON.parse(networkJson || '{}');
+    const browserType = require('playwright')[browserName || 'chromium'];
+    const launchOptions = { headless: headlessFlag !== '0' };
+    if (network.proxy) {
+      launchOptions.proxy = network.proxy;
+    }
+    const contextOptions = {
       viewport: {
         width: parseInt(width, 10),
         height: parseInt(height, 10)
-      }
-    });
+      },
+      deviceScaleFactor: parseFloat(scale) || 1,
+      ignoreHTTPSErrors: !!network.ignoreHTTPSErrors
+    };
+    let context;
+    if (userDataDir) {
+      // A session profile: the persistent context owns (and closes) its browser.
+      context = await browserType.launchPersistentContext(userDataDir, { ...launchOptions, ...contextOptions });
+      await context.clearCookies();
+      browser = context;
+    } else {
+      browser = await browserType.launch(launchOptions);
+      context = await browser.newContext(contextOptions);
+    }
     const page = await context.newPage();
     const navMs = parseInt(navTimeout, 10);
     const idleMs = parseInt(idleTimeout, 10);
@@ -52,20 +70,162 @@ run();
 
 /// Playwright script that captures both screenshot and DOM snapshot.
 pub(crate) const PLAYWRIGHT_SCRIPT_WITH_DOM: &str = r#"
-const [, url, width, height, navTimeout, idleTimeout, screenshotPath, headlessFlag] = process.argv;
+const [, url, width, height, navTimeout, idleTimeout, screenshotPath, headlessFlag, scale, browserName, networkJson, harPath, rulesJson, initScriptsJson, emulationJson, userDataDir, domSnapshotScript, stateStyleFn] = process.argv;
+
+const MAX_STATE_ELEMENTS = 200;
+
+// Styles of each interactive element the DOM walk registered, by default and
+// with :hover and :focus-visible forced through CDP (Chromium only).
+async function captureInteractionStates(context, page) {
+  const client = await context.newCDPSession(page);
+  await client.send('DOM.enable');
+  await client.send('CSS.enable');
+  await client.send('DOM.getDocument', { depth: 0 });
+  const ids = await page.evaluate(() => Array.from((window.__dpcInteractiveElements || new Map()).keys()));
+  const states = {};
+  for (const id of ids.slice(0, MAX_STATE_ELEMENTS)) {
+    const { result } = await client.send('Runtime.evaluate', {
+      expression: `window.__dpcInteractiveElements.get(${JSON.stringify(id)})`
+    });
+    if (!result.objectId) continue;
+    const { nodeId } = await client.send('DOM.requestNode', { objectId: result.objectId });
+    const styleWith = async (forcedPseudoClasses) => {
+      await client.send('CSS.forcePseudoState', { nodeId, forcedPseudoClasses });
+      const { result: style } = await client.send('Runtime.callFunctionOn', {
+        objectId: result.objectId,
+        functionDeclaration: stateStyleFn,
+        returnByValue: true
+      });
+      return style.value;
+    };
+    const base = await styleWith([]);
+    const hover = await styleWith(['hover']);
+    const focusVisible = await styleWith(['focus', 'focus-visible']);
+    await client.send('CSS.forcePseudoState', { nodeId, forcedPseudoClasses: [] });
+    states[id] = { default: base, hover, focusVisible };
+  }
+  await client.detach();
+  return states;
+}
 
 async function run() {
   let browser;
   try {
-    const { chromium } = require('playwright');
-    browser = await chromium.launch({ headless: headlessFlag !== '0' });
-    const context = await browser.newContext({
+    const network = JSON.parse(networkJson || '{}');
+    const browserType = require('playwright')[browserName || 'chromium'];
+    const launchOptions = { headless: headlessFlag !== '0' };
+    if (network.proxy) {
+      launchOptions.proxy = network.proxy;
+    }
+    const contextOptions = {
       viewport: {
         width: parseInt(width, 10),
         height: parseInt(height, 10)
-      }
-    });
+      },
+      deviceScaleFactor: parseFloat(scale) || 1,
+      ignoreHTTPSErrors: !!network.ignoreHTTPSErrors
+    };
+    if (harPath) {
+      contextOptions.recordHar = { path: harPath, content: 'omit' };
+    }
+    const emulation = JSON.parse(emulationJson || '{}');
+    if (emulation.locale) {
+      contextOptions.locale = emulation.locale;
+    }
+    if (emulation.timezoneId) {
+      contextOptions.timezoneId = emulation.timezoneId;
+    }
+    if (emulation.geolocation) {
+      contextOptions.geolocation = emulation.geolocation;
+    }
+    if (emulation.permissions && emulation.permissions.length) {
+      contextOptions.permissions = emulation.permissions;
+    }
+    if (emulation.reducedMotion) {
+      contextOptions.reducedMotion = emulation.reducedMotion;
+    }
+    if (emulation.forcedColors) {
+      contextOptions.forcedColors = emulation.forcedColors;
+    }
+    let context;
+    if (userDataDir) {
+      // A session profile: the persistent context owns (and closes) its browser.
+      context = await browserType.launchPersistentContext(userDataDir, { ...launchOptions, ...contextOptions });
+      await context.clearCookies();
+      browser = context;
+    } else {
+      browser = await browserType.launch(launchOptions);
+      context = await browser.newContext(contextOptions);
+    }
+
+    // Later routes take precedence, so mocks registered after blocks win.
+    const rules = JSON.parse(rulesJson || '{}');
+    const blocked = new Set();
+    for (const pattern of rules.block || []) {
+      await context.route(pattern, (route) => {
+        blocked.add(route.request());
+        return route.abort('blockedbyclient');
+      });
+    }
+    for (const mock of rules.mock || []) {
+      await context.route(mock.url, (route) => {
+        const response = { status: mock.status || 200 };
+        if (mock.contentType) {
+          response.contentType = mock.contentType;
+        }
+        if (mock.file) {
+          response.path = mock.file;
+        } else {
+          response.body = mock.body || '';
+        }
+        return route.fulfill(response);
+      });
+    }
+
+    for (const script of JSON.parse(initScriptsJson || '[]')) {
+      await context.addInitScript({ path: script });
+    }
+
     const page = await context.newPage();
+
+    // With a HAR requested, also collect console errors and failed requests.
+    const MAX_DIAGNOSTICS = 100;
+    const diagnostics = harPath ? { consoleErrors: [], failedRequests: [] } : null;
+    if (diagnostics) {
+      const pushLimited = (list, item) => {
+        if (list.length < MAX_DIAGNOSTICS) {
+          list.push(item);
+        }
+      };
+      page.on('console', (msg) => {
+        if (msg.type() === 'error') {
+          pushLimited(diagnostics.consoleErrors, msg.text());
+        }
+      });
+      page.on('pageerror', (err) => {
+        pushLimited(diagnostics.consoleErrors, err && err.message ? err.message : String(err));
+      });
+      page.on('requestfailed', (request) => {
+        if (blocked.has(request)) {
+          return;
+        }
+        const failure = request.failure();
+        pushLimited(diagnostics.failedRequests, {
+          url: request.url(),
+          resourceType: request.resourceType(),
+          error: failure ? failure.errorText : null
+        });
+      });
+      page.on('response', (response) => {
+        if (response.status() >= 400) {
+          pushLimited(diagnostics.failedRequests, {
+            url: response.url(),
+            resourceType: response.request().resourceType(),
+            status: response.status()
+          });
+        }
+      });
+    }
     const navMs = parseInt(navTimeout, 10);
     const idleMs = parseInt(idleTimeout, 10);
 
@@ -76,106 +236,280 @@ async function run() {
       await page.screenshot({ path: screenshotPath, fullPage: false });
     }
 
-    // Extract DOM snapshot
-    const domSnapshot = await page.evaluate(() => {
-      const nodes = [];
-      let nodeId = 0;
-      const nodeMap = new Map();
-
-      function getComputedStyleInfo(el) {
-        const style = window.getComputedStyle(el);
-        const letterSpacing = parseFloat(style.letterSpacing);
-        return {
-          fontFamily: style.fontFamily || null,
-          fontSize: parseFloat(style.fontSize) || null,
-          fontWeight: style.fontWeight || null,
-          lineHeight: parseFloat(style.lineHeight) || null,
-          letterSpacing: Number.isNaN(letterSpacing) ? null : letterSpacing,
-          color: style.color || null,
-          backgroundColor: style.backgroundColor || null,
-          display: style.display || null,
-          visibility: style.visibility || null,
-          opacity: style.opacity !== '' ? parseFloat(style.opacity) : null
-        };
+    const domSnapshot = await page.evaluate(domSnapshotScript);
+    if (stateStyleFn && (browserName || 'chromium') === 'chromium') {
+      domSnapshot.states = await captureInteractionStates(context, page);
+    }
+    // The HAR is written when its context closes.
+    await context.close();
+
+    console.log(JSON.stringify({ status: 'ok', dom: domSnapshot, diagnostics }));
+  } catch (err) {
+    const message = err && err.message ? err.message : String(err);
+    console.error(JSON.stringify({ status: 'error', message }));
+    process.exitCode = 1;
+  } finally {
+    if (browser) {
+      await browser.close();
+    }
+  }
+}
+
+run();
+"#;
+
+/// In-page function (called with an interactive element as `this`) listing
+/// the `[property, value]` pairs a state could change, for the element and
+/// its first descendants. Running transitions are finished first so the
+/// forced state's end value is read.
+pub(crate) const STATE_STYLE_FN: &str = r#"function () {
+  const PROPERTIES = [
+    'color', 'background-color', 'background-image', 'border-top-color',
+    'border-bottom-color', 'outline-style', 'outline-width', 'outline-color',
+    'box-shadow', 'text-decoration-line', 'opacity', 'transform', 'filter'
+  ];
+  const entries = [];
+  for (const el of [this, ...Array.from(this.querySelectorAll('*')).slice(0, 20)]) {
+    for (const animation of el.getAnimations ? el.getAnimations() : []) {
+      try { animation.finish(); } catch (e) {}
+    }
+    const style = window.getComputedStyle(el);
+    for (const property of PROPERTIES) {
+      entries.push([property, style.getPropertyValue(property)]);
+    }
+  }
+  return entries;
+}"#;
+
+/// In-page function that walks `document.body` into the raw DOM snapshot
+/// ([`super::dom::RawDomSnapshot`]); shared by every capture backend.
+pub(crate) const DOM_SNAPSHOT_FN: &str = r#"() => {
+  const nodes = [];
+  let nodeId = 0;
+  const nodeMap = new Map();
+  // Enabled controls whose states a quality run forces afterwards.
+  const INTERACTIVE = 'a[href], button, input:not([type="hidden"]), select, textarea, summary, ' +
+    '[role="button"], [role="link"], [role="tab"], [role="menuitem"], [role="checkbox"], ' +
+    '[tabindex]:not([tabindex="-1"])';
+  const interactive = new Map();
+
+  // Which family in a font-family stack actually renders: a named family
+  // counts when a loaded FontFace provides it or text measures differently
+  // than with the generic fallbacks (i.e. it is installed locally).
+  const GENERIC_FAMILIES = new Set([
+    'serif', 'sans-serif', 'monospace', 'cursive', 'fantasy', 'system-ui',
+    'ui-serif', 'ui-sans-serif', 'ui-monospace', 'ui-rounded', 'emoji', 'math'
+  ]);
+  const loadedFaces = new Set();
+  if (document.fonts) {
+    document.fonts.forEach((face) => {
+      if (face.status === 'loaded') {
+        loadedFaces.add(face.family.replace(/^["']|["']$/g, '').toLowerCase());
       }
+    });
+  }
+  const measureCtx = document.createElement('canvas').getContext('2d');
+  const probe = 'mmmmmmmmmmlli1WQ@#';
+  const measure = (font) => {
+    measureCtx.font = font;
+    return measureCtx.measureText(probe).width;
+  };
+  const baselines = ['monospace', 'serif', 'sans-serif'].map((g) => [g, measure(`72px ${g}`)]);
+  const availability = new Map();
+  function familyAvailable(family) {
+    const key = family.toLowerCase();
+    if (GENERIC_FAMILIES.has(key)) return true;
+    if (!availability.has(key)) {
+      const available = loadedFaces.has(key) ||
+        baselines.some(([generic, width]) => measure(`72px "${family}", ${generic}`) !== width);
+      availability.set(key, available);
+    }
+    return availability.get(key);
+  }
+  const renderedFamilies = new Map();
+  function renderedFontFamily(stack) {
+    if (!stack || !measureCtx) return null;
+    if (!renderedFamilies.has(stack)) {
+      const families = stack.split(',').map((f) => f.trim().replace(/^["']|["']$/g, '')).filter(Boolean);
+      renderedFamilies.set(stack, families.find(familyAvailable) || 'serif');
+    }
+    return renderedFamilies.get(stack);
+  }
 
-      function traverse(node, parentId) {
-        if (node.nodeType !== Node.ELEMENT_NODE) return null;
+  function getComputedStyleInfo(el) {
+    const style = window.getComputedStyle(el);
+    const letterSpacing = parseFloat(style.letterSpacing);
+    return {
+      fontFamily: style.fontFamily || null,
+      renderedFontFamily: renderedFontFamily(style.fontFamily),
+      fontSize: parseFloat(style.fontSize) || null,
+      fontWeight: style.fontWeight || null,
+      lineHeight: parseFloat(style.lineHeight) || null,
+      letterSpacing: Number.isNaN(letterSpacing) ? null : letterSpacing,
+      color: style.color || null,
+      backgroundColor: style.backgroundColor || null,
+      backgroundImage: style.backgroundImage && style.backgroundImage !== 'none' ? style.backgroundImage : null,
+      display: style.display || null,
+      visibility: style.visibility || null,
+      opacity: style.opacity !== '' ? parseFloat(style.opacity) : null,
+      flex: flexLayoutInfo(style)
+    };
+  }
 
-        const el = node;
-        const id = `node-${nodeId++}`;
-        nodeMap.set(el, id);
+  // Flexbox settings of flex containers, compared against Figma auto layout.
+  function flexLayoutInfo(style) {
+    if (style.display !== 'flex' && style.display !== 'inline-flex') return null;
+    const px = (value) => parseFloat(value) || 0;
+    return {
+      direction: style.flexDirection || 'row',
+      rowGap: px(style.rowGap),
+      columnGap: px(style.columnGap),
+      padding: {
+        top: px(style.paddingTop),
+        right: px(style.paddingRight),
+        bottom: px(style.paddingBottom),
+        left: px(style.paddingLeft)
+      },
+      justifyContent: style.justifyContent || null,
+      alignItems: style.alignItems || null,
+      wrap: style.flexWrap === 'wrap' || style.flexWrap === 'wrap-reverse'
+    };
+  }
 
-        const rect = el.getBoundingClientRect();
-        const tag = el.tagName.toLowerCase();
+  // Baseline of the element's first line of own text: the top of the text's
+  // content area plus the ascent of the font it renders in.
+  function textBaseline(el) {
+    if (!measureCtx) return null;
+    const range = document.createRange();
+    for (const child of el.childNodes) {
+      if (child.nodeType !== Node.TEXT_NODE || !child.textContent.trim()) continue;
+      range.selectNodeContents(child);
+      const rect = range.getClientRects()[0];
+      if (!rect) continue;
+      const style = window.getComputedStyle(el);
+      measureCtx.font = `${style.fontStyle} ${style.fontWeight} ${style.fontSize} ${style.fontFamily}`;
+      const ascent = measureCtx.measureText(child.textContent.trim()).fontBoundingBoxAscent;
+      return typeof ascent === 'number' ? rect.top + ascent : null;
+    }
+    return null;
+  }
 
-        // Skip invisible elements
-        if (rect.width === 0 && rect.height === 0) return null;
+  function traverse(node, parentId) {
+    if (node.nodeType !== Node.ELEMENT_NODE) return null;
 
-        const attributes = {};
-        for (const attr of el.attributes) {
-          attributes[attr.name] = attr.value;
-        }
+    const el = node;
+    const id = `node-${nodeId++}`;
+    nodeMap.set(el, id);
 
-        // Get direct text content (not from children)
-        let text = null;
-        for (const child of el.childNodes) {
-          if (child.nodeType === Node.TEXT_NODE) {
-            const trimmed = child.textContent.trim();
-            if (trimmed) {
-              text = text ? text + ' ' + trimmed : trimmed;
-            }
-          }
-        }
+    const rect = el.getBoundingClientRect();
+    const tag = el.tagName.toLowerCase();
 
-        const childIds = [];
-        for (const child of el.children) {
-          const childId = traverse(child, id);
-          if (childId) childIds.push(childId);
-        }
+    // Skip invisible elements
+    if (rect.width === 0 && rect.height === 0) return null;
+    if (el.matches(INTERACTIVE) && !el.disabled) interactive.set(id, el);
 
-        nodes.push({
-          id,
-          tag,
-          children: childIds,
-          parent: parentId,
-          attributes,
-          text,
-          boundingBox: {
-            x: rect.x,
-            y: rect.y,
-            width: rect.width,
-            height: rect.height
-          },
-          computedStyle: getComputedStyleInfo(el)
-        });
+    const attributes = {};
+    for (const attr of el.attributes) {
+      attributes[attr.name] = attr.value;
+    }
 
-        return id;
+    // Get direct text content (not from children)
+    let text = null;
+    for (const child of el.childNodes) {
+      if (child.nodeType === Node.TEXT_NODE) {
+        const trimmed = child.textContent.trim();
+        if (trimmed) {
+          text = text ? text + ' ' + trimmed : trimmed;
+        }
       }
+    }
 
-      traverse(document.body, null);
+    const childIds = [];
+    for (const child of el.children) {
+      const childId = traverse(child, id);
+      if (childId) childIds.push(childId);
+    }
 
-      return {
-        url: window.location.href,
-        title: document.title,
-        nodes
-      };
+    nodes.push({
+      id,
+      tag,
+      children: childIds,
+      parent: parentId,
+      attributes,
+      text,
+      boundingBox: {
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height
+      },
+      textBaseline: text ? textBaseline(el) : null,
+      computedStyle: getComputedStyleInfo(el)
     });
 
-    console.log(JSON.stringify({ status: 'ok', dom: domSnapshot }));
-  } catch (err) {
-    const message = err && err.message ? err.message : String(err);
-    console.error(JSON.stringify({ status: 'error', message }));
-    process.exitCode = 1;
-  } finally {
-    if (browser) {
-      await browser.close();
+    return id;
+  }
+
+  // Custom properties declared by same-origin stylesheets (and the inline
+  // style of <html>): resolved on :root for :root/html rules, else on the
+  // first element the rule's selector matches.
+  function customProperties() {
+    const rootNames = new Set(Array.from(document.documentElement.style).filter((n) => n.startsWith('--')));
+    const scoped = new Map();
+    const visit = (rules) => {
+      for (const rule of rules) {
+        if (rule.style && rule.selectorText) {
+          const names = Array.from(rule.style).filter((n) => n.startsWith('--'));
+          const selector = rule.selectorText.trim();
+          if (/^(:root|html)$/i.test(selector)) {
+            names.forEach((n) => rootNames.add(n));
+          } else if (names.length) {
+            let el = null;
+            try { el = document.querySelector(selector); } catch (e) {}
+            if (el) {
+              const style = window.getComputedStyle(el);
+              const values = scoped.get(selector) || {};
+              names.forEach((n) => { values[n] = style.getPropertyValue(n).trim(); });
+              scoped.set(selector, values);
+            }
+          }
+        }
+        // @media, @supports, @layer and nested rules; @import
+        if (rule.cssRules) visit(rule.cssRules);
+        if (rule.styleSheet) {
+          try { visit(rule.styleSheet.cssRules); } catch (e) {}
+        }
+      }
+    };
+    for (const sheet of Array.from(document.styleSheets)) {
+      let rules = null;
+      try { rules = sheet.cssRules; } catch (e) {} // cross-origin sheets are unreadable
+      if (rules) visit(rules);
     }
+    const rootStyle = window.getComputedStyle(document.documentElement);
+    const root = {};
+    rootNames.forEach((n) => { root[n] = rootStyle.getPropertyValue(n).trim(); });
+    return {
+      root,
+      scoped: Array.from(scoped, ([selector, values]) => ({ selector, values }))
+    };
   }
-}
 
-run();
-"#;
+  traverse(document.body, null);
+  window.__dpcInteractiveElements = interactive;
+
+  return {
+    url: window.location.href,
+    title: document.title,
+    nodes,
+    customProperties: customProperties()
+  };
+}"#;
+
+/// [`DOM_SNAPSHOT_FN`] as an expression that evaluates to the snapshot.
+pub(crate) fn dom_snapshot_expression() -> String {
+    format!("({DOM_SNAPSHOT_FN})()")
+}
 
 /// Timeout for checking node/playwright availability.
 pub(crate) const NODE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
@@ -183,6 +517,11 @@ pub(crate) const NODE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 /// Script to check if Playwright is installed.
 const PLAYWRIGHT_CHECK_SCRIPT: &str = "require('playwright'); process.stdout.write('ok');";
 
+/// Script printing the Chromium binary Playwright launches; exits 3 when it was never downloaded.
+const CHROMIUM_CHECK_SCRIPT: &str =
+    "const path = require('playwright').chromium.executablePath(); \
+process.stdout.write(path); process.exit(require('fs').existsSync(path) ? 0 : 3);";
+
 /// Simple script result for basic Playwright operations.
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct ScriptResult {
@@ -293,8 +632,69 @@ pub(crate) async fn ensure_node_available(node_command: &str) -> Result<()> {
     Ok(())
 }
 
+/// Returns the output of `node --version`, e.g. `v20.11.1`.
+pub async fn node_version(node_command: &str) -> Result<String> {
+    let mut cmd = Command::new(node_command);
+    cmd.arg("--version")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+
+    let output = tokio::time::timeout(NODE_CHECK_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| {
+            DpcError::Config(format!(
+                "Timed out checking node version after {:?}",
+                NODE_CHECK_TIMEOUT
+            ))
+        })?
+        .map_err(|err| map_spawn_error(err, node_command))?;
+
+    if !output.status.success() {
+        return Err(DpcError::Config(format!(
+            "Node command {:?} is not available (exit {})",
+            node_command, output.status
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the Chromium binary Playwright launches, failing when its browsers
+/// have not been downloaded (`npx playwright install chromium`).
+pub async fn chromium_executable(node_command: &str) -> Result<PathBuf> {
+    let mut cmd = Command::new(node_command);
+    cmd.arg("-e")
+        .arg(CHROMIUM_CHECK_SCRIPT)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    apply_managed_env(&mut cmd);
+
+    let output = tokio::time::timeout(NODE_CHECK_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| {
+            DpcError::Config(format!(
+                "Timed out locating the Playwright Chromium build after {:?}",
+                NODE_CHECK_TIMEOUT
+            ))
+        })?
+        .map_err(|err| map_spawn_error(err, node_command))?;
+
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    match output.status.code() {
+        Some(0) => Ok(path),
+        Some(3) => Err(DpcError::Config(format!(
+            "Playwright Chromium is not installed (expected at {}); run `dpc install-browser` or `npx playwright install chromium`",
+            path.display()
+        ))),
+        _ => Err(map_playwright_error(
+            format!("{:?}", output.status),
+            &String::from_utf8_lossy(&output.stderr),
+        )),
+    }
+}
+
 /// Ensures Playwright npm package is installed.
-pub(crate) async fn ensure_playwright_available(node_command: &str) -> Result<()> {
+pub async fn ensure_playwright_available(node_command: &str) -> Result<()> {
     if is_mock_rendering_enabled() {
         return Ok(());
     }
@@ -304,6 +704,7 @@ pub(crate) async fn ensure_playwright_available(node_command: &str) -> Result<()
         .arg(PLAYWRIGHT_CHECK_SCRIPT)
         .stdout(Stdio::null())
         .stderr(Stdio::piped());
+    apply_managed_env(&mut cmd);
 
     let output = tokio::time::timeout(NODE_CHECK_TIMEOUT, cmd.output())
         .await
@@ -431,6 +832,14 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn version_and_chromium_checks_fail_for_missing_binary() {
+        assert!(node_version("definitely-not-a-binary").await.is_err());
+        assert!(chromium_executable("definitely-not-a-binary")
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn ensure_playwright_available_fails_for_missing_binary() {
         let result = ensure_playwright_available("definitely-not-a-binary").await;
@@ -0,0 +1,251 @@
+//! Reusable browser sessions for [`BrowserManager`](super::BrowserManager).
+//!
+//! A pool owns `max_concurrent_sessions` numbered sessions, each with its own
+//! browser profile directory. Renders queue for a free session (first come,
+//! first served), so two renders never share a profile at the same time,
+//! while the renders one session handles in turn reuse its warm HTTP cache.
+//! The pool records how long renders waited in the queue, and removes the
+//! profile directories when the last handle to it is dropped.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{DpcError, Result};
+
+static POOL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Queue and usage counters for a [`BrowserManager`](super::BrowserManager).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    /// Number of sessions in the pool.
+    pub sessions: usize,
+    /// Sessions currently rendering.
+    pub busy: usize,
+    /// Renders waiting for a free session.
+    pub queued: usize,
+    /// Renders that have been handed a session so far.
+    pub renders: u64,
+    /// Queue wait summed over all renders.
+    pub total_wait: Duration,
+    /// Longest single queue wait.
+    pub max_wait: Duration,
+}
+
+impl SessionStats {
+    /// Mean queue wait per render.
+    pub fn average_wait(&self) -> Duration {
+        if self.renders == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total_wait.as_nanos() / u128::from(self.renders)) as u64)
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    /// Ids of idle sessions, lowest first so a lightly used pool keeps
+    /// reusing the same warm profiles.
+    idle: Vec<usize>,
+    queued: usize,
+    renders: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    semaphore: Arc<Semaphore>,
+    profiles: Vec<PathBuf>,
+    state: Mutex<PoolState>,
+}
+
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        for profile in &self.profiles {
+            let _ = std::fs::remove_dir_all(profile);
+        }
+    }
+}
+
+/// A fixed set of browser sessions shared by clones of one manager.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionPool {
+    inner: Arc<PoolInner>,
+}
+
+impl SessionPool {
+    /// A pool of `size` sessions (at least one) with profiles under the
+    /// system temp directory.
+    pub(crate) fn new(size: usize) -> Self {
+        Self::with_profile_root(size, &std::env::temp_dir())
+    }
+
+    fn with_profile_root(size: usize, root: &Path) -> Self {
+        let size = size.max(1);
+        let pool = POOL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let profiles = (0..size)
+            .map(|id| root.join(format!("dpc-session-{}-{pool}-{id}", std::process::id())))
+            .collect();
+        Self {
+            inner: Arc::new(PoolInner {
+                semaphore: Arc::new(Semaphore::new(size)),
+                profiles,
+                state: Mutex::new(PoolState {
+                    idle: (0..size).collect(),
+                    queued: 0,
+                    renders: 0,
+                    total_wait: Duration::ZERO,
+                    max_wait: Duration::ZERO,
+                }),
+            }),
+        }
+    }
+
+    /// Wait for a free session and reserve it until the lease is dropped.
+    pub(crate) async fn acquire(&self) -> Result<SessionLease> {
+        let start = Instant::now();
+        let queued = Queued::enter(self);
+        let permit = self.inner.semaphore.clone().acquire_owned().await;
+        let wait = start.elapsed();
+        drop(queued);
+
+        let mut state = self.lock();
+        let permit =
+            permit.map_err(|_| DpcError::Config("Browser manager unavailable".to_string()))?;
+        // The semaphore guarantees an idle session for every permit.
+        let id = state.idle.remove(0);
+        state.renders += 1;
+        state.total_wait += wait;
+        state.max_wait = state.max_wait.max(wait);
+        drop(state);
+
+        let profile_dir = self.inner.profiles[id].clone();
+        std::fs::create_dir_all(&profile_dir).map_err(|err| {
+            DpcError::Config(format!(
+                "Failed to create browser profile {}: {err}",
+                profile_dir.display()
+            ))
+        })?;
+
+        Ok(SessionLease {
+            pool: self.inner.clone(),
+            id,
+            profile_dir,
+            queue_wait: wait,
+            _permit: permit,
+        })
+    }
+
+    pub(crate) fn stats(&self) -> SessionStats {
+        let state = self.lock();
+        let sessions = self.inner.profiles.len();
+        SessionStats {
+            sessions,
+            busy: sessions - state.idle.len(),
+            queued: state.queued,
+            renders: state.renders,
+            total_wait: state.total_wait,
+            max_wait: state.max_wait,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Counts a render as queued until it gets a session or gives up waiting.
+struct Queued<'a>(&'a SessionPool);
+
+impl<'a> Queued<'a> {
+    fn enter(pool: &'a SessionPool) -> Self {
+        pool.lock().queued += 1;
+        Self(pool)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.lock().queued -= 1;
+    }
+}
+
+/// Exclusive use of one session; returns it to the pool on drop.
+#[derive(Debug)]
+pub(crate) struct SessionLease {
+    pool: Arc<PoolInner>,
+    pub(crate) id: usize,
+    pub(crate) profile_dir: PathBuf,
+    pub(crate) queue_wait: Duration,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for SessionLease {
+    fn drop(&mut self) {
+        let mut state = self
+            .pool
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let slot = state.idle.partition_point(|&idle| idle < self.id);
+        state.idle.insert(slot, self.id);
+        // `_permit` is released after this, once the id is back in `idle`.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sessions_are_exclusive_and_queue_in_order() {
+        let root = tempfile::tempdir().unwrap();
+        let pool = SessionPool::with_profile_root(2, root.path());
+
+        let first = pool.acquire().await.unwrap();
+        let second = pool.acquire().await.unwrap();
+        assert_eq!((first.id, second.id), (0, 1));
+        assert_ne!(first.profile_dir, second.profile_dir);
+        assert!(first.profile_dir.is_dir());
+
+        let waiting = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.acquire().await.unwrap() })
+        };
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let stats = pool.stats();
+        assert_eq!((stats.busy, stats.queued), (2, 1));
+
+        drop(second);
+        let third = waiting.await.unwrap();
+        assert_eq!(third.id, 1);
+        assert!(third.queue_wait >= Duration::from_millis(30));
+
+        let stats = pool.stats();
+        assert_eq!(stats.renders, 3);
+        assert_eq!(stats.queued, 0);
+        assert_eq!(stats.max_wait, third.queue_wait);
+        assert!(stats.average_wait() <= stats.max_wait);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_pool_removes_profiles() {
+        let root = tempfile::tempdir().unwrap();
+        let pool = SessionPool::with_profile_root(0, root.path());
+        let lease = pool.acquire().await.unwrap();
+        let profile = lease.profile_dir.clone();
+        assert_eq!(pool.stats().sessions, 1);
+
+        drop(pool);
+        assert!(profile.is_dir(), "an active lease keeps its profile");
+        drop(lease);
+        assert!(!profile.exists());
+    }
+}
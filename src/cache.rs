@@ -0,0 +1,251 @@
+//! Metric result cache keyed by input hashes.
+//!
+//! When neither side changed since a previous run (same screenshot bytes, same
+//! DOM/Figma data after ignores, same metric options and dpc version), the
+//! stored [`MetricScores`] are reused instead of recomputed.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use dpc_lib::types::MetricScores;
+use dpc_lib::{BudgetedScores, DpcError, MetricKind, NormalizedView};
+
+/// Directory of `<key>.json` score files.
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache key for a comparison, or `None` when an input cannot be hashed.
+    ///
+    /// `fingerprint` must change whenever metric configuration does.
+    pub fn key(
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+        metrics: &[MetricKind],
+        fingerprint: &str,
+    ) -> Option<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update([0]);
+        hasher.update(fingerprint);
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(metrics).ok()?);
+        for view in [reference, implementation] {
+            hasher.update([0]);
+            hasher.update(view_digest(view)?);
+        }
+        Some(hex::encode(hasher.finalize()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<MetricScores> {
+        let data = std::fs::read(self.path(key)).ok()?;
+        // A truncated or outdated entry is just a miss.
+        serde_json::from_slice(&data).ok()
+    }
+
+    pub fn put(&self, key: &str, scores: &MetricScores) -> Result<(), DpcError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!("{key}.json.tmp"));
+        std::fs::write(&tmp, serde_json::to_vec(scores)?)?;
+        std::fs::rename(&tmp, self.path(key))?;
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Reuse cached scores for this comparison or compute and store them.
+///
+/// Returns the scores and whether they came from the cache. Cache write
+/// failures only warn; the freshly computed scores are still returned.
+pub fn cached_scores<F>(
+    cache: Option<&ResultCache>,
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    metrics: &[MetricKind],
+    fingerprint: &str,
+    compute: F,
+) -> dpc_lib::Result<(MetricScores, bool)>
+where
+    F: FnOnce() -> dpc_lib::Result<MetricScores>,
+{
+    let key = cache.and_then(|_| ResultCache::key(reference, implementation, metrics, fingerprint));
+    if let (Some(cache), Some(key)) = (cache, key.as_deref()) {
+        if let Some(scores) = cache.get(key) {
+            return Ok((scores, true));
+        }
+    }
+    let scores = compute()?;
+    if let (Some(cache), Some(key)) = (cache, key.as_deref()) {
+        if let Err(err) = cache.put(key, &scores) {
+            eprintln!("Warning: failed to write result cache: {err}");
+        }
+    }
+    Ok((scores, false))
+}
+
+/// [`cached_scores`] for a run under `--budget`: a cache hit skips nothing,
+/// and only runs that finished every metric are stored.
+pub async fn cached_budgeted_scores<F, Fut>(
+    cache: Option<&ResultCache>,
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    metrics: &[MetricKind],
+    fingerprint: &str,
+    compute: F,
+) -> dpc_lib::Result<(BudgetedScores, bool)>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = dpc_lib::Result<BudgetedScores>>,
+{
+    let key = cache.and_then(|_| ResultCache::key(reference, implementation, metrics, fingerprint));
+    if let (Some(cache), Some(key)) = (cache, key.as_deref()) {
+        if let Some(scores) = cache.get(key) {
+            let skipped = Vec::new();
+            return Ok((BudgetedScores { scores, skipped }, true));
+        }
+    }
+    let budgeted = compute().await?;
+    if let (Some(cache), Some(key), true) = (cache, key.as_deref(), budgeted.skipped.is_empty()) {
+        if let Err(err) = cache.put(key, &budgeted.scores) {
+            eprintln!("Warning: failed to write result cache: {err}");
+        }
+    }
+    Ok((budgeted, false))
+}
+
+/// Content hash of everything metrics read from a view: the screenshot bytes
+/// plus its DOM/Figma/OCR data. The screenshot path itself is excluded since
+/// it differs between runs.
+fn view_digest(view: &NormalizedView) -> Option<Vec<u8>> {
+    let mut value = serde_json::to_value(view).ok()?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("screenshotPath");
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(file_bytes_digest(&view.screenshot_path)?);
+    // serde_json maps are sorted, so DOM attribute order does not matter.
+    hasher.update(serde_json::to_vec(&value).ok()?);
+    Some(hasher.finalize().to_vec())
+}
+
+fn file_bytes_digest(path: &Path) -> Option<Vec<u8>> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(Sha256::digest(bytes).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpc_lib::types::{ColorMetric, ResourceKind};
+
+    fn view(dir: &Path, name: &str, bytes: &[u8]) -> NormalizedView {
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        NormalizedView {
+            kind: ResourceKind::Image,
+            screenshot_path: path,
+            width: 10,
+            height: 10,
+            dom: None,
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        }
+    }
+
+    fn scores(color: f32) -> MetricScores {
+        MetricScores {
+            pixel: None,
+            layout: None,
+            typography: None,
+            color: Some(ColorMetric {
+                score: color,
+                diffs: Vec::new(),
+            }),
+            content: None,
+            semantic: None,
+        }
+    }
+
+    #[test]
+    fn key_ignores_paths_but_tracks_content_and_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = view(dir.path(), "a.png", b"same");
+        let b = view(dir.path(), "b.png", b"same");
+        let c = view(dir.path(), "c.png", b"changed");
+        let kinds = [MetricKind::Pixel];
+
+        let key = ResultCache::key(&a, &a, &kinds, "cfg").unwrap();
+        assert_eq!(ResultCache::key(&b, &b, &kinds, "cfg").unwrap(), key);
+        assert_ne!(ResultCache::key(&a, &c, &kinds, "cfg").unwrap(), key);
+        assert_ne!(ResultCache::key(&a, &a, &kinds, "other").unwrap(), key);
+        assert_ne!(
+            ResultCache::key(&a, &a, &[MetricKind::Color], "cfg").unwrap(),
+            key
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_budgeted_scores_only_store_complete_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(dir.path().join("cache"));
+        let a = view(dir.path(), "a.png", b"a");
+        let kinds = [MetricKind::Pixel, MetricKind::Color];
+        let run = |skipped: Vec<MetricKind>| async move {
+            Ok(BudgetedScores {
+                scores: scores(0.5),
+                skipped,
+            })
+        };
+
+        let (partial, hit) = cached_budgeted_scores(Some(&cache), &a, &a, &kinds, "cfg", || {
+            run(vec![MetricKind::Pixel])
+        })
+        .await
+        .unwrap();
+        assert!(!hit);
+        assert_eq!(partial.skipped, vec![MetricKind::Pixel]);
+
+        let (_, hit) = cached_budgeted_scores(Some(&cache), &a, &a, &kinds, "cfg", || run(vec![]))
+            .await
+            .unwrap();
+        assert!(!hit, "a partial run must not be cached");
+
+        let (cached, hit) =
+            cached_budgeted_scores(Some(&cache), &a, &a, &kinds, "cfg", || run(vec![]))
+                .await
+                .unwrap();
+        assert!(hit);
+        assert!(cached.skipped.is_empty());
+    }
+
+    #[test]
+    fn cached_scores_reuse_previous_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(dir.path().join("cache"));
+        let a = view(dir.path(), "a.png", b"a");
+        let kinds = [MetricKind::Color];
+
+        let (first, hit) =
+            cached_scores(Some(&cache), &a, &a, &kinds, "cfg", || Ok(scores(0.5))).unwrap();
+        assert!(!hit);
+        assert_eq!(first.color.unwrap().score, 0.5);
+
+        let (second, hit) = cached_scores(Some(&cache), &a, &a, &kinds, "cfg", || {
+            panic!("should not recompute")
+        })
+        .unwrap();
+        assert!(hit);
+        assert_eq!(second.color.unwrap().score, 0.5);
+    }
+}
@@ -1,13 +1,14 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use dpc_lib::Viewport;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use dpc_lib::{BrowserEngine, Geolocation, TransparencyMode, Viewport};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "dpc")]
 #[command(
     version,
     about = "Design Parity Checker - Compare implementations against design references",
-    long_about = "Design Parity Checker (DPC)\n\nModes:\n- compare: measure similarity between a reference (Figma/URL/image) and an implementation (Figma/URL/image).\n- generate-code: create HTML/Tailwind from a single input via a screenshot-to-code backend (or mock).\n- quality: experimental reference-free scoring.\n\nUse --help on any subcommand for details."
+    long_about = "Design Parity Checker (DPC)\n\nModes:\n- compare: measure similarity between a reference (Figma/URL/image) and an implementation (Figma/URL/image).\n- self-compare: visual regression of a URL against a previous run's artifacts.\n- generate-code: create HTML/Tailwind from a single input via a screenshot-to-code backend (or mock).\n- quality: experimental reference-free scoring.\n\nUse --help on any subcommand for details."
 )]
 #[command(propagate_version = true)]
 pub struct Cli {
@@ -24,31 +25,69 @@ pub struct Cli {
         help = "Optional config file (TOML) to set defaults for viewport/threshold/weights/timeouts; CLI flags override config"
     )]
     pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        help = "Proxy for Figma API calls, downloads and browser captures (default: HTTPS_PROXY/HTTP_PROXY)"
+    )]
+    pub proxy: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "PEM bundle of extra CA certificates to trust, e.g. for a TLS-inspecting proxy (env: DPC_CA_BUNDLE)"
+    )]
+    pub ca_bundle: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Accept invalid or self-signed TLS certificates (env: DPC_IGNORE_TLS_ERRORS)"
+    )]
+    pub ignore_tls_errors: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "MP",
+        help = "Reject image inputs and Figma exports larger than this many megapixels (width x height) before decoding; default 128 (env: DPC_MAX_IMAGE_MEGAPIXELS)"
+    )]
+    pub max_image_megapixels: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "BYTES",
+        help = "Reject encoded image files and downloads larger than this; default 67108864 (64 MiB) (env: DPC_MAX_IMAGE_BYTES)"
+    )]
+    pub max_image_bytes: Option<u64>,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Compare a reference design against an implementation
-    Compare {
-        #[arg(long, help = "Reference resource (Figma URL, web URL, or local image)")]
-        r#ref: String,
+    Compare(CompareArgs),
+
+    /// Compare a fresh capture of a URL against a previous run's artifacts (visual regression)
+    SelfCompare {
+        #[arg(help = "URL to capture")]
+        url: String,
 
         #[arg(
             long,
-            help = "Implementation resource (Figma URL, web URL, or local image)"
+            value_name = "DIR",
+            help = "Artifacts directory of an earlier run (from --artifacts-dir) holding impl_screenshot.png and impl_dom.json"
         )]
-        r#impl: String,
-
-        #[arg(long, value_enum, help = "Override type detection for reference")]
-        ref_type: Option<ResourceType>,
-
-        #[arg(long, value_enum, help = "Override type detection for implementation")]
-        impl_type: Option<ResourceType>,
+        baseline: PathBuf,
 
         #[arg(
             long,
             default_value = "1440x900",
-            help = "Viewport dimensions (WIDTHxHEIGHT)"
+            help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx]) or a preset; should match the baseline run"
         )]
         viewport: Viewport,
 
@@ -62,7 +101,7 @@ pub enum Commands {
         #[arg(
             long,
             value_delimiter = ',',
-            help = "Metrics to compute (pixel,layout,typography,color,content)"
+            help = "Metrics to compute (pixel,layout,typography,color,content,semantic)"
         )]
         metrics: Option<Vec<String>>,
 
@@ -72,140 +111,1123 @@ pub enum Commands {
         )]
         ignore_selectors: Option<String>,
 
-        #[arg(
-            long,
-            help = "Path to JSON array of {x,y,width,height} regions to mask before metrics (values can be px or 0-1 normalized)"
-        )]
-        ignore_regions: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Path to JSON array of {x,y,width,height} regions to mask before metrics (values can be px or 0-1 normalized)"
+        )]
+        ignore_regions: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(long, short, help = "Output file path (stdout if omitted)")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Keep intermediate artifacts (screenshots, DOM snapshots); otherwise cleaned up"
+        )]
+        keep_artifacts: bool,
+
+        #[arg(
+            long,
+            help = "Directory to store this run's artifacts (implies --keep-artifacts); usable as the next baseline",
+            value_name = "PATH"
+        )]
+        artifacts_dir: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "URI",
+            help = "Publish artifacts to s3://bucket/prefix, gs://bucket/prefix or a local directory (see compare --artifact-store)"
+        )]
+        artifact_store: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Reuse metric scores cached in PATH when both inputs and metric options are unchanged since a previous run"
+        )]
+        cache_dir: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "30",
+            help = "Navigation timeout (seconds) for URL rendering"
+        )]
+        nav_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "10",
+            help = "Network idle timeout (seconds) for URL rendering"
+        )]
+        network_idle_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "45",
+            help = "Process timeout (seconds) for Playwright invocation"
+        )]
+        process_timeout: u64,
+
+        #[arg(
+            long = "init-script",
+            value_name = "PATH",
+            help = "JavaScript file run in the page before its own scripts (repeatable); see compare --init-script"
+        )]
+        init_scripts: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "TAG",
+            help = "Render the page with this locale (BCP 47, e.g. de-DE); see compare --locale"
+        )]
+        locale: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "ZONE",
+            help = "Render the page in this IANA timezone (e.g. Europe/Berlin)"
+        )]
+        timezone: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "LAT,LON[,ACCURACY]",
+            help = "Report this position to navigator.geolocation; see compare --geolocation"
+        )]
+        geolocation: Option<Geolocation>,
+
+        #[arg(
+            long,
+            value_name = "LIST",
+            help = "Comma-separated permissions granted to the page; see compare --grant-permissions"
+        )]
+        grant_permissions: Option<String>,
+
+        #[arg(long, help = "Render the page with prefers-reduced-motion: reduce")]
+        reduced_motion: bool,
+
+        #[arg(long, help = "Render the page with forced-colors: active")]
+        forced_colors: bool,
+    },
+
+    /// Compare every screen of a Figma prototype flow with its implementation
+    Flow {
+        #[arg(
+            long,
+            help = "Start frame of the flow (Figma URL with node-id, figma:<KEY>#node-id=..., or figma-json:<PATH>#node-id=...)"
+        )]
+        r#ref: String,
+
+        #[arg(
+            long = "impl",
+            value_name = "RESOURCE",
+            required = true,
+            help = "Implementation of the next flow screen (repeatable, in flow order): URL, route or image"
+        )]
+        impls: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Base URL that --impl routes starting with / are joined to"
+        )]
+        base_url: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "1440x900",
+            help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx]) or a preset: desktop, laptop, tablet, mobile"
+        )]
+        viewport: Viewport,
+
+        #[arg(
+            long,
+            default_value = "0.95",
+            help = "Similarity threshold each screen must reach"
+        )]
+        threshold: f64,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Metrics to compute (pixel,layout,typography,color,content,semantic)"
+        )]
+        metrics: Option<Vec<String>>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(long, short, help = "Output file path (stdout if omitted)")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Keep intermediate artifacts (screenshots, DOM snapshots); otherwise cleaned up"
+        )]
+        keep_artifacts: bool,
+
+        #[arg(
+            long,
+            help = "Directory to store artifacts (implies --keep-artifacts)",
+            value_name = "PATH"
+        )]
+        artifacts_dir: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "30",
+            help = "Navigation timeout (seconds) for URL rendering"
+        )]
+        nav_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "10",
+            help = "Network idle timeout (seconds) for URL rendering"
+        )]
+        network_idle_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "45",
+            help = "Process timeout (seconds) for Playwright invocation"
+        )]
+        process_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "chromium",
+            value_name = "ENGINE",
+            help = "Browser engine for URL inputs: chromium, firefox or webkit"
+        )]
+        browser: BrowserEngine,
+
+        #[arg(
+            long,
+            value_name = "TAG",
+            help = "Render URL inputs with this locale (BCP 47, e.g. de-DE); see compare --locale"
+        )]
+        locale: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "ZONE",
+            help = "Render URL inputs in this IANA timezone (e.g. Europe/Berlin)"
+        )]
+        timezone: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "LAT,LON[,ACCURACY]",
+            help = "Report this position to navigator.geolocation in URL inputs; see compare --geolocation"
+        )]
+        geolocation: Option<Geolocation>,
+
+        #[arg(
+            long,
+            value_name = "LIST",
+            help = "Comma-separated permissions granted to URL inputs; see compare --grant-permissions"
+        )]
+        grant_permissions: Option<String>,
+
+        #[arg(long, help = "Render URL inputs with prefers-reduced-motion: reduce")]
+        reduced_motion: bool,
+
+        #[arg(long, help = "Render URL inputs with forced-colors: active")]
+        forced_colors: bool,
+    },
+
+    /// Crawl implementation pages and compare each with the reference its route maps to
+    Audit {
+        #[arg(
+            long,
+            value_name = "URL|PATH",
+            help = "Where to find pages: a sitemap (.xml URL or file) or a page whose links are followed"
+        )]
+        crawl: String,
+
+        #[arg(
+            long = "route",
+            value_name = "PATTERN=REFERENCE",
+            required = true,
+            help = "Map page paths to references (repeatable, first match wins); * matches within a segment, ** across, {1}.. insert matches"
+        )]
+        routes: Vec<String>,
+
+        #[arg(
+            long,
+            default_value = "1",
+            help = "Link hops followed from the start page (link crawling only)"
+        )]
+        max_depth: usize,
+
+        #[arg(long, default_value = "20", help = "Most pages to audit")]
+        max_pages: usize,
+
+        #[arg(
+            long,
+            default_value = "1440x900",
+            help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx]) or a preset: desktop, laptop, tablet, mobile"
+        )]
+        viewport: Viewport,
+
+        #[arg(
+            long,
+            default_value = "0.95",
+            help = "Similarity threshold each page must reach"
+        )]
+        threshold: f64,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Metrics to compute (pixel,layout,typography,color,content,semantic)"
+        )]
+        metrics: Option<Vec<String>>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(long, short, help = "Output file path (stdout if omitted)")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Keep intermediate artifacts (screenshots, DOM snapshots); otherwise cleaned up"
+        )]
+        keep_artifacts: bool,
+
+        #[arg(
+            long,
+            help = "Directory to store artifacts (implies --keep-artifacts)",
+            value_name = "PATH"
+        )]
+        artifacts_dir: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "30",
+            help = "Navigation timeout (seconds) for URL rendering"
+        )]
+        nav_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "10",
+            help = "Network idle timeout (seconds) for URL rendering"
+        )]
+        network_idle_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "45",
+            help = "Process timeout (seconds) for Playwright invocation"
+        )]
+        process_timeout: u64,
+
+        #[arg(
+            long,
+            default_value = "chromium",
+            value_name = "ENGINE",
+            help = "Browser engine for URL inputs: chromium, firefox or webkit"
+        )]
+        browser: BrowserEngine,
+
+        #[arg(
+            long,
+            value_name = "TAG",
+            help = "Render URL inputs with this locale (BCP 47, e.g. de-DE); see compare --locale"
+        )]
+        locale: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "ZONE",
+            help = "Render URL inputs in this IANA timezone (e.g. Europe/Berlin)"
+        )]
+        timezone: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "LAT,LON[,ACCURACY]",
+            help = "Report this position to navigator.geolocation in URL inputs; see compare --geolocation"
+        )]
+        geolocation: Option<Geolocation>,
+
+        #[arg(
+            long,
+            value_name = "LIST",
+            help = "Comma-separated permissions granted to URL inputs; see compare --grant-permissions"
+        )]
+        grant_permissions: Option<String>,
+
+        #[arg(long, help = "Render URL inputs with prefers-reduced-motion: reduce")]
+        reduced_motion: bool,
+
+        #[arg(long, help = "Render URL inputs with forced-colors: active")]
+        forced_colors: bool,
+    },
+
+    /// Generate HTML/Tailwind code from a design input
+    GenerateCode {
+        #[arg(long, help = "Input resource (Figma URL, web URL, or local image)")]
+        input: String,
+
+        #[arg(long, value_enum, help = "Override type detection for input")]
+        input_type: Option<ResourceType>,
+
+        #[arg(
+            long,
+            default_value = "html+tailwind",
+            help = "Output stack (e.g., html+tailwind)"
+        )]
+        stack: String,
+
+        #[arg(
+            long,
+            default_value = "1440x900",
+            help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx]) or a preset: desktop, laptop, tablet, mobile"
+        )]
+        viewport: Viewport,
+
+        #[arg(
+            long,
+            short,
+            help = "Write generated code to this file (JSON status is printed to stdout)"
+        )]
+        output: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+    },
+
+    /// Compute reference-free design quality score (experimental)
+    Quality {
+        #[arg(long, help = "Input resource (Figma URL, web URL, or local image)")]
+        input: String,
+
+        #[arg(long, value_enum, help = "Override type detection for input")]
+        input_type: Option<ResourceType>,
+
+        #[arg(
+            long,
+            default_value = "1440x900",
+            help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx]) or a preset: desktop, laptop, tablet, mobile"
+        )]
+        viewport: Viewport,
+
+        #[arg(long, short, help = "Output file path")]
+        output: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+    },
+
+    /// Manage Figma credentials
+    Figma {
+        #[command(subcommand)]
+        command: FigmaCommands,
+    },
+
+    /// Check Node.js, Playwright browsers, Figma credentials and the temp dir, with fixes for each problem
+    Doctor {
+        #[arg(long, value_enum, default_value = "pretty", help = "Output format")]
+        format: OutputFormat,
+    },
+
+    /// Install Playwright and Chromium into a directory managed by dpc and use it for URL rendering
+    InstallBrowser {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Install location (default: <data dir>/dpc/playwright); remembered for later runs"
+        )]
+        dir: Option<PathBuf>,
+
+        #[arg(
+            long = "browser",
+            value_name = "ENGINE",
+            value_delimiter = ',',
+            default_value = "chromium",
+            help = "Browser engines to download (comma-separated): chromium, firefox, webkit"
+        )]
+        browsers: Vec<BrowserEngine>,
+    },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_enum, help = "Shell to generate completions for")]
+        shell: clap_complete::Shell,
+    },
+
+    /// Generate man pages
+    Man {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Write dpc.1 plus one page per subcommand into DIR instead of printing dpc.1 to stdout"
+        )]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Print the JSON Schema of dpc's JSON output
+    Schema {
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the schema to PATH instead of stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+
+    /// Step through the findings of a kept compare run and accept known differences
+    Review {
+        #[arg(
+            value_name = "ARTIFACTS_DIR",
+            help = "Directory written by `dpc compare --artifacts-dir` (must contain result.json)"
+        )]
+        dir: PathBuf,
+    },
+
+    /// Show which metrics improved or regressed and which findings appeared or disappeared between two compare results
+    DiffResults {
+        #[arg(
+            value_name = "OLD",
+            help = "Earlier compare result (JSON written by `dpc compare`)"
+        )]
+        old: PathBuf,
+
+        #[arg(value_name = "NEW", help = "Later compare result")]
+        new: PathBuf,
+
+        #[arg(long, short, help = "Output file path")]
+        output: Option<PathBuf>,
+
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(
+            long,
+            help = "Exit with code 1 when a metric regressed or was dropped, or a finding appeared"
+        )]
+        fail_on_regression: bool,
+    },
+
+    /// Publish a compare result to a GitHub pull request as a comment and/or a check run with annotations
+    PublishGithub {
+        #[arg(
+            value_name = "RESULT",
+            help = "Compare result JSON, or a kept artifacts directory containing result.json"
+        )]
+        result: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "OWNER/NAME",
+            help = "Repository to publish to [default: $GITHUB_REPOSITORY]"
+        )]
+        repo: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "NUMBER",
+            help = "Pull request to comment on; the comment is updated on later runs"
+        )]
+        pr: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Create a check run with one annotation per finding on the commit given by --sha"
+        )]
+        check_run: bool,
+
+        #[arg(
+            long,
+            value_name = "SHA",
+            help = "Commit for the check run [default: $GITHUB_SHA]"
+        )]
+        sha: Option<String>,
+
+        #[arg(long, default_value = "dpc", help = "Name of the check run")]
+        check_name: String,
+
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Repository file the annotations point at [default: the RESULT path]"
+        )]
+        annotation_path: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "URI",
+            help = "Upload the composite image here (s3://, gs://) so the comment can embed it"
+        )]
+        artifact_store: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            default_value = dpc_lib::github::DEFAULT_GITHUB_API_URL,
+            help = "GitHub API base URL (GitHub Enterprise Server: https://HOST/api/v3)"
+        )]
+        api_url: String,
+    },
+
+    /// Open or update a Jira/Linear issue with the top findings of a failing compare result
+    OpenIssue {
+        #[arg(
+            value_name = "RESULT",
+            help = "Compare result JSON, or a kept artifacts directory containing result.json"
+        )]
+        result: PathBuf,
+
+        #[arg(long, value_enum, help = "Issue tracker to file the issue in")]
+        tracker: IssueTracker,
+
+        #[arg(
+            long,
+            value_name = "KEY",
+            help = "Jira project key or Linear team key the issue belongs to"
+        )]
+        project: String,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value = "major",
+            help = "Only open an issue when a finding is at least this severe"
+        )]
+        min_severity: SeverityLevel,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Base URL the artifacts are served from (e.g. the CI job's artifact page); links point at local paths otherwise"
+        )]
+        artifacts_url: Option<String>,
+
+        #[arg(long, default_value = "Bug", help = "Jira issue type for new issues")]
+        issue_type: String,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Jira site URL [default: $JIRA_URL]; for Linear, the GraphQL endpoint"
+        )]
+        tracker_url: Option<String>,
+    },
+}
+
+/// Flags of `dpc compare`.
+#[derive(Args)]
+pub struct CompareArgs {
+    #[arg(
+        long,
+        required_unless_present = "only_regions",
+        help = "Reference resource (Figma URL, web URL, or local image)"
+    )]
+    pub r#ref: Option<String>,
+
+    #[arg(
+        long = "alt-ref",
+        value_name = "RESOURCE",
+        help = "Additional reference candidate (repeatable); the best-matching reference is used"
+    )]
+    pub alt_refs: Vec<String>,
+
+    #[arg(
+        long,
+        required_unless_present = "only_regions",
+        help = "Implementation resource (Figma URL, web URL, or local image)"
+    )]
+    pub r#impl: Option<String>,
+
+    #[arg(long, value_enum, help = "Override type detection for reference")]
+    pub ref_type: Option<ResourceType>,
+
+    #[arg(long, value_enum, help = "Override type detection for implementation")]
+    pub impl_type: Option<ResourceType>,
+
+    #[arg(
+        long,
+        default_value = "1440x900",
+        help = "Viewport dimensions (WIDTHxHEIGHT[@SCALEx], e.g. 1440x900@2x) or a preset: desktop, laptop, tablet, mobile"
+    )]
+    pub viewport: Viewport,
+
+    #[arg(
+        long,
+        help = "Fail instead of warning when a Figma frame's aspect ratio is far from the viewport's (it would be letterboxed)"
+    )]
+    pub strict_aspect: bool,
+
+    #[arg(
+        long,
+        default_value = "0.95",
+        help = "Similarity threshold for pass/fail (similarity >= threshold passes)"
+    )]
+    pub threshold: f64,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Metrics to compute (pixel,layout,typography,color,content,semantic)"
+    )]
+    pub metrics: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "CSS selectors to ignore in DOM comparisons (comma-separated; supports #id, .class, tag)"
+    )]
+    pub ignore_selectors: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to JSON array of {x,y,width,height} regions to mask before metrics (values can be px or 0-1 normalized)"
+    )]
+    pub ignore_regions: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "accepted.json written by `dpc review`; accepted pixel regions are masked and other accepted findings are dropped from the output"
+    )]
+    pub accepted: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Suppression file listing finding fingerprints that must not fail the run (default: ./.dpc-ignore.json when present)"
+    )]
+    pub ignore_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Add the fingerprints of every current finding to the suppression file (creating it if needed) before applying it"
+    )]
+    pub update_ignore_file: bool,
+
+    #[arg(long, value_enum, default_value = "json", help = "Output format")]
+    pub format: OutputFormat,
+
+    #[arg(long, short, help = "Output file path (stdout if omitted)")]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Keep intermediate artifacts (screenshots, DOM snapshots); otherwise cleaned up"
+    )]
+    pub keep_artifacts: bool,
+
+    #[arg(
+        long,
+        help = "Directory to store artifacts (implies --keep-artifacts); created if missing",
+        value_name = "PATH"
+    )]
+    pub artifacts_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "URI",
+        help = "Publish artifacts to s3://bucket/prefix, gs://bucket/prefix or a local directory; artifact paths in the output become durable URLs"
+    )]
+    pub artifact_store: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Reuse metric scores cached in PATH when both inputs and metric options are unchanged since a previous run"
+    )]
+    pub cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value = "30",
+        help = "Navigation timeout (seconds) for URL rendering"
+    )]
+    pub nav_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "10",
+        help = "Network idle timeout (seconds) for URL rendering"
+    )]
+    pub network_idle_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "45",
+        help = "Process timeout (seconds) for Playwright invocation"
+    )]
+    pub process_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "chromium",
+        value_name = "ENGINE",
+        help = "Browser engine for URL inputs: chromium, firefox or webkit"
+    )]
+    pub browser: BrowserEngine,
+
+    #[arg(
+        long,
+        help = "Record a HAR plus console errors and failed requests for URL inputs into the artifacts dir and summarize them in the output"
+    )]
+    pub record_network: bool,
+
+    #[arg(
+        long,
+        value_name = "PATTERNS",
+        help = "Comma-separated URL globs to block while rendering URL inputs (e.g. \"**/analytics.js,https://*.doubleclick.net/**\"); adds to the config's [requests] rules"
+    )]
+    pub block_urls: Option<String>,
+
+    #[arg(
+        long = "init-script",
+        value_name = "PATH",
+        help = "JavaScript file run in every page before its own scripts (repeatable), e.g. to seed Math.random, freeze Date or set localStorage flags"
+    )]
+    pub init_scripts: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "TAG",
+        help = "Render URL inputs with this locale (BCP 47, e.g. de-DE): navigator.language, Accept-Language and Intl formatting"
+    )]
+    pub locale: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ZONE",
+        help = "Render URL inputs in this IANA timezone (e.g. Europe/Berlin)"
+    )]
+    pub timezone: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "LAT,LON[,ACCURACY]",
+        help = "Report this position to navigator.geolocation in URL inputs (grants the geolocation permission)"
+    )]
+    pub geolocation: Option<Geolocation>,
+
+    #[arg(
+        long,
+        value_name = "LIST",
+        help = "Comma-separated permissions granted to URL inputs (e.g. notifications,clipboard-read); others keep the browser default, denied in headless runs"
+    )]
+    pub grant_permissions: Option<String>,
+
+    #[arg(
+        long,
+        help = "Render URL inputs with prefers-reduced-motion: reduce, so well-behaved animations settle"
+    )]
+    pub reduced_motion: bool,
+
+    #[arg(
+        long,
+        help = "Render URL inputs with forced-colors: active (Windows high-contrast mode)"
+    )]
+    pub forced_colors: bool,
+
+    #[arg(
+        long,
+        value_name = "BOOL",
+        help = "Enable pixel alignment (true/false) to compensate for x/y shifts"
+    )]
+    pub pixel_align: Option<bool>,
+
+    #[arg(
+        long,
+        value_name = "PX",
+        help = "Max pixel shift for alignment search (pixels)"
+    )]
+    pub pixel_align_max_shift: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "PX",
+        help = "Downscale max dimension for alignment search (pixels)"
+    )]
+    pub pixel_align_downscale: Option<u32>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "How pixel alignment estimates the x/y shift: search (try every offset) or phase-correlation"
+    )]
+    pub pixel_align_method: Option<PixelAlignMethod>,
+
+    #[arg(
+        long,
+        value_name = "PX",
+        help = "Drop pixel diff regions smaller than this many pixels"
+    )]
+    pub min_region_px: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        help = "Drop pixel diff regions smaller than this fraction (0-1) of the image area"
+    )]
+    pub min_region_area: Option<f32>,
+
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        help = "Minimum fraction (0-1) of changed pixels inside a block before it is reported"
+    )]
+    pub min_block_density: Option<f32>,
+
+    #[arg(
+        long,
+        value_name = "MODE",
+        default_value = "ignore",
+        help = "Transparent pixels in pixel/color metrics: ignore (compare as stored), exclude (skip fully transparent pixels), composite or composite:#RRGGBB (flatten over a background, white by default)"
+    )]
+    pub transparency_mode: TransparencyMode,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "luma",
+        help = "Per-pixel difference behind pixel diff regions: luma, or ciede2000 (perceptual Lab difference that also catches hue changes at equal brightness)"
+    )]
+    pub pixel_diff_space: PixelDiffSpace,
+
+    #[arg(
+        long,
+        help = "Leave a near-white or near-black dominant color (usually the page background) out of the color palette"
+    )]
+    pub color_exclude_background: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "token",
+        help = "Text matching for the content metric (token overlap, levenshtein ratio, or the best of both)"
+    )]
+    pub content_match: ContentMatch,
+
+    #[arg(
+        long,
+        help = "Ignore accents when matching text (e.g., 'Café' matches 'Cafe')"
+    )]
+    pub content_fold_diacritics: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON file of equivalent phrases/translations treated as identical by the content metric"
+    )]
+    pub content_synonyms: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "off",
+        help = "Normalize numbers, currency amounts and dates before text matching (canonical rewrites formats; mask ignores values)"
+    )]
+    pub content_formats: ContentFormats,
+
+    #[arg(
+        long,
+        help = "Skip lorem ipsum and {{variable}}-style placeholder text in the reference when comparing content (typography and layout are still checked)"
+    )]
+    pub treat_placeholders: bool,
+
+    #[arg(
+        long,
+        help = "Enable semantic analysis of diff regions using a vision model (requires DPC_VISION_API_KEY or OPENAI_API_KEY)"
+    )]
+    pub semantic_analysis: bool,
+
+    #[arg(
+        long,
+        help = "Context description for semantic analysis (e.g., 'Home alarm signup page with partner logos')",
+        value_name = "TEXT"
+    )]
+    pub context: Option<String>,
+
+    #[arg(
+        long,
+        help = "Replace the template summary in pretty output and reports with a short narrative from a language model (requires DPC_SUMMARY_API_KEY or OPENAI_API_KEY)"
+    )]
+    pub summarize: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "normalized",
+        help = "Coordinate space for diff regions (normalized adds nothing; pixel/css/all add absolute bounds)"
+    )]
+    pub coordinate_space: CoordinateSpace,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "severity",
+        help = "Color palette for diff_heatmap.png (viridis/magma are colorblind-safe)"
+    )]
+    pub heatmap_palette: HeatmapPalette,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON array of weighted rectangles ({x,y,width,height,weight}; normalized 0-1 or pixels) that scale pixel/layout penalties by location"
+    )]
+    pub region_weights: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FLOAT",
+        help = "Emphasize the top of the page: weight 1 + FLOAT at the top fading to 1 at the bottom (applied to pixel/layout penalties)"
+    )]
+    pub top_emphasis: Option<f32>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON overrides ({\"roles\": {...}, \"tags\": {...}, \"default\": 1.0}) for the element importance that scales layout/content penalties"
+    )]
+    pub importance_model: Option<PathBuf>,
 
-        #[arg(long, value_enum, default_value = "json", help = "Output format")]
-        format: OutputFormat,
+    #[arg(
+        long,
+        help = "Weight pixel/layout penalties by a heuristic saliency estimate of the reference screenshot (contrast, color rarity, edges, position), so diffs in prominent elements count more than background texture"
+    )]
+    pub saliency: bool,
 
-        #[arg(long, short, help = "Output file path (stdout if omitted)")]
-        output: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON weights ({\"bias\", \"contrast\", \"rarity\", \"edges\", \"center\"}) replacing the built-in saliency heuristic's; implies --saliency"
+    )]
+    pub saliency_weights: Option<PathBuf>,
 
-        #[arg(
-            long,
-            help = "Keep intermediate artifacts (screenshots, DOM snapshots); otherwise cleaned up"
-        )]
-        keep_artifacts: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "saliency_weights",
+        help = "ONNX saliency model (1x3xHxW RGB in, HxW attention map out) to use instead of the heuristic; implies --saliency; needs dpc built with the `saliency` feature"
+    )]
+    pub saliency_model: Option<PathBuf>,
 
-        #[arg(
-            long,
-            help = "Directory to store artifacts (implies --keep-artifacts); created if missing",
-            value_name = "PATH"
-        )]
-        artifacts_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "CLIP-style ONNX image encoder for --metrics semantic (overrides [metrics.semantic] model); needs dpc built with the `embeddings` feature"
+    )]
+    pub semantic_model: Option<PathBuf>,
 
-        #[arg(
-            long,
-            default_value = "30",
-            help = "Navigation timeout (seconds) for URL rendering"
-        )]
-        nav_timeout: u64,
+    #[arg(
+        long,
+        help = "For image-vs-image comparisons, detect element boxes from screenshot edges and run the layout metric on them"
+    )]
+    pub layout_from_pixels: bool,
 
-        #[arg(
-            long,
-            default_value = "10",
-            help = "Network idle timeout (seconds) for URL rendering"
-        )]
-        network_idle_timeout: u64,
+    #[arg(
+        long,
+        help = "For image-vs-image comparisons, detect text lines without OCR: faint pixel diffs inside text found in both screenshots are reported as anti_aliasing, and with --layout-from-pixels boxes on text lines are typed text"
+    )]
+    pub detect_text: bool,
 
-        #[arg(
-            long,
-            default_value = "45",
-            help = "Process timeout (seconds) for Playwright invocation"
-        )]
-        process_timeout: u64,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "stretch",
+        help = "When the screenshots' aspect ratios differ: stretch the implementation to the reference size, letterbox it into the reference canvas, crop both to the area they share, or fail"
+    )]
+    pub dimension_mismatch: DimensionMismatch,
 
-        #[arg(
-            long,
-            value_name = "BOOL",
-            help = "Enable pixel alignment (true/false) to compensate for x/y shifts"
-        )]
-        pixel_align: Option<bool>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON array of named page sections ({\"name\": \"hero\", \"selector\": \".hero\"} or {\"name\": \"footer\", \"region\": {x,y,width,height}}) to score separately"
+    )]
+    pub sections: Option<PathBuf>,
 
-        #[arg(
-            long,
-            value_name = "PX",
-            help = "Max pixel shift for alignment search (pixels)"
-        )]
-        pixel_align_max_shift: Option<u32>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        help = "Overall time budget for capture + metrics (e.g. 60s, 2m); metrics still running when it runs out are skipped and reported in skippedMetrics"
+    )]
+    pub budget: Option<Duration>,
 
-        #[arg(
-            long,
-            value_name = "PX",
-            help = "Downscale max dimension for alignment search (pixels)"
-        )]
-        pixel_align_downscale: Option<u32>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Capture the implementation N times and report the score spread with a 95% confidence interval; flags runs whose scores vary (animations, async content)"
+    )]
+    pub samples: u32,
 
-        #[arg(
-            long,
-            help = "Enable semantic analysis of diff regions using a vision model (requires DPC_VISION_API_KEY or OPENAI_API_KEY)"
-        )]
-        semantic_analysis: bool,
+    #[arg(
+        long,
+        help = "Comment major typography and gradient findings on their nodes in the Figma reference file (needs comment write access, see `dpc figma login --comments`)"
+    )]
+    pub post_figma_comments: bool,
 
-        #[arg(
-            long,
-            help = "Context description for semantic analysis (e.g., 'Home alarm signup page with partner logos')",
-            value_name = "TEXT"
-        )]
-        context: Option<String>,
-    },
+    #[arg(
+        long,
+        help = "Compare colors and typography against the published color and text styles of the Figma reference file, and name the style in findings (needs a Figma token)"
+    )]
+    pub figma_styles: bool,
 
-    /// Generate HTML/Tailwind code from a design input
-    GenerateCode {
-        #[arg(long, help = "Input resource (Figma URL, web URL, or local image)")]
-        input: String,
+    #[arg(
+        long,
+        help = "Compare the implementation's CSS custom properties (--*) with the variables of the Figma reference file, per mode, and report values that differ (needs a Figma Enterprise token)"
+    )]
+    pub figma_variables: bool,
 
-        #[arg(long, value_enum, help = "Override type detection for input")]
-        input_type: Option<ResourceType>,
+    #[arg(
+        long,
+        value_name = "RESULT",
+        conflicts_with = "sections",
+        help = "Re-score only what failed in a previous compare result (its failing sections and pixel diff regions) with 8px pixel blocks; --ref/--impl default to the captures saved in its artifacts directory"
+    )]
+    pub only_regions: Option<PathBuf>,
+}
 
+#[derive(Subcommand)]
+pub enum FigmaCommands {
+    /// Sign in through the Figma OAuth browser flow and store the refresh token in the OS keyring
+    Login {
         #[arg(
             long,
-            default_value = "html+tailwind",
-            help = "Output stack (e.g., html+tailwind)"
+            default_value_t = dpc_lib::figma_oauth::DEFAULT_REDIRECT_PORT,
+            help = "Local port for the OAuth redirect (must match the app's registered callback)"
         )]
-        stack: String,
+        port: u16,
 
         #[arg(
             long,
-            default_value = "1440x900",
-            help = "Viewport dimensions (WIDTHxHEIGHT)"
+            help = "Print the authorization URL instead of opening a browser"
         )]
-        viewport: Viewport,
+        no_browser: bool,
 
         #[arg(
             long,
-            short,
-            help = "Write generated code to this file (JSON status is printed to stdout)"
+            help = "Also request permission to post comments (needed by compare --post-figma-comments)"
         )]
-        output: Option<PathBuf>,
-
-        #[arg(long, value_enum, default_value = "json", help = "Output format")]
-        format: OutputFormat,
+        comments: bool,
     },
 
-    /// Compute reference-free design quality score (experimental)
-    Quality {
-        #[arg(long, help = "Input resource (Figma URL, web URL, or local image)")]
-        input: String,
-
-        #[arg(long, value_enum, help = "Override type detection for input")]
-        input_type: Option<ResourceType>,
+    /// Remove Figma OAuth credentials from the OS keyring
+    Logout,
 
+    /// Print the file key and node id dpc parses from a Figma URL or figma: reference
+    Resolve {
         #[arg(
-            long,
-            default_value = "1440x900",
-            help = "Viewport dimensions (WIDTHxHEIGHT)"
+            value_name = "URL",
+            help = "Figma URL, figma:<KEY>#node-id=... or figma-json:<PATH>#node-id=..."
         )]
-        viewport: Viewport,
-
-        #[arg(long, short, help = "Output file path")]
-        output: Option<PathBuf>,
-
-        #[arg(long, value_enum, default_value = "json", help = "Output format")]
-        format: OutputFormat,
+        value: String,
     },
 }
 
@@ -216,6 +1238,74 @@ pub enum ResourceType {
     Figma,
 }
 
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum CoordinateSpace {
+    #[default]
+    Normalized,
+    Pixel,
+    Css,
+    All,
+}
+
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum ContentMatch {
+    #[default]
+    Token,
+    Levenshtein,
+    Hybrid,
+}
+
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum ContentFormats {
+    #[default]
+    Off,
+    Canonical,
+    Mask,
+}
+
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum DimensionMismatch {
+    #[default]
+    Stretch,
+    Letterbox,
+    Crop,
+    Fail,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PixelAlignMethod {
+    Search,
+    PhaseCorrelation,
+}
+
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum PixelDiffSpace {
+    #[default]
+    Luma,
+    Ciede2000,
+}
+
+#[derive(Clone, Copy, ValueEnum, Default)]
+pub enum HeatmapPalette {
+    #[default]
+    Severity,
+    Viridis,
+    Magma,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum IssueTracker {
+    Jira,
+    Linear,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SeverityLevel {
+    Minor,
+    Moderate,
+    Major,
+}
+
 #[derive(Clone, Copy, ValueEnum, Default)]
 pub enum OutputFormat {
     #[default]
@@ -227,10 +1317,139 @@ pub fn parse() -> Cli {
     Cli::parse()
 }
 
+/// The full command tree, for generating completions and man pages.
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Cli, Commands, OutputFormat, ResourceType};
+    use super::{Cli, Commands, CompareArgs, FigmaCommands, OutputFormat, ResourceType};
     use clap::Parser;
+    use dpc_lib::BrowserEngine;
+    use std::path::PathBuf;
+
+    #[test]
+    fn network_flags_are_global() {
+        let cli = Cli::parse_from([
+            "dpc",
+            "doctor",
+            "--proxy",
+            "http://proxy.corp:3128",
+            "--ca-bundle",
+            "corp-ca.pem",
+            "--ignore-tls-errors",
+        ]);
+        assert_eq!(cli.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(cli.ca_bundle, Some(PathBuf::from("corp-ca.pem")));
+        assert!(cli.ignore_tls_errors);
+    }
+
+    #[test]
+    fn image_limit_flags_are_global() {
+        let cli = Cli::parse_from([
+            "dpc",
+            "compare",
+            "--ref",
+            "ref.png",
+            "--impl",
+            "impl.png",
+            "--max-image-megapixels",
+            "300",
+            "--max-image-bytes",
+            "134217728",
+        ]);
+        assert_eq!(cli.max_image_megapixels, Some(300));
+        assert_eq!(cli.max_image_bytes, Some(134_217_728));
+    }
+
+    #[test]
+    fn self_compare_command_takes_url_and_baseline() {
+        let cli = Cli::parse_from([
+            "dpc",
+            "self-compare",
+            "https://example.com",
+            "--baseline",
+            "artifacts/run1",
+        ]);
+
+        match cli.command {
+            Commands::SelfCompare {
+                url,
+                baseline,
+                threshold,
+                artifacts_dir,
+                ..
+            } => {
+                assert_eq!(url, "https://example.com");
+                assert_eq!(baseline, std::path::PathBuf::from("artifacts/run1"));
+                assert!((threshold - 0.95).abs() < f64::EPSILON);
+                assert!(artifacts_dir.is_none());
+            }
+            _ => panic!("expected self-compare command"),
+        }
+    }
+
+    #[test]
+    fn flow_command_takes_repeated_impls() {
+        let cli = Cli::parse_from([
+            "dpc",
+            "flow",
+            "--ref",
+            "figma:ABC#node-id=1:1",
+            "--impl",
+            "/cart",
+            "--impl",
+            "/payment",
+            "--base-url",
+            "https://staging.example.com",
+        ]);
+
+        match cli.command {
+            Commands::Flow {
+                r#ref,
+                impls,
+                base_url,
+                ..
+            } => {
+                assert_eq!(r#ref, "figma:ABC#node-id=1:1");
+                assert_eq!(impls, ["/cart", "/payment"]);
+                assert_eq!(base_url.as_deref(), Some("https://staging.example.com"));
+            }
+            _ => panic!("expected flow command"),
+        }
+    }
+
+    #[test]
+    fn audit_command_takes_crawl_limits_and_routes() {
+        let cli = Cli::parse_from([
+            "dpc",
+            "audit",
+            "--crawl",
+            "https://staging.example.com/sitemap.xml",
+            "--route",
+            "/=home.png",
+            "--route",
+            "/products/*=figma-json:designs/{1}.json",
+            "--max-pages",
+            "5",
+        ]);
+
+        match cli.command {
+            Commands::Audit {
+                crawl,
+                routes,
+                max_depth,
+                max_pages,
+                ..
+            } => {
+                assert_eq!(crawl, "https://staging.example.com/sitemap.xml");
+                assert_eq!(routes.len(), 2);
+                assert_eq!((max_depth, max_pages), (1, 5));
+            }
+            _ => panic!("expected audit command"),
+        }
+    }
 
     #[test]
     fn compare_command_uses_defaults() {
@@ -247,7 +1466,7 @@ mod tests {
         assert!(cli.config.is_none());
 
         match cli.command {
-            Commands::Compare {
+            Commands::Compare(CompareArgs {
                 r#ref,
                 r#impl,
                 ref_type,
@@ -264,10 +1483,12 @@ mod tests {
                 nav_timeout,
                 network_idle_timeout,
                 process_timeout,
+                browser,
+                record_network,
                 ..
-            } => {
-                assert_eq!(r#ref, "https://example.com/design");
-                assert_eq!(r#impl, "https://example.com/build");
+            }) => {
+                assert_eq!(r#ref.as_deref(), Some("https://example.com/design"));
+                assert_eq!(r#impl.as_deref(), Some("https://example.com/build"));
                 assert!(ref_type.is_none());
                 assert!(impl_type.is_none());
                 assert_eq!(viewport.width, 1440);
@@ -283,9 +1504,27 @@ mod tests {
                 assert_eq!(nav_timeout, 30);
                 assert_eq!(network_idle_timeout, 10);
                 assert_eq!(process_timeout, 45);
+                assert_eq!(browser, BrowserEngine::Chromium);
+                assert!(!record_network);
+            }
+            _ => panic!("expected compare command"),
+        }
+    }
+
+    #[test]
+    fn compare_budget_accepts_human_durations() {
+        let parse = |budget: &str| {
+            Cli::try_parse_from([
+                "dpc", "compare", "--ref", "a.png", "--impl", "b.png", "--budget", budget,
+            ])
+        };
+        match parse("1m 30s").unwrap().command {
+            Commands::Compare(CompareArgs { budget, .. }) => {
+                assert_eq!(budget, Some(std::time::Duration::from_secs(90)));
             }
             _ => panic!("expected compare command"),
         }
+        assert!(parse("soon").is_err());
     }
 
     #[test]
@@ -324,12 +1563,14 @@ mod tests {
             "6",
             "--process-timeout",
             "50",
+            "--browser",
+            "firefox",
             "--config",
             "dpc.toml",
         ]);
 
         match cli.command {
-            Commands::Compare {
+            Commands::Compare(CompareArgs {
                 ref_type,
                 impl_type,
                 viewport,
@@ -344,8 +1585,9 @@ mod tests {
                 nav_timeout,
                 network_idle_timeout,
                 process_timeout,
+                browser,
                 ..
-            } => {
+            }) => {
                 assert!(matches!(ref_type, Some(ResourceType::Image)));
                 assert!(matches!(impl_type, Some(ResourceType::Figma)));
                 assert_eq!(viewport.width, 1920);
@@ -370,6 +1612,7 @@ mod tests {
                 assert_eq!(nav_timeout, 20);
                 assert_eq!(network_idle_timeout, 6);
                 assert_eq!(process_timeout, 50);
+                assert_eq!(browser, BrowserEngine::Firefox);
             }
             _ => panic!("expected compare command with overrides"),
         }
@@ -405,4 +1648,38 @@ mod tests {
             _ => panic!("expected quality command"),
         }
     }
+
+    #[test]
+    fn parses_figma_login_subcommand() {
+        let cli = Cli::parse_from(["dpc", "figma", "login", "--port", "9001", "--no-browser"]);
+
+        match cli.command {
+            Commands::Figma {
+                command:
+                    FigmaCommands::Login {
+                        port,
+                        no_browser,
+                        comments,
+                    },
+            } => {
+                assert_eq!(port, 9001);
+                assert!(no_browser);
+                assert!(!comments);
+            }
+            _ => panic!("expected figma login command"),
+        }
+    }
+
+    #[test]
+    fn parses_figma_resolve_subcommand() {
+        let url = "https://www.figma.com/design/ABC/Name?node-id=1-23";
+        let cli = Cli::parse_from(["dpc", "figma", "resolve", url]);
+
+        match cli.command {
+            Commands::Figma {
+                command: FigmaCommands::Resolve { value },
+            } => assert_eq!(value, url),
+            _ => panic!("expected figma resolve command"),
+        }
+    }
 }
@@ -0,0 +1,218 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dpc_lib::output::DPC_OUTPUT_VERSION;
+use dpc_lib::{
+    crawl, match_route, parse_resource, AuditOutput, AuditPage, BrowserEngine, CrawlOptions,
+    DpcError, DpcOutput, Emulation, Geolocation, ResourceDescriptor, RouteRule, Viewport,
+};
+
+use super::compare::parse_metric_kinds;
+use super::screens::ScreenScorer;
+use crate::cli::OutputFormat;
+use crate::formatting::{exit_code_for_compare, render_error, write_output};
+use crate::pipeline::resolve_artifacts_dir;
+use crate::settings::{load_config, resolve_compare_settings, CompareFlagSources};
+
+/// Run `dpc audit`: crawl the implementation's pages and compare each with
+/// the reference its route maps to.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_audit(
+    raw_args: &[String],
+    config_path: Option<PathBuf>,
+    verbose: bool,
+    crawl_start: String,
+    routes: Vec<String>,
+    crawl_options: CrawlOptions,
+    viewport: Viewport,
+    threshold: f64,
+    metrics: Option<Vec<String>>,
+    format: OutputFormat,
+    output: Option<PathBuf>,
+    keep_artifacts: bool,
+    artifacts_dir: Option<PathBuf>,
+    nav_timeout: u64,
+    network_idle_timeout: u64,
+    process_timeout: u64,
+    browser: BrowserEngine,
+    locale: Option<String>,
+    timezone: Option<String>,
+    geolocation: Option<Geolocation>,
+    grant_permissions: Option<String>,
+    reduced_motion: bool,
+    forced_colors: bool,
+) -> ExitCode {
+    let config = match load_config(config_path.as_deref()) {
+        Ok(cfg) => cfg,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    let resolved = resolve_compare_settings(
+        viewport,
+        threshold,
+        nav_timeout,
+        network_idle_timeout,
+        process_timeout,
+        None,
+        None,
+        None,
+        None,
+        &config,
+        &CompareFlagSources::from_args(raw_args),
+    );
+    let emulation = Emulation {
+        locale,
+        timezone,
+        geolocation,
+        reduced_motion,
+        forced_colors,
+        ..Emulation::default()
+    }
+    .grant_permissions(grant_permissions.as_deref().unwrap_or_default());
+    if let Err(err) = emulation.validate() {
+        return render_error(DpcError::Config(err), format, output.clone());
+    }
+    let viewport = resolved.viewport;
+    let threshold = resolved.threshold as f32;
+
+    let rules = match routes
+        .iter()
+        .map(|rule| rule.parse::<RouteRule>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(rules) => rules,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    let selected_metrics = match parse_metric_kinds(metrics.as_deref()) {
+        Ok(k) => k,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+
+    let urls = match crawl(&crawl_start, &crawl_options).await {
+        Ok(urls) => urls,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    if verbose {
+        eprintln!("Crawl found {} page(s) from {}", urls.len(), crawl_start);
+    }
+    let mut warnings = Vec::new();
+    if urls.is_empty() {
+        warnings.push(format!("the crawl of {crawl_start} found no pages"));
+    } else if urls.len() == crawl_options.max_pages {
+        warnings.push(format!(
+            "stopped at --max-pages {}; the site may have more pages",
+            crawl_options.max_pages
+        ));
+    }
+
+    let (artifacts_dir, artifacts_from_cli) = resolve_artifacts_dir(artifacts_dir.as_deref());
+    if let Err(err) = std::fs::create_dir_all(&artifacts_dir) {
+        return render_error(DpcError::Io(err), format, output.clone());
+    }
+    let should_keep_artifacts = keep_artifacts || artifacts_from_cli;
+    let scorer = ScreenScorer::new(
+        resolved,
+        config.requests.clone(),
+        browser,
+        emulation,
+        config.severity.calibration(),
+        &config.metrics,
+        selected_metrics,
+        &artifacts_dir,
+        verbose,
+    );
+
+    let mut pages = Vec::with_capacity(urls.len());
+    let mut unmatched = Vec::new();
+    for (idx, url) in urls.iter().enumerate() {
+        let mut page = AuditPage {
+            url: url.to_string(),
+            ref_resource: None,
+            similarity: None,
+            passed: false,
+            metrics: None,
+            top_issues: Vec::new(),
+            error: None,
+        };
+        let Some(reference) = match_route(&rules, url) else {
+            unmatched.push(url.path().to_string());
+            pages.push(page);
+            continue;
+        };
+        let reference = match parse_resource(&reference, None) {
+            Ok(resource) => resource,
+            Err(err) => {
+                page.error = Some(format!("invalid reference '{reference}': {err}"));
+                pages.push(page);
+                continue;
+            }
+        };
+        page.ref_resource = Some(ResourceDescriptor {
+            kind: reference.kind,
+            value: reference.display_value(),
+        });
+        let implementation = match parse_resource(url.as_str(), None) {
+            Ok(resource) => resource,
+            Err(err) => {
+                page.error = Some(err.to_string());
+                pages.push(page);
+                continue;
+            }
+        };
+        if verbose {
+            eprintln!(
+                "Page {}/{}: {} vs {}",
+                idx + 1,
+                urls.len(),
+                url,
+                reference.display_value()
+            );
+        }
+        match scorer.score(&reference, &implementation, idx + 1).await {
+            Ok(score) => {
+                page.similarity = Some(score.similarity);
+                page.passed = score.similarity >= threshold;
+                page.top_issues = score.top_issues;
+                page.metrics = Some(score.metrics);
+            }
+            Err(err) => page.error = Some(err),
+        }
+        pages.push(page);
+    }
+    if !unmatched.is_empty() {
+        warnings.push(format!(
+            "{} page(s) matched no --route and were not compared: {}",
+            unmatched.len(),
+            unmatched.join(", ")
+        ));
+    }
+
+    let audited: Vec<&AuditPage> = pages.iter().filter(|p| p.ref_resource.is_some()).collect();
+    let scored: Vec<f32> = audited.iter().filter_map(|p| p.similarity).collect();
+    let similarity = if scored.is_empty() {
+        0.0
+    } else {
+        scored.iter().sum::<f32>() / scored.len() as f32
+    };
+    let passed = !audited.is_empty() && audited.iter().all(|p| p.passed);
+    let body = DpcOutput::Audit(AuditOutput {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        crawl: crawl_start,
+        viewport,
+        similarity,
+        threshold,
+        passed,
+        pages,
+        warnings,
+    });
+
+    if let Err(err) = write_output(&body, format, output.clone()) {
+        return render_error(DpcError::Config(err.to_string()), format, output);
+    }
+    if should_keep_artifacts {
+        eprintln!("Artifacts directory: {}", artifacts_dir.display());
+    } else {
+        let _ = std::fs::remove_dir_all(&artifacts_dir);
+    }
+
+    exit_code_for_compare(passed)
+}
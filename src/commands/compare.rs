@@ -1,21 +1,48 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration;
 
+use image::GenericImageView;
+use tokio::time::Instant;
+
+use dpc_lib::config::FigmaConfig;
+use dpc_lib::image_alignment::AlignmentMethod;
 use dpc_lib::output::DPC_OUTPUT_VERSION;
-use dpc_lib::types::ResourceKind;
+use dpc_lib::suppression::fingerprints;
+use dpc_lib::types::{CustomProperties, FigmaStyle, RegionBounds, ResourceKind};
+use dpc_lib::view_bundle::{VIEW_FILE, VIEW_SCHEME};
 use dpc_lib::{
-    calculate_combined_score, parse_resource, run_metrics, ColorPaletteMetric, CompareOutput,
-    ContentSimilarity, DpcError, DpcOutput, LayoutSimilarity, Metric, MetricKind, PixelSimilarity,
-    ResourceDescriptor, SemanticAnalyzer, TypographySimilarity, Viewport,
+    apply_coordinate_space, assign_fingerprints, calculate_combined_score, compare_variables,
+    downscale_view, encode_compare_artifacts, failing_regions, fetch_figma_styles,
+    fetch_figma_variables, figma_comment_drafts, generate_top_issues_with, load_sections,
+    metric_score, parse_resource, post_figma_comments, run_metrics, run_metrics_until,
+    section_view, store_from_spec, write_artifact_index, write_overlay, AcceptedFindings,
+    ApiSummarizer, BudgetedScores, ColorPaletteMetric, CompareOutput, ContentMatchMode,
+    ContentSimilarity, CoordinateFrame, CoordinateSpace, Degradation, DimensionMismatch,
+    DimensionMismatchPolicy, DpcError, DpcOutput, EdgeBoxDetector, Emulation, FigmaApiClient,
+    FormatNormalization, ImageEncoder, ImportanceModel, LayoutSimilarity, Metric, MetricKind,
+    MetricScores, NoiseSuppression, NormalizedView, ParsedResource, PixelDiffSpace,
+    PixelSimilarity, PostedFigmaComment, ReferenceCandidate, RegionWeights, ResourceDescriptor,
+    SaliencyEstimator, SaliencyHeuristic, SaliencyModel, SampleStats, ScoreWeights, Section,
+    SectionScore, SemanticAnalyzer, SeverityCalibration, SharedMetric, SkippedMetric, Summarizer,
+    SummaryRequest, SuppressionFile, SynonymTable, TextDetector, TypographySimilarity,
+    VariableDrift, DEFAULT_SUPPRESSION_FILE,
 };
 
-use crate::cli::OutputFormat;
+use super::diff_results::load_result;
+use crate::cache::{cached_budgeted_scores, cached_scores, ResultCache};
+use crate::cli::{CompareArgs, OutputFormat};
 use crate::formatting::{exit_code_for_compare, render_error, write_output};
+use crate::manifest::{persist_run_manifest, ManifestInput, ManifestInputs, RunManifest};
 use crate::pipeline::{
-    apply_dom_ignores, apply_ignore_regions, generate_summary, load_ignore_regions,
-    parse_ignore_selectors, persist_compare_artifacts, resolve_artifacts_dir,
-    resource_to_normalized_view,
+    apply_dom_ignores, apply_ignore_regions, aspect_ratio_warning, check_init_scripts,
+    compare_page_diagnostics, generate_summary, load_ignore_regions, page_diagnostics_warnings,
+    parse_ignore_selectors, persist_compare_artifacts, reconcile_dimensions, resolve_artifacts_dir,
+    resource_to_normalized_view, truncate_dom, upload_compare_artifacts, validate_ignore_selectors,
+    write_json_pretty, HeatmapPalette, IgnoreRegion,
 };
 use crate::progress::ProgressCallback;
 use crate::settings::{
@@ -23,34 +50,100 @@ use crate::settings::{
     CompareFlagSources,
 };
 
+/// Pixel block size of a focused re-run (`--only-regions`), finer than the
+/// default so small fixes inside a region show up.
+const FOCUS_BLOCK_SIZE: u32 = 8;
+
+/// The pixel block size to use, capped at [`FOCUS_BLOCK_SIZE`] on a focused
+/// re-run.
+fn focused_block_size(block_size: u32, focused: bool) -> u32 {
+    if focused {
+        block_size.min(FOCUS_BLOCK_SIZE)
+    } else {
+        block_size
+    }
+}
+
 /// Run the compare command.
-#[allow(clippy::too_many_arguments)]
 pub async fn run_compare(
     raw_args: &[String],
     config_path: Option<PathBuf>,
     verbose: bool,
-    r#ref: String,
-    r#impl: String,
-    ref_type: Option<crate::cli::ResourceType>,
-    impl_type: Option<crate::cli::ResourceType>,
-    viewport: Viewport,
-    threshold: f64,
-    metrics: Option<Vec<String>>,
-    format: OutputFormat,
-    output: Option<PathBuf>,
-    keep_artifacts: bool,
-    ignore_selectors: Option<String>,
-    ignore_regions: Option<PathBuf>,
-    artifacts_dir: Option<PathBuf>,
-    nav_timeout: u64,
-    network_idle_timeout: u64,
-    process_timeout: u64,
-    pixel_align: Option<bool>,
-    pixel_align_max_shift: Option<u32>,
-    pixel_align_downscale: Option<u32>,
-    semantic_analysis: bool,
-    context: Option<String>,
+    args: CompareArgs,
 ) -> ExitCode {
+    let CompareArgs {
+        r#ref,
+        alt_refs,
+        r#impl,
+        ref_type,
+        impl_type,
+        viewport,
+        strict_aspect,
+        threshold,
+        metrics,
+        format,
+        output,
+        keep_artifacts,
+        ignore_selectors,
+        ignore_regions,
+        accepted,
+        ignore_file,
+        update_ignore_file,
+        artifacts_dir,
+        artifact_store,
+        cache_dir,
+        nav_timeout,
+        network_idle_timeout,
+        process_timeout,
+        browser,
+        record_network,
+        block_urls,
+        init_scripts,
+        locale,
+        timezone,
+        geolocation,
+        grant_permissions,
+        reduced_motion,
+        forced_colors,
+        pixel_align,
+        pixel_align_max_shift,
+        pixel_align_downscale,
+        pixel_align_method,
+        min_region_px,
+        min_region_area,
+        min_block_density,
+        transparency_mode,
+        pixel_diff_space,
+        color_exclude_background,
+        content_match,
+        content_fold_diacritics,
+        content_synonyms,
+        content_formats,
+        treat_placeholders,
+        semantic_analysis,
+        context,
+        summarize,
+        coordinate_space,
+        heatmap_palette,
+        region_weights,
+        top_emphasis,
+        importance_model,
+        saliency,
+        saliency_weights,
+        saliency_model,
+        semantic_model,
+        layout_from_pixels,
+        detect_text,
+        dimension_mismatch,
+        sections,
+        budget,
+        samples,
+        post_figma_comments,
+        figma_styles,
+        figma_variables,
+        only_regions,
+    } = args;
+    let budget = budget.map(Budget::start);
     let config = match load_config(config_path.as_deref()) {
         Ok(cfg) => cfg,
         Err(err) => return render_error(err, format, output.clone()),
@@ -66,6 +159,7 @@ pub async fn run_compare(
         pixel_align,
         pixel_align_max_shift,
         pixel_align_downscale,
+        pixel_align_method.map(alignment_method_from_cli),
         &config,
         &flag_sources,
     );
@@ -76,6 +170,10 @@ pub async fn run_compare(
     let process_timeout = resolved.process_timeout;
     let score_weights = resolved.weights;
     let pixel_alignment = resolved.pixel_alignment;
+    let request_rules = match block_urls.as_deref() {
+        Some(patterns) => config.requests.clone().block_patterns(patterns),
+        None => config.requests.clone(),
+    };
 
     if verbose {
         log_effective_config(
@@ -106,27 +204,190 @@ pub async fn run_compare(
         eprintln!("Parsing resources\u{2026}");
     }
 
-    let ref_res = match parse_resource(&r#ref, ref_type.map(resource_kind_from_cli)) {
-        Ok(res) => res,
-        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    let prior = match only_regions.as_deref().map(load_result) {
+        Some(Ok(result)) => Some(result),
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => None,
+    };
+    let (r#ref, r#impl) = match focus_inputs(r#ref, r#impl, prior.as_ref()) {
+        Ok(inputs) => inputs,
+        Err(err) => return render_error(err, format, output.clone()),
     };
+    let mut ref_candidates = Vec::with_capacity(1 + alt_refs.len());
+    for raw in std::iter::once(&r#ref).chain(alt_refs.iter()) {
+        match parse_resource(raw, ref_type.map(resource_kind_from_cli)) {
+            Ok(res) => ref_candidates.push(res),
+            Err(err) => {
+                return render_error(DpcError::Config(err.to_string()), format, output.clone())
+            }
+        }
+    }
     let impl_res = match parse_resource(&r#impl, impl_type.map(resource_kind_from_cli)) {
         Ok(res) => res,
         Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
     };
+    let stdin_inputs = ref_candidates
+        .iter()
+        .chain(std::iter::once(&impl_res))
+        .filter(|res| res.is_stdin())
+        .count();
+    if stdin_inputs > 1 {
+        return render_error(
+            DpcError::Config("Only one input can be read from stdin ('-')".to_string()),
+            format,
+            output.clone(),
+        );
+    }
+    // Stdin can only be read once, and image or saved-view captures never
+    // vary, so resampling them is an error rather than N identical scores.
+    if samples > 1 && matches!(impl_res.kind, ResourceKind::Image | ResourceKind::View) {
+        return render_error(
+            DpcError::Config(format!(
+                "--samples needs a live implementation such as a URL; {} is a static input",
+                impl_res.display_value()
+            )),
+            format,
+            output.clone(),
+        );
+    }
+
+    if let Err(err) = check_init_scripts(&init_scripts) {
+        return render_error(err, format, output.clone());
+    }
+    let emulation = Emulation {
+        locale,
+        timezone,
+        geolocation,
+        reduced_motion,
+        forced_colors,
+        ..Emulation::default()
+    }
+    .grant_permissions(grant_permissions.as_deref().unwrap_or_default());
+    if let Err(err) = emulation.validate() {
+        return render_error(DpcError::Config(err), format, output.clone());
+    }
+    if post_figma_comments && figma_file_key(&ref_candidates[0]).is_none() {
+        return render_error(
+            DpcError::Config("--post-figma-comments needs a Figma --ref".to_string()),
+            format,
+            output.clone(),
+        );
+    }
+    if figma_styles && figma_file_key(&ref_candidates[0]).is_none() {
+        return render_error(
+            DpcError::Config("--figma-styles needs a Figma --ref".to_string()),
+            format,
+            output.clone(),
+        );
+    }
+    if figma_variables && figma_file_key(&ref_candidates[0]).is_none() {
+        return render_error(
+            DpcError::Config("--figma-variables needs a Figma --ref".to_string()),
+            format,
+            output.clone(),
+        );
+    }
 
     let selected_metrics = match parse_metric_kinds(metrics.as_deref()) {
         Ok(k) => k,
         Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
     };
     let ignore_selectors = parse_ignore_selectors(ignore_selectors.as_deref());
-    let ignore_regions = match ignore_regions {
+    if let Err(err) = validate_ignore_selectors(&ignore_selectors) {
+        return render_error(err, format, output.clone());
+    }
+    let mut ignore_regions = match ignore_regions {
         Some(path) => match load_ignore_regions(&path) {
             Ok(regions) => regions,
             Err(err) => return render_error(err, format, output.clone()),
         },
         None => Vec::new(),
     };
+    let accepted = match accepted.as_deref().map(AcceptedFindings::load) {
+        Some(Ok(accepted)) => accepted,
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => AcceptedFindings::default(),
+    };
+    let ignore_file = ignore_file.or_else(|| {
+        let default = PathBuf::from(DEFAULT_SUPPRESSION_FILE);
+        (default.is_file() || update_ignore_file).then_some(default)
+    });
+    let mut suppression_file = match ignore_file.as_deref() {
+        Some(path) if path.is_file() => match SuppressionFile::load(path) {
+            Ok(file) => file,
+            Err(err) => return render_error(err, format, output.clone()),
+        },
+        Some(path) if !update_ignore_file => {
+            return render_error(
+                DpcError::Config(format!("Suppression file not found: {}", path.display())),
+                format,
+                output.clone(),
+            )
+        }
+        _ => SuppressionFile::default(),
+    };
+    // Accepted pixel diffs are masked so they stop counting against the score.
+    ignore_regions.extend(
+        accepted
+            .pixel_regions()
+            .into_iter()
+            .map(|region| IgnoreRegion {
+                x: region.x,
+                y: region.y,
+                width: region.width,
+                height: region.height,
+            }),
+    );
+    let synonyms = match content_synonyms
+        .as_deref()
+        .map(SynonymTable::from_json_file)
+    {
+        Some(Ok(table)) => Some(Arc::new(table)),
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => None,
+    };
+    let region_weights = {
+        let mut weights = match region_weights.as_deref().map(RegionWeights::from_json_file) {
+            Some(Ok(weights)) => weights,
+            Some(Err(err)) => return render_error(err, format, output.clone()),
+            None => RegionWeights::default(),
+        };
+        weights.top_emphasis = top_emphasis.unwrap_or(0.0).max(0.0);
+        (!weights.is_uniform()).then(|| Arc::new(weights))
+    };
+    let dimension_policy = dimension_policy_from_cli(dimension_mismatch);
+    let sections = match sections.as_deref().map(load_sections) {
+        Some(Ok(sections)) => sections,
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => prior.as_ref().map(failing_regions).unwrap_or_default(),
+    };
+    let importance = match importance_model
+        .as_deref()
+        .map(ImportanceModel::from_json_file)
+    {
+        Some(Ok(model)) => Arc::new(model),
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => Arc::new(ImportanceModel::default()),
+    };
+    let saliency =
+        match (saliency_model.as_deref(), saliency_weights.as_deref()) {
+            (Some(path), _) => SaliencyModel::load(path)
+                .map(|model| Some(SaliencyEstimator::Model(Arc::new(model)))),
+            (None, Some(path)) => SaliencyHeuristic::from_json_file(path)
+                .map(|h| Some(SaliencyEstimator::Heuristic(h))),
+            (None, None) => {
+                Ok(saliency.then(|| SaliencyEstimator::Heuristic(SaliencyHeuristic::default())))
+            }
+        };
+    let saliency = match saliency {
+        Ok(saliency) => saliency,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    let artifact_store = match artifact_store.as_deref().map(store_from_spec) {
+        Some(Ok(store)) => Some(store),
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => None,
+    };
 
     // Create temp directory for artifacts
     let (artifacts_dir, artifacts_from_cli) = resolve_artifacts_dir(artifacts_dir.as_deref());
@@ -141,102 +402,448 @@ pub async fn run_compare(
     };
 
     // Convert resources to NormalizedViews
-    if verbose {
-        eprintln!("Normalizing reference ({:?})\u{2026}", ref_res.kind);
+    let mut ref_views_raw = Vec::with_capacity(ref_candidates.len());
+    for (idx, candidate) in ref_candidates.iter().enumerate() {
+        if verbose {
+            eprintln!("Normalizing reference ({:?})\u{2026}", candidate.kind);
+        }
+        let prefix = reference_prefix(idx);
+        let capture = resource_to_normalized_view(
+            candidate,
+            &viewport,
+            &artifacts_dir,
+            &prefix,
+            progress_logger.clone(),
+            nav_timeout,
+            network_idle_timeout,
+            budget.map_or(process_timeout, |b| b.cap_secs(process_timeout)),
+            browser,
+            record_network,
+            &request_rules,
+            &init_scripts,
+            &emulation,
+            false,
+        );
+        match within_budget(budget, "reference capture", capture).await {
+            Ok(view) => ref_views_raw.push(view),
+            Err(err) => {
+                return render_error(
+                    DpcError::Config(format!("Failed to process reference: {}", err)),
+                    format,
+                    output.clone(),
+                )
+            }
+        }
     }
-    let ref_view_raw = match resource_to_normalized_view(
-        &ref_res,
-        &viewport,
-        &artifacts_dir,
-        "ref",
-        progress_logger.clone(),
-        nav_timeout,
-        network_idle_timeout,
-        process_timeout,
-    )
-    .await
-    {
-        Ok(view) => view,
-        Err(err) => {
-            return render_error(
-                DpcError::Config(format!("Failed to process reference: {}", err)),
-                format,
-                output.clone(),
-            )
+
+    let mut impl_views_raw = Vec::with_capacity(samples as usize);
+    for sample in 0..samples as usize {
+        if verbose {
+            eprintln!("Normalizing implementation ({:?})\u{2026}", impl_res.kind);
         }
-    };
+        let prefix = sample_prefix(sample);
+        let capture = resource_to_normalized_view(
+            &impl_res,
+            &viewport,
+            &artifacts_dir,
+            &prefix,
+            progress_logger.clone(),
+            nav_timeout,
+            network_idle_timeout,
+            budget.map_or(process_timeout, |b| b.cap_secs(process_timeout)),
+            browser,
+            record_network,
+            &request_rules,
+            &init_scripts,
+            &emulation,
+            false,
+        );
+        match within_budget(budget, "implementation capture", capture).await {
+            Ok(view) => impl_views_raw.push(view),
+            Err(err) => {
+                return render_error(
+                    DpcError::Config(format!("Failed to process implementation: {}", err)),
+                    format,
+                    output.clone(),
+                )
+            }
+        }
+    }
 
-    if verbose {
-        eprintln!("Normalizing implementation ({:?})\u{2026}", impl_res.kind);
+    let mut warnings: Vec<String> = ref_views_raw
+        .iter()
+        .enumerate()
+        .map(|(idx, view)| (reference_prefix(idx), view))
+        .chain(std::iter::once(("impl".to_string(), &impl_views_raw[0])))
+        .filter_map(|(label, view)| aspect_ratio_warning(&label, view, &viewport))
+        .collect();
+    if strict_aspect && !warnings.is_empty() {
+        return render_error(
+            DpcError::Config(format!("--strict-aspect: {}", warnings.join("; "))),
+            format,
+            output.clone(),
+        );
     }
-    let impl_view_raw = match resource_to_normalized_view(
-        &impl_res,
-        &viewport,
-        &artifacts_dir,
-        "impl",
-        progress_logger.clone(),
-        nav_timeout,
-        network_idle_timeout,
-        process_timeout,
-    )
-    .await
-    {
-        Ok(view) => view,
-        Err(err) => {
-            return render_error(
-                DpcError::Config(format!("Failed to process implementation: {}", err)),
-                format,
-                output.clone(),
-            )
+    if prior.is_some() && sections.is_empty() {
+        warnings.push(
+            "the previous result has no failing sections or pixel diff regions to re-score"
+                .to_string(),
+        );
+    }
+    if figma_styles {
+        if verbose {
+            eprintln!("Fetching published Figma styles\u{2026}");
+        }
+        let attached = attach_figma_styles(&ref_candidates, &mut ref_views_raw, &config.figma);
+        if let Err(err) = attached.await {
+            warnings.push(format!("could not fetch Figma styles: {err}"));
+        }
+    }
+    if let Some(max_nodes) = config.dom.max_nodes {
+        for (idx, view) in ref_views_raw.iter_mut().enumerate() {
+            if let Some(warning) = truncate_dom(&reference_prefix(idx), view, max_nodes) {
+                warnings.push(warning);
+            }
+        }
+        for (sample, view) in impl_views_raw.iter_mut().enumerate() {
+            let warning = truncate_dom("impl", view, max_nodes);
+            if let Some(warning) = warning.filter(|_| sample == 0) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    // Under memory/CPU pressure, analyze smaller screenshots instead of
+    // risking the OOM killer.
+    let degradation = match config.resources.pressure() {
+        Some(reason) => {
+            let max_megapixels = config.resources.max_megapixels();
+            // A focused re-run keeps its finer blocks; the regions it scores
+            // are small enough that the coarse degraded blocks would hide them.
+            let block_size = focused_block_size(
+                config
+                    .metrics
+                    .pixel
+                    .build()
+                    .block_size
+                    .max(config.resources.block_size()),
+                prior.is_some(),
+            );
+            let mut downscaled = Vec::new();
+            let views = ref_views_raw
+                .iter_mut()
+                .enumerate()
+                .map(|(idx, view)| (reference_prefix(idx), view))
+                .chain(
+                    impl_views_raw
+                        .iter_mut()
+                        .enumerate()
+                        .map(|(sample, view)| (sample_prefix(sample), view)),
+                );
+            for (prefix, view) in views {
+                match downscale_view(view, max_megapixels, &artifacts_dir, &prefix) {
+                    Ok(Some((smaller, report))) => {
+                        *view = smaller;
+                        downscaled.push(report);
+                    }
+                    Ok(None) => {}
+                    Err(err) => return render_error(err, format, output.clone()),
+                }
+            }
+            warnings.push(format!(
+                "{reason}; analyzed screenshots of at most {max_megapixels} megapixels \
+                 in {block_size}px pixel blocks ([resources])"
+            ));
+            Some(Degradation {
+                reason,
+                max_megapixels,
+                block_size,
+                downscaled,
+            })
         }
+        None => None,
     };
 
-    let ref_view = apply_dom_ignores(&ref_view_raw, &ignore_selectors);
-    let impl_view = apply_dom_ignores(&impl_view_raw, &ignore_selectors);
+    let mut impl_views = Vec::with_capacity(impl_views_raw.len());
+    for (sample, impl_view_raw) in impl_views_raw.iter().enumerate() {
+        let impl_view = apply_dom_ignores(impl_view_raw, &ignore_selectors);
+        let impl_view = if ignore_regions.is_empty() {
+            impl_view
+        } else {
+            let prefix = sample_prefix(sample);
+            match apply_ignore_regions(&impl_view, &ignore_regions, &artifacts_dir, &prefix) {
+                Ok(view) => view,
+                Err(err) => return render_error(err, format, output.clone()),
+            }
+        };
+        impl_views.push(impl_view);
+    }
 
-    let ref_view = if ignore_regions.is_empty() {
-        ref_view
-    } else {
-        match apply_ignore_regions(&ref_view, &ignore_regions, &artifacts_dir, "ref") {
-            Ok(view) => view,
-            Err(err) => return render_error(err, format, output.clone()),
+    // Saliency is estimated on the (first) reference, where the design puts
+    // the viewer's attention.
+    let region_weights = match &saliency {
+        Some(estimator) => {
+            if verbose {
+                eprintln!("Estimating reference saliency\u{2026}");
+            }
+            let map = image::open(&ref_views_raw[0].screenshot_path)
+                .map_err(DpcError::from)
+                .and_then(|img| estimator.estimate(&img));
+            let map = match map {
+                Ok(map) => map,
+                Err(err) => return render_error(err, format, output.clone()),
+            };
+            let mut weights = region_weights.as_deref().cloned().unwrap_or_default();
+            weights.saliency = Some(Arc::new(map));
+            Some(Arc::new(weights))
         }
+        None => region_weights,
     };
-    let impl_view = if ignore_regions.is_empty() {
-        impl_view
-    } else {
-        match apply_ignore_regions(&impl_view, &ignore_regions, &artifacts_dir, "impl") {
-            Ok(view) => view,
-            Err(err) => return render_error(err, format, output.clone()),
-        }
+
+    let severity = config.severity.calibration();
+    let mut pixel_metric = PixelSimilarity {
+        alignment: pixel_alignment,
+        thresholds: severity.pixel,
+        noise: NoiseSuppression {
+            min_region_pixels: min_region_px.unwrap_or(0),
+            min_region_area: min_region_area.unwrap_or(0.0).clamp(0.0, 1.0),
+            min_block_density: min_block_density.unwrap_or(0.0).clamp(0.0, 1.0),
+        },
+        region_weights: region_weights.clone(),
+        transparency_mode,
+        diff_space: pixel_diff_space_from_cli(pixel_diff_space),
+        text_regions: detect_text.then(TextDetector::default),
+        ..config.metrics.pixel.build()
     };
+    pixel_metric.block_size = match &degradation {
+        Some(degradation) => degradation.block_size,
+        None => focused_block_size(pixel_metric.block_size, prior.is_some()),
+    };
+    let content_metric = ContentSimilarity {
+        mode: content_match_from_cli(content_match),
+        fold_diacritics: content_fold_diacritics,
+        synonyms,
+        formats: content_formats_from_cli(content_formats),
+        treat_placeholders,
+        importance: Some(Arc::clone(&importance)),
+        ..config.metrics.content.build()
+    };
+    let layout_metric = LayoutSimilarity {
+        region_weights,
+        importance: Some(importance),
+        pixel_boxes: layout_from_pixels.then(EdgeBoxDetector::default),
+        pixel_text: detect_text.then(TextDetector::default),
+        ..config.metrics.layout.build()
+    };
+    let cache = cache_dir.map(ResultCache::new);
+    let color_metric = ColorPaletteMetric {
+        transparency_mode,
+        exclude_background: color_exclude_background,
+        ..config.metrics.color.build()
+    };
+    let typography_metric = config.metrics.typography.build();
+    let mut semantic_metric = config.metrics.semantic.build();
+    if let Some(path) = semantic_model {
+        semantic_metric.encoder = Some(Arc::new(ImageEncoder::new(path)));
+    }
+    let metrics_fingerprint = format!(
+        "{pixel_metric:?}{layout_metric:?}{typography_metric:?}{color_metric:?}{content_metric:?}{semantic_metric:?}"
+    );
+    let all_metrics: Vec<SharedMetric> = vec![
+        Arc::new(pixel_metric),
+        Arc::new(layout_metric),
+        Arc::new(typography_metric),
+        Arc::new(color_metric),
+        Arc::new(content_metric),
+        Arc::new(semantic_metric),
+    ];
 
-    // Determine effective metrics based on input types
-    let effective_metrics =
-        if selected_metrics.is_empty() && ref_view.dom.is_none() && impl_view.dom.is_none() {
-            vec![MetricKind::Pixel, MetricKind::Color]
+    // Score every reference candidate and keep the best match
+    let mut best: Option<CandidateRun> = None;
+    let mut candidate_scores = Vec::with_capacity(ref_views_raw.len());
+    for (idx, ref_view_raw) in ref_views_raw.iter().enumerate() {
+        let ref_view = apply_dom_ignores(ref_view_raw, &ignore_selectors);
+        let ref_view = if ignore_regions.is_empty() {
+            ref_view
         } else {
-            selected_metrics
+            match apply_ignore_regions(
+                &ref_view,
+                &ignore_regions,
+                &artifacts_dir,
+                &reference_prefix(idx),
+            ) {
+                Ok(view) => view,
+                Err(err) => return render_error(err, format, output.clone()),
+            }
         };
 
-    // Run metrics
-    if verbose {
-        eprintln!("Running metrics: {:?}", effective_metrics);
+        let (ref_view, impl_view, mismatch) = match reconcile_dimensions(
+            &ref_view,
+            &impl_views[0],
+            dimension_policy,
+            &artifacts_dir,
+            &fitted_prefix(&reference_prefix(idx), &sample_prefix(0)),
+            &fitted_prefix(&sample_prefix(0), &reference_prefix(idx)),
+        ) {
+            Ok(views) => views,
+            Err(err) => return render_error(err, format, output.clone()),
+        };
+
+        // Determine effective metrics based on input types
+        let effective_metrics =
+            if selected_metrics.is_empty() && ref_view.dom.is_none() && impl_view.dom.is_none() {
+                let pixels_only = ref_view.figma_tree.is_none() && impl_view.figma_tree.is_none();
+                if layout_from_pixels && pixels_only {
+                    vec![MetricKind::Pixel, MetricKind::Layout, MetricKind::Color]
+                } else {
+                    vec![MetricKind::Pixel, MetricKind::Color]
+                }
+            } else {
+                selected_metrics.clone()
+            };
+
+        // Run metrics
+        if verbose {
+            eprintln!("Running metrics: {:?}", effective_metrics);
+        }
+        let computed = score_views(
+            cache.as_ref(),
+            &all_metrics,
+            &effective_metrics,
+            &metrics_fingerprint,
+            budget,
+            &ref_view,
+            &impl_view,
+        )
+        .await;
+        let scores = match computed {
+            Ok((scores, cached)) => {
+                if cached && verbose {
+                    eprintln!("Inputs unchanged; reusing cached metric scores");
+                }
+                scores
+            }
+            Err(err) => {
+                return render_error(
+                    DpcError::Config(format!("Failed to compute metrics: {}", err)),
+                    format,
+                    output.clone(),
+                )
+            }
+        };
+        if verbose && !scores.skipped.is_empty() {
+            eprintln!("Budget exhausted; skipped metrics: {:?}", scores.skipped);
+        }
+        let candidate_similarity = calculate_combined_score(&scores.scores, &score_weights);
+        if ref_candidates.len() > 1 && verbose {
+            eprintln!(
+                "Reference candidate {} scored {:.3}",
+                ref_candidates[idx].value, candidate_similarity
+            );
+        }
+        candidate_scores.push(candidate_similarity);
+
+        let is_better = best
+            .as_ref()
+            .map(|best| candidate_similarity > best.similarity)
+            .unwrap_or(true);
+        if is_better {
+            best = Some(CandidateRun {
+                idx,
+                ref_view,
+                impl_view,
+                mismatch,
+                scores,
+                similarity: candidate_similarity,
+            });
+        }
     }
-    let pixel_metric = PixelSimilarity {
-        alignment: pixel_alignment,
-        ..PixelSimilarity::default()
+    let Some(CandidateRun {
+        idx: selected_ref,
+        ref_view,
+        impl_view: first_impl_view,
+        mismatch: dimension_mismatch,
+        scores: budgeted,
+        ..
+    }) = best
+    else {
+        return render_error(
+            DpcError::Config("No reference resource provided".to_string()),
+            format,
+            output.clone(),
+        );
     };
-    let all_metrics: Vec<Box<dyn Metric>> = vec![
-        Box::new(pixel_metric),
-        Box::new(LayoutSimilarity::default()),
-        Box::new(TypographySimilarity::default()),
-        Box::new(ColorPaletteMetric::default()),
-        Box::new(ContentSimilarity::default()),
-    ];
-    let mut metrics_scores =
-        match run_metrics(&all_metrics, &effective_metrics, &ref_view, &impl_view) {
-            Ok(scores) => scores,
+    let reference_candidates = (ref_candidates.len() > 1).then(|| {
+        ref_candidates
+            .iter()
+            .zip(&candidate_scores)
+            .enumerate()
+            .map(|(idx, (res, similarity))| ReferenceCandidate {
+                resource: ResourceDescriptor {
+                    kind: res.kind,
+                    value: res.display_value(),
+                },
+                similarity: *similarity,
+                selected: idx == selected_ref,
+            })
+            .collect::<Vec<_>>()
+    });
+    let ref_res = ref_candidates.swap_remove(selected_ref);
+    let BudgetedScores {
+        scores: primary_scores,
+        skipped,
+    } = budgeted;
+
+    // Score the remaining captures against the chosen reference, limited to the
+    // metrics the first capture finished so every sample covers the same ones.
+    let finished: Vec<MetricKind> = MetricKind::all()
+        .into_iter()
+        .filter(|metric| metric_score(&primary_scores, *metric).is_some())
+        .collect();
+    let mut sample_scores = vec![(0, primary_scores)];
+    let mut sample_views = vec![first_impl_view];
+    for (sample, impl_view) in impl_views.iter().enumerate().skip(1) {
+        if finished.is_empty() {
+            break;
+        }
+        if verbose {
+            eprintln!(
+                "Scoring implementation sample {}/{}",
+                sample + 1,
+                impl_views.len()
+            );
+        }
+        let (sample_ref, impl_view) = match reconcile_dimensions(
+            &ref_view,
+            impl_view,
+            dimension_policy,
+            &artifacts_dir,
+            &fitted_prefix(&reference_prefix(selected_ref), &sample_prefix(sample)),
+            &fitted_prefix(&sample_prefix(sample), &reference_prefix(selected_ref)),
+        ) {
+            Ok((sample_ref, impl_view, _)) => (sample_ref, impl_view),
+            Err(err) => return render_error(err, format, output.clone()),
+        };
+        let computed = score_views(
+            cache.as_ref(),
+            &all_metrics,
+            &finished,
+            &metrics_fingerprint,
+            budget,
+            &sample_ref,
+            &impl_view,
+        )
+        .await;
+        sample_views.push(impl_view);
+        match computed {
+            Ok((scores, _)) if scores.skipped.is_empty() => {
+                sample_scores.push((sample, scores.scores))
+            }
+            Ok(_) => {
+                if verbose {
+                    eprintln!("Budget exhausted; sample {} left out", sample + 1);
+                }
+            }
             Err(err) => {
                 return render_error(
                     DpcError::Config(format!("Failed to compute metrics: {}", err)),
@@ -244,7 +851,29 @@ pub async fn run_compare(
                     output.clone(),
                 )
             }
-        };
+        }
+    }
+    let mut sampling = if sample_scores.len() > 1 {
+        let scores: Vec<MetricScores> = sample_scores.iter().map(|(_, s)| s.clone()).collect();
+        SampleStats::from_scores(&scores, &score_weights)
+    } else {
+        None
+    };
+    // Report the capture with the median similarity.
+    let (reported, mut metrics_scores) =
+        sample_scores.swap_remove(sampling.as_ref().map_or(0, |s| s.reported_sample));
+    if let Some(stats) = sampling.as_mut() {
+        stats.reported_sample = reported;
+    }
+    let impl_view_raw = &impl_views_raw[reported];
+    let impl_view = &sample_views[reported];
+    let skipped_metrics: Vec<SkippedMetric> = skipped
+        .into_iter()
+        .map(|metric| SkippedMetric {
+            metric,
+            reason: "budget".to_string(),
+        })
+        .collect();
 
     // Run semantic analysis if enabled and we have pixel diff regions
     if semantic_analysis {
@@ -259,15 +888,14 @@ pub async fn run_compare(
 
                 if let Some(analyzer) = SemanticAnalyzer::from_config(&config.semantic) {
                     // Use image-aware clustering to separate different UI components
-                    match analyzer
-                        .analyze_diff_regions(
-                            &ref_view.screenshot_path,
-                            &impl_view.screenshot_path,
-                            &pixel_metric.diff_regions,
-                            context.as_deref(),
-                        )
-                        .await
-                    {
+                    let analysis = analyzer.analyze_diff_regions(
+                        &ref_view.screenshot_path,
+                        &impl_view.screenshot_path,
+                        &pixel_metric.diff_regions,
+                        context.as_deref(),
+                    );
+                    let analysis = async { analysis.await.map_err(|err| err.to_string()) };
+                    match within_budget(budget, "semantic analysis", analysis).await {
                         Ok(semantic_diffs) => {
                             if verbose {
                                 eprintln!(
@@ -341,25 +969,205 @@ pub async fn run_compare(
         }
     }
 
+    let suppressed = accepted.suppress(&mut metrics_scores);
+    if suppressed > 0 && verbose {
+        eprintln!("Suppressed {suppressed} accepted finding(s)");
+    }
+
+    assign_fingerprints(&mut metrics_scores);
+    if let (true, Some(path)) = (update_ignore_file, ignore_file.as_deref()) {
+        let added = suppression_file.extend_from(&metrics_scores, "baseline");
+        if let Err(err) = suppression_file.save(path) {
+            return render_error(err, format, output.clone());
+        }
+        if verbose {
+            eprintln!("Added {added} fingerprint(s) to {}", path.display());
+        }
+    }
+    let suppressed = suppression_file.apply(&mut metrics_scores);
+
+    apply_coordinate_space(
+        &mut metrics_scores,
+        coordinate_space_from_cli(coordinate_space),
+        &CoordinateFrame::for_screenshot(ref_view.width, ref_view.height, &viewport),
+    );
+
     // Calculate combined score
     let similarity = calculate_combined_score(&metrics_scores, &score_weights);
 
-    // Determine pass/fail
-    let passed = similarity >= threshold as f32;
+    // Determine pass/fail; a run whose every finding is suppressed passes even
+    // below the threshold, so only new findings fail the build.
+    let only_suppressed = !suppressed.is_empty() && fingerprints(&metrics_scores).is_empty();
+    let passed = similarity >= threshold as f32 || only_suppressed;
+
+    if verbose && !sections.is_empty() {
+        eprintln!("Scoring {} section(s)", sections.len());
+    }
+    let section_scores = match score_sections(
+        &sections,
+        &ref_view,
+        impl_view,
+        &all_metrics,
+        &finished,
+        budget,
+        &artifacts_dir,
+        &score_weights,
+        &severity,
+        threshold as f32,
+    )
+    .await
+    {
+        Ok((scores, missing)) => {
+            if !missing.is_empty() {
+                warnings.push(format!(
+                    "sections not found on either page and left out: {}",
+                    missing.join(", ")
+                ));
+            }
+            scores
+        }
+        Err(err) => return render_error(err, format, output.clone()),
+    };
 
     // Generate summary
-    let summary = generate_summary(&metrics_scores, similarity, threshold as f32);
+    let mut summary = generate_summary(&metrics_scores, similarity, threshold as f32, &severity);
+    if passed && similarity < threshold as f32 {
+        summary.top_issues[0] = format!(
+            "Design parity check passed: all {} findings are suppressed ({:.1}% similarity, threshold: {:.1}%)",
+            suppressed.len(),
+            similarity * 100.0,
+            threshold * 100.0
+        );
+    }
+
+    let mut figma_comments = Vec::new();
+    if post_figma_comments {
+        if verbose {
+            eprintln!("Posting Figma comments\u{2026}");
+        }
+        match comment_on_figma(
+            &ref_res,
+            &ref_view,
+            impl_view,
+            &metrics_scores,
+            &severity,
+            &config.figma,
+        )
+        .await
+        {
+            Ok(posted) => figma_comments = posted,
+            Err(err) => warnings.push(format!("could not post Figma comments: {err}")),
+        }
+    }
+
+    let mut variable_drift = None;
+    if figma_variables {
+        let properties = impl_view
+            .dom
+            .as_ref()
+            .and_then(|dom| dom.custom_properties.as_ref());
+        match properties {
+            Some(properties) => {
+                if verbose {
+                    eprintln!("Comparing Figma variables\u{2026}");
+                }
+                match compare_figma_variables(&ref_res, properties, &config.figma).await {
+                    Ok(drift) => variable_drift = Some(drift),
+                    Err(err) => warnings.push(format!("could not fetch Figma variables: {err}")),
+                }
+            }
+            None => warnings.push(
+                "--figma-variables: the implementation has no CSS custom properties to compare"
+                    .to_string(),
+            ),
+        }
+    }
+
+    if summarize {
+        match ApiSummarizer::from_config(&config.summarizer) {
+            Some(summarizer) => {
+                if verbose {
+                    eprintln!("Summarizing findings\u{2026}");
+                }
+                let mut request = SummaryRequest::new(
+                    &metrics_scores,
+                    similarity,
+                    threshold as f32,
+                    passed,
+                    &severity,
+                    summarizer.max_findings,
+                );
+                request.warnings = warnings.clone();
+                request.context = context.clone();
+                let narrative = async {
+                    summarizer
+                        .summarize(&request)
+                        .await
+                        .map_err(|err| err.to_string())
+                };
+                match within_budget(budget, "summary", narrative).await {
+                    Ok(narrative) => summary.narrative = Some(narrative),
+                    Err(err) => {
+                        warnings.push(format!("could not summarize the findings: {err}"))
+                    }
+                }
+            }
+            None => warnings.push(
+                "--summarize needs DPC_SUMMARY_API_KEY, OPENAI_API_KEY or [summarizer] api_key; kept the template summary"
+                    .to_string(),
+            ),
+        }
+    }
 
-    let artifacts = match persist_compare_artifacts(
+    let mut artifacts = match persist_compare_artifacts(
         &artifacts_dir,
         &ref_view,
-        &impl_view,
-        should_keep_artifacts,
+        impl_view,
+        should_keep_artifacts || artifact_store.is_some(),
         pixel_alignment,
+        heatmap_palette_from_cli(heatmap_palette),
+        config.artifacts.dom_compression,
     ) {
         Ok(paths) => Some(paths),
         Err(err) => return render_error(err, format, output.clone()),
     };
+    if let Some(paths) = artifacts.as_mut() {
+        let manifest = RunManifest::new(
+            "compare",
+            raw_args,
+            config_path.as_deref(),
+            &resolved,
+            &metrics_scores,
+            ManifestInputs {
+                reference: ManifestInput::new(&ref_res, &ref_views_raw[selected_ref]),
+                implementation: ManifestInput::new(&impl_res, impl_view_raw),
+            },
+        );
+        if let Err(err) = persist_run_manifest(paths, &manifest) {
+            return render_error(err, format, output.clone());
+        }
+    }
+    if let Some(paths) = artifacts.as_mut().filter(|paths| paths.kept) {
+        if let Err(err) = encode_compare_artifacts(paths, &config.artifacts) {
+            return render_error(err, format, output.clone());
+        }
+    }
+    if let Some(paths) = artifacts.as_mut() {
+        if let Err(err) = write_overlay(paths, &ref_view, impl_view, &metrics_scores, &severity) {
+            return render_error(err, format, output.clone());
+        }
+        if let Err(err) = write_artifact_index(paths, &metrics_scores, &severity) {
+            return render_error(err, format, output.clone());
+        }
+    }
+    if let (Some(store), Some(paths)) = (&artifact_store, artifacts.as_mut()) {
+        if verbose {
+            eprintln!("Uploading artifacts\u{2026}");
+        }
+        if let Err(err) = upload_compare_artifacts(store.as_ref(), paths).await {
+            return render_error(err, format, output.clone());
+        }
+    }
 
     if let Some(paths) = &artifacts {
         let should_log = matches!(format, OutputFormat::Json | OutputFormat::Pretty)
@@ -385,6 +1193,12 @@ pub async fn run_compare(
                 if let Some(path) = &paths.impl_dom_snapshot {
                     eprintln!("  impl DOM: {}", path.display());
                 }
+                if let Some(path) = &paths.ref_network_har {
+                    eprintln!("  ref HAR: {}", path.display());
+                }
+                if let Some(path) = &paths.impl_network_har {
+                    eprintln!("  impl HAR: {}", path.display());
+                }
                 if let Some(path) = &paths.ref_figma_snapshot {
                     eprintln!("  ref figma tree: {}", path.display());
                 }
@@ -425,25 +1239,77 @@ pub async fn run_compare(
         }
     }
 
+    let page_diagnostics = compare_page_diagnostics(&ref_views_raw[selected_ref], impl_view_raw);
+    if let Some(report) = &page_diagnostics {
+        warnings.extend(page_diagnostics_warnings(report));
+    }
+    if let Some(stats) = sampling.as_ref().filter(|stats| stats.unstable) {
+        let mut warning = format!(
+            "scores varied across {} implementation captures (similarity {:.3}\u{2013}{:.3}, std dev {:.3}); animations or async content make the result unreliable",
+            stats.samples, stats.similarity.min, stats.similarity.max, stats.similarity.std_dev
+        );
+        if stats.similarity.straddles(threshold as f32) {
+            warning.push_str("; pass/fail may flip on a recapture");
+        }
+        warnings.push(warning);
+    }
+    if let (Some(budget), false) = (budget, skipped_metrics.is_empty()) {
+        let names: Vec<String> = skipped_metrics
+            .iter()
+            .map(|s| s.metric.to_string())
+            .collect();
+        warnings.push(format!(
+            "--budget {} ran out; skipped metrics: {} (similarity covers only the metrics that finished)",
+            budget.label(),
+            names.join(", ")
+        ));
+    }
+
+    let rendered_with = [&ref_views_raw[selected_ref], impl_view_raw]
+        .into_iter()
+        .find_map(|view| view.dom.as_ref().and_then(|dom| dom.browser));
     let body = DpcOutput::Compare(CompareOutput {
         version: DPC_OUTPUT_VERSION.to_string(),
         ref_resource: ResourceDescriptor {
             kind: ref_res.kind,
-            value: ref_res.value,
+            value: ref_res.display_value(),
         },
         impl_resource: ResourceDescriptor {
             kind: impl_res.kind,
-            value: impl_res.value,
+            value: impl_res.display_value(),
         },
         viewport,
+        browser: rendered_with,
+        emulation: rendered_with
+            .is_some()
+            .then_some(emulation)
+            .filter(|emulation| !emulation.is_empty()),
         similarity,
         threshold: threshold as f32,
         passed,
         metrics: metrics_scores,
         summary: Some(summary),
         artifacts,
+        reference_candidates,
+        warnings,
+        suppressed,
+        page_diagnostics,
+        skipped_metrics,
+        sampling,
+        dimension_mismatch,
+        sections: section_scores,
+        degradation,
+        figma_comments,
+        variable_drift,
     });
 
+    // `dpc review` reads the result back from kept artifacts.
+    if should_keep_artifacts {
+        if let Err(err) = write_json_pretty(&artifacts_dir.join("result.json"), &body) {
+            return render_error(err, format, output.clone());
+        }
+    }
+
     if let Err(err) = write_output(&body, format, output.clone()) {
         return render_error(DpcError::Config(err.to_string()), format, output);
     }
@@ -456,6 +1322,323 @@ pub async fn run_compare(
     exit_code_for_compare(passed)
 }
 
+/// `--ref` and `--impl`, where a side left out of a focused re-run is the
+/// capture the previous result saved in its artifacts directory.
+fn focus_inputs(
+    reference: Option<String>,
+    implementation: Option<String>,
+    prior: Option<&CompareOutput>,
+) -> Result<(String, String), DpcError> {
+    let saved = |side: &str| {
+        prior
+            .and_then(|result| result.artifacts.as_ref())
+            .filter(|artifacts| artifacts.kept)
+            .map(|artifacts| artifacts.directory.join(format!("{side}_view")))
+            .filter(|bundle| bundle.join(VIEW_FILE).is_file())
+            .map(|bundle| format!("{VIEW_SCHEME}{}", bundle.display()))
+            .ok_or_else(|| {
+                DpcError::Config(format!(
+                    "--{side} is needed: the previous result kept no {side} capture (run it with --keep-artifacts)"
+                ))
+            })
+    };
+    let reference = match reference {
+        Some(value) => value,
+        None => saved("ref")?,
+    };
+    let implementation = match implementation {
+        Some(value) => value,
+        None => saved("impl")?,
+    };
+    Ok((reference, implementation))
+}
+
+/// Key of the Figma file a reference input comes from.
+fn figma_file_key(resource: &ParsedResource) -> Option<&str> {
+    resource
+        .figma_info
+        .as_ref()
+        .filter(|_| resource.kind == ResourceKind::Figma)
+        .map(|info| info.file_key.as_str())
+}
+
+/// Store the published styles of each Figma reference's file on its
+/// snapshot, fetching every file once.
+async fn attach_figma_styles(
+    candidates: &[ParsedResource],
+    views: &mut [NormalizedView],
+    figma: &FigmaConfig,
+) -> Result<(), DpcError> {
+    let client = FigmaApiClient::from_config(figma).await?.ok_or_else(|| {
+        DpcError::Config(
+            "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN, or run `dpc figma login`"
+                .to_string(),
+        )
+    })?;
+    let mut fetched: HashMap<&str, Vec<FigmaStyle>> = HashMap::new();
+    for (candidate, view) in candidates.iter().zip(views) {
+        let (Some(file_key), Some(tree)) = (figma_file_key(candidate), view.figma_tree.as_mut())
+        else {
+            continue;
+        };
+        if !fetched.contains_key(file_key) {
+            let styles = fetch_figma_styles(&client, file_key).await?;
+            fetched.insert(file_key, styles);
+        }
+        tree.styles = fetched[file_key].clone();
+    }
+    Ok(())
+}
+
+/// Compare the implementation's custom properties with the variables of the
+/// Figma reference file.
+async fn compare_figma_variables(
+    ref_res: &ParsedResource,
+    properties: &CustomProperties,
+    figma: &FigmaConfig,
+) -> Result<VariableDrift, DpcError> {
+    let file_key = figma_file_key(ref_res).ok_or_else(|| {
+        DpcError::Config("the selected reference is not a Figma file".to_string())
+    })?;
+    let client = FigmaApiClient::from_config(figma).await?.ok_or_else(|| {
+        DpcError::Config(
+            "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN, or run `dpc figma login`"
+                .to_string(),
+        )
+    })?;
+    let variables = fetch_figma_variables(&client, file_key).await?;
+    Ok(compare_variables(&variables, properties))
+}
+
+/// Comment the major findings on their nodes in the Figma reference file.
+async fn comment_on_figma(
+    ref_res: &ParsedResource,
+    ref_view: &NormalizedView,
+    impl_view: &NormalizedView,
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+    figma: &FigmaConfig,
+) -> Result<Vec<PostedFigmaComment>, DpcError> {
+    let file_key = figma_file_key(ref_res).ok_or_else(|| {
+        DpcError::Config("the selected reference is not a Figma file".to_string())
+    })?;
+    let client = FigmaApiClient::from_config(figma).await?.ok_or_else(|| {
+        DpcError::Config(
+            "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN, or run `dpc figma login --comments`"
+                .to_string(),
+        )
+    })?;
+    let rows = TypographySimilarity::default()
+        .report(ref_view, impl_view)
+        .unwrap_or_default();
+    let drafts = figma_comment_drafts(scores, calibration, &rows);
+    post_figma_comments(&client, file_key, &drafts).await
+}
+
+/// Overall deadline set by `--budget`.
+#[derive(Clone, Copy)]
+struct Budget {
+    total: Duration,
+    deadline: Instant,
+}
+
+impl Budget {
+    fn start(total: Duration) -> Self {
+        Self {
+            total,
+            deadline: Instant::now() + total,
+        }
+    }
+
+    /// `timeout` (seconds) capped to the time left, so a browser process gives
+    /// up on its own before the budget does.
+    fn cap_secs(&self, timeout: u64) -> u64 {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        timeout.min(left.as_secs().max(1))
+    }
+
+    fn label(&self) -> String {
+        humantime::format_duration(self.total).to_string()
+    }
+}
+
+/// Score `implementation` against `reference` through the result cache, within
+/// the budget when one is set.
+/// Score each section on its own crop of both screenshots with the metrics
+/// the whole page finished; also returns the names of sections found on
+/// neither side.
+#[allow(clippy::too_many_arguments)]
+async fn score_sections(
+    sections: &[Section],
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    metrics: &[SharedMetric],
+    selected: &[MetricKind],
+    budget: Option<Budget>,
+    artifacts_dir: &Path,
+    weights: &ScoreWeights,
+    severity: &SeverityCalibration,
+    threshold: f32,
+) -> dpc_lib::Result<(Vec<SectionScore>, Vec<String>)> {
+    if sections.is_empty() || selected.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let ref_image = image::open(&reference.screenshot_path)?;
+    let impl_image = image::open(&implementation.screenshot_path)?;
+    let (mut scored, mut missing) = (Vec::new(), Vec::new());
+    for (idx, section) in sections.iter().enumerate() {
+        let Some((ref_rect, impl_rect)) = section.bounds(
+            reference,
+            ref_image.dimensions(),
+            implementation,
+            impl_image.dimensions(),
+        ) else {
+            missing.push(section.name.clone());
+            continue;
+        };
+        let ref_section = section_view(
+            reference,
+            &ref_image,
+            ref_rect,
+            &artifacts_dir.join(format!("section_{idx}_ref.png")),
+        )?;
+        let impl_section = section_view(
+            implementation,
+            &impl_image,
+            impl_rect,
+            &artifacts_dir.join(format!("section_{idx}_impl.png")),
+        )?;
+        let (scores, _) = score_views(
+            None,
+            metrics,
+            selected,
+            "",
+            budget,
+            &ref_section,
+            &impl_section,
+        )
+        .await?;
+        let similarity = calculate_combined_score(&scores.scores, weights);
+        let (x, y, width, height) = ref_rect;
+        scored.push(SectionScore {
+            name: section.name.clone(),
+            bounds: RegionBounds {
+                x: x as f32,
+                y: y as f32,
+                width: width as f32,
+                height: height as f32,
+            },
+            similarity,
+            passed: similarity >= threshold,
+            top_issues: generate_top_issues_with(&scores.scores, 3, severity),
+            metrics: scores.scores,
+        });
+    }
+    Ok((scored, missing))
+}
+
+async fn score_views(
+    cache: Option<&ResultCache>,
+    metrics: &[SharedMetric],
+    selected: &[MetricKind],
+    fingerprint: &str,
+    budget: Option<Budget>,
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+) -> dpc_lib::Result<(BudgetedScores, bool)> {
+    match budget {
+        Some(budget) => {
+            cached_budgeted_scores(
+                cache,
+                reference,
+                implementation,
+                selected,
+                fingerprint,
+                || {
+                    run_metrics_until(
+                        metrics,
+                        selected,
+                        reference,
+                        implementation,
+                        budget.deadline,
+                    )
+                },
+            )
+            .await
+        }
+        None => cached_scores(
+            cache,
+            reference,
+            implementation,
+            selected,
+            fingerprint,
+            || {
+                let metrics: Vec<Box<dyn Metric>> = metrics
+                    .iter()
+                    .map(|metric| Box::new(Arc::clone(metric)) as Box<dyn Metric>)
+                    .collect();
+                run_metrics(&metrics, selected, reference, implementation)
+            },
+        )
+        .map(|(scores, cached)| {
+            let skipped = Vec::new();
+            (BudgetedScores { scores, skipped }, cached)
+        }),
+    }
+}
+
+/// Await `work`, failing with a budget error once the deadline passes.
+async fn within_budget<T, E: From<String>>(
+    budget: Option<Budget>,
+    what: &str,
+    work: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let Some(budget) = budget else {
+        return work.await;
+    };
+    tokio::time::timeout_at(budget.deadline, work)
+        .await
+        .unwrap_or_else(|_| {
+            Err(E::from(format!(
+                "{what} did not finish within --budget {}",
+                budget.label()
+            )))
+        })
+}
+
+/// Artifact/mock prefix for implementation capture `sample` (`impl`, `impl_sample2`, …).
+fn sample_prefix(sample: usize) -> String {
+    if sample == 0 {
+        "impl".to_string()
+    } else {
+        format!("impl_sample{}", sample + 1)
+    }
+}
+
+/// The views and scores of one reference candidate.
+struct CandidateRun {
+    idx: usize,
+    ref_view: NormalizedView,
+    impl_view: NormalizedView,
+    mismatch: Option<DimensionMismatch>,
+    scores: BudgetedScores,
+    similarity: f32,
+}
+
+/// Artifact prefix of a screenshot fitted to the one it is compared with.
+fn fitted_prefix(prefix: &str, other: &str) -> String {
+    format!("{prefix}_vs_{other}")
+}
+
+/// Artifact/mock prefix for the reference candidate at `idx` (`ref`, `ref2`, `ref3`, …).
+fn reference_prefix(idx: usize) -> String {
+    if idx == 0 {
+        "ref".to_string()
+    } else {
+        format!("ref{}", idx + 1)
+    }
+}
+
 fn resource_kind_from_cli(rt: crate::cli::ResourceType) -> ResourceKind {
     match rt {
         crate::cli::ResourceType::Url => ResourceKind::Url,
@@ -464,7 +1647,63 @@ fn resource_kind_from_cli(rt: crate::cli::ResourceType) -> ResourceKind {
     }
 }
 
-fn parse_metric_kinds(
+fn coordinate_space_from_cli(space: crate::cli::CoordinateSpace) -> CoordinateSpace {
+    match space {
+        crate::cli::CoordinateSpace::Normalized => CoordinateSpace::Normalized,
+        crate::cli::CoordinateSpace::Pixel => CoordinateSpace::Pixel,
+        crate::cli::CoordinateSpace::Css => CoordinateSpace::Css,
+        crate::cli::CoordinateSpace::All => CoordinateSpace::All,
+    }
+}
+
+fn content_match_from_cli(mode: crate::cli::ContentMatch) -> ContentMatchMode {
+    match mode {
+        crate::cli::ContentMatch::Token => ContentMatchMode::Token,
+        crate::cli::ContentMatch::Levenshtein => ContentMatchMode::Levenshtein,
+        crate::cli::ContentMatch::Hybrid => ContentMatchMode::Hybrid,
+    }
+}
+
+fn content_formats_from_cli(formats: crate::cli::ContentFormats) -> FormatNormalization {
+    match formats {
+        crate::cli::ContentFormats::Off => FormatNormalization::Off,
+        crate::cli::ContentFormats::Canonical => FormatNormalization::Canonical,
+        crate::cli::ContentFormats::Mask => FormatNormalization::Mask,
+    }
+}
+
+fn alignment_method_from_cli(method: crate::cli::PixelAlignMethod) -> AlignmentMethod {
+    match method {
+        crate::cli::PixelAlignMethod::Search => AlignmentMethod::Search,
+        crate::cli::PixelAlignMethod::PhaseCorrelation => AlignmentMethod::PhaseCorrelation,
+    }
+}
+
+fn dimension_policy_from_cli(policy: crate::cli::DimensionMismatch) -> DimensionMismatchPolicy {
+    match policy {
+        crate::cli::DimensionMismatch::Stretch => DimensionMismatchPolicy::Stretch,
+        crate::cli::DimensionMismatch::Letterbox => DimensionMismatchPolicy::Letterbox,
+        crate::cli::DimensionMismatch::Crop => DimensionMismatchPolicy::Crop,
+        crate::cli::DimensionMismatch::Fail => DimensionMismatchPolicy::Fail,
+    }
+}
+
+fn pixel_diff_space_from_cli(space: crate::cli::PixelDiffSpace) -> PixelDiffSpace {
+    match space {
+        crate::cli::PixelDiffSpace::Luma => PixelDiffSpace::Luma,
+        crate::cli::PixelDiffSpace::Ciede2000 => PixelDiffSpace::Ciede2000,
+    }
+}
+
+fn heatmap_palette_from_cli(palette: crate::cli::HeatmapPalette) -> HeatmapPalette {
+    match palette {
+        crate::cli::HeatmapPalette::Severity => HeatmapPalette::Severity,
+        crate::cli::HeatmapPalette::Viridis => HeatmapPalette::Viridis,
+        crate::cli::HeatmapPalette::Magma => HeatmapPalette::Magma,
+    }
+}
+
+pub(super) fn parse_metric_kinds(
     kinds: Option<&[String]>,
 ) -> Result<Vec<MetricKind>, Box<dyn std::error::Error>> {
     use std::io;
@@ -0,0 +1,64 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap_complete::Shell;
+use dpc_lib::DpcError;
+
+use crate::cli::{self, OutputFormat};
+use crate::formatting::render_error;
+
+/// Run `dpc completions <shell>`.
+pub fn run_completions(shell: Shell) -> ExitCode {
+    write_completions(shell, &mut std::io::stdout());
+    ExitCode::SUCCESS
+}
+
+/// Run `dpc man [--out-dir DIR]`.
+pub fn run_man(out_dir: Option<PathBuf>) -> ExitCode {
+    let result = match out_dir {
+        Some(dir) => write_man_pages(&dir),
+        None => clap_mangen::Man::new(cli::command())
+            .render(&mut std::io::stdout())
+            .map_err(DpcError::from),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
+
+fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut command = cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+fn write_man_pages(dir: &Path) -> Result<(), DpcError> {
+    std::fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(cli::command(), dir)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions_cover_subcommands_and_flags() {
+        let mut out = Vec::new();
+        write_completions(Shell::Bash, &mut out);
+        let script = String::from_utf8(out).unwrap();
+        assert!(script.contains("self-compare"));
+        assert!(script.contains("--alt-ref"));
+    }
+
+    #[test]
+    fn man_pages_are_written_per_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        write_man_pages(dir.path()).unwrap();
+        for page in ["dpc.1", "dpc-compare.1", "dpc-figma-login.1"] {
+            assert!(dir.path().join(page).is_file(), "missing {page}");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use dpc_lib::output::DPC_OUTPUT_VERSION;
+use dpc_lib::{diff_results, CompareOutput, DiffResultsOutput, DpcError, DpcOutput};
+
+use crate::cli::OutputFormat;
+use crate::formatting::{render_error, write_output};
+
+/// Run `dpc diff-results <old> <new>`.
+pub fn run_diff_results(
+    old: PathBuf,
+    new: PathBuf,
+    format: OutputFormat,
+    output: Option<PathBuf>,
+    fail_on_regression: bool,
+) -> ExitCode {
+    let (old_result, new_result) = match (load_result(&old), load_result(&new)) {
+        (Ok(old_result), Ok(new_result)) => (old_result, new_result),
+        (Err(err), _) | (_, Err(err)) => return render_error(err, format, output),
+    };
+    let diff = diff_results(&old_result, &new_result);
+    let regressed = diff.has_regressions();
+
+    let body = DpcOutput::DiffResults(DiffResultsOutput {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        old,
+        new,
+        diff,
+    });
+    if let Err(err) = write_output(&body, format, output.clone()) {
+        return render_error(DpcError::Config(err.to_string()), format, output);
+    }
+    if fail_on_regression && regressed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+pub(crate) fn load_result(path: &Path) -> Result<CompareOutput, DpcError> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|err| DpcError::Config(format!("Failed to read {}: {}", path.display(), err)))?;
+    let parsed: DpcOutput = serde_json::from_str(&data).map_err(|err| {
+        DpcError::Config(format!("{} is not a dpc result: {}", path.display(), err))
+    })?;
+    match parsed {
+        DpcOutput::Compare(result) => Ok(result),
+        _ => Err(DpcError::Config(format!(
+            "{} does not hold a compare result",
+            path.display()
+        ))),
+    }
+}
@@ -0,0 +1,288 @@
+use std::process::ExitCode;
+
+use dpc_lib::browser::{
+    chromium_executable, ensure_playwright_available, managed_install, node_version,
+};
+use dpc_lib::{FigmaAuth, FigmaClient, FigmaError};
+use serde::Serialize;
+
+use crate::cli::OutputFormat;
+
+/// Oldest Node.js major version Playwright supports.
+const MIN_NODE_MAJOR: u32 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DoctorCheck {
+    name: &'static str,
+    status: CheckStatus,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remediation: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+
+    fn problem(
+        name: &'static str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DoctorReport {
+    passed: bool,
+    checks: Vec<DoctorCheck>,
+}
+
+/// Run the doctor command: check the environment URL and Figma inputs depend on.
+///
+/// Exits 1 when any check fails; warnings (e.g. no Figma token) do not fail.
+pub async fn run_doctor(format: OutputFormat) -> ExitCode {
+    let mut checks = Vec::new();
+
+    let node = check_node().await;
+    let node_ok = node.status == CheckStatus::Ok;
+    checks.push(node);
+    if node_ok {
+        let playwright = check_playwright().await;
+        let playwright_ok = playwright.status == CheckStatus::Ok;
+        checks.push(playwright);
+        if playwright_ok {
+            checks.push(check_browsers().await);
+        }
+    }
+    checks.push(check_figma_token().await);
+    checks.push(check_temp_dir());
+
+    let report = DoctorReport {
+        passed: checks.iter().all(|check| check.status != CheckStatus::Fail),
+        checks,
+    };
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string(&report).unwrap_or_else(|_| "{}".into())
+        ),
+        OutputFormat::Pretty => print!("{}", format_report(&report)),
+    }
+
+    if report.passed {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+async fn check_node() -> DoctorCheck {
+    match node_version("node").await {
+        Ok(version) => match node_major(&version) {
+            Some(major) if major >= MIN_NODE_MAJOR => DoctorCheck::ok("node", version),
+            _ => DoctorCheck::problem(
+                "node",
+                CheckStatus::Fail,
+                format!("Node.js {version} is too old"),
+                format!("Install Node.js {MIN_NODE_MAJOR} or newer (https://nodejs.org)."),
+            ),
+        },
+        Err(err) => DoctorCheck::problem(
+            "node",
+            CheckStatus::Fail,
+            err.to_payload().message,
+            "Install Node.js (https://nodejs.org) and make sure `node` is on PATH; URL inputs render through it.",
+        ),
+    }
+}
+
+async fn check_playwright() -> DoctorCheck {
+    match ensure_playwright_available("node").await {
+        Ok(()) => match managed_install() {
+            Some(install) => DoctorCheck::ok(
+                "playwright",
+                format!(
+                    "playwright npm package found (managed install at {})",
+                    install.dir.display()
+                ),
+            ),
+            None => DoctorCheck::ok("playwright", "playwright npm package found"),
+        },
+        Err(err) => DoctorCheck::problem(
+            "playwright",
+            CheckStatus::Fail,
+            err.to_payload().message,
+            "Run `dpc install-browser` (or `npm install playwright` in the working directory).",
+        ),
+    }
+}
+
+async fn check_browsers() -> DoctorCheck {
+    match chromium_executable("node").await {
+        Ok(path) => DoctorCheck::ok("browsers", format!("Chromium at {}", path.display())),
+        Err(err) => DoctorCheck::problem(
+            "browsers",
+            CheckStatus::Fail,
+            err.to_payload().message,
+            "Run `dpc install-browser` (or `npx playwright install chromium`; add `--with-deps` on fresh Linux machines).",
+        ),
+    }
+}
+
+async fn check_figma_token() -> DoctorCheck {
+    let auth = match FigmaAuth::resolve().await {
+        Ok(Some(auth)) => auth,
+        Ok(None) => {
+            return DoctorCheck::problem(
+                "figma",
+                CheckStatus::Warn,
+                "no Figma token configured",
+                "Set FIGMA_TOKEN to a personal access token or run `dpc figma login` to compare Figma designs.",
+            )
+        }
+        Err(err) => {
+            return DoctorCheck::problem(
+                "figma",
+                CheckStatus::Fail,
+                err.to_payload().message,
+                "Run `dpc figma login` again or set FIGMA_TOKEN.",
+            )
+        }
+    };
+    if let Err(err) = auth.ensure_fresh().await {
+        return DoctorCheck::problem(
+            "figma",
+            CheckStatus::Fail,
+            err.to_payload().message,
+            "The stored session could not be refreshed; run `dpc figma login` again.",
+        );
+    }
+    let client = match FigmaClient::from_auth(auth) {
+        Ok(client) => client,
+        Err(err) => {
+            return DoctorCheck::problem(
+                "figma",
+                CheckStatus::Fail,
+                err.to_string(),
+                "Set FIGMA_TOKEN to a non-empty personal access token.",
+            )
+        }
+    };
+    match client.get_me().await {
+        Ok(user) => DoctorCheck::ok("figma", format!("token valid for {}", user.handle)),
+        Err(FigmaError::Api { status, .. }) if status == 401 || status == 403 => {
+            DoctorCheck::problem(
+                "figma",
+                CheckStatus::Fail,
+                format!("Figma rejected the token (HTTP {status})"),
+                "The token is invalid or expired; create a new personal access token in Figma settings.",
+            )
+        }
+        Err(err) => DoctorCheck::problem(
+            "figma",
+            CheckStatus::Warn,
+            err.to_string(),
+            "Could not reach api.figma.com; check connectivity/proxy and retry.",
+        ),
+    }
+}
+
+fn check_temp_dir() -> DoctorCheck {
+    let dir = std::env::temp_dir();
+    let probe = dir.join(format!("dpc-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"ok").and_then(|()| std::fs::remove_file(&probe)) {
+        Ok(()) => DoctorCheck::ok("temp_dir", format!("{} is writable", dir.display())),
+        Err(err) => DoctorCheck::problem(
+            "temp_dir",
+            CheckStatus::Fail,
+            format!("cannot write to {}: {err}", dir.display()),
+            "Point TMPDIR at a writable directory, or pass --artifacts-dir to compare.",
+        ),
+    }
+}
+
+/// Major version from `node --version` output such as `v20.11.1`.
+fn node_major(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn format_report(report: &DoctorReport) -> String {
+    let mut out = String::new();
+    for check in &report.checks {
+        let label = match check.status {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        };
+        out.push_str(&format!(
+            "[{label:>4}] {:<10} {}\n",
+            check.name, check.detail
+        ));
+        if let Some(remediation) = &check.remediation {
+            out.push_str(&format!("{:18}\u{2192} {remediation}\n", ""));
+        }
+    }
+    out.push_str(if report.passed {
+        "All required checks passed.\n"
+    } else {
+        "Some checks failed; fix the items marked FAIL above.\n"
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_major_parses_version_output() {
+        assert_eq!(node_major("v20.11.1\n"), Some(20));
+        assert_eq!(node_major("18.0.0"), Some(18));
+        assert_eq!(node_major("nightly"), None);
+    }
+
+    #[test]
+    fn warnings_do_not_fail_the_report() {
+        let report = DoctorReport {
+            passed: true,
+            checks: vec![
+                DoctorCheck::ok("node", "v20.11.1"),
+                DoctorCheck::problem("figma", CheckStatus::Warn, "no token", "Set FIGMA_TOKEN."),
+            ],
+        };
+        let text = format_report(&report);
+        assert!(text.contains("[  ok] node       v20.11.1"));
+        assert!(text.contains("\u{2192} Set FIGMA_TOKEN."));
+        assert!(text.ends_with("All required checks passed.\n"));
+    }
+}
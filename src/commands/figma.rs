@@ -0,0 +1,122 @@
+use std::process::{Command, ExitCode, Stdio};
+
+use dpc_lib::figma_client::COMMENTS_WRITE_SCOPE;
+use dpc_lib::figma_oauth::{
+    clear_credentials, exchange_code, new_state, save_credentials, wait_for_callback,
+    FigmaOAuthConfig,
+};
+use dpc_lib::{parse_resource, DpcError, ResourceKind};
+use tokio::net::TcpListener;
+
+use crate::cli::{FigmaCommands, OutputFormat};
+use crate::formatting::render_error;
+
+/// Run a `dpc figma` subcommand.
+pub async fn run_figma(command: FigmaCommands) -> ExitCode {
+    let result = match command {
+        FigmaCommands::Login {
+            port,
+            no_browser,
+            comments,
+        } => login(port, no_browser, comments).await,
+        FigmaCommands::Logout => logout().await,
+        FigmaCommands::Resolve { value } => resolve(&value),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
+
+async fn login(port: u16, no_browser: bool, comments: bool) -> Result<(), DpcError> {
+    let mut config = FigmaOAuthConfig::from_env().ok_or_else(|| {
+        DpcError::Config(
+            "Figma OAuth app is not configured; set FIGMA_CLIENT_ID and FIGMA_CLIENT_SECRET"
+                .to_string(),
+        )
+    })?;
+    config.redirect_port = port;
+    if comments {
+        config = config.with_scope(COMMENTS_WRITE_SCOPE);
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    let state = new_state()?;
+    let url = config.authorization_url(&state)?;
+
+    eprintln!("Open this URL to authorize dpc with Figma:\n\n  {url}\n");
+    if !no_browser && !open_browser(url.as_str()) {
+        eprintln!("Could not open a browser automatically; open the URL manually.");
+    }
+    eprintln!(
+        "Waiting for the redirect on {}\u{2026}",
+        config.redirect_uri()
+    );
+
+    let code = wait_for_callback(listener, &state).await?;
+    let credentials = exchange_code(&dpc_lib::network::http_client(), &config, &code).await?;
+    tokio::task::spawn_blocking(move || save_credentials(&credentials))
+        .await
+        .map_err(|e| DpcError::Unknown(e.to_string()))??;
+
+    println!("Logged in to Figma; credentials stored in the OS keyring.");
+    Ok(())
+}
+
+async fn logout() -> Result<(), DpcError> {
+    let removed = tokio::task::spawn_blocking(clear_credentials)
+        .await
+        .map_err(|e| DpcError::Unknown(e.to_string()))??;
+    if removed {
+        println!("Removed Figma credentials from the OS keyring.");
+    } else {
+        println!("No stored Figma credentials found.");
+    }
+    Ok(())
+}
+
+/// Print what dpc parses from a Figma reference, for debugging links that
+/// resolve to the wrong file or frame.
+fn resolve(value: &str) -> Result<(), DpcError> {
+    let resource = parse_resource(value, None).map_err(|e| DpcError::Config(e.to_string()))?;
+    let info = resource
+        .figma_info
+        .filter(|_| resource.kind == ResourceKind::Figma)
+        .ok_or_else(|| DpcError::Config(format!("'{value}' is not a Figma reference")))?;
+
+    println!("file key:   {}", info.file_key);
+    if let Some(main) = &info.main_file_key {
+        println!("branch of:  {main}");
+    }
+    println!(
+        "node id:    {}",
+        info.node_id.as_deref().unwrap_or("(none)")
+    );
+    if let Some(frame_name) = &info.frame_name {
+        println!("frame name: {frame_name}");
+    }
+    if let Some(json_path) = &info.json_path {
+        println!("json file:  {}", json_path.display());
+    }
+    Ok(())
+}
+
+fn open_browser(url: &str) -> bool {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // `cmd /C start` would cut the URL at the first `&`.
+        let mut cmd = Command::new("rundll32");
+        cmd.arg("url.dll,FileProtocolHandler");
+        cmd
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .is_ok()
+}
@@ -0,0 +1,311 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dpc_lib::figma::{
+    document_from_json, find_frame_by_name, map_figma_error, prototype_flow, FrameCandidate,
+};
+use dpc_lib::output::DPC_OUTPUT_VERSION;
+use dpc_lib::resource::FigmaInfo;
+use dpc_lib::types::ResourceKind;
+use dpc_lib::{
+    parse_resource, BrowserEngine, DpcError, DpcOutput, Emulation, FigmaAuth, FigmaClient,
+    FlowOutput, FlowScreen, Geolocation, ParsedResource, ResourceDescriptor, Viewport,
+};
+
+use super::compare::parse_metric_kinds;
+use super::screens::ScreenScorer;
+use crate::cli::OutputFormat;
+use crate::formatting::{exit_code_for_compare, render_error, write_output};
+use crate::pipeline::resolve_artifacts_dir;
+use crate::settings::{load_config, resolve_compare_settings, CompareFlagSources};
+
+/// Run `dpc flow`: pair each screen of a Figma prototype flow with the next
+/// `--impl` and compare them one by one.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_flow(
+    raw_args: &[String],
+    config_path: Option<PathBuf>,
+    verbose: bool,
+    r#ref: String,
+    impls: Vec<String>,
+    base_url: Option<String>,
+    viewport: Viewport,
+    threshold: f64,
+    metrics: Option<Vec<String>>,
+    format: OutputFormat,
+    output: Option<PathBuf>,
+    keep_artifacts: bool,
+    artifacts_dir: Option<PathBuf>,
+    nav_timeout: u64,
+    network_idle_timeout: u64,
+    process_timeout: u64,
+    browser: BrowserEngine,
+    locale: Option<String>,
+    timezone: Option<String>,
+    geolocation: Option<Geolocation>,
+    grant_permissions: Option<String>,
+    reduced_motion: bool,
+    forced_colors: bool,
+) -> ExitCode {
+    let config = match load_config(config_path.as_deref()) {
+        Ok(cfg) => cfg,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    let resolved = resolve_compare_settings(
+        viewport,
+        threshold,
+        nav_timeout,
+        network_idle_timeout,
+        process_timeout,
+        None,
+        None,
+        None,
+        None,
+        &config,
+        &CompareFlagSources::from_args(raw_args),
+    );
+    let emulation = Emulation {
+        locale,
+        timezone,
+        geolocation,
+        reduced_motion,
+        forced_colors,
+        ..Emulation::default()
+    }
+    .grant_permissions(grant_permissions.as_deref().unwrap_or_default());
+    if let Err(err) = emulation.validate() {
+        return render_error(DpcError::Config(err), format, output.clone());
+    }
+    let viewport = resolved.viewport;
+    let threshold = resolved.threshold as f32;
+
+    let start = match parse_resource(&r#ref, None) {
+        Ok(res) => res,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+    let Some(start_info) = start
+        .figma_info
+        .clone()
+        .filter(|_| start.kind == ResourceKind::Figma)
+    else {
+        return render_error(
+            DpcError::Config(format!(
+                "--ref must be a Figma frame (URL with node-id, figma: or figma-json:), got '{}'",
+                r#ref
+            )),
+            format,
+            output.clone(),
+        );
+    };
+    let implementations = match impls
+        .iter()
+        .map(|value| parse_resource(&join_route(base_url.as_deref(), value), None))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(resources) => resources,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+    let selected_metrics = match parse_metric_kinds(metrics.as_deref()) {
+        Ok(k) => k,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+
+    let frames = match load_flow(&start_info).await {
+        Ok(frames) => frames,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    if verbose {
+        eprintln!(
+            "Flow has {} screen(s): {}",
+            frames.len(),
+            frames
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" \u{2192} ")
+        );
+    }
+    let mut warnings = Vec::new();
+    if frames.len() > implementations.len() {
+        warnings.push(format!(
+            "the flow has {} screens but only {} --impl were given; the last {} screen(s) were not compared",
+            frames.len(),
+            implementations.len(),
+            frames.len() - implementations.len()
+        ));
+    } else if implementations.len() > frames.len() {
+        warnings.push(format!(
+            "the flow has {} screens; the last {} --impl were ignored",
+            frames.len(),
+            implementations.len() - frames.len()
+        ));
+    }
+
+    let (artifacts_dir, artifacts_from_cli) = resolve_artifacts_dir(artifacts_dir.as_deref());
+    if let Err(err) = std::fs::create_dir_all(&artifacts_dir) {
+        return render_error(DpcError::Io(err), format, output.clone());
+    }
+    let should_keep_artifacts = keep_artifacts || artifacts_from_cli;
+    let scorer = ScreenScorer::new(
+        resolved,
+        config.requests.clone(),
+        browser,
+        emulation,
+        config.severity.calibration(),
+        &config.metrics,
+        selected_metrics,
+        &artifacts_dir,
+        verbose,
+    );
+
+    let mut screens = Vec::with_capacity(frames.len());
+    for (step, frame) in frames.iter().enumerate() {
+        let mut screen = FlowScreen {
+            frame_id: frame.id.clone(),
+            frame_name: frame.name.clone(),
+            page: frame.page.clone(),
+            impl_resource: None,
+            similarity: None,
+            passed: false,
+            metrics: None,
+            top_issues: Vec::new(),
+            error: None,
+        };
+        let Some(implementation) = implementations.get(step) else {
+            screens.push(screen);
+            continue;
+        };
+        screen.impl_resource = Some(ResourceDescriptor {
+            kind: implementation.kind,
+            value: implementation.display_value(),
+        });
+        if verbose {
+            eprintln!(
+                "Screen {}/{}: {} vs {}",
+                step + 1,
+                frames.len(),
+                frame.name,
+                implementation.display_value()
+            );
+        }
+        let reference = ParsedResource {
+            kind: ResourceKind::Figma,
+            value: start.value.clone(),
+            figma_info: Some(FigmaInfo {
+                node_id: Some(frame.id.clone()),
+                frame_name: None,
+                ..start_info.clone()
+            }),
+        };
+
+        match scorer.score(&reference, implementation, step + 1).await {
+            Ok(score) => {
+                screen.similarity = Some(score.similarity);
+                screen.passed = score.similarity >= threshold;
+                screen.top_issues = score.top_issues;
+                screen.metrics = Some(score.metrics);
+            }
+            Err(err) => screen.error = Some(err),
+        }
+        screens.push(screen);
+    }
+
+    let scored: Vec<f32> = screens.iter().filter_map(|s| s.similarity).collect();
+    let similarity = if scored.is_empty() {
+        0.0
+    } else {
+        scored.iter().sum::<f32>() / scored.len() as f32
+    };
+    let passed = !screens.is_empty() && screens.iter().all(|s| s.passed);
+    let body = DpcOutput::Flow(FlowOutput {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        ref_resource: ResourceDescriptor {
+            kind: start.kind,
+            value: start.display_value(),
+        },
+        viewport,
+        similarity,
+        threshold,
+        passed,
+        screens,
+        warnings,
+    });
+
+    if let Err(err) = write_output(&body, format, output.clone()) {
+        return render_error(DpcError::Config(err.to_string()), format, output);
+    }
+    if should_keep_artifacts {
+        eprintln!("Artifacts directory: {}", artifacts_dir.display());
+    } else {
+        let _ = std::fs::remove_dir_all(&artifacts_dir);
+    }
+
+    exit_code_for_compare(passed)
+}
+
+/// The screens of the flow starting at the `--ref` frame, read from the
+/// saved JSON for `figma-json:` inputs and from the Figma API otherwise.
+async fn load_flow(start: &FigmaInfo) -> Result<Vec<FrameCandidate>, DpcError> {
+    let document = match &start.json_path {
+        Some(path) => document_from_json(&std::fs::read_to_string(path)?)?,
+        None => {
+            let auth = FigmaAuth::resolve().await?.ok_or_else(|| {
+                DpcError::Config(
+                    "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN, or run `dpc figma login`"
+                        .to_string(),
+                )
+            })?;
+            auth.ensure_fresh().await?;
+            let client = FigmaClient::from_auth(auth).map_err(map_figma_error)?;
+            client
+                .get_file(&start.file_key)
+                .await
+                .map_err(map_figma_error)?
+                .document
+        }
+    };
+    let start_id = match (&start.node_id, &start.frame_name) {
+        (Some(id), _) => id.clone(),
+        (None, Some(name)) => {
+            find_frame_by_name(&document, name)
+                .map_err(map_figma_error)?
+                .id
+        }
+        (None, None) => {
+            return Err(DpcError::Config(
+                "--ref needs the flow's start frame: add node-id or frame-name".to_string(),
+            ))
+        }
+    };
+    prototype_flow(&document, &start_id).map_err(map_figma_error)
+}
+
+/// `route` joined to `base` when it is a path (`/checkout`); anything else is
+/// used as given.
+fn join_route(base: Option<&str>, route: &str) -> String {
+    match base {
+        Some(base) if route.starts_with('/') => {
+            format!("{}{}", base.trim_end_matches('/'), route)
+        }
+        _ => route.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_route_prefixes_paths_with_the_base_url() {
+        let base = Some("https://staging.example.com/");
+        assert_eq!(
+            join_route(base, "/checkout"),
+            "https://staging.example.com/checkout"
+        );
+        assert_eq!(
+            join_route(base, "https://other.example.com/"),
+            "https://other.example.com/"
+        );
+        assert_eq!(join_route(None, "/checkout"), "/checkout");
+    }
+}
@@ -5,9 +5,10 @@ use std::sync::Arc;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use dpc_lib::output::DPC_OUTPUT_VERSION;
-use dpc_lib::types::ResourceKind;
+use dpc_lib::types::{AutoLayout, NormalizedView, ResourceKind};
 use dpc_lib::{
-    parse_resource, DpcError, DpcOutput, GenerateCodeOutput, ResourceDescriptor, Summary, Viewport,
+    parse_resource, BrowserEngine, DpcError, DpcOutput, Emulation, GenerateCodeOutput,
+    RequestRules, ResourceDescriptor, Summary, Viewport,
 };
 use serde::{Deserialize, Serialize};
 
@@ -69,13 +70,14 @@ pub async fn run_generate_code(
             } else {
                 Some(Summary {
                     top_issues: codegen.warnings,
+                    narrative: None,
                 })
             };
             let body = DpcOutput::GenerateCode(GenerateCodeOutput {
                 version: DPC_OUTPUT_VERSION.to_string(),
                 input: ResourceDescriptor {
                     kind: input_res.kind,
-                    value: input_res.value,
+                    value: input_res.display_value(),
                 },
                 viewport: Some(viewport),
                 stack: Some(normalized_stack.clone()),
@@ -117,6 +119,12 @@ pub async fn run_generate_code(
         nav_timeout,
         network_idle_timeout,
         process_timeout,
+        BrowserEngine::default(),
+        false,
+        &RequestRules::default(),
+        &[],
+        &Emulation::default(),
+        false,
     )
     .await
     {
@@ -139,10 +147,18 @@ pub async fn run_generate_code(
         );
     }
 
+    let layout_hints = auto_layout_hints(&view);
+    if verbose && !layout_hints.is_empty() {
+        eprintln!(
+            "Passing {} Figma auto-layout frame(s) to codegen",
+            layout_hints.len()
+        );
+    }
     let codegen = match generate_code(
         &view.screenshot_path,
         &normalized_stack,
         Some(viewport),
+        &layout_hints,
         verbose,
     )
     .await
@@ -165,6 +181,7 @@ pub async fn run_generate_code(
     } else {
         Some(Summary {
             top_issues: codegen.warnings.clone(),
+            narrative: None,
         })
     };
 
@@ -172,7 +189,7 @@ pub async fn run_generate_code(
         version: DPC_OUTPUT_VERSION.to_string(),
         input: ResourceDescriptor {
             kind: input_res.kind,
-            value: input_res.value,
+            value: input_res.display_value(),
         },
         viewport: Some(viewport),
         stack: Some(normalized_stack),
@@ -211,6 +228,48 @@ struct CodegenResult {
     warnings: Vec<String>,
 }
 
+/// A Figma auto-layout frame, so codegen can emit the matching flexbox instead
+/// of guessing spacing from pixels.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutHint {
+    node_id: String,
+    name: Option<String>,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    auto_layout: AutoLayout,
+    /// Tailwind classes reproducing the layout, e.g. `flex flex-col gap-4 p-6`.
+    tailwind: String,
+}
+
+/// Environment variable naming the JSON file of [`LayoutHint`]s passed to
+/// `DPC_CODEGEN_CMD`.
+const CODEGEN_AUTO_LAYOUT_ENV: &str = "DPC_CODEGEN_AUTO_LAYOUT";
+
+fn auto_layout_hints(view: &NormalizedView) -> Vec<LayoutHint> {
+    let Some(tree) = &view.figma_tree else {
+        return Vec::new();
+    };
+    tree.nodes
+        .iter()
+        .filter_map(|node| {
+            let layout = node.auto_layout?;
+            Some(LayoutHint {
+                node_id: node.id.clone(),
+                name: node.name.clone(),
+                x: node.bounding_box.x,
+                y: node.bounding_box.y,
+                width: node.bounding_box.width,
+                height: node.bounding_box.height,
+                auto_layout: layout,
+                tailwind: layout.tailwind_classes(),
+            })
+        })
+        .collect()
+}
+
 fn mock_codegen_from_env() -> Result<Option<CodegenResult>, DpcError> {
     if let Ok(mock) = std::env::var("DPC_MOCK_CODE") {
         if !mock.trim().is_empty() {
@@ -242,6 +301,7 @@ async fn generate_code(
     screenshot_path: &Path,
     stack: &str,
     viewport: Option<Viewport>,
+    layout_hints: &[LayoutHint],
     verbose: bool,
 ) -> Result<CodegenResult, DpcError> {
     if let Ok(cmd) = std::env::var("DPC_CODEGEN_CMD") {
@@ -249,7 +309,7 @@ async fn generate_code(
             if verbose {
                 eprintln!("Invoking codegen command: {}", cmd);
             }
-            return run_codegen_command(&cmd, screenshot_path, stack);
+            return run_codegen_command(&cmd, screenshot_path, stack, layout_hints);
         }
     }
 
@@ -258,7 +318,7 @@ async fn generate_code(
             if verbose {
                 eprintln!("Calling codegen endpoint: {}", url);
             }
-            return call_http_codegen(&url, screenshot_path, stack, viewport).await;
+            return call_http_codegen(&url, screenshot_path, stack, viewport, layout_hints).await;
         }
     }
 
@@ -272,8 +332,14 @@ fn run_codegen_command(
     cmd: &str,
     screenshot_path: &Path,
     stack: &str,
+    layout_hints: &[LayoutHint],
 ) -> Result<CodegenResult, DpcError> {
     let mut command = Command::new(cmd);
+    if !layout_hints.is_empty() {
+        let hints_path = screenshot_path.with_file_name("auto_layout.json");
+        std::fs::write(&hints_path, serde_json::to_vec_pretty(layout_hints)?)?;
+        command.env(CODEGEN_AUTO_LAYOUT_ENV, &hints_path);
+    }
 
     if let Ok(args) = std::env::var("DPC_CODEGEN_ARGS") {
         for arg in args.split_whitespace() {
@@ -319,13 +385,15 @@ fn run_codegen_command(
 }
 
 #[derive(Debug, Serialize)]
-struct HttpCodegenRequest {
+struct HttpCodegenRequest<'a> {
     #[serde(rename = "imageBase64")]
     image_base64: String,
     #[serde(rename = "dataUrl")]
     data_url: String,
     stack: String,
     viewport: Option<Viewport>,
+    #[serde(rename = "autoLayout", skip_serializing_if = "<[_]>::is_empty")]
+    auto_layout: &'a [LayoutHint],
 }
 
 #[derive(Debug, Deserialize)]
@@ -343,6 +411,7 @@ async fn call_http_codegen(
     screenshot_path: &Path,
     stack: &str,
     viewport: Option<Viewport>,
+    layout_hints: &[LayoutHint],
 ) -> Result<CodegenResult, DpcError> {
     let bytes = std::fs::read(screenshot_path)?;
     let encoded = BASE64_STANDARD.encode(bytes);
@@ -351,9 +420,10 @@ async fn call_http_codegen(
         data_url: format!("data:image/png;base64,{encoded}"),
         stack: stack.to_string(),
         viewport,
+        auto_layout: layout_hints,
     };
 
-    let client = reqwest::Client::new();
+    let client = dpc_lib::network::http_client();
     let mut request = client.post(url).json(&payload);
     if let Ok(token) = std::env::var("DPC_CODEGEN_API_KEY") {
         if !token.trim().is_empty() {
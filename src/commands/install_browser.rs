@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dpc_lib::browser::{default_install_dir, install_playwright};
+use dpc_lib::{BrowserEngine, DpcError};
+
+use crate::cli::OutputFormat;
+use crate::formatting::render_error;
+
+/// Run the install-browser command: install Playwright and the requested
+/// browser engines into a managed directory that later URL renders pick up
+/// automatically.
+pub async fn run_install_browser(dir: Option<PathBuf>, browsers: Vec<BrowserEngine>) -> ExitCode {
+    let Some(dir) = dir.or_else(default_install_dir) else {
+        return render_error(
+            DpcError::Config(
+                "Could not determine a data directory; pass --dir to choose the install location"
+                    .to_string(),
+            ),
+            OutputFormat::Pretty,
+            None,
+        );
+    };
+
+    let names: Vec<&str> = browsers.iter().map(|engine| engine.as_str()).collect();
+    eprintln!(
+        "Installing Playwright ({}) into {}\u{2026}",
+        names.join(", "),
+        dir.display()
+    );
+    match install_playwright(&dir, "node", &browsers).await {
+        Ok(install) => {
+            println!(
+                "Installed Playwright into {}; dpc will use it for URL inputs.",
+                install.dir.display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
@@ -1,7 +1,32 @@
+mod audit;
 mod compare;
+mod completions;
+mod diff_results;
+mod doctor;
+mod figma;
+mod flow;
 mod generate;
+mod install_browser;
+mod open_issue;
+mod publish_github;
 mod quality;
+mod review;
+mod schema;
+mod screens;
+mod self_compare;
 
+pub use audit::run_audit;
 pub use compare::run_compare;
+pub use completions::{run_completions, run_man};
+pub use diff_results::run_diff_results;
+pub use doctor::run_doctor;
+pub use figma::run_figma;
+pub use flow::run_flow;
 pub use generate::run_generate_code;
+pub use install_browser::run_install_browser;
+pub use open_issue::run_open_issue;
+pub use publish_github::run_publish_github;
 pub use quality::run_quality;
+pub use review::run_review;
+pub use schema::run_schema;
+pub use self_compare::run_self_compare;
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use dpc_lib::issue_tracker::DEFAULT_LINEAR_API_URL;
+use dpc_lib::types::DiffSeverity;
+use dpc_lib::{DpcError, IssuePublisher, IssueReport};
+
+use super::diff_results::load_result;
+use crate::cli::{IssueTracker, OutputFormat, SeverityLevel};
+use crate::formatting::render_error;
+use crate::settings::load_config;
+
+const RESULT_FILE: &str = "result.json";
+
+/// Run `dpc open-issue <result>`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_open_issue(
+    config_path: Option<PathBuf>,
+    result: PathBuf,
+    tracker: IssueTracker,
+    project: String,
+    min_severity: SeverityLevel,
+    artifacts_url: Option<String>,
+    issue_type: String,
+    tracker_url: Option<String>,
+) -> ExitCode {
+    let min_severity = match min_severity {
+        SeverityLevel::Minor => DiffSeverity::Minor,
+        SeverityLevel::Moderate => DiffSeverity::Moderate,
+        SeverityLevel::Major => DiffSeverity::Major,
+    };
+    let opened = open_issue(
+        config_path.as_deref(),
+        &result,
+        tracker,
+        &project,
+        min_severity,
+        artifacts_url.as_deref(),
+        &issue_type,
+        tracker_url,
+    )
+    .await;
+    match opened {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn open_issue(
+    config_path: Option<&Path>,
+    result_path: &Path,
+    tracker: IssueTracker,
+    project: &str,
+    min_severity: DiffSeverity,
+    artifacts_url: Option<&str>,
+    issue_type: &str,
+    tracker_url: Option<String>,
+) -> Result<(), DpcError> {
+    let publisher = match tracker {
+        IssueTracker::Jira => {
+            let token = required_env("JIRA_API_TOKEN", "Jira API token")?;
+            let base_url = tracker_url
+                .or_else(|| env_value("JIRA_URL"))
+                .ok_or_else(|| {
+                    DpcError::Config(
+                        "Jira site missing; set JIRA_URL or pass --tracker-url".to_string(),
+                    )
+                })?;
+            IssuePublisher::jira(
+                &base_url,
+                env_value("JIRA_EMAIL"),
+                token,
+                project,
+                issue_type,
+            )?
+        }
+        IssueTracker::Linear => {
+            let api_key = required_env("LINEAR_API_KEY", "Linear API key")?;
+            let api_url = tracker_url.unwrap_or_else(|| DEFAULT_LINEAR_API_URL.to_string());
+            IssuePublisher::linear(api_key, project, &api_url)?
+        }
+    };
+    let config = load_config(config_path)?;
+
+    let result_file = if result_path.is_dir() {
+        result_path.join(RESULT_FILE)
+    } else {
+        result_path.to_path_buf()
+    };
+    let result = load_result(&result_file)?;
+    let Some(report) = IssueReport::from_result(
+        &result,
+        &config.severity.calibration(),
+        min_severity,
+        artifacts_url,
+    ) else {
+        println!(
+            "Nothing to file: the comparison passed or has no finding at the minimum severity."
+        );
+        return Ok(());
+    };
+
+    let issue = publisher.publish(&report).await?;
+    let action = if issue.created { "Opened" } else { "Updated" };
+    println!(
+        "{action} {} ({} finding(s)): {}",
+        issue.key,
+        report.findings.len(),
+        issue.url
+    );
+    Ok(())
+}
+
+fn required_env(name: &str, what: &str) -> Result<String, DpcError> {
+    env_value(name).ok_or_else(|| DpcError::Config(format!("{what} missing; set {name}")))
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use dpc_lib::{
+    check_annotations, composite_image, markdown_summary, store_from_spec, DpcError,
+    GitHubPublisher,
+};
+
+use super::diff_results::load_result;
+use crate::cli::OutputFormat;
+use crate::formatting::render_error;
+use crate::settings::load_config;
+
+const RESULT_FILE: &str = "result.json";
+const COMPOSITE_FILE: &str = "composite.png";
+
+/// Run `dpc publish-github <result>`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_publish_github(
+    config_path: Option<PathBuf>,
+    result: PathBuf,
+    repo: Option<String>,
+    pr: Option<u64>,
+    check_run: bool,
+    sha: Option<String>,
+    check_name: String,
+    annotation_path: Option<String>,
+    artifact_store: Option<String>,
+    api_url: String,
+) -> ExitCode {
+    let published = publish(
+        config_path.as_deref(),
+        &result,
+        repo,
+        pr,
+        check_run.then_some(sha),
+        &check_name,
+        annotation_path,
+        artifact_store.as_deref(),
+        &api_url,
+    )
+    .await;
+    match published {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn publish(
+    config_path: Option<&Path>,
+    result_path: &Path,
+    repo: Option<String>,
+    pr: Option<u64>,
+    check_run: Option<Option<String>>,
+    check_name: &str,
+    annotation_path: Option<String>,
+    artifact_store: Option<&str>,
+    api_url: &str,
+) -> Result<(), DpcError> {
+    if pr.is_none() && check_run.is_none() {
+        return Err(DpcError::Config(
+            "Nothing to publish; pass --pr NUMBER and/or --check-run".to_string(),
+        ));
+    }
+    let token = env_value("GITHUB_TOKEN").ok_or_else(|| {
+        DpcError::Config(
+            "GitHub token missing; set GITHUB_TOKEN (pull-requests: write, checks: write)"
+                .to_string(),
+        )
+    })?;
+    let repo = repo
+        .or_else(|| env_value("GITHUB_REPOSITORY"))
+        .ok_or_else(|| {
+            DpcError::Config("GitHub repository missing; pass --repo OWNER/NAME".to_string())
+        })?;
+    let sha = match check_run {
+        Some(sha) => Some(sha.or_else(|| env_value("GITHUB_SHA")).ok_or_else(|| {
+            DpcError::Config("--check-run needs the commit; pass --sha SHA".to_string())
+        })?),
+        None => None,
+    };
+    let publisher = GitHubPublisher::with_api_url(token, &repo, api_url)?;
+    let config = load_config(config_path)?;
+
+    let result_file = if result_path.is_dir() {
+        result_path.join(RESULT_FILE)
+    } else {
+        result_path.to_path_buf()
+    };
+    let result = load_result(&result_file)?;
+
+    let image_url = match artifact_store {
+        Some(spec) => upload_composite(spec, &result, &result_file).await?,
+        None => None,
+    };
+    let summary = markdown_summary(&result, image_url.as_deref());
+
+    if let Some(pr) = pr {
+        let url = publisher.publish_comment(pr, &summary).await?;
+        println!("Pull request comment: {url}");
+    }
+    if let Some(sha) = sha {
+        let path = annotation_path.unwrap_or_else(|| result_file.display().to_string());
+        let annotations = check_annotations(&result, &config.severity.calibration(), &path);
+        let url = publisher
+            .publish_check_run(check_name, &sha, &result, &summary, &annotations)
+            .await?;
+        println!("Check run: {url} ({} annotation(s))", annotations.len());
+    }
+    Ok(())
+}
+
+/// Render the composite image next to the result, upload it and return its
+/// URL when the store serves it over HTTP.
+async fn upload_composite(
+    spec: &str,
+    result: &dpc_lib::CompareOutput,
+    result_file: &Path,
+) -> Result<Option<String>, DpcError> {
+    let dir = result
+        .artifacts
+        .as_ref()
+        .map(|artifacts| artifacts.directory.clone())
+        .filter(|dir| dir.is_dir())
+        .or_else(|| result_file.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let Some(path) = composite_image(result, &dir.join(COMPOSITE_FILE))? else {
+        eprintln!("No screenshots in the result's artifacts; publishing without an image.");
+        return Ok(None);
+    };
+    let run = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "dpc".to_string());
+    let store = store_from_spec(spec)?;
+    let location = store.put(&format!("{run}/{COMPOSITE_FILE}"), &path).await?;
+    if location.starts_with("http://") || location.starts_with("https://") {
+        Ok(Some(location))
+    } else {
+        eprintln!("Composite image stored at {location}; only http(s) locations can be embedded.");
+        Ok(None)
+    }
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
@@ -6,11 +6,13 @@ use std::sync::Arc;
 
 use dpc_lib::output::DPC_OUTPUT_VERSION;
 use dpc_lib::types::{
-    BoundingBox, DomNode, FigmaNode, FigmaPaintKind, NormalizedView, ResourceKind,
+    BoundingBox, DomNode, DomSnapshot, FigmaNode, FigmaPaintKind, InteractionStates,
+    NormalizedView, ResourceKind,
 };
 use dpc_lib::QualityFindingType;
 use dpc_lib::{
-    parse_resource, DpcError, DpcOutput, FindingSeverity, QualityFinding, QualityOutput,
+    parse_css_color, parse_resource, BrowserEngine, DpcError, DpcOutput, Emulation,
+    FindingSeverity, PaletteSprawl, QualityFinding, QualityOutput, RequestRules,
     ResourceDescriptor, Viewport,
 };
 use image::{DynamicImage, GenericImageView};
@@ -80,6 +82,12 @@ pub async fn run_quality(
         nav_timeout,
         network_idle_timeout,
         process_timeout,
+        BrowserEngine::default(),
+        false,
+        &RequestRules::default(),
+        &[],
+        &Emulation::default(),
+        true,
     )
     .await
     {
@@ -96,13 +104,13 @@ pub async fn run_quality(
     if verbose {
         eprintln!("Scoring quality heuristics…");
     }
-    let (score, findings) = score_quality(&view, &viewport);
+    let (score, findings) = score_quality(&view, &viewport, &config.quality.palette());
 
     let body = DpcOutput::Quality(QualityOutput {
         version: DPC_OUTPUT_VERSION.to_string(),
         input: ResourceDescriptor {
             kind: input_res.kind,
-            value: input_res.value,
+            value: input_res.display_value(),
         },
         viewport,
         score,
@@ -122,7 +130,11 @@ fn resource_kind_from_cli(rt: crate::cli::ResourceType) -> ResourceKind {
     }
 }
 
-fn score_quality(view: &NormalizedView, viewport: &Viewport) -> (f32, Vec<QualityFinding>) {
+fn score_quality(
+    view: &NormalizedView,
+    viewport: &Viewport,
+    palette: &PaletteSprawl,
+) -> (f32, Vec<QualityFinding>) {
     let mut findings = Vec::new();
     let mut score = 0.4;
     let spacing_gaps = collect_vertical_gaps(view);
@@ -201,6 +213,14 @@ fn score_quality(view: &NormalizedView, viewport: &Viewport) -> (f32, Vec<Qualit
     }
     findings.push(contrast_finding);
 
+    let (state_penalty, state_findings) = interaction_state_heuristic(view);
+    score -= state_penalty;
+    findings.extend(state_findings);
+
+    let (semantic_penalty, semantic_findings) = semantic_heuristic(view);
+    score -= semantic_penalty;
+    findings.extend(semantic_findings);
+
     if let Some((finding, penalty)) = evaluate_spacing(&spacing_gaps) {
         findings.push(finding);
         score -= penalty;
@@ -209,6 +229,16 @@ fn score_quality(view: &NormalizedView, viewport: &Viewport) -> (f32, Vec<Qualit
         score += 0.02;
     }
 
+    if let Some((finding, penalty)) = spacing_scale_heuristic(view) {
+        findings.push(finding);
+        score -= penalty;
+    }
+
+    if let Some((finding, penalty)) = palette_heuristic(view, palette) {
+        findings.push(finding);
+        score -= penalty;
+    }
+
     (score.clamp(0.0, 1.0), findings)
 }
 
@@ -532,61 +562,6 @@ fn contrast_for_figma_node(
     Some(contrast_ratio(text_color, background))
 }
 
-fn parse_css_color(value: &str) -> Option<[f32; 4]> {
-    let v = value.trim().to_ascii_lowercase();
-    if v == "transparent" {
-        return None;
-    }
-
-    if let Some(hex) = v.strip_prefix('#') {
-        let expanded = match hex.len() {
-            3 => hex
-                .chars()
-                .map(|c| format!("{c}{c}"))
-                .collect::<Vec<_>>()
-                .join(""),
-            6 | 8 => hex.to_string(),
-            _ => return None,
-        };
-        let r = u8::from_str_radix(&expanded[0..2], 16).ok()?;
-        let g = u8::from_str_radix(&expanded[2..4], 16).ok()?;
-        let b = u8::from_str_radix(&expanded[4..6], 16).ok()?;
-        let a = if expanded.len() == 8 {
-            u8::from_str_radix(&expanded[6..8], 16).ok()? as f32 / 255.0
-        } else {
-            1.0
-        };
-        return Some([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a]);
-    }
-
-    if let Some(body) = v.strip_prefix("rgba(").or_else(|| v.strip_prefix("rgb(")) {
-        let cleaned = body.trim_end_matches(')').replace('/', " ");
-        let parts: Vec<_> = cleaned
-            .split(&[',', ' '][..])
-            .filter(|p| !p.trim().is_empty())
-            .collect();
-        if parts.len() < 3 {
-            return None;
-        }
-        let r: f32 = parts.first()?.trim().parse::<f32>().ok()? / 255.0;
-        let g: f32 = parts.get(1)?.trim().parse::<f32>().ok()? / 255.0;
-        let b: f32 = parts.get(2)?.trim().parse::<f32>().ok()? / 255.0;
-        let a: f32 = if let Some(alpha) = parts.get(3) {
-            alpha
-                .trim()
-                .parse::<f32>()
-                .ok()
-                .map(|v| v.clamp(0.0, 1.0))
-                .unwrap_or(1.0)
-        } else {
-            1.0
-        };
-        return Some([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), a]);
-    }
-
-    None
-}
-
 fn contrast_ratio(fg: [f32; 3], bg: [f32; 3]) -> f32 {
     let l1 = relative_luminance(fg);
     let l2 = relative_luminance(bg);
@@ -714,6 +689,259 @@ fn bbox_to_pixels(
     }
 }
 
+/// Interactive elements that render exactly like their default state with
+/// `:focus-visible` forced (any control) or `:hover` forced (buttons and
+/// links). Needs states captured from a URL; other inputs add nothing.
+fn interaction_state_heuristic(view: &NormalizedView) -> (f32, Vec<QualityFinding>) {
+    let Some(dom) = &view.dom else {
+        return (0.0, Vec::new());
+    };
+    let captured: Vec<(&DomNode, &InteractionStates)> = dom
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node, node.interaction_states.as_ref()?)))
+        .collect();
+    let mut penalty = 0.0;
+    let mut findings = Vec::new();
+
+    let no_focus: Vec<&DomNode> = captured
+        .iter()
+        .filter(|(_, states)| states.focus_visible.is_empty())
+        .map(|(node, _)| *node)
+        .collect();
+    if !no_focus.is_empty() {
+        penalty += 0.1 * no_focus.len() as f32 / captured.len() as f32;
+        findings.push(QualityFinding {
+            severity: FindingSeverity::Warning,
+            finding_type: QualityFindingType::MissingFocusStyle,
+            message: format!(
+                "{} of {} interactive elements look the same under :focus-visible ({}); keyboard users cannot see where focus is.",
+                no_focus.len(),
+                captured.len(),
+                element_examples(&no_focus)
+            ),
+        });
+    }
+
+    let clickable: Vec<&(&DomNode, &InteractionStates)> = captured
+        .iter()
+        .filter(|(node, _)| is_clickable(node))
+        .collect();
+    let no_hover: Vec<&DomNode> = clickable
+        .iter()
+        .filter(|(_, states)| states.hover.is_empty())
+        .map(|(node, _)| *node)
+        .collect();
+    if !no_hover.is_empty() {
+        penalty += 0.05 * no_hover.len() as f32 / clickable.len() as f32;
+        findings.push(QualityFinding {
+            severity: FindingSeverity::Info,
+            finding_type: QualityFindingType::MissingHoverStyle,
+            message: format!(
+                "{} of {} buttons and links do not change on hover ({}); add a hover affordance.",
+                no_hover.len(),
+                clickable.len(),
+                element_examples(&no_hover)
+            ),
+        });
+    }
+
+    (penalty, findings)
+}
+
+/// Semantic structure from the DOM snapshot: headings that skip a level or
+/// have no content, images without `alt`, and inline `onclick` handlers on
+/// elements that are neither controls nor given a widget role.
+fn semantic_heuristic(view: &NormalizedView) -> (f32, Vec<QualityFinding>) {
+    const PENALTY_PER_CHECK: f32 = 0.03;
+    let Some(dom) = &view.dom else {
+        return (0.0, Vec::new());
+    };
+    let by_id: HashMap<&str, &DomNode> = dom
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+    let mut findings = Vec::new();
+    let mut warn = |finding_type, message| {
+        findings.push(QualityFinding {
+            severity: FindingSeverity::Warning,
+            finding_type,
+            message,
+        })
+    };
+
+    // Headings don't nest, so snapshot order is their document order.
+    let headings: Vec<(&DomNode, u8)> = dom
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node, heading_level(node)?)))
+        .collect();
+    let skips: Vec<String> = headings
+        .windows(2)
+        .filter(|pair| pair[1].1 > pair[0].1 + 1)
+        .map(|pair| {
+            format!(
+                "h{} {} after h{} {}",
+                pair[1].1,
+                element_examples(&[pair[1].0]),
+                pair[0].1,
+                element_examples(&[pair[0].0])
+            )
+        })
+        .collect();
+    if let Some(first) = skips.first() {
+        warn(
+            QualityFindingType::SkippedHeadingLevel,
+            format!(
+                "{} heading(s) skip a level (e.g. {first}); nest headings one level at a time.",
+                skips.len()
+            ),
+        );
+    }
+
+    let empty: Vec<&DomNode> = headings
+        .iter()
+        .map(|(node, _)| *node)
+        .filter(|node| !has_content(node, &by_id))
+        .collect();
+    if !empty.is_empty() {
+        warn(
+            QualityFindingType::EmptyHeading,
+            format!(
+                "{} of {} headings have no text ({}); screen readers announce them as blank.",
+                empty.len(),
+                headings.len(),
+                element_examples(&empty)
+            ),
+        );
+    }
+
+    let images: Vec<&DomNode> = dom
+        .nodes
+        .iter()
+        .filter(|node| {
+            node.tag.eq_ignore_ascii_case("img")
+                || (node.tag.eq_ignore_ascii_case("input")
+                    && node.attributes.get("type").map(String::as_str) == Some("image"))
+        })
+        .collect();
+    let no_alt: Vec<&DomNode> = images
+        .iter()
+        .filter(|node| !node.attributes.contains_key("alt"))
+        .copied()
+        .collect();
+    if !no_alt.is_empty() {
+        let sources: Vec<String> = no_alt
+            .iter()
+            .take(3)
+            .map(|node| match node.attributes.get("src") {
+                Some(src) => format!("'{}'", src.rsplit('/').next().unwrap_or(src)),
+                None => format!("<{}>", node.tag),
+            })
+            .collect();
+        warn(
+            QualityFindingType::MissingAltText,
+            format!(
+                "{} of {} images have no alt attribute ({}{}); describe them, or use alt=\"\" for decoration.",
+                no_alt.len(),
+                images.len(),
+                sources.join(", "),
+                if no_alt.len() > 3 { ", …" } else { "" }
+            ),
+        );
+    }
+
+    let click_handlers: Vec<&DomNode> = dom
+        .nodes
+        .iter()
+        .filter(|node| node.attributes.contains_key("onclick") && !is_semantic_control(node))
+        .collect();
+    if !click_handlers.is_empty() {
+        warn(
+            QualityFindingType::NonSemanticClickHandler,
+            format!(
+                "{} element(s) handle clicks without being a button or link ({}); use <button>/<a>, or add a role and tabindex.",
+                click_handlers.len(),
+                element_examples(&click_handlers)
+            ),
+        );
+    }
+
+    (findings.len() as f32 * PENALTY_PER_CHECK, findings)
+}
+
+/// `1..=6` for `h1`–`h6`.
+fn heading_level(node: &DomNode) -> Option<u8> {
+    let tag = node.tag.to_ascii_lowercase();
+    let level = tag.strip_prefix('h')?.parse().ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+/// Whether the element or a descendant has text or an image with alt text.
+fn has_content(node: &DomNode, by_id: &HashMap<&str, &DomNode>) -> bool {
+    node_has_text(node)
+        || node
+            .attributes
+            .get("alt")
+            .is_some_and(|alt| !alt.trim().is_empty())
+        || node
+            .children
+            .iter()
+            .filter_map(|id| by_id.get(id.as_str()))
+            .any(|child| has_content(child, by_id))
+}
+
+/// Native controls, and elements given a widget role, that may take clicks.
+fn is_semantic_control(node: &DomNode) -> bool {
+    let native = matches!(
+        node.tag.to_ascii_lowercase().as_str(),
+        "a" | "button" | "input" | "select" | "textarea" | "summary" | "label" | "option"
+    );
+    let role = node.attributes.get("role").map(String::as_str);
+    native
+        || matches!(
+            role,
+            Some(
+                "button" | "link" | "tab" | "menuitem" | "checkbox" | "radio" | "switch" | "option"
+            )
+        )
+}
+
+/// Buttons and links, which are expected to react to the pointer.
+fn is_clickable(node: &DomNode) -> bool {
+    let role = node.attributes.get("role").map(String::as_str);
+    let input_type = node.attributes.get("type").map(String::as_str);
+    match node.tag.to_ascii_lowercase().as_str() {
+        "a" | "button" | "summary" => true,
+        "input" => matches!(input_type, Some("button" | "submit" | "reset")),
+        _ => matches!(role, Some("button" | "link" | "tab" | "menuitem")),
+    }
+}
+
+/// Up to three element names, e.g. `'Sign up', 'Menu', <a>, …`.
+fn element_examples(nodes: &[&DomNode]) -> String {
+    let mut names: Vec<String> = nodes
+        .iter()
+        .take(3)
+        .map(|node| {
+            match node
+                .label()
+                .or(node.text.as_deref())
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+            {
+                Some(name) => format!("'{}'", name.chars().take(40).collect::<String>()),
+                None => format!("<{}>", node.tag),
+            }
+        })
+        .collect();
+    if nodes.len() > 3 {
+        names.push("…".to_string());
+    }
+    names.join(", ")
+}
+
 fn node_has_text(node: &DomNode) -> bool {
     node.text
         .as_ref()
@@ -808,6 +1036,233 @@ fn evaluate_spacing(gaps: &[f32]) -> Option<(QualityFinding, f32)> {
     Some((finding, penalty))
 }
 
+/// Grid steps a spacing scale may be built on, largest first.
+const SCALE_BASES: [u32; 4] = [8, 6, 5, 4];
+
+/// Infer the spacing scale of a DOM snapshot (the grid step most of its
+/// paddings and gaps are multiples of) and report the spacings off it.
+fn spacing_scale_heuristic(view: &NormalizedView) -> Option<(QualityFinding, f32)> {
+    const MIN_SAMPLES: usize = 5;
+    const MIN_FIT: f32 = 0.6;
+    let spacings = collect_spacings(view.dom.as_ref()?);
+    if spacings.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let fit = |base: u32| {
+        spacings.iter().filter(|(px, _)| px % base == 0).count() as f32 / spacings.len() as f32
+    };
+    let best_fit = SCALE_BASES
+        .iter()
+        .map(|base| fit(*base))
+        .fold(0.0, f32::max);
+    if best_fit < MIN_FIT {
+        return Some((
+            QualityFinding {
+                severity: FindingSeverity::Warning,
+                finding_type: QualityFindingType::SpacingOffScale,
+                message: format!(
+                    "No consistent spacing scale: at most {:.0}% of {} spacings fit a 4, 5, 6 or 8px grid.",
+                    best_fit * 100.0,
+                    spacings.len()
+                ),
+            },
+            0.08,
+        ));
+    }
+    // A coarser grid wins when it explains nearly as much as the best one.
+    let base = SCALE_BASES
+        .into_iter()
+        .find(|base| fit(*base) >= best_fit - 0.05)
+        .unwrap_or(4);
+
+    let mut outliers: Vec<(u32, Vec<&DomNode>)> = Vec::new();
+    for (px, node) in spacings.iter().filter(|(px, _)| px % base != 0) {
+        match outliers.iter_mut().find(|(value, _)| value == px) {
+            Some((_, nodes)) => nodes.push(node),
+            None => outliers.push((*px, vec![node])),
+        }
+    }
+    if outliers.is_empty() {
+        return Some((
+            QualityFinding {
+                severity: FindingSeverity::Info,
+                finding_type: QualityFindingType::SpacingOffScale,
+                message: format!("All {} spacings fit a {base}px scale.", spacings.len()),
+            },
+            0.0,
+        ));
+    }
+
+    outliers.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+    let off_scale: usize = outliers.iter().map(|(_, nodes)| nodes.len()).sum();
+    let ratio = off_scale as f32 / spacings.len() as f32;
+    let mut examples: Vec<String> = outliers
+        .iter()
+        .take(3)
+        .map(|(px, nodes)| {
+            let mut unique = nodes.clone();
+            unique.dedup_by_key(|node| node.id.as_str());
+            unique.truncate(2);
+            format!(
+                "{px}px ×{} (e.g. {})",
+                nodes.len(),
+                element_examples(&unique)
+            )
+        })
+        .collect();
+    if outliers.len() > 3 {
+        examples.push("…".to_string());
+    }
+    let severity = if off_scale >= 3 && ratio >= 0.1 {
+        FindingSeverity::Warning
+    } else {
+        FindingSeverity::Info
+    };
+    Some((
+        QualityFinding {
+            severity,
+            finding_type: QualityFindingType::SpacingOffScale,
+            message: format!(
+                "Spacing follows a {base}px scale for {} of {} values; off-scale: {}.",
+                spacings.len() - off_scale,
+                spacings.len(),
+                examples.join(", ")
+            ),
+        },
+        (ratio * 0.1).min(0.08),
+    ))
+}
+
+/// Count the distinct colors of the view against the palette budget and list
+/// the near-duplicate pairs that likely should be one token.
+fn palette_heuristic(
+    view: &NormalizedView,
+    palette: &PaletteSprawl,
+) -> Option<(QualityFinding, f32)> {
+    const MAX_PAIRS: usize = 5;
+    let report = palette.analyze(view)?;
+    let count = report.colors.len();
+    let mut pairs: Vec<String> = report
+        .near_duplicates
+        .iter()
+        .take(MAX_PAIRS)
+        .map(|pair| format!("{}/{} (ΔE {:.1})", pair.first, pair.second, pair.delta_e))
+        .collect();
+    if report.near_duplicates.len() > MAX_PAIRS {
+        pairs.push("…".to_string());
+    }
+    let near = if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("; likely the same token: {}", pairs.join(", "))
+    };
+
+    if !report.over_budget() {
+        return Some((
+            QualityFinding {
+                severity: FindingSeverity::Info,
+                finding_type: QualityFindingType::PaletteSprawl,
+                message: format!(
+                    "Uses {count} distinct colors (budget {}){near}.",
+                    report.budget
+                ),
+            },
+            0.0,
+        ));
+    }
+    let over = count - report.budget;
+    Some((
+        QualityFinding {
+            severity: FindingSeverity::Warning,
+            finding_type: QualityFindingType::PaletteSprawl,
+            message: format!(
+                "Palette sprawl: {count} distinct colors, {over} over the budget of {}{near}.",
+                report.budget
+            ),
+        },
+        (0.02 * over as f32).min(0.1),
+    ))
+}
+
+/// Paddings and gaps in whole CSS pixels, each with the element it pads
+/// (flex containers) or separates from the sibling before it. Flex
+/// containers report their CSS padding and gap; other siblings are measured
+/// from their boxes, vertically when stacked and horizontally only in
+/// flex/grid rows, where no inline whitespace sits between them. Hairlines
+/// (≤2px) and very wide spans are left out.
+fn collect_spacings(dom: &DomSnapshot) -> Vec<(u32, &DomNode)> {
+    const MAX_SPACING: f32 = 256.0;
+    let by_id: HashMap<&str, &DomNode> = dom
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+    let mut spacings = Vec::new();
+    let mut push = |px: f32, node| {
+        if px > 2.0 && px <= MAX_SPACING {
+            spacings.push((px.round() as u32, node));
+        }
+    };
+
+    for parent in &dom.nodes {
+        let style = parent.computed_style.as_ref();
+        let flex = style.and_then(|style| style.flex.as_ref());
+        if let Some(flex) = flex {
+            let padding = &flex.padding;
+            for side in [padding.top, padding.right, padding.bottom, padding.left] {
+                push(side, parent);
+            }
+            push(flex.main_gap(), parent);
+            if flex.wrap {
+                let cross_gap = if flex.is_column() {
+                    flex.column_gap
+                } else {
+                    flex.row_gap
+                };
+                push(cross_gap, parent);
+            }
+            // Gaps between items repeat the flex gap, or are whatever
+            // space justify-content distributes.
+            let distributed = flex
+                .justify_content
+                .as_deref()
+                .is_some_and(|justify| justify.starts_with("space-"));
+            if flex.main_gap() > 0.0 || distributed {
+                continue;
+            }
+        }
+        let in_row = flex.is_some()
+            || style
+                .and_then(|style| style.display.as_deref())
+                .is_some_and(|display| display.contains("grid"));
+
+        let mut children: Vec<&DomNode> = parent
+            .children
+            .iter()
+            .filter_map(|id| by_id.get(id.as_str()).copied())
+            .filter(|child| child.bounding_box.width > 0.0 && child.bounding_box.height > 0.0)
+            .collect();
+        children.sort_by(|a, b| {
+            let (a, b) = (&a.bounding_box, &b.bounding_box);
+            a.y.partial_cmp(&b.y)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
+        });
+        for pair in children.windows(2) {
+            let (a, b) = (&pair[0].bounding_box, &pair[1].bounding_box);
+            let overlaps_x = a.x < b.x + b.width && b.x < a.x + a.width;
+            let overlaps_y = a.y < b.y + b.height && b.y < a.y + a.height;
+            if overlaps_x && b.y >= a.y + a.height {
+                push(b.y - (a.y + a.height), pair[1]);
+            } else if in_row && overlaps_y && b.x >= a.x + a.width {
+                push(b.x - (a.x + a.width), pair[1]);
+            }
+        }
+    }
+    spacings
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -827,6 +1282,8 @@ mod tests {
                 attributes: HashMap::new(),
                 text: None,
                 bounding_box: bbox,
+                text_baseline: None,
+                interaction_states: None,
                 computed_style: None,
             })
             .collect();
@@ -837,12 +1294,17 @@ mod tests {
             width: 100,
             height: 100,
             dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
                 url: None,
                 title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
                 nodes,
             }),
             figma_tree: None,
             ocr_blocks: None,
+            content_area: None,
         }
     }
 
@@ -893,13 +1355,8 @@ mod tests {
             },
         ]);
 
-        let (_score, findings) = score_quality(
-            &view,
-            &Viewport {
-                width: 800,
-                height: 600,
-            },
-        );
+        let (_score, findings) =
+            score_quality(&view, &Viewport::new(800, 600), &PaletteSprawl::default());
         assert!(
             findings
                 .iter()
@@ -937,13 +1394,8 @@ mod tests {
             },
         ]);
 
-        let (_score, findings) = score_quality(
-            &view,
-            &Viewport {
-                width: 800,
-                height: 600,
-            },
-        );
+        let (_score, findings) =
+            score_quality(&view, &Viewport::new(800, 600), &PaletteSprawl::default());
         assert!(
             !findings
                 .iter()
@@ -969,6 +1421,8 @@ mod tests {
                     width: 50.0,
                     height: 10.0,
                 },
+                text_baseline: None,
+                interaction_states: None,
                 computed_style: Some(ComputedStyle {
                     font_size: Some(*size),
                     ..ComputedStyle::default()
@@ -982,12 +1436,17 @@ mod tests {
             width: 800,
             height: 600,
             dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
                 url: None,
                 title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
                 nodes,
             }),
             figma_tree: None,
             ocr_blocks: None,
+            content_area: None,
         }
     }
 
@@ -996,20 +1455,10 @@ mod tests {
         let tiered = view_with_font_sizes(&[32.0, 20.0, 16.0]);
         let flat = view_with_font_sizes(&[16.0, 16.0, 16.0]);
 
-        let (tiered_score, tiered_findings) = score_quality(
-            &tiered,
-            &Viewport {
-                width: 800,
-                height: 600,
-            },
-        );
-        let (flat_score, flat_findings) = score_quality(
-            &flat,
-            &Viewport {
-                width: 800,
-                height: 600,
-            },
-        );
+        let (tiered_score, tiered_findings) =
+            score_quality(&tiered, &Viewport::new(800, 600), &PaletteSprawl::default());
+        let (flat_score, flat_findings) =
+            score_quality(&flat, &Viewport::new(800, 600), &PaletteSprawl::default());
 
         assert!(
             tiered_score > flat_score,
@@ -1030,6 +1479,247 @@ mod tests {
         );
     }
 
+    fn control(tag: &str, text: &str, hover: &[&str], focus_visible: &[&str]) -> DomNode {
+        let owned = |props: &[&str]| props.iter().map(|p| p.to_string()).collect();
+        DomNode {
+            id: text.to_string(),
+            tag: tag.to_string(),
+            children: Vec::new(),
+            parent: None,
+            attributes: HashMap::new(),
+            text: Some(text.to_string()),
+            bounding_box: BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 80.0,
+                height: 32.0,
+            },
+            text_baseline: None,
+            interaction_states: Some(InteractionStates {
+                hover: owned(hover),
+                focus_visible: owned(focus_visible),
+            }),
+            computed_style: None,
+        }
+    }
+
+    #[test]
+    fn flags_controls_without_focus_or_hover_styles() {
+        let mut view = view_with_boxes(Vec::new());
+        view.dom.as_mut().unwrap().nodes = vec![
+            control(
+                "button",
+                "Sign up",
+                &["background-color"],
+                &["outline-style"],
+            ),
+            control("a", "Pricing", &[], &[]),
+            control("input", "Email", &[], &["box-shadow"]),
+        ];
+
+        let (_, findings) = interaction_state_heuristic(&view);
+        let focus = findings
+            .iter()
+            .find(|f| f.finding_type == QualityFindingType::MissingFocusStyle)
+            .expect("missing focus style finding");
+        assert_eq!(focus.severity, FindingSeverity::Warning);
+        assert!(focus.message.starts_with("1 of 3"), "{}", focus.message);
+        assert!(focus.message.contains("'Pricing'"), "{}", focus.message);
+
+        // The text input is not expected to react to hover.
+        let hover = findings
+            .iter()
+            .find(|f| f.finding_type == QualityFindingType::MissingHoverStyle)
+            .expect("missing hover style finding");
+        assert!(hover.message.starts_with("1 of 2"), "{}", hover.message);
+    }
+
+    #[test]
+    fn skips_state_checks_without_captured_states() {
+        let view = view_with_font_sizes(&[16.0, 24.0]);
+        let (penalty, findings) = interaction_state_heuristic(&view);
+        assert_eq!(penalty, 0.0);
+        assert!(findings.is_empty());
+    }
+
+    fn element(id: &str, tag: &str, text: Option<&str>, attributes: &[(&str, &str)]) -> DomNode {
+        DomNode {
+            id: id.to_string(),
+            tag: tag.to_string(),
+            children: Vec::new(),
+            parent: None,
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            text: text.map(str::to_string),
+            bounding_box: BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 20.0,
+            },
+            text_baseline: None,
+            interaction_states: None,
+            computed_style: None,
+        }
+    }
+
+    #[test]
+    fn flags_semantic_structure_problems() {
+        let mut empty_heading = element("h2", "h2", None, &[]);
+        empty_heading.children = vec!["spacer".into()];
+        let mut view = view_with_boxes(Vec::new());
+        view.dom.as_mut().unwrap().nodes = vec![
+            element("h1", "h1", Some("Plans"), &[]),
+            element("h3", "h3", Some("Pricing"), &[]),
+            element("spacer", "img", None, &[("alt", ""), ("src", "dot.png")]),
+            empty_heading,
+            element("hero", "img", None, &[("src", "/assets/hero.png")]),
+            element("buy", "div", Some("Buy"), &[("onclick", "buy()")]),
+            element("ok", "button", Some("OK"), &[("onclick", "ok()")]),
+            element(
+                "tab",
+                "div",
+                Some("Tab"),
+                &[("onclick", "t()"), ("role", "tab")],
+            ),
+        ];
+
+        let (penalty, findings) = semantic_heuristic(&view);
+        assert!(penalty > 0.0);
+        let message = |kind: QualityFindingType| {
+            findings
+                .iter()
+                .find(|f| f.finding_type == kind)
+                .map(|f| f.message.clone())
+                .unwrap_or_else(|| panic!("no {kind:?} finding in {findings:?}"))
+        };
+        assert!(message(QualityFindingType::SkippedHeadingLevel)
+            .contains("h3 'Pricing' after h1 'Plans'"));
+        assert!(message(QualityFindingType::EmptyHeading).starts_with("1 of 3 headings"));
+        let alt = message(QualityFindingType::MissingAltText);
+        assert!(
+            alt.starts_with("1 of 2 images") && alt.contains("'hero.png'"),
+            "{alt}"
+        );
+        let clicks = message(QualityFindingType::NonSemanticClickHandler);
+        assert!(
+            clicks.starts_with("1 element(s)") && clicks.contains("'Buy'"),
+            "{clicks}"
+        );
+    }
+
+    #[test]
+    fn semantic_checks_pass_well_structured_markup() {
+        let mut view = view_with_boxes(Vec::new());
+        view.dom.as_mut().unwrap().nodes = vec![
+            element("h1", "h1", Some("Plans"), &[]),
+            element("h2", "h2", Some("Pro"), &[]),
+            element("h3", "h3", Some("Features"), &[]),
+            element("h2b", "h2", Some("Team"), &[]),
+            element("logo", "img", None, &[("alt", "Acme")]),
+        ];
+        let (penalty, findings) = semantic_heuristic(&view);
+        assert_eq!(penalty, 0.0);
+        assert!(findings.is_empty(), "{findings:?}");
+    }
+
+    #[test]
+    fn infers_spacing_scale_and_reports_off_scale_values() {
+        use dpc_lib::types::{EdgeInsets, FlexLayout};
+
+        let mut card = element("card", "div", None, &[]);
+        card.computed_style = Some(ComputedStyle {
+            flex: Some(FlexLayout {
+                direction: "row".into(),
+                row_gap: 0.0,
+                column_gap: 8.0,
+                padding: EdgeInsets {
+                    top: 16.0,
+                    right: 16.0,
+                    bottom: 16.0,
+                    left: 16.0,
+                },
+                justify_content: None,
+                align_items: None,
+                wrap: false,
+            }),
+            ..ComputedStyle::default()
+        });
+        let stacked = |id: &str, y: f32| {
+            let mut node = element(id, "p", Some(id), &[]);
+            node.bounding_box.y = y;
+            node
+        };
+        let mut list = element("list", "div", None, &[]);
+        list.children = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        let mut view = view_with_boxes(Vec::new());
+        view.dom.as_mut().unwrap().nodes = vec![
+            card,
+            stacked("a", 0.0),
+            stacked("b", 44.0),
+            stacked("c", 96.0),
+            stacked("d", 129.0),
+            stacked("e", 162.0),
+            list,
+        ];
+
+        let (finding, penalty) = spacing_scale_heuristic(&view).expect("spacing scale finding");
+        assert_eq!(finding.finding_type, QualityFindingType::SpacingOffScale);
+        assert!(penalty > 0.0);
+        assert!(
+            finding.message.contains("8px scale for 7 of 9 values")
+                && finding.message.contains("13px ×2 (e.g. 'd', 'e')"),
+            "{}",
+            finding.message
+        );
+
+        let nodes = &mut view.dom.as_mut().unwrap().nodes;
+        nodes[4].bounding_box.y = 124.0;
+        nodes[5].bounding_box.y = 168.0;
+        let (finding, penalty) = spacing_scale_heuristic(&view).unwrap();
+        assert_eq!(finding.message, "All 9 spacings fit a 8px scale.");
+        assert_eq!(penalty, 0.0);
+    }
+
+    #[test]
+    fn reports_palette_sprawl_with_near_duplicate_pairs() {
+        let colored = |id: &str, color: &str| DomNode {
+            computed_style: Some(ComputedStyle {
+                color: Some(color.to_string()),
+                ..ComputedStyle::default()
+            }),
+            ..element(id, "p", Some("Text"), &[])
+        };
+        let mut view = view_with_boxes(Vec::new());
+        view.dom.as_mut().unwrap().nodes = vec![
+            colored("a", "#222222"),
+            colored("b", "#262626"),
+            colored("c", "#0055ff"),
+            colored("d", "#0057ff"),
+            colored("e", "#e53935"),
+        ];
+
+        let (finding, penalty) = palette_heuristic(&view, &PaletteSprawl::default()).unwrap();
+        assert_eq!(finding.severity, FindingSeverity::Info);
+        assert_eq!(penalty, 0.0);
+        assert!(finding
+            .message
+            .starts_with("Uses 4 distinct colors (budget 16)"));
+        assert!(finding.message.contains("#222222/#262626"));
+
+        let tight = PaletteSprawl {
+            budget: 2,
+            ..PaletteSprawl::default()
+        };
+        let (finding, penalty) = palette_heuristic(&view, &tight).unwrap();
+        assert_eq!(finding.severity, FindingSeverity::Warning);
+        assert_eq!(finding.finding_type, QualityFindingType::PaletteSprawl);
+        assert!((penalty - 0.04).abs() < 1e-6);
+        assert!(finding.message.contains("2 over the budget of 2"));
+    }
+
     #[test]
     fn flags_low_contrast_text_against_background() {
         let tmp = tempfile::Builder::new()
@@ -1053,17 +1743,22 @@ mod tests {
                 width: 80.0,
                 height: 20.0,
             },
+            text_baseline: None,
+            interaction_states: None,
             computed_style: Some(ComputedStyle {
                 font_family: None,
+                rendered_font_family: None,
                 font_size: None,
                 font_weight: None,
                 line_height: None,
                 letter_spacing: None,
                 color: Some("rgb(210, 210, 210)".to_string()),
                 background_color: None,
+                background_image: None,
                 display: None,
                 visibility: None,
                 opacity: Some(1.0),
+                flex: None,
             }),
         };
 
@@ -1073,21 +1768,21 @@ mod tests {
             width: 120,
             height: 80,
             dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
                 url: None,
                 title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
                 nodes: vec![node],
             }),
             figma_tree: None,
             ocr_blocks: None,
+            content_area: None,
         };
 
-        let (_score, findings) = score_quality(
-            &view,
-            &Viewport {
-                width: 120,
-                height: 80,
-            },
-        );
+        let (_score, findings) =
+            score_quality(&view, &Viewport::new(120, 80), &PaletteSprawl::default());
         let finding = findings
             .iter()
             .find(|f| matches!(f.finding_type, QualityFindingType::LowContrast))
@@ -1127,17 +1822,22 @@ mod tests {
                 width: 50.0,
                 height: 18.0,
             },
+            text_baseline: None,
+            interaction_states: None,
             computed_style: Some(ComputedStyle {
                 font_family: None,
+                rendered_font_family: None,
                 font_size: None,
                 font_weight: None,
                 line_height: None,
                 letter_spacing: None,
                 color: Some("rgb(30, 30, 30)".to_string()),
                 background_color: None,
+                background_image: None,
                 display: None,
                 visibility: None,
                 opacity: Some(1.0),
+                flex: None,
             }),
         };
 
@@ -1147,21 +1847,21 @@ mod tests {
             width: 100,
             height: 60,
             dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
                 url: None,
                 title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
                 nodes: vec![node],
             }),
             figma_tree: None,
             ocr_blocks: None,
+            content_area: None,
         };
 
-        let (_score, findings) = score_quality(
-            &view,
-            &Viewport {
-                width: 100,
-                height: 60,
-            },
-        );
+        let (_score, findings) =
+            score_quality(&view, &Viewport::new(100, 60), &PaletteSprawl::default());
         let finding = findings
             .iter()
             .find(|f| matches!(f.finding_type, QualityFindingType::LowContrast))
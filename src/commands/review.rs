@@ -0,0 +1,457 @@
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use dpc_lib::types::RegionBounds;
+use dpc_lib::{AcceptedFindings, CompareOutput, DpcError, Finding};
+use image::RgbaImage;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::OutputFormat;
+use crate::formatting::render_error;
+
+const RESULT_FILE: &str = "result.json";
+const ACCEPTED_FILE: &str = "accepted.json";
+
+/// Run `dpc review <artifacts-dir>`.
+pub fn run_review(dir: PathBuf) -> ExitCode {
+    match review(&dir) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Pretty, None),
+    }
+}
+
+/// A finding and whether the user accepted it.
+#[derive(Debug, Clone, PartialEq)]
+struct ReviewItem {
+    finding: Finding,
+    accepted: bool,
+}
+
+struct ReviewState {
+    items: Vec<ReviewItem>,
+    /// Accepted entries from an earlier review that this run no longer reports
+    /// (masked pixel regions, diffs that did not recur); kept when saving.
+    carried_over: Vec<Finding>,
+    list: ListState,
+    preview: Option<RgbaImage>,
+    header: String,
+}
+
+impl ReviewState {
+    fn new(result: &CompareOutput, previous: AcceptedFindings, preview: Option<RgbaImage>) -> Self {
+        let items: Vec<ReviewItem> = Finding::collect(&result.metrics)
+            .into_iter()
+            .map(|finding| ReviewItem {
+                accepted: previous.is_accepted(&finding),
+                finding,
+            })
+            .collect();
+        let carried_over = previous
+            .accepted
+            .into_iter()
+            .filter(|accepted| !items.iter().any(|item| accepted.covers(&item.finding)))
+            .collect();
+        let mut list = ListState::default();
+        list.select((!items.is_empty()).then_some(0));
+        Self {
+            items,
+            carried_over,
+            list,
+            preview,
+            header: format!(
+                "{} vs {} — similarity {:.3} (threshold {:.2}, {})",
+                result.ref_resource.value,
+                result.impl_resource.value,
+                result.similarity,
+                result.threshold,
+                if result.passed { "passed" } else { "failed" }
+            ),
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(item) = self.list.selected().and_then(|idx| self.items.get_mut(idx)) {
+            item.accepted = !item.accepted;
+        }
+    }
+
+    fn accepted(&self) -> AcceptedFindings {
+        AcceptedFindings {
+            accepted: self
+                .carried_over
+                .iter()
+                .cloned()
+                .chain(
+                    self.items
+                        .iter()
+                        .filter(|item| item.accepted)
+                        .map(|item| item.finding.clone()),
+                )
+                .collect(),
+        }
+    }
+}
+
+fn review(dir: &Path) -> Result<(), DpcError> {
+    let result = load_result(dir)?;
+    let accepted_path = dir.join(ACCEPTED_FILE);
+    let previous = if accepted_path.is_file() {
+        AcceptedFindings::load(&accepted_path)?
+    } else {
+        AcceptedFindings::default()
+    };
+    let mut state = ReviewState::new(&result, previous, load_preview(dir, &result));
+    if state.items.is_empty() && state.carried_over.is_empty() {
+        println!("No findings to review in {}", dir.display());
+        return Ok(());
+    }
+    if !std::io::stdout().is_terminal() {
+        return Err(DpcError::Config(
+            "dpc review needs an interactive terminal".to_string(),
+        ));
+    }
+
+    let mut terminal = ratatui::init();
+    let outcome = event_loop(&mut terminal, &mut state);
+    ratatui::restore();
+
+    if outcome? {
+        state.accepted().save(&accepted_path)?;
+        println!(
+            "Saved {} accepted finding(s) to {}",
+            state.accepted().accepted.len(),
+            accepted_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn load_result(dir: &Path) -> Result<CompareOutput, DpcError> {
+    let path = dir.join(RESULT_FILE);
+    if !path.is_file() {
+        return Err(DpcError::Config(format!(
+            "No compare result at {} (run `dpc compare --artifacts-dir {}` first)",
+            path.display(),
+            dir.display()
+        )));
+    }
+    super::diff_results::load_result(&path)
+}
+
+/// The diff heatmap if it was written, otherwise the implementation screenshot.
+fn load_preview(dir: &Path, result: &CompareOutput) -> Option<RgbaImage> {
+    let artifacts = result.artifacts.as_ref()?;
+    [&artifacts.diff_image, &artifacts.impl_screenshot]
+        .into_iter()
+        .flatten()
+        .flat_map(|path| {
+            // Prefer the copy inside `dir` so moved artifact directories still work.
+            let local = path.file_name().map(|name| dir.join(name));
+            local.into_iter().chain(std::iter::once(path.clone()))
+        })
+        .find_map(|path| image::open(path).ok())
+        .map(|img| img.to_rgba8())
+}
+
+/// Returns whether accepted findings should be saved.
+fn event_loop(terminal: &mut DefaultTerminal, state: &mut ReviewState) -> Result<bool, DpcError> {
+    loop {
+        terminal.draw(|frame| draw(frame, state))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') => return Ok(true),
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            KeyCode::Down | KeyCode::Char('j') => state.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => state.list.select_previous(),
+            KeyCode::Char(' ') | KeyCode::Enter => state.toggle(),
+            KeyCode::Char('a') => state.items.iter_mut().for_each(|item| item.accepted = true),
+            KeyCode::Char('n') => state
+                .items
+                .iter_mut()
+                .for_each(|item| item.accepted = false),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, state: &mut ReviewState) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    frame.render_widget(
+        Paragraph::new(state.header.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
+        rows[0],
+    );
+
+    let items: Vec<ListItem> = state
+        .items
+        .iter()
+        .map(|item| {
+            let mark = if item.accepted { "[x] " } else { "[ ] " };
+            let style = if item.accepted {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(mark),
+                Span::styled(item.finding.describe(), style),
+            ]))
+        })
+        .collect();
+    let accepted = state.items.iter().filter(|item| item.accepted).count();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " Findings ({}/{} accepted) ",
+            accepted,
+            state.items.len()
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, columns[0], &mut state.list);
+
+    let selected = state
+        .list
+        .selected()
+        .and_then(|idx| state.items.get(idx))
+        .map(|item| &item.finding);
+    let preview_block = Block::default().borders(Borders::ALL).title(" Preview ");
+    let inner = preview_block.inner(columns[1]);
+    let preview = match (&state.preview, selected) {
+        (Some(img), Some(finding)) => Paragraph::new(preview_lines(
+            img,
+            finding.region(),
+            inner.width as u32,
+            inner.height as u32,
+        )),
+        (None, _) => Paragraph::new("No screenshot or heatmap in the artifacts directory"),
+        (_, None) => Paragraph::new(""),
+    };
+    frame.render_widget(
+        preview.block(preview_block).wrap(Wrap { trim: false }),
+        columns[1],
+    );
+
+    frame.render_widget(
+        Paragraph::new(
+            "j/k move  space toggle  a accept all  n clear all  q save & quit  esc quit without saving",
+        )
+        .style(Style::default().fg(Color::DarkGray)),
+        rows[2],
+    );
+}
+
+/// Render the part of `img` around `region` as half-block cells: each cell's
+/// foreground is the upper pixel and its background the lower one.
+fn preview_lines(
+    img: &RgbaImage,
+    region: Option<RegionBounds>,
+    cols: u32,
+    rows: u32,
+) -> Vec<Line<'static>> {
+    preview_cells(img, region, cols, rows)
+        .into_iter()
+        .map(|row| {
+            Line::from(
+                row.into_iter()
+                    .map(|(top, bottom)| {
+                        Span::styled(
+                            "▀",
+                            Style::default()
+                                .fg(Color::Rgb(top[0], top[1], top[2]))
+                                .bg(Color::Rgb(bottom[0], bottom[1], bottom[2])),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+/// Sample `(top, bottom)` colors for a `cols` × `rows` grid of half-block cells
+/// covering `region` plus some context, or the whole image without a region.
+fn preview_cells(
+    img: &RgbaImage,
+    region: Option<RegionBounds>,
+    cols: u32,
+    rows: u32,
+) -> Vec<Vec<([u8; 3], [u8; 3])>> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 || cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+    // Layout regions in DOM/Figma coordinates cannot be placed on the image.
+    let region = region
+        .filter(|r| r.x + r.width <= 1.0 + f32::EPSILON && r.y + r.height <= 1.0 + f32::EPSILON);
+    let (x0, y0, x1, y1) = match region {
+        Some(r) => {
+            // Pad by half the region size so the diff is seen in context.
+            let pad_x = r.width * 0.5;
+            let pad_y = r.height * 0.5;
+            (
+                ((r.x - pad_x).max(0.0) * width as f32) as u32,
+                ((r.y - pad_y).max(0.0) * height as f32) as u32,
+                ((r.x + r.width + pad_x).min(1.0) * width as f32).ceil() as u32,
+                ((r.y + r.height + pad_y).min(1.0) * height as f32).ceil() as u32,
+            )
+        }
+        None => (0, 0, width, height),
+    };
+    let crop_w = x1.saturating_sub(x0).max(1);
+    let crop_h = y1.saturating_sub(y0).max(1);
+    // Each cell is two pixels tall; fit the crop while keeping its aspect ratio.
+    let scale = (cols as f32 / crop_w as f32).min((rows * 2) as f32 / crop_h as f32);
+    let grid_w = ((crop_w as f32 * scale).round() as u32).clamp(1, cols);
+    let grid_h = ((crop_h as f32 * scale / 2.0).round() as u32).clamp(1, rows);
+
+    let sample = |gx: u32, gy: u32| -> [u8; 3] {
+        let px = (x0 + gx * crop_w / grid_w).min(width - 1);
+        let py = (y0 + gy * crop_h / (grid_h * 2)).min(height - 1);
+        let p = img.get_pixel(px, py).0;
+        [p[0], p[1], p[2]]
+    };
+    (0..grid_h)
+        .map(|row| {
+            (0..grid_w)
+                .map(|col| (sample(col, row * 2), sample(col, row * 2 + 1)))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpc_lib::output::DPC_OUTPUT_VERSION;
+    use dpc_lib::types::{ContentMetric, MetricScores, ResourceKind};
+    use dpc_lib::{ResourceDescriptor, Viewport};
+
+    fn result() -> CompareOutput {
+        CompareOutput {
+            version: DPC_OUTPUT_VERSION.to_string(),
+            ref_resource: ResourceDescriptor {
+                kind: ResourceKind::Image,
+                value: "ref.png".to_string(),
+            },
+            impl_resource: ResourceDescriptor {
+                kind: ResourceKind::Image,
+                value: "impl.png".to_string(),
+            },
+            viewport: Viewport::new(800, 600),
+            browser: None,
+            emulation: None,
+            similarity: 0.8,
+            threshold: 0.95,
+            passed: false,
+            metrics: MetricScores {
+                pixel: None,
+                layout: None,
+                typography: None,
+                color: None,
+                content: Some(ContentMetric {
+                    missing_text_fingerprints: Vec::new(),
+                    extra_text_fingerprints: Vec::new(),
+                    score: 0.5,
+                    missing_text: vec!["Sign up".to_string()],
+                    extra_text: vec!["Beta".to_string()],
+                    placeholder_text: Vec::new(),
+                    text_labels: Default::default(),
+                }),
+                semantic: None,
+            },
+            summary: None,
+            artifacts: None,
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
+        }
+    }
+
+    #[test]
+    fn previous_acceptance_is_restored_and_kept() {
+        let previous = AcceptedFindings {
+            accepted: vec![
+                Finding::ExtraText {
+                    text: "Beta".to_string(),
+                },
+                Finding::MissingText {
+                    text: "Old copy".to_string(),
+                },
+            ],
+        };
+        let mut state = ReviewState::new(&result(), previous, None);
+        assert_eq!(
+            state
+                .items
+                .iter()
+                .map(|item| item.accepted)
+                .collect::<Vec<_>>(),
+            vec![false, true]
+        );
+
+        state.toggle();
+        let saved = state.accepted();
+        assert_eq!(saved.accepted.len(), 3);
+        assert!(saved.accepted.contains(&Finding::MissingText {
+            text: "Old copy".to_string()
+        }));
+        assert!(saved.is_accepted(&Finding::MissingText {
+            text: "Sign up".to_string()
+        }));
+    }
+
+    #[test]
+    fn preview_crops_to_the_region() {
+        let mut img = RgbaImage::from_pixel(100, 100, image::Rgba([255, 255, 255, 255]));
+        for y in 40..60 {
+            for x in 40..60 {
+                img.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+            }
+        }
+        let region = RegionBounds {
+            x: 0.4,
+            y: 0.4,
+            width: 0.2,
+            height: 0.2,
+        };
+        let cells = preview_cells(&img, Some(region), 20, 10);
+        assert_eq!((cells.len(), cells[0].len()), (10, 20));
+        // Centre is the diff, corners are the padding around it.
+        assert_eq!(cells[5][10].0, [255, 0, 0]);
+        assert_eq!(cells[0][0].0, [255, 255, 255]);
+
+        let whole = preview_cells(&img, None, 10, 10);
+        assert_eq!((whole.len(), whole[0].len()), (5, 10));
+    }
+}
@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dpc_lib::output::output_schema;
+use dpc_lib::DpcError;
+
+use crate::cli::OutputFormat;
+use crate::formatting::render_error;
+
+/// Run `dpc schema [--output PATH]`: print the JSON Schema of the output.
+pub fn run_schema(output: Option<PathBuf>) -> ExitCode {
+    let mut schema = serde_json::to_string_pretty(&output_schema()).expect("schema serializes");
+    schema.push('\n');
+    let result = match &output {
+        Some(path) => std::fs::write(path, schema).map_err(DpcError::from),
+        None => {
+            print!("{schema}");
+            Ok(())
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => render_error(err, OutputFormat::Json, output),
+    }
+}
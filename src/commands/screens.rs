@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256};
+
+use dpc_lib::config::MetricsConfig;
+use dpc_lib::{
+    calculate_combined_score, generate_top_issues_with, run_metrics, BrowserEngine, Emulation,
+    Metric, MetricKind, MetricScores, NormalizedView, ParsedResource, PixelSimilarity,
+    RequestRules, SeverityCalibration, Viewport,
+};
+
+use crate::pipeline::resource_to_normalized_view;
+use crate::progress::ProgressCallback;
+use crate::settings::ResolvedCompareSettings;
+
+/// Scores of one reference/implementation pair.
+pub(super) struct ScreenScore {
+    pub similarity: f32,
+    pub metrics: MetricScores,
+    pub top_issues: Vec<String>,
+}
+
+/// Renders and scores reference/implementation pairs one after another, for
+/// commands that compare many screens (`flow`, `audit`).
+///
+/// Both sides of a pair render concurrently, and a resource that appears in
+/// several pairs (one URL compared against different frames, one frame
+/// routed to several pages) is captured once and its view reused.
+pub(super) struct ScreenScorer {
+    settings: ResolvedCompareSettings,
+    requests: RequestRules,
+    engine: BrowserEngine,
+    emulation: Emulation,
+    severity: SeverityCalibration,
+    selected: Vec<MetricKind>,
+    metrics: Vec<Box<dyn Metric>>,
+    artifacts_dir: PathBuf,
+    progress: Option<ProgressCallback>,
+    /// Hash of the capture options shared by every render.
+    capture_options: String,
+    captures: Mutex<HashMap<String, NormalizedView>>,
+}
+
+impl ScreenScorer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        settings: ResolvedCompareSettings,
+        requests: RequestRules,
+        engine: BrowserEngine,
+        emulation: Emulation,
+        severity: SeverityCalibration,
+        metric_config: &MetricsConfig,
+        selected: Vec<MetricKind>,
+        artifacts_dir: &Path,
+        verbose: bool,
+    ) -> Self {
+        let metrics: Vec<Box<dyn Metric>> = vec![
+            Box::new(PixelSimilarity {
+                alignment: settings.pixel_alignment,
+                thresholds: severity.pixel,
+                ..metric_config.pixel.build()
+            }),
+            Box::new(metric_config.layout.build()),
+            Box::new(metric_config.typography.build()),
+            Box::new(metric_config.color.build()),
+            Box::new(metric_config.content.build()),
+            Box::new(metric_config.semantic.build()),
+        ];
+        let progress: Option<ProgressCallback> = if verbose {
+            Some(Arc::new(|msg: &str| eprintln!("{msg}")))
+        } else {
+            None
+        };
+        let capture_options = capture_options_hash(&settings, &requests, engine, &emulation);
+        Self {
+            settings,
+            requests,
+            engine,
+            emulation,
+            severity,
+            selected,
+            metrics,
+            artifacts_dir: artifacts_dir.to_path_buf(),
+            progress,
+            capture_options,
+            captures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Render both sides as `ref_step<step>` / `impl_step<step>` and score
+    /// them; the error says which side failed.
+    pub async fn score(
+        &self,
+        reference: &ParsedResource,
+        implementation: &ParsedResource,
+        step: usize,
+    ) -> Result<ScreenScore, String> {
+        let (ref_view, impl_view) = futures::future::try_join(
+            self.view(reference, "ref", step),
+            self.view(implementation, "impl", step),
+        )
+        .await?;
+        let (ref_view, impl_view) = (&ref_view, &impl_view);
+
+        let effective_metrics =
+            if self.selected.is_empty() && ref_view.dom.is_none() && impl_view.dom.is_none() {
+                vec![MetricKind::Pixel, MetricKind::Color]
+            } else {
+                self.selected.clone()
+            };
+        let metrics = run_metrics(&self.metrics, &effective_metrics, ref_view, impl_view)
+            .map_err(|err| format!("failed to compute metrics: {err}"))?;
+        Ok(ScreenScore {
+            similarity: calculate_combined_score(&metrics, &self.settings.weights),
+            top_issues: generate_top_issues_with(&metrics, 3, &self.severity),
+            metrics,
+        })
+    }
+
+    /// The view of `resource`, reused when an earlier pair already captured
+    /// it and rendered as `<side>_step<step>` otherwise.
+    async fn view(
+        &self,
+        resource: &ParsedResource,
+        side: &str,
+        step: usize,
+    ) -> Result<NormalizedView, String> {
+        let key = capture_key(resource, &self.settings.viewport, &self.capture_options);
+        let cached = self.captures.lock().unwrap().get(&key).cloned();
+        if let Some(view) = cached {
+            if let Some(progress) = &self.progress {
+                progress(&format!(
+                    "Reusing the capture of {} for {side}",
+                    resource.display_value()
+                ));
+            }
+            return Ok(view);
+        }
+        let view = resource_to_normalized_view(
+            resource,
+            &self.settings.viewport,
+            &self.artifacts_dir,
+            &format!("{side}_step{step}"),
+            self.progress.clone(),
+            self.settings.nav_timeout,
+            self.settings.network_idle_timeout,
+            self.settings.process_timeout,
+            self.engine,
+            false,
+            &self.requests,
+            &[],
+            &self.emulation,
+            false,
+        )
+        .await
+        .map_err(|err| format!("failed to render {side}: {err}"))?;
+        self.captures.lock().unwrap().insert(key, view.clone());
+        Ok(view)
+    }
+}
+
+/// Hash of the options that change what a capture looks like, apart from the
+/// resource and viewport.
+fn capture_options_hash(
+    settings: &ResolvedCompareSettings,
+    requests: &RequestRules,
+    engine: BrowserEngine,
+    emulation: &Emulation,
+) -> String {
+    let mut hasher = Sha256::new();
+    for timeout in [
+        settings.nav_timeout,
+        settings.network_idle_timeout,
+        settings.process_timeout,
+    ] {
+        hasher.update(timeout.to_le_bytes());
+    }
+    hasher.update(serde_json::to_vec(requests).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&engine).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(emulation).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+/// Capture cache key: the resource (including its Figma frame), the viewport
+/// and the capture options hash.
+fn capture_key(resource: &ParsedResource, viewport: &Viewport, options: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{resource:?}"));
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(viewport).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(options);
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpc_lib::image_alignment::ImageAlignmentOptions;
+    use dpc_lib::{parse_resource, ScoreWeights};
+
+    #[test]
+    fn capture_key_separates_frames_viewports_and_options() {
+        let viewport = Viewport {
+            width: 1440,
+            height: 900,
+            device_scale_factor: 1.0,
+        };
+        let key = |value: &str, viewport: &Viewport, options: &str| {
+            capture_key(&parse_resource(value, None).unwrap(), viewport, options)
+        };
+        let url = "https://app.example.test/checkout";
+        assert_eq!(key(url, &viewport, "a"), key(url, &viewport, "a"));
+        assert_ne!(key(url, &viewport, "a"), key(url, &viewport, "b"));
+        let mobile = Viewport {
+            width: 390,
+            height: 844,
+            ..viewport
+        };
+        assert_ne!(key(url, &viewport, "a"), key(url, &mobile, "a"));
+        assert_ne!(
+            key(
+                "https://www.figma.com/file/abc/Shop?node-id=1-1",
+                &viewport,
+                "a"
+            ),
+            key(
+                "https://www.figma.com/file/abc/Shop?node-id=1-2",
+                &viewport,
+                "a"
+            )
+        );
+    }
+
+    #[test]
+    fn capture_options_separate_engines_and_emulation() {
+        let settings = ResolvedCompareSettings {
+            viewport: Viewport::new(1440, 900),
+            threshold: 0.95,
+            nav_timeout: 30,
+            network_idle_timeout: 10,
+            process_timeout: 45,
+            weights: ScoreWeights::default(),
+            pixel_alignment: ImageAlignmentOptions::default(),
+        };
+        let requests = RequestRules::default();
+        let hash = |engine: BrowserEngine, emulation: &Emulation| {
+            capture_options_hash(&settings, &requests, engine, emulation)
+        };
+        let default = Emulation::default();
+        let german = Emulation {
+            locale: Some("de-DE".to_string()),
+            ..Emulation::default()
+        };
+        assert_eq!(
+            hash(BrowserEngine::Chromium, &default),
+            hash(BrowserEngine::Chromium, &default)
+        );
+        assert_ne!(
+            hash(BrowserEngine::Chromium, &default),
+            hash(BrowserEngine::Firefox, &default)
+        );
+        assert_ne!(
+            hash(BrowserEngine::Chromium, &default),
+            hash(BrowserEngine::Chromium, &german)
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use dpc_lib::output::DPC_OUTPUT_VERSION;
+use dpc_lib::types::ResourceKind;
+use dpc_lib::{
+    assign_fingerprints, calculate_combined_score, parse_resource, run_metrics, store_from_spec,
+    write_artifact_index, write_overlay, BrowserEngine, CompareOutput, DpcError, DpcOutput,
+    Emulation, Geolocation, Metric, MetricKind, ParsedResource, PixelSimilarity,
+    ResourceDescriptor, Viewport,
+};
+
+use super::compare::parse_metric_kinds;
+use crate::cache::{cached_scores, ResultCache};
+use crate::cli::OutputFormat;
+use crate::formatting::{exit_code_for_compare, render_error, write_output};
+use crate::manifest::{persist_run_manifest, ManifestInput, ManifestInputs, RunManifest};
+use crate::pipeline::{
+    apply_dom_ignores, apply_ignore_regions, check_init_scripts, generate_summary,
+    load_baseline_view, load_ignore_regions, parse_ignore_selectors, persist_compare_artifacts,
+    resolve_artifacts_dir, resource_to_normalized_view, upload_compare_artifacts,
+    validate_ignore_selectors, HeatmapPalette,
+};
+use crate::progress::ProgressCallback;
+use crate::settings::{load_config, resolve_compare_settings, CompareFlagSources};
+
+/// Run the self-compare command: a fresh capture of `url` against a stored baseline.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_self_compare(
+    raw_args: &[String],
+    config_path: Option<PathBuf>,
+    verbose: bool,
+    url: String,
+    baseline: PathBuf,
+    viewport: Viewport,
+    threshold: f64,
+    metrics: Option<Vec<String>>,
+    ignore_selectors: Option<String>,
+    ignore_regions: Option<PathBuf>,
+    format: OutputFormat,
+    output: Option<PathBuf>,
+    keep_artifacts: bool,
+    artifacts_dir: Option<PathBuf>,
+    artifact_store: Option<String>,
+    cache_dir: Option<PathBuf>,
+    nav_timeout: u64,
+    network_idle_timeout: u64,
+    process_timeout: u64,
+    init_scripts: Vec<PathBuf>,
+    locale: Option<String>,
+    timezone: Option<String>,
+    geolocation: Option<Geolocation>,
+    grant_permissions: Option<String>,
+    reduced_motion: bool,
+    forced_colors: bool,
+) -> ExitCode {
+    let config = match load_config(config_path.as_deref()) {
+        Ok(cfg) => cfg,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    let resolved = resolve_compare_settings(
+        viewport,
+        threshold,
+        nav_timeout,
+        network_idle_timeout,
+        process_timeout,
+        None,
+        None,
+        None,
+        None,
+        &config,
+        &CompareFlagSources::from_args(raw_args),
+    );
+    let viewport = resolved.viewport;
+    let threshold = resolved.threshold;
+    if let Err(err) = check_init_scripts(&init_scripts) {
+        return render_error(err, format, output.clone());
+    }
+    let emulation = Emulation {
+        locale,
+        timezone,
+        geolocation,
+        reduced_motion,
+        forced_colors,
+        ..Emulation::default()
+    }
+    .grant_permissions(grant_permissions.as_deref().unwrap_or_default());
+    if let Err(err) = emulation.validate() {
+        return render_error(DpcError::Config(err), format, output.clone());
+    }
+
+    let url_res = match parse_resource(&url, Some(ResourceKind::Url)) {
+        Ok(res) => res,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+    let selected_metrics = match parse_metric_kinds(metrics.as_deref()) {
+        Ok(k) => k,
+        Err(err) => return render_error(DpcError::Config(err.to_string()), format, output.clone()),
+    };
+    let ignore_selectors = parse_ignore_selectors(ignore_selectors.as_deref());
+    if let Err(err) = validate_ignore_selectors(&ignore_selectors) {
+        return render_error(err, format, output.clone());
+    }
+    let ignore_regions = match ignore_regions {
+        Some(path) => match load_ignore_regions(&path) {
+            Ok(regions) => regions,
+            Err(err) => return render_error(err, format, output.clone()),
+        },
+        None => Vec::new(),
+    };
+    let artifact_store = match artifact_store.as_deref().map(store_from_spec) {
+        Some(Ok(store)) => Some(store),
+        Some(Err(err)) => return render_error(err, format, output.clone()),
+        None => None,
+    };
+
+    if verbose {
+        eprintln!("Loading baseline from {}\u{2026}", baseline.display());
+    }
+    let baseline_view = match load_baseline_view(&baseline) {
+        Ok(view) => view,
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+
+    let (artifacts_dir, artifacts_from_cli) = resolve_artifacts_dir(artifacts_dir.as_deref());
+    if same_dir(&artifacts_dir, &baseline) {
+        return render_error(
+            DpcError::Config(
+                "--artifacts-dir must differ from --baseline; the new capture would overwrite the baseline"
+                    .to_string(),
+            ),
+            format,
+            output.clone(),
+        );
+    }
+    if let Err(err) = std::fs::create_dir_all(&artifacts_dir) {
+        return render_error(DpcError::Io(err), format, output.clone());
+    }
+    let should_keep_artifacts = keep_artifacts || artifacts_from_cli;
+    let progress_logger: Option<ProgressCallback> = if verbose {
+        Some(Arc::new(|msg: &str| eprintln!("{msg}")))
+    } else {
+        None
+    };
+
+    if verbose {
+        eprintln!("Capturing {}\u{2026}", url_res.value);
+    }
+    let current_view = match resource_to_normalized_view(
+        &url_res,
+        &viewport,
+        &artifacts_dir,
+        "impl",
+        progress_logger,
+        resolved.nav_timeout,
+        resolved.network_idle_timeout,
+        resolved.process_timeout,
+        BrowserEngine::default(),
+        false,
+        &config.requests,
+        &init_scripts,
+        &emulation,
+        false,
+    )
+    .await
+    {
+        Ok(view) => view,
+        Err(err) => {
+            return render_error(
+                DpcError::Config(format!("Failed to capture URL: {}", err)),
+                format,
+                output.clone(),
+            )
+        }
+    };
+
+    let mut views = Vec::with_capacity(2);
+    for (view, prefix) in [(&baseline_view, "baseline"), (&current_view, "impl")] {
+        let view = apply_dom_ignores(view, &ignore_selectors);
+        let view = if ignore_regions.is_empty() {
+            view
+        } else {
+            match apply_ignore_regions(&view, &ignore_regions, &artifacts_dir, prefix) {
+                Ok(view) => view,
+                Err(err) => return render_error(err, format, output.clone()),
+            }
+        };
+        views.push(view);
+    }
+    let (ref_view, impl_view) = (&views[0], &views[1]);
+
+    let severity = config.severity.calibration();
+    let all_metrics: Vec<Box<dyn Metric>> = vec![
+        Box::new(PixelSimilarity {
+            alignment: resolved.pixel_alignment,
+            thresholds: severity.pixel,
+            ..config.metrics.pixel.build()
+        }),
+        Box::new(config.metrics.layout.build()),
+        Box::new(config.metrics.typography.build()),
+        Box::new(config.metrics.color.build()),
+        Box::new(config.metrics.content.build()),
+        Box::new(config.metrics.semantic.build()),
+    ];
+    let effective_metrics =
+        if selected_metrics.is_empty() && ref_view.dom.is_none() && impl_view.dom.is_none() {
+            vec![MetricKind::Pixel, MetricKind::Color]
+        } else {
+            selected_metrics
+        };
+    if verbose {
+        eprintln!("Running metrics: {:?}", effective_metrics);
+    }
+    let cache = cache_dir.map(ResultCache::new);
+    let mut metrics_scores = match cached_scores(
+        cache.as_ref(),
+        ref_view,
+        impl_view,
+        &effective_metrics,
+        &format!("{:?}", resolved.pixel_alignment),
+        || run_metrics(&all_metrics, &effective_metrics, ref_view, impl_view),
+    ) {
+        Ok((scores, cached)) => {
+            if cached && verbose {
+                eprintln!("Inputs unchanged; reusing cached metric scores");
+            }
+            scores
+        }
+        Err(err) => {
+            return render_error(
+                DpcError::Config(format!("Failed to compute metrics: {}", err)),
+                format,
+                output.clone(),
+            )
+        }
+    };
+
+    assign_fingerprints(&mut metrics_scores);
+
+    let similarity = calculate_combined_score(&metrics_scores, &resolved.weights);
+    let passed = similarity >= threshold as f32;
+    let summary = generate_summary(&metrics_scores, similarity, threshold as f32, &severity);
+
+    let mut artifacts = match persist_compare_artifacts(
+        &artifacts_dir,
+        ref_view,
+        impl_view,
+        should_keep_artifacts || artifact_store.is_some(),
+        resolved.pixel_alignment,
+        HeatmapPalette::default(),
+        config.artifacts.dom_compression,
+    ) {
+        Ok(paths) => Some(paths),
+        Err(err) => return render_error(err, format, output.clone()),
+    };
+    if let Some(paths) = artifacts.as_mut() {
+        let baseline_res = ParsedResource {
+            kind: ResourceKind::Image,
+            value: baseline.display().to_string(),
+            figma_info: None,
+        };
+        let manifest = RunManifest::new(
+            "self-compare",
+            raw_args,
+            config_path.as_deref(),
+            &resolved,
+            &metrics_scores,
+            ManifestInputs {
+                reference: ManifestInput::new(&baseline_res, &baseline_view),
+                implementation: ManifestInput::new(&url_res, &current_view),
+            },
+        );
+        if let Err(err) = persist_run_manifest(paths, &manifest) {
+            return render_error(err, format, output.clone());
+        }
+        if let Err(err) = write_overlay(
+            paths,
+            &baseline_view,
+            &current_view,
+            &metrics_scores,
+            &severity,
+        ) {
+            return render_error(err, format, output.clone());
+        }
+        if let Err(err) = write_artifact_index(paths, &metrics_scores, &severity) {
+            return render_error(err, format, output.clone());
+        }
+    }
+    if let (Some(store), Some(paths)) = (&artifact_store, artifacts.as_mut()) {
+        if let Err(err) = upload_compare_artifacts(store.as_ref(), paths).await {
+            return render_error(err, format, output.clone());
+        }
+    }
+    if let Some(paths) = artifacts
+        .as_ref()
+        .filter(|paths| paths.kept && should_keep_artifacts)
+    {
+        eprintln!(
+            "Artifacts directory: {} (use as --baseline for the next run)",
+            paths.directory.display()
+        );
+    }
+
+    let body = DpcOutput::Compare(CompareOutput {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        ref_resource: ResourceDescriptor {
+            kind: ResourceKind::Image,
+            value: baseline.display().to_string(),
+        },
+        impl_resource: ResourceDescriptor {
+            kind: url_res.kind,
+            value: url_res.display_value(),
+        },
+        viewport,
+        browser: current_view.dom.as_ref().and_then(|dom| dom.browser),
+        emulation: (!emulation.is_empty()).then_some(emulation),
+        similarity,
+        threshold: threshold as f32,
+        passed,
+        metrics: metrics_scores,
+        summary: Some(summary),
+        artifacts,
+        reference_candidates: None,
+        warnings: Vec::new(),
+        suppressed: Vec::new(),
+        page_diagnostics: None,
+        skipped_metrics: Vec::new(),
+        sampling: None,
+        dimension_mismatch: None,
+        sections: Vec::new(),
+        degradation: None,
+        figma_comments: Vec::new(),
+        variable_drift: None,
+    });
+
+    if let Err(err) = write_output(&body, format, output.clone()) {
+        return render_error(DpcError::Config(err.to_string()), format, output);
+    }
+
+    if !should_keep_artifacts {
+        let _ = std::fs::remove_dir_all(&artifacts_dir);
+    }
+
+    exit_code_for_compare(passed)
+}
+
+fn same_dir(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -1,8 +1,20 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::artifact_encoding::ArtifactEncoding;
+use crate::browser::RequestRules;
+use crate::image_alignment::{AlignmentMethod, ImageAlignmentOptions};
+use crate::metrics::{
+    ColorPaletteMetric, ContentSimilarity, DeltaEThresholds, ImageEncoder, LayoutSimilarity,
+    Metric, PaletteSprawl, PixelSimilarity, SemanticSimilarity, SeverityCalibration,
+    SeverityProfile, TypographySimilarity,
+};
+use crate::resource_guard::ResourceLimits;
+use crate::types::{ColorDiffKind, DiffSeverity, LayoutDiffKind, TypographyIssue};
 use crate::Viewport;
 use serde::de::{self, Deserializer};
 use serde::{Deserialize, Serialize};
@@ -19,7 +31,16 @@ pub struct Config {
     pub metric_weights: MetricWeights,
     pub timeouts: Timeouts,
     pub semantic: SemanticConfig,
+    pub summarizer: SummarizerConfig,
     pub pixel_alignment: PixelAlignmentConfig,
+    pub severity: SeverityConfig,
+    pub artifacts: ArtifactEncoding,
+    pub requests: RequestRules,
+    pub dom: DomConfig,
+    pub metrics: MetricsConfig,
+    pub resources: ResourceLimits,
+    pub figma: FigmaConfig,
+    pub quality: QualityConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -34,12 +55,24 @@ pub struct SemanticConfig {
     pub min_intensity: Option<f32>,
 }
 
+/// Language model used by `compare --summarize`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SummarizerConfig {
+    pub api_key: Option<String>,
+    pub api_endpoint: Option<String>,
+    pub model: Option<String>,
+    /// Findings passed to the model, most severe first.
+    pub max_findings: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct PixelAlignmentConfig {
     pub enabled: bool,
     pub max_shift: u32,
     pub downscale_max_dim: u32,
+    pub method: AlignmentMethod,
 }
 
 impl Default for PixelAlignmentConfig {
@@ -48,6 +81,379 @@ impl Default for PixelAlignmentConfig {
             enabled: false,
             max_shift: 16,
             downscale_max_dim: 256,
+            method: AlignmentMethod::default(),
+        }
+    }
+}
+
+/// How findings are rated minor/moderate/major: a preset profile plus
+/// per-metric overrides.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SeverityConfig {
+    pub profile: SeverityProfile,
+    pub pixel: PixelSeverityThresholds,
+    pub layout: HashMap<LayoutDiffKind, DiffSeverity>,
+    pub typography: HashMap<TypographyIssue, DiffSeverity>,
+    pub color: HashMap<ColorDiffKind, DiffSeverity>,
+    /// Rate color findings by Delta E instead of by kind.
+    pub color_delta_e: Option<DeltaEThresholds>,
+    pub content: ContentSeverity,
+}
+
+/// Average per-block pixel difference (0.0-1.0) at which a region becomes
+/// minor, moderate or major.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PixelSeverityThresholds {
+    pub minor: Option<f32>,
+    pub moderate: Option<f32>,
+    pub major: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ContentSeverity {
+    pub missing_text: Option<DiffSeverity>,
+    pub extra_text: Option<DiffSeverity>,
+}
+
+impl SeverityConfig {
+    /// The profile's calibration with this section's overrides applied.
+    pub fn calibration(&self) -> SeverityCalibration {
+        let mut calibration = self.profile.calibration();
+        let pixel = &mut calibration.pixel;
+        pixel.minor = self.pixel.minor.unwrap_or(pixel.minor);
+        pixel.moderate = self.pixel.moderate.unwrap_or(pixel.moderate);
+        pixel.major = self.pixel.major.unwrap_or(pixel.major);
+        calibration
+            .layout
+            .extend(self.layout.iter().map(|(k, v)| (*k, *v)));
+        calibration
+            .typography
+            .extend(self.typography.iter().map(|(k, v)| (*k, *v)));
+        calibration
+            .color
+            .extend(self.color.iter().map(|(k, v)| (*k, *v)));
+        if self.color_delta_e.is_some() {
+            calibration.color_delta_e = self.color_delta_e;
+        }
+        if let Some(severity) = self.content.missing_text {
+            calibration.missing_text = severity;
+        }
+        if let Some(severity) = self.content.extra_text {
+            calibration.extra_text = severity;
+        }
+        calibration
+    }
+}
+
+impl MetricsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let unit = |name: &str, value: Option<f32>| match value {
+            Some(v) if !(0.0..=1.0).contains(&v) => {
+                Err(format!("metrics.{name} must be between 0.0 and 1.0"))
+            }
+            _ => Ok(()),
+        };
+        let non_negative = |name: &str, value: Option<f32>| match value {
+            Some(v) if !(v.is_finite() && v >= 0.0) => {
+                Err(format!("metrics.{name} must not be negative"))
+            }
+            _ => Ok(()),
+        };
+        if self.pixel.block_size == Some(0) {
+            return Err("metrics.pixel.block_size must be greater than zero".to_string());
+        }
+        if self.pixel.refine_block_size == Some(0) {
+            return Err("metrics.pixel.refine_block_size must be greater than zero".to_string());
+        }
+        unit(
+            "pixel.coverage_penalty_threshold",
+            self.pixel.coverage_penalty_threshold,
+        )?;
+        non_negative(
+            "pixel.coverage_penalty_scale",
+            self.pixel.coverage_penalty_scale,
+        )?;
+        unit(
+            "pixel.coverage_penalty_max",
+            self.pixel.coverage_penalty_max,
+        )?;
+        unit("layout.iou_threshold", self.layout.iou_threshold)?;
+        unit("layout.match_threshold", self.layout.match_threshold)?;
+        non_negative("typography.size_tolerance", self.typography.size_tolerance)?;
+        non_negative(
+            "typography.line_height_tolerance",
+            self.typography.line_height_tolerance,
+        )?;
+        non_negative(
+            "typography.letter_spacing_tolerance",
+            self.typography.letter_spacing_tolerance,
+        )?;
+        non_negative(
+            "typography.baseline_tolerance",
+            self.typography.baseline_tolerance,
+        )?;
+        non_negative(
+            "typography.rhythm_tolerance",
+            self.typography.rhythm_tolerance,
+        )?;
+        if self.color.clusters == Some(0) {
+            return Err("metrics.color.clusters must be greater than zero".to_string());
+        }
+        if self.color.sample_stride == Some(0) {
+            return Err("metrics.color.sample_stride must be greater than zero".to_string());
+        }
+        non_negative("color.saliency_weight", self.color.saliency_weight)?;
+        unit("content.match_threshold", self.content.match_threshold)?;
+        non_negative(
+            "content.extra_penalty_weight",
+            self.content.extra_penalty_weight,
+        )?;
+        match self.semantic.floor {
+            Some(floor) if !(0.0..1.0).contains(&floor) => {
+                Err("metrics.semantic.floor must be at least 0.0 and below 1.0".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Limits on captured DOM snapshots (`[dom]`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DomConfig {
+    /// Keep at most this many nodes per snapshot, in document order; the rest
+    /// are dropped with a warning. Unlimited when unset.
+    pub max_nodes: Option<usize>,
+}
+
+/// Figma REST API access (`[figma]`), for organizations that reach Figma
+/// through a proxy or an enterprise API gateway.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FigmaConfig {
+    /// Base URL of the API, path prefix included; `https://api.figma.com`
+    /// when unset.
+    pub api_url: Option<String>,
+    /// Environment variables holding personal access tokens, used in turn:
+    /// a rate-limited (429) request is retried with the next token. When
+    /// none of them is set, the usual `FIGMA_TOKEN` lookup applies.
+    pub token_env: Vec<String>,
+}
+
+impl FigmaConfig {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(api_url) = &self.api_url {
+            match url::Url::parse(api_url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => return Err(format!("figma.api_url is not an http(s) URL: {api_url}")),
+            }
+        }
+        if self.token_env.iter().any(|name| name.trim().is_empty()) {
+            return Err("figma.token_env entries must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Checks of the `quality` command (`[quality]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityConfig {
+    /// Distinct colors a page may use before its palette is reported as
+    /// sprawling.
+    pub palette_budget: Option<usize>,
+    /// CIEDE2000 difference below which two distinct colors are listed as
+    /// likely duplicates of one token.
+    pub near_duplicate_delta_e: Option<f32>,
+}
+
+impl QualityConfig {
+    pub fn palette(&self) -> PaletteSprawl {
+        let defaults = PaletteSprawl::default();
+        PaletteSprawl {
+            budget: self.palette_budget.unwrap_or(defaults.budget),
+            near_duplicate_delta_e: self
+                .near_duplicate_delta_e
+                .unwrap_or(defaults.near_duplicate_delta_e),
+            ..defaults
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.palette_budget == Some(0) {
+            return Err("quality.palette_budget must be greater than zero".to_string());
+        }
+        if let Some(delta_e) = self.near_duplicate_delta_e {
+            if !(delta_e.is_finite() && delta_e >= 0.0) {
+                return Err("quality.near_duplicate_delta_e must not be negative".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tuning of the individual metrics (`[metrics.pixel]`, `[metrics.layout]`,
+/// ...); unset keys keep the metric's default. See [`Config::to_metrics`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub pixel: PixelMetricConfig,
+    pub layout: LayoutMetricConfig,
+    pub typography: TypographyMetricConfig,
+    pub color: ColorMetricConfig,
+    pub content: ContentMetricConfig,
+    pub semantic: SemanticMetricConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PixelMetricConfig {
+    /// Side of the square blocks diffs are measured in, in pixels.
+    pub block_size: Option<u32>,
+    /// Refine flagged blocks into quadrants down to this side, in pixels.
+    pub refine_block_size: Option<u32>,
+    /// Diff coverage (0.0-1.0) above which the score is penalized further.
+    pub coverage_penalty_threshold: Option<f32>,
+    pub coverage_penalty_scale: Option<f32>,
+    pub coverage_penalty_max: Option<f32>,
+}
+
+impl PixelMetricConfig {
+    pub fn build(&self) -> PixelSimilarity {
+        let defaults = PixelSimilarity::default();
+        PixelSimilarity {
+            block_size: self.block_size.unwrap_or(defaults.block_size),
+            refine_block_size: self.refine_block_size.or(defaults.refine_block_size),
+            coverage_penalty_threshold: self
+                .coverage_penalty_threshold
+                .unwrap_or(defaults.coverage_penalty_threshold),
+            coverage_penalty_scale: self
+                .coverage_penalty_scale
+                .unwrap_or(defaults.coverage_penalty_scale),
+            coverage_penalty_max: self
+                .coverage_penalty_max
+                .unwrap_or(defaults.coverage_penalty_max),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutMetricConfig {
+    /// Box overlap (0.0-1.0) for two elements to count as the same element.
+    pub iou_threshold: Option<f32>,
+    pub match_threshold: Option<f32>,
+}
+
+impl LayoutMetricConfig {
+    pub fn build(&self) -> LayoutSimilarity {
+        let defaults = LayoutSimilarity::default();
+        LayoutSimilarity {
+            iou_threshold: self.iou_threshold.unwrap_or(defaults.iou_threshold),
+            match_threshold: self.match_threshold.unwrap_or(defaults.match_threshold),
+            ..defaults
+        }
+    }
+}
+
+/// Relative differences tolerated before a typography property is reported,
+/// and pixels for the baseline checks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypographyMetricConfig {
+    pub size_tolerance: Option<f32>,
+    pub line_height_tolerance: Option<f32>,
+    pub letter_spacing_tolerance: Option<f32>,
+    pub baseline_tolerance: Option<f32>,
+    pub rhythm_tolerance: Option<f32>,
+}
+
+impl TypographyMetricConfig {
+    pub fn build(&self) -> TypographySimilarity {
+        let defaults = TypographySimilarity::default();
+        TypographySimilarity {
+            size_tolerance: self.size_tolerance.unwrap_or(defaults.size_tolerance),
+            line_height_tolerance: self
+                .line_height_tolerance
+                .unwrap_or(defaults.line_height_tolerance),
+            letter_spacing_tolerance: self
+                .letter_spacing_tolerance
+                .unwrap_or(defaults.letter_spacing_tolerance),
+            baseline_tolerance: self
+                .baseline_tolerance
+                .unwrap_or(defaults.baseline_tolerance),
+            rhythm_tolerance: self.rhythm_tolerance.unwrap_or(defaults.rhythm_tolerance),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorMetricConfig {
+    /// Palette size per side.
+    pub clusters: Option<usize>,
+    /// Sample every Nth pixel in each direction.
+    pub sample_stride: Option<u32>,
+    pub saliency_weight: Option<f32>,
+}
+
+impl ColorMetricConfig {
+    pub fn build(&self) -> ColorPaletteMetric {
+        let defaults = ColorPaletteMetric::default();
+        ColorPaletteMetric {
+            clusters: self.clusters.unwrap_or(defaults.clusters),
+            sample_stride: self.sample_stride.unwrap_or(defaults.sample_stride),
+            saliency_weight: self.saliency_weight.unwrap_or(defaults.saliency_weight),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentMetricConfig {
+    /// Text similarity (0.0-1.0) for two strings to match.
+    pub match_threshold: Option<f32>,
+    /// How much extra implementation text counts against the score.
+    pub extra_penalty_weight: Option<f32>,
+}
+
+impl ContentMetricConfig {
+    pub fn build(&self) -> ContentSimilarity {
+        let defaults = ContentSimilarity::default();
+        ContentSimilarity {
+            match_threshold: self.match_threshold.unwrap_or(defaults.match_threshold),
+            extra_penalty_weight: self
+                .extra_penalty_weight
+                .unwrap_or(defaults.extra_penalty_weight),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SemanticMetricConfig {
+    /// Embedding cosine similarity that scores 0.0.
+    pub floor: Option<f32>,
+    /// CLIP-style ONNX image encoder producing the embeddings.
+    pub model: Option<PathBuf>,
+}
+
+impl SemanticMetricConfig {
+    pub fn build(&self) -> SemanticSimilarity {
+        let defaults = SemanticSimilarity::default();
+        SemanticSimilarity {
+            floor: self.floor.unwrap_or(defaults.floor),
+            encoder: self
+                .model
+                .clone()
+                .map(|path| Arc::new(ImageEncoder::new(path)))
+                .or(defaults.encoder),
         }
     }
 }
@@ -60,6 +466,7 @@ pub struct MetricWeights {
     pub typography: f32,
     pub color: f32,
     pub content: f32,
+    pub semantic: f32,
 }
 
 impl Default for MetricWeights {
@@ -71,6 +478,7 @@ impl Default for MetricWeights {
             typography: 0.15,
             color: 0.15,
             content: 0.10,
+            semantic: 0.10,
         }
     }
 }
@@ -104,7 +512,16 @@ impl Default for Config {
             metric_weights: MetricWeights::default(),
             timeouts: Timeouts::default(),
             semantic: SemanticConfig::default(),
+            summarizer: SummarizerConfig::default(),
             pixel_alignment: PixelAlignmentConfig::default(),
+            severity: SeverityConfig::default(),
+            artifacts: ArtifactEncoding::default(),
+            requests: RequestRules::default(),
+            dom: DomConfig::default(),
+            metrics: MetricsConfig::default(),
+            resources: ResourceLimits::default(),
+            figma: FigmaConfig::default(),
+            quality: QualityConfig::default(),
         }
     }
 }
@@ -117,18 +534,36 @@ where
     #[serde(untagged)]
     enum ViewportToml {
         String(String),
-        Table { width: u32, height: u32 },
+        Table {
+            width: u32,
+            height: u32,
+            #[serde(default)]
+            device_scale_factor: Option<f32>,
+        },
     }
 
     match ViewportToml::deserialize(deserializer)? {
         ViewportToml::String(s) => Viewport::from_str(&s).map_err(de::Error::custom),
-        ViewportToml::Table { width, height } => {
+        ViewportToml::Table {
+            width,
+            height,
+            device_scale_factor,
+        } => {
             if width == 0 || height == 0 {
                 return Err(de::Error::custom(
                     "viewport width and height must be greater than zero",
                 ));
             }
-            Ok(Viewport { width, height })
+            let mut viewport = Viewport::new(width, height);
+            if let Some(factor) = device_scale_factor {
+                if !factor.is_finite() || factor <= 0.0 {
+                    return Err(de::Error::custom(
+                        "viewport device_scale_factor must be greater than zero",
+                    ));
+                }
+                viewport.device_scale_factor = factor;
+            }
+            Ok(viewport)
         }
     }
 }
@@ -171,6 +606,42 @@ impl Config {
         Self::from_central_config()
     }
 
+    /// The metrics as configured by this file.
+    ///
+    /// | Config key | Metric field |
+    /// | --- | --- |
+    /// | `[metrics.pixel] block_size`, `refine_block_size`, `coverage_penalty_*` | [`PixelSimilarity`] fields of the same name |
+    /// | `[severity] pixel` | [`PixelSimilarity::thresholds`] |
+    /// | `[pixel_alignment]` | [`PixelSimilarity::alignment`] |
+    /// | `[metrics.layout] iou_threshold`, `match_threshold` | [`LayoutSimilarity`] fields of the same name |
+    /// | `[metrics.typography] *_tolerance` | [`TypographySimilarity`] fields of the same name |
+    /// | `[metrics.color] clusters`, `sample_stride`, `saliency_weight` | [`ColorPaletteMetric`] fields of the same name |
+    /// | `[metrics.content] match_threshold`, `extra_penalty_weight` | [`ContentSimilarity`] fields of the same name |
+    /// | `[metrics.semantic] floor` | [`SemanticSimilarity::floor`] |
+    ///
+    /// Everything else keeps the metric's default; `compare` layers its CLI
+    /// flags on top of these instances.
+    pub fn to_metrics(&self) -> Vec<Box<dyn Metric>> {
+        let alignment = &self.pixel_alignment;
+        vec![
+            Box::new(PixelSimilarity {
+                thresholds: self.severity.calibration().pixel,
+                alignment: ImageAlignmentOptions {
+                    enabled: alignment.enabled,
+                    max_shift: alignment.max_shift,
+                    downscale_max_dim: alignment.downscale_max_dim,
+                    method: alignment.method,
+                },
+                ..self.metrics.pixel.build()
+            }),
+            Box::new(self.metrics.layout.build()),
+            Box::new(self.metrics.typography.build()),
+            Box::new(self.metrics.color.build()),
+            Box::new(self.metrics.content.build()),
+            Box::new(self.metrics.semantic.build()),
+        ]
+    }
+
     /// Ensure defaults are applied when deserializing partial configs.
     fn apply_defaults(&mut self) {
         let defaults = Config::default();
@@ -203,6 +674,11 @@ impl Config {
             } else {
                 self.metric_weights.content
             },
+            semantic: if self.metric_weights.semantic <= 0.0 {
+                defaults.metric_weights.semantic
+            } else {
+                self.metric_weights.semantic
+            },
         };
         self.timeouts = Timeouts {
             navigation: if self.timeouts.navigation == Duration::from_secs(0) {
@@ -233,6 +709,7 @@ impl Config {
             } else {
                 self.pixel_alignment.downscale_max_dim
             },
+            method: self.pixel_alignment.method,
         };
     }
 
@@ -247,6 +724,7 @@ impl Config {
             self.metric_weights.typography,
             self.metric_weights.color,
             self.metric_weights.content,
+            self.metric_weights.semantic,
         ];
         if weights.iter().any(|w| *w <= 0.0) {
             return Err("all metric weights must be positive".to_string());
@@ -271,13 +749,44 @@ impl Config {
                     .to_string(),
             );
         }
-        Ok(())
+        let pixel = self.severity.calibration().pixel;
+        if !(pixel.minor > 0.0
+            && pixel.minor <= pixel.moderate
+            && pixel.moderate <= pixel.major
+            && pixel.major <= 1.0)
+        {
+            return Err(
+                "severity.pixel thresholds must satisfy 0 < minor <= moderate <= major <= 1"
+                    .to_string(),
+            );
+        }
+        if let Some(delta_e) = self.severity.color_delta_e {
+            if !(delta_e.moderate > 0.0 && delta_e.moderate <= delta_e.major) {
+                return Err("severity.color_delta_e must satisfy 0 < moderate <= major".to_string());
+            }
+        }
+        if self.dom.max_nodes == Some(0) {
+            return Err("dom.max_nodes must be greater than zero".to_string());
+        }
+        self.metrics.validate()?;
+        self.resources.validate()?;
+        self.artifacts.validate()?;
+        self.figma.validate()?;
+        self.quality.validate()?;
+        self.requests.validate()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, MetricWeights, PixelAlignmentConfig, SemanticConfig, Timeouts};
+    use super::{
+        AlignmentMethod, ArtifactEncoding, Config, DomConfig, FigmaConfig, MetricWeights,
+        MetricsConfig, PixelAlignmentConfig, QualityConfig, RequestRules, ResourceLimits,
+        SemanticConfig, SeverityConfig, SummarizerConfig, Timeouts,
+    };
+    use crate::artifact_encoding::ArtifactImageFormat;
+    use crate::metrics::SeverityProfile;
+    use crate::types::{DiffSeverity, LayoutDiffKind, TypographyIssue};
     use crate::Viewport;
     use std::time::Duration;
 
@@ -301,10 +810,7 @@ mod tests {
     #[test]
     fn can_override_weights_and_timeouts() {
         let cfg = Config {
-            viewport: Viewport {
-                width: 1280,
-                height: 720,
-            },
+            viewport: Viewport::new(1280, 720),
             threshold: 0.9,
             metric_weights: MetricWeights {
                 pixel: 0.5,
@@ -312,6 +818,7 @@ mod tests {
                 typography: 1.0,
                 color: 0.8,
                 content: 0.7,
+                semantic: 0.6,
             },
             timeouts: Timeouts {
                 navigation: Duration::from_secs(20),
@@ -319,11 +826,21 @@ mod tests {
                 process: Duration::from_secs(60),
             },
             semantic: SemanticConfig::default(),
+            summarizer: SummarizerConfig::default(),
             pixel_alignment: PixelAlignmentConfig {
                 enabled: true,
                 max_shift: 8,
                 downscale_max_dim: 128,
+                method: AlignmentMethod::PhaseCorrelation,
             },
+            severity: SeverityConfig::default(),
+            artifacts: ArtifactEncoding::default(),
+            requests: RequestRules::default(),
+            dom: DomConfig::default(),
+            metrics: MetricsConfig::default(),
+            resources: ResourceLimits::default(),
+            figma: FigmaConfig::default(),
+            quality: QualityConfig::default(),
         };
 
         assert_eq!(cfg.viewport.width, 1280);
@@ -385,6 +902,97 @@ process = "55s"
         assert_eq!(cfg.timeouts.process, Duration::from_secs(55));
     }
 
+    #[test]
+    fn load_from_toml_builds_configured_metrics() {
+        let tmp = tempfile::Builder::new()
+            .suffix(".toml")
+            .tempfile()
+            .expect("temp file");
+        std::fs::write(
+            tmp.path(),
+            r#"
+[metrics.pixel]
+block_size = 16
+
+[metrics.layout]
+iou_threshold = 0.6
+
+[metrics.typography]
+size_tolerance = 0.1
+
+[metrics.color]
+clusters = 8
+
+[metrics.content]
+match_threshold = 0.9
+
+[metrics.semantic]
+floor = 0.6
+model = "models/clip-vision.onnx"
+"#,
+        )
+        .unwrap();
+
+        let cfg = Config::from_toml_file(tmp.path()).expect("load config");
+        cfg.validate().expect("valid config");
+        let pixel = cfg.metrics.pixel.build();
+        assert_eq!(pixel.block_size, 16);
+        assert_eq!(pixel.coverage_penalty_max, 0.30);
+        assert_eq!(cfg.metrics.layout.build().iou_threshold, 0.6);
+        assert_eq!(cfg.metrics.typography.build().size_tolerance, 0.1);
+        assert_eq!(cfg.metrics.typography.build().line_height_tolerance, 0.05);
+        assert_eq!(cfg.metrics.color.build().clusters, 8);
+        assert_eq!(cfg.metrics.content.build().match_threshold, 0.9);
+        let semantic = cfg.metrics.semantic.build();
+        assert_eq!(semantic.floor, 0.6);
+        assert_eq!(
+            semantic.encoder.as_deref().map(|e| e.path()),
+            Some(std::path::Path::new("models/clip-vision.onnx"))
+        );
+        let kinds: Vec<String> = cfg
+            .to_metrics()
+            .iter()
+            .map(|m| m.kind().to_string())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "pixel",
+                "layout",
+                "typography",
+                "color",
+                "content",
+                "semantic"
+            ]
+        );
+
+        let mut cfg = Config::default();
+        cfg.metrics.color.clusters = Some(0);
+        assert_eq!(
+            cfg.validate().unwrap_err(),
+            "metrics.color.clusters must be greater than zero"
+        );
+        cfg.metrics.color.clusters = None;
+        cfg.metrics.layout.iou_threshold = Some(1.5);
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_non_http_figma_api_url() {
+        let mut cfg: Config = toml::from_str(
+            r#"
+[figma]
+api_url = "ftp://figma.example.com"
+token_env = ["FIGMA_TOKEN_A", "FIGMA_TOKEN_B"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.figma.token_env.len(), 2);
+        assert!(cfg.validate().unwrap_err().starts_with("figma.api_url"));
+        cfg.figma.api_url = Some("https://gateway.example.com/figma".to_string());
+        assert!(cfg.validate().is_ok());
+    }
+
     #[test]
     fn load_from_toml_accepts_viewport_string() {
         let tmp = tempfile::Builder::new()
@@ -414,13 +1022,149 @@ process = "15s"
     #[test]
     fn validate_rejects_zero_viewport_dimensions() {
         let cfg = Config {
-            viewport: Viewport {
-                width: 0,
-                height: 0,
-            },
+            viewport: Viewport::new(0, 0),
             ..Config::default()
         };
 
         assert!(cfg.validate().is_err());
     }
+
+    #[test]
+    fn load_from_toml_applies_severity_overrides_on_top_of_profile() {
+        let tmp = tempfile::Builder::new()
+            .suffix(".toml")
+            .tempfile()
+            .expect("temp file");
+        std::fs::write(
+            tmp.path(),
+            r#"
+[severity]
+profile = "lenient"
+pixel = { major = 0.5 }
+layout = { position_shift = "major" }
+typography = { line_height_diff = "moderate" }
+content = { extra_text = "moderate" }
+"#,
+        )
+        .unwrap();
+
+        let cfg = Config::from_toml_file(tmp.path()).expect("load config");
+        assert_eq!(cfg.severity.profile, SeverityProfile::Lenient);
+        let calibration = cfg.severity.calibration();
+        assert!((calibration.pixel.minor - 0.08).abs() < f32::EPSILON);
+        assert!((calibration.pixel.major - 0.5).abs() < f32::EPSILON);
+        assert_eq!(
+            calibration.layout(LayoutDiffKind::PositionShift),
+            DiffSeverity::Major
+        );
+        assert_eq!(
+            calibration.layout(LayoutDiffKind::SizeChange),
+            DiffSeverity::Minor
+        );
+        assert_eq!(
+            calibration.typography(&[TypographyIssue::LineHeightDiff]),
+            DiffSeverity::Moderate
+        );
+        assert_eq!(calibration.extra_text, DiffSeverity::Moderate);
+    }
+
+    #[test]
+    fn validate_rejects_unordered_pixel_severity_thresholds() {
+        let mut cfg = Config::default();
+        cfg.severity.pixel.moderate = Some(0.5);
+        assert!(cfg.validate().is_err());
+
+        cfg.severity.pixel.major = Some(0.6);
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn load_from_toml_reads_artifact_encoding() {
+        let tmp = tempfile::Builder::new()
+            .suffix(".toml")
+            .tempfile()
+            .expect("temp file");
+        std::fs::write(
+            tmp.path(),
+            r#"
+[artifacts.screenshots]
+format = "jpeg"
+quality = 80
+[artifacts.diff]
+optimize = true
+[artifacts.thumbnails]
+format = "webp"
+max_dim = 320
+"#,
+        )
+        .unwrap();
+
+        let cfg = Config::from_toml_file(tmp.path()).expect("load config");
+        assert_eq!(cfg.artifacts.screenshots.format, ArtifactImageFormat::Jpeg);
+        assert_eq!(cfg.artifacts.screenshots.quality, 80);
+        assert_eq!(cfg.artifacts.diff.format, ArtifactImageFormat::Png);
+        assert!(cfg.artifacts.diff.optimize);
+        assert_eq!(cfg.artifacts.thumbnails.max_dim, Some(320));
+        assert_eq!(cfg.artifacts.thumbnails.quality, 85);
+        assert!(cfg.validate().is_ok());
+        assert_ne!(cfg.artifacts, ArtifactEncoding::default());
+    }
+
+    #[test]
+    fn load_from_toml_reads_request_rules() {
+        let tmp = tempfile::Builder::new()
+            .suffix(".toml")
+            .tempfile()
+            .expect("temp file");
+        std::fs::write(
+            tmp.path(),
+            r#"
+[requests]
+block = ["**/gtag/**", "https://*.doubleclick.net/**"]
+
+[[requests.mock]]
+url = "**/api/user"
+body = '{"name":"Ada"}'
+content_type = "application/json"
+"#,
+        )
+        .unwrap();
+
+        let cfg = Config::from_toml_file(tmp.path()).expect("load config");
+        assert_eq!(cfg.requests.block.len(), 2);
+        assert_eq!(cfg.requests.mock[0].status, 200);
+        assert_eq!(
+            cfg.requests.mock[0].content_type.as_deref(),
+            Some("application/json")
+        );
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn load_from_toml_reads_resource_limits() {
+        let tmp = tempfile::Builder::new()
+            .suffix(".toml")
+            .tempfile()
+            .expect("temp file");
+        std::fs::write(
+            tmp.path(),
+            "[resources]\nmin_available_mb = 2048\nmax_megapixels = 2.5\n",
+        )
+        .unwrap();
+
+        let cfg = Config::from_toml_file(tmp.path()).expect("load config");
+        assert!(cfg.resources.is_enabled());
+        assert_eq!(cfg.resources.min_available_mb, Some(2048));
+        assert!((cfg.resources.max_megapixels() - 2.5).abs() < f32::EPSILON);
+        assert_eq!(
+            cfg.resources.block_size(),
+            ResourceLimits::DEFAULT_BLOCK_SIZE
+        );
+        assert!(cfg.validate().is_ok());
+
+        let mut bad = cfg.clone();
+        bad.resources.max_megapixels = Some(0.0);
+        assert!(bad.validate().is_err());
+        assert!(!Config::default().resources.is_enabled());
+    }
 }
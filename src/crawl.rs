@@ -0,0 +1,442 @@
+//! Implementation page discovery for site-wide audits.
+//!
+//! A crawl starts either from a sitemap (`*.xml`, fetched or read from disk;
+//! sitemap indexes are followed) or from a page whose same-origin links are
+//! followed breadth first, within a depth and page budget. Pages are fetched
+//! as plain HTML, so links added by scripts are not found. Each page is then
+//! paired with its design reference through [`RouteRule`]s.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::ops::Range;
+use std::str::FromStr;
+
+use url::Url;
+
+use crate::error::{DpcError, Result};
+use crate::network::http_client;
+
+/// Limits of a crawl.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrawlOptions {
+    /// Link hops followed from the start page (sitemaps ignore it).
+    pub max_depth: usize,
+    /// Pages returned at most.
+    pub max_pages: usize,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 1,
+            max_pages: 20,
+        }
+    }
+}
+
+/// Link targets that are never pages.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "css", "js", "json", "xml", "png", "jpg", "jpeg", "gif", "svg", "webp", "ico", "pdf", "zip",
+    "mp4", "webm", "woff", "woff2",
+];
+
+/// Pages reachable from `start`, a sitemap (URL or file ending in `.xml`) or
+/// a page URL, in discovery order.
+pub async fn crawl(start: &str, options: &CrawlOptions) -> Result<Vec<Url>> {
+    if options.max_pages == 0 {
+        return Ok(Vec::new());
+    }
+    let is_sitemap = Url::parse(start)
+        .map(|url| url.path().to_ascii_lowercase().ends_with(".xml"))
+        .unwrap_or_else(|_| start.to_ascii_lowercase().ends_with(".xml"));
+    if is_sitemap {
+        crawl_sitemap(start, options.max_pages).await
+    } else {
+        crawl_links(start, options).await
+    }
+}
+
+async fn crawl_sitemap(start: &str, max_pages: usize) -> Result<Vec<Url>> {
+    let mut pending = VecDeque::from([start.to_string()]);
+    let mut visited = BTreeSet::new();
+    let mut pages = Vec::new();
+    let mut seen = BTreeSet::new();
+    while let Some(location) = pending.pop_front() {
+        if !visited.insert(location.clone()) {
+            continue;
+        }
+        let sitemap = parse_sitemap(&fetch_text(&location).await?);
+        if sitemap.is_index {
+            pending.extend(sitemap.locations);
+            continue;
+        }
+        for location in sitemap.locations {
+            let Ok(url) = Url::parse(&location) else {
+                continue;
+            };
+            if seen.insert(url.to_string()) {
+                pages.push(url);
+                if pages.len() == max_pages {
+                    return Ok(pages);
+                }
+            }
+        }
+    }
+    Ok(pages)
+}
+
+async fn crawl_links(start: &str, options: &CrawlOptions) -> Result<Vec<Url>> {
+    let mut start = Url::parse(start).map_err(|err| {
+        DpcError::Config(format!(
+            "--crawl must be a page URL or a sitemap (.xml), got '{start}': {err}"
+        ))
+    })?;
+    start.set_fragment(None);
+    let mut seen = BTreeSet::from([start.to_string()]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    let mut pages = Vec::new();
+    while let Some((page, depth)) = queue.pop_front() {
+        pages.push(page.clone());
+        if pages.len() == options.max_pages {
+            break;
+        }
+        if depth >= options.max_depth {
+            continue;
+        }
+        let html = match fetch_text(page.as_str()).await {
+            Ok(html) => html,
+            // The start page must load; later pages are still audited and
+            // report their own render error.
+            Err(err) if depth == 0 => return Err(err),
+            Err(_) => continue,
+        };
+        for link in page_links(&html, &page) {
+            if seen.insert(link.to_string()) {
+                queue.push_back((link, depth + 1));
+            }
+        }
+    }
+    Ok(pages)
+}
+
+async fn fetch_text(location: &str) -> Result<String> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let response = http_client()
+            .get(location)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.text().await?)
+    } else {
+        std::fs::read_to_string(location)
+            .map_err(|err| DpcError::Config(format!("failed to read {location}: {err}")))
+    }
+}
+
+/// The `<loc>` entries of a sitemap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sitemap {
+    /// A `<sitemapindex>`, whose locations are further sitemaps.
+    pub is_index: bool,
+    pub locations: Vec<String>,
+}
+
+/// Read the `<loc>` values of a sitemap or sitemap index.
+pub fn parse_sitemap(xml: &str) -> Sitemap {
+    let mut locations = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = rest.find("<loc>") {
+        rest = &rest[open + "<loc>".len()..];
+        let Some(close) = rest.find("</loc>") else {
+            break;
+        };
+        let value = rest[..close].trim();
+        let value = value
+            .strip_prefix("<![CDATA[")
+            .and_then(|v| v.strip_suffix("]]>"))
+            .unwrap_or(value);
+        locations.push(decode_entities(value.trim()));
+        rest = &rest[close..];
+    }
+    Sitemap {
+        is_index: xml.contains("<sitemapindex"),
+        locations,
+    }
+}
+
+/// Same-origin page links of `html`, resolved against `base`, without
+/// fragments and in document order.
+pub fn page_links(html: &str, base: &Url) -> Vec<Url> {
+    let lower = html.to_ascii_lowercase();
+    let mut links = Vec::new();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find("<a") {
+        let tag_start = from + found;
+        let Some(tag_len) = lower[tag_start..].find('>') else {
+            break;
+        };
+        from = tag_start + tag_len;
+        let after = lower.as_bytes().get(tag_start + 2);
+        if !matches!(after, Some(c) if c.is_ascii_whitespace()) {
+            continue;
+        }
+        let Some(href) = attribute(&html[tag_start..from], &lower[tag_start..from], "href") else {
+            continue;
+        };
+        let Ok(mut link) = base.join(&decode_entities(href)) else {
+            continue;
+        };
+        link.set_fragment(None);
+        if link.origin() == base.origin() && !is_asset(&link) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+/// Value of attribute `name` in a tag (`lower` is the tag lowercased).
+fn attribute<'a>(tag: &'a str, lower: &str, name: &str) -> Option<&'a str> {
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(name) {
+        let start = from + found;
+        from = start + name.len();
+        let preceded = lower[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_whitespace());
+        let rest = lower[from..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = lower.len() - rest[1..].trim_start().len();
+        let value = &tag[value_start..];
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace() || c == '>')
+                .next()
+                .unwrap_or(""),
+        });
+    }
+    None
+}
+
+fn is_asset(url: &Url) -> bool {
+    let last = url.path().rsplit('/').next().unwrap_or("");
+    last.rsplit_once('.').is_some_and(|(_, ext)| {
+        ASSET_EXTENSIONS
+            .iter()
+            .any(|asset| ext.eq_ignore_ascii_case(asset))
+    })
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Pairs crawled pages with design references: `PATTERN=REFERENCE`.
+///
+/// The pattern is matched against the page path (and query, if any); `*`
+/// matches within one path segment and `**` across segments. `{1}`, `{2}`…
+/// in the reference are replaced with what the wildcards matched, e.g.
+/// `/products/*=figma-json:designs/{1}.json`. Trailing slashes are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteRule {
+    pub pattern: String,
+    pub reference: String,
+}
+
+impl FromStr for RouteRule {
+    type Err = DpcError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let Some((pattern, reference)) = value.split_once('=') else {
+            return Err(DpcError::Config(format!(
+                "route rule '{value}' must look like PATTERN=REFERENCE"
+            )));
+        };
+        let (pattern, reference) = (pattern.trim(), reference.trim());
+        if !pattern.starts_with('/') || reference.is_empty() {
+            return Err(DpcError::Config(format!(
+                "route rule '{value}' needs a pattern starting with '/' and a reference"
+            )));
+        }
+        let wildcards = pattern.replace("**", "*").matches('*').count();
+        if let Some(index) = placeholders(reference).find(|&index| index == 0 || index > wildcards)
+        {
+            return Err(DpcError::Config(format!(
+                "route rule '{value}' uses {{{index}}} but the pattern has {wildcards} wildcard(s)"
+            )));
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            reference: reference.to_string(),
+        })
+    }
+}
+
+impl RouteRule {
+    /// The reference for `url`, if the rule matches it.
+    pub fn reference_for(&self, url: &Url) -> Option<String> {
+        let mut target = url.path().to_string();
+        if let Some(query) = url.query() {
+            target = format!("{target}?{query}");
+        }
+        let target = trim_slash(&target);
+        let pattern = trim_slash(&self.pattern);
+        let mut captures = Vec::new();
+        if !glob(pattern.as_bytes(), target.as_bytes(), 0, &mut captures) {
+            return None;
+        }
+        let mut reference = self.reference.clone();
+        for (idx, range) in captures.iter().enumerate().rev() {
+            reference = reference.replace(&format!("{{{}}}", idx + 1), &target[range.clone()]);
+        }
+        Some(reference)
+    }
+}
+
+/// The reference of the first rule matching `url`.
+pub fn match_route(rules: &[RouteRule], url: &Url) -> Option<String> {
+    rules.iter().find_map(|rule| rule.reference_for(url))
+}
+
+/// Indices of the `{n}` placeholders in `reference`.
+fn placeholders(reference: &str) -> impl Iterator<Item = usize> + '_ {
+    reference.split('{').skip(1).filter_map(|part| {
+        let (index, _) = part.split_once('}')?;
+        index.parse().ok()
+    })
+}
+
+fn trim_slash(path: &str) -> &str {
+    match path.strip_suffix('/') {
+        Some(trimmed) if !trimmed.is_empty() => trimmed,
+        _ => path,
+    }
+}
+
+/// Match `pattern` against `text[at..]`, recording the wildcard ranges.
+fn glob(pattern: &[u8], text: &[u8], at: usize, captures: &mut Vec<Range<usize>>) -> bool {
+    match pattern {
+        [] => at == text.len(),
+        [b'*', b'*', rest @ ..] => {
+            for end in at..=text.len() {
+                captures.push(at..end);
+                if glob(rest, text, end, captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+        [b'*', rest @ ..] => {
+            let mut end = at;
+            loop {
+                captures.push(at..end);
+                if glob(rest, text, end, captures) {
+                    return true;
+                }
+                captures.pop();
+                if end == text.len() || text[end] == b'/' {
+                    return false;
+                }
+                end += 1;
+            }
+        }
+        [c, rest @ ..] => text.get(at) == Some(c) && glob(rest, text, at + 1, captures),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sitemap_locations_and_indexes_are_read() {
+        let xml = r#"<?xml version="1.0"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://example.com/</loc></url>
+              <url><loc> https://example.com/search?q=a&amp;page=2 </loc></url>
+              <url><loc><![CDATA[https://example.com/about]]></loc></url>
+            </urlset>"#;
+        let sitemap = parse_sitemap(xml);
+        assert!(!sitemap.is_index);
+        assert_eq!(
+            sitemap.locations,
+            vec![
+                "https://example.com/",
+                "https://example.com/search?q=a&page=2",
+                "https://example.com/about",
+            ]
+        );
+        let index = parse_sitemap(
+            "<sitemapindex><sitemap><loc>https://example.com/s1.xml</loc></sitemap></sitemapindex>",
+        );
+        assert!(index.is_index);
+        assert_eq!(index.locations, vec!["https://example.com/s1.xml"]);
+    }
+
+    #[test]
+    fn page_links_keep_same_origin_pages() {
+        let base = Url::parse("https://example.com/docs/intro").unwrap();
+        let html = r##"
+            <nav><A class="x" HREF="/pricing#plans">Pricing</A>
+            <a href='guide'>Guide</a>
+            <a href=https://example.com/blog?x=1&amp;y=2>Blog</a>
+            <a href="https://other.com/">Elsewhere</a>
+            <a href="/files/brochure.pdf">PDF</a>
+            <a href="mailto:hi@example.com">Mail</a>
+            <abbr href="/nope">x</abbr><a data-href="/nope" href="/contact">Contact</a></nav>"##;
+        let links: Vec<String> = page_links(html, &base).iter().map(Url::to_string).collect();
+        assert_eq!(
+            links,
+            vec![
+                "https://example.com/pricing",
+                "https://example.com/docs/guide",
+                "https://example.com/blog?x=1&y=2",
+                "https://example.com/contact",
+            ]
+        );
+    }
+
+    #[test]
+    fn route_rules_match_paths_and_fill_captures() {
+        let rules: Vec<RouteRule> = [
+            "/=home.png",
+            "/products/*=figma-json:designs/product-{1}.json",
+            "/docs/**=docs.png",
+        ]
+        .iter()
+        .map(|rule| rule.parse().unwrap())
+        .collect();
+        let route = |url: &str| match_route(&rules, &Url::parse(url).unwrap());
+        assert_eq!(route("https://example.com/").as_deref(), Some("home.png"));
+        assert_eq!(
+            route("https://example.com/products/shoes/").as_deref(),
+            Some("figma-json:designs/product-shoes.json")
+        );
+        assert_eq!(route("https://example.com/products/shoes/red"), None);
+        assert_eq!(
+            route("https://example.com/docs/a/b").as_deref(),
+            Some("docs.png")
+        );
+        assert_eq!(route("https://example.com/blog"), None);
+
+        assert!("products=ref.png".parse::<RouteRule>().is_err());
+        assert!("/products/*=ref-{2}.png".parse::<RouteRule>().is_err());
+        // References may contain '=' themselves.
+        let rule: RouteRule = "/x=https://www.figma.com/design/abc/F?node-id=1-2"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rule.reference,
+            "https://www.figma.com/design/abc/F?node-id=1-2"
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Screenshots from Android emulators/devices, iOS simulators and desktop windows.
+//!
+//! `adb:[SERIAL]` runs `adb exec-out screencap -p`; `simctl:[DEVICE]` runs
+//! `xcrun simctl io DEVICE screenshot`; `window:<TITLE|PID>` grabs a native
+//! window through the platform capture APIs (`window-capture` feature). The
+//! captured PNG then goes through the regular image pipeline, so native apps
+//! get the same metrics as images.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::error::{DpcError, Result};
+use crate::image_loader::{image_to_normalized_view, ImageLoadOptions};
+use crate::types::{NormalizedView, ResourceKind};
+
+pub const ADB_SCHEME: &str = "adb:";
+pub const SIMCTL_SCHEME: &str = "simctl:";
+pub const WINDOW_SCHEME: &str = "window:";
+
+/// Device a screenshot is taken from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceTarget {
+    /// Android device or emulator; `None` lets adb pick the only attached one.
+    Adb { serial: Option<String> },
+    /// iOS simulator UDID or name, `booted` by default.
+    Simctl { device: String },
+    /// Native desktop window.
+    Window(WindowSelector),
+}
+
+/// Which desktop window a `window:` resource refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowSelector {
+    /// Owning process id.
+    Pid(u32),
+    /// Case-insensitive substring of the window title, or the app name.
+    Title(String),
+}
+
+impl WindowSelector {
+    pub fn matches(&self, pid: u32, title: &str, app_name: &str) -> bool {
+        match self {
+            WindowSelector::Pid(wanted) => pid == *wanted,
+            WindowSelector::Title(wanted) => {
+                let wanted = wanted.to_lowercase();
+                title.to_lowercase().contains(&wanted) || app_name.to_lowercase() == wanted
+            }
+        }
+    }
+}
+
+impl DeviceTarget {
+    /// Parse an `adb:` / `simctl:` / `window:` resource value; `None` for
+    /// anything else.
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(serial) = value.strip_prefix(ADB_SCHEME) {
+            let serial = serial.trim();
+            Some(DeviceTarget::Adb {
+                serial: (!serial.is_empty()).then(|| serial.to_string()),
+            })
+        } else if let Some(device) = value.strip_prefix(SIMCTL_SCHEME) {
+            let device = device.trim();
+            Some(DeviceTarget::Simctl {
+                device: if device.is_empty() {
+                    "booted".to_string()
+                } else {
+                    device.to_string()
+                },
+            })
+        } else if let Some(selector) = value.strip_prefix(WINDOW_SCHEME) {
+            let selector = selector.trim();
+            if selector.is_empty() {
+                return None;
+            }
+            Some(DeviceTarget::Window(match selector.parse() {
+                Ok(pid) => WindowSelector::Pid(pid),
+                Err(_) => WindowSelector::Title(selector.to_string()),
+            }))
+        } else {
+            None
+        }
+    }
+
+    pub fn kind(&self) -> ResourceKind {
+        match self {
+            DeviceTarget::Adb { .. } | DeviceTarget::Simctl { .. } => ResourceKind::Device,
+            DeviceTarget::Window(_) => ResourceKind::Window,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceCaptureOptions {
+    pub adb_command: String,
+    pub xcrun_command: String,
+    pub timeout: Duration,
+}
+
+impl Default for DeviceCaptureOptions {
+    fn default() -> Self {
+        Self {
+            adb_command: "adb".to_string(),
+            xcrun_command: "xcrun".to_string(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Write a PNG screenshot of `target` to `output_path`.
+pub async fn capture_device_screenshot(
+    target: &DeviceTarget,
+    output_path: &Path,
+    options: &DeviceCaptureOptions,
+) -> Result<()> {
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match target {
+        DeviceTarget::Adb { serial } => {
+            let mut cmd = Command::new(&options.adb_command);
+            if let Some(serial) = serial {
+                cmd.arg("-s").arg(serial);
+            }
+            cmd.args(["exec-out", "screencap", "-p"]);
+            let png = run_capture(cmd, &options.adb_command, options.timeout).await?;
+            if png.is_empty() {
+                return Err(DpcError::Config(
+                    "adb screencap returned no data; is the device unlocked and authorized?"
+                        .to_string(),
+                ));
+            }
+            std::fs::write(output_path, png)?;
+        }
+        DeviceTarget::Simctl { device } => {
+            let mut cmd = Command::new(&options.xcrun_command);
+            cmd.args(["simctl", "io", device, "screenshot", "--type=png"])
+                .arg(output_path);
+            run_capture(cmd, &options.xcrun_command, options.timeout).await?;
+        }
+        DeviceTarget::Window(selector) => {
+            let selector = selector.clone();
+            let path = output_path.to_path_buf();
+            tokio::time::timeout(
+                options.timeout,
+                tokio::task::spawn_blocking(move || capture_window(&selector, &path)),
+            )
+            .await
+            .map_err(|_| {
+                DpcError::Config(format!(
+                    "Window capture timed out after {:?}",
+                    options.timeout
+                ))
+            })?
+            .map_err(|err| DpcError::Unknown(format!("Window capture task failed: {}", err)))??;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "window-capture")]
+fn capture_window(selector: &WindowSelector, output_path: &Path) -> Result<()> {
+    let windows = xcap::Window::all()
+        .map_err(|err| DpcError::Config(format!("Unable to list windows: {}", err)))?;
+    let mut candidates: Vec<(xcap::Window, String)> = windows
+        .into_iter()
+        .filter(|window| !window.is_minimized().unwrap_or(false))
+        .filter_map(|window| {
+            let title = window.title().unwrap_or_default();
+            let app_name = window.app_name().unwrap_or_default();
+            let pid = window.pid().unwrap_or(0);
+            selector
+                .matches(pid, &title, &app_name)
+                .then(|| (window, format!("{} ({}, pid {})", title, app_name, pid)))
+        })
+        .collect();
+    let window = match candidates.len() {
+        0 => {
+            return Err(DpcError::Config(format!(
+                "No visible window matches {:?}",
+                selector
+            )))
+        }
+        1 => candidates.remove(0).0,
+        _ => {
+            let names: Vec<String> = candidates.into_iter().map(|(_, name)| name).collect();
+            return Err(DpcError::Config(format!(
+                "Window selector {:?} is ambiguous; candidates: {}",
+                selector,
+                names.join(", ")
+            )));
+        }
+    };
+    let captured = window
+        .capture_image()
+        .map_err(|err| DpcError::Config(format!("Window capture failed: {}", err)))?;
+    let (width, height) = (captured.width(), captured.height());
+    let image =
+        image::RgbaImage::from_raw(width, height, captured.into_raw()).ok_or_else(|| {
+            DpcError::Unknown("Window capture returned a malformed buffer".to_string())
+        })?;
+    image.save(output_path)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "window-capture"))]
+fn capture_window(_selector: &WindowSelector, _output_path: &Path) -> Result<()> {
+    Err(DpcError::Config(
+        "window: resources need dpc built with the `window-capture` feature (cargo install dpc --features window-capture)"
+            .to_string(),
+    ))
+}
+
+/// Capture `target` and normalize the screenshot like an image input.
+pub async fn device_to_normalized_view(
+    target: &DeviceTarget,
+    capture_path: &Path,
+    output_path: &Path,
+    load_options: ImageLoadOptions,
+    capture_options: &DeviceCaptureOptions,
+) -> Result<NormalizedView> {
+    capture_device_screenshot(target, capture_path, capture_options).await?;
+    let mut view = image_to_normalized_view(capture_path, output_path, load_options)?;
+    view.kind = target.kind();
+    Ok(view)
+}
+
+async fn run_capture(mut cmd: Command, program: &str, timeout: Duration) -> Result<Vec<u8>> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| {
+            DpcError::Config(format!(
+                "Device screenshot via '{}' timed out after {:?}",
+                program, timeout
+            ))
+        })?
+        .map_err(|err| {
+            if err.kind() == std::io::ErrorKind::NotFound {
+                DpcError::Config(format!(
+                    "Unable to capture device screenshot; '{}' was not found on PATH",
+                    program
+                ))
+            } else {
+                DpcError::Io(err)
+            }
+        })?;
+    if !output.status.success() {
+        return Err(DpcError::Config(format!(
+            "Device screenshot via '{}' failed ({}): {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_device_targets() {
+        assert_eq!(
+            DeviceTarget::parse("adb:"),
+            Some(DeviceTarget::Adb { serial: None })
+        );
+        assert_eq!(
+            DeviceTarget::parse("adb:emulator-5554"),
+            Some(DeviceTarget::Adb {
+                serial: Some("emulator-5554".to_string())
+            })
+        );
+        assert_eq!(
+            DeviceTarget::parse("simctl:"),
+            Some(DeviceTarget::Simctl {
+                device: "booted".to_string()
+            })
+        );
+        assert_eq!(
+            DeviceTarget::parse("window:4242"),
+            Some(DeviceTarget::Window(WindowSelector::Pid(4242)))
+        );
+        assert_eq!(DeviceTarget::parse("window:"), None);
+        assert_eq!(DeviceTarget::parse("impl.png"), None);
+    }
+
+    #[test]
+    fn window_selector_matches_title_app_or_pid() {
+        let selector = WindowSelector::Title("checkout".to_string());
+        assert!(selector.matches(1, "Acme — Checkout", "Acme"));
+        assert!(!selector.matches(1, "Acme — Cart", "Acme"));
+        assert!(WindowSelector::Title("acme".to_string()).matches(1, "Cart", "Acme"));
+        assert!(WindowSelector::Pid(7).matches(7, "", ""));
+        assert!(!WindowSelector::Pid(7).matches(8, "", ""));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn adb_capture_writes_screencap_output() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let png = dir.path().join("screen.png");
+        image::RgbaImage::from_pixel(8, 16, image::Rgba([1, 2, 3, 255]))
+            .save(&png)
+            .unwrap();
+        // Stand-in for adb that checks its arguments and prints the PNG.
+        let fake_adb = dir.path().join("adb");
+        std::fs::write(
+            &fake_adb,
+            format!(
+                "#!/bin/sh\n[ \"$*\" = \"-s emulator-5554 exec-out screencap -p\" ] || exit 3\ncat '{}'\n",
+                png.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake_adb, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let options = DeviceCaptureOptions {
+            adb_command: fake_adb.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let view = device_to_normalized_view(
+            &DeviceTarget::parse("adb:emulator-5554").unwrap(),
+            &dir.path().join("capture.png"),
+            &dir.path().join("out.png"),
+            ImageLoadOptions {
+                no_resize: true,
+                ..Default::default()
+            },
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(view.kind, ResourceKind::Device);
+        assert_eq!((view.width, view.height), (8, 16));
+
+        let missing = DeviceCaptureOptions {
+            adb_command: dir.path().join("no-adb").to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let err = capture_device_screenshot(
+            &DeviceTarget::Adb { serial: None },
+            &dir.path().join("x.png"),
+            &missing,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, DpcError::Config(msg) if msg.contains("not found")));
+    }
+}
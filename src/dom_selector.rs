@@ -0,0 +1,690 @@
+//! CSS selectors over captured DOM snapshots.
+//!
+//! Covers what ignore rules need on real pages: type and universal selectors,
+//! `#id`, `.class`, attribute selectors (`[data-testid=ad]`, `[href^=http]`),
+//! `:first-child`, `:last-child`, `:only-child`, `:nth-child()`,
+//! `:nth-last-child()` and `:not()`, joined by the descendant, `>`, `+` and
+//! `~` combinators. Nodes are related through their `parent`/`children`
+//! links, so sibling positions count captured (visible) elements only.
+//! Matching is ASCII case-insensitive throughout, attribute values included.
+
+use std::collections::HashMap;
+
+use crate::error::{DpcError, Result};
+use crate::types::DomNode;
+
+/// A comma-separated list of selectors; a node matches if any of them does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorList {
+    selectors: Vec<ComplexSelector>,
+}
+
+/// Parent and sibling lookups over a snapshot's nodes.
+pub struct DomIndex<'a> {
+    by_id: HashMap<&'a str, &'a DomNode>,
+}
+
+impl<'a> DomIndex<'a> {
+    pub fn new(nodes: &'a [DomNode]) -> Self {
+        Self {
+            by_id: nodes.iter().map(|node| (node.id.as_str(), node)).collect(),
+        }
+    }
+
+    fn parent(&self, node: &DomNode) -> Option<&'a DomNode> {
+        node.parent
+            .as_deref()
+            .and_then(|id| self.by_id.get(id).copied())
+    }
+
+    /// The node's parent's children and the node's index among them, or
+    /// `None` when the parent was not captured.
+    fn siblings(&self, node: &DomNode) -> Option<(&'a [String], usize)> {
+        let siblings = self.parent(node)?.children.as_slice();
+        let position = siblings.iter().position(|id| *id == node.id)?;
+        Some((siblings, position))
+    }
+
+    fn preceding_siblings(&self, node: &DomNode) -> impl Iterator<Item = &'a DomNode> + '_ {
+        let preceding = self
+            .siblings(node)
+            .map_or(&[][..], |(siblings, position)| &siblings[..position]);
+        preceding
+            .iter()
+            .rev()
+            .filter_map(|id| self.by_id.get(id.as_str()).copied())
+    }
+}
+
+impl SelectorList {
+    /// Parse a selector list such as `header .logo, [data-testid=ad]`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let selectors = split_selector_list(input)
+            .iter()
+            .map(|part| {
+                if part.is_empty() {
+                    Err(invalid(input, "empty selector in list"))
+                } else {
+                    Parser::new(part)
+                        .complex()
+                        .map_err(|reason| invalid(part, &reason))
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { selectors })
+    }
+
+    /// Whether `node` matches any selector of the list.
+    pub fn matches(&self, node: &DomNode, dom: &DomIndex) -> bool {
+        self.selectors
+            .iter()
+            .any(|selector| match_steps(&selector.steps, node, dom))
+    }
+}
+
+/// Split a selector list on its top-level commas, ignoring commas inside
+/// brackets, parentheses and quotes. Parts are trimmed but may be empty.
+pub fn split_selector_list(input: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    for c in input.chars() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+fn invalid(selector: &str, reason: &str) -> DpcError {
+    DpcError::Config(format!("Invalid selector '{selector}': {reason}"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling,
+}
+
+/// Compounds left to right, each with the combinator joining it to the
+/// previous one.
+#[derive(Debug, Clone, PartialEq)]
+struct ComplexSelector {
+    steps: Vec<(Option<Combinator>, Compound)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attributes: Vec<AttributeSelector>,
+    pseudos: Vec<Pseudo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AttributeSelector {
+    name: String,
+    test: Option<(AttributeOp, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttributeOp {
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pseudo {
+    /// `an+b`, counting from the first sibling.
+    NthChild(i32, i32),
+    /// `an+b`, counting from the last sibling.
+    NthLastChild(i32, i32),
+    Not(Box<Compound>),
+}
+
+fn match_steps(steps: &[(Option<Combinator>, Compound)], node: &DomNode, dom: &DomIndex) -> bool {
+    let Some(((combinator, compound), rest)) = steps.split_last() else {
+        return true;
+    };
+    if !compound.matches(node, dom) {
+        return false;
+    }
+    match combinator {
+        None => true,
+        Some(Combinator::Child) => dom
+            .parent(node)
+            .is_some_and(|parent| match_steps(rest, parent, dom)),
+        Some(Combinator::Descendant) => {
+            let mut ancestor = dom.parent(node);
+            while let Some(current) = ancestor {
+                if match_steps(rest, current, dom) {
+                    return true;
+                }
+                ancestor = dom.parent(current);
+            }
+            false
+        }
+        Some(Combinator::Adjacent) => dom
+            .preceding_siblings(node)
+            .next()
+            .is_some_and(|sibling| match_steps(rest, sibling, dom)),
+        Some(Combinator::Sibling) => dom
+            .preceding_siblings(node)
+            .any(|sibling| match_steps(rest, sibling, dom)),
+    }
+}
+
+impl Compound {
+    fn matches(&self, node: &DomNode, dom: &DomIndex) -> bool {
+        if let Some(tag) = &self.tag {
+            if !node.tag.eq_ignore_ascii_case(tag) {
+                return false;
+            }
+        }
+        // Capture ids (`node-12`) are accepted alongside the `id` attribute.
+        let id_attr = attribute(node, "id");
+        let ids_match = self.ids.iter().all(|id| {
+            node.id.eq_ignore_ascii_case(id) || id_attr.is_some_and(|v| v.eq_ignore_ascii_case(id))
+        });
+        let class_attr = attribute(node, "class").unwrap_or_default();
+        let classes_match = self.classes.iter().all(|class| {
+            class_attr
+                .split_whitespace()
+                .any(|c| c.eq_ignore_ascii_case(class))
+        });
+        ids_match
+            && classes_match
+            && self.attributes.iter().all(|attr| attr.matches(node))
+            && self.pseudos.iter().all(|pseudo| pseudo.matches(node, dom))
+    }
+}
+
+fn attribute<'n>(node: &'n DomNode, name: &str) -> Option<&'n str> {
+    node.attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+impl AttributeSelector {
+    fn matches(&self, node: &DomNode) -> bool {
+        let Some(value) = attribute(node, &self.name) else {
+            return false;
+        };
+        let Some((op, expected)) = &self.test else {
+            return true;
+        };
+        let value = value.to_ascii_lowercase();
+        let expected = expected.to_ascii_lowercase();
+        match op {
+            AttributeOp::Equals => value == expected,
+            AttributeOp::Includes => value.split_whitespace().any(|word| word == expected),
+            AttributeOp::DashMatch => {
+                value == expected || value.starts_with(&format!("{expected}-"))
+            }
+            AttributeOp::Prefix => !expected.is_empty() && value.starts_with(&expected),
+            AttributeOp::Suffix => !expected.is_empty() && value.ends_with(&expected),
+            AttributeOp::Substring => !expected.is_empty() && value.contains(&expected),
+        }
+    }
+}
+
+impl Pseudo {
+    fn matches(&self, node: &DomNode, dom: &DomIndex) -> bool {
+        let (position, count) = dom
+            .siblings(node)
+            .map_or((1, 1), |(siblings, index)| (index + 1, siblings.len()));
+        match self {
+            Pseudo::NthChild(a, b) => nth_matches(*a, *b, position),
+            Pseudo::NthLastChild(a, b) => nth_matches(*a, *b, count + 1 - position),
+            Pseudo::Not(compound) => !compound.matches(node, dom),
+        }
+    }
+}
+
+/// Whether the 1-based `position` equals `a*n + b` for some `n >= 0`.
+fn nth_matches(a: i32, b: i32, position: usize) -> bool {
+    let offset = position as i64 - b as i64;
+    match a {
+        0 => offset == 0,
+        a => offset % a as i64 == 0 && offset / a as i64 >= 0,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+type ParseResult<T> = std::result::Result<T, String>;
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> ParseResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(match self.peek() {
+                Some(found) => format!("expected '{c}', found '{found}'"),
+                None => format!("expected '{c}'"),
+            })
+        }
+    }
+
+    /// Skip whitespace, returning whether any was skipped.
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    fn ident(&mut self) -> ParseResult<String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(match self.peek() {
+                Some(found) => format!("expected a name, found '{found}'"),
+                None => "expected a name".to_string(),
+            });
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn complex(&mut self) -> ParseResult<ComplexSelector> {
+        let mut steps = Vec::new();
+        let mut pending: Option<Combinator> = None;
+        loop {
+            let spaced = self.skip_whitespace();
+            let Some(c) = self.peek() else { break };
+            let explicit = match c {
+                '>' => Some(Combinator::Child),
+                '+' => Some(Combinator::Adjacent),
+                '~' => Some(Combinator::Sibling),
+                _ => None,
+            };
+            if let Some(combinator) = explicit {
+                if steps.is_empty() || pending.is_some() {
+                    return Err(format!("unexpected combinator '{c}'"));
+                }
+                self.pos += 1;
+                pending = Some(combinator);
+                continue;
+            }
+            if !steps.is_empty() && pending.is_none() {
+                if !spaced {
+                    return Err(format!("unexpected '{c}'"));
+                }
+                pending = Some(Combinator::Descendant);
+            }
+            steps.push((pending.take(), self.compound()?));
+        }
+        if pending.is_some() {
+            return Err("selector ends with a combinator".to_string());
+        }
+        if steps.is_empty() {
+            return Err("empty selector".to_string());
+        }
+        Ok(ComplexSelector { steps })
+    }
+
+    fn compound(&mut self) -> ParseResult<Compound> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+        if self.eat('*') {
+            // Universal selector: no tag constraint.
+        } else if self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            compound.tag = Some(self.ident()?);
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    compound.ids.push(self.ident()?);
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    compound.classes.push(self.ident()?);
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    compound.attributes.push(self.attribute()?);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    self.pseudo(&mut compound)?;
+                }
+                _ => break,
+            }
+        }
+        if self.pos == start {
+            return Err(match self.peek() {
+                Some(found) => format!("unexpected '{found}'"),
+                None => "empty selector".to_string(),
+            });
+        }
+        Ok(compound)
+    }
+
+    fn attribute(&mut self) -> ParseResult<AttributeSelector> {
+        self.skip_whitespace();
+        let name = self.ident()?;
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(AttributeSelector { name, test: None });
+        }
+        let op = match self.peek() {
+            Some('=') => AttributeOp::Equals,
+            Some('~') => AttributeOp::Includes,
+            Some('|') => AttributeOp::DashMatch,
+            Some('^') => AttributeOp::Prefix,
+            Some('$') => AttributeOp::Suffix,
+            Some('*') => AttributeOp::Substring,
+            Some(found) => return Err(format!("unexpected '{found}' in attribute selector")),
+            None => return Err("unterminated attribute selector".to_string()),
+        };
+        self.pos += 1;
+        if op != AttributeOp::Equals {
+            self.expect('=')?;
+        }
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != quote) {
+                    self.pos += 1;
+                }
+                let value: String = self.chars[start..self.pos].iter().collect();
+                self.expect(quote)?;
+                value
+            }
+            _ => self.ident()?,
+        };
+        self.skip_whitespace();
+        // Case flags are accepted; matching is case-insensitive regardless.
+        if self.peek().is_some_and(|c| c == 'i' || c == 's') {
+            self.pos += 1;
+            self.skip_whitespace();
+        }
+        self.expect(']')?;
+        Ok(AttributeSelector {
+            name,
+            test: Some((op, value)),
+        })
+    }
+
+    fn pseudo(&mut self, compound: &mut Compound) -> ParseResult<()> {
+        let name = self.ident()?.to_ascii_lowercase();
+        match name.as_str() {
+            "first-child" => compound.pseudos.push(Pseudo::NthChild(0, 1)),
+            "last-child" => compound.pseudos.push(Pseudo::NthLastChild(0, 1)),
+            "only-child" => {
+                compound.pseudos.push(Pseudo::NthChild(0, 1));
+                compound.pseudos.push(Pseudo::NthLastChild(0, 1));
+            }
+            "nth-child" | "nth-last-child" => {
+                self.expect('(')?;
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != ')') {
+                    self.pos += 1;
+                }
+                let argument: String = self.chars[start..self.pos].iter().collect();
+                self.expect(')')?;
+                let (a, b) = parse_nth(&argument)?;
+                compound.pseudos.push(if name == "nth-child" {
+                    Pseudo::NthChild(a, b)
+                } else {
+                    Pseudo::NthLastChild(a, b)
+                });
+            }
+            "not" => {
+                self.expect('(')?;
+                self.skip_whitespace();
+                let inner = self.compound()?;
+                self.skip_whitespace();
+                self.expect(')')?;
+                compound.pseudos.push(Pseudo::Not(Box::new(inner)));
+            }
+            _ => return Err(format!("unsupported pseudo-class ':{name}'")),
+        }
+        Ok(())
+    }
+}
+
+/// Parse an `an+b` argument (`odd`, `even`, `3`, `2n+1`, `-n+3`).
+fn parse_nth(argument: &str) -> ParseResult<(i32, i32)> {
+    let compact: String = argument
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let bad = || format!("invalid :nth-child argument '{}'", argument.trim());
+    match compact.as_str() {
+        "odd" => return Ok((2, 1)),
+        "even" => return Ok((2, 0)),
+        _ => {}
+    }
+    let Some((a, b)) = compact.split_once('n') else {
+        return compact.parse().map(|b| (0, b)).map_err(|_| bad());
+    };
+    let a = match a {
+        "" | "+" => 1,
+        "-" => -1,
+        a => a.parse().map_err(|_| bad())?,
+    };
+    let b = if b.is_empty() {
+        0
+    } else if b.starts_with(['+', '-']) {
+        b.parse().map_err(|_| bad())?
+    } else {
+        return Err(bad());
+    };
+    Ok((a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BoundingBox;
+
+    /// `(id, tag, parent, attributes)`; children are derived from parents in
+    /// list order.
+    type NodeSpec<'a> = (&'a str, &'a str, Option<&'a str>, &'a [(&'a str, &'a str)]);
+
+    fn dom(specs: &[NodeSpec]) -> Vec<DomNode> {
+        specs
+            .iter()
+            .map(|(id, tag, parent, attributes)| DomNode {
+                id: id.to_string(),
+                tag: tag.to_string(),
+                children: specs
+                    .iter()
+                    .filter(|(_, _, p, _)| p == &Some(*id))
+                    .map(|(child, ..)| child.to_string())
+                    .collect(),
+                parent: parent.map(str::to_string),
+                attributes: attributes
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                text: None,
+                bounding_box: BoundingBox {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 1.0,
+                    height: 1.0,
+                },
+                text_baseline: None,
+                interaction_states: None,
+                computed_style: None,
+            })
+            .collect()
+    }
+
+    fn select(selector: &str, nodes: &[DomNode]) -> Vec<String> {
+        let list = SelectorList::parse(selector).unwrap();
+        let index = DomIndex::new(nodes);
+        nodes
+            .iter()
+            .filter(|node| list.matches(node, &index))
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    fn page() -> Vec<DomNode> {
+        dom(&[
+            ("body", "body", None, &[]),
+            (
+                "header",
+                "header",
+                Some("body"),
+                &[("class", "site-header")],
+            ),
+            ("logo", "img", Some("header"), &[("class", "Logo brand")]),
+            ("nav", "nav", Some("header"), &[]),
+            ("a1", "a", Some("nav"), &[("href", "/home")]),
+            (
+                "a2",
+                "a",
+                Some("nav"),
+                &[("href", "https://ads.example.com")],
+            ),
+            (
+                "a3",
+                "a",
+                Some("nav"),
+                &[("href", "/about"), ("lang", "en-US")],
+            ),
+            ("main", "main", Some("body"), &[]),
+            (
+                "ad",
+                "div",
+                Some("main"),
+                &[("data-testid", "ad"), ("id", "promo")],
+            ),
+            ("card", "div", Some("main"), &[("class", "card")]),
+            ("logo2", "img", Some("card"), &[("class", "logo")]),
+        ])
+    }
+
+    #[test]
+    fn simple_selectors_keep_legacy_behavior() {
+        let nodes = page();
+        assert_eq!(select("NAV", &nodes), vec!["nav"]);
+        assert_eq!(select(".LOGO", &nodes), vec!["logo", "logo2"]);
+        assert_eq!(select("#promo", &nodes), vec!["ad"]);
+        // Capture ids still work as `#id`.
+        assert_eq!(select("#a2", &nodes), vec!["a2"]);
+        assert_eq!(select("*", &nodes).len(), nodes.len());
+    }
+
+    #[test]
+    fn combinators_follow_the_tree() {
+        let nodes = page();
+        assert_eq!(select("header .logo", &nodes), vec!["logo"]);
+        assert_eq!(select("body > img", &nodes), Vec::<String>::new());
+        assert_eq!(select("header > nav > a", &nodes), vec!["a1", "a2", "a3"]);
+        assert_eq!(select("a + a", &nodes), vec!["a2", "a3"]);
+        assert_eq!(select("img ~ nav", &nodes), vec!["nav"]);
+        assert_eq!(select("main div.card img", &nodes), vec!["logo2"]);
+    }
+
+    #[test]
+    fn attribute_selectors_support_css_operators() {
+        let nodes = page();
+        assert_eq!(select("[data-testid=ad]", &nodes), vec!["ad"]);
+        assert_eq!(select("[data-testid='AD']", &nodes), vec!["ad"]);
+        assert_eq!(select("a[href^=http]", &nodes), vec!["a2"]);
+        assert_eq!(select("a[href$=\"out\"]", &nodes), vec!["a3"]);
+        assert_eq!(select("[href*=ads]", &nodes), vec!["a2"]);
+        assert_eq!(select("[class~=brand]", &nodes), vec!["logo"]);
+        assert_eq!(select("[lang|=en]", &nodes), vec!["a3"]);
+        assert_eq!(select("div[id]", &nodes), vec!["ad"]);
+    }
+
+    #[test]
+    fn structural_pseudo_classes_use_sibling_positions() {
+        let nodes = page();
+        assert_eq!(select("nav a:first-child", &nodes), vec!["a1"]);
+        assert_eq!(select("nav a:last-child", &nodes), vec!["a3"]);
+        assert_eq!(select("a:nth-child(2)", &nodes), vec!["a2"]);
+        assert_eq!(select("a:nth-child(odd)", &nodes), vec!["a1", "a3"]);
+        assert_eq!(select("a:nth-child(-n+2)", &nodes), vec!["a1", "a2"]);
+        assert_eq!(select("a:nth-last-child(1)", &nodes), vec!["a3"]);
+        assert_eq!(select("img:only-child", &nodes), vec!["logo2"]);
+        assert_eq!(select("main > div:not(.card)", &nodes), vec!["ad"]);
+    }
+
+    #[test]
+    fn selector_lists_split_on_top_level_commas() {
+        assert_eq!(
+            split_selector_list(" .ad , [title='a, b'] ,, p:not(.x) "),
+            vec![".ad", "[title='a, b']", "", "p:not(.x)"]
+        );
+        let nodes = page();
+        assert_eq!(select("#promo, nav", &nodes), vec!["nav", "ad"]);
+    }
+
+    #[test]
+    fn invalid_selectors_are_config_errors() {
+        for selector in [
+            "",
+            "a >",
+            "> a",
+            "[href",
+            "a:hover",
+            "a:nth-child(x)",
+            "a,,b",
+            "a)",
+        ] {
+            let err = SelectorList::parse(selector).unwrap_err();
+            assert!(matches!(err, DpcError::Config(_)), "{selector}: {err}");
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::image_loader::ImageLoadError;
 use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::ParseError;
@@ -92,25 +93,37 @@ impl DpcError {
                     ErrorPayload::new(
                         ErrorCategory::Config,
                         msg.to_string(),
-                        "Install Playwright (e.g., `npm install playwright` and `npx playwright install chromium`).",
+                        "Run `dpc install-browser`, or install Playwright yourself (`npm install playwright` and `npx playwright install chromium`).",
                     )
                 } else if lower.contains("chromium executable") {
                     ErrorPayload::new(
                         ErrorCategory::Config,
                         msg.to_string(),
-                        "Run `npx playwright install chromium` (or `playwright install chromium`) to download the browser.",
+                        "Run `dpc install-browser` or `npx playwright install chromium` to download the browser.",
+                    )
+                } else if lower.contains("executable doesn't exist") {
+                    ErrorPayload::new(
+                        ErrorCategory::Config,
+                        msg.to_string(),
+                        "The browser engine is not downloaded; run `dpc install-browser --browser <engine>` (or `npx playwright install firefox|webkit|chromium`).",
                     )
                 } else if lower.contains("figma_token") || lower.contains("figma token") {
                     ErrorPayload::new(
                         ErrorCategory::Config,
                         msg.to_string(),
-                        "Set FIGMA_TOKEN (or FIGMA_OAUTH_TOKEN) before running Figma inputs.",
+                        "Set FIGMA_TOKEN (or FIGMA_OAUTH_TOKEN), or run `dpc figma login`, before running Figma inputs.",
+                    )
+                } else if lower.contains("figma frame") {
+                    ErrorPayload::new(
+                        ErrorCategory::Config,
+                        msg.to_string(),
+                        "Use the exact frame name (e.g., figma:<FILE_KEY>#frame-name=\"Page / Frame\") or target the frame by node-id.",
                     )
                 } else if lower.contains("node-id") {
                     ErrorPayload::new(
                         ErrorCategory::Config,
                         msg.to_string(),
-                        "Include a Figma node-id in the URL (e.g., ?node-id=1-2) or pass --ref-type/--impl-type explicitly.",
+                        "Include a Figma node-id in the URL (e.g., ?node-id=1-2), name a frame with figma:<FILE_KEY>#frame-name=\"...\", or pass --ref-type/--impl-type explicitly.",
                     )
                 } else if lower.contains("file key") && lower.contains("figma") {
                     ErrorPayload::new(
@@ -171,6 +184,13 @@ impl From<ImageLoadError> for DpcError {
         match err {
             ImageLoadError::Load(e) => DpcError::Image(e),
             ImageLoadError::NotFound(path) => DpcError::Config(format!("File not found: {}", path)),
+            ImageLoadError::TooLarge(msg) => DpcError::Config(format!("Image too large: {}", msg)),
+            ImageLoadError::Network(e) => DpcError::Network(e),
+            ImageLoadError::InvalidData(msg) => DpcError::Config(msg),
+            ImageLoadError::Http { url, status } => DpcError::Config(format!(
+                "Image download failed: HTTP {} for {}",
+                status, url
+            )),
             ImageLoadError::Save(msg) => DpcError::Io(std::io::Error::other(format!(
                 "Failed to save image: {}",
                 msg
@@ -181,7 +201,7 @@ impl From<ImageLoadError> for DpcError {
 
 pub type Result<T> = std::result::Result<T, DpcError>;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ErrorCategory {
     Config,
@@ -192,7 +212,7 @@ pub enum ErrorCategory {
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorPayload {
     pub category: ErrorCategory,
@@ -339,4 +359,16 @@ mod tests {
             "expected remediation to mention playwright install chromium, got: {remediation}"
         );
     }
+
+    #[test]
+    fn config_payload_hints_at_missing_browser_engine() {
+        let err = DpcError::Config(
+            "Playwright error (status error): browserType.launch: Executable doesn't exist at /home/ci/.cache/ms-playwright/firefox-1471/firefox/firefox".to_string(),
+        );
+        let remediation = err.to_payload().remediation.unwrap_or_default();
+        assert!(
+            remediation.contains("--browser"),
+            "expected remediation to mention install-browser --browser, got: {remediation}"
+        );
+    }
 }
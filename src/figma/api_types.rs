@@ -56,14 +56,92 @@ pub struct FigmaNodeData {
     #[serde(default)]
     pub children: Vec<FigmaNodeData>,
     pub absolute_bounding_box: Option<FigmaBoundingBox>,
+    /// Layout box grown by effects and strokes drawn outside it (drop
+    /// shadows, blurs); `null` for layers that render nothing.
+    #[serde(default)]
+    pub absolute_render_bounds: Option<FigmaBoundingBox>,
     pub characters: Option<String>,
     pub style: Option<FigmaTypeStyle>,
     #[serde(default)]
     pub fills: Vec<FigmaPaintData>,
+    /// `false` for layers hidden in the editor.
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    /// Layer opacity; absent means fully opaque.
+    pub opacity: Option<f32>,
+    /// Whether the frame clips its children to its bounds.
+    #[serde(default)]
+    pub clips_content: bool,
+    /// Resizing constraints relative to the parent frame.
+    #[serde(default)]
+    pub constraints: Option<FigmaLayoutConstraint>,
+    #[serde(flatten)]
+    pub auto_layout: FigmaAutoLayoutData,
+    /// Legacy single prototype link of the node.
+    #[serde(
+        default,
+        rename = "transitionNodeID",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transition_node_id: Option<String>,
+    /// Prototype interactions (click, hover, ...) and what they trigger.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interactions: Vec<FigmaInteraction>,
+    /// Style IDs applied to the node, by property (`fill`, `text`, ...).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub styles: HashMap<String, String>,
 }
 
-/// Bounding box coordinates from Figma.
+/// A prototype interaction: a trigger and the actions it runs.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaInteraction {
+    /// The API leaves out actions it cannot describe as `null`.
+    #[serde(default)]
+    pub actions: Vec<Option<FigmaAction>>,
+}
+
+/// A prototype action, e.g. `{"type": "NODE", "destinationId": "1:2",
+/// "navigation": "NAVIGATE"}`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaAction {
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub destination_id: Option<String>,
+    /// `NAVIGATE`, `SWAP`, `OVERLAY`, `SCROLL_TO` or `CHANGE_TO`.
+    pub navigation: Option<String>,
+}
+
+/// Layer constraints, e.g. `{"vertical": "TOP", "horizontal": "LEFT_RIGHT"}`.
 #[derive(Debug, Deserialize, Serialize)]
+pub struct FigmaLayoutConstraint {
+    pub vertical: String,
+    pub horizontal: String,
+}
+
+/// Auto-layout properties of a frame; `layout_mode` is `NONE` or absent for
+/// frames without auto layout.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaAutoLayoutData {
+    pub layout_mode: Option<String>,
+    pub item_spacing: Option<f32>,
+    pub padding_top: Option<f32>,
+    pub padding_right: Option<f32>,
+    pub padding_bottom: Option<f32>,
+    pub padding_left: Option<f32>,
+    pub primary_axis_align_items: Option<String>,
+    pub counter_axis_align_items: Option<String>,
+    pub layout_wrap: Option<String>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+/// Bounding box coordinates from Figma.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FigmaBoundingBox {
     pub x: f32,
@@ -90,6 +168,24 @@ pub struct FigmaPaintData {
     pub paint_type: String,
     pub color: Option<FigmaColor>,
     pub opacity: Option<f32>,
+    #[serde(default)]
+    pub gradient_handle_positions: Vec<FigmaVector>,
+    #[serde(default)]
+    pub gradient_stops: Vec<FigmaColorStop>,
+}
+
+/// Point in a node's unit square, used for gradient handles.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FigmaVector {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Gradient color stop from Figma.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FigmaColorStop {
+    pub color: FigmaColor,
+    pub position: f32,
 }
 
 /// RGBA color from Figma (0.0-1.0 range).
@@ -117,6 +213,8 @@ impl FigmaColor {
 /// Response from the nodes endpoint.
 #[derive(Debug, Deserialize)]
 pub struct FigmaNodesResponse {
+    #[serde(default)]
+    pub version: Option<String>,
     pub nodes: HashMap<String, FigmaNodeWrapper>,
 }
 
@@ -126,6 +224,15 @@ pub struct FigmaNodeWrapper {
     pub document: FigmaNodeData,
 }
 
+/// Response from the `/me` endpoint: the user the token belongs to.
+#[derive(Debug, Deserialize)]
+pub struct FigmaUser {
+    pub id: String,
+    pub handle: String,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
 /// Response from the images export endpoint.
 #[derive(Debug, Deserialize)]
 pub struct FigmaImageExport {
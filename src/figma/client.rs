@@ -1,12 +1,13 @@
 //! Figma API client for fetching file data and exporting images.
 
 use crate::figma_client::FigmaAuth;
+use crate::image_loader::ImageLimits;
 use crate::DpcError;
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use serde::Deserialize;
 use thiserror::Error;
 
-use super::api_types::{FigmaImageExport, FigmaNodesResponse, ImageFormat};
+use super::api_types::{FigmaImageExport, FigmaNodesResponse, FigmaUser, ImageFormat};
 
 #[derive(Debug, Error)]
 pub enum FigmaError {
@@ -22,6 +23,18 @@ pub enum FigmaError {
     NodeNotFound(String),
     #[error("Rate limited, retry after {0} seconds")]
     RateLimited(u64),
+    #[error("No Figma frame matches \"{name}\"")]
+    FrameNotFound {
+        name: String,
+        suggestions: Vec<String>,
+    },
+    #[error("Figma frame name \"{name}\" is ambiguous")]
+    AmbiguousFrame {
+        name: String,
+        candidates: Vec<String>,
+    },
+    #[error("Download too large: {0}")]
+    TooLarge(String),
 }
 
 pub fn map_figma_error(e: FigmaError) -> DpcError {
@@ -49,9 +62,52 @@ pub fn map_figma_error(e: FigmaError) -> DpcError {
             status: Some(reqwest::StatusCode::TOO_MANY_REQUESTS),
             message: format!("Rate limited, retry after {} seconds", secs),
         },
+        FigmaError::FrameNotFound { name, suggestions } => {
+            let hint = if suggestions.is_empty() {
+                "the file has no top-level frames".to_string()
+            } else {
+                format!("did you mean {}?", suggestions.join(", "))
+            };
+            DpcError::Config(format!("No Figma frame matches \"{}\"; {}", name, hint))
+        }
+        FigmaError::AmbiguousFrame { name, candidates } => DpcError::Config(format!(
+            "Figma frame name \"{}\" is ambiguous; matches {}",
+            name,
+            candidates.join(", ")
+        )),
+        FigmaError::TooLarge(message) => {
+            DpcError::Config(format!("Figma image too large: {}", message))
+        }
     }
 }
 
+/// Read a response body, failing once it exceeds `max_bytes` rather than
+/// buffering an arbitrarily large download.
+pub(crate) async fn read_body_limited(
+    mut response: reqwest::Response,
+    max_bytes: u64,
+) -> std::result::Result<Vec<u8>, FigmaError> {
+    let too_large = |len: u64| {
+        FigmaError::TooLarge(format!(
+            "image download exceeds {} bytes (got {}; raise with --max-image-bytes)",
+            max_bytes, len
+        ))
+    };
+    if let Some(len) = response.content_length() {
+        if len > max_bytes {
+            return Err(too_large(len));
+        }
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > max_bytes {
+            return Err(too_large(body.len() as u64));
+        }
+    }
+    Ok(body)
+}
+
 #[derive(Debug)]
 pub struct FigmaClient {
     client: reqwest::Client,
@@ -74,6 +130,7 @@ impl FigmaClient {
     ) -> std::result::Result<Self, FigmaError> {
         let token = match &auth {
             FigmaAuth::PersonalAccessToken(token) | FigmaAuth::OAuthToken(token) => token.clone(),
+            FigmaAuth::OAuthSession(session) => session.current_access_token(),
         };
 
         if token.is_empty() {
@@ -82,13 +139,13 @@ impl FigmaClient {
 
         let mut headers = HeaderMap::new();
         match auth {
-            FigmaAuth::PersonalAccessToken(token) => {
+            FigmaAuth::PersonalAccessToken(_) => {
                 headers.insert(
                     reqwest::header::HeaderName::from_static("x-figma-token"),
                     HeaderValue::from_str(&token).map_err(|_| FigmaError::MissingToken)?,
                 );
             }
-            FigmaAuth::OAuthToken(token) => {
+            FigmaAuth::OAuthToken(_) | FigmaAuth::OAuthSession(_) => {
                 headers.insert(
                     AUTHORIZATION,
                     HeaderValue::from_str(&format!("Bearer {}", token))
@@ -97,9 +154,8 @@ impl FigmaClient {
             }
         }
 
-        let client = reqwest::Client::builder()
+        let client = crate::network::client_builder()
             .default_headers(headers)
-            .no_proxy()
             .timeout(std::time::Duration::from_secs(30))
             .build()?;
 
@@ -110,6 +166,14 @@ impl FigmaClient {
         })
     }
 
+    /// The user the token belongs to; a cheap way to check that it is valid.
+    pub async fn get_me(&self) -> std::result::Result<FigmaUser, FigmaError> {
+        let url = format!("{}/me", self.base_url);
+        let response = self.client.get(&url).send().await?;
+
+        self.handle_response(response).await
+    }
+
     pub async fn get_file(
         &self,
         file_key: &str,
@@ -166,7 +230,7 @@ impl FigmaClient {
                 message: response.text().await.unwrap_or_default(),
             });
         }
-        Ok(response.bytes().await?.to_vec())
+        read_body_limited(response, ImageLimits::current().max_bytes).await
     }
 
     async fn handle_response<T: for<'de> Deserialize<'de>>(
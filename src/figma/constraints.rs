@@ -0,0 +1,147 @@
+//! Constraint-based placement of Figma layers when a frame is resized.
+
+use crate::types::{
+    AutoLayout, AutoLayoutAlign, BoundingBox, Constraint, FigmaSnapshot, LayoutConstraints,
+    LayoutDirection,
+};
+use std::collections::HashMap;
+
+/// Predict where each node lands when the root `frame` (design units) is
+/// resized to `target` view pixels, and store it as
+/// [`FigmaNode::responsive_box`](crate::types::FigmaNode::responsive_box).
+///
+/// Layers follow their parent by their constraints, as they would in Figma;
+/// children of auto-layout frames follow the frame's alignment instead. Does
+/// nothing when no layer carries constraints or the frame already has the
+/// target size. Must run before the boxes are normalized to the image.
+pub fn apply_responsive_constraints(
+    snapshot: &mut FigmaSnapshot,
+    frame: BoundingBox,
+    target: (u32, u32),
+) {
+    let (target_w, target_h) = (target.0 as f32, target.1 as f32);
+    if frame.width <= 0.0 || frame.height <= 0.0 {
+        return;
+    }
+    if (frame.width - target_w).abs() < 0.5 && (frame.height - target_h).abs() < 0.5 {
+        return;
+    }
+    if snapshot.nodes.iter().all(|node| node.constraints.is_none()) {
+        return;
+    }
+
+    let mut parent_of: HashMap<&str, usize> = HashMap::new();
+    for (idx, node) in snapshot.nodes.iter().enumerate() {
+        for child in &node.children {
+            parent_of.insert(child.as_str(), idx);
+        }
+    }
+    let resized_frame = BoundingBox {
+        x: 0.0,
+        y: 0.0,
+        width: target_w,
+        height: target_h,
+    };
+
+    // Nodes are collected children first, so walking backwards places every
+    // parent before its children.
+    let mut predicted: Vec<Option<BoundingBox>> = vec![None; snapshot.nodes.len()];
+    for idx in (0..snapshot.nodes.len()).rev() {
+        let node = &snapshot.nodes[idx];
+        if node.id == snapshot.node_id {
+            predicted[idx] = Some(resized_frame);
+            continue;
+        }
+        let parent = parent_of
+            .get(node.id.as_str())
+            .and_then(|&p| Some((&snapshot.nodes[p], predicted[p]?)));
+        let (old_parent, new_parent, flow) = match parent {
+            Some((parent, resized)) => (parent.bounding_box, resized, parent.auto_layout),
+            None => (frame, resized_frame, None),
+        };
+        let constraints = match flow {
+            Some(layout) => flow_constraints(&layout),
+            None => node.constraints.unwrap_or(LayoutConstraints {
+                horizontal: Constraint::Min,
+                vertical: Constraint::Min,
+            }),
+        };
+        let bb = node.bounding_box;
+        let (x, width) = place(
+            constraints.horizontal,
+            (bb.x, bb.width),
+            (old_parent.x, old_parent.width),
+            (new_parent.x, new_parent.width),
+        );
+        let (y, height) = place(
+            constraints.vertical,
+            (bb.y, bb.height),
+            (old_parent.y, old_parent.height),
+            (new_parent.y, new_parent.height),
+        );
+        predicted[idx] = Some(BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    for (node, responsive_box) in snapshot.nodes.iter_mut().zip(predicted) {
+        node.responsive_box = responsive_box;
+    }
+}
+
+/// How children of an auto-layout frame move when the frame is resized.
+fn flow_constraints(layout: &AutoLayout) -> LayoutConstraints {
+    let primary = match layout.primary_align {
+        AutoLayoutAlign::Min | AutoLayoutAlign::Baseline => Constraint::Min,
+        AutoLayoutAlign::Center => Constraint::Center,
+        AutoLayoutAlign::Max => Constraint::Max,
+        AutoLayoutAlign::SpaceBetween => Constraint::Scale,
+    };
+    let counter = match layout.counter_align {
+        AutoLayoutAlign::Center => Constraint::Center,
+        AutoLayoutAlign::Max => Constraint::Max,
+        _ => Constraint::Min,
+    };
+    match layout.direction {
+        LayoutDirection::Horizontal => LayoutConstraints {
+            horizontal: primary,
+            vertical: counter,
+        },
+        LayoutDirection::Vertical => LayoutConstraints {
+            horizontal: counter,
+            vertical: primary,
+        },
+    }
+}
+
+/// Start and size of a span on one axis after its parent span moved from
+/// `old_parent` to `new_parent`; spans are `(start, size)`.
+fn place(
+    constraint: Constraint,
+    (start, size): (f32, f32),
+    (parent_start, parent_size): (f32, f32),
+    (new_start, new_size): (f32, f32),
+) -> (f32, f32) {
+    let before = start - parent_start;
+    let after = parent_start + parent_size - (start + size);
+    match constraint {
+        Constraint::Min => (new_start + before, size),
+        Constraint::Max => (new_start + new_size - after - size, size),
+        Constraint::Center => {
+            let offset = (start + size / 2.0) - (parent_start + parent_size / 2.0);
+            (new_start + new_size / 2.0 + offset - size / 2.0, size)
+        }
+        Constraint::Stretch => (new_start + before, (new_size - before - after).max(0.0)),
+        Constraint::Scale => {
+            let ratio = if parent_size > 0.0 {
+                new_size / parent_size
+            } else {
+                1.0
+            };
+            (new_start + before * ratio, size * ratio)
+        }
+    }
+}
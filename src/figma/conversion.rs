@@ -1,29 +1,231 @@
 //! Figma node tree building and mapping to internal types.
 
+use std::collections::HashMap;
+
 use crate::types::{
-    BoundingBox, FigmaNode, FigmaPaint, FigmaPaintKind, FigmaSnapshot, TypographyStyle,
+    AutoLayout, AutoLayoutAlign, BoundingBox, Constraint, EdgeInsets, FigmaNode, FigmaPaint,
+    FigmaPaintKind, FigmaSnapshot, Gradient, GradientKind, GradientStop, LayoutConstraints,
+    LayoutDirection, TypographyStyle,
+};
+use crate::{DpcError, Result};
+
+use super::api_types::{
+    FigmaAutoLayoutData, FigmaBoundingBox, FigmaDocument, FigmaFile, FigmaLayoutConstraint,
+    FigmaNodeData, FigmaNodesResponse, FigmaPaintData, FigmaTypeStyle,
 };
+use super::client::map_figma_error;
+use super::frames::{collect_frames, find_frame_by_name};
+
+/// Which node to take from a saved Figma response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FigmaNodeSelector<'a> {
+    pub node_id: Option<&'a str>,
+    pub frame_name: Option<&'a str>,
+}
+
+/// Set to `1`/`true` to keep hidden, fully transparent and clipped-away
+/// layers in Figma snapshots (for debugging the structural tree).
+pub const FIGMA_INCLUDE_HIDDEN_ENV: &str = "DPC_FIGMA_INCLUDE_HIDDEN";
 
-use super::api_types::{FigmaBoundingBox, FigmaNodeData, FigmaPaintData, FigmaTypeStyle};
+/// Whether `DPC_FIGMA_INCLUDE_HIDDEN` asks for unrendered layers.
+pub fn include_hidden_from_env() -> bool {
+    std::env::var(FIGMA_INCLUDE_HIDDEN_ENV)
+        .map(|value| matches!(value.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
 
 /// Build a FigmaSnapshot from raw Figma API data.
-pub fn build_figma_snapshot(file_key: &str, node_id: &str, root: &FigmaNodeData) -> FigmaSnapshot {
+///
+/// Layers that don't appear in the export (see [`collect_rendered_figma_nodes`])
+/// are left out unless `include_hidden` is set, so they can't show up as
+/// missing elements.
+pub fn build_figma_snapshot(
+    file_key: &str,
+    node_id: &str,
+    root: &FigmaNodeData,
+    include_hidden: bool,
+) -> FigmaSnapshot {
     let mut nodes = Vec::new();
-    collect_figma_nodes(root, &mut nodes);
+    if include_hidden {
+        collect_figma_nodes(root, &mut nodes);
+    } else {
+        collect_rendered_figma_nodes(root, &mut nodes);
+    }
 
     FigmaSnapshot {
+        schema_version: FigmaSnapshot::SCHEMA_VERSION,
         file_key: file_key.to_string(),
         node_id: node_id.to_string(),
         name: Some(root.name.clone()),
+        version: None,
+        frame_size: None,
+        export_scale: None,
         nodes,
+        styles: Vec::new(),
+    }
+}
+
+/// Build a FigmaSnapshot from a saved Figma REST response (offline input).
+///
+/// See [`node_from_json`] for the accepted JSON shapes.
+pub fn figma_snapshot_from_json(
+    json: &str,
+    file_key: &str,
+    selector: FigmaNodeSelector<'_>,
+) -> Result<FigmaSnapshot> {
+    let node = node_from_json(json, selector)?;
+    let mut snapshot = build_figma_snapshot(file_key, &node.id, &node, false);
+    snapshot.version = file_version_from_json(json);
+    Ok(snapshot)
+}
+
+/// Extract one node from a saved Figma REST response.
+///
+/// Accepts a `/v1/files/:key/nodes` response, a full `/v1/files/:key` response, or a
+/// bare node object. Without a selector the response must hold exactly one node
+/// (or, for full files, exactly one top-level frame).
+pub fn node_from_json(json: &str, selector: FigmaNodeSelector<'_>) -> Result<FigmaNodeData> {
+    let document = document_from_json(json)?;
+
+    let target_id = match (selector.node_id, selector.frame_name) {
+        (Some(id), _) => id.to_string(),
+        (None, Some(name)) => {
+            find_frame_by_name(&document, name)
+                .map_err(map_figma_error)?
+                .id
+        }
+        (None, None) => default_node_id(&document)?,
+    };
+
+    take_node(document.children, &target_id).ok_or_else(|| {
+        DpcError::Config(format!("Figma node {} not found in saved JSON", target_id))
+    })
+}
+
+/// The document of a saved Figma REST response, in any of the shapes
+/// [`node_from_json`] accepts; nodes and bare node responses are put on a
+/// single unnamed page.
+pub fn document_from_json(json: &str) -> Result<FigmaDocument> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    Ok(if value.get("nodes").is_some() {
+        let response: FigmaNodesResponse = serde_json::from_value(value)?;
+        let mut roots: Vec<FigmaNodeData> =
+            response.nodes.into_values().map(|n| n.document).collect();
+        roots.sort_by(|a, b| a.id.cmp(&b.id));
+        wrap_roots(roots)
+    } else if value.get("document").is_some() {
+        let file: FigmaFile = serde_json::from_value(value)?;
+        file.document
+    } else {
+        wrap_roots(vec![serde_json::from_value(value)?])
+    })
+}
+
+/// Top-level `version` of a saved files or nodes response, if present.
+pub fn file_version_from_json(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    value.get("version")?.as_str().map(str::to_string)
+}
+
+/// Put loose root nodes on a synthetic page so frame lookups work uniformly.
+fn wrap_roots(roots: Vec<FigmaNodeData>) -> FigmaDocument {
+    FigmaDocument {
+        id: String::new(),
+        name: String::new(),
+        node_type: "DOCUMENT".to_string(),
+        children: vec![FigmaNodeData {
+            id: String::new(),
+            name: String::new(),
+            node_type: "CANVAS".to_string(),
+            children: roots,
+            absolute_bounding_box: None,
+            absolute_render_bounds: None,
+            characters: None,
+            style: None,
+            fills: Vec::new(),
+            visible: true,
+            opacity: None,
+            clips_content: false,
+            constraints: None,
+            auto_layout: FigmaAutoLayoutData::default(),
+            transition_node_id: None,
+            interactions: Vec::new(),
+            styles: HashMap::new(),
+        }],
+    }
+}
+
+fn default_node_id(document: &FigmaDocument) -> Result<String> {
+    let pages = &document.children;
+    if let [page] = pages.as_slice() {
+        if page.id.is_empty() {
+            if let [root] = page.children.as_slice() {
+                return Ok(root.id.clone());
+            }
+        }
+    }
+    let frames = collect_frames(document);
+    match frames.as_slice() {
+        [frame] => Ok(frame.id.clone()),
+        _ => Err(DpcError::Config(format!(
+            "Saved Figma JSON contains {} frames; select one with #node-id=... or #frame-name=...",
+            frames.len()
+        ))),
+    }
+}
+
+fn take_node(nodes: Vec<FigmaNodeData>, id: &str) -> Option<FigmaNodeData> {
+    for node in nodes {
+        if node.id == id {
+            return Some(node);
+        }
+        if let Some(found) = take_node(node.children, id) {
+            return Some(found);
+        }
     }
+    None
 }
 
 /// Recursively collect all Figma nodes into a flat list.
 pub fn collect_figma_nodes(node: &FigmaNodeData, acc: &mut Vec<FigmaNode>) {
-    let children_ids: Vec<String> = node.children.iter().map(|c| c.id.clone()).collect();
-    for child in &node.children {
-        collect_figma_nodes(child, acc);
+    collect_nodes(node, None, false, acc);
+}
+
+/// Like [`collect_figma_nodes`], but skip subtrees that the export doesn't
+/// show: layers with `visible: false` or opacity 0, and layers lying entirely
+/// outside an ancestor frame that clips its content. `node` itself is always
+/// kept.
+pub fn collect_rendered_figma_nodes(node: &FigmaNodeData, acc: &mut Vec<FigmaNode>) {
+    collect_nodes(node, None, true, acc);
+}
+
+/// Whether a layer is drawn at all, ignoring clipping.
+fn is_drawn(node: &FigmaNodeData) -> bool {
+    node.visible && node.opacity.is_none_or(|opacity| opacity > 0.0)
+}
+
+fn collect_nodes(
+    node: &FigmaNodeData,
+    clip: Option<&FigmaBoundingBox>,
+    prune: bool,
+    acc: &mut Vec<FigmaNode>,
+) {
+    let child_clip = match (node.clips_content, node.absolute_bounding_box.as_ref()) {
+        (true, Some(bounds)) => Some(clip.map_or(*bounds, |clip| intersect_boxes(clip, bounds))),
+        _ => clip.copied(),
+    };
+    let children: Vec<&FigmaNodeData> = node
+        .children
+        .iter()
+        .filter(|child| {
+            !prune
+                || (is_drawn(child)
+                    && !is_clipped_out(child.absolute_bounding_box.as_ref(), child_clip.as_ref()))
+        })
+        .collect();
+    let children_ids: Vec<String> = children.iter().map(|c| c.id.clone()).collect();
+    for child in children {
+        collect_nodes(child, child_clip.as_ref(), prune, acc);
     }
 
     acc.push(FigmaNode {
@@ -33,11 +235,96 @@ pub fn collect_figma_nodes(node: &FigmaNodeData, acc: &mut Vec<FigmaNode>) {
         bounding_box: map_bounding_box(node.absolute_bounding_box.as_ref()),
         text: node.characters.clone(),
         typography: node.style.as_ref().map(map_typography),
-        fills: node.fills.iter().filter_map(map_paint).collect(),
+        // Design units: bounding boxes are rescaled later, line heights are not.
+        line_count: node.characters.as_ref().and_then(|_| {
+            let height = node.absolute_bounding_box.as_ref()?.height;
+            map_typography(node.style.as_ref()?).line_count(height)
+        }),
+        fills: node
+            .fills
+            .iter()
+            .filter_map(|paint| map_paint(paint, node.absolute_bounding_box.as_ref()))
+            .collect(),
         children: children_ids,
+        auto_layout: map_auto_layout(&node.auto_layout),
+        constraints: node.constraints.as_ref().map(map_constraints),
+        responsive_box: None,
+        styles: node.styles.clone(),
     });
 }
 
+/// Overlap of two boxes; empty (zero-sized) when they don't overlap.
+fn intersect_boxes(a: &FigmaBoundingBox, b: &FigmaBoundingBox) -> FigmaBoundingBox {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    FigmaBoundingBox {
+        x,
+        y,
+        width: ((a.x + a.width).min(b.x + b.width) - x).max(0.0),
+        height: ((a.y + a.height).min(b.y + b.height) - y).max(0.0),
+    }
+}
+
+/// A box with area that doesn't overlap the clip region at all. Boxless and
+/// zero-sized layers (e.g. lines) are never treated as clipped out.
+fn is_clipped_out(bounds: Option<&FigmaBoundingBox>, clip: Option<&FigmaBoundingBox>) -> bool {
+    let (Some(bounds), Some(clip)) = (bounds, clip) else {
+        return false;
+    };
+    if bounds.width <= 0.0 && bounds.height <= 0.0 {
+        return false;
+    }
+    bounds.x >= clip.x + clip.width
+        || bounds.x + bounds.width <= clip.x
+        || bounds.y >= clip.y + clip.height
+        || bounds.y + bounds.height <= clip.y
+}
+
+/// Map a frame's auto-layout properties; `None` without auto layout.
+pub fn map_auto_layout(data: &FigmaAutoLayoutData) -> Option<AutoLayout> {
+    let direction = match data.layout_mode.as_deref()? {
+        "HORIZONTAL" => LayoutDirection::Horizontal,
+        "VERTICAL" => LayoutDirection::Vertical,
+        _ => return None,
+    };
+    let align = |value: Option<&str>| match value {
+        Some("CENTER") => AutoLayoutAlign::Center,
+        Some("MAX") => AutoLayoutAlign::Max,
+        Some("SPACE_BETWEEN") => AutoLayoutAlign::SpaceBetween,
+        Some("BASELINE") => AutoLayoutAlign::Baseline,
+        _ => AutoLayoutAlign::Min,
+    };
+    Some(AutoLayout {
+        direction,
+        gap: data.item_spacing.unwrap_or(0.0),
+        padding: EdgeInsets {
+            top: data.padding_top.unwrap_or(0.0),
+            right: data.padding_right.unwrap_or(0.0),
+            bottom: data.padding_bottom.unwrap_or(0.0),
+            left: data.padding_left.unwrap_or(0.0),
+        },
+        primary_align: align(data.primary_axis_align_items.as_deref()),
+        counter_align: align(data.counter_axis_align_items.as_deref()),
+        wrap: data.layout_wrap.as_deref() == Some("WRAP"),
+    })
+}
+
+/// Map a layer's `constraints`; unknown values fall back to Figma's default
+/// of pinning to the left/top edge.
+pub fn map_constraints(data: &FigmaLayoutConstraint) -> LayoutConstraints {
+    let constraint = |value: &str| match value {
+        "RIGHT" | "BOTTOM" => Constraint::Max,
+        "CENTER" => Constraint::Center,
+        "LEFT_RIGHT" | "TOP_BOTTOM" => Constraint::Stretch,
+        "SCALE" => Constraint::Scale,
+        _ => Constraint::Min,
+    };
+    LayoutConstraints {
+        horizontal: constraint(&data.horizontal),
+        vertical: constraint(&data.vertical),
+    }
+}
+
 /// Map Figma typography style to internal TypographyStyle.
 pub fn map_typography(style: &FigmaTypeStyle) -> TypographyStyle {
     TypographyStyle {
@@ -65,8 +352,9 @@ pub fn map_bounding_box(bb: Option<&FigmaBoundingBox>) -> BoundingBox {
     })
 }
 
-/// Map Figma paint data to internal FigmaPaint.
-pub fn map_paint(paint: &FigmaPaintData) -> Option<FigmaPaint> {
+/// Map Figma paint data to internal FigmaPaint. `bbox` is the painted node's
+/// box, which gradient handles are relative to.
+pub fn map_paint(paint: &FigmaPaintData, bbox: Option<&FigmaBoundingBox>) -> Option<FigmaPaint> {
     let kind = match paint.paint_type.to_lowercase().as_str() {
         "solid" => FigmaPaintKind::Solid,
         "image" => FigmaPaintKind::Image,
@@ -78,5 +366,46 @@ pub fn map_paint(paint: &FigmaPaintData) -> Option<FigmaPaint> {
         kind,
         color: paint.color.as_ref().map(|c| c.to_hex()),
         opacity: paint.opacity,
+        gradient: map_gradient(paint, bbox),
+    })
+}
+
+/// Map a Figma gradient paint to a CSS-style [`Gradient`].
+///
+/// The direction runs from the first to the second gradient handle. Handles
+/// are in the node's unit square, so they are stretched by the box size
+/// before taking the angle.
+pub fn map_gradient(paint: &FigmaPaintData, bbox: Option<&FigmaBoundingBox>) -> Option<Gradient> {
+    let kind = match paint.paint_type.to_ascii_uppercase().as_str() {
+        "GRADIENT_LINEAR" => GradientKind::Linear,
+        "GRADIENT_RADIAL" | "GRADIENT_DIAMOND" => GradientKind::Radial,
+        "GRADIENT_ANGULAR" => GradientKind::Conic,
+        _ => return None,
+    };
+    if paint.gradient_stops.is_empty() {
+        return None;
+    }
+    let angle = match (kind, paint.gradient_handle_positions.as_slice()) {
+        (GradientKind::Linear, [start, end, ..]) => {
+            let (width, height) = bbox.map_or((1.0, 1.0), |b| (b.width, b.height));
+            let dx = (end.x - start.x) * width;
+            let dy = (end.y - start.y) * height;
+            // CSS angles start at "to top" and turn clockwise; y grows downwards.
+            Some(dx.atan2(-dy).to_degrees().rem_euclid(360.0))
+        }
+        (GradientKind::Linear, _) => Some(180.0),
+        _ => None,
+    };
+    Some(Gradient {
+        kind,
+        angle,
+        stops: paint
+            .gradient_stops
+            .iter()
+            .map(|stop| GradientStop {
+                color: stop.color.to_hex(),
+                position: stop.position.clamp(0.0, 1.0),
+            })
+            .collect(),
     })
 }
@@ -0,0 +1,83 @@
+//! Prototype flow discovery: the screens reachable from a start frame.
+//!
+//! A prototype links frames through interactions ("on click, navigate to").
+//! Following the navigate links from a start frame, breadth first, yields the
+//! screens of the flow in the order a user would reach them. Overlays, swaps
+//! and scroll-to links stay on the current screen and are not followed.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use super::api_types::{FigmaDocument, FigmaNodeData};
+use super::client::{map_figma_error, FigmaClient, FigmaError};
+use super::frames::{frame_nodes, FrameCandidate};
+use crate::Result;
+
+/// Top-level frames reachable from `start` (a frame node id) through
+/// prototype navigation, starting with `start` itself.
+pub fn prototype_flow(
+    document: &FigmaDocument,
+    start: &str,
+) -> std::result::Result<Vec<FrameCandidate>, FigmaError> {
+    let frames: HashMap<&str, (FrameCandidate, &FigmaNodeData)> = frame_nodes(document)
+        .into_iter()
+        .map(|(frame, node)| (node.id.as_str(), (frame, node)))
+        .collect();
+    if !frames.contains_key(start) {
+        return Err(FigmaError::NodeNotFound(format!(
+            "{start} (a prototype flow must start at a top-level frame)"
+        )));
+    }
+
+    let mut seen = BTreeSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut flow = Vec::new();
+    while let Some(id) = queue.pop_front() {
+        let (frame, node) = &frames[id];
+        flow.push(frame.clone());
+        let mut targets = Vec::new();
+        navigation_targets(node, &mut targets);
+        for target in targets {
+            if let Some((key, _)) = frames.get_key_value(target) {
+                if seen.insert(key) {
+                    queue.push_back(key);
+                }
+            }
+        }
+    }
+    Ok(flow)
+}
+
+/// Destinations of the navigate links on `node` and its descendants, in
+/// document order.
+fn navigation_targets<'a>(node: &'a FigmaNodeData, out: &mut Vec<&'a str>) {
+    if let Some(target) = &node.transition_node_id {
+        out.push(target);
+    }
+    for action in node
+        .interactions
+        .iter()
+        .flat_map(|interaction| interaction.actions.iter().flatten())
+    {
+        let navigates = action.navigation.as_deref().unwrap_or("NAVIGATE") == "NAVIGATE";
+        if let (true, "NODE", Some(target)) = (
+            navigates,
+            action.action_type.as_str(),
+            &action.destination_id,
+        ) {
+            out.push(target);
+        }
+    }
+    for child in &node.children {
+        navigation_targets(child, out);
+    }
+}
+
+/// Fetch the file tree and list the prototype flow starting at `start`.
+pub async fn resolve_prototype_flow(
+    client: &FigmaClient,
+    file_key: &str,
+    start: &str,
+) -> Result<Vec<FrameCandidate>> {
+    let file = client.get_file(file_key).await.map_err(map_figma_error)?;
+    prototype_flow(&file.document, start).map_err(map_figma_error)
+}
@@ -0,0 +1,134 @@
+//! Frame discovery: resolve a human-readable frame name to a Figma node id.
+//!
+//! Frames are collected from the top level of every page (and from sections on
+//! those pages). Names are compared case-insensitively with whitespace and
+//! slash spacing normalized, so `checkout/desktop` matches `Checkout / Desktop`.
+
+use super::api_types::{FigmaDocument, FigmaNodeData};
+use super::client::{map_figma_error, FigmaClient, FigmaError};
+use crate::strings::edit_distance;
+use crate::Result;
+
+/// Node types that can be targeted by name.
+const FRAME_TYPES: &[&str] = &["FRAME", "COMPONENT", "COMPONENT_SET"];
+/// Container types whose children are still considered top-level frames.
+const CONTAINER_TYPES: &[&str] = &["SECTION"];
+/// Maximum number of suggestions reported when no frame matches.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A frame discovered in a Figma document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCandidate {
+    pub id: String,
+    pub name: String,
+    pub page: String,
+}
+
+impl FrameCandidate {
+    fn describe(&self) -> String {
+        format!("\"{}\" on page \"{}\" ({})", self.name, self.page, self.id)
+    }
+}
+
+/// Collect every targetable frame in document order.
+pub fn collect_frames(document: &FigmaDocument) -> Vec<FrameCandidate> {
+    frame_nodes(document)
+        .into_iter()
+        .map(|(frame, _)| frame)
+        .collect()
+}
+
+/// Every targetable frame with its node, in document order.
+pub(super) fn frame_nodes(document: &FigmaDocument) -> Vec<(FrameCandidate, &FigmaNodeData)> {
+    let mut frames = Vec::new();
+    for page in &document.children {
+        collect_page_frames(&page.name, &page.children, &mut frames);
+    }
+    frames
+}
+
+fn collect_page_frames<'a>(
+    page: &str,
+    nodes: &'a [FigmaNodeData],
+    out: &mut Vec<(FrameCandidate, &'a FigmaNodeData)>,
+) {
+    for node in nodes {
+        if FRAME_TYPES.contains(&node.node_type.as_str()) {
+            let frame = FrameCandidate {
+                id: node.id.clone(),
+                name: node.name.clone(),
+                page: page.to_string(),
+            };
+            out.push((frame, node));
+        } else if CONTAINER_TYPES.contains(&node.node_type.as_str()) {
+            collect_page_frames(page, &node.children, out);
+        }
+    }
+}
+
+/// Resolve `name` to a single frame.
+///
+/// Matching is tried in order: exact (normalized) frame name, exact
+/// `Page / Frame` path, then substring. The first tier with any hit decides;
+/// more than one hit in that tier is reported as ambiguous.
+pub fn find_frame_by_name(
+    document: &FigmaDocument,
+    name: &str,
+) -> std::result::Result<FrameCandidate, FigmaError> {
+    let query = normalize_name(name);
+    let frames = collect_frames(document);
+
+    let tiers: [&dyn Fn(&FrameCandidate) -> bool; 3] = [
+        &|f| normalize_name(&f.name) == query,
+        &|f| normalize_name(&format!("{} / {}", f.page, f.name)) == query,
+        &|f| !query.is_empty() && normalize_name(&f.name).contains(&query),
+    ];
+
+    for matches in tiers {
+        let hits: Vec<&FrameCandidate> = frames.iter().filter(|f| matches(f)).collect();
+        match hits.as_slice() {
+            [] => continue,
+            [single] => return Ok((*single).clone()),
+            _ => {
+                return Err(FigmaError::AmbiguousFrame {
+                    name: name.to_string(),
+                    candidates: hits.iter().map(|f| f.describe()).collect(),
+                })
+            }
+        }
+    }
+
+    let mut ranked: Vec<(usize, &FrameCandidate)> = frames
+        .iter()
+        .map(|f| (edit_distance(&normalize_name(&f.name), &query), f))
+        .collect();
+    ranked.sort_by_key(|(distance, _)| *distance);
+    Err(FigmaError::FrameNotFound {
+        name: name.to_string(),
+        suggestions: ranked
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, f)| f.describe())
+            .collect(),
+    })
+}
+
+/// Fetch the file tree and resolve `name` to a node id.
+pub async fn resolve_frame_node_id(
+    client: &FigmaClient,
+    file_key: &str,
+    name: &str,
+) -> Result<String> {
+    let file = client.get_file(file_key).await.map_err(map_figma_error)?;
+    find_frame_by_name(&file.document, name)
+        .map(|frame| frame.id)
+        .map_err(map_figma_error)
+}
+
+fn normalize_name(name: &str) -> String {
+    name.replace('/', " / ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
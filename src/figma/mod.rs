@@ -4,11 +4,17 @@
 //! - [`FigmaClient`] - HTTP client for the Figma REST API
 //! - [`figma_to_normalized_view`] - Main conversion function
 //! - [`FigmaRenderOptions`] - Configuration for Figma exports
+//! - [`figma_json_to_normalized_view`] - Offline conversion from saved JSON + PNG
+//! - [`resolve_frame_node_id`] - Frame lookup by name
+//! - [`prototype_flow`] - Frames reachable from a start frame through prototype links
 //! - API types for parsing Figma JSON responses
 
 pub mod api_types;
 pub mod client;
+pub mod constraints;
 pub mod conversion;
+pub mod flows;
+pub mod frames;
 pub mod transform;
 
 #[cfg(test)]
@@ -16,17 +22,28 @@ mod tests;
 
 // Re-export primary public API
 pub use client::{map_figma_error, FigmaClient, FigmaError};
-pub use transform::FigmaRenderOptions;
+pub use conversion::{
+    document_from_json, figma_snapshot_from_json, include_hidden_from_env, node_from_json,
+    FigmaNodeSelector, FIGMA_INCLUDE_HIDDEN_ENV,
+};
+pub use flows::{prototype_flow, resolve_prototype_flow};
+pub use frames::{collect_frames, find_frame_by_name, resolve_frame_node_id, FrameCandidate};
+pub use transform::{FigmaOfflineOptions, FigmaRenderOptions};
 
 // Re-export API types that may be needed externally
 pub use api_types::{
-    FigmaBoundingBox, FigmaColor, FigmaDocument, FigmaFile, FigmaImageExport, FigmaNodeData,
-    FigmaNodeWrapper, FigmaNodesResponse, FigmaPaintData, FigmaTypeStyle, ImageFormat,
+    FigmaAction, FigmaBoundingBox, FigmaColor, FigmaColorStop, FigmaDocument, FigmaFile,
+    FigmaImageExport, FigmaInteraction, FigmaLayoutConstraint, FigmaNodeData, FigmaNodeWrapper,
+    FigmaNodesResponse, FigmaPaintData, FigmaTypeStyle, FigmaUser, FigmaVector, ImageFormat,
 };
 
-use crate::types::{NormalizedView, ResourceKind};
-use crate::{DpcError, Result};
-use image::{load_from_memory, GenericImageView};
+use crate::image_loader::{
+    decode_image_bytes, letterbox_content_area, load_image_with_limits, ImageLimits,
+};
+use crate::types::{FigmaSnapshot, NormalizedView, ResourceKind};
+use crate::{DpcError, Result, Viewport};
+use image::{DynamicImage, GenericImageView};
+use std::path::Path;
 
 /// Convert a Figma frame to a NormalizedView.
 ///
@@ -39,7 +56,7 @@ pub async fn figma_to_normalized_view(
     client: &FigmaClient,
     options: &FigmaRenderOptions,
 ) -> Result<NormalizedView> {
-    if options.scale <= 0.0 {
+    if options.scale.is_some_and(|scale| scale <= 0.0) {
         return Err(DpcError::Config(
             "Figma export scale must be greater than zero".to_string(),
         ));
@@ -73,15 +90,32 @@ pub async fn figma_to_normalized_view(
             message: format!("Node {} not found in Figma response", options.node_id),
         })?;
 
-    let figma_snapshot =
-        conversion::build_figma_snapshot(&options.file_key, &options.node_id, &node.document);
+    let mut figma_snapshot = conversion::build_figma_snapshot(
+        &options.file_key,
+        &options.node_id,
+        &node.document,
+        options.include_hidden,
+    );
+    figma_snapshot.version = nodes_response.version.clone();
+
+    // Without a scale the export is sized from its render bounds, which is
+    // what the PNG will cover.
+    let scale = options.scale.unwrap_or_else(|| {
+        node.document
+            .absolute_render_bounds
+            .as_ref()
+            .or(node.document.absolute_bounding_box.as_ref())
+            .map(|bb| transform::auto_export_scale(bb.width, bb.height, options.viewport))
+            .unwrap_or(1.0)
+    });
+    figma_snapshot.export_scale = Some(scale);
 
     let image_url = client
         .export_image(
             &options.file_key,
             &options.node_id,
             api_types::ImageFormat::Png,
-            options.scale,
+            scale,
         )
         .await
         .map_err(map_figma_error)?;
@@ -91,26 +125,103 @@ pub async fn figma_to_normalized_view(
         .await
         .map_err(map_figma_error)?;
 
-    let decoded_image = load_from_memory(&bytes)?;
+    let decoded_image = decode_image_bytes(&bytes, &ImageLimits::current(), "Figma export")?;
+    finish_normalized_view(
+        figma_snapshot,
+        &node.document,
+        decoded_image,
+        &options.output_path,
+        options.viewport,
+    )
+}
+
+/// Convert a saved Figma REST response plus its exported PNG to a NormalizedView.
+///
+/// Runs fully offline; see [`conversion::node_from_json`] for the accepted JSON shapes.
+pub fn figma_json_to_normalized_view(options: &FigmaOfflineOptions) -> Result<NormalizedView> {
+    if options.output_path.as_os_str().is_empty() {
+        return Err(DpcError::Config(
+            "Figma export output_path is required".to_string(),
+        ));
+    }
+
+    let json = std::fs::read_to_string(&options.json_path)?;
+    let selector = FigmaNodeSelector {
+        node_id: options.node_id.as_deref(),
+        frame_name: options.frame_name.as_deref(),
+    };
+    let node = conversion::node_from_json(&json, selector)?;
+    let mut figma_snapshot = conversion::build_figma_snapshot(
+        &options.file_key,
+        &node.id,
+        &node,
+        options.include_hidden,
+    );
+    figma_snapshot.version = conversion::file_version_from_json(&json);
+    let decoded_image = load_image_with_limits(&options.image_path, &ImageLimits::current())?;
+
+    finish_normalized_view(
+        figma_snapshot,
+        &node,
+        decoded_image,
+        &options.output_path,
+        options.viewport,
+    )
+}
+
+fn finish_normalized_view(
+    figma_snapshot: FigmaSnapshot,
+    root: &FigmaNodeData,
+    decoded_image: DynamicImage,
+    output_path: &Path,
+    viewport: Option<Viewport>,
+) -> Result<NormalizedView> {
     let source_dimensions = decoded_image.dimensions();
     let (width, height, letterbox) =
-        transform::finalize_figma_image(decoded_image, &options.output_path, options.viewport)?;
+        transform::finalize_figma_image(decoded_image, output_path, viewport)?;
+
+    let mut figma_snapshot = figma_snapshot;
+    if let (Some(_), Some(frame)) = (viewport, root.absolute_bounding_box.as_ref()) {
+        constraints::apply_responsive_constraints(
+            &mut figma_snapshot,
+            conversion::map_bounding_box(Some(frame)),
+            (width, height),
+        );
+    }
 
-    let root_bb = node
-        .document
-        .absolute_bounding_box
-        .as_ref()
-        .map(|bb| conversion::map_bounding_box(Some(bb)));
-    let figma_snapshot =
-        transform::normalize_figma_snapshot(figma_snapshot, root_bb, source_dimensions, &letterbox);
+    let root_bb = transform::export_bounds(
+        root.absolute_bounding_box
+            .as_ref()
+            .map(|bb| conversion::map_bounding_box(Some(bb))),
+        root.absolute_render_bounds
+            .as_ref()
+            .map(|bb| conversion::map_bounding_box(Some(bb))),
+        source_dimensions,
+    );
+    let figma_snapshot = FigmaSnapshot {
+        schema_version: FigmaSnapshot::SCHEMA_VERSION,
+        frame_size: Some(source_dimensions),
+        ..transform::normalize_figma_snapshot(
+            figma_snapshot,
+            root_bb,
+            source_dimensions,
+            &letterbox,
+        )
+    };
 
     Ok(NormalizedView {
         kind: ResourceKind::Figma,
-        screenshot_path: options.output_path.clone(),
+        screenshot_path: output_path.to_path_buf(),
         width,
         height,
         dom: None,
         figma_tree: Some(figma_snapshot),
         ocr_blocks: None,
+        content_area: letterbox_content_area(
+            source_dimensions.0,
+            source_dimensions.1,
+            width,
+            height,
+        ),
     })
 }
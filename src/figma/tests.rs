@@ -2,17 +2,30 @@
 
 #[cfg(test)]
 mod figma_tests {
+    use crate::figma::api_types::FigmaDocument;
     use crate::figma::api_types::{
         FigmaBoundingBox, FigmaColor, FigmaNodeData, FigmaPaintData, FigmaTypeStyle, ImageFormat,
     };
     use crate::figma::client::{FigmaClient, FigmaError};
-    use crate::figma::conversion::collect_figma_nodes;
+    use crate::figma::constraints::apply_responsive_constraints;
+    use crate::figma::conversion::{
+        build_figma_snapshot, collect_figma_nodes, file_version_from_json, map_paint,
+        node_from_json, FigmaNodeSelector,
+    };
+    use crate::figma::flows::prototype_flow;
+    use crate::figma::frames::{collect_frames, find_frame_by_name};
     use crate::figma::transform::{
-        compute_letterbox_transform, finalize_figma_image, normalize_figma_snapshot,
+        auto_export_scale, compute_letterbox_transform, export_bounds, finalize_figma_image,
+        normalize_figma_snapshot,
+    };
+    use crate::figma::{figma_json_to_normalized_view, FigmaOfflineOptions};
+    use crate::types::{
+        AutoLayoutAlign, BoundingBox, FigmaNode, FigmaSnapshot, GradientKind, LayoutDirection,
+        TypographyStyle,
     };
-    use crate::types::{BoundingBox, FigmaNode, FigmaSnapshot, TypographyStyle};
     use crate::Viewport;
     use image::{DynamicImage, GenericImageView, RgbaImage};
+    use std::collections::HashMap;
     use tempfile::TempDir;
 
     #[test]
@@ -72,6 +85,7 @@ mod figma_tests {
             name: "Heading".to_string(),
             node_type: "TEXT".to_string(),
             children: vec![],
+            absolute_render_bounds: None,
             absolute_bounding_box: Some(FigmaBoundingBox {
                 x: 10.0,
                 y: 20.0,
@@ -79,6 +93,14 @@ mod figma_tests {
                 height: 30.0,
             }),
             characters: Some("Hello".to_string()),
+            visible: true,
+            opacity: None,
+            clips_content: false,
+            constraints: None,
+            auto_layout: Default::default(),
+            transition_node_id: None,
+            interactions: Vec::new(),
+            styles: HashMap::new(),
             style: Some(FigmaTypeStyle {
                 font_family: Some("Inter".to_string()),
                 font_size: Some(16.0),
@@ -94,6 +116,8 @@ mod figma_tests {
                     a: 1.0,
                 }),
                 opacity: Some(0.8),
+                gradient_handle_positions: vec![],
+                gradient_stops: vec![],
             }],
         };
 
@@ -102,6 +126,7 @@ mod figma_tests {
             name: "Frame".to_string(),
             node_type: "FRAME".to_string(),
             children: vec![child],
+            absolute_render_bounds: None,
             absolute_bounding_box: Some(FigmaBoundingBox {
                 x: 0.0,
                 y: 0.0,
@@ -111,6 +136,14 @@ mod figma_tests {
             characters: None,
             style: None,
             fills: vec![],
+            visible: true,
+            opacity: None,
+            clips_content: false,
+            constraints: None,
+            auto_layout: Default::default(),
+            transition_node_id: None,
+            interactions: Vec::new(),
+            styles: HashMap::new(),
         };
 
         let mut nodes = Vec::new();
@@ -132,6 +165,41 @@ mod figma_tests {
         assert!((root_node.bounding_box.width - 1200.0).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn map_paint_reads_gradient_direction_and_stops() {
+        let paint: FigmaPaintData = serde_json::from_str(
+            r#"{
+                "type": "GRADIENT_LINEAR",
+                "gradientHandlePositions": [
+                    {"x": 0.0, "y": 0.5}, {"x": 1.0, "y": 0.5}, {"x": 0.0, "y": 1.0}
+                ],
+                "gradientStops": [
+                    {"color": {"r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0}, "position": 0.0},
+                    {"color": {"r": 0.0, "g": 0.0, "b": 1.0, "a": 1.0}, "position": 1.0}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let bbox = FigmaBoundingBox {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        };
+
+        let mapped = map_paint(&paint, Some(&bbox)).unwrap();
+        let gradient = mapped.gradient.expect("gradient");
+        assert_eq!(gradient.kind, GradientKind::Linear);
+        assert!((gradient.angle.unwrap() - 90.0).abs() < 1e-3);
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.stops[0].color, "#ff0000");
+        assert_eq!(gradient.stops[1].position, 1.0);
+        assert_eq!(
+            gradient.to_css(),
+            "linear-gradient(90deg, #ff0000 0%, #0000ff 100%)"
+        );
+    }
+
     #[test]
     fn finalize_figma_image_resizes_to_viewport() {
         let dir = TempDir::new().expect("tempdir");
@@ -140,21 +208,26 @@ mod figma_tests {
         let img = RgbaImage::from_pixel(10, 5, image::Rgba([255, 0, 0, 255]));
         let img = DynamicImage::ImageRgba8(img);
 
-        let (w, h, _) = finalize_figma_image(
-            img,
-            &out_path,
-            Some(Viewport {
-                width: 20,
-                height: 20,
-            }),
-        )
-        .expect("finalize");
+        let (w, h, _) =
+            finalize_figma_image(img, &out_path, Some(Viewport::new(20, 20))).expect("finalize");
 
         assert_eq!((w, h), (20, 20));
         let saved = image::open(&out_path).expect("open saved");
         assert_eq!(saved.dimensions(), (20, 20));
     }
 
+    #[test]
+    fn auto_export_scale_matches_viewport_pixels() {
+        let desktop = Some(Viewport::new(1440, 900));
+        assert_eq!(auto_export_scale(1440.0, 900.0, desktop), 1.0);
+        assert_eq!(auto_export_scale(720.0, 450.0, desktop), 2.0);
+        // Tall frames are fitted by height, like the letterbox.
+        assert_eq!(auto_export_scale(720.0, 1800.0, desktop), 0.5);
+        assert_eq!(auto_export_scale(10.0, 10.0, desktop), 4.0);
+        assert_eq!(auto_export_scale(720.0, 450.0, None), 1.0);
+        assert_eq!(auto_export_scale(0.0, 450.0, desktop), 1.0);
+    }
+
     #[test]
     fn finalize_figma_image_keeps_original_size_when_no_viewport() {
         let dir = TempDir::new().expect("tempdir");
@@ -182,9 +255,13 @@ mod figma_tests {
             height: 50.0,
         };
         let mut snapshot = FigmaSnapshot {
+            schema_version: FigmaSnapshot::SCHEMA_VERSION,
             file_key: "FILE".into(),
             node_id: "root".into(),
             name: Some("Frame".into()),
+            version: None,
+            frame_size: None,
+            export_scale: None,
             nodes: vec![
                 FigmaNode {
                     id: "root".into(),
@@ -193,8 +270,13 @@ mod figma_tests {
                     bounding_box: root_bb,
                     text: None,
                     typography: None,
+                    line_count: None,
                     fills: vec![],
                     children: vec!["child".into()],
+                    auto_layout: None,
+                    constraints: None,
+                    responsive_box: None,
+                    styles: HashMap::new(),
                 },
                 FigmaNode {
                     id: "child".into(),
@@ -214,10 +296,16 @@ mod figma_tests {
                         line_height: Some(24.0),
                         letter_spacing: None,
                     }),
+                    line_count: Some(1),
                     fills: vec![],
                     children: vec![],
+                    auto_layout: None,
+                    constraints: None,
+                    responsive_box: None,
+                    styles: HashMap::new(),
                 },
             ],
+            styles: Vec::new(),
         };
 
         let letterbox = compute_letterbox_transform(100, 50, 200, 200);
@@ -235,4 +323,469 @@ mod figma_tests {
         assert!((child.bounding_box.width - 40.0).abs() < f32::EPSILON);
         assert!((child.bounding_box.height - 20.0).abs() < f32::EPSILON);
     }
+
+    fn frame_document() -> FigmaDocument {
+        serde_json::from_value(frame_document_json()).expect("valid document")
+    }
+
+    fn frame_document_json() -> serde_json::Value {
+        serde_json::json!({
+            "id": "0:0",
+            "name": "Document",
+            "type": "DOCUMENT",
+            "children": [
+                {
+                    "id": "0:1",
+                    "name": "Flows",
+                    "type": "CANVAS",
+                    "children": [
+                        {"id": "1:1", "name": "Checkout / Desktop", "type": "FRAME"},
+                        {"id": "1:2", "name": "Checkout / Mobile", "type": "FRAME"},
+                        {"id": "1:3", "name": "Note", "type": "TEXT"},
+                        {
+                            "id": "1:4",
+                            "name": "Onboarding",
+                            "type": "SECTION",
+                            "children": [
+                                {"id": "1:5", "name": "Welcome", "type": "FRAME"}
+                            ]
+                        }
+                    ]
+                },
+                {
+                    "id": "0:2",
+                    "name": "Archive",
+                    "type": "CANVAS",
+                    "children": [
+                        {"id": "2:1", "name": "Welcome", "type": "FRAME"}
+                    ]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn collect_frames_includes_section_children_and_skips_non_frames() {
+        let ids: Vec<String> = collect_frames(&frame_document())
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(ids, vec!["1:1", "1:2", "1:5", "2:1"]);
+    }
+
+    #[test]
+    fn find_frame_by_name_normalizes_case_and_slashes() {
+        let doc = frame_document();
+        let frame = find_frame_by_name(&doc, "checkout/desktop").unwrap();
+        assert_eq!(frame.id, "1:1");
+
+        let frame = find_frame_by_name(&doc, "Archive / Welcome").unwrap();
+        assert_eq!(frame.id, "2:1");
+
+        let frame = find_frame_by_name(&doc, "mobile").unwrap();
+        assert_eq!(frame.id, "1:2");
+    }
+
+    #[test]
+    fn find_frame_by_name_reports_ambiguity_and_suggestions() {
+        let doc = frame_document();
+        match find_frame_by_name(&doc, "Welcome") {
+            Err(FigmaError::AmbiguousFrame { candidates, .. }) => {
+                assert_eq!(candidates.len(), 2);
+                assert!(candidates[0].contains("Flows"));
+            }
+            other => panic!("expected ambiguity, got {other:?}"),
+        }
+        match find_frame_by_name(&doc, "Chekout / Desktp") {
+            Err(FigmaError::FrameNotFound { suggestions, .. }) => {
+                assert!(suggestions[0].contains("Checkout / Desktop"));
+            }
+            other => panic!("expected not found, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn prototype_flow_follows_navigate_links_breadth_first() {
+        let doc: FigmaDocument = serde_json::from_value(serde_json::json!({
+            "id": "0:0",
+            "name": "Document",
+            "type": "DOCUMENT",
+            "children": [{
+                "id": "0:1",
+                "name": "Flows",
+                "type": "CANVAS",
+                "children": [
+                    {
+                        "id": "1:1",
+                        "name": "Cart",
+                        "type": "FRAME",
+                        "children": [
+                            {
+                                "id": "1:10",
+                                "name": "Checkout",
+                                "type": "INSTANCE",
+                                "interactions": [{
+                                    "trigger": {"type": "ON_CLICK"},
+                                    "actions": [
+                                        null,
+                                        {"type": "NODE", "destinationId": "1:2", "navigation": "NAVIGATE"},
+                                        {"type": "NODE", "destinationId": "1:4", "navigation": "OVERLAY"}
+                                    ]
+                                }]
+                            },
+                            {"id": "1:11", "name": "Help", "type": "TEXT", "transitionNodeID": "1:5"}
+                        ]
+                    },
+                    {
+                        "id": "1:2",
+                        "name": "Payment",
+                        "type": "FRAME",
+                        "interactions": [{"actions": [
+                            {"type": "NODE", "destinationId": "1:3", "navigation": "NAVIGATE"},
+                            {"type": "BACK"}
+                        ]}]
+                    },
+                    {
+                        "id": "1:3",
+                        "name": "Done",
+                        "type": "FRAME",
+                        "transitionNodeID": "1:1"
+                    },
+                    {"id": "1:4", "name": "Coupon", "type": "FRAME"},
+                    {"id": "1:5", "name": "Support", "type": "FRAME"},
+                    {"id": "1:6", "name": "Unlinked", "type": "FRAME"}
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let names: Vec<String> = prototype_flow(&doc, "1:1")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, ["Cart", "Payment", "Support", "Done"]);
+        assert!(matches!(
+            prototype_flow(&doc, "1:10"),
+            Err(FigmaError::NodeNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn node_from_json_accepts_nodes_file_and_bare_shapes() {
+        let nodes =
+            r#"{"nodes": {"4:2": {"document": {"id": "4:2", "name": "Card", "type": "FRAME"}}}}"#;
+        let node = node_from_json(nodes, FigmaNodeSelector::default()).unwrap();
+        assert_eq!(node.id, "4:2");
+
+        let bare = r#"{"id": "9:9", "name": "Solo", "type": "FRAME"}"#;
+        assert_eq!(
+            node_from_json(bare, FigmaNodeSelector::default())
+                .unwrap()
+                .id,
+            "9:9"
+        );
+
+        let file = serde_json::json!({
+            "name": "Flows",
+            "lastModified": "2024-01-01T00:00:00Z",
+            "version": "1",
+            "document": frame_document_json()
+        })
+        .to_string();
+        let selector = FigmaNodeSelector {
+            frame_name: Some("checkout / mobile"),
+            ..Default::default()
+        };
+        assert_eq!(node_from_json(&file, selector).unwrap().id, "1:2");
+        let selector = FigmaNodeSelector {
+            node_id: Some("1:5"),
+            ..Default::default()
+        };
+        assert_eq!(node_from_json(&file, selector).unwrap().name, "Welcome");
+        assert_eq!(file_version_from_json(&file).as_deref(), Some("1"));
+        assert_eq!(file_version_from_json(nodes), None);
+    }
+
+    #[test]
+    fn build_figma_snapshot_prunes_layers_missing_from_the_export() {
+        let frame = r#"{"id": "1:1", "name": "Card", "type": "FRAME", "clipsContent": true,
+            "absoluteBoundingBox": {"x": 0, "y": 0, "width": 100, "height": 100},
+            "children": [
+                {"id": "1:2", "name": "Title", "type": "TEXT", "characters": "Hi",
+                    "absoluteBoundingBox": {"x": 10, "y": 10, "width": 50, "height": 20}},
+                {"id": "1:3", "name": "Tooltip", "type": "FRAME", "visible": false,
+                    "absoluteBoundingBox": {"x": 10, "y": 40, "width": 50, "height": 20},
+                    "children": [{"id": "1:4", "name": "Tip", "type": "TEXT", "characters": "Tip",
+                        "absoluteBoundingBox": {"x": 12, "y": 42, "width": 30, "height": 10}}]},
+                {"id": "1:5", "name": "Ghost", "type": "RECTANGLE", "opacity": 0,
+                    "absoluteBoundingBox": {"x": 10, "y": 70, "width": 50, "height": 20}},
+                {"id": "1:6", "name": "Offscreen", "type": "TEXT", "characters": "Later",
+                    "absoluteBoundingBox": {"x": 120, "y": 10, "width": 50, "height": 20}},
+                {"id": "1:7", "name": "Edge", "type": "RECTANGLE",
+                    "absoluteBoundingBox": {"x": 90, "y": 90, "width": 50, "height": 50}}
+            ]}"#;
+        let root = node_from_json(frame, FigmaNodeSelector::default()).unwrap();
+
+        let pruned = build_figma_snapshot("key", "1:1", &root, false);
+        let mut ids: Vec<&str> = pruned.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["1:1", "1:2", "1:7"]);
+        let card = pruned.nodes.iter().find(|n| n.id == "1:1").unwrap();
+        assert_eq!(card.children, vec!["1:2", "1:7"]);
+
+        let everything = build_figma_snapshot("key", "1:1", &root, true);
+        assert_eq!(everything.nodes.len(), 7);
+    }
+
+    #[test]
+    fn node_from_json_requires_selector_for_multiple_frames() {
+        let nodes = r#"{"nodes": {
+            "1:1": {"document": {"id": "1:1", "name": "A", "type": "FRAME"}},
+            "1:2": {"document": {"id": "1:2", "name": "B", "type": "FRAME"}}
+        }}"#;
+        let err = node_from_json(nodes, FigmaNodeSelector::default()).unwrap_err();
+        assert!(err.to_string().contains("2 frames"), "{err}");
+    }
+
+    #[test]
+    fn figma_json_to_normalized_view_maps_nodes_onto_exported_png() {
+        let dir = TempDir::new().unwrap();
+        let json_path = dir.path().join("card.json");
+        let image_path = dir.path().join("card.png");
+        std::fs::write(
+            &json_path,
+            r#"{"id": "1:1", "name": "Card", "type": "FRAME",
+                "absoluteBoundingBox": {"x": 100, "y": 100, "width": 50, "height": 25},
+                "children": [{"id": "1:2", "name": "Label", "type": "TEXT", "characters": "Hi",
+                    "absoluteBoundingBox": {"x": 110, "y": 105, "width": 20, "height": 10}}]}"#,
+        )
+        .unwrap();
+        // Exported at 2x.
+        DynamicImage::ImageRgba8(RgbaImage::new(100, 50))
+            .save(&image_path)
+            .unwrap();
+
+        let view = figma_json_to_normalized_view(&FigmaOfflineOptions {
+            json_path,
+            image_path,
+            file_key: "card".to_string(),
+            output_path: dir.path().join("out.png"),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!((view.width, view.height), (100, 50));
+        let tree = view.figma_tree.unwrap();
+        assert_eq!(tree.node_id, "1:1");
+        let label = tree.nodes.iter().find(|n| n.id == "1:2").unwrap();
+        assert!((label.bounding_box.x - 20.0).abs() < 1e-3);
+        assert!((label.bounding_box.width - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn figma_json_with_drop_shadow_maps_from_render_bounds() {
+        let dir = TempDir::new().unwrap();
+        let json_path = dir.path().join("card.json");
+        let image_path = dir.path().join("card.png");
+        // A 10px shadow on every side makes the export 70x45 instead of 50x25.
+        std::fs::write(
+            &json_path,
+            r#"{"id": "1:1", "name": "Card", "type": "FRAME",
+                "absoluteBoundingBox": {"x": 100, "y": 100, "width": 50, "height": 25},
+                "absoluteRenderBounds": {"x": 90, "y": 90, "width": 70, "height": 45},
+                "children": [{"id": "1:2", "name": "Label", "type": "TEXT", "characters": "Hi",
+                    "absoluteBoundingBox": {"x": 110, "y": 105, "width": 20, "height": 10}}]}"#,
+        )
+        .unwrap();
+        DynamicImage::ImageRgba8(RgbaImage::new(140, 90))
+            .save(&image_path)
+            .unwrap();
+
+        let view = figma_json_to_normalized_view(&FigmaOfflineOptions {
+            json_path,
+            image_path,
+            file_key: "card".to_string(),
+            output_path: dir.path().join("out.png"),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let tree = view.figma_tree.unwrap();
+        let label = tree.nodes.iter().find(|n| n.id == "1:2").unwrap();
+        assert!((label.bounding_box.x - 40.0).abs() < 1e-3);
+        assert!((label.bounding_box.y - 30.0).abs() < 1e-3);
+        assert!((label.bounding_box.width - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn figma_json_auto_layout_is_mapped_and_scaled() {
+        let dir = TempDir::new().unwrap();
+        let json_path = dir.path().join("row.json");
+        let image_path = dir.path().join("row.png");
+        std::fs::write(
+            &json_path,
+            r#"{"id": "1:1", "name": "Row", "type": "FRAME",
+                "absoluteBoundingBox": {"x": 0, "y": 0, "width": 100, "height": 50},
+                "layoutMode": "HORIZONTAL", "itemSpacing": 8,
+                "paddingTop": 12, "paddingBottom": 12, "paddingLeft": 16, "paddingRight": 16,
+                "primaryAxisAlignItems": "SPACE_BETWEEN", "counterAxisAlignItems": "CENTER",
+                "children": [{"id": "1:2", "name": "Plain", "type": "FRAME",
+                    "layoutMode": "NONE",
+                    "absoluteBoundingBox": {"x": 16, "y": 12, "width": 20, "height": 20}}]}"#,
+        )
+        .unwrap();
+        // Exported at 2x.
+        DynamicImage::ImageRgba8(RgbaImage::new(200, 100))
+            .save(&image_path)
+            .unwrap();
+
+        let view = figma_json_to_normalized_view(&FigmaOfflineOptions {
+            json_path,
+            image_path,
+            file_key: "row".to_string(),
+            output_path: dir.path().join("out.png"),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let tree = view.figma_tree.unwrap();
+        let plain = tree.nodes.iter().find(|n| n.id == "1:2").unwrap();
+        assert!(plain.auto_layout.is_none());
+        let row = tree.nodes.iter().find(|n| n.id == "1:1").unwrap();
+        let layout = row.auto_layout.expect("row uses auto layout");
+        assert_eq!(layout.direction, LayoutDirection::Horizontal);
+        assert_eq!(layout.primary_align, AutoLayoutAlign::SpaceBetween);
+        assert_eq!(layout.counter_align, AutoLayoutAlign::Center);
+        assert!((layout.gap - 16.0).abs() < 1e-3);
+        assert!((layout.padding.top - 24.0).abs() < 1e-3);
+        assert!((layout.padding.left - 32.0).abs() < 1e-3);
+        assert_eq!(
+            layout.tailwind_classes(),
+            "flex justify-between items-center gap-4 py-6 px-8"
+        );
+        assert_eq!(
+            layout.scaled(0.5, 0.5).tailwind_classes(),
+            "flex justify-between items-center gap-2 py-3 px-4"
+        );
+    }
+
+    #[test]
+    fn figma_constraints_predict_boxes_at_a_different_viewport() {
+        let dir = TempDir::new().unwrap();
+        let json_path = dir.path().join("page.json");
+        let image_path = dir.path().join("page.png");
+        std::fs::write(
+            &json_path,
+            r#"{"id": "1:1", "name": "Page", "type": "FRAME",
+                "absoluteBoundingBox": {"x": 100, "y": 100, "width": 400, "height": 200},
+                "children": [
+                  {"id": "1:2", "name": "Header", "type": "FRAME",
+                   "constraints": {"vertical": "TOP", "horizontal": "LEFT_RIGHT"},
+                   "absoluteBoundingBox": {"x": 100, "y": 100, "width": 400, "height": 40}},
+                  {"id": "1:3", "name": "Submit", "type": "RECTANGLE",
+                   "constraints": {"vertical": "BOTTOM", "horizontal": "RIGHT"},
+                   "absoluteBoundingBox": {"x": 440, "y": 260, "width": 40, "height": 20}},
+                  {"id": "1:4", "name": "Logo", "type": "RECTANGLE",
+                   "constraints": {"vertical": "CENTER", "horizontal": "CENTER"},
+                   "absoluteBoundingBox": {"x": 280, "y": 190, "width": 40, "height": 20}}]}"#,
+        )
+        .unwrap();
+        DynamicImage::ImageRgba8(RgbaImage::new(400, 200))
+            .save(&image_path)
+            .unwrap();
+
+        let view = figma_json_to_normalized_view(&FigmaOfflineOptions {
+            json_path,
+            image_path,
+            file_key: "page".to_string(),
+            output_path: dir.path().join("out.png"),
+            viewport: Some(Viewport::new(800, 400)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let tree = view.figma_tree.unwrap();
+        let node = |id: &str| tree.nodes.iter().find(|n| n.id == id).unwrap();
+        let header = node("1:2").responsive_box.unwrap();
+        assert_eq!((header.x, header.width, header.height), (0.0, 800.0, 40.0));
+        let submit = node("1:3");
+        let predicted = submit.responsive_box.unwrap();
+        assert_eq!((predicted.x, predicted.y), (740.0, 360.0));
+        assert_eq!((predicted.width, predicted.height), (40.0, 20.0));
+        // The screenshot box still follows the letterboxed export.
+        assert!((submit.bounding_box.x - 680.0).abs() < 1e-3);
+        let logo = node("1:4").layout_box();
+        assert_eq!((logo.x, logo.y), (380.0, 190.0));
+    }
+
+    #[test]
+    fn figma_constraints_are_ignored_at_the_native_size() {
+        let mut snapshot = FigmaSnapshot {
+            schema_version: FigmaSnapshot::SCHEMA_VERSION,
+            file_key: "f".to_string(),
+            node_id: "1:1".to_string(),
+            name: None,
+            version: None,
+            frame_size: None,
+            export_scale: None,
+            nodes: vec![],
+            styles: Vec::new(),
+        };
+        let json = r#"{"id": "1:1", "name": "Page", "type": "FRAME",
+            "absoluteBoundingBox": {"x": 0, "y": 0, "width": 400, "height": 200},
+            "children": [{"id": "1:2", "name": "Submit", "type": "RECTANGLE",
+                "constraints": {"vertical": "BOTTOM", "horizontal": "RIGHT"},
+                "absoluteBoundingBox": {"x": 340, "y": 160, "width": 40, "height": 20}}]}"#;
+        let root: FigmaNodeData = serde_json::from_str(json).unwrap();
+        collect_figma_nodes(&root, &mut snapshot.nodes);
+        let frame = BoundingBox {
+            x: 0.0,
+            y: 0.0,
+            width: 400.0,
+            height: 200.0,
+        };
+
+        apply_responsive_constraints(&mut snapshot, frame, (400, 200));
+        assert!(snapshot.nodes.iter().all(|n| n.responsive_box.is_none()));
+
+        apply_responsive_constraints(&mut snapshot, frame, (300, 200));
+        let submit = snapshot.nodes.iter().find(|n| n.id == "1:2").unwrap();
+        assert_eq!(submit.responsive_box.unwrap().x, 240.0);
+    }
+
+    #[test]
+    fn export_bounds_follow_the_image_geometry() {
+        let layout = BoundingBox {
+            x: 100.0,
+            y: 100.0,
+            width: 50.0,
+            height: 25.0,
+        };
+        let render = BoundingBox {
+            x: 90.0,
+            y: 90.0,
+            width: 70.0,
+            height: 45.0,
+        };
+        let chosen = |dims| export_bounds(Some(layout), Some(render), dims);
+
+        assert_eq!(chosen((140, 90)).unwrap().width, 70.0);
+        // Exported with `use_absolute_bounds`: the image matches the layout box.
+        assert_eq!(chosen((100, 50)).unwrap().width, 50.0);
+        // Equal aspect ratios keep the render bounds.
+        let padded = BoundingBox {
+            x: 90.0,
+            y: 95.0,
+            width: 70.0,
+            height: 35.0,
+        };
+        assert_eq!(
+            export_bounds(Some(layout), Some(padded), (100, 50))
+                .unwrap()
+                .x,
+            90.0
+        );
+        assert_eq!(export_bounds(Some(layout), None, (1, 1)).unwrap().x, 100.0);
+    }
 }
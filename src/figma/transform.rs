@@ -14,7 +14,10 @@ pub struct FigmaRenderOptions {
     pub node_id: String,
     pub output_path: PathBuf,
     pub viewport: Option<Viewport>,
-    pub scale: f32,
+    /// Figma export scale; `None` picks [`auto_export_scale`] for the viewport.
+    pub scale: Option<f32>,
+    /// Keep hidden, fully transparent and clipped-away layers in the tree.
+    pub include_hidden: bool,
 }
 
 impl Default for FigmaRenderOptions {
@@ -24,11 +27,77 @@ impl Default for FigmaRenderOptions {
             node_id: String::new(),
             output_path: PathBuf::new(),
             viewport: None,
-            scale: 1.0,
+            scale: None,
+            include_hidden: false,
         }
     }
 }
 
+/// Smallest and largest export scale the Figma images endpoint accepts.
+pub const MIN_EXPORT_SCALE: f32 = 0.01;
+pub const MAX_EXPORT_SCALE: f32 = 4.0;
+
+/// Export scale at which a `frame_width` x `frame_height` frame fills the
+/// viewport bitmap without further resampling.
+///
+/// Uses the same fit as the letterbox, so the export only needs padding, not
+/// a Lanczos resize. Falls back to 1.0 without a viewport or frame size.
+pub fn auto_export_scale(frame_width: f32, frame_height: f32, viewport: Option<Viewport>) -> f32 {
+    let Some(viewport) = viewport else {
+        return 1.0;
+    };
+    if frame_width <= 0.0 || frame_height <= 0.0 {
+        return 1.0;
+    }
+    let scale = (viewport.width as f32 / frame_width).min(viewport.height as f32 / frame_height);
+    scale.clamp(MIN_EXPORT_SCALE, MAX_EXPORT_SCALE)
+}
+
+/// The box of the exported node that its PNG covers, used as the origin and
+/// scale reference when mapping node boxes onto the image.
+///
+/// Figma exports a node at its render bounds, which extend past the layout
+/// box for drop shadows, blurs and outside strokes. Exports made with
+/// `use_absolute_bounds` cover the layout box instead, so the box whose aspect
+/// ratio matches the image is chosen, preferring render bounds on a tie.
+pub fn export_bounds(
+    layout: Option<BoundingBox>,
+    render: Option<BoundingBox>,
+    image_dimensions: (u32, u32),
+) -> Option<BoundingBox> {
+    let usable = |bb: &BoundingBox| bb.width > 0.0 && bb.height > 0.0;
+    let (layout, render) = match (layout.filter(usable), render.filter(usable)) {
+        (Some(layout), Some(render)) => (layout, render),
+        (layout, render) => return render.or(layout),
+    };
+    let (image_w, image_h) = image_dimensions;
+    if image_w == 0 || image_h == 0 {
+        return Some(render);
+    }
+    let image_aspect = image_w as f32 / image_h as f32;
+    let mismatch = |bb: &BoundingBox| ((bb.width / bb.height) / image_aspect).ln().abs();
+    // Allow for the export rounding each side to whole pixels.
+    if mismatch(&layout) + 1e-3 < mismatch(&render) {
+        Some(layout)
+    } else {
+        Some(render)
+    }
+}
+
+/// Options for building a normalized view from a saved Figma response and exported PNG.
+#[derive(Debug, Clone, Default)]
+pub struct FigmaOfflineOptions {
+    pub json_path: PathBuf,
+    pub image_path: PathBuf,
+    pub file_key: String,
+    pub node_id: Option<String>,
+    pub frame_name: Option<String>,
+    pub output_path: PathBuf,
+    pub viewport: Option<Viewport>,
+    /// Keep hidden, fully transparent and clipped-away layers in the tree.
+    pub include_hidden: bool,
+}
+
 /// Transform parameters for letterbox scaling.
 #[derive(Debug, Clone, Copy)]
 pub struct LetterboxTransform {
@@ -130,6 +199,13 @@ pub fn normalize_figma_snapshot(
         let final_w = scaled_w * letterbox.scale;
         let final_h = scaled_h * letterbox.scale;
 
+        // Constraint-placed layers keep design units, like their responsive box.
+        let auto_layout = match node.responsive_box {
+            Some(_) => node.auto_layout,
+            None => node
+                .auto_layout
+                .map(|layout| layout.scaled(scale_x * letterbox.scale, scale_y * letterbox.scale)),
+        };
         nodes.push(FigmaNode {
             bounding_box: BoundingBox {
                 x: final_x,
@@ -137,6 +213,7 @@ pub fn normalize_figma_snapshot(
                 width: final_w,
                 height: final_h,
             },
+            auto_layout,
             ..node
         });
     }
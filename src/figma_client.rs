@@ -1,21 +1,31 @@
+use crate::config::FigmaConfig;
 use crate::error::{DpcError, Result};
+use crate::figma::client::{map_figma_error, read_body_limited};
+use crate::figma_oauth::OAuthSession;
+use crate::image_loader::ImageLimits;
 #[cfg(test)]
 use reqwest::header::HeaderMap;
 use reqwest::{header::RETRY_AFTER, Client, RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
 const DEFAULT_BASE_URL: &str = "https://api.figma.com";
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// OAuth scope (and personal access token permission) needed to post comments.
+pub const COMMENTS_WRITE_SCOPE: &str = "file_comments:write";
 
 #[derive(Debug, Clone)]
 pub enum FigmaAuth {
     PersonalAccessToken(String),
     OAuthToken(String),
+    /// Tokens from `dpc figma login`, refreshed automatically when they expire.
+    OAuthSession(OAuthSession),
 }
 
 impl FigmaAuth {
@@ -35,14 +45,36 @@ impl FigmaAuth {
         None
     }
 
-    fn apply(&self, builder: RequestBuilder) -> RequestBuilder {
-        self.apply_headers(builder)
+    /// Environment tokens first, then the session stored by `dpc figma login`.
+    pub async fn resolve() -> Result<Option<Self>> {
+        if let Some(auth) = Self::from_env() {
+            return Ok(Some(auth));
+        }
+        Ok(OAuthSession::load().await?.map(Self::OAuthSession))
+    }
+
+    /// Refresh an expired session token; a no-op for static tokens.
+    pub async fn ensure_fresh(&self) -> Result<()> {
+        if let FigmaAuth::OAuthSession(session) = self {
+            session.access_token().await?;
+        }
+        Ok(())
+    }
+
+    async fn apply(&self, builder: RequestBuilder) -> Result<RequestBuilder> {
+        match self {
+            FigmaAuth::OAuthSession(session) => {
+                Ok(builder.bearer_auth(session.access_token().await?))
+            }
+            _ => Ok(self.apply_headers(builder)),
+        }
     }
 
     fn apply_headers(&self, builder: RequestBuilder) -> RequestBuilder {
         match self {
             FigmaAuth::PersonalAccessToken(token) => builder.header("X-FIGMA-TOKEN", token),
             FigmaAuth::OAuthToken(token) => builder.bearer_auth(token),
+            FigmaAuth::OAuthSession(session) => builder.bearer_auth(session.current_access_token()),
         }
     }
 
@@ -58,6 +90,14 @@ impl FigmaAuth {
                     format!("Bearer {token}").parse().unwrap(),
                 );
             }
+            FigmaAuth::OAuthSession(session) => {
+                headers.insert(
+                    reqwest::header::AUTHORIZATION,
+                    format!("Bearer {}", session.current_access_token())
+                        .parse()
+                        .unwrap(),
+                );
+            }
         }
     }
 }
@@ -65,7 +105,10 @@ impl FigmaAuth {
 #[derive(Debug, Clone)]
 pub struct FigmaClient {
     http: Client,
-    auth: FigmaAuth,
+    /// Tokens used in turn; a 429 moves on to the next one.
+    tokens: Vec<FigmaAuth>,
+    /// Index of the token requests start with, shared between clones.
+    current: Arc<AtomicUsize>,
     base_url: Url,
 }
 
@@ -86,24 +129,65 @@ impl FigmaClient {
         base_url: impl AsRef<str>,
         timeout: Duration,
     ) -> Result<Self> {
-        let base_url = Url::parse(base_url.as_ref())?;
+        Self::with_tokens(vec![auth], base_url, timeout)
+    }
+
+    /// A client that rotates through `tokens`: when Figma answers 429, the
+    /// request is retried with the next token, and later requests start there.
+    pub fn with_tokens(
+        tokens: Vec<FigmaAuth>,
+        base_url: impl AsRef<str>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        if tokens.is_empty() {
+            return Err(DpcError::Config(
+                "at least one Figma token is required".to_string(),
+            ));
+        }
+        let mut base_url = Url::parse(base_url.as_ref())?;
+        // Keep a gateway's path prefix when joining endpoint paths onto it.
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
 
-        let http = Client::builder()
+        let http = crate::network::client_builder()
             .timeout(timeout)
             .build()
             .map_err(DpcError::Network)?;
 
         Ok(Self {
             http,
-            auth,
+            tokens,
+            current: Arc::new(AtomicUsize::new(0)),
             base_url,
         })
     }
 
+    /// A client for the `[figma]` config section: the tokens named by
+    /// `token_env` (falling back to [`FigmaAuth::resolve`]) against `api_url`.
+    /// `None` when no token is available.
+    pub async fn from_config(config: &FigmaConfig) -> Result<Option<Self>> {
+        let mut tokens: Vec<FigmaAuth> = config
+            .token_env
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .filter(|token| !token.is_empty())
+            .map(FigmaAuth::PersonalAccessToken)
+            .collect();
+        if tokens.is_empty() {
+            tokens.extend(FigmaAuth::resolve().await?);
+        }
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let base_url = config.api_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        Self::with_tokens(tokens, base_url, DEFAULT_TIMEOUT).map(Some)
+    }
+
     pub async fn fetch_file(&self, file_key: &str) -> Result<FigmaFileResponse> {
         let url = self.endpoint(&format!("/v1/files/{file_key}"))?;
-        let req = self.authed(self.http.get(url));
-        self.send_json(req).await
+        self.send_json(self.http.get(url)).await
     }
 
     pub async fn fetch_nodes(
@@ -119,8 +203,21 @@ impl FigmaClient {
 
         let ids = node_ids.join(",");
         let url = self.endpoint(&format!("/v1/files/{file_key}/nodes?ids={ids}"))?;
-        let req = self.authed(self.http.get(url));
-        self.send_json(req).await
+        self.send_json(self.http.get(url)).await
+    }
+
+    /// The styles published from `file_key`. Their values are on the nodes
+    /// holding them, see [`fetch_nodes`](Self::fetch_nodes).
+    pub async fn fetch_styles(&self, file_key: &str) -> Result<FigmaStylesResponse> {
+        let url = self.endpoint(&format!("/v1/files/{file_key}/styles"))?;
+        self.send_json(self.http.get(url)).await
+    }
+
+    /// The local variables and variable collections of `file_key` (an
+    /// Enterprise plan endpoint).
+    pub async fn fetch_local_variables(&self, file_key: &str) -> Result<FigmaVariablesResponse> {
+        let url = self.endpoint(&format!("/v1/files/{file_key}/variables/local"))?;
+        self.send_json(self.http.get(url)).await
     }
 
     pub async fn export_images(
@@ -139,8 +236,7 @@ impl FigmaClient {
             format = options.format.as_str(),
         ))?;
 
-        let req = self.authed(self.http.get(url));
-        self.send_json(req).await
+        self.send_json(self.http.get(url)).await
     }
 
     pub async fn export_image(
@@ -167,11 +263,9 @@ impl FigmaClient {
         let status = response.status();
 
         if status.is_success() {
-            return response
-                .bytes()
+            return read_body_limited(response, ImageLimits::current().max_bytes)
                 .await
-                .map(|b| b.to_vec())
-                .map_err(DpcError::Network);
+                .map_err(map_figma_error);
         }
 
         let body = response.text().await.unwrap_or_default();
@@ -185,16 +279,75 @@ impl FigmaClient {
         ))
     }
 
-    fn authed(&self, builder: RequestBuilder) -> RequestBuilder {
-        self.auth.apply(builder)
+    pub async fn fetch_comments(&self, file_key: &str) -> Result<FigmaCommentsResponse> {
+        let url = self.endpoint(&format!("/v1/files/{file_key}/comments"))?;
+        self.send_json(self.http.get(url)).await
+    }
+
+    /// Post `message` as a comment pinned to `node_id`. Needs a token with
+    /// the [`COMMENTS_WRITE_SCOPE`] scope; a read-only token fails with a hint.
+    pub async fn post_comment(
+        &self,
+        file_key: &str,
+        node_id: &str,
+        message: &str,
+    ) -> Result<FigmaComment> {
+        let url = self.endpoint(&format!("/v1/files/{file_key}/comments"))?;
+        let body = json!({
+            "message": message,
+            "client_meta": { "node_id": node_id, "node_offset": { "x": 0, "y": 0 } },
+        });
+        self.send_json(self.http.post(url).json(&body))
+            .await
+            .map_err(|err| match err {
+            DpcError::FigmaApi {
+                status: Some(status @ (StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED)),
+                message,
+            } => DpcError::figma_api(
+                Some(status),
+                format!(
+                    "{message}; posting comments needs the {COMMENTS_WRITE_SCOPE} scope (run `dpc figma login --comments`, or use a personal access token with comment write access)"
+                ),
+            ),
+            other => other,
+        })
     }
 
     fn endpoint(&self, path: &str) -> Result<Url> {
-        self.base_url.join(path).map_err(DpcError::InvalidUrl)
+        self.base_url
+            .join(path.trim_start_matches('/'))
+            .map_err(DpcError::InvalidUrl)
     }
 
-    async fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
-        let response = request.send().await.map_err(DpcError::Network)?;
+    async fn send_json<T: DeserializeOwned>(&self, mut request: RequestBuilder) -> Result<T> {
+        let mut untried = self.tokens.len();
+        let response = loop {
+            untried -= 1;
+            let index = self.current.load(Ordering::Relaxed) % self.tokens.len();
+            let retry = if untried > 0 {
+                request.try_clone()
+            } else {
+                None
+            };
+            let response = self.tokens[index]
+                .apply(request)
+                .await?
+                .send()
+                .await
+                .map_err(DpcError::Network)?;
+            match retry {
+                Some(next) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    let _ = self.current.compare_exchange(
+                        index,
+                        (index + 1) % self.tokens.len(),
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    );
+                    request = next;
+                }
+                _ => break response,
+            }
+        };
         let status = response.status();
         let retry_after = response
             .headers()
@@ -208,10 +361,14 @@ impl FigmaClient {
             return serde_json::from_str(&body).map_err(DpcError::Serialization);
         }
 
-        Err(DpcError::figma_api(
-            Some(status),
-            error_message(status, &body, retry_after.as_deref()),
-        ))
+        let mut message = error_message(status, &body, retry_after.as_deref());
+        if status == StatusCode::TOO_MANY_REQUESTS && self.tokens.len() > 1 {
+            message.push_str(&format!(
+                "; all {} configured tokens are rate limited",
+                self.tokens.len()
+            ));
+        }
+        Err(DpcError::figma_api(Some(status), message))
     }
 }
 
@@ -263,6 +420,81 @@ pub struct FigmaNodeContainer {
     pub name: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FigmaStylesResponse {
+    #[serde(default)]
+    pub meta: FigmaStylesMeta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FigmaStylesMeta {
+    #[serde(default)]
+    pub styles: Vec<FigmaPublishedStyle>,
+}
+
+/// A style as listed by the styles endpoint, without its value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FigmaPublishedStyle {
+    pub key: String,
+    pub node_id: String,
+    /// `FILL`, `TEXT`, `EFFECT` or `GRID`
+    pub style_type: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FigmaVariablesResponse {
+    #[serde(default)]
+    pub meta: FigmaVariablesMeta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaVariablesMeta {
+    /// Variables by ID.
+    #[serde(default)]
+    pub variables: HashMap<String, FigmaVariable>,
+    /// Collections by ID.
+    #[serde(default)]
+    pub variable_collections: HashMap<String, FigmaVariableCollection>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaVariable {
+    pub id: String,
+    pub name: String,
+    pub variable_collection_id: String,
+    /// `COLOR`, `FLOAT`, `STRING` or `BOOLEAN`
+    pub resolved_type: String,
+    /// Value per mode ID: a color `{r, g, b, a}`, number, string, boolean or
+    /// `{"type": "VARIABLE_ALIAS", "id": ...}`.
+    #[serde(default)]
+    pub values_by_mode: HashMap<String, Value>,
+    /// Code names by platform, e.g. `{"WEB": "var(--color-primary)"}`.
+    #[serde(default)]
+    pub code_syntax: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaVariableCollection {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub modes: Vec<FigmaVariableMode>,
+    pub default_mode_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaVariableMode {
+    pub mode_id: String,
+    pub name: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct FigmaImageResponse {
     #[serde(default)]
@@ -271,6 +503,19 @@ pub struct FigmaImageResponse {
     pub err: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FigmaCommentsResponse {
+    #[serde(default)]
+    pub comments: Vec<FigmaComment>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FigmaComment {
+    pub id: String,
+    #[serde(default)]
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ImageExportOptions {
     pub scale: f32,
@@ -373,6 +618,66 @@ mod tests {
         }
     }
 
+    /// Serve Figma responses on a local port: 200 for requests carrying the
+    /// `good` token, 429 otherwise. Returns the base URL and the request lines.
+    fn serve_rate_limited(good: &'static str) -> (String, std::sync::mpsc::Receiver<String>) {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 2048];
+                let len = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..len]).to_lowercase();
+                let _ = tx.send(request.lines().next().unwrap_or_default().to_string());
+                let (status, body) = if request.contains(&format!("x-figma-token: {good}")) {
+                    ("200 OK", r#"{"comments":[]}"#)
+                } else {
+                    ("429 Too Many Requests", r#"{"err":"Rate limit exceeded"}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nRetry-After: 30\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{addr}/figma-gateway"), rx)
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_rotate_tokens_behind_a_gateway() {
+        let (base_url, requests) = serve_rate_limited("second");
+        let tokens = vec![
+            FigmaAuth::PersonalAccessToken("first".into()),
+            FigmaAuth::PersonalAccessToken("second".into()),
+        ];
+        let client = FigmaClient::with_tokens(tokens, &base_url, DEFAULT_TIMEOUT).unwrap();
+
+        client.fetch_comments("abc").await.expect("second token");
+        client
+            .fetch_comments("abc")
+            .await
+            .expect("starts with second");
+        let lines: Vec<String> = requests.try_iter().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with("get /figma-gateway/v1/files/abc/comments ")));
+
+        let (base_url, _) = serve_rate_limited("none");
+        let tokens = vec![
+            FigmaAuth::PersonalAccessToken("first".into()),
+            FigmaAuth::PersonalAccessToken("second".into()),
+        ];
+        let client = FigmaClient::with_tokens(tokens, &base_url, DEFAULT_TIMEOUT).unwrap();
+        let err = client.fetch_comments("abc").await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("retry after 30s); all 2 configured tokens are rate limited"));
+    }
+
     struct EnvGuard;
 
     impl EnvGuard {
@@ -0,0 +1,280 @@
+//! Major findings posted back to the Figma file as comments
+//! (`--post-figma-comments`).
+//!
+//! Designers rarely read CI output, so a typography or gradient finding tied
+//! to a Figma node is also left as a comment pinned to that node ("the
+//! implementation uses 14px Roboto here"). Every comment ends with the
+//! finding's fingerprint; findings whose fingerprint already appears in a
+//! comment on the file are not posted again, so reruns on the same branch do
+//! not pile up duplicates.
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::figma_client::FigmaClient;
+use crate::metrics::{SeverityCalibration, TypographyReportRow};
+use crate::output::PostedFigmaComment;
+use crate::types::{ColorDiffKind, DiffSeverity, MetricScores, TypographyIssue};
+
+/// A comment to post on a Figma node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FigmaCommentDraft {
+    pub node_id: String,
+    pub message: String,
+    pub fingerprint: String,
+}
+
+/// Comments for the major typography and gradient findings of `scores`
+/// whose reference element is a Figma node. `rows` (the typography report)
+/// supplies the implementation's font for each matched text.
+pub fn figma_comment_drafts(
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+    rows: &[TypographyReportRow],
+) -> Vec<FigmaCommentDraft> {
+    let rows: HashMap<(&str, &str), &TypographyReportRow> = rows
+        .iter()
+        .map(|row| ((row.ref_id.as_str(), row.impl_id.as_str()), row))
+        .collect();
+    let mut drafts = Vec::new();
+
+    for diff in scores.typography.iter().flat_map(|m| &m.diffs) {
+        let (Some(node_id), Some(fingerprint)) = (&diff.element_id_ref, &diff.fingerprint) else {
+            continue;
+        };
+        if calibration.typography(&diff.issues) != DiffSeverity::Major {
+            continue;
+        }
+        let row = diff
+            .element_id_impl
+            .as_deref()
+            .and_then(|impl_id| rows.get(&(node_id.as_str(), impl_id)));
+        let finding = match row {
+            Some(row) => describe_typography(row, &diff.issues),
+            None => "This text is missing from the implementation.".to_string(),
+        };
+        drafts.push(draft(node_id, &finding, fingerprint));
+    }
+
+    for diff in scores.color.iter().flat_map(|m| &m.diffs) {
+        let (Some(node_id), Some(fingerprint)) = (&diff.element_id, &diff.fingerprint) else {
+            continue;
+        };
+        if diff.kind != ColorDiffKind::GradientShift
+            || calibration.color(diff) != DiffSeverity::Major
+        {
+            continue;
+        }
+        let mut finding = format!(
+            "The implementation fills this with {} (design: {})",
+            diff.impl_color, diff.ref_color
+        );
+        if let Some(delta) = diff.angle_delta.filter(|delta| *delta >= 1.0) {
+            finding.push_str(&format!(", with the gradient turned by {delta:.0}\u{b0}"));
+        }
+        finding.push('.');
+        drafts.push(draft(node_id, &finding, fingerprint));
+    }
+
+    drafts
+}
+
+fn draft(node_id: &str, finding: &str, fingerprint: &str) -> FigmaCommentDraft {
+    FigmaCommentDraft {
+        node_id: node_id.to_string(),
+        message: format!("{finding}\n\ndpc finding {fingerprint}"),
+        fingerprint: fingerprint.to_string(),
+    }
+}
+
+fn describe_typography(row: &TypographyReportRow, issues: &[TypographyIssue]) -> String {
+    if issues == [TypographyIssue::LineWrapDiff] {
+        if let (Some(impl_lines), Some(ref_lines)) = (row.impl_lines, row.ref_lines) {
+            return format!(
+                "The implementation wraps this text onto {impl_lines} line(s) (design: {ref_lines})."
+            );
+        }
+    }
+    let positional = [
+        TypographyIssue::BaselineShift,
+        TypographyIssue::VerticalRhythmDiff,
+    ];
+    if !issues.is_empty() && issues.iter().all(|issue| positional.contains(issue)) {
+        return if issues.contains(&TypographyIssue::BaselineShift) {
+            "The implementation sets this text higher or lower in its container than the design."
+                .to_string()
+        } else {
+            "The implementation spaces this text differently from the text above it (baseline to baseline)."
+                .to_string()
+        };
+    }
+    let with_weight = issues.contains(&TypographyIssue::FontWeightDiff);
+    let implementation = font_label(
+        row.impl_font_size,
+        row.impl_font_family.as_deref(),
+        row.impl_font_weight.as_deref().filter(|_| with_weight),
+    );
+    let design = font_label(
+        row.ref_font_size,
+        row.ref_font_family.as_deref(),
+        row.ref_font_weight.as_deref().filter(|_| with_weight),
+    );
+    let verb = if issues.contains(&TypographyIssue::FontNotLoaded) {
+        "renders"
+    } else {
+        "uses"
+    };
+    format!("The implementation {verb} {implementation} here (design: {design}).")
+}
+
+/// `14px Roboto 700`, leaving out what is unknown.
+fn font_label(size: Option<f32>, family: Option<&str>, weight: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(size) = size {
+        parts.push(format!("{}px", (size * 10.0).round() / 10.0));
+    }
+    if let Some(family) = family {
+        parts.push(family.to_string());
+    }
+    if let Some(weight) = weight {
+        parts.push(weight.to_string());
+    }
+    if parts.is_empty() {
+        "an unknown font".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Post `drafts` on the Figma file `file_key`, skipping findings already
+/// commented on by an earlier run.
+pub async fn post_figma_comments(
+    client: &FigmaClient,
+    file_key: &str,
+    drafts: &[FigmaCommentDraft],
+) -> Result<Vec<PostedFigmaComment>> {
+    if drafts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let existing = client.fetch_comments(file_key).await?.comments;
+    let mut posted = Vec::with_capacity(drafts.len());
+    for draft in drafts {
+        let tag = format!("dpc finding {}", draft.fingerprint);
+        let previous = existing
+            .iter()
+            .find(|comment| comment.message.contains(&tag));
+        let (comment_id, already_posted) = match previous {
+            Some(comment) => (comment.id.clone(), true),
+            None => {
+                let comment = client
+                    .post_comment(file_key, &draft.node_id, &draft.message)
+                    .await?;
+                (comment.id, false)
+            }
+        };
+        posted.push(PostedFigmaComment {
+            node_id: draft.node_id.clone(),
+            fingerprint: draft.fingerprint.clone(),
+            message: draft.message.clone(),
+            comment_id,
+            already_posted,
+        });
+    }
+    Ok(posted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TypographyDiff, TypographyMetric};
+
+    fn row(ref_id: &str, impl_id: &str) -> TypographyReportRow {
+        TypographyReportRow {
+            text: "Buy now".into(),
+            ref_id: ref_id.into(),
+            impl_id: impl_id.into(),
+            ref_font_family: Some("Inter".into()),
+            impl_font_family: Some("Roboto".into()),
+            ref_font_size: Some(16.0),
+            impl_font_size: Some(14.0),
+            ref_font_weight: Some("600".into()),
+            impl_font_weight: Some("400".into()),
+            ref_line_height: None,
+            impl_line_height: None,
+            ref_lines: Some(1),
+            impl_lines: Some(1),
+            penalty: 1.0,
+            issues: vec![],
+        }
+    }
+
+    #[test]
+    fn drafts_describe_major_typography_findings_on_figma_nodes() {
+        let diff = |ref_id: Option<&str>, impl_id: Option<&str>, issues, fingerprint: &str| {
+            TypographyDiff {
+                element_id_ref: ref_id.map(str::to_string),
+                element_id_impl: impl_id.map(str::to_string),
+                label: None,
+                style: None,
+                issues,
+                details: None,
+                fingerprint: Some(fingerprint.to_string()),
+            }
+        };
+        let scores = MetricScores {
+            pixel: None,
+            layout: None,
+            typography: Some(TypographyMetric {
+                score: 0.5,
+                diffs: vec![
+                    diff(
+                        Some("1:2"),
+                        Some("impl-7"),
+                        vec![
+                            TypographyIssue::FontFamilyMismatch,
+                            TypographyIssue::FontSizeDiff,
+                        ],
+                        "typo-a",
+                    ),
+                    diff(
+                        Some("1:3"),
+                        None,
+                        vec![TypographyIssue::FontFamilyMismatch],
+                        "typo-b",
+                    ),
+                    // Moderate: left to the CI report.
+                    diff(
+                        Some("1:4"),
+                        Some("impl-8"),
+                        vec![TypographyIssue::LetterSpacingDiff],
+                        "typo-c",
+                    ),
+                    // No Figma node to pin the comment to.
+                    diff(
+                        None,
+                        Some("impl-9"),
+                        vec![TypographyIssue::FontFamilyMismatch],
+                        "typo-d",
+                    ),
+                ],
+            }),
+            color: None,
+            content: None,
+            semantic: None,
+        };
+
+        let drafts = figma_comment_drafts(
+            &scores,
+            &SeverityCalibration::default(),
+            &[row("1:2", "impl-7"), row("1:4", "impl-8")],
+        );
+        let nodes: Vec<&str> = drafts.iter().map(|d| d.node_id.as_str()).collect();
+        assert_eq!(nodes, ["1:2", "1:3"]);
+        assert_eq!(
+            drafts[0].message,
+            "The implementation uses 14px Roboto here (design: 16px Inter).\n\ndpc finding typo-a"
+        );
+        assert!(drafts[1].message.starts_with("This text is missing"));
+        assert_eq!(drafts[1].fingerprint, "typo-b");
+    }
+}
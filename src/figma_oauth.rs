@@ -0,0 +1,494 @@
+//! Figma OAuth2 browser login with refresh tokens kept in the OS keyring.
+//!
+//! Figma does not offer a device-code grant, so `dpc figma login` runs the
+//! authorization-code flow against a loopback redirect. The resulting
+//! credentials are stored under the `dpc` keyring service and refreshed
+//! transparently by [`OAuthSession::access_token`].
+
+use crate::error::{DpcError, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+const AUTHORIZE_URL: &str = "https://www.figma.com/oauth";
+const TOKEN_URL: &str = "https://api.figma.com/v1/oauth/token";
+const REFRESH_URL: &str = "https://api.figma.com/v1/oauth/refresh";
+const DEFAULT_SCOPE: &str = "file_content:read";
+pub const DEFAULT_REDIRECT_PORT: u16 = 8976;
+const KEYRING_SERVICE: &str = "dpc";
+const KEYRING_USER: &str = "figma-oauth";
+/// Refresh this many seconds before the access token actually expires.
+const REFRESH_MARGIN_SECS: u64 = 60;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// OAuth application settings, read from `FIGMA_CLIENT_ID` / `FIGMA_CLIENT_SECRET`.
+#[derive(Debug, Clone)]
+pub struct FigmaOAuthConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_port: u16,
+    pub scope: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    pub refresh_url: String,
+}
+
+impl FigmaOAuthConfig {
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_port: DEFAULT_REDIRECT_PORT,
+            scope: DEFAULT_SCOPE.to_string(),
+            authorize_url: AUTHORIZE_URL.to_string(),
+            token_url: TOKEN_URL.to_string(),
+            refresh_url: REFRESH_URL.to_string(),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let client_id = std::env::var("FIGMA_CLIENT_ID").ok()?;
+        let client_secret = std::env::var("FIGMA_CLIENT_SECRET").ok()?;
+        if client_id.is_empty() || client_secret.is_empty() {
+            return None;
+        }
+        Some(Self::new(client_id, client_secret))
+    }
+
+    /// Also request `scope` (e.g. comment write access), keeping the defaults.
+    pub fn with_scope(mut self, scope: &str) -> Self {
+        if !self
+            .scope
+            .split_whitespace()
+            .any(|existing| existing == scope)
+        {
+            self.scope = format!("{} {scope}", self.scope);
+        }
+        self
+    }
+
+    pub fn redirect_uri(&self) -> String {
+        format!("http://localhost:{}/callback", self.redirect_port)
+    }
+
+    /// Browser URL that starts the authorization-code flow.
+    pub fn authorization_url(&self, state: &str) -> Result<Url> {
+        let mut url = Url::parse(&self.authorize_url)?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri())
+            .append_pair("scope", &self.scope)
+            .append_pair("state", state)
+            .append_pair("response_type", "code");
+        Ok(url)
+    }
+}
+
+/// Tokens persisted in the OS keyring.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCredentials {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Unix timestamp (seconds) at which `access_token` expires.
+    pub expires_at: u64,
+}
+
+impl StoredCredentials {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now + REFRESH_MARGIN_SECS >= self.expires_at
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+impl TokenResponse {
+    fn into_credentials(
+        self,
+        previous_refresh: Option<&str>,
+        now: u64,
+    ) -> Result<StoredCredentials> {
+        let refresh_token = self
+            .refresh_token
+            .or_else(|| previous_refresh.map(str::to_owned))
+            .ok_or_else(|| {
+                DpcError::figma_api(None, "OAuth response is missing a refresh token")
+            })?;
+        Ok(StoredCredentials {
+            access_token: self.access_token,
+            refresh_token,
+            expires_at: now + self.expires_in,
+        })
+    }
+}
+
+fn keyring_entry() -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(keyring_error)
+}
+
+fn keyring_error(err: keyring::Error) -> DpcError {
+    DpcError::Config(format!("OS keyring error: {err}"))
+}
+
+/// Read stored credentials; `Ok(None)` when nobody has logged in yet.
+pub fn load_credentials() -> Result<Option<StoredCredentials>> {
+    match keyring_entry()?.get_password() {
+        Ok(raw) => Ok(Some(serde_json::from_str(&raw)?)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(keyring_error(err)),
+    }
+}
+
+pub fn save_credentials(credentials: &StoredCredentials) -> Result<()> {
+    let raw = serde_json::to_string(credentials)?;
+    keyring_entry()?.set_password(&raw).map_err(keyring_error)
+}
+
+/// Remove stored credentials. Returns `false` when there was nothing to remove.
+pub fn clear_credentials() -> Result<bool> {
+    match keyring_entry()?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(keyring_error(err)),
+    }
+}
+
+/// Exchange an authorization code for tokens.
+pub async fn exchange_code(
+    http: &Client,
+    config: &FigmaOAuthConfig,
+    code: &str,
+) -> Result<StoredCredentials> {
+    let redirect_uri = config.redirect_uri();
+    let form = [
+        ("client_id", config.client_id.as_str()),
+        ("client_secret", config.client_secret.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("code", code),
+        ("grant_type", "authorization_code"),
+    ];
+    let response = post_token_form(http, &config.token_url, &form).await?;
+    response.into_credentials(None, unix_now())
+}
+
+/// Trade a refresh token for a new access token.
+pub async fn refresh_credentials(
+    http: &Client,
+    config: &FigmaOAuthConfig,
+    credentials: &StoredCredentials,
+) -> Result<StoredCredentials> {
+    let form = [
+        ("client_id", config.client_id.as_str()),
+        ("client_secret", config.client_secret.as_str()),
+        ("refresh_token", credentials.refresh_token.as_str()),
+    ];
+    let response = post_token_form(http, &config.refresh_url, &form).await?;
+    response.into_credentials(Some(&credentials.refresh_token), unix_now())
+}
+
+async fn post_token_form(http: &Client, url: &str, form: &[(&str, &str)]) -> Result<TokenResponse> {
+    let response = http
+        .post(url)
+        .form(form)
+        .send()
+        .await
+        .map_err(DpcError::Network)?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(DpcError::figma_api(
+            Some(status),
+            format!(
+                "OAuth token request failed (status {}): {}",
+                status.as_u16(),
+                body
+            ),
+        ));
+    }
+    serde_json::from_str(&body).map_err(DpcError::Serialization)
+}
+
+/// Random value for the OAuth `state` parameter: 128 bits from the OS CSPRNG.
+pub fn new_state() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| DpcError::Unknown(format!("Failed to generate OAuth state: {e}")))?;
+    Ok(hex::encode(bytes))
+}
+
+/// Accept the browser redirect on `listener` and return the authorization code.
+pub async fn wait_for_callback(listener: TcpListener, expected_state: &str) -> Result<String> {
+    let accept = async {
+        loop {
+            let (mut stream, _) = listener.accept().await?;
+            let mut buf = vec![0u8; 8192];
+            let read = stream.read(&mut buf).await?;
+            let request = String::from_utf8_lossy(&buf[..read]).into_owned();
+            let result = parse_callback_request(&request, expected_state);
+            let body = match &result {
+                Some(Ok(_)) => "Figma login complete. You can close this window.",
+                Some(Err(_)) => "Figma login failed. Check the terminal for details.",
+                None => "Waiting for the Figma redirect\u{2026}",
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await?;
+            if let Some(result) = result {
+                return result;
+            }
+        }
+    };
+    tokio::time::timeout(CALLBACK_TIMEOUT, accept)
+        .await
+        .map_err(|_| DpcError::Config("Timed out waiting for the Figma OAuth redirect".into()))?
+}
+
+/// Parse the redirect request line. `None` means the request was not the callback
+/// (e.g. a favicon fetch) and the listener should keep waiting.
+fn parse_callback_request(request: &str, expected_state: &str) -> Option<Result<String>> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let url = Url::parse("http://localhost").ok()?.join(target).ok()?;
+    if url.path() != "/callback" {
+        return None;
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    if let Some(error) = param("error") {
+        return Some(Err(DpcError::Config(format!(
+            "Figma OAuth authorization was denied: {error}"
+        ))));
+    }
+    if param("state").as_deref() != Some(expected_state) {
+        return Some(Err(DpcError::Config(
+            "Figma OAuth state mismatch; restart `dpc figma login`".into(),
+        )));
+    }
+    Some(param("code").ok_or_else(|| {
+        DpcError::Config("Figma OAuth redirect did not include an authorization code".into())
+    }))
+}
+
+/// Stored credentials plus the app settings needed to refresh them.
+#[derive(Clone)]
+pub struct OAuthSession {
+    config: FigmaOAuthConfig,
+    http: Client,
+    credentials: Arc<Mutex<StoredCredentials>>,
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+    persist: bool,
+}
+
+impl std::fmt::Debug for OAuthSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthSession")
+            .field("client_id", &self.config.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OAuthSession {
+    /// Build a session that writes refreshed tokens back to the OS keyring.
+    pub fn new(config: FigmaOAuthConfig, credentials: StoredCredentials) -> Self {
+        Self {
+            config,
+            http: crate::network::http_client(),
+            credentials: Arc::new(Mutex::new(credentials)),
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+            persist: true,
+        }
+    }
+
+    /// Build a session that keeps refreshed tokens in memory only.
+    pub fn in_memory(config: FigmaOAuthConfig, credentials: StoredCredentials) -> Self {
+        Self {
+            persist: false,
+            ..Self::new(config, credentials)
+        }
+    }
+
+    /// Load a session from the keyring; `None` if the app settings or tokens are missing.
+    pub async fn load() -> Result<Option<Self>> {
+        let Some(config) = FigmaOAuthConfig::from_env() else {
+            return Ok(None);
+        };
+        let credentials = tokio::task::spawn_blocking(load_credentials)
+            .await
+            .map_err(|e| DpcError::Unknown(e.to_string()))??;
+        Ok(credentials.map(|credentials| Self::new(config, credentials)))
+    }
+
+    /// Last known access token, without refreshing.
+    pub fn current_access_token(&self) -> String {
+        self.credentials
+            .lock()
+            .expect("credentials lock poisoned")
+            .access_token
+            .clone()
+    }
+
+    /// Access token, refreshed first if it has expired (or is about to).
+    pub async fn access_token(&self) -> Result<String> {
+        let _guard = self.refresh_lock.lock().await;
+        let current = self
+            .credentials
+            .lock()
+            .expect("credentials lock poisoned")
+            .clone();
+        if !current.is_expired(unix_now()) {
+            return Ok(current.access_token);
+        }
+
+        let refreshed = refresh_credentials(&self.http, &self.config, &current).await?;
+        if self.persist {
+            let to_save = refreshed.clone();
+            tokio::task::spawn_blocking(move || save_credentials(&to_save))
+                .await
+                .map_err(|e| DpcError::Unknown(e.to_string()))??;
+        }
+        let token = refreshed.access_token.clone();
+        *self.credentials.lock().expect("credentials lock poisoned") = refreshed;
+        Ok(token)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authorization_url_includes_client_and_state() {
+        let config = FigmaOAuthConfig::new("client", "secret");
+        let url = config.authorization_url("abc").unwrap();
+        let pairs: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(pairs["client_id"], "client");
+        assert_eq!(pairs["redirect_uri"], "http://localhost:8976/callback");
+        assert_eq!(pairs["state"], "abc");
+        assert_eq!(pairs["response_type"], "code");
+        assert!(!pairs.contains_key("client_secret"));
+        assert_eq!(pairs["scope"], "file_content:read");
+
+        let config = config
+            .with_scope("file_comments:write")
+            .with_scope("file_comments:write");
+        let url = config.authorization_url("abc").unwrap();
+        let pairs: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs["scope"], "file_content:read file_comments:write");
+    }
+
+    #[test]
+    fn state_is_128_random_bits() {
+        let state = new_state().unwrap();
+        assert_eq!(state.len(), 32);
+        assert!(state.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(state, new_state().unwrap());
+    }
+
+    #[test]
+    fn credentials_expire_within_refresh_margin() {
+        let creds = StoredCredentials {
+            access_token: "a".into(),
+            refresh_token: "r".into(),
+            expires_at: 1_000,
+        };
+        assert!(!creds.is_expired(900));
+        assert!(creds.is_expired(1_000 - REFRESH_MARGIN_SECS));
+    }
+
+    #[test]
+    fn refresh_response_keeps_previous_refresh_token() {
+        let response: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"new","expires_in":3600}"#).unwrap();
+        let creds = response.into_credentials(Some("old-refresh"), 100).unwrap();
+
+        assert_eq!(creds.access_token, "new");
+        assert_eq!(creds.refresh_token, "old-refresh");
+        assert_eq!(creds.expires_at, 3_700);
+    }
+
+    #[test]
+    fn callback_request_checks_state_and_extracts_code() {
+        let ok = parse_callback_request("GET /callback?code=xyz&state=s1 HTTP/1.1\r\n", "s1");
+        assert_eq!(ok.unwrap().unwrap(), "xyz");
+
+        let mismatch = parse_callback_request("GET /callback?code=xyz&state=s2 HTTP/1.1\r\n", "s1");
+        assert!(matches!(mismatch, Some(Err(DpcError::Config(_)))));
+
+        let denied = parse_callback_request("GET /callback?error=access_denied HTTP/1.1\r\n", "s1");
+        assert!(matches!(denied, Some(Err(DpcError::Config(_)))));
+
+        assert!(parse_callback_request("GET /favicon.ico HTTP/1.1\r\n", "s1").is_none());
+    }
+
+    #[tokio::test]
+    async fn in_memory_session_skips_refresh_for_fresh_token() {
+        let session = OAuthSession::in_memory(
+            FigmaOAuthConfig::new("client", "secret"),
+            StoredCredentials {
+                access_token: "live".into(),
+                refresh_token: "r".into(),
+                expires_at: unix_now() + 3_600,
+            },
+        );
+        assert_eq!(session.access_token().await.unwrap(), "live");
+    }
+
+    #[tokio::test]
+    async fn expired_session_refreshes_from_token_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let read = stream.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..read]).into_owned();
+            let body = r#"{"access_token":"refreshed","expires_in":3600}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let mut config = FigmaOAuthConfig::new("client", "secret");
+        config.refresh_url = format!("http://{addr}/v1/oauth/refresh");
+        let session = OAuthSession::in_memory(
+            config,
+            StoredCredentials {
+                access_token: "stale".into(),
+                refresh_token: "r1".into(),
+                expires_at: 0,
+            },
+        );
+
+        assert_eq!(session.access_token().await.unwrap(), "refreshed");
+        assert_eq!(session.current_access_token(), "refreshed");
+        let request = server.await.unwrap();
+        assert!(request.contains("refresh_token=r1"));
+    }
+}
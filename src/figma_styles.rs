@@ -0,0 +1,169 @@
+//! Published Figma styles as the reference's design tokens
+//! (`--figma-styles`).
+//!
+//! The styles endpoint lists a file's published color and text styles by
+//! name; their values live on the nodes holding them, which are fetched in
+//! batches. The resolved styles are stored on the reference
+//! [`FigmaSnapshot`](crate::types::FigmaSnapshot), where the color metric
+//! takes the fill styles its nodes use as the reference palette instead of
+//! clustering screenshot pixels, and color and typography findings name the
+//! style they break ("Primary/600").
+
+use crate::error::Result;
+use crate::figma::api_types::FigmaNodeData;
+use crate::figma::conversion::{map_paint, map_typography};
+use crate::figma_client::{FigmaClient, FigmaNodesResponse, FigmaPublishedStyle};
+use crate::types::{FigmaPaintKind, FigmaStyle, FigmaStyleKind};
+
+/// Style nodes fetched per nodes request, to keep URLs short.
+const NODES_PER_REQUEST: usize = 50;
+
+/// The published fill and text styles of `file_key` with their values.
+/// Effect and grid styles, and fill styles that are not a solid color, are
+/// left out.
+pub async fn fetch_figma_styles(client: &FigmaClient, file_key: &str) -> Result<Vec<FigmaStyle>> {
+    let published: Vec<FigmaPublishedStyle> = client
+        .fetch_styles(file_key)
+        .await?
+        .meta
+        .styles
+        .into_iter()
+        .filter(|style| style_kind(style).is_some())
+        .collect();
+    let mut styles = Vec::new();
+    for batch in published.chunks(NODES_PER_REQUEST) {
+        let ids: Vec<String> = batch.iter().map(|style| style.node_id.clone()).collect();
+        let nodes = client.fetch_nodes(file_key, &ids).await?;
+        styles.extend(resolve_styles(batch, &nodes));
+    }
+    Ok(styles)
+}
+
+/// Values of the `published` styles, read from the nodes holding them.
+pub fn resolve_styles(
+    published: &[FigmaPublishedStyle],
+    nodes: &FigmaNodesResponse,
+) -> Vec<FigmaStyle> {
+    published
+        .iter()
+        .filter_map(|style| {
+            let kind = style_kind(style)?;
+            let container = nodes.nodes.get(&style.node_id)?;
+            let node: FigmaNodeData = serde_json::from_value(container.document.clone()).ok()?;
+            let (color, typography) = match kind {
+                FigmaStyleKind::Fill => {
+                    let paint = node
+                        .fills
+                        .iter()
+                        .filter_map(|paint| map_paint(paint, None))
+                        .find(|paint| paint.kind == FigmaPaintKind::Solid)?;
+                    (Some(paint.color?), None)
+                }
+                FigmaStyleKind::Text => (None, Some(map_typography(node.style.as_ref()?))),
+            };
+            Some(FigmaStyle {
+                node_id: style.node_id.clone(),
+                key: style.key.clone(),
+                name: style.name.clone(),
+                kind,
+                color,
+                typography,
+            })
+        })
+        .collect()
+}
+
+fn style_kind(style: &FigmaPublishedStyle) -> Option<FigmaStyleKind> {
+    match style.style_type.as_str() {
+        "FILL" => Some(FigmaStyleKind::Fill),
+        "TEXT" => Some(FigmaStyleKind::Text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::figma_client::FigmaStylesResponse;
+    use crate::types::{BoundingBox, FigmaNode, FigmaSnapshot};
+
+    #[test]
+    fn resolves_fill_and_text_styles_from_their_nodes() {
+        let published: FigmaStylesResponse = serde_json::from_str(
+            r#"{"meta": {"styles": [
+                {"key": "k1", "node_id": "1:2", "style_type": "FILL", "name": "Primary/600"},
+                {"key": "k2", "node_id": "1:3", "style_type": "TEXT", "name": "Heading/H1"},
+                {"key": "k3", "node_id": "1:4", "style_type": "EFFECT", "name": "Shadow/Card"},
+                {"key": "k4", "node_id": "1:5", "style_type": "FILL", "name": "Brand/Gradient"}
+            ]}}"#,
+        )
+        .unwrap();
+        let nodes: FigmaNodesResponse = serde_json::from_str(
+            r#"{"nodes": {
+                "1:2": {"document": {"id": "1:2", "name": "Primary/600", "type": "RECTANGLE",
+                    "fills": [{"type": "SOLID", "color": {"r": 0.1, "g": 0.45, "b": 0.91, "a": 1}}]}},
+                "1:3": {"document": {"id": "1:3", "name": "Heading/H1", "type": "TEXT",
+                    "style": {"fontFamily": "Inter", "fontSize": 32, "fontWeight": 700, "lineHeightPx": 40}}},
+                "1:5": {"document": {"id": "1:5", "name": "Brand/Gradient", "type": "RECTANGLE",
+                    "fills": [{"type": "GRADIENT_LINEAR"}]}}
+            }}"#,
+        )
+        .unwrap();
+
+        let styles = resolve_styles(&published.meta.styles, &nodes);
+
+        assert_eq!(styles.len(), 2);
+        assert_eq!(styles[0].name, "Primary/600");
+        assert_eq!(styles[0].kind, FigmaStyleKind::Fill);
+        assert_eq!(styles[0].color.as_deref(), Some("#1972e8"));
+        let heading = styles[1].typography.as_ref().unwrap();
+        assert_eq!(heading.font_family.as_deref(), Some("Inter"));
+        assert_eq!(heading.font_size, Some(32.0));
+
+        // Nodes refer to local styles by node ID, to library ones by key.
+        let node = |styles: &[(&str, &str)]| FigmaNode {
+            id: "9:1".into(),
+            name: None,
+            node_type: "TEXT".into(),
+            bounding_box: BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 10.0,
+                height: 10.0,
+            },
+            text: None,
+            typography: None,
+            line_count: None,
+            fills: Vec::new(),
+            children: Vec::new(),
+            auto_layout: None,
+            constraints: None,
+            responsive_box: None,
+            styles: styles
+                .iter()
+                .map(|(property, id)| (property.to_string(), id.to_string()))
+                .collect(),
+        };
+        let snapshot = FigmaSnapshot {
+            schema_version: FigmaSnapshot::SCHEMA_VERSION,
+            file_key: "file".into(),
+            node_id: "0:1".into(),
+            name: None,
+            version: None,
+            frame_size: None,
+            export_scale: None,
+            nodes: Vec::new(),
+            styles,
+        };
+        let styled = node(&[("fill", "1:2"), ("text", "S:k2,7:1")]);
+        let fill = snapshot.style_of(&styled, "fill").unwrap();
+        assert_eq!(fill.name, "Primary/600");
+        assert_eq!(
+            snapshot.style_of(&styled, "text").unwrap().name,
+            "Heading/H1"
+        );
+        assert!(snapshot
+            .style_of(&node(&[("fill", "S:k9,1:1")]), "fill")
+            .is_none());
+    }
+}
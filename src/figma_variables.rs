@@ -0,0 +1,506 @@
+//! Figma variables compared with the implementation's CSS custom properties
+//! (`--figma-variables`).
+//!
+//! The local variables endpoint lists a file's variables with a value for
+//! every mode of their collection; aliases are followed to the value they
+//! point at. Each variable is matched to a custom property captured with the
+//! implementation's DOM snapshot, by its web code syntax
+//! (`var(--color-primary)`) or else by its name in kebab case
+//! (`color/primary` → `--color-primary`). Values on `:root` are compared
+//! with the collection's default mode; a rule whose selector names another
+//! mode (`[data-theme="dark"]`, `.dark`) is compared with that mode.
+//! Variables the implementation does not define are not reported.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::Result;
+use crate::figma_client::{FigmaClient, FigmaVariable, FigmaVariablesMeta};
+use crate::metrics::{parse_css_color, srgb_delta_e};
+use crate::output::{VariableDrift, VariableMismatch};
+use crate::types::CustomProperties;
+
+/// ΔE2000 above which a color variable counts as changed.
+const COLOR_DELTA_E: f32 = 1.0;
+const ALPHA_TOLERANCE: f32 = 0.02;
+const NUMBER_TOLERANCE: f32 = 0.01;
+/// Pixels per `rem` and `em` when comparing number variables.
+const ROOT_FONT_SIZE: f32 = 16.0;
+/// Alias chains longer than this are treated as cycles.
+const MAX_ALIAS_DEPTH: usize = 16;
+const ROOT_SCOPE: &str = ":root";
+
+/// A variable's value in one mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableValue {
+    /// RGBA with channels in 0.0 - 1.0
+    Color([f32; 4]),
+    Number(f32),
+    Text(String),
+    Boolean(bool),
+}
+
+/// A Figma variable with its aliases resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignVariable {
+    pub name: String,
+    pub collection: String,
+    /// Custom property named by the variable's web code syntax, when set.
+    pub css_property: Option<String>,
+    /// Value per mode name, the collection's default mode first.
+    pub values: Vec<(String, VariableValue)>,
+}
+
+/// The local variables of `file_key` with their values per mode. Reading
+/// variables needs a Figma Enterprise plan.
+pub async fn fetch_figma_variables(
+    client: &FigmaClient,
+    file_key: &str,
+) -> Result<Vec<DesignVariable>> {
+    let response = client.fetch_local_variables(file_key).await?;
+    Ok(resolve_variables(&response.meta))
+}
+
+/// Variables of `meta` with aliases resolved, sorted by collection and name.
+/// Variables without a usable value in any mode are left out.
+pub fn resolve_variables(meta: &FigmaVariablesMeta) -> Vec<DesignVariable> {
+    let mut variables: Vec<DesignVariable> = meta
+        .variables
+        .values()
+        .filter_map(|variable| {
+            let collection = meta
+                .variable_collections
+                .get(&variable.variable_collection_id)?;
+            let mut modes: Vec<_> = collection.modes.iter().collect();
+            modes.sort_by_key(|mode| mode.mode_id != collection.default_mode_id);
+            let values: Vec<(String, VariableValue)> = modes
+                .into_iter()
+                .filter_map(|mode| {
+                    let value = resolve_value(meta, variable, &mode.mode_id, &mode.name, 0)?;
+                    Some((mode.name.clone(), value))
+                })
+                .collect();
+            if values.is_empty() {
+                return None;
+            }
+            Some(DesignVariable {
+                name: variable.name.clone(),
+                collection: collection.name.clone(),
+                css_property: variable
+                    .code_syntax
+                    .get("WEB")
+                    .and_then(|syntax| web_property(syntax)),
+                values,
+            })
+        })
+        .collect();
+    variables.sort_by(|a, b| (&a.collection, &a.name).cmp(&(&b.collection, &b.name)));
+    variables
+}
+
+/// Value of `variable` in `mode_id`. An alias into another collection takes
+/// the target's mode with the same name, or its default mode.
+fn resolve_value(
+    meta: &FigmaVariablesMeta,
+    variable: &FigmaVariable,
+    mode_id: &str,
+    mode_name: &str,
+    depth: usize,
+) -> Option<VariableValue> {
+    let value = variable.values_by_mode.get(mode_id)?;
+    if value.get("type").and_then(Value::as_str) == Some("VARIABLE_ALIAS") {
+        if depth >= MAX_ALIAS_DEPTH {
+            return None;
+        }
+        let target = meta.variables.get(value.get("id")?.as_str()?)?;
+        let collection = meta
+            .variable_collections
+            .get(&target.variable_collection_id)?;
+        let target_mode = collection
+            .modes
+            .iter()
+            .find(|mode| mode.name == mode_name)
+            .map_or(collection.default_mode_id.as_str(), |mode| {
+                mode.mode_id.as_str()
+            });
+        return resolve_value(meta, target, target_mode, mode_name, depth + 1);
+    }
+    match variable.resolved_type.as_str() {
+        "COLOR" => {
+            let channel = |key: &str| value.get(key).and_then(Value::as_f64).map(|v| v as f32);
+            Some(VariableValue::Color([
+                channel("r")?,
+                channel("g")?,
+                channel("b")?,
+                channel("a").unwrap_or(1.0),
+            ]))
+        }
+        "FLOAT" => value.as_f64().map(|v| VariableValue::Number(v as f32)),
+        "STRING" => value.as_str().map(|v| VariableValue::Text(v.to_string())),
+        "BOOLEAN" => value.as_bool().map(VariableValue::Boolean),
+        _ => None,
+    }
+}
+
+/// `--color-primary` from a web code syntax such as `var(--color-primary)`.
+fn web_property(syntax: &str) -> Option<String> {
+    let syntax = syntax.trim();
+    let name = syntax
+        .strip_prefix("var(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(syntax)
+        .trim();
+    name.starts_with("--").then(|| name.to_string())
+}
+
+/// Compare `variables` with the custom properties the implementation
+/// defines.
+pub fn compare_variables(
+    variables: &[DesignVariable],
+    properties: &CustomProperties,
+) -> VariableDrift {
+    let names = properties.root.keys().chain(
+        properties
+            .scoped
+            .iter()
+            .flat_map(|scope| scope.values.keys()),
+    );
+    let mut by_key: HashMap<String, &str> = HashMap::new();
+    for name in names {
+        by_key.entry(token_key(name)).or_insert(name);
+    }
+    let scopes: Vec<_> = std::iter::once((ROOT_SCOPE, &properties.root))
+        .chain(
+            properties
+                .scoped
+                .iter()
+                .map(|scope| (scope.selector.as_str(), &scope.values)),
+        )
+        .collect();
+
+    let mut drift = VariableDrift::default();
+    for variable in variables {
+        let Some(property) = property_for(variable, &by_key) else {
+            continue;
+        };
+        for (scope, values) in &scopes {
+            let Some(actual) = values.get(property) else {
+                continue;
+            };
+            let (mode, expected) = if *scope == ROOT_SCOPE {
+                &variable.values[0]
+            } else {
+                mode_for_selector(variable, scope)
+            };
+            let Some((matches, delta_e)) = compare_value(expected, actual) else {
+                continue;
+            };
+            drift.compared += 1;
+            if !matches {
+                drift.mismatches.push(VariableMismatch {
+                    variable: variable.name.clone(),
+                    collection: variable.collection.clone(),
+                    mode: mode.clone(),
+                    property: property.to_string(),
+                    scope: scope.to_string(),
+                    expected: css_value(expected),
+                    actual: actual.trim().to_string(),
+                    delta_e,
+                });
+            }
+        }
+    }
+    drift
+}
+
+/// Custom property the implementation defines for `variable`.
+fn property_for<'a>(
+    variable: &DesignVariable,
+    by_key: &HashMap<String, &'a str>,
+) -> Option<&'a str> {
+    let named = variable.css_property.as_deref().map(token_key);
+    named
+        .into_iter()
+        .chain([
+            token_key(&variable.name),
+            token_key(&format!("{}/{}", variable.collection, variable.name)),
+        ])
+        .find_map(|key| by_key.get(&key).copied())
+}
+
+/// The mode a scoped rule's selector names, else the default mode.
+fn mode_for_selector<'a>(
+    variable: &'a DesignVariable,
+    selector: &str,
+) -> &'a (String, VariableValue) {
+    let selector = format!("-{}-", token_key(selector));
+    variable
+        .values
+        .iter()
+        .skip(1)
+        .chain(variable.values.first())
+        .find(|(mode, _)| {
+            let mode = token_key(mode);
+            !mode.is_empty() && selector.contains(&format!("-{mode}-"))
+        })
+        .unwrap_or(&variable.values[0])
+}
+
+/// Lowercase kebab case for matching names, split between words and
+/// numbers: `color/primary/600`, `--color-primary-600` and `colorPrimary600`
+/// all give `color-primary-600`.
+fn token_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    let mut previous: Option<char> = None;
+    for ch in name.chars() {
+        if ch.is_alphanumeric() {
+            let boundary = previous.is_some_and(|prev| {
+                (ch.is_uppercase() && prev.is_lowercase()) || (ch.is_numeric() != prev.is_numeric())
+            });
+            if boundary || (previous.is_none() && !key.is_empty()) {
+                key.push('-');
+            }
+            key.extend(ch.to_lowercase());
+            previous = Some(ch);
+        } else {
+            previous = None;
+        }
+    }
+    key
+}
+
+/// Whether `actual` matches `expected`, with the ΔE of colors. None when
+/// the CSS value cannot be read as the variable's type.
+fn compare_value(expected: &VariableValue, actual: &str) -> Option<(bool, Option<f32>)> {
+    match expected {
+        VariableValue::Color(expected) => {
+            let actual = parse_css_color(actual)?;
+            let delta_e = srgb_delta_e(
+                [expected[0], expected[1], expected[2]],
+                [actual[0], actual[1], actual[2]],
+            );
+            let matches =
+                delta_e <= COLOR_DELTA_E && (expected[3] - actual[3]).abs() <= ALPHA_TOLERANCE;
+            Some((matches, Some(delta_e)))
+        }
+        VariableValue::Number(expected) => {
+            let actual = parse_css_number(actual)?;
+            Some(((expected - actual).abs() <= NUMBER_TOLERANCE, None))
+        }
+        VariableValue::Text(expected) => Some((unquote(actual) == expected, None)),
+        VariableValue::Boolean(expected) => {
+            let actual = match actual.trim() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => return None,
+            };
+            Some((actual == *expected, None))
+        }
+    }
+}
+
+/// Pixels of a length or plain number (`16px`, `1.5rem`, `400`).
+fn parse_css_number(value: &str) -> Option<f32> {
+    let value = value.trim();
+    if let Some(rem) = value
+        .strip_suffix("rem")
+        .or_else(|| value.strip_suffix("em"))
+    {
+        return rem.trim().parse::<f32>().ok().map(|v| v * ROOT_FONT_SIZE);
+    }
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| {
+            value
+                .strip_prefix(quote)
+                .and_then(|rest| rest.strip_suffix(quote))
+        })
+        .unwrap_or(value)
+}
+
+/// A variable value in CSS notation.
+fn css_value(value: &VariableValue) -> String {
+    match value {
+        VariableValue::Color([r, g, b, a]) => {
+            let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            if *a >= 0.999 {
+                format!("#{:02x}{:02x}{:02x}", channel(*r), channel(*g), channel(*b))
+            } else {
+                format!(
+                    "rgba({}, {}, {}, {})",
+                    channel(*r),
+                    channel(*g),
+                    channel(*b),
+                    format_number(*a)
+                )
+            }
+        }
+        VariableValue::Number(value) => format_number(*value),
+        VariableValue::Text(value) => value.clone(),
+        VariableValue::Boolean(value) => value.to_string(),
+    }
+}
+
+fn format_number(value: f32) -> String {
+    let formatted = format!("{value:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ScopedCustomProperties;
+    use std::collections::BTreeMap;
+
+    fn meta() -> FigmaVariablesMeta {
+        serde_json::from_value(serde_json::json!({
+            "variableCollections": {
+                "c:prim": {
+                    "id": "c:prim", "name": "Primitives", "defaultModeId": "p",
+                    "modes": [{"modeId": "p", "name": "Value"}]
+                },
+                "c:theme": {
+                    "id": "c:theme", "name": "Theme", "defaultModeId": "light",
+                    "modes": [{"modeId": "dark", "name": "Dark"}, {"modeId": "light", "name": "Light"}]
+                }
+            },
+            "variables": {
+                "v:blue": {
+                    "id": "v:blue", "name": "blue/600", "variableCollectionId": "c:prim",
+                    "resolvedType": "COLOR",
+                    "valuesByMode": {"p": {"r": 0.1, "g": 0.45, "b": 0.91, "a": 1.0}}
+                },
+                "v:white": {
+                    "id": "v:white", "name": "white", "variableCollectionId": "c:prim",
+                    "resolvedType": "COLOR",
+                    "valuesByMode": {"p": {"r": 1.0, "g": 1.0, "b": 1.0, "a": 1.0}}
+                },
+                "v:surface": {
+                    "id": "v:surface", "name": "color/surface", "variableCollectionId": "c:theme",
+                    "resolvedType": "COLOR",
+                    "valuesByMode": {
+                        "light": {"type": "VARIABLE_ALIAS", "id": "v:white"},
+                        "dark": {"r": 0.0, "g": 0.0, "b": 0.0, "a": 1.0}
+                    },
+                    "codeSyntax": {"WEB": "var(--surface)"}
+                },
+                "v:radius": {
+                    "id": "v:radius", "name": "radiusMd", "variableCollectionId": "c:theme",
+                    "resolvedType": "FLOAT",
+                    "valuesByMode": {"light": 8, "dark": 8}
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn properties(root: &[(&str, &str)], scoped: &[(&str, &[(&str, &str)])]) -> CustomProperties {
+        let values = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+        CustomProperties {
+            root: values(root),
+            scoped: scoped
+                .iter()
+                .map(|(selector, pairs)| ScopedCustomProperties {
+                    selector: selector.to_string(),
+                    values: values(pairs),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn resolves_aliases_and_puts_the_default_mode_first() {
+        let variables = resolve_variables(&meta());
+        let surface = variables
+            .iter()
+            .find(|variable| variable.name == "color/surface")
+            .unwrap();
+        assert_eq!(surface.collection, "Theme");
+        assert_eq!(surface.css_property.as_deref(), Some("--surface"));
+        assert_eq!(
+            surface.values,
+            [
+                ("Light".to_string(), VariableValue::Color([1.0; 4])),
+                (
+                    "Dark".to_string(),
+                    VariableValue::Color([0.0, 0.0, 0.0, 1.0])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn token_keys_match_figma_and_css_names() {
+        assert_eq!(token_key("color/primary/600"), "color-primary-600");
+        assert_eq!(token_key("--color-primary-600"), "color-primary-600");
+        assert_eq!(token_key("colorPrimary600"), "color-primary-600");
+        assert_eq!(token_key("Spacing / LG"), "spacing-lg");
+        assert_eq!(token_key("[data-theme=\"dark\"]"), "data-theme-dark");
+    }
+
+    #[test]
+    fn reports_values_that_differ_per_mode() {
+        let variables = resolve_variables(&meta());
+        let drift = compare_variables(
+            &variables,
+            &properties(
+                &[
+                    ("--blue-600", "rgb(26, 115, 232)"),
+                    ("--surface", "#ffffff"),
+                    ("--radius-md", "0.25rem"),
+                ],
+                &[("[data-theme=\"dark\"]", &[("--surface", "#1e1e1e")])],
+            ),
+        );
+
+        assert_eq!(drift.compared, 4);
+        let described: Vec<(&str, &str, &str)> = drift
+            .mismatches
+            .iter()
+            .map(|m| (m.variable.as_str(), m.mode.as_str(), m.expected.as_str()))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("color/surface", "Dark", "#000000"),
+                ("radiusMd", "Light", "8")
+            ]
+        );
+        assert_eq!(drift.mismatches[0].scope, "[data-theme=\"dark\"]");
+        assert!(drift.mismatches[0].delta_e.unwrap() > COLOR_DELTA_E);
+        assert_eq!(drift.mismatches[1].actual, "0.25rem");
+    }
+
+    #[test]
+    fn skips_values_that_cannot_be_read() {
+        let variables = resolve_variables(&meta());
+        let drift = compare_variables(
+            &variables,
+            &properties(
+                &[("--surface", "hsl(0 0% 100%)"), ("--radius-md", "8px")],
+                &[],
+            ),
+        );
+        assert_eq!(drift.compared, 1);
+        assert!(drift.mismatches.is_empty());
+    }
+}
@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 use dpc_lib::output::DPC_OUTPUT_VERSION;
-use dpc_lib::{DpcError, DpcOutput, ErrorOutput, QualityFindingType};
+use dpc_lib::{DpcError, DpcOutput, ErrorOutput, MetricTrend, QualityFindingType};
 
 use crate::cli::OutputFormat;
 
@@ -120,7 +120,48 @@ pub fn format_pretty(body: &DpcOutput, colorize: bool) -> String {
             let header = format!("{} Design parity check", status_colored);
             writeln!(buf, "{header}").ok();
             writeln!(buf, "Similarity: {similarity} (threshold {threshold})").ok();
+            if let Some(sampling) = &out.sampling {
+                writeln!(
+                    buf,
+                    "Samples: {} (mean {:.3}, 95% CI {:.3}\u{2013}{:.3}, std dev {:.3}{})",
+                    sampling.samples,
+                    sampling.similarity.mean,
+                    sampling.similarity.lower,
+                    sampling.similarity.upper,
+                    sampling.similarity.std_dev,
+                    if sampling.unstable { ", unstable" } else { "" }
+                )
+                .ok();
+            }
+            if let Some(mismatch) = &out.dimension_mismatch {
+                writeln!(
+                    buf,
+                    "Dimensions: ref {}x{}, impl {}x{}, compared {}x{} ({})",
+                    mismatch.reference.width,
+                    mismatch.reference.height,
+                    mismatch.implementation.width,
+                    mismatch.implementation.height,
+                    mismatch.compared.width,
+                    mismatch.compared.height,
+                    format!("{:?}", mismatch.policy).to_ascii_lowercase()
+                )
+                .ok();
+            }
+            if let Some(candidates) = &out.reference_candidates {
+                writeln!(
+                    buf,
+                    "Reference: {} (best of {} candidates)",
+                    out.ref_resource.value,
+                    candidates.len()
+                )
+                .ok();
+            }
+
+            for warning in &out.warnings {
+                writeln!(buf, "{} {warning}", color("Warning:", "33", colorize)).ok();
+            }
 
+            let narrative = out.summary.as_ref().and_then(|s| s.narrative.as_deref());
             let mut issues: Vec<String> = out
                 .summary
                 .as_ref()
@@ -129,7 +170,12 @@ pub fn format_pretty(body: &DpcOutput, colorize: bool) -> String {
             if issues.len() > 5 {
                 issues.truncate(5);
             }
-            if !issues.is_empty() {
+            if let Some(narrative) = narrative {
+                writeln!(buf, "Summary:").ok();
+                for line in narrative.lines() {
+                    writeln!(buf, "  {line}").ok();
+                }
+            } else if !issues.is_empty() {
                 writeln!(buf, "Top issues (max 5):").ok();
                 for issue in issues {
                     writeln!(buf, "- {issue}").ok();
@@ -152,6 +198,9 @@ pub fn format_pretty(body: &DpcOutput, colorize: bool) -> String {
             if let Some(content) = &out.metrics.content {
                 metrics.push(("content", content.score));
             }
+            if let Some(semantic) = &out.metrics.semantic {
+                metrics.push(("semantic", semantic.score));
+            }
             if !metrics.is_empty() {
                 writeln!(buf, "Metrics:").ok();
                 for (name, score) in metrics {
@@ -160,6 +209,33 @@ pub fn format_pretty(body: &DpcOutput, colorize: bool) -> String {
                 }
             }
 
+            if !out.sections.is_empty() {
+                writeln!(buf, "Sections:").ok();
+                for section in &out.sections {
+                    let styled = format_score(section.similarity, Some(out.threshold));
+                    writeln!(buf, "- {:12} {}", section.name, styled).ok();
+                    if let Some(issue) = section.top_issues.first() {
+                        writeln!(buf, "  {issue}").ok();
+                    }
+                }
+            }
+
+            if let Some(drift) = &out.variable_drift {
+                writeln!(
+                    buf,
+                    "Variables: {} of {} differ",
+                    drift.mismatches.len(),
+                    drift.compared
+                )
+                .ok();
+                for mismatch in drift.mismatches.iter().take(5) {
+                    writeln!(buf, "- {}", mismatch.describe()).ok();
+                }
+                if drift.mismatches.len() > 5 {
+                    writeln!(buf, "- ... and {} more", drift.mismatches.len() - 5).ok();
+                }
+            }
+
             if let Some(art) = &out.artifacts {
                 let mut paths = Vec::new();
                 paths.push(("directory", art.directory.clone()));
@@ -262,12 +338,157 @@ pub fn format_pretty(body: &DpcOutput, colorize: bool) -> String {
                         QualityFindingType::SpacingInconsistent => "spacing_inconsistent",
                         QualityFindingType::LowContrast => "low_contrast",
                         QualityFindingType::MissingHierarchy => "missing_hierarchy",
+                        QualityFindingType::MissingFocusStyle => "missing_focus_style",
+                        QualityFindingType::MissingHoverStyle => "missing_hover_style",
+                        QualityFindingType::SkippedHeadingLevel => "skipped_heading_level",
+                        QualityFindingType::EmptyHeading => "empty_heading",
+                        QualityFindingType::MissingAltText => "missing_alt_text",
+                        QualityFindingType::NonSemanticClickHandler => "non_semantic_click_handler",
+                        QualityFindingType::SpacingOffScale => "spacing_off_scale",
+                        QualityFindingType::PaletteSprawl => "palette_sprawl",
                     };
                     writeln!(buf, "- [{}] {}: {}", severity, kind, finding.message).ok();
                 }
             }
             buf
         }
+        DpcOutput::DiffResults(out) => {
+            let mut buf = String::new();
+            let diff = &out.diff;
+            let header = color("[DIFF]", "34", colorize);
+            let delta = format!("{:+.3}", diff.similarity_delta);
+            let delta_code = if diff.similarity_delta >= 0.0 {
+                "32"
+            } else {
+                "31"
+            };
+            writeln!(
+                buf,
+                "{} Similarity {:.3} -> {:.3} ({})",
+                header,
+                diff.old_similarity,
+                diff.new_similarity,
+                color(&delta, delta_code, colorize)
+            )
+            .ok();
+            writeln!(buf, "Old: {}", out.old.display()).ok();
+            writeln!(buf, "New: {}", out.new.display()).ok();
+            if !diff.metrics.is_empty() {
+                writeln!(buf, "Metrics:").ok();
+                for change in &diff.metrics {
+                    let score = |value: Option<f32>| {
+                        value.map_or_else(|| "-".to_string(), |v| format!("{v:.3}"))
+                    };
+                    let (label, code) = match change.trend {
+                        MetricTrend::Improved => ("improved", "32"),
+                        MetricTrend::Regressed => ("regressed", "31"),
+                        MetricTrend::Unchanged => ("unchanged", "0"),
+                        MetricTrend::Added => ("added", "36"),
+                        MetricTrend::Removed => ("removed", "33"),
+                    };
+                    writeln!(
+                        buf,
+                        "- {:12} {} -> {} {}",
+                        change.metric.to_string(),
+                        score(change.old),
+                        score(change.new),
+                        color(label, code, colorize)
+                    )
+                    .ok();
+                }
+            }
+            for (title, findings) in [
+                ("Appeared", &diff.appeared),
+                ("Disappeared", &diff.disappeared),
+            ] {
+                if !findings.is_empty() {
+                    writeln!(buf, "{} ({}):", title, findings.len()).ok();
+                    for finding in findings {
+                        writeln!(buf, "- {}", finding.description).ok();
+                    }
+                }
+            }
+            writeln!(buf, "Unchanged findings: {}", diff.unchanged_findings).ok();
+            buf
+        }
+        DpcOutput::Flow(out) => {
+            let mut buf = String::new();
+            let status = if out.passed { "PASS" } else { "FAIL" };
+            let status_colored = color(status, if out.passed { "32" } else { "31" }, colorize);
+            writeln!(
+                buf,
+                "{} Flow parity check: {} screens from {}",
+                status_colored,
+                out.screens.len(),
+                out.ref_resource.value
+            )
+            .ok();
+            writeln!(
+                buf,
+                "Similarity: {} (mean, threshold {:.1}%)",
+                format_score(out.similarity, Some(out.threshold)),
+                out.threshold * 100.0
+            )
+            .ok();
+            for warning in &out.warnings {
+                writeln!(buf, "{} {warning}", color("Warning:", "33", colorize)).ok();
+            }
+            writeln!(buf, "Screens:").ok();
+            for (step, screen) in out.screens.iter().enumerate() {
+                let result = match (&screen.error, screen.similarity) {
+                    (Some(error), _) => color(&format!("error: {error}"), "31", colorize),
+                    (None, Some(similarity)) => format_score(similarity, Some(out.threshold)),
+                    (None, None) => color("no implementation", "33", colorize),
+                };
+                writeln!(buf, "{}. {} {}", step + 1, screen.frame_name, result).ok();
+                if let Some(implementation) = &screen.impl_resource {
+                    writeln!(buf, "   impl: {}", implementation.value).ok();
+                }
+                if let Some(issue) = screen.top_issues.first() {
+                    writeln!(buf, "   {issue}").ok();
+                }
+            }
+            buf
+        }
+        DpcOutput::Audit(out) => {
+            let mut buf = String::new();
+            let status = if out.passed { "PASS" } else { "FAIL" };
+            let status_colored = color(status, if out.passed { "32" } else { "31" }, colorize);
+            writeln!(
+                buf,
+                "{} Site audit: {} pages from {}",
+                status_colored,
+                out.pages.len(),
+                out.crawl
+            )
+            .ok();
+            writeln!(
+                buf,
+                "Similarity: {} (mean, threshold {:.1}%)",
+                format_score(out.similarity, Some(out.threshold)),
+                out.threshold * 100.0
+            )
+            .ok();
+            for warning in &out.warnings {
+                writeln!(buf, "{} {warning}", color("Warning:", "33", colorize)).ok();
+            }
+            writeln!(buf, "Pages:").ok();
+            for page in &out.pages {
+                let result = match (&page.error, page.similarity) {
+                    (Some(error), _) => color(&format!("error: {error}"), "31", colorize),
+                    (None, Some(similarity)) => format_score(similarity, Some(out.threshold)),
+                    (None, None) => color("no reference", "33", colorize),
+                };
+                writeln!(buf, "- {} {}", page.url, result).ok();
+                if let Some(reference) = &page.ref_resource {
+                    writeln!(buf, "  ref: {}", reference.value).ok();
+                }
+                if let Some(issue) = page.top_issues.first() {
+                    writeln!(buf, "  {issue}").ok();
+                }
+            }
+            buf
+        }
         DpcOutput::Error(out) => {
             let mut buf = String::new();
             let header = color("[ERROR]", "31", colorize);
@@ -343,6 +564,7 @@ mod tests {
                 score: 0.99,
                 diff_regions: vec![],
                 semantic_diffs: None,
+                offset: None,
             }),
             layout: Some(LayoutMetric {
                 score: 0.75,
@@ -354,6 +576,7 @@ mod tests {
                 diffs: vec![],
             }),
             content: None,
+            semantic: None,
         };
         let artifacts = CompareArtifacts {
             directory: PathBuf::from("/tmp/dpc-run"),
@@ -361,10 +584,22 @@ mod tests {
             ref_screenshot: Some(PathBuf::from("/tmp/dpc-run/ref.png")),
             impl_screenshot: Some(PathBuf::from("/tmp/dpc-run/impl.png")),
             diff_image: Some(PathBuf::from("/tmp/dpc-run/diff.png")),
+            color_drift_image: None,
+            ref_thumbnail: None,
+            impl_thumbnail: None,
+            diff_thumbnail: None,
             ref_dom_snapshot: None,
             impl_dom_snapshot: None,
             ref_figma_snapshot: None,
             impl_figma_snapshot: None,
+            typography_report: None,
+            typography_report_csv: None,
+            typography_specimen: None,
+            run_manifest: None,
+            ref_network_har: None,
+            impl_network_har: None,
+            overlay: None,
+            artifact_index: None,
         };
         let output = DpcOutput::Compare(CompareOutput {
             version: DPC_OUTPUT_VERSION.to_string(),
@@ -376,18 +611,29 @@ mod tests {
                 kind: ResourceKind::Image,
                 value: "impl.png".into(),
             },
-            viewport: Viewport {
-                width: 1440,
-                height: 900,
-            },
+            viewport: Viewport::new(1440, 900),
+            browser: None,
+            emulation: None,
             similarity: 0.96,
             threshold: 0.95,
             passed: true,
             metrics,
             summary: Some(Summary {
                 top_issues: vec!["Design parity check passed".into()],
+                narrative: None,
             }),
             artifacts: Some(artifacts),
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
         });
 
         let pretty = format_pretty(&output, false);
@@ -413,10 +659,9 @@ mod tests {
                 kind: ResourceKind::Image,
                 value: "impl.png".to_string(),
             },
-            viewport: Viewport {
-                width: 800,
-                height: 600,
-            },
+            viewport: Viewport::new(800, 600),
+            browser: None,
+            emulation: None,
             similarity: 0.96,
             threshold: 0.95,
             passed: true,
@@ -425,18 +670,32 @@ mod tests {
                     score: 0.96,
                     diff_regions: Vec::new(),
                     semantic_diffs: None,
+                    offset: None,
                 }),
                 layout: None,
                 typography: None,
                 color: None,
                 content: None,
+                semantic: None,
             },
             summary: Some(Summary {
                 top_issues: vec![
                     "Design parity check passed (96.0% similarity, threshold: 95.0%)".into(),
                 ],
+                narrative: None,
             }),
             artifacts: None,
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
         });
 
         let pretty = format_pretty(&output, false);
@@ -446,6 +705,15 @@ mod tests {
         assert!(pretty.contains("Metrics:"));
         assert!(pretty.contains("pixel") && pretty.contains("0.96"));
         assert!(pretty.contains("Top issues") || pretty.contains("Top issues (max 5):"));
+
+        let mut output = output;
+        if let DpcOutput::Compare(out) = &mut output {
+            out.summary.as_mut().unwrap().narrative =
+                Some("Passed; only the pixel score dipped slightly.".into());
+        }
+        let pretty = format_pretty(&output, false);
+        assert!(pretty.contains("Summary:\n  Passed; only the pixel score dipped slightly."));
+        assert!(!pretty.contains("Top issues"));
     }
 
     #[test]
@@ -0,0 +1,443 @@
+//! Publishing compare results to GitHub pull requests.
+//!
+//! CI jobs used to hand-roll the same glue after every `dpc compare`: turn the
+//! result into markdown, post it on the pull request, and surface findings as
+//! check annotations. [`GitHubPublisher`] does both against the REST API. The
+//! PR comment carries a hidden marker and is edited in place on later runs, so
+//! a pull request keeps one up-to-date report instead of one per push.
+
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{Rgba, RgbaImage};
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::accepted::{finding_ratings, Finding};
+use crate::error::{DpcError, Result};
+use crate::metrics::{metric_score, MetricKind, SeverityCalibration};
+use crate::output::CompareOutput;
+use crate::types::DiffSeverity;
+
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+/// Hidden line that identifies the dpc report among a pull request's comments.
+pub const COMMENT_MARKER: &str = "<!-- dpc-report -->";
+/// GitHub accepts at most this many annotations per check run request.
+const ANNOTATIONS_PER_REQUEST: usize = 50;
+const COMMENTS_PER_PAGE: usize = 100;
+/// Height of each panel in the composite image.
+const COMPOSITE_HEIGHT: u32 = 600;
+const COMPOSITE_GAP: u32 = 8;
+
+/// A check run annotation for one finding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckAnnotation {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// `notice`, `warning` or `failure`.
+    pub annotation_level: &'static str,
+    pub title: String,
+    pub message: String,
+}
+
+/// Client for one repository (`owner/name`).
+#[derive(Debug, Clone)]
+pub struct GitHubPublisher {
+    http: Client,
+    token: String,
+    repo: String,
+    api_url: Url,
+}
+
+#[derive(Deserialize)]
+struct IssueComment {
+    id: u64,
+    #[serde(default)]
+    body: String,
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    id: u64,
+    html_url: String,
+}
+
+impl GitHubPublisher {
+    pub fn new(token: impl Into<String>, repo: &str) -> Result<Self> {
+        Self::with_api_url(token, repo, DEFAULT_GITHUB_API_URL)
+    }
+
+    /// Publisher for a GitHub Enterprise Server, e.g. `https://ghe.example.com/api/v3`.
+    pub fn with_api_url(token: impl Into<String>, repo: &str, api_url: &str) -> Result<Self> {
+        let valid = repo.split_once('/').is_some_and(|(owner, name)| {
+            !owner.is_empty() && !name.is_empty() && !name.contains('/')
+        });
+        if !valid {
+            return Err(DpcError::Config(format!(
+                "GitHub repository '{repo}' must be OWNER/NAME"
+            )));
+        }
+        let http = crate::network::client_builder()
+            .build()
+            .map_err(DpcError::Network)?;
+        Ok(Self {
+            http,
+            token: token.into(),
+            repo: repo.to_string(),
+            api_url: Url::parse(&format!("{}/", api_url.trim_end_matches('/')))?,
+        })
+    }
+
+    /// Post `body` on pull request `pr`, or update the comment an earlier run
+    /// left there. Returns the comment's URL.
+    pub async fn publish_comment(&self, pr: u64, body: &str) -> Result<String> {
+        let body = if body.contains(COMMENT_MARKER) {
+            body.to_string()
+        } else {
+            format!("{COMMENT_MARKER}\n{body}")
+        };
+        let existing = self.find_report_comment(pr).await?;
+        let comment: IssueComment = match existing {
+            Some(id) => {
+                let url = self.endpoint(&format!("repos/{}/issues/comments/{id}", self.repo))?;
+                self.send(self.http.patch(url).json(&json!({ "body": body })))
+                    .await?
+            }
+            None => {
+                let url = self.endpoint(&format!("repos/{}/issues/{pr}/comments", self.repo))?;
+                self.send(self.http.post(url).json(&json!({ "body": body })))
+                    .await?
+            }
+        };
+        Ok(comment.html_url)
+    }
+
+    async fn find_report_comment(&self, pr: u64) -> Result<Option<u64>> {
+        for page in 1.. {
+            let url = self.endpoint(&format!(
+                "repos/{}/issues/{pr}/comments?per_page={COMMENTS_PER_PAGE}&page={page}",
+                self.repo
+            ))?;
+            let comments: Vec<IssueComment> = self.send(self.http.get(url)).await?;
+            if let Some(comment) = comments.iter().find(|c| c.body.contains(COMMENT_MARKER)) {
+                return Ok(Some(comment.id));
+            }
+            if comments.len() < COMMENTS_PER_PAGE {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Create a completed check run named `name` on commit `sha`, concluding
+    /// `success` when the comparison passed. Annotations beyond GitHub's
+    /// per-request limit are added in follow-up updates. Returns the run's URL.
+    pub async fn publish_check_run(
+        &self,
+        name: &str,
+        sha: &str,
+        result: &CompareOutput,
+        summary: &str,
+        annotations: &[CheckAnnotation],
+    ) -> Result<String> {
+        let title = format!(
+            "{:.1}% similarity (threshold {:.1}%)",
+            result.similarity * 100.0,
+            result.threshold * 100.0
+        );
+        let output = |batch: &[CheckAnnotation]| {
+            json!({
+                "title": title,
+                "summary": summary,
+                "annotations": batch,
+            })
+        };
+        let mut batches = annotations.chunks(ANNOTATIONS_PER_REQUEST);
+        let url = self.endpoint(&format!("repos/{}/check-runs", self.repo))?;
+        let run: CheckRun = self
+            .send(self.http.post(url).json(&json!({
+                "name": name,
+                "head_sha": sha,
+                "status": "completed",
+                "conclusion": if result.passed { "success" } else { "failure" },
+                "output": output(batches.next().unwrap_or_default()),
+            })))
+            .await?;
+        for batch in batches {
+            let url = self.endpoint(&format!("repos/{}/check-runs/{}", self.repo, run.id))?;
+            let _: Value = self
+                .send(
+                    self.http
+                        .patch(url)
+                        .json(&json!({ "output": output(batch) })),
+                )
+                .await?;
+        }
+        Ok(run.html_url)
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url> {
+        self.api_url.join(path).map_err(DpcError::InvalidUrl)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header("accept", "application/vnd.github+json")
+            .header("x-github-api-version", "2022-11-28")
+            .header("user-agent", "dpc")
+            .send()
+            .await?;
+        let status = response.status();
+        let url = response.url().clone();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| value.get("message")?.as_str().map(str::to_owned))
+                .unwrap_or_else(|| body.trim().to_string());
+            return Err(DpcError::Config(format!(
+                "GitHub API request to {url} failed ({status}): {message}"
+            )));
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+/// Markdown report of `result` for a PR comment or check run summary, with
+/// the composite image when it has a public URL.
+pub fn markdown_summary(result: &CompareOutput, image_url: Option<&str>) -> String {
+    let status = if result.passed {
+        "\u{2705} Design parity passed"
+    } else {
+        "\u{274c} Design parity failed"
+    };
+    let mut md = format!(
+        "{COMMENT_MARKER}\n### {status}: {:.1}% similarity (threshold {:.1}%)\n\n",
+        result.similarity * 100.0,
+        result.threshold * 100.0
+    );
+    md.push_str(&format!(
+        "Reference `{}` vs implementation `{}`\n\n",
+        result.ref_resource.value, result.impl_resource.value
+    ));
+
+    md.push_str("| Metric | Score |\n| --- | ---: |\n");
+    for kind in MetricKind::all() {
+        if let Some(score) = metric_score(&result.metrics, kind) {
+            md.push_str(&format!("| {kind} | {:.1}% |\n", score * 100.0));
+        }
+    }
+
+    let issues = result
+        .summary
+        .as_ref()
+        .map(|summary| summary.top_issues.as_slice())
+        .unwrap_or_default();
+    let narrative = result
+        .summary
+        .as_ref()
+        .and_then(|summary| summary.narrative.as_deref());
+    if let Some(narrative) = narrative {
+        md.push_str(&format!("\n**Summary**\n\n{narrative}\n"));
+    } else if !issues.is_empty() {
+        md.push_str("\n**Top issues**\n\n");
+        for issue in issues {
+            md.push_str(&format!("- {issue}\n"));
+        }
+    }
+    if !result.warnings.is_empty() {
+        md.push_str("\n**Warnings**\n\n");
+        for warning in &result.warnings {
+            md.push_str(&format!("- {warning}\n"));
+        }
+    }
+    if let Some(url) = image_url {
+        md.push_str(&format!("\n![Reference, implementation and diff]({url})\n"));
+    }
+    if !result.suppressed.is_empty() {
+        md.push_str(&format!(
+            "\n{} finding(s) suppressed by the ignore file.\n",
+            result.suppressed.len()
+        ));
+    }
+    md
+}
+
+/// One annotation per finding of `result`, placed on line 1 of `path` (check
+/// annotations must point into the repository; the result file or the page's
+/// source are natural choices). The level follows the finding's severity.
+pub fn check_annotations(
+    result: &CompareOutput,
+    calibration: &SeverityCalibration,
+    path: &str,
+) -> Vec<CheckAnnotation> {
+    Finding::collect(&result.metrics)
+        .iter()
+        .zip(finding_ratings(&result.metrics, calibration))
+        .map(|(finding, (severity, fingerprint))| {
+            let (level, label) = match severity {
+                DiffSeverity::Major => ("failure", "Major"),
+                DiffSeverity::Moderate => ("warning", "Moderate"),
+                DiffSeverity::Minor => ("notice", "Minor"),
+            };
+            let description = finding.describe();
+            let metric = description.split(':').next().unwrap_or("design");
+            let mut message = description.clone();
+            if let Some(fingerprint) = fingerprint {
+                message.push_str(&format!("\nfingerprint: {fingerprint}"));
+            }
+            CheckAnnotation {
+                path: path.to_string(),
+                start_line: 1,
+                end_line: 1,
+                annotation_level: level,
+                title: format!("{label} {metric} finding"),
+                message,
+            }
+        })
+        .collect()
+}
+
+/// Put the reference screenshot, implementation screenshot and diff heatmap
+/// of `result` side by side at a common height and save the image to `path`.
+/// `None` when the result's artifacts hold none of them (e.g. they were not
+/// kept).
+pub fn composite_image(result: &CompareOutput, path: &Path) -> Result<Option<PathBuf>> {
+    let Some(artifacts) = &result.artifacts else {
+        return Ok(None);
+    };
+    let mut panels = Vec::new();
+    for source in [
+        &artifacts.ref_screenshot,
+        &artifacts.impl_screenshot,
+        &artifacts.diff_image,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|source| source.is_file())
+    {
+        let img = image::open(source)?;
+        let height = img.height().clamp(1, COMPOSITE_HEIGHT);
+        let width = ((img.width() as u64 * height as u64) / img.height().max(1) as u64).max(1);
+        panels.push(
+            img.resize_exact(width as u32, height, FilterType::Triangle)
+                .to_rgba8(),
+        );
+    }
+    if panels.is_empty() {
+        return Ok(None);
+    }
+
+    let height = panels.iter().map(|p| p.height()).max().unwrap_or(1);
+    let width =
+        panels.iter().map(|p| p.width()).sum::<u32>() + COMPOSITE_GAP * (panels.len() as u32 - 1);
+    let mut canvas = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    let mut x = 0i64;
+    for panel in &panels {
+        image::imageops::overlay(&mut canvas, panel, x, 0);
+        x += (panel.width() + COMPOSITE_GAP) as i64;
+    }
+    canvas
+        .save(path)
+        .map_err(|err| DpcError::Config(format!("Failed to save {}: {err}", path.display())))?;
+    Ok(Some(path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(artifacts: Value) -> CompareOutput {
+        serde_json::from_value(json!({
+            "version": "1",
+            "ref": { "kind": "image", "value": "design.png" },
+            "impl": { "kind": "url", "value": "https://example.com" },
+            "viewport": { "width": 1440, "height": 900 },
+            "similarity": 0.9,
+            "threshold": 0.95,
+            "passed": false,
+            "metrics": {
+                "pixel": {
+                    "score": 0.8,
+                    "diffRegions": [{
+                        "x": 0.1, "y": 0.1, "width": 0.2, "height": 0.2,
+                        "severity": "major", "reason": "pixel_change",
+                        "fingerprint": "px-1"
+                    }]
+                },
+                "content": {
+                    "score": 0.9,
+                    "missingText": ["Buy now"],
+                    "missingTextFingerprints": ["txt-1"]
+                }
+            },
+            "summary": { "topIssues": ["Pixel: 1 major region"] },
+            "artifacts": artifacts
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn summary_and_annotations_describe_the_findings() {
+        let result = result(Value::Null);
+        let md = markdown_summary(&result, Some("https://cdn.example.com/composite.png"));
+        assert!(md.starts_with(COMMENT_MARKER));
+        assert!(md.contains("Design parity failed: 90.0% similarity (threshold 95.0%)"));
+        assert!(md.contains("| pixel | 80.0% |"));
+        assert!(md.contains("- Pixel: 1 major region"));
+        assert!(md.contains("](https://cdn.example.com/composite.png)"));
+
+        let mut narrated = result.clone();
+        narrated.summary.as_mut().unwrap().narrative =
+            Some("Failed: the hero image changed and 'Buy now' is missing.".into());
+        let md = markdown_summary(&narrated, None);
+        assert!(md.contains("**Summary**\n\nFailed: the hero image changed"));
+        assert!(!md.contains("**Top issues**"));
+
+        let annotations =
+            check_annotations(&result, &SeverityCalibration::default(), "dpc-result.json");
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].annotation_level, "failure");
+        assert_eq!(annotations[0].title, "Major pixel finding");
+        assert!(annotations[0].message.ends_with("fingerprint: px-1"));
+        assert_eq!(annotations[1].title, "Major content finding");
+        assert_eq!(annotations[1].path, "dpc-result.json");
+
+        assert!(GitHubPublisher::new("token", "just-a-name").is_err());
+        assert!(GitHubPublisher::new("token", "owner/name").is_ok());
+    }
+
+    #[test]
+    fn composite_image_puts_screenshots_side_by_side() {
+        let dir = tempfile::tempdir().unwrap();
+        let save = |name: &str, width, height| {
+            let path = dir.path().join(name);
+            RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        };
+        let result = result(json!({
+            "directory": dir.path(),
+            "refScreenshot": save("ref.png", 200, 100),
+            "implScreenshot": save("impl.png", 100, 100),
+            "diffImage": dir.path().join("missing.png"),
+        }));
+
+        let path = dir.path().join("composite.png");
+        assert_eq!(composite_image(&result, &path).unwrap(), Some(path.clone()));
+        assert_eq!(
+            image::image_dimensions(&path).unwrap(),
+            (200 + COMPOSITE_GAP + 100, 100)
+        );
+        assert_eq!(
+            composite_image(&super::tests::result(Value::Null), &path).unwrap(),
+            None
+        );
+    }
+}
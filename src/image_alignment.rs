@@ -1,11 +1,13 @@
 use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView, GrayImage};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy)]
 pub struct ImageAlignmentOptions {
     pub enabled: bool,
     pub max_shift: u32,
     pub downscale_max_dim: u32,
+    pub method: AlignmentMethod,
 }
 
 impl Default for ImageAlignmentOptions {
@@ -14,10 +16,34 @@ impl Default for ImageAlignmentOptions {
             enabled: false,
             max_shift: 16,
             downscale_max_dim: 256,
+            method: AlignmentMethod::default(),
         }
     }
 }
 
+/// How the global translation between the screenshots is estimated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignmentMethod {
+    /// Try every offset up to `max_shift` and keep the one with the smallest
+    /// mean luma difference.
+    #[default]
+    Search,
+    /// Peak of the phase correlation (normalized cross-power spectrum); its
+    /// cost doesn't grow with `max_shift`.
+    PhaseCorrelation,
+}
+
+impl AlignmentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlignmentMethod::Search => "search",
+            AlignmentMethod::PhaseCorrelation => "phase_correlation",
+        }
+    }
+}
+
+/// Shift applied to the implementation to line it up with the reference.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AlignmentOffset {
     pub dx: i32,
@@ -82,11 +108,13 @@ fn find_best_offset(
         max_shift_scaled = 1;
     }
 
-    let (dx_small, dy_small) = best_shift_luma(
-        &ref_small.to_luma8(),
-        &impl_small.to_luma8(),
-        max_shift_scaled,
-    );
+    let (ref_luma, impl_luma) = (ref_small.to_luma8(), impl_small.to_luma8());
+    let (dx_small, dy_small) = match options.method {
+        AlignmentMethod::Search => best_shift_luma(&ref_luma, &impl_luma, max_shift_scaled),
+        AlignmentMethod::PhaseCorrelation => {
+            phase_correlation_shift(&ref_luma, &impl_luma, max_shift_scaled)
+        }
+    };
 
     let dx = ((dx_small as f64) / scale).round() as i32;
     let dy = ((dy_small as f64) / scale).round() as i32;
@@ -150,6 +178,119 @@ fn best_shift_luma(ref_luma: &GrayImage, impl_luma: &GrayImage, max_shift: i32)
     best
 }
 
+/// Offset maximizing the phase correlation of the two images, within
+/// `max_shift`. Both are mean-centered and zero-padded by `max_shift` so the
+/// circular correlation doesn't wrap content around within that range.
+fn phase_correlation_shift(
+    ref_luma: &GrayImage,
+    impl_luma: &GrayImage,
+    max_shift: i32,
+) -> (i32, i32) {
+    let (w, h) = ref_luma.dimensions();
+    if w == 0 || h == 0 {
+        return (0, 0);
+    }
+    let pad = max_shift.max(0) as usize;
+    let fw = (w as usize + pad).next_power_of_two();
+    let fh = (h as usize + pad).next_power_of_two();
+
+    let spectrum = |img: &GrayImage| {
+        let mean = img.as_raw().iter().map(|&v| v as f64).sum::<f64>() / (w * h) as f64;
+        let mut grid = vec![(0.0, 0.0); fw * fh];
+        for (x, y, pixel) in img.enumerate_pixels() {
+            grid[y as usize * fw + x as usize].0 = pixel[0] as f64 - mean;
+        }
+        fft_2d(&mut grid, fw, fh, false);
+        grid
+    };
+    let reference = spectrum(ref_luma);
+    let implementation = spectrum(impl_luma);
+
+    // Cross-power spectrum F * conj(G), normalized to unit magnitude.
+    let mut cross: Vec<(f64, f64)> = reference
+        .iter()
+        .zip(&implementation)
+        .map(|(&(a, b), &(c, d))| {
+            let (re, im) = (a * c + b * d, b * c - a * d);
+            let magnitude = (re * re + im * im).sqrt();
+            if magnitude > 1e-9 {
+                (re / magnitude, im / magnitude)
+            } else {
+                (0.0, 0.0)
+            }
+        })
+        .collect();
+    fft_2d(&mut cross, fw, fh, true);
+
+    let mut best: (i32, i32) = (0, 0);
+    let mut best_peak = f64::NEG_INFINITY;
+    for dy in -max_shift..=max_shift {
+        for dx in -max_shift..=max_shift {
+            let x = dx.rem_euclid(fw as i32) as usize;
+            let y = dy.rem_euclid(fh as i32) as usize;
+            let peak = cross[y * fw + x].0;
+            let closer = dx.abs() + dy.abs() < best.0.abs() + best.1.abs();
+            if peak > best_peak + 1e-9 || ((peak - best_peak).abs() <= 1e-9 && closer) {
+                best_peak = peak;
+                best = (dx, dy);
+            }
+        }
+    }
+    best
+}
+
+/// In-place 2D FFT of a row-major `width` x `height` grid (both powers of
+/// two); the inverse is unscaled, which doesn't move the peak.
+fn fft_2d(grid: &mut [(f64, f64)], width: usize, height: usize, inverse: bool) {
+    for row in grid.chunks_mut(width) {
+        fft(row, inverse);
+    }
+    let mut column = vec![(0.0, 0.0); height];
+    for x in 0..width {
+        for (y, value) in column.iter_mut().enumerate() {
+            *value = grid[y * width + x];
+        }
+        fft(&mut column, inverse);
+        for (y, value) in column.iter().enumerate() {
+            grid[y * width + x] = *value;
+        }
+    }
+}
+
+/// Iterative radix-2 Cooley-Tukey FFT; `data.len()` must be a power of two.
+fn fft(data: &mut [(f64, f64)], inverse: bool) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = data[start + k];
+                let (c, d) = data[start + k + len / 2];
+                let (tr, ti) = (c * cos - d * sin, c * sin + d * cos);
+                data[start + k] = (a + tr, b + ti);
+                data[start + k + len / 2] = (a - tr, b - ti);
+            }
+        }
+        len <<= 1;
+    }
+}
+
 fn apply_shift(
     reference: &DynamicImage,
     implementation: &DynamicImage,
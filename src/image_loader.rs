@@ -1,10 +1,15 @@
 use std::fs;
+use std::io::{BufRead, Cursor, Read, Seek};
 use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
 
+use base64::Engine;
+use image::io::{Limits, Reader};
 use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageError};
 use thiserror::Error;
 
-use crate::types::{NormalizedView, ResourceKind};
+use crate::types::{BoundingBox, NormalizedView, ResourceKind};
 
 #[derive(Debug, Error)]
 pub enum ImageLoadError {
@@ -14,6 +19,182 @@ pub enum ImageLoadError {
     NotFound(String),
     #[error("Failed to save normalized image: {0}")]
     Save(String),
+    #[error("Image too large: {0}")]
+    TooLarge(String),
+    #[error("Failed to download image: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("Failed to download image from {url}: HTTP {status}")]
+    Http { url: String, status: u16 },
+    #[error("Invalid inline image: {0}")]
+    InvalidData(String),
+}
+
+/// Image area limit in megapixels when `--max-image-megapixels` is not given.
+pub const MAX_IMAGE_MEGAPIXELS_ENV: &str = "DPC_MAX_IMAGE_MEGAPIXELS";
+
+/// Encoded image size limit in bytes when `--max-image-bytes` is not given.
+pub const MAX_IMAGE_BYTES_ENV: &str = "DPC_MAX_IMAGE_BYTES";
+
+/// Decoder allocation allowed per pixel: 16-bit RGBA, the widest layout the
+/// decoders produce.
+const BYTES_PER_PIXEL: u64 = 8;
+
+/// Upper bounds on image inputs, checked before and during decoding so a
+/// corrupt or hostile file (e.g. a tiny PNG declaring 100000x100000 pixels)
+/// fails with a clear error instead of exhausting memory.
+///
+/// The area is bounded rather than each side, so tall full-page screenshots
+/// pass as long as their pixel count (and so their decoded size) fits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    /// Maximum width × height in pixels; the decoder may allocate
+    /// [`BYTES_PER_PIXEL`] bytes for each.
+    pub max_pixels: u64,
+    /// Maximum encoded size in bytes (file or download).
+    pub max_bytes: u64,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_pixels: 128_000_000,
+            max_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+static IMAGE_LIMITS: OnceLock<ImageLimits> = OnceLock::new();
+
+impl ImageLimits {
+    /// Defaults, overridden by `DPC_MAX_IMAGE_MEGAPIXELS` and
+    /// `DPC_MAX_IMAGE_BYTES` when set.
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        if let Some(value) = env_u64(MAX_IMAGE_MEGAPIXELS_ENV) {
+            limits.max_pixels = value.saturating_mul(1_000_000);
+        }
+        if let Some(value) = env_u64(MAX_IMAGE_BYTES_ENV) {
+            limits.max_bytes = value;
+        }
+        limits
+    }
+
+    /// The limits installed with [`configure`], or [`Self::from_env`].
+    pub fn current() -> Self {
+        IMAGE_LIMITS.get().copied().unwrap_or_else(Self::from_env)
+    }
+
+    /// Reject an encoded payload of `len` bytes.
+    pub fn check_bytes(&self, len: u64, source: &str) -> Result<(), ImageLoadError> {
+        if len > self.max_bytes {
+            return Err(ImageLoadError::TooLarge(format!(
+                "{source} is {len} bytes, limit is {} (raise with --max-image-bytes)",
+                self.max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject declared dimensions before any pixels are decoded.
+    pub fn check_dimensions(
+        &self,
+        width: u32,
+        height: u32,
+        source: &str,
+    ) -> Result<(), ImageLoadError> {
+        let pixels = width as u64 * height as u64;
+        if pixels > self.max_pixels {
+            return Err(ImageLoadError::TooLarge(format!(
+                "{source} is {width}x{height} pixels ({:.1} megapixels), limit is {:.1} megapixels (raise with --max-image-megapixels)",
+                pixels as f64 / 1e6,
+                self.max_pixels as f64 / 1e6
+            )));
+        }
+        Ok(())
+    }
+
+    /// Bytes the decoder may allocate for an image at the pixel limit.
+    fn max_alloc(&self) -> u64 {
+        self.max_pixels.saturating_mul(BYTES_PER_PIXEL)
+    }
+
+    fn decoder_limits(&self) -> Limits {
+        let mut limits = Limits::no_limits();
+        limits.max_alloc = Some(self.max_alloc());
+        limits
+    }
+}
+
+/// Install `limits` for every image loaded by the rest of the process. Later
+/// calls keep the first limits.
+pub fn configure(limits: ImageLimits) {
+    let _ = IMAGE_LIMITS.set(limits);
+}
+
+fn env_u64(key: &str) -> Option<u64> {
+    std::env::var(key).ok()?.trim().parse().ok()
+}
+
+/// Decode an in-memory image (e.g. a download) within `limits`.
+pub fn decode_image_bytes(
+    bytes: &[u8],
+    limits: &ImageLimits,
+    source: &str,
+) -> Result<DynamicImage, ImageLoadError> {
+    limits.check_bytes(bytes.len() as u64, source)?;
+    decode_with_limits(
+        || Ok(Reader::new(Cursor::new(bytes)).with_guessed_format()?),
+        limits,
+        source,
+    )
+}
+
+/// Open and decode an image file within `limits`.
+pub fn load_image_with_limits(
+    path: &Path,
+    limits: &ImageLimits,
+) -> Result<DynamicImage, ImageLoadError> {
+    if !path.exists() {
+        return Err(ImageLoadError::NotFound(path.display().to_string()));
+    }
+    let source = path.display().to_string();
+    let len = fs::metadata(path)
+        .map_err(|e| ImageLoadError::Load(ImageError::IoError(e)))?
+        .len();
+    limits.check_bytes(len, &source)?;
+    decode_with_limits(
+        || {
+            Ok(Reader::open(path)
+                .map_err(ImageError::IoError)?
+                .with_guessed_format()?)
+        },
+        limits,
+        &source,
+    )
+}
+
+/// Read the header for the declared size first, then decode with the
+/// decoder's own allocation limits as a backstop.
+fn decode_with_limits<R, F>(
+    open: F,
+    limits: &ImageLimits,
+    source: &str,
+) -> Result<DynamicImage, ImageLoadError>
+where
+    R: BufRead + Seek,
+    F: Fn() -> Result<Reader<R>, ImageError>,
+{
+    let (width, height) = open()?.into_dimensions()?;
+    limits.check_dimensions(width, height, source)?;
+    let mut reader = open()?;
+    reader.limits(limits.decoder_limits());
+    reader.decode().map_err(|err| match err {
+        ImageError::Limits(_) => ImageLoadError::TooLarge(format!(
+            "{source} would need more than {} bytes to decode",
+            limits.max_alloc()
+        )),
+        other => ImageLoadError::Load(other),
+    })
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -23,48 +204,128 @@ pub struct ImageLoadOptions {
     pub target_height: Option<u32>,
 }
 
-pub fn load_image(path: &str) -> Result<DynamicImage, ImageLoadError> {
-    let path = Path::new(path);
-    if !path.exists() {
-        return Err(ImageLoadError::NotFound(path.display().to_string()));
+pub fn load_image(path: &Path) -> Result<DynamicImage, ImageLoadError> {
+    load_image_with_limits(path, &ImageLimits::current())
+}
+
+/// Download a bitmap over http(s), enforcing the byte limit while streaming
+/// and the dimension limits before decoding.
+pub async fn download_image(
+    url: &str,
+    limits: &ImageLimits,
+) -> Result<DynamicImage, ImageLoadError> {
+    let client = crate::network::client_builder()
+        .timeout(Duration::from_secs(60))
+        .build()?;
+    let mut response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ImageLoadError::Http {
+            url: url.to_string(),
+            status: status.as_u16(),
+        });
+    }
+    if let Some(len) = response.content_length() {
+        limits.check_bytes(len, url)?;
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        limits.check_bytes(body.len() as u64, url)?;
     }
-    Ok(image::open(path)?)
+    decode_image_bytes(&body, limits, url)
+}
+
+/// Decode the base64 payload of a `data:image/...;base64,` URI.
+pub fn decode_data_uri(uri: &str) -> Result<Vec<u8>, ImageLoadError> {
+    let (_, data) = uri
+        .split_once(',')
+        .ok_or_else(|| ImageLoadError::InvalidData("data URI has no payload".to_string()))?;
+    let data: String = data.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| ImageLoadError::InvalidData(format!("bad base64 in data URI: {e}")))
+}
+
+/// Read an encoded image from stdin, stopping once `limits.max_bytes` is
+/// exceeded.
+pub fn read_image_stdin(limits: &ImageLimits) -> Result<Vec<u8>, ImageLoadError> {
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .lock()
+        .take(limits.max_bytes.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|e| ImageLoadError::InvalidData(format!("failed to read stdin: {e}")))?;
+    limits.check_bytes(bytes.len() as u64, "stdin")?;
+    if bytes.is_empty() {
+        return Err(ImageLoadError::InvalidData("stdin was empty".to_string()));
+    }
+    Ok(bytes)
+}
+
+/// Like [`image_to_normalized_view`] for an already-read encoded image.
+pub fn bytes_to_normalized_view(
+    bytes: &[u8],
+    source: &str,
+    output_path: &Path,
+    options: ImageLoadOptions,
+) -> Result<NormalizedView, ImageLoadError> {
+    let img = decode_image_bytes(bytes, &ImageLimits::current(), source)?;
+    normalize_image(img, output_path, options)
 }
 
 pub fn image_to_normalized_view(
-    path: &str,
-    output_path: &str,
+    path: &Path,
+    output_path: &Path,
+    options: ImageLoadOptions,
+) -> Result<NormalizedView, ImageLoadError> {
+    normalize_image(load_image(path)?, output_path, options)
+}
+
+/// Like [`image_to_normalized_view`] for an image hosted at an http(s) URL.
+pub async fn remote_image_to_normalized_view(
+    url: &str,
+    output_path: &Path,
+    options: ImageLoadOptions,
+) -> Result<NormalizedView, ImageLoadError> {
+    let img = download_image(url, &ImageLimits::current()).await?;
+    normalize_image(img, output_path, options)
+}
+
+pub(crate) fn normalize_image(
+    img: DynamicImage,
+    output_path: &Path,
     options: ImageLoadOptions,
 ) -> Result<NormalizedView, ImageLoadError> {
-    let img = load_image(path)?;
     let (orig_width, orig_height) = img.dimensions();
 
-    let (final_img, width, height) = if options.no_resize {
-        (img.clone(), orig_width, orig_height)
+    let (final_img, width, height, content_area) = if options.no_resize {
+        (img.clone(), orig_width, orig_height, None)
     } else if let (Some(tw), Some(th)) = (options.target_width, options.target_height) {
         let resized = resize_with_letterbox(&img, tw, th);
-        (resized, tw, th)
+        let content_area = letterbox_content_area(orig_width, orig_height, tw, th);
+        (resized, tw, th, content_area)
     } else {
-        (img.clone(), orig_width, orig_height)
+        (img.clone(), orig_width, orig_height, None)
     };
 
-    let out_path = Path::new(output_path);
-    if let Some(parent) = out_path.parent() {
+    if let Some(parent) = output_path.parent() {
         fs::create_dir_all(parent).map_err(|e| ImageLoadError::Save(e.to_string()))?;
     }
 
     final_img
-        .save(out_path)
+        .save(output_path)
         .map_err(|e| ImageLoadError::Save(e.to_string()))?;
 
     Ok(NormalizedView {
         kind: ResourceKind::Image,
-        screenshot_path: out_path.to_path_buf(),
+        screenshot_path: output_path.to_path_buf(),
         width,
         height,
         dom: None,
         figma_tree: None,
         ocr_blocks: None,
+        content_area,
     })
 }
 
@@ -93,6 +354,35 @@ pub fn resize_with_letterbox(
     canvas
 }
 
+/// Where [`resize_with_letterbox`] places the source image inside the target,
+/// or `None` when it fills the target and no padding is added.
+pub fn letterbox_content_area(
+    src_w: u32,
+    src_h: u32,
+    target_width: u32,
+    target_height: u32,
+) -> Option<BoundingBox> {
+    if src_w == 0 || src_h == 0 {
+        return None;
+    }
+    let scale_w = target_width as f64 / src_w as f64;
+    let scale_h = target_height as f64 / src_h as f64;
+    let scale = scale_w.min(scale_h);
+
+    let new_w = (src_w as f64 * scale).round() as u32;
+    let new_h = (src_h as f64 * scale).round() as u32;
+    if new_w >= target_width && new_h >= target_height {
+        return None;
+    }
+
+    Some(BoundingBox {
+        x: ((target_width - new_w) / 2) as f32,
+        y: ((target_height - new_h) / 2) as f32,
+        width: new_w as f32,
+        height: new_h as f32,
+    })
+}
+
 pub fn resize_to_match(img: &DynamicImage, target_width: u32, target_height: u32) -> DynamicImage {
     img.resize_exact(target_width, target_height, FilterType::Lanczos3)
 }
@@ -105,7 +395,7 @@ mod tests {
 
     #[test]
     fn test_load_nonexistent_file() {
-        let result = load_image("/nonexistent/path/image.png");
+        let result = load_image(Path::new("/nonexistent/path/image.png"));
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ImageLoadError::NotFound(_)));
     }
@@ -117,6 +407,140 @@ mod tests {
         assert_eq!(result.dimensions(), (100, 100));
     }
 
+    #[test]
+    fn test_letterbox_content_area() {
+        let area = letterbox_content_area(200, 100, 100, 100).unwrap();
+        assert_eq!(
+            (area.x, area.y, area.width, area.height),
+            (0.0, 25.0, 100.0, 50.0)
+        );
+        assert!(letterbox_content_area(200, 100, 100, 50).is_none());
+    }
+
+    #[test]
+    fn load_image_with_limits_rejects_oversized_inputs() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("big.png");
+        RgbaImage::from_pixel(64, 32, image::Rgba([0, 0, 255, 255]))
+            .save(&path)
+            .expect("write input image");
+
+        let small = ImageLimits {
+            max_pixels: 1024,
+            ..Default::default()
+        };
+        let err = load_image_with_limits(&path, &small).unwrap_err();
+        assert!(matches!(err, ImageLoadError::TooLarge(ref msg) if msg.contains("64x32")));
+
+        let tiny = ImageLimits {
+            max_bytes: 16,
+            ..Default::default()
+        };
+        assert!(matches!(
+            load_image_with_limits(&path, &tiny),
+            Err(ImageLoadError::TooLarge(_))
+        ));
+
+        let img = load_image_with_limits(&path, &ImageLimits::default()).expect("within limits");
+        assert_eq!(img.dimensions(), (64, 32));
+    }
+
+    #[test]
+    fn image_limits_bound_the_area_not_each_side() {
+        let limits = ImageLimits::default();
+        // A 1440px-wide page captured at 2x, 20000 CSS px tall.
+        assert!(limits.check_dimensions(2880, 40_000, "page").is_ok());
+        assert!(matches!(
+            limits.check_dimensions(100_000, 100_000, "bomb"),
+            Err(ImageLoadError::TooLarge(ref msg)) if msg.contains("100000x100000")
+        ));
+    }
+
+    #[test]
+    fn decode_image_bytes_checks_header_before_decoding() {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgba8(40, 10)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Png)
+            .expect("encode png");
+        let limits = ImageLimits {
+            max_pixels: 200,
+            ..Default::default()
+        };
+        assert!(matches!(
+            decode_image_bytes(&bytes, &limits, "download"),
+            Err(ImageLoadError::TooLarge(_))
+        ));
+        let img = decode_image_bytes(&bytes, &ImageLimits::default(), "download").unwrap();
+        assert_eq!(img.dimensions(), (40, 10));
+        assert!(decode_image_bytes(b"not an image", &ImageLimits::default(), "download").is_err());
+    }
+
+    /// Serve one canned HTTP response per connection on a local port.
+    fn serve(status: &'static str, body: Vec<u8>) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+                let header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        format!("http://{addr}/shared/hero.png")
+    }
+
+    #[tokio::test]
+    async fn download_image_fetches_bitmap_and_enforces_limits() {
+        let mut png = Vec::new();
+        DynamicImage::new_rgba8(12, 6)
+            .write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)
+            .expect("encode png");
+        let url = serve("200 OK", png);
+
+        let img = download_image(&url, &ImageLimits::default()).await.unwrap();
+        assert_eq!(img.dimensions(), (12, 6));
+
+        let tiny = ImageLimits {
+            max_bytes: 8,
+            ..Default::default()
+        };
+        assert!(matches!(
+            download_image(&url, &tiny).await,
+            Err(ImageLoadError::TooLarge(_))
+        ));
+
+        let missing = serve("404 Not Found", Vec::new());
+        assert!(matches!(
+            download_image(&missing, &ImageLimits::default()).await,
+            Err(ImageLoadError::Http { status: 404, .. })
+        ));
+    }
+
+    #[test]
+    fn data_uri_payload_decodes_to_an_image() {
+        let mut png = Vec::new();
+        DynamicImage::new_rgba8(3, 2)
+            .write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)
+            .expect("encode png");
+        let uri = format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&png)
+        );
+        let bytes = decode_data_uri(&uri).unwrap();
+        let img = decode_image_bytes(&bytes, &ImageLimits::default(), "data URI").unwrap();
+        assert_eq!(img.dimensions(), (3, 2));
+        assert!(matches!(
+            decode_data_uri("data:image/png;base64,@@@"),
+            Err(ImageLoadError::InvalidData(_))
+        ));
+    }
+
     #[test]
     fn test_resize_to_match() {
         let img = DynamicImage::new_rgba8(200, 100);
@@ -134,8 +558,8 @@ mod tests {
         img.save(&input_path).expect("write input image");
 
         let view = image_to_normalized_view(
-            input_path.to_str().unwrap(),
-            output_path.to_str().unwrap(),
+            &input_path,
+            &output_path,
             ImageLoadOptions {
                 no_resize: true,
                 ..Default::default()
@@ -158,8 +582,8 @@ mod tests {
         img.save(&input_path).expect("write input image");
 
         let view = image_to_normalized_view(
-            input_path.to_str().unwrap(),
-            output_path.to_str().unwrap(),
+            &input_path,
+            &output_path,
             ImageLoadOptions {
                 no_resize: false,
                 target_width: Some(40),
@@ -175,4 +599,45 @@ mod tests {
         let saved = image::open(&output_path).expect("open saved image");
         assert_eq!(saved.dimensions(), (40, 20));
     }
+
+    #[cfg(windows)]
+    #[test]
+    fn image_to_normalized_view_accepts_verbatim_paths() {
+        let dir = TempDir::new().expect("tempdir");
+        // `canonicalize` yields `\\?\C:\...` verbatim paths on Windows.
+        let root = fs::canonicalize(dir.path()).expect("canonicalize tempdir");
+        let input_path = root.join("ref.png");
+        let output_path = root.join("out").join("ref.png");
+        RgbaImage::from_pixel(6, 4, image::Rgba([0, 0, 255, 255]))
+            .save(&input_path)
+            .expect("write input image");
+
+        let view = image_to_normalized_view(&input_path, &output_path, ImageLoadOptions::default())
+            .expect("normalize verbatim path");
+
+        assert_eq!(view.screenshot_path, output_path);
+        assert!(output_path.is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn image_to_normalized_view_accepts_non_utf8_paths() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = TempDir::new().expect("tempdir");
+        let subdir = dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+        let input_path = subdir.join(OsStr::from_bytes(b"ref-\xff.png"));
+        let output_path = subdir.join(OsStr::from_bytes(b"out-\xfe.png"));
+        fs::create_dir_all(&subdir).expect("create non-UTF8 dir");
+        RgbaImage::from_pixel(6, 4, image::Rgba([0, 0, 255, 255]))
+            .save(&input_path)
+            .expect("write input image");
+
+        let view = image_to_normalized_view(&input_path, &output_path, ImageLoadOptions::default())
+            .expect("normalize non-UTF8 path");
+
+        assert_eq!(view.screenshot_path, output_path);
+        assert_eq!(load_image(&output_path).unwrap().dimensions(), (6, 4));
+    }
 }
@@ -0,0 +1,652 @@
+//! Tracking issues in Jira or Linear for failing comparisons.
+//!
+//! A failing nightly run is easy to miss in CI logs, so `dpc open-issue` files
+//! the top findings and artifact links as a ticket. Each issue lists the
+//! fingerprints of its findings; a later run whose findings share a
+//! fingerprint with an open dpc issue (or whose title matches one) updates
+//! that issue instead of opening another, so a page that keeps failing keeps
+//! one ticket.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::accepted::{finding_ratings, Finding};
+use crate::error::{DpcError, Result};
+use crate::metrics::SeverityCalibration;
+use crate::output::CompareOutput;
+use crate::types::DiffSeverity;
+
+pub const DEFAULT_LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+/// Label put on every Jira issue dpc opens, and used to find them again.
+pub const JIRA_LABEL: &str = "dpc";
+/// Prefix of the line listing an issue's finding fingerprints.
+const FINGERPRINT_PREFIX: &str = "dpc fingerprints:";
+/// Findings listed in an issue; the rest are counted.
+const MAX_LISTED_FINDINGS: usize = 20;
+const PAGE_SIZE: usize = 50;
+
+/// One finding in a tracking issue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueFinding {
+    pub severity: DiffSeverity,
+    pub description: String,
+    pub fingerprint: Option<String>,
+}
+
+/// The content of a tracking issue for one failing comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueReport {
+    pub title: String,
+    pub similarity: f32,
+    pub threshold: f32,
+    pub reference: String,
+    pub implementation: String,
+    /// Findings at or above the minimum severity, most severe first.
+    pub findings: Vec<IssueFinding>,
+    /// `(label, location)` of the screenshots and diff image.
+    pub artifact_links: Vec<(String, String)>,
+}
+
+/// An issue opened or updated by [`IssuePublisher::publish`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedIssue {
+    /// Jira key (`WEB-42`) or Linear identifier (`WEB-42`).
+    pub key: String,
+    pub url: String,
+    pub created: bool,
+}
+
+impl IssueReport {
+    /// The report for `result`, or `None` when the comparison passed or has
+    /// no finding at `min_severity` or above. Artifact links point below
+    /// `artifacts_url` when given (e.g. the CI job's artifact page) and at the
+    /// local files otherwise.
+    pub fn from_result(
+        result: &CompareOutput,
+        calibration: &SeverityCalibration,
+        min_severity: DiffSeverity,
+        artifacts_url: Option<&str>,
+    ) -> Option<Self> {
+        if result.passed {
+            return None;
+        }
+        let mut findings: Vec<IssueFinding> = Finding::collect(&result.metrics)
+            .iter()
+            .zip(finding_ratings(&result.metrics, calibration))
+            .filter(|(_, (severity, _))| rank(*severity) >= rank(min_severity))
+            .map(|(finding, (severity, fingerprint))| IssueFinding {
+                severity,
+                description: finding.describe(),
+                fingerprint,
+            })
+            .collect();
+        if findings.is_empty() {
+            return None;
+        }
+        findings.sort_by_key(|finding| std::cmp::Reverse(rank(finding.severity)));
+
+        let mut artifact_links = Vec::new();
+        if let Some(artifacts) = &result.artifacts {
+            for (label, path) in [
+                ("Reference screenshot", &artifacts.ref_screenshot),
+                ("Implementation screenshot", &artifacts.impl_screenshot),
+                ("Diff heatmap", &artifacts.diff_image),
+            ] {
+                let Some(path) = path else { continue };
+                artifact_links.push((label.to_string(), artifact_location(path, artifacts_url)));
+            }
+        }
+
+        Some(Self {
+            title: format!("Design parity failure: {}", result.impl_resource.value),
+            similarity: result.similarity,
+            threshold: result.threshold,
+            reference: result.ref_resource.value.clone(),
+            implementation: result.impl_resource.value.clone(),
+            findings,
+            artifact_links,
+        })
+    }
+
+    /// Fingerprints of the listed findings.
+    pub fn fingerprints(&self) -> Vec<&str> {
+        self.findings
+            .iter()
+            .take(MAX_LISTED_FINDINGS)
+            .filter_map(|finding| finding.fingerprint.as_deref())
+            .collect()
+    }
+
+    /// Issue body in Markdown (Linear).
+    pub fn markdown(&self) -> String {
+        let mut md = format!(
+            "**{:.1}% similarity** (threshold {:.1}%)\n\nReference `{}` vs implementation `{}`\n\n",
+            self.similarity * 100.0,
+            self.threshold * 100.0,
+            self.reference,
+            self.implementation
+        );
+        md.push_str("### Top findings\n\n");
+        for finding in self.findings.iter().take(MAX_LISTED_FINDINGS) {
+            md.push_str(&format!(
+                "- **{}** {}\n",
+                severity_label(finding.severity),
+                finding.description
+            ));
+        }
+        self.push_remaining(&mut md);
+        if !self.artifact_links.is_empty() {
+            md.push_str("\n### Artifacts\n\n");
+            for (label, location) in &self.artifact_links {
+                md.push_str(&format!("- [{label}]({location})\n"));
+            }
+        }
+        self.push_fingerprints(&mut md);
+        md
+    }
+
+    /// Issue description in Jira wiki markup.
+    pub fn jira_wiki(&self) -> String {
+        let mut wiki = format!(
+            "*{:.1}% similarity* (threshold {:.1}%)\n\nReference {{{{{}}}}} vs implementation {{{{{}}}}}\n\n",
+            self.similarity * 100.0,
+            self.threshold * 100.0,
+            self.reference,
+            self.implementation
+        );
+        wiki.push_str("h3. Top findings\n\n");
+        for finding in self.findings.iter().take(MAX_LISTED_FINDINGS) {
+            wiki.push_str(&format!(
+                "* *{}* {}\n",
+                severity_label(finding.severity),
+                finding.description
+            ));
+        }
+        self.push_remaining(&mut wiki);
+        if !self.artifact_links.is_empty() {
+            wiki.push_str("\nh3. Artifacts\n\n");
+            for (label, location) in &self.artifact_links {
+                wiki.push_str(&format!("* [{label}|{location}]\n"));
+            }
+        }
+        self.push_fingerprints(&mut wiki);
+        wiki
+    }
+
+    fn push_remaining(&self, body: &mut String) {
+        let remaining = self.findings.len().saturating_sub(MAX_LISTED_FINDINGS);
+        if remaining > 0 {
+            body.push_str(&format!("\n…and {remaining} more finding(s).\n"));
+        }
+    }
+
+    fn push_fingerprints(&self, body: &mut String) {
+        body.push_str(&format!(
+            "\n{FINGERPRINT_PREFIX} {}\n",
+            self.fingerprints().join(" ")
+        ));
+    }
+
+    /// Whether an existing issue with `title` and `description` tracks the
+    /// same failure.
+    fn matches(&self, title: &str, description: &str) -> bool {
+        if title == self.title {
+            return true;
+        }
+        let existing: HashSet<&str> = description
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(FINGERPRINT_PREFIX))
+            .flat_map(str::split_whitespace)
+            .collect();
+        self.fingerprints()
+            .iter()
+            .any(|fingerprint| existing.contains(fingerprint))
+    }
+}
+
+fn rank(severity: DiffSeverity) -> u8 {
+    match severity {
+        DiffSeverity::Minor => 0,
+        DiffSeverity::Moderate => 1,
+        DiffSeverity::Major => 2,
+    }
+}
+
+fn severity_label(severity: DiffSeverity) -> &'static str {
+    match severity {
+        DiffSeverity::Major => "Major",
+        DiffSeverity::Moderate => "Moderate",
+        DiffSeverity::Minor => "Minor",
+    }
+}
+
+fn artifact_location(path: &Path, artifacts_url: Option<&str>) -> String {
+    match (artifacts_url, path.file_name()) {
+        (Some(base), Some(name)) => {
+            format!("{}/{}", base.trim_end_matches('/'), name.to_string_lossy())
+        }
+        _ => path.display().to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Jira {
+        base_url: Url,
+        email: Option<String>,
+        project: String,
+        issue_type: String,
+    },
+    Linear {
+        api_url: Url,
+        team: String,
+    },
+}
+
+/// Client for one Jira project or Linear team.
+#[derive(Debug, Clone)]
+pub struct IssuePublisher {
+    http: Client,
+    token: String,
+    backend: Backend,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JiraSearch {
+    #[serde(default)]
+    issues: Vec<JiraIssue>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JiraIssue {
+    key: String,
+    #[serde(default)]
+    fields: JiraFields,
+}
+
+#[derive(Default, Deserialize)]
+struct JiraFields {
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LinearIssue {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl IssuePublisher {
+    /// Publisher for the Jira project `project` at `base_url`
+    /// (`https://acme.atlassian.net`). Jira Cloud authenticates with `email`
+    /// and an API token; without `email` the token is sent as a bearer
+    /// personal access token (Jira Data Center).
+    pub fn jira(
+        base_url: &str,
+        email: Option<String>,
+        token: impl Into<String>,
+        project: &str,
+        issue_type: &str,
+    ) -> Result<Self> {
+        if project.is_empty() {
+            return Err(DpcError::Config("Jira project key is empty".to_string()));
+        }
+        Ok(Self {
+            http: http_client()?,
+            token: token.into(),
+            backend: Backend::Jira {
+                base_url: Url::parse(&format!("{}/", base_url.trim_end_matches('/')))?,
+                email,
+                project: project.to_string(),
+                issue_type: issue_type.to_string(),
+            },
+        })
+    }
+
+    /// Publisher for the Linear team with key `team` (`WEB`).
+    pub fn linear(api_key: impl Into<String>, team: &str, api_url: &str) -> Result<Self> {
+        if team.is_empty() {
+            return Err(DpcError::Config("Linear team key is empty".to_string()));
+        }
+        Ok(Self {
+            http: http_client()?,
+            token: api_key.into(),
+            backend: Backend::Linear {
+                api_url: Url::parse(api_url)?,
+                team: team.to_string(),
+            },
+        })
+    }
+
+    /// Update the open issue tracking the same failure as `report`, or open
+    /// a new one.
+    pub async fn publish(&self, report: &IssueReport) -> Result<PublishedIssue> {
+        match &self.backend {
+            Backend::Jira {
+                base_url,
+                email,
+                project,
+                issue_type,
+            } => {
+                self.publish_jira(report, base_url, email.as_deref(), project, issue_type)
+                    .await
+            }
+            Backend::Linear { api_url, team } => self.publish_linear(report, api_url, team).await,
+        }
+    }
+
+    async fn publish_jira(
+        &self,
+        report: &IssueReport,
+        base_url: &Url,
+        email: Option<&str>,
+        project: &str,
+        issue_type: &str,
+    ) -> Result<PublishedIssue> {
+        let auth = |request: RequestBuilder| match email {
+            Some(email) => request.basic_auth(email, Some(&self.token)),
+            None => request.bearer_auth(&self.token),
+        };
+        let endpoint = |path: &str| base_url.join(path).map_err(DpcError::InvalidUrl);
+        let jql = format!(
+            "project = \"{project}\" AND labels = \"{JIRA_LABEL}\" AND statusCategory != Done"
+        );
+
+        let mut page_token: Option<String> = None;
+        let existing = loop {
+            let mut url = endpoint("rest/api/2/search/jql")?;
+            url.query_pairs_mut()
+                .append_pair("jql", &jql)
+                .append_pair("fields", "summary,description")
+                .append_pair("maxResults", &PAGE_SIZE.to_string());
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("nextPageToken", token);
+            }
+            let page: JiraSearch = self.send("Jira", auth(self.http.get(url))).await?;
+            let found = page.issues.into_iter().find(|issue| {
+                report.matches(
+                    &issue.fields.summary,
+                    issue.fields.description.as_deref().unwrap_or_default(),
+                )
+            });
+            match page.next_page_token {
+                Some(token) if found.is_none() => page_token = Some(token),
+                _ => break found,
+            }
+        };
+
+        let description = report.jira_wiki();
+        let (key, created) = match existing {
+            Some(issue) => {
+                let url = endpoint(&format!("rest/api/2/issue/{}", issue.key))?;
+                let request = self.http.put(url).json(&json!({
+                    "fields": { "summary": report.title, "description": description }
+                }));
+                self.send_empty("Jira", auth(request)).await?;
+                (issue.key, false)
+            }
+            None => {
+                let url = endpoint("rest/api/2/issue")?;
+                let request = self.http.post(url).json(&json!({
+                    "fields": {
+                        "project": { "key": project },
+                        "issuetype": { "name": issue_type },
+                        "summary": report.title,
+                        "description": description,
+                        "labels": [JIRA_LABEL],
+                    }
+                }));
+                let issue: Value = self.send("Jira", auth(request)).await?;
+                let key = issue["key"].as_str().unwrap_or_default().to_string();
+                (key, true)
+            }
+        };
+        let url = endpoint(&format!("browse/{key}"))?.to_string();
+        Ok(PublishedIssue { key, url, created })
+    }
+
+    async fn publish_linear(
+        &self,
+        report: &IssueReport,
+        api_url: &Url,
+        team: &str,
+    ) -> Result<PublishedIssue> {
+        let mut after: Option<String> = None;
+        let existing = loop {
+            let data = self
+                .linear_query(
+                    api_url,
+                    "query($team: String!, $after: String, $first: Int!) {
+                        issues(first: $first, after: $after, filter: {
+                            team: { key: { eq: $team } },
+                            state: { type: { nin: [\"completed\", \"canceled\"] } },
+                            description: { contains: \"dpc fingerprints:\" }
+                        }) {
+                            nodes { id title description }
+                            pageInfo { hasNextPage endCursor }
+                        }
+                    }",
+                    json!({ "team": team, "after": after, "first": PAGE_SIZE }),
+                )
+                .await?;
+            let issues: Vec<LinearIssue> = serde_json::from_value(data["issues"]["nodes"].clone())?;
+            let found = issues.into_iter().find(|issue| {
+                report.matches(
+                    &issue.title,
+                    issue.description.as_deref().unwrap_or_default(),
+                )
+            });
+            let page = &data["issues"]["pageInfo"];
+            match page["endCursor"].as_str() {
+                Some(cursor) if found.is_none() && page["hasNextPage"] == true => {
+                    after = Some(cursor.to_string())
+                }
+                _ => break found,
+            }
+        };
+
+        let description = report.markdown();
+        let (data, created) = match existing {
+            Some(issue) => {
+                let data = self
+                    .linear_query(
+                        api_url,
+                        "mutation($id: String!, $title: String!, $description: String!) {
+                            issueUpdate(id: $id, input: { title: $title, description: $description }) {
+                                issue { identifier url }
+                            }
+                        }",
+                        json!({ "id": issue.id, "title": report.title, "description": description }),
+                    )
+                    .await?;
+                (data["issueUpdate"]["issue"].clone(), false)
+            }
+            None => {
+                let teams = self
+                    .linear_query(
+                        api_url,
+                        "query($team: String!) {
+                            teams(filter: { key: { eq: $team } }) { nodes { id } }
+                        }",
+                        json!({ "team": team }),
+                    )
+                    .await?;
+                let team_id = teams["teams"]["nodes"][0]["id"]
+                    .as_str()
+                    .ok_or_else(|| DpcError::Config(format!("Linear team '{team}' not found")))?;
+                let data = self
+                    .linear_query(
+                        api_url,
+                        "mutation($team: String!, $title: String!, $description: String!) {
+                            issueCreate(input: { teamId: $team, title: $title, description: $description }) {
+                                issue { identifier url }
+                            }
+                        }",
+                        json!({ "team": team_id, "title": report.title, "description": description }),
+                    )
+                    .await?;
+                (data["issueCreate"]["issue"].clone(), true)
+            }
+        };
+        Ok(PublishedIssue {
+            key: data["identifier"].as_str().unwrap_or_default().to_string(),
+            url: data["url"].as_str().unwrap_or_default().to_string(),
+            created,
+        })
+    }
+
+    /// Run a GraphQL operation against Linear and return its `data`.
+    async fn linear_query(&self, api_url: &Url, query: &str, variables: Value) -> Result<Value> {
+        let request = self
+            .http
+            .post(api_url.clone())
+            .header("authorization", &self.token)
+            .json(&json!({ "query": query, "variables": variables }));
+        let mut response: Value = self.send("Linear", request).await?;
+        if let Some(message) = response["errors"][0]["message"].as_str() {
+            return Err(DpcError::Config(format!(
+                "Linear API request failed: {message}"
+            )));
+        }
+        Ok(response["data"].take())
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        service: &str,
+        request: RequestBuilder,
+    ) -> Result<T> {
+        let body = self.send_empty(service, request).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Send `request` and return the response body, failing on an error
+    /// status.
+    async fn send_empty(&self, service: &str, request: RequestBuilder) -> Result<String> {
+        let response = request
+            .header("accept", "application/json")
+            .header("user-agent", "dpc")
+            .send()
+            .await?;
+        let status = response.status();
+        let url = response.url().clone();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| {
+                    let message = value
+                        .get("errorMessages")
+                        .and_then(|messages| messages.get(0))
+                        .or_else(|| value.get("errors")?.get(0)?.get("message"))
+                        .or_else(|| value.get("message"))?;
+                    message.as_str().map(str::to_owned)
+                })
+                .unwrap_or_else(|| body.trim().to_string());
+            return Err(DpcError::Config(format!(
+                "{service} API request to {url} failed ({status}): {message}"
+            )));
+        }
+        Ok(body)
+    }
+}
+
+fn http_client() -> Result<Client> {
+    crate::network::client_builder()
+        .build()
+        .map_err(DpcError::Network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(passed: bool) -> CompareOutput {
+        serde_json::from_value(json!({
+            "version": "1",
+            "ref": { "kind": "image", "value": "design.png" },
+            "impl": { "kind": "url", "value": "https://example.com/pricing" },
+            "viewport": { "width": 1440, "height": 900 },
+            "similarity": 0.9,
+            "threshold": 0.95,
+            "passed": passed,
+            "metrics": {
+                "content": {
+                    "score": 0.9,
+                    "missingText": ["Buy now"],
+                    "missingTextFingerprints": ["txt-1"],
+                    "extraText": ["Sale"],
+                    "extraTextFingerprints": ["txt-2"]
+                },
+                "pixel": {
+                    "score": 0.8,
+                    "diffRegions": [{
+                        "x": 0.1, "y": 0.1, "width": 0.2, "height": 0.2,
+                        "severity": "major", "reason": "pixel_change",
+                        "fingerprint": "px-1"
+                    }]
+                }
+            },
+            "artifacts": {
+                "directory": "/tmp/run",
+                "diffImage": "/tmp/run/diff.png"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn report_lists_findings_at_the_minimum_severity_and_matches_reruns() {
+        let calibration = SeverityCalibration::default();
+        assert_eq!(
+            IssueReport::from_result(&result(true), &calibration, DiffSeverity::Minor, None),
+            None
+        );
+
+        let report = IssueReport::from_result(
+            &result(false),
+            &calibration,
+            DiffSeverity::Moderate,
+            Some("https://ci.example.com/artifacts/"),
+        )
+        .unwrap();
+        assert_eq!(
+            report.title,
+            "Design parity failure: https://example.com/pricing"
+        );
+        // Extra text is minor by default and falls below the threshold.
+        assert_eq!(report.fingerprints(), ["px-1", "txt-1"]);
+        assert_eq!(
+            report.artifact_links,
+            [(
+                "Diff heatmap".to_string(),
+                "https://ci.example.com/artifacts/diff.png".to_string()
+            )]
+        );
+
+        let markdown = report.markdown();
+        assert!(markdown.contains("- **Major** pixel: changed region"));
+        assert!(markdown.contains("[Diff heatmap](https://ci.example.com/artifacts/diff.png)"));
+        assert!(markdown.ends_with("dpc fingerprints: px-1 txt-1\n"));
+        assert!(report
+            .jira_wiki()
+            .contains("* [Diff heatmap|https://ci.example.com/artifacts/diff.png]"));
+
+        assert!(report.matches("Older title", "…\ndpc fingerprints: txt-9 px-1\n"));
+        assert!(report.matches(&report.title, ""));
+        assert!(!report.matches("Older title", "dpc fingerprints: txt-9\n"));
+    }
+}
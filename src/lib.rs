@@ -6,13 +6,33 @@
 //!
 //! # Module Overview
 //!
+//! - [`accepted`] - Accepted findings suppressed in later comparisons
+//! - [`artifact_index`] - `index.json` listing the files of a kept artifacts directory
+//! - [`artifact_store`] - Local and S3-compatible storage for compare artifacts
 //! - [`browser`] - Headless browser automation for URL capture
+//! - [`device`] - Android/iOS simulator and desktop window screenshot capture
+//! - [`dom_selector`] - CSS selectors over captured DOM snapshots
 //! - [`figma`] - Figma API integration and design extraction
+//! - [`figma_comments`] - Major findings posted back to the Figma file as comments
+//! - [`figma_oauth`] - Figma OAuth login and keyring-backed token refresh
+//! - [`figma_styles`] - Published Figma styles as the reference's design tokens
+//! - [`figma_variables`] - Figma variables compared with the implementation's CSS custom properties
+//! - [`github`] - PR comments and check runs with the result of a comparison
+//! - [`issue_tracker`] - Jira and Linear tracking issues for failing comparisons
 //! - [`image_loader`] - Local image loading and processing
 //! - [`metrics`] - Parity metrics (pixel, layout, typography, color, content)
 //! - [`config`] - Configuration file support
+//! - [`crawl`] - Implementation page discovery for site-wide audits
+//! - [`result_diff`] - Differences between two compare results
+//! - [`sections`] - Named page sections scored on their own
+//! - [`summarizer`] - Narrative summaries of the findings from a language model
+//! - [`suppression`] - Finding fingerprints and the `.dpc-ignore.json` suppression file
 //! - [`types`] - Core data types and structures
+//! - [`video`] - Video frame inputs (`clip.mp4@00:05.2`)
+//! - [`view_bundle`] - Saved views replayed as `view:<dir>` inputs
 //! - [`output`] - JSON output schemas
+//! - [`overlay`] - `overlay.json` with the findings as rectangles for review front-ends
+//! - [`resource_guard`] - Memory/CPU guardrails that lower the analysis resolution under pressure
 //!
 //! # Example
 //!
@@ -33,33 +53,85 @@
 //! # }
 //! ```
 
+pub mod accepted;
+pub mod artifact_encoding;
+pub mod artifact_index;
+pub mod artifact_store;
 pub mod browser;
 pub mod config;
+pub mod crawl;
+pub mod device;
+pub mod dom_selector;
 pub mod error;
 #[path = "figma/mod.rs"]
 pub mod figma;
 pub mod figma_client;
+pub mod figma_comments;
+pub mod figma_oauth;
+pub mod figma_styles;
+pub mod figma_variables;
+pub mod github;
 pub mod image_alignment;
 pub mod image_loader;
+pub mod issue_tracker;
 pub mod metrics;
+pub mod network;
 pub mod output;
+pub mod overlay;
 pub mod resource;
+pub mod resource_guard;
+pub mod result_diff;
+pub mod sections;
+mod strings;
+pub mod summarizer;
+pub mod suppression;
 pub mod types;
+pub mod video;
+pub mod view_bundle;
 pub mod viewport;
 
+pub use accepted::{AcceptedFindings, Finding};
+pub use artifact_encoding::{
+    encode_compare_artifacts, read_json_snapshot, write_json_snapshot, ArtifactEncoding,
+    ArtifactImageFormat, ImageEncoding, SnapshotCompression,
+};
+pub use artifact_index::{
+    build_artifact_index, write_artifact_index, ArtifactEntry, ArtifactIndex, ArtifactType,
+    ARTIFACT_INDEX_FILE,
+};
+pub use artifact_store::{store_from_spec, ArtifactStore, LocalDirStore, S3Store};
 // Browser module re-exports
 pub use browser::{
-    url_to_normalized_view, BrowserManager, BrowserOptions, PageRenderResult, UrlToViewOptions,
+    url_to_normalized_view, BrowserManager, BrowserOptions, CaptureBackend, Emulation, Geolocation,
+    MockRoute, PageRenderResult, RequestRules, SessionStats, UrlToViewOptions, BROWSER_BACKEND_ENV,
     DEFAULT_NAVIGATION_TIMEOUT, DEFAULT_NETWORK_IDLE_TIMEOUT, DEFAULT_PROCESS_TIMEOUT,
 };
 pub use config::Config;
+pub use crawl::{crawl, match_route, CrawlOptions, RouteRule};
+pub use device::{
+    capture_device_screenshot, device_to_normalized_view, DeviceCaptureOptions, DeviceTarget,
+    WindowSelector,
+};
 pub use error::{DpcError, Result};
 pub use figma::{figma_to_normalized_view, FigmaClient, FigmaError, FigmaRenderOptions};
 pub use figma_client::{
     FigmaApiClient, FigmaAuth, FigmaFileResponse, FigmaImageFormat, FigmaImageResponse,
-    FigmaNodesResponse, ImageExportOptions,
+    FigmaNodesResponse, FigmaPublishedStyle, FigmaStylesResponse, FigmaVariablesResponse,
+    ImageExportOptions,
+};
+pub use figma_comments::{figma_comment_drafts, post_figma_comments, FigmaCommentDraft};
+pub use figma_styles::fetch_figma_styles;
+pub use figma_variables::{
+    compare_variables, fetch_figma_variables, DesignVariable, VariableValue,
+};
+pub use github::{
+    check_annotations, composite_image, markdown_summary, CheckAnnotation, GitHubPublisher,
 };
-pub use image_loader::{image_to_normalized_view, load_image, ImageLoadOptions};
+pub use image_loader::{
+    bytes_to_normalized_view, image_to_normalized_view, load_image,
+    remote_image_to_normalized_view, ImageLoadOptions,
+};
+pub use issue_tracker::{IssueFinding, IssuePublisher, IssueReport, PublishedIssue};
 // Metrics module re-exports
 pub use metrics::{
     // Semantic analysis
@@ -68,39 +140,105 @@ pub use metrics::{
     calculate_combined_score,
     // Concrete metric implementations (for custom configuration)
     cluster_diff_regions,
+    cluster_diff_regions_with_density,
     // Region clustering
     cluster_regions,
     cluster_regions_image_aware,
+    compare_stream,
     default_metrics,
+    default_metrics_v2,
+    element_color_drift,
     generate_top_issues,
+    generate_top_issues_with,
+    metric_score,
+    parse_css_color,
+    parse_css_gradient,
     run_metrics,
+    run_metrics_until,
+    run_metrics_v2,
+    ArtifactSink,
+    BudgetedScores,
     ClusteredRegion,
     ClusteringConfig,
     // Metric implementations
     ColorPaletteMetric,
+    CompareEvent,
+    CompareStreamOptions,
+    ContentMatchMode,
     ContentSimilarity,
+    DeltaEThresholds,
+    EdgeBoxDetector,
+    ElementColorDrift,
+    FormatNormalization,
     ImageAwareClusteringConfig,
+    ImageEncoder,
+    ImportanceModel,
     LayoutSimilarity,
     Metric,
+    MetricAdapter,
+    MetricContext,
+    MetricInterval,
     MetricKind,
     MetricResult,
+    MetricV2,
+    NearDuplicatePair,
+    NoiseSuppression,
+    PaletteColor,
+    PaletteReport,
+    PaletteSprawl,
+    PixelDiffSpace,
     PixelDiffThresholds,
     PixelSimilarity,
+    RegionWeights,
+    SaliencyEstimator,
+    SaliencyHeuristic,
+    SaliencyMap,
+    SaliencyModel,
+    SampleStats,
+    ScoreInterval,
     ScoreWeights,
     SemanticAnalyzer,
     SemanticAnalyzerConfig,
     SemanticDiff,
     SemanticDiffType,
+    SemanticSimilarity,
+    SeverityCalibration,
+    SeverityProfile,
+    SharedMetric,
+    SynonymTable,
+    TextDetector,
+    TransparencyMode,
+    TypeSpecimenRow,
+    TypographyReportRow,
     TypographySimilarity,
+    WeightedRegion,
+    UNSTABLE_STD_DEV,
 };
+pub use network::NetworkOptions;
 pub use output::{
-    CompareArtifacts, CompareOutput, DpcOutput, ErrorOutput, FindingSeverity, GenerateCodeOutput,
-    QualityFinding, QualityFindingType, QualityOutput, ResourceDescriptor, Summary,
-    DPC_OUTPUT_VERSION,
+    apply_coordinate_space, AuditOutput, AuditPage, CompareArtifacts, CompareOutput,
+    ComparePageDiagnostics, CoordinateFrame, CoordinateSpace, Degradation, DiffResultsOutput,
+    DimensionMismatch, DimensionMismatchPolicy, DownscaledScreenshot, DpcOutput, ErrorOutput,
+    FindingSeverity, FlowOutput, FlowScreen, GenerateCodeOutput, ImageSize, PostedFigmaComment,
+    QualityFinding, QualityFindingType, QualityOutput, ReferenceCandidate, ResourceDescriptor,
+    SectionScore, SkippedMetric, Summary, VariableDrift, VariableMismatch, DPC_OUTPUT_VERSION,
+};
+pub use overlay::{
+    build_overlay, write_overlay, Overlay, OverlayFeature, OverlayGeometry, OverlayImage,
+    OverlayProperties, OverlaySide, OVERLAY_FILE,
+};
+pub use resource::{normalize_node_id, parse_resource, FigmaInfo, ParsedResource};
+pub use resource_guard::{available_memory, downscale_view, ResourceLimits};
+pub use result_diff::{diff_results, ChangedFinding, MetricChange, MetricTrend, ResultDiff};
+pub use sections::{
+    failing_regions, load_sections, section_view, validate_sections, Section, SectionRegion,
+};
+pub use summarizer::{ApiSummarizer, Summarizer, SummaryRequest};
+pub use suppression::{
+    assign_fingerprints, SuppressedFinding, Suppression, SuppressionFile, DEFAULT_SUPPRESSION_FILE,
 };
-pub use resource::{parse_resource, FigmaInfo, ParsedResource};
 pub use types::{
-    ColorMetric, ContentMetric, LayoutMetric, MetricScores, NormalizedView, PixelMetric,
-    ResourceKind, TypographyMetric,
+    BrowserEngine, ColorMetric, ContentMetric, FailedRequest, LayoutMetric, MetricScores,
+    NormalizedView, PageDiagnostics, PixelMetric, ResourceKind, SemanticMetric, TypographyMetric,
 };
 pub use viewport::Viewport;
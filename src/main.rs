@@ -1,6 +1,8 @@
+mod cache;
 mod cli;
 mod commands;
 mod formatting;
+mod manifest;
 mod pipeline;
 mod progress;
 mod settings;
@@ -8,7 +10,14 @@ mod settings;
 use std::process::ExitCode;
 
 use cli::Commands;
-use commands::{run_compare, run_generate_code, run_quality};
+use commands::{
+    run_audit, run_compare, run_completions, run_diff_results, run_doctor, run_figma, run_flow,
+    run_generate_code, run_install_browser, run_man, run_open_issue, run_publish_github,
+    run_quality, run_review, run_schema, run_self_compare,
+};
+use dpc_lib::image_loader::{self, ImageLimits};
+use dpc_lib::network::{self, NetworkOptions};
+use dpc_lib::CrawlOptions;
 
 #[tokio::main]
 async fn main() -> ExitCode {
@@ -19,55 +28,183 @@ async fn run() -> ExitCode {
     let raw_args: Vec<String> = std::env::args().collect();
     let args = cli::parse();
 
+    let network = NetworkOptions {
+        proxy: args.proxy.clone(),
+        ca_bundle: args.ca_bundle.clone(),
+        ignore_tls_errors: args.ignore_tls_errors,
+    }
+    .with_env_defaults();
+    if let Err(err) = network::configure(network) {
+        return formatting::render_error(err, cli::OutputFormat::Json, None);
+    }
+
+    let mut image_limits = ImageLimits::from_env();
+    if let Some(megapixels) = args.max_image_megapixels {
+        image_limits.max_pixels = megapixels.saturating_mul(1_000_000);
+    }
+    if let Some(bytes) = args.max_image_bytes {
+        image_limits.max_bytes = bytes;
+    }
+    image_loader::configure(image_limits);
+
     match args.command {
-        Commands::Compare {
+        Commands::Compare(compare) => {
+            run_compare(&raw_args, args.config, args.verbose, compare).await
+        }
+        Commands::SelfCompare {
+            url,
+            baseline,
+            viewport,
+            threshold,
+            metrics,
+            ignore_selectors,
+            ignore_regions,
+            format,
+            output,
+            keep_artifacts,
+            artifacts_dir,
+            artifact_store,
+            cache_dir,
+            nav_timeout,
+            network_idle_timeout,
+            process_timeout,
+            init_scripts,
+            locale,
+            timezone,
+            geolocation,
+            grant_permissions,
+            reduced_motion,
+            forced_colors,
+        } => {
+            run_self_compare(
+                &raw_args,
+                args.config,
+                args.verbose,
+                url,
+                baseline,
+                viewport,
+                threshold,
+                metrics,
+                ignore_selectors,
+                ignore_regions,
+                format,
+                output,
+                keep_artifacts,
+                artifacts_dir,
+                artifact_store,
+                cache_dir,
+                nav_timeout,
+                network_idle_timeout,
+                process_timeout,
+                init_scripts,
+                locale,
+                timezone,
+                geolocation,
+                grant_permissions,
+                reduced_motion,
+                forced_colors,
+            )
+            .await
+        }
+        Commands::Flow {
             r#ref,
-            r#impl,
-            ref_type,
-            impl_type,
+            impls,
+            base_url,
             viewport,
             threshold,
             metrics,
             format,
             output,
             keep_artifacts,
-            ignore_selectors,
-            ignore_regions,
             artifacts_dir,
             nav_timeout,
             network_idle_timeout,
             process_timeout,
-            pixel_align,
-            pixel_align_max_shift,
-            pixel_align_downscale,
-            semantic_analysis,
-            context,
+            browser,
+            locale,
+            timezone,
+            geolocation,
+            grant_permissions,
+            reduced_motion,
+            forced_colors,
         } => {
-            run_compare(
+            run_flow(
                 &raw_args,
                 args.config,
                 args.verbose,
                 r#ref,
-                r#impl,
-                ref_type,
-                impl_type,
+                impls,
+                base_url,
+                viewport,
+                threshold,
+                metrics,
+                format,
+                output,
+                keep_artifacts,
+                artifacts_dir,
+                nav_timeout,
+                network_idle_timeout,
+                process_timeout,
+                browser,
+                locale,
+                timezone,
+                geolocation,
+                grant_permissions,
+                reduced_motion,
+                forced_colors,
+            )
+            .await
+        }
+        Commands::Audit {
+            crawl,
+            routes,
+            max_depth,
+            max_pages,
+            viewport,
+            threshold,
+            metrics,
+            format,
+            output,
+            keep_artifacts,
+            artifacts_dir,
+            nav_timeout,
+            network_idle_timeout,
+            process_timeout,
+            browser,
+            locale,
+            timezone,
+            geolocation,
+            grant_permissions,
+            reduced_motion,
+            forced_colors,
+        } => {
+            run_audit(
+                &raw_args,
+                args.config,
+                args.verbose,
+                crawl,
+                routes,
+                CrawlOptions {
+                    max_depth,
+                    max_pages,
+                },
                 viewport,
                 threshold,
                 metrics,
                 format,
                 output,
                 keep_artifacts,
-                ignore_selectors,
-                ignore_regions,
                 artifacts_dir,
                 nav_timeout,
                 network_idle_timeout,
                 process_timeout,
-                pixel_align,
-                pixel_align_max_shift,
-                pixel_align_downscale,
-                semantic_analysis,
-                context,
+                browser,
+                locale,
+                timezone,
+                geolocation,
+                grant_permissions,
+                reduced_motion,
+                forced_colors,
             )
             .await
         }
@@ -111,5 +248,65 @@ async fn run() -> ExitCode {
             )
             .await
         }
+        Commands::Figma { command } => run_figma(command).await,
+        Commands::Doctor { format } => run_doctor(format).await,
+        Commands::InstallBrowser { dir, browsers } => run_install_browser(dir, browsers).await,
+        Commands::Completions { shell } => run_completions(shell),
+        Commands::Man { out_dir } => run_man(out_dir),
+        Commands::Schema { output } => run_schema(output),
+        Commands::Review { dir } => run_review(dir),
+        Commands::DiffResults {
+            old,
+            new,
+            output,
+            format,
+            fail_on_regression,
+        } => run_diff_results(old, new, format, output, fail_on_regression),
+        Commands::PublishGithub {
+            result,
+            repo,
+            pr,
+            check_run,
+            sha,
+            check_name,
+            annotation_path,
+            artifact_store,
+            api_url,
+        } => {
+            run_publish_github(
+                args.config,
+                result,
+                repo,
+                pr,
+                check_run,
+                sha,
+                check_name,
+                annotation_path,
+                artifact_store,
+                api_url,
+            )
+            .await
+        }
+        Commands::OpenIssue {
+            result,
+            tracker,
+            project,
+            min_severity,
+            artifacts_url,
+            issue_type,
+            tracker_url,
+        } => {
+            run_open_issue(
+                args.config,
+                result,
+                tracker,
+                project,
+                min_severity,
+                artifacts_url,
+                issue_type,
+                tracker_url,
+            )
+            .await
+        }
     }
 }
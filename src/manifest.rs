@@ -0,0 +1,320 @@
+//! `run.json`: everything that went into a compare result.
+//!
+//! The manifest records the tool version, resolved options, content hashes of
+//! the inputs and a little environment info, so a result can be audited and a
+//! "passed yesterday, fails today" diff narrowed down to what actually changed.
+//! It deliberately contains no timestamps: identical inputs and options produce
+//! a byte-identical manifest.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use dpc_lib::output::DPC_OUTPUT_VERSION;
+use dpc_lib::types::{BrowserEngine, MetricScores, ResourceKind, Viewport};
+use dpc_lib::{CompareArtifacts, DpcError, MetricKind, NormalizedView, ParsedResource};
+
+use crate::settings::ResolvedCompareSettings;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunManifest {
+    pub dpc_version: &'static str,
+    pub output_version: &'static str,
+    pub command: String,
+    /// Command-line arguments after the binary name, as given.
+    pub args: Vec<String>,
+    pub options: ManifestOptions,
+    pub inputs: ManifestInputs,
+    pub environment: ManifestEnvironment,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+    pub viewport: Viewport,
+    pub threshold: f64,
+    /// Metrics that produced a score in this run.
+    pub metrics: Vec<MetricKind>,
+    pub weights: ManifestWeights,
+    pub pixel_alignment: ManifestPixelAlignment,
+    pub nav_timeout_secs: u64,
+    pub network_idle_timeout_secs: u64,
+    pub process_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestWeights {
+    pub pixel: f32,
+    pub layout: f32,
+    pub typography: f32,
+    pub color: f32,
+    pub content: f32,
+    pub semantic: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPixelAlignment {
+    pub enabled: bool,
+    pub max_shift: u32,
+    pub downscale_max_dim: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestInputs {
+    #[serde(rename = "ref")]
+    pub reference: ManifestInput,
+    #[serde(rename = "impl")]
+    pub implementation: ManifestInput,
+}
+
+/// One side of the comparison, identified by content rather than location.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestInput {
+    pub kind: ResourceKind,
+    pub value: String,
+    /// SHA-256 of the local source file (images, saved Figma JSON).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_sha256: Option<String>,
+    /// SHA-256 of the normalized screenshot the metrics ran on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot_sha256: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// URL the page ended up on after redirects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// Browser engine that rendered the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserEngine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figma_file_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figma_node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figma_version: Option<String>,
+    /// Scale the Figma frame was exported at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figma_export_scale: Option<f32>,
+}
+
+impl ManifestInput {
+    pub fn new(resource: &ParsedResource, view: &NormalizedView) -> Self {
+        let source = match resource.kind {
+            ResourceKind::Image => resource.local_image_path().map(Path::to_path_buf),
+            ResourceKind::Figma => resource
+                .figma_info
+                .as_ref()
+                .and_then(|info| info.json_path.clone()),
+            ResourceKind::View => resource
+                .view_bundle_dir()
+                .map(|dir| dir.join(dpc_lib::view_bundle::VIEW_FILE)),
+            ResourceKind::Url | ResourceKind::Device | ResourceKind::Window => None,
+        };
+        Self {
+            kind: resource.kind,
+            value: resource.display_value(),
+            source_sha256: source.as_deref().and_then(file_sha256),
+            screenshot_sha256: file_sha256(&view.screenshot_path),
+            width: view.width,
+            height: view.height,
+            final_url: view.dom.as_ref().and_then(|dom| dom.url.clone()),
+            browser: view.dom.as_ref().and_then(|dom| dom.browser),
+            figma_file_key: view.figma_tree.as_ref().map(|tree| tree.file_key.clone()),
+            figma_node_id: view.figma_tree.as_ref().map(|tree| tree.node_id.clone()),
+            figma_version: view
+                .figma_tree
+                .as_ref()
+                .and_then(|tree| tree.version.clone()),
+            figma_export_scale: view.figma_tree.as_ref().and_then(|tree| tree.export_scale),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEnvironment {
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// Whether a `CI` environment variable was set.
+    pub ci: bool,
+    /// `DPC_MOCK_*` variables that replaced real rendering, sorted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mock_renderers: Vec<String>,
+}
+
+impl ManifestEnvironment {
+    pub fn detect() -> Self {
+        let mut mock_renderers: Vec<String> = std::env::vars()
+            .map(|(key, _)| key)
+            .filter(|key| key.starts_with("DPC_MOCK_"))
+            .collect();
+        mock_renderers.sort();
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            ci: std::env::var_os("CI").is_some(),
+            mock_renderers,
+        }
+    }
+}
+
+impl RunManifest {
+    pub fn new(
+        command: &str,
+        raw_args: &[String],
+        config: Option<&Path>,
+        resolved: &ResolvedCompareSettings,
+        scores: &MetricScores,
+        inputs: ManifestInputs,
+    ) -> Self {
+        let weights = &resolved.weights;
+        let alignment = &resolved.pixel_alignment;
+        Self {
+            dpc_version: env!("CARGO_PKG_VERSION"),
+            output_version: DPC_OUTPUT_VERSION,
+            command: command.to_string(),
+            args: raw_args.iter().skip(1).cloned().collect(),
+            options: ManifestOptions {
+                config: config.map(Path::to_path_buf),
+                viewport: resolved.viewport,
+                threshold: resolved.threshold,
+                metrics: scored_metrics(scores),
+                weights: ManifestWeights {
+                    pixel: weights.pixel,
+                    layout: weights.layout,
+                    typography: weights.typography,
+                    color: weights.color,
+                    content: weights.content,
+                    semantic: weights.semantic,
+                },
+                pixel_alignment: ManifestPixelAlignment {
+                    enabled: alignment.enabled,
+                    max_shift: alignment.max_shift,
+                    downscale_max_dim: alignment.downscale_max_dim,
+                },
+                nav_timeout_secs: resolved.nav_timeout,
+                network_idle_timeout_secs: resolved.network_idle_timeout,
+                process_timeout_secs: resolved.process_timeout,
+            },
+            inputs,
+            environment: ManifestEnvironment::detect(),
+        }
+    }
+}
+
+/// Write `run.json` next to the other artifacts when they are kept.
+pub fn persist_run_manifest(
+    artifacts: &mut CompareArtifacts,
+    manifest: &RunManifest,
+) -> Result<(), DpcError> {
+    if !artifacts.kept {
+        return Ok(());
+    }
+    let path = artifacts.directory.join("run.json");
+    let json = serde_json::to_string_pretty(manifest)?;
+    std::fs::write(&path, json)?;
+    artifacts.run_manifest = Some(path);
+    Ok(())
+}
+
+fn scored_metrics(scores: &MetricScores) -> Vec<MetricKind> {
+    MetricKind::all()
+        .into_iter()
+        .filter(|kind| match kind {
+            MetricKind::Pixel => scores.pixel.is_some(),
+            MetricKind::Layout => scores.layout.is_some(),
+            MetricKind::Typography => scores.typography.is_some(),
+            MetricKind::Color => scores.color.is_some(),
+            MetricKind::Content => scores.content.is_some(),
+            MetricKind::Semantic => scores.semantic.is_some(),
+        })
+        .collect()
+}
+
+fn file_sha256(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(hex::encode(Sha256::digest(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpc_lib::image_alignment::ImageAlignmentOptions;
+    use dpc_lib::ScoreWeights;
+
+    fn settings() -> ResolvedCompareSettings {
+        ResolvedCompareSettings {
+            viewport: Viewport::new(800, 600),
+            threshold: 0.9,
+            nav_timeout: 30,
+            network_idle_timeout: 10,
+            process_timeout: 45,
+            weights: ScoreWeights::default(),
+            pixel_alignment: ImageAlignmentOptions::default(),
+        }
+    }
+
+    #[test]
+    fn manifest_hashes_inputs_and_is_deterministic() {
+        let dir = tempfile::tempdir().unwrap();
+        let shot = dir.path().join("ref.png");
+        std::fs::write(&shot, b"abc").unwrap();
+        let resource = ParsedResource {
+            kind: ResourceKind::Image,
+            value: shot.display().to_string(),
+            figma_info: None,
+        };
+        let view = NormalizedView {
+            kind: ResourceKind::Image,
+            screenshot_path: shot.clone(),
+            width: 800,
+            height: 600,
+            dom: None,
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        };
+        let build = || {
+            RunManifest::new(
+                "compare",
+                &["dpc".to_string(), "compare".to_string()],
+                None,
+                &settings(),
+                &MetricScores {
+                    pixel: None,
+                    layout: None,
+                    typography: None,
+                    color: None,
+                    content: None,
+                    semantic: None,
+                },
+                ManifestInputs {
+                    reference: ManifestInput::new(&resource, &view),
+                    implementation: ManifestInput::new(&resource, &view),
+                },
+            )
+        };
+
+        let manifest = build();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(
+            manifest.inputs.reference.source_sha256.as_deref(),
+            Some(abc)
+        );
+        assert_eq!(
+            manifest.inputs.reference.screenshot_sha256.as_deref(),
+            Some(abc)
+        );
+        assert_eq!(manifest.args, vec!["compare".to_string()]);
+        assert_eq!(
+            serde_json::to_string(&manifest).unwrap(),
+            serde_json::to_string(&build()).unwrap()
+        );
+    }
+}
@@ -64,12 +64,15 @@ pub struct ClusteredRegion {
     pub region_count: usize,
     /// Average diff intensity (0.0-1.0) across the cluster
     pub intensity: f32,
+    /// Largest single-pixel diff (0.0-1.0) in the cluster, when known
+    pub max_intensity: Option<f32>,
 }
 
 impl ClusteredRegion {
     /// Convert back to a PixelDiffRegion for output compatibility.
     pub fn to_pixel_diff_region(&self) -> PixelDiffRegion {
         PixelDiffRegion {
+            fingerprint: None,
             x: self.x,
             y: self.y,
             width: self.width,
@@ -77,6 +80,9 @@ impl ClusteredRegion {
             severity: self.severity,
             reason: PixelDiffReason::PixelChange,
             intensity: Some(self.intensity),
+            max_intensity: self.max_intensity,
+            pixel_bounds: None,
+            css_bounds: None,
         }
     }
 }
@@ -173,51 +179,10 @@ pub fn cluster_regions(
     for indices in groups.values() {
         if indices.len() < config.min_cluster_size {
             // Keep as individual regions if below threshold
-            for &i in indices {
-                let intensity = regions[i]
-                    .intensity
-                    .unwrap_or_else(|| severity_to_intensity(regions[i].severity));
-                clustered.push(ClusteredRegion {
-                    x: regions[i].x,
-                    y: regions[i].y,
-                    width: regions[i].width,
-                    height: regions[i].height,
-                    severity: regions[i].severity,
-                    region_count: 1,
-                    intensity,
-                });
-            }
+            clustered.extend(indices.iter().map(|&i| merge_group(regions, &[i])));
             continue;
         }
-
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        let mut max_x = f32::MIN;
-        let mut max_y = f32::MIN;
-        let mut max_severity = DiffSeverity::Minor;
-        let mut intensity_sum = 0.0f32;
-
-        for &i in indices {
-            let r = &regions[i];
-            min_x = min_x.min(r.x);
-            min_y = min_y.min(r.y);
-            max_x = max_x.max(r.x + r.width);
-            max_y = max_y.max(r.y + r.height);
-            max_severity = max_severity.max(r.severity);
-            intensity_sum += r
-                .intensity
-                .unwrap_or_else(|| severity_to_intensity(r.severity));
-        }
-
-        clustered.push(ClusteredRegion {
-            x: min_x,
-            y: min_y,
-            width: max_x - min_x,
-            height: max_y - min_y,
-            severity: max_severity,
-            region_count: indices.len(),
-            intensity: intensity_sum / indices.len() as f32,
-        });
+        clustered.push(merge_group(regions, indices));
     }
 
     // Sort by severity (descending) then by area (descending)
@@ -234,6 +199,49 @@ pub fn cluster_regions(
     clustered
 }
 
+/// Bounding box of `regions[indices]` with their highest severity, their
+/// area-weighted average intensity and their highest single-pixel intensity.
+fn merge_group(regions: &[PixelDiffRegion], indices: &[usize]) -> ClusteredRegion {
+    let mut min_x = f32::MAX;
+    let mut min_y = f32::MAX;
+    let mut max_x = f32::MIN;
+    let mut max_y = f32::MIN;
+    let mut max_severity = DiffSeverity::Minor;
+    let mut intensity_sum = 0.0f32;
+    let mut area_sum = 0.0f32;
+    let mut max_intensity: Option<f32> = None;
+
+    for &i in indices {
+        let r = &regions[i];
+        min_x = min_x.min(r.x);
+        min_y = min_y.min(r.y);
+        max_x = max_x.max(r.x + r.width);
+        max_y = max_y.max(r.y + r.height);
+        max_severity = max_severity.max(r.severity);
+        let intensity = r
+            .intensity
+            .unwrap_or_else(|| severity_to_intensity(r.severity));
+        // Refined regions differ in size; a zero-area region still counts once.
+        let area = (r.width * r.height).max(f32::MIN_POSITIVE);
+        intensity_sum += intensity * area;
+        area_sum += area;
+        if let Some(peak) = r.max_intensity {
+            max_intensity = Some(max_intensity.map_or(peak, |m: f32| m.max(peak)));
+        }
+    }
+
+    ClusteredRegion {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+        severity: max_severity,
+        region_count: indices.len(),
+        intensity: intensity_sum / area_sum,
+        max_intensity,
+    }
+}
+
 fn severity_to_intensity(severity: DiffSeverity) -> f32 {
     match severity {
         DiffSeverity::Minor => 0.33,
@@ -339,37 +347,10 @@ pub fn cluster_regions_image_aware(
     }
 
     // Merge each group into a clustered region
-    let mut clustered = Vec::new();
-    for indices in groups.values() {
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        let mut max_x = f32::MIN;
-        let mut max_y = f32::MIN;
-        let mut max_severity = DiffSeverity::Minor;
-        let mut intensity_sum = 0.0f32;
-
-        for &i in indices {
-            let r = &regions[i];
-            min_x = min_x.min(r.x);
-            min_y = min_y.min(r.y);
-            max_x = max_x.max(r.x + r.width);
-            max_y = max_y.max(r.y + r.height);
-            max_severity = max_severity.max(r.severity);
-            intensity_sum += r
-                .intensity
-                .unwrap_or_else(|| severity_to_intensity(r.severity));
-        }
-
-        clustered.push(ClusteredRegion {
-            x: min_x,
-            y: min_y,
-            width: max_x - min_x,
-            height: max_y - min_y,
-            severity: max_severity,
-            region_count: indices.len(),
-            intensity: intensity_sum / indices.len() as f32,
-        });
-    }
+    let mut clustered: Vec<ClusteredRegion> = groups
+        .values()
+        .map(|indices| merge_group(regions, indices))
+        .collect();
 
     // Sort by severity (descending) then by area (descending)
     clustered.sort_by(|a, b| {
@@ -517,6 +498,7 @@ mod tests {
 
     fn make_region(x: f32, y: f32, w: f32, h: f32, severity: DiffSeverity) -> PixelDiffRegion {
         PixelDiffRegion {
+            fingerprint: None,
             x,
             y,
             width: w,
@@ -524,6 +506,9 @@ mod tests {
             severity,
             reason: PixelDiffReason::PixelChange,
             intensity: None,
+            max_intensity: None,
+            pixel_bounds: None,
+            css_bounds: None,
         }
     }
 
@@ -595,6 +580,7 @@ mod tests {
         // Two adjacent regions, but on different colored backgrounds
         let regions = vec![
             PixelDiffRegion {
+                fingerprint: None,
                 x: 0.3,
                 y: 0.3,
                 width: 0.2,
@@ -602,8 +588,12 @@ mod tests {
                 severity: DiffSeverity::Major,
                 reason: PixelDiffReason::PixelChange,
                 intensity: Some(0.4),
+                max_intensity: None,
+                pixel_bounds: None,
+                css_bounds: None,
             },
             PixelDiffRegion {
+                fingerprint: None,
                 x: 0.65,
                 y: 0.3,
                 width: 0.2,
@@ -611,6 +601,9 @@ mod tests {
                 severity: DiffSeverity::Major,
                 reason: PixelDiffReason::PixelChange,
                 intensity: Some(0.4),
+                max_intensity: None,
+                pixel_bounds: None,
+                css_bounds: None,
             },
         ];
 
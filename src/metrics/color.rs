@@ -1,15 +1,37 @@
 use crate::error::DpcError;
-use crate::types::{ColorDiff, ColorDiffKind, ColorMetric, NormalizedView};
+use crate::types::{ColorDiff, ColorDiffKind, ColorMetric, FigmaStyleKind, NormalizedView};
 use crate::Result;
 use image::{DynamicImage, GenericImageView};
-use palette::{convert::FromColorUnclamped, Lab, Srgb};
+use palette::{color_difference::Ciede2000, convert::FromColorUnclamped, Lab, Srgb};
 
+use super::gradient::{compare_gradients, parse_css_color};
+use super::transparency::TransparencyMode;
 use super::{Metric, MetricKind, MetricResult};
 
+/// Seed for k-means++ center selection, fixed so palettes are reproducible.
+const KMEANS_SEED: u64 = 0x5EED_C010_0B5E_55ED;
+const KMEANS_MAX_ITERATIONS: usize = 20;
+/// Stop iterating once no center moves further than this (Delta E).
+const KMEANS_TOLERANCE: f32 = 0.1;
+/// Delta E 2000 at which a palette color counts as not matched at all.
+pub(super) const PALETTE_MATCH_MAX_DELTA_E: f32 = 25.0;
+/// Share of the color score given to matched elements' gradient fills, when
+/// either view has any.
+const GRADIENT_SCORE_WEIGHT: f32 = 0.3;
+
 #[derive(Debug, Clone, Copy)]
 pub struct ColorPaletteMetric {
     pub clusters: usize,
     pub sample_stride: u32,
+    /// How transparent pixels are sampled.
+    pub transparency_mode: TransparencyMode,
+    /// Drop the dominant cluster when it is near-white or near-black, so the
+    /// page background does not become the primary palette color.
+    pub exclude_background: bool,
+    /// How much samples that stand out from the image's mean color count
+    /// over the rest (frequency-tuned saliency). `0.0` weights all samples
+    /// equally.
+    pub saliency_weight: f32,
 }
 
 impl Default for ColorPaletteMetric {
@@ -17,6 +39,9 @@ impl Default for ColorPaletteMetric {
         Self {
             clusters: 5,
             sample_stride: 4,
+            transparency_mode: TransparencyMode::default(),
+            exclude_background: false,
+            saliency_weight: 1.0,
         }
     }
 }
@@ -27,13 +52,34 @@ impl ColorPaletteMetric {
         reference: &NormalizedView,
         implementation: &NormalizedView,
     ) -> Result<ColorMetric> {
-        let ref_img = image::open(&reference.screenshot_path).map_err(DpcError::from)?;
-        let impl_img = image::open(&implementation.screenshot_path).map_err(DpcError::from)?;
-
-        let ref_palette = dominant_palette(&ref_img, self.clusters, self.sample_stride);
-        let impl_palette = dominant_palette(&impl_img, self.clusters, self.sample_stride);
+        let mode = self.transparency_mode;
+        let ref_img = mode.prepare(content_only(
+            reference,
+            image::open(&reference.screenshot_path).map_err(DpcError::from)?,
+        ));
+        let impl_img = mode.prepare(content_only(
+            implementation,
+            image::open(&implementation.screenshot_path).map_err(DpcError::from)?,
+        ));
+
+        // Published fill styles are the reference's exact colors; only
+        // without them is its palette estimated from the screenshot.
+        let styled = style_palette(reference);
+        let ref_palette = if styled.is_empty() {
+            self.dominant_palette(&ref_img)
+        } else {
+            styled
+                .iter()
+                .map(|(lab, weight, _)| (*lab, *weight))
+                .collect()
+        };
+        let impl_palette = self.dominant_palette(&impl_img);
 
         let mut diffs = palette_diffs(&ref_palette, &impl_palette, 3);
+        // Both list the heaviest color first.
+        for (diff, (_, _, name)) in diffs.iter_mut().zip(&styled) {
+            diff.style = Some(name.clone());
+        }
         let mut score = palette_similarity(&ref_palette, &impl_palette);
         let needs_fallback = diffs.is_empty()
             || diffs
@@ -41,17 +87,27 @@ impl ColorPaletteMetric {
                 .all(|d| d.ref_color == d.impl_color && d.delta_e.unwrap_or(0.0) <= 1.0);
 
         if needs_fallback {
-            let avg_ref = average_rgb(&ref_img);
-            let avg_impl = average_rgb(&impl_img);
-            let delta = rgb_distance(&avg_ref, &avg_impl);
+            let avg_ref = average_rgb(&ref_img, mode);
+            let avg_impl = average_rgb(&impl_img, mode);
+            let delta = delta_e_2000(rgb_to_lab(avg_ref), rgb_to_lab(avg_impl));
             diffs.push(ColorDiff {
+                fingerprint: None,
                 kind: ColorDiffKind::PrimaryColorShift,
                 ref_color: format!("#{:02X}{:02X}{:02X}", avg_ref[0], avg_ref[1], avg_ref[2]),
                 impl_color: format!("#{:02X}{:02X}{:02X}", avg_impl[0], avg_impl[1], avg_impl[2]),
                 delta_e: Some(delta),
+                element_id: None,
+                angle_delta: None,
+                style: None,
             });
         }
 
+        let gradients = compare_gradients(reference, implementation);
+        diffs.extend(gradients.diffs);
+        if let Some(gradient_score) = gradients.similarity {
+            score = score * (1.0 - GRADIENT_SCORE_WEIGHT) + gradient_score * GRADIENT_SCORE_WEIGHT;
+        }
+
         let has_meaningful_diff = diffs
             .iter()
             .any(|d| d.ref_color != d.impl_color || d.delta_e.unwrap_or(0.0) > 1.0);
@@ -61,47 +117,200 @@ impl ColorPaletteMetric {
 
         Ok(ColorMetric { score, diffs })
     }
+
+    fn dominant_palette(&self, img: &DynamicImage) -> Vec<(Lab, f32)> {
+        let mut samples = sample_pixels(img, self.sample_stride, self.transparency_mode);
+        if samples.is_empty() {
+            return Vec::new();
+        }
+        apply_saliency(&mut samples, self.saliency_weight);
+
+        let k = self.clusters.max(1).min(samples.len());
+        let mut palette = kmeans(&samples, k, KMEANS_MAX_ITERATIONS);
+        if self.exclude_background {
+            exclude_background(&mut palette);
+        }
+        palette
+    }
 }
 
-fn dominant_palette(img: &DynamicImage, clusters: usize, stride: u32) -> Vec<(Lab, f32)> {
-    let samples = sample_pixels(img, stride);
-    if samples.is_empty() {
+/// The published fill styles used by the reference's Figma nodes, weighted
+/// by the area of the nodes using them and heaviest first; empty when the
+/// snapshot has no resolved styles.
+fn style_palette(view: &NormalizedView) -> Vec<(Lab, f32, String)> {
+    let Some(figma) = &view.figma_tree else {
+        return Vec::new();
+    };
+    let mut palette: Vec<(Lab, f32, String)> = Vec::new();
+    for node in &figma.nodes {
+        let Some(style) = figma
+            .style_of(node, "fill")
+            .filter(|style| style.kind == FigmaStyleKind::Fill)
+        else {
+            continue;
+        };
+        let Some(rgba) = style.color.as_deref().and_then(parse_css_color) else {
+            continue;
+        };
+        let area = (node.bounding_box.width * node.bounding_box.height).max(0.0);
+        match palette.iter_mut().find(|(_, _, name)| *name == style.name) {
+            Some((_, weight, _)) => *weight += area,
+            None => palette.push((
+                srgb_to_lab([rgba[0], rgba[1], rgba[2]]),
+                area,
+                style.name.clone(),
+            )),
+        }
+    }
+    let total: f32 = palette.iter().map(|(_, weight, _)| weight).sum();
+    if total <= 0.0 {
         return Vec::new();
     }
+    for (_, weight, _) in &mut palette {
+        *weight /= total;
+    }
+    palette.sort_by(|a, b| b.1.total_cmp(&a.1));
+    palette
+}
 
-    let k = clusters.max(1).min(samples.len());
-    kmeans(&samples, k, 8)
+/// Drop letterbox padding so the bars do not register as a palette color.
+fn content_only(view: &NormalizedView, img: DynamicImage) -> DynamicImage {
+    match view.content_rect(img.width(), img.height()) {
+        Some((x, y, w, h)) => img.crop_imm(x, y, w, h),
+        None => img,
+    }
 }
 
-fn sample_pixels(img: &DynamicImage, stride: u32) -> Vec<(Lab, f32)> {
+fn sample_pixels(img: &DynamicImage, stride: u32, mode: TransparencyMode) -> Vec<(Lab, f32)> {
     let (w, h) = img.dimensions();
     let mut samples = Vec::new();
     let step = stride.max(1);
 
     for y in (0..h).step_by(step as usize) {
         for x in (0..w).step_by(step as usize) {
-            let pixel = img.get_pixel(x, y).0;
-            let srgb = Srgb::new(
-                pixel[0] as f32 / 255.0,
-                pixel[1] as f32 / 255.0,
-                pixel[2] as f32 / 255.0,
-            );
-            let lab: Lab = Lab::from_color_unclamped(srgb);
-            samples.push((lab, 1.0));
+            let pixel = img.get_pixel(x, y);
+            if mode.excludes(&pixel) {
+                continue;
+            }
+            let pixel = pixel.0;
+            samples.push((rgb_to_lab([pixel[0], pixel[1], pixel[2]]), 1.0));
         }
     }
 
     samples
 }
 
-fn kmeans(samples: &[(Lab, f32)], k: usize, iterations: usize) -> Vec<(Lab, f32)> {
-    let mut centers: Vec<Lab> = Vec::with_capacity(k);
-    let mut weights = Vec::with_capacity(k);
+/// Scale each sample's weight by its distance from the mean color, after
+/// Achanta et al.'s frequency-tuned saliency: large flat areas (usually the
+/// background) weigh less than the accents that stand out from them.
+fn apply_saliency(samples: &mut [(Lab, f32)], strength: f32) {
+    if strength <= 0.0 || samples.is_empty() {
+        return;
+    }
+    let n = samples.len() as f32;
+    let (l, a, b) = samples.iter().fold((0.0, 0.0, 0.0), |acc, (lab, _)| {
+        (acc.0 + lab.l, acc.1 + lab.a, acc.2 + lab.b)
+    });
+    let mean = Lab::new(l / n, a / n, b / n);
+    for (lab, weight) in samples.iter_mut() {
+        // Delta E 100 spans roughly black to white.
+        let saliency = (lab_distance2(*lab, mean).sqrt() / 100.0).min(1.0);
+        *weight *= 1.0 + strength * saliency;
+    }
+}
+
+/// Remove the heaviest cluster if it is a near-white or near-black neutral,
+/// renormalizing the remaining weights. Single-cluster palettes are kept.
+fn exclude_background(palette: &mut Vec<(Lab, f32)>) {
+    if palette.len() < 2 {
+        return;
+    }
+    let Some((idx, (lab, _))) = palette.iter().enumerate().max_by(|a, b| {
+        a.1 .1
+            .partial_cmp(&b.1 .1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }) else {
+        return;
+    };
+    let chroma = (lab.a * lab.a + lab.b * lab.b).sqrt();
+    if chroma > 10.0 || (10.0..=90.0).contains(&lab.l) {
+        return;
+    }
+    palette.remove(idx);
+    let total: f32 = palette
+        .iter()
+        .map(|(_, w)| *w)
+        .sum::<f32>()
+        .max(f32::EPSILON);
+    for (_, w) in palette.iter_mut() {
+        *w /= total;
+    }
+}
 
-    let step = (samples.len() / k).max(1);
-    for i in 0..k {
-        centers.push(samples[i * step % samples.len()].0);
+/// SplitMix64, enough randomness for seeding and fully deterministic.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
     }
+}
+
+/// k-means++ seeding: each new center is drawn with probability proportional
+/// to its weighted squared distance from the nearest chosen center. Stops
+/// early when every remaining sample coincides with a center.
+fn kmeans_plus_plus(samples: &[(Lab, f32)], k: usize) -> Vec<Lab> {
+    let mut rng = SplitMix64(KMEANS_SEED);
+    let pick = |rng: &mut SplitMix64, weights: &[f32]| -> Option<usize> {
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = rng.next_f32() * total;
+        for (idx, w) in weights.iter().enumerate() {
+            if target < *w {
+                return Some(idx);
+            }
+            target -= w;
+        }
+        weights.iter().rposition(|w| *w > 0.0)
+    };
+
+    let sample_weights: Vec<f32> = samples.iter().map(|(_, w)| *w).collect();
+    let first = pick(&mut rng, &sample_weights).unwrap_or(0);
+    let mut centers = vec![samples[first].0];
+    let mut nearest: Vec<f32> = samples
+        .iter()
+        .map(|(lab, _)| lab_distance2(*lab, centers[0]))
+        .collect();
+
+    while centers.len() < k {
+        let scores: Vec<f32> = nearest
+            .iter()
+            .zip(&sample_weights)
+            .map(|(d, w)| d * w)
+            .collect();
+        let Some(idx) = pick(&mut rng, &scores) else {
+            break;
+        };
+        let center = samples[idx].0;
+        centers.push(center);
+        for (d, (lab, _)) in nearest.iter_mut().zip(samples) {
+            *d = d.min(lab_distance2(*lab, center));
+        }
+    }
+    centers
+}
+
+fn kmeans(samples: &[(Lab, f32)], k: usize, iterations: usize) -> Vec<(Lab, f32)> {
+    let mut centers = kmeans_plus_plus(samples, k);
+    let k = centers.len();
+    let mut weights = Vec::with_capacity(k);
 
     for _ in 0..iterations {
         let mut accum = vec![(0.0f32, 0.0f32, 0.0f32, 0.0f32); k];
@@ -123,15 +332,21 @@ fn kmeans(samples: &[(Lab, f32)], k: usize, iterations: usize) -> Vec<(Lab, f32)
             weights[idx] += *w;
         }
 
+        let mut max_shift = 0.0f32;
         for i in 0..k {
             if accum[i].3 > 0.0 {
-                centers[i] = Lab::new(
+                let center = Lab::new(
                     accum[i].0 / accum[i].3,
                     accum[i].1 / accum[i].3,
                     accum[i].2 / accum[i].3,
                 );
+                max_shift = max_shift.max(lab_distance2(center, centers[i]).sqrt());
+                centers[i] = center;
             }
         }
+        if max_shift < KMEANS_TOLERANCE {
+            break;
+        }
     }
 
     let total_weight: f32 = weights.iter().copied().sum::<f32>().max(f32::EPSILON);
@@ -152,10 +367,10 @@ fn palette_similarity(ref_palette: &[(Lab, f32)], impl_palette: &[(Lab, f32)]) -
         .map(|(lab_ref, weight)| {
             let delta = impl_palette
                 .iter()
-                .map(|(lab_impl, _)| lab_distance2(*lab_ref, *lab_impl).sqrt())
+                .map(|(lab_impl, _)| delta_e_2000(*lab_ref, *lab_impl))
                 .fold(f32::INFINITY, f32::min);
 
-            let match_score = 1.0 - (delta / 25.0).min(1.0);
+            let match_score = 1.0 - (delta / PALETTE_MATCH_MAX_DELTA_E).min(1.0);
             weight * match_score
         })
         .sum::<f32>()
@@ -179,10 +394,7 @@ fn palette_diffs(
     for (idx, (lab_ref, _w)) in sorted.iter().enumerate() {
         if let Some((lab_impl, delta)) = impl_palette
             .iter()
-            .map(|(l, _)| {
-                let d = lab_distance2(*lab_ref, *l).sqrt();
-                (l, d)
-            })
+            .map(|(l, _)| (l, delta_e_2000(*lab_ref, *l)))
             .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
         {
             let kind = match idx {
@@ -191,10 +403,14 @@ fn palette_diffs(
                 _ => ColorDiffKind::BackgroundColorShift,
             };
             diffs.push(ColorDiff {
+                fingerprint: None,
                 kind,
                 ref_color: lab_to_hex(*lab_ref),
                 impl_color: lab_to_hex(*lab_impl),
                 delta_e: Some(delta),
+                element_id: None,
+                angle_delta: None,
+                style: None,
             });
         }
     }
@@ -202,10 +418,13 @@ fn palette_diffs(
     diffs
 }
 
-fn average_rgb(img: &DynamicImage) -> [u8; 3] {
+fn average_rgb(img: &DynamicImage, mode: TransparencyMode) -> [u8; 3] {
     let mut sum = [0u64; 3];
     let mut count = 0u64;
     for (_x, _y, pixel) in img.pixels() {
+        if mode.excludes(&pixel) {
+            continue;
+        }
         let c = pixel.0;
         sum[0] += c[0] as u64;
         sum[1] += c[1] as u64;
@@ -222,11 +441,30 @@ fn average_rgb(img: &DynamicImage) -> [u8; 3] {
     ]
 }
 
-fn rgb_distance(a: &[u8; 3], b: &[u8; 3]) -> f32 {
-    let dr = a[0] as f32 - b[0] as f32;
-    let dg = a[1] as f32 - b[1] as f32;
-    let db = a[2] as f32 - b[2] as f32;
-    ((dr * dr + dg * dg + db * db).sqrt()).max(0.0)
+pub(super) fn rgb_to_lab(rgb: [u8; 3]) -> Lab {
+    srgb_to_lab([
+        rgb[0] as f32 / 255.0,
+        rgb[1] as f32 / 255.0,
+        rgb[2] as f32 / 255.0,
+    ])
+}
+
+/// sRGB channels in 0.0 - 1.0 to Lab.
+pub(super) fn srgb_to_lab(rgb: [f32; 3]) -> Lab {
+    Lab::from_color_unclamped(Srgb::new(rgb[0], rgb[1], rgb[2]))
+}
+
+/// CIEDE2000 difference of two sRGB colors with channels in 0.0 - 1.0.
+pub(crate) fn srgb_delta_e(a: [f32; 3], b: [f32; 3]) -> f32 {
+    delta_e_2000(srgb_to_lab(a), srgb_to_lab(b))
+}
+
+/// CIEDE2000 color difference, the value reported as `delta_e`.
+///
+/// Unlike the Euclidean Lab distance, equal values look equally different
+/// across hues and lightness, so thresholds carry over between palettes.
+pub(crate) fn delta_e_2000(a: Lab, b: Lab) -> f32 {
+    a.difference(b)
 }
 
 fn lab_to_hex(lab: Lab) -> String {
@@ -240,6 +478,9 @@ fn lab_to_hex(lab: Lab) -> String {
     )
 }
 
+/// Squared Euclidean Lab distance. k-means and saliency need a metric whose
+/// minimizer is the mean, so they keep using it; reported differences use
+/// [`delta_e_2000`].
 fn lab_distance2(a: Lab, b: Lab) -> f32 {
     let dl = a.l - b.l;
     let da = a.a - b.a;
@@ -0,0 +1,143 @@
+//! Per-element color drift between matched elements.
+//!
+//! The palette comparison in [`super::color`] says which colors shifted, not
+//! where. Here each implementation element with a solid background or text
+//! color is matched to the element in the same place on the reference (DOM
+//! or Figma), and the CIEDE2000 difference of each color is measured. The
+//! result drives the color drift heatmap artifact.
+
+use super::color::{delta_e_2000, srgb_to_lab};
+use super::gradient::parse_css_color;
+use super::layout::iou;
+use crate::types::{BoundingBox, FigmaPaintKind, NormalizedView};
+
+/// Minimum box overlap for two elements to be the same element.
+const DRIFT_MATCH_IOU: f32 = 0.5;
+
+/// How far one implementation element's colors drifted from its reference.
+#[derive(Debug, Clone)]
+pub struct ElementColorDrift {
+    /// Implementation element id.
+    pub element_id: String,
+    /// Matched reference element id.
+    pub ref_element_id: String,
+    /// Implementation element box, in implementation view units.
+    pub bbox: BoundingBox,
+    /// Delta E 2000 of the background colors, when both sides have one.
+    pub background_delta_e: Option<f32>,
+    /// Delta E 2000 of the text colors, when both sides have text.
+    pub foreground_delta_e: Option<f32>,
+}
+
+impl ElementColorDrift {
+    /// The larger of the background and text drift.
+    pub fn delta_e(&self) -> f32 {
+        self.background_delta_e
+            .into_iter()
+            .chain(self.foreground_delta_e)
+            .fold(0.0, f32::max)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ColoredElement {
+    id: String,
+    bbox: BoundingBox,
+    background: Option<[f32; 3]>,
+    foreground: Option<[f32; 3]>,
+}
+
+/// Color drift of every implementation element matched to a reference
+/// element with a comparable color, in implementation document order.
+pub fn element_color_drift(
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+) -> Vec<ElementColorDrift> {
+    let ref_elements = colored_elements(reference);
+    let impl_elements = colored_elements(implementation);
+    let mut taken = vec![false; ref_elements.len()];
+    let mut drifts = Vec::new();
+    for impl_el in &impl_elements {
+        let best = ref_elements
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !taken[*idx])
+            .map(|(idx, ref_el)| (idx, iou(&impl_el.bbox, &ref_el.bbox)))
+            .filter(|(_, overlap)| *overlap >= DRIFT_MATCH_IOU)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((ref_idx, _)) = best else {
+            continue;
+        };
+        let ref_el = &ref_elements[ref_idx];
+        let delta = |a: Option<[f32; 3]>, b: Option<[f32; 3]>| {
+            Some(delta_e_2000(srgb_to_lab(a?), srgb_to_lab(b?)))
+        };
+        let background_delta_e = delta(ref_el.background, impl_el.background);
+        let foreground_delta_e = delta(ref_el.foreground, impl_el.foreground);
+        if background_delta_e.is_none() && foreground_delta_e.is_none() {
+            continue;
+        }
+        taken[ref_idx] = true;
+        drifts.push(ElementColorDrift {
+            element_id: impl_el.id.clone(),
+            ref_element_id: ref_el.id.clone(),
+            bbox: impl_el.bbox,
+            background_delta_e,
+            foreground_delta_e,
+        });
+    }
+    drifts
+}
+
+/// Elements with an opaque solid background or a text color: DOM nodes when
+/// the view has them, Figma nodes otherwise.
+fn colored_elements(view: &NormalizedView) -> Vec<ColoredElement> {
+    let solid = |css: Option<&str>| -> Option<[f32; 3]> {
+        let rgba = parse_css_color(css?)?;
+        (rgba[3] >= 0.5).then_some([rgba[0], rgba[1], rgba[2]])
+    };
+
+    if let Some(dom) = &view.dom {
+        let elements: Vec<ColoredElement> = dom
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let style = node.computed_style.as_ref()?;
+                let has_text = node.text.as_deref().is_some_and(|t| !t.trim().is_empty());
+                let element = ColoredElement {
+                    id: node.id.clone(),
+                    bbox: node.bounding_box,
+                    background: solid(style.background_color.as_deref()),
+                    foreground: solid(style.color.as_deref()).filter(|_| has_text),
+                };
+                (element.background.is_some() || element.foreground.is_some()).then_some(element)
+            })
+            .collect();
+        if !elements.is_empty() {
+            return elements;
+        }
+    }
+
+    if let Some(figma) = &view.figma_tree {
+        return figma
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let fill = node
+                    .fills
+                    .iter()
+                    .filter(|paint| paint.kind == FigmaPaintKind::Solid)
+                    .find_map(|paint| solid(paint.color.as_deref()))?;
+                let is_text = node.node_type.eq_ignore_ascii_case("text");
+                Some(ColoredElement {
+                    id: node.id.clone(),
+                    bbox: node.bounding_box,
+                    background: (!is_text).then_some(fill),
+                    foreground: is_text.then_some(fill),
+                })
+            })
+            .collect();
+    }
+
+    Vec::new()
+}
@@ -1,13 +1,112 @@
-use crate::types::{ContentMetric, NormalizedView};
-use crate::Result;
-use std::collections::HashSet;
+use crate::strings::edit_distance;
+use crate::types::{BoundingBox, ContentMetric, NormalizedView};
+use crate::{DpcError, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
+use super::content_formats::{normalize_formats, FormatNormalization};
+use super::content_placeholders::{self, Placeholder};
+use super::importance::ImportanceModel;
 use super::{Metric, MetricKind, MetricResult};
 
-#[derive(Debug, Clone, Copy)]
+/// How two normalized strings are scored against each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentMatchMode {
+    /// Dice coefficient over word tokens.
+    #[default]
+    Token,
+    /// Character-level Levenshtein ratio; tolerant of typos and small rewordings.
+    Levenshtein,
+    /// Best of token and Levenshtein scores.
+    Hybrid,
+}
+
+/// Phrase equivalences (synonyms or translations) applied before matching.
+///
+/// Every phrase in a group is rewritten to the group's first entry, so
+/// `["Sign in", "Log in", "Anmelden"]` makes all three compare as equal.
+#[derive(Debug, Clone, Default)]
+pub struct SynonymTable {
+    groups: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SynonymFile {
+    Groups(Vec<Vec<String>>),
+    Map(BTreeMap<String, Vec<String>>),
+}
+
+impl SynonymTable {
+    pub fn from_groups(groups: Vec<Vec<String>>) -> Self {
+        Self {
+            groups: groups.into_iter().filter(|g| g.len() > 1).collect(),
+        }
+    }
+
+    /// Load a JSON file holding either an array of groups
+    /// (`[["Sign in", "Log in"]]`) or a map from canonical phrase to
+    /// alternatives (`{"Sign in": ["Log in", "Anmelden"]}`). Map entries
+    /// are taken in key order, so ties between groups resolve the same way
+    /// on every run.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let parsed: SynonymFile = serde_json::from_str(&raw).map_err(|e| {
+            DpcError::Config(format!("Invalid synonyms file {}: {}", path.display(), e))
+        })?;
+        let groups = match parsed {
+            SynonymFile::Groups(groups) => groups,
+            SynonymFile::Map(map) => map
+                .into_iter()
+                .map(|(canonical, alternatives)| {
+                    std::iter::once(canonical).chain(alternatives).collect()
+                })
+                .collect(),
+        };
+        Ok(Self::from_groups(groups))
+    }
+
+    /// Normalized (phrase, canonical) pairs, longest phrase first.
+    fn replacements(&self, fold_diacritics: bool) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        for group in &self.groups {
+            let mut normalized = group
+                .iter()
+                .filter_map(|phrase| normalize_text(phrase, fold_diacritics));
+            let Some(canonical) = normalized.next() else {
+                continue;
+            };
+            for phrase in normalized {
+                if phrase != canonical {
+                    pairs.push((phrase, canonical.clone()));
+                }
+            }
+        }
+        pairs.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.len()));
+        pairs
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ContentSimilarity {
     pub match_threshold: f32,
     pub extra_penalty_weight: f32,
+    pub mode: ContentMatchMode,
+    /// Strip accents before comparing (`Café` matches `Cafe`).
+    pub fold_diacritics: bool,
+    pub synonyms: Option<Arc<SynonymTable>>,
+    /// Numeral/currency/date rewriting applied before matching.
+    pub formats: FormatNormalization,
+    /// Skip lorem ipsum and `{{variable}}`-style reference text instead of
+    /// reporting it as missing.
+    pub treat_placeholders: bool,
+    /// Weights missing and extra text by the role/tag of its element;
+    /// `None` counts every text alike.
+    pub importance: Option<Arc<ImportanceModel>>,
 }
 
 impl Default for ContentSimilarity {
@@ -15,6 +114,12 @@ impl Default for ContentSimilarity {
         Self {
             match_threshold: 0.7,
             extra_penalty_weight: 0.2,
+            mode: ContentMatchMode::Token,
+            fold_diacritics: false,
+            synonyms: None,
+            formats: FormatNormalization::Off,
+            treat_placeholders: false,
+            importance: Some(Arc::new(ImportanceModel::default())),
         }
     }
 }
@@ -25,44 +130,95 @@ impl ContentSimilarity {
         reference: &NormalizedView,
         implementation: &NormalizedView,
     ) -> Result<ContentMetric> {
-        let ref_texts = extract_texts(reference);
-        let impl_texts = extract_texts(implementation);
+        let model = self.importance.as_deref();
+        let ref_texts = extract_texts(reference, model);
+        let impl_texts = extract_texts(implementation, model);
 
         if ref_texts.is_empty() && impl_texts.is_empty() {
             return Ok(ContentMetric {
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
                 score: 1.0,
                 missing_text: vec![],
                 extra_text: vec![],
+                placeholder_text: vec![],
+                text_labels: BTreeMap::new(),
             });
         }
 
-        let normalized_ref: Vec<(String, String)> = ref_texts
-            .into_iter()
-            .filter_map(|original| normalize_text(&original).map(|norm| (original, norm)))
-            .collect();
-        let normalized_impl: Vec<(String, String)> = impl_texts
+        let replacements = self
+            .synonyms
+            .as_ref()
+            .map(|table| table.replacements(self.fold_diacritics))
+            .unwrap_or_default();
+        let prepare = |original: &str| {
+            normalize_text(
+                &normalize_formats(original, self.formats),
+                self.fold_diacritics,
+            )
+            .map(|norm| apply_synonyms(&norm, &replacements))
+        };
+
+        let mut placeholder_text = Vec::new();
+        let mut placeholder_boxes = Vec::new();
+        let mut text_labels = BTreeMap::new();
+        // (original, normalized, is_template, importance)
+        let mut normalized_ref: Vec<(String, String, bool, f32)> = Vec::new();
+        for (original, bbox, importance, label) in ref_texts {
+            let (source, is_template) = match self.placeholder_kind(&original) {
+                Placeholder::Full => {
+                    placeholder_text.push(original);
+                    placeholder_boxes.push(bbox);
+                    continue;
+                }
+                Placeholder::Template(literal) => (literal, true),
+                Placeholder::None => (original.clone(), false),
+            };
+            if let Some(norm) = prepare(&source) {
+                if let Some(label) = label {
+                    text_labels.insert(original.clone(), label);
+                }
+                normalized_ref.push((original, norm, is_template, importance));
+            }
+        }
+        let mut impl_labels = BTreeMap::new();
+        let normalized_impl: Vec<(String, String, BoundingBox, f32)> = impl_texts
             .into_iter()
-            .filter_map(|original| normalize_text(&original).map(|norm| (original, norm)))
+            .filter_map(|(original, bbox, importance, label)| {
+                let norm = prepare(&original)?;
+                if let Some(label) = label {
+                    impl_labels.entry(original.clone()).or_insert(label);
+                }
+                Some((original, norm, bbox, importance))
+            })
             .collect();
 
         if normalized_ref.is_empty() && normalized_impl.is_empty() {
             return Ok(ContentMetric {
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
                 score: 1.0,
                 missing_text: vec![],
                 extra_text: vec![],
+                placeholder_text,
+                text_labels: BTreeMap::new(),
             });
         }
 
         let mut matched_impl = vec![false; normalized_impl.len()];
-        let mut matched_count = 0usize;
+        let mut matched_weight = 0.0f32;
         let mut missing_text = Vec::new();
 
-        for (ref_orig, ref_norm) in &normalized_ref {
+        for (ref_orig, ref_norm, is_template, importance) in &normalized_ref {
             let mut best_score = 0.0f32;
             let mut best_idx = None;
 
-            for (idx, (_impl_orig, impl_norm)) in normalized_impl.iter().enumerate() {
-                let score = token_similarity(ref_norm, impl_norm);
+            for (idx, (_impl_orig, impl_norm, _, _)) in normalized_impl.iter().enumerate() {
+                let mut score = self.similarity(ref_norm, impl_norm);
+                if *is_template {
+                    // The filled-in slots add words the reference can't know about.
+                    score = score.max(token_containment(ref_norm, impl_norm));
+                }
                 if score > best_score {
                     best_score = score;
                     best_idx = Some(idx);
@@ -70,7 +226,7 @@ impl ContentSimilarity {
             }
 
             if best_score >= self.match_threshold {
-                matched_count += 1;
+                matched_weight += importance;
                 if let Some(idx) = best_idx {
                     matched_impl[idx] = true;
                 }
@@ -79,54 +235,102 @@ impl ContentSimilarity {
             }
         }
 
-        let extra_text: Vec<String> = normalized_impl
+        // Real copy rendered where the design had filler is not "extra".
+        for (idx, (_, _, bbox, _)) in normalized_impl.iter().enumerate() {
+            if !matched_impl[idx] && placeholder_boxes.iter().any(|p| mostly_inside(bbox, p)) {
+                matched_impl[idx] = true;
+            }
+        }
+
+        let extras: Vec<(&String, f32)> = normalized_impl
             .iter()
             .enumerate()
-            .filter_map(|(idx, (orig, _))| {
-                if matched_impl[idx] {
-                    None
-                } else {
-                    Some(orig.clone())
-                }
-            })
+            .filter(|(idx, _)| !matched_impl[*idx])
+            .map(|(_, (orig, _, _, importance))| (orig, *importance))
             .collect();
+        let extra_text: Vec<String> = extras.iter().map(|(orig, _)| (*orig).clone()).collect();
+
+        // Only the reported texts need a name; a design label wins over an
+        // implementation one for text on both sides.
+        text_labels.retain(|text, _| missing_text.contains(text));
+        for text in &extra_text {
+            if let Some(label) = impl_labels.remove(text) {
+                text_labels.entry(text.clone()).or_insert(label);
+            }
+        }
 
-        let ref_len = normalized_ref.len() as f32;
-        let base_score = if ref_len == 0.0 {
+        // Without an importance model every weight is 1.0 and these reduce to
+        // plain text and character counts.
+        let ref_weight: f32 = normalized_ref.iter().map(|(_, _, _, w)| w).sum();
+        let base_score = if ref_weight <= 0.0 {
             1.0
         } else {
-            matched_count as f32 / ref_len
+            matched_weight / ref_weight
         };
 
-        let ref_chars: usize = normalized_ref.iter().map(|(orig, _)| orig.len()).sum();
-        let extra_chars: usize = extra_text.iter().map(|s| s.len()).sum();
-        let penalty = if ref_chars == 0 {
+        let ref_chars: f32 = normalized_ref
+            .iter()
+            .map(|(orig, _, _, w)| orig.len() as f32 * w)
+            .sum();
+        let extra_chars: f32 = extras.iter().map(|(orig, w)| orig.len() as f32 * w).sum();
+        let penalty = if ref_chars <= 0.0 {
             0.0
         } else {
-            let frac = extra_chars as f32 / ref_chars as f32;
+            let frac = extra_chars / ref_chars;
             (frac * self.extra_penalty_weight).min(0.5)
         };
 
         let score = (base_score - penalty).clamp(0.0, 1.0);
 
         Ok(ContentMetric {
+            missing_text_fingerprints: Vec::new(),
+            extra_text_fingerprints: Vec::new(),
             score,
             missing_text,
             extra_text,
+            placeholder_text,
+            text_labels,
         })
     }
+
+    fn placeholder_kind(&self, text: &str) -> Placeholder {
+        if self.treat_placeholders {
+            content_placeholders::classify(text)
+        } else {
+            Placeholder::None
+        }
+    }
+
+    fn similarity(&self, a: &str, b: &str) -> f32 {
+        match self.mode {
+            ContentMatchMode::Token => token_similarity(a, b),
+            ContentMatchMode::Levenshtein => levenshtein_ratio(a, b),
+            ContentMatchMode::Hybrid => token_similarity(a, b).max(levenshtein_ratio(a, b)),
+        }
+    }
 }
 
-fn extract_texts(view: &NormalizedView) -> Vec<String> {
+/// Text, box, importance and element name of every text element; OCR
+/// blocks carry no role, tag or name and weigh the model's default.
+fn extract_texts(
+    view: &NormalizedView,
+    model: Option<&ImportanceModel>,
+) -> Vec<(String, BoundingBox, f32, Option<String>)> {
     let mut texts = Vec::new();
+    let mut push = |text: &str, bbox: &BoundingBox, importance: f32, label: Option<&str>| {
+        let trimmed = text.trim();
+        if !trimmed.is_empty() {
+            // A layer named after its own text adds nothing.
+            let label = label.filter(|label| *label != trimmed).map(str::to_string);
+            texts.push((trimmed.to_string(), *bbox, importance, label));
+        }
+    };
 
     if let Some(dom) = &view.dom {
         for node in &dom.nodes {
             if let Some(text) = &node.text {
-                let trimmed = text.trim();
-                if !trimmed.is_empty() {
-                    texts.push(trimmed.to_string());
-                }
+                let importance = model.map_or(1.0, |m| m.dom_importance(node));
+                push(text, &node.bounding_box, importance, node.label());
             }
         }
     }
@@ -134,28 +338,47 @@ fn extract_texts(view: &NormalizedView) -> Vec<String> {
     if let Some(figma) = &view.figma_tree {
         for node in &figma.nodes {
             if let Some(text) = &node.text {
-                let trimmed = text.trim();
-                if !trimmed.is_empty() {
-                    texts.push(trimmed.to_string());
-                }
+                let importance = model.map_or(1.0, |m| m.figma_importance(node));
+                push(text, &node.bounding_box, importance, node.label());
             }
         }
     }
 
     if let Some(blocks) = &view.ocr_blocks {
         for block in blocks {
-            let trimmed = block.text.trim();
-            if !trimmed.is_empty() {
-                texts.push(trimmed.to_string());
-            }
+            push(
+                &block.text,
+                &block.bounding_box,
+                model.map_or(1.0, |m| m.default),
+                None,
+            );
         }
     }
 
     texts
 }
 
-fn normalize_text(input: &str) -> Option<String> {
-    let lower = input.to_lowercase();
+/// True when at least half of `inner`'s area lies within `outer`.
+fn mostly_inside(inner: &BoundingBox, outer: &BoundingBox) -> bool {
+    let overlap_w = (inner.x + inner.width).min(outer.x + outer.width) - inner.x.max(outer.x);
+    let overlap_h = (inner.y + inner.height).min(outer.y + outer.height) - inner.y.max(outer.y);
+    if overlap_w <= 0.0 || overlap_h <= 0.0 {
+        return false;
+    }
+    let area = inner.width * inner.height;
+    area > 0.0 && overlap_w * overlap_h >= 0.5 * area
+}
+
+fn normalize_text(input: &str, fold_diacritics: bool) -> Option<String> {
+    let lower = if fold_diacritics {
+        input
+            .nfd()
+            .filter(|c| !is_combining_mark(*c))
+            .collect::<String>()
+            .to_lowercase()
+    } else {
+        input.to_lowercase()
+    };
     let mut cleaned = String::new();
 
     for ch in lower.chars() {
@@ -174,6 +397,36 @@ fn normalize_text(input: &str) -> Option<String> {
     }
 }
 
+/// Rewrite whole-word occurrences of each phrase to its canonical form.
+fn apply_synonyms(text: &str, replacements: &[(String, String)]) -> String {
+    if replacements.is_empty() {
+        return text.to_string();
+    }
+    let mut padded = format!(" {text} ");
+    for (phrase, canonical) in replacements {
+        padded = padded.replace(&format!(" {phrase} "), &format!(" {canonical} "));
+    }
+    padded.trim().to_string()
+}
+
+fn levenshtein_ratio(a: &str, b: &str) -> f32 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f32 / longest as f32
+}
+
+/// Fraction of `needle`'s words that also appear in `haystack`.
+fn token_containment(needle: &str, haystack: &str) -> f32 {
+    let needle: HashSet<&str> = needle.split_whitespace().collect();
+    if needle.is_empty() {
+        return 0.0;
+    }
+    let haystack: HashSet<&str> = haystack.split_whitespace().collect();
+    needle.intersection(&haystack).count() as f32 / needle.len() as f32
+}
+
 fn token_similarity(a: &str, b: &str) -> f32 {
     let set_a: HashSet<&str> = a.split_whitespace().collect();
     let set_b: HashSet<&str> = b.split_whitespace().collect();
@@ -0,0 +1,297 @@
+//! Number, currency and date normalization for the content metric.
+//!
+//! Runs on raw text before the content metric lowercases and strips punctuation,
+//! so `$1,299.00`, `1.299,00 $` and `1299 USD` all become `usd 1299`.
+
+/// How numerals, currency amounts and dates are rewritten before text matching.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatNormalization {
+    /// Compare text as written.
+    #[default]
+    Off,
+    /// Rewrite to canonical forms (plain decimals, ISO currency codes, ISO dates).
+    Canonical,
+    /// Replace every number and date with a placeholder so live values never differ.
+    Mask,
+}
+
+const NUMBER_PLACEHOLDER: &str = "num";
+const DATE_PLACEHOLDER: &str = "date";
+
+/// Currency symbols, longest first so `US$` wins over `$`.
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("US$", "usd"),
+    ("CA$", "cad"),
+    ("A$", "aud"),
+    ("R$", "brl"),
+    ("$", "usd"),
+    ("€", "eur"),
+    ("£", "gbp"),
+    ("¥", "jpy"),
+    ("₹", "inr"),
+    ("₩", "krw"),
+    ("₽", "rub"),
+    ("₺", "try"),
+    ("zł", "pln"),
+];
+
+const CURRENCY_CODES: &[&str] = &[
+    "usd", "eur", "gbp", "jpy", "chf", "cad", "aud", "nzd", "sek", "nok", "dkk", "pln", "czk",
+    "huf", "inr", "cny", "krw", "brl", "mxn", "rub", "try", "zar", "sgd", "hkd",
+];
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Word(String),
+    Currency(&'static str),
+    Number(String),
+    Date(String),
+}
+
+/// Rewrite numerals, currency amounts and dates in `text` according to `mode`.
+pub(crate) fn normalize_formats(text: &str, mode: FormatNormalization) -> String {
+    if mode == FormatNormalization::Off {
+        return text.to_string();
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut pieces = Vec::with_capacity(words.len());
+    let mut idx = 0;
+    while idx < words.len() {
+        if let Some((date, consumed)) = month_name_date(&words[idx..]) {
+            pieces.push(Piece::Date(date));
+            idx += consumed;
+            continue;
+        }
+        pieces.extend(classify_word(words[idx]));
+        idx += 1;
+    }
+
+    // Put currency codes in front of their amount so `1299 USD` reads like `$1,299`.
+    for i in 1..pieces.len() {
+        if matches!(pieces[i], Piece::Currency(_)) && matches!(pieces[i - 1], Piece::Number(_)) {
+            pieces.swap(i - 1, i);
+        }
+    }
+
+    pieces
+        .into_iter()
+        .map(|piece| match piece {
+            Piece::Word(word) => word,
+            Piece::Currency(code) => code.to_string(),
+            Piece::Number(_) if mode == FormatNormalization::Mask => NUMBER_PLACEHOLDER.into(),
+            Piece::Date(_) if mode == FormatNormalization::Mask => DATE_PLACEHOLDER.into(),
+            Piece::Number(value) | Piece::Date(value) => value,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn classify_word(word: &str) -> Vec<Piece> {
+    let core = word
+        .trim_start_matches(['(', '[', '"', '\''])
+        .trim_end_matches([',', ';', ':', '!', '?', ')', ']', '"', '\'', '%']);
+    if core.is_empty() {
+        return vec![Piece::Word(word.to_string())];
+    }
+    if let Some(code) = currency_code(core) {
+        return vec![Piece::Currency(code)];
+    }
+
+    let (prefix, rest) = strip_currency_prefix(core);
+    let (rest, suffix) = strip_currency_suffix(rest);
+    let rest = rest.trim_end_matches(['.', ',']);
+
+    if prefix.is_none() && suffix.is_none() {
+        if let Some(date) = numeric_date(rest) {
+            return vec![Piece::Date(date)];
+        }
+    }
+    match canonical_number(rest) {
+        Some(number) => prefix
+            .or(suffix)
+            .map(Piece::Currency)
+            .into_iter()
+            .chain(std::iter::once(Piece::Number(number)))
+            .collect(),
+        None => vec![Piece::Word(word.to_string())],
+    }
+}
+
+fn currency_code(word: &str) -> Option<&'static str> {
+    let lower = word.to_ascii_lowercase();
+    CURRENCY_CODES
+        .iter()
+        .find(|code| **code == lower)
+        .copied()
+        .or_else(|| {
+            CURRENCY_SYMBOLS
+                .iter()
+                .find(|(symbol, _)| *symbol == word)
+                .map(|(_, code)| *code)
+        })
+}
+
+fn strip_currency_prefix(word: &str) -> (Option<&'static str>, &str) {
+    let unsigned = word.trim_start_matches(['-', '+']);
+    for (symbol, code) in CURRENCY_SYMBOLS {
+        if let Some(rest) = unsigned.strip_prefix(symbol) {
+            return (Some(code), rest);
+        }
+    }
+    (None, word)
+}
+
+fn strip_currency_suffix(word: &str) -> (&str, Option<&'static str>) {
+    for (symbol, code) in CURRENCY_SYMBOLS {
+        if let Some(rest) = word.strip_suffix(symbol) {
+            return (rest.trim_end_matches(['\u{a0}', '\u{202f}']), Some(code));
+        }
+    }
+    (word, None)
+}
+
+/// Canonical decimal for `1,299.00`, `1.299,00`, `1'299` or non-breaking-space grouped numbers.
+fn canonical_number(raw: &str) -> Option<String> {
+    let unsigned = raw.trim_start_matches(['-', '+']);
+    if unsigned.is_empty()
+        || !unsigned.starts_with(|c: char| c.is_ascii_digit())
+        || !unsigned.ends_with(|c: char| c.is_ascii_digit())
+        || !unsigned
+            .chars()
+            .all(|c| c.is_ascii_digit() || is_separator(c))
+    {
+        return None;
+    }
+
+    let groups: Vec<&str> = unsigned.split(is_separator).collect();
+    let separators: Vec<char> = unsigned.chars().filter(|c| is_separator(*c)).collect();
+    let decimal_at = match separators.as_slice() {
+        [] => None,
+        [single] if matches!(single, '.' | ',') && groups[1].len() != 3 => Some(1),
+        seps => {
+            let last = *seps.last().unwrap();
+            let mixed = seps.iter().any(|s| *s != last);
+            if mixed && matches!(last, '.' | ',') {
+                Some(groups.len() - 1)
+            } else {
+                None
+            }
+        }
+    };
+
+    let integer_groups = &groups[..decimal_at.unwrap_or(groups.len())];
+    if integer_groups.len() > 1
+        && (integer_groups[0].len() > 3 || integer_groups[1..].iter().any(|g| g.len() != 3))
+    {
+        return None;
+    }
+
+    let integer = integer_groups.concat();
+    let integer = integer.trim_start_matches('0');
+    let integer = if integer.is_empty() { "0" } else { integer };
+    let fraction = decimal_at
+        .map(|i| groups[i].trim_end_matches('0'))
+        .unwrap_or("");
+    let sign = if raw.starts_with('-') { "-" } else { "" };
+
+    Some(if fraction.is_empty() {
+        format!("{sign}{integer}")
+    } else {
+        format!("{sign}{integer}.{fraction}")
+    })
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '.' | ',' | '\'' | '\u{a0}' | '\u{202f}')
+}
+
+/// ISO date for `2024-03-05`, `03/05/2024` (month first) or `5.3.2024` (day first).
+fn numeric_date(raw: &str) -> Option<String> {
+    let sep = raw.chars().find(|c| matches!(c, '-' | '/' | '.'))?;
+    let parts: Vec<&str> = raw.split(sep).collect();
+    let [p0, p1, p2] = parts[..] else {
+        return None;
+    };
+    let number = |p: &str| -> Option<u32> {
+        if p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        p.parse().ok()
+    };
+    let (n0, n1, n2) = (number(p0)?, number(p1)?, number(p2)?);
+
+    let (year, month, day) = if p0.len() == 4 {
+        (n0, n1, n2)
+    } else if matches!(p2.len(), 2 | 4) {
+        let year = if p2.len() == 2 { 2000 + n2 } else { n2 };
+        let month_first = sep == '/';
+        match (month_first, n0 > 12, n1 > 12) {
+            (_, true, false) => (year, n1, n0),
+            (_, false, true) => (year, n0, n1),
+            (true, _, _) => (year, n0, n1),
+            (false, _, _) => (year, n1, n0),
+        }
+    } else {
+        return None;
+    };
+    iso_date(year, month, day)
+}
+
+/// ISO date for `March 5, 2024`, `Mar 5th 2024` or `5 March 2024`; returns words consumed.
+fn month_name_date(words: &[&str]) -> Option<(String, usize)> {
+    let clean = |w: &str| {
+        let lower = w.trim_end_matches([',', '.']).to_lowercase();
+        if lower.starts_with(|c: char| c.is_ascii_digit()) {
+            ["st", "nd", "rd", "th"]
+                .iter()
+                .find_map(|suffix| lower.strip_suffix(suffix))
+                .unwrap_or(&lower)
+                .to_string()
+        } else {
+            lower
+        }
+    };
+    let [first, second, third, ..] = words else {
+        return None;
+    };
+    let (a, b, c) = (clean(first), clean(second), clean(third));
+    let year: u32 = c.parse().ok().filter(|_| c.len() == 4)?;
+
+    let (month, day) = match (month_number(&a), month_number(&b)) {
+        (Some(month), None) => (month, b.parse().ok()?),
+        (None, Some(month)) => (month, a.parse().ok()?),
+        _ => return None,
+    };
+    iso_date(year, month, day).map(|date| (date, 3))
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(word))
+        .map(|idx| idx as u32 + 1)
+}
+
+fn iso_date(year: u32, month: u32, day: u32) -> Option<String> {
+    ((1..=12).contains(&month) && (1..=31).contains(&day))
+        .then(|| format!("{year:04}-{month:02}-{day:02}"))
+}
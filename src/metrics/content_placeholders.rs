@@ -0,0 +1,158 @@
+//! Placeholder detection for the content metric.
+//!
+//! Design files are full of filler copy — lorem ipsum, `{{user.name}}`,
+//! `[Company]`, `%s` — that the shipped UI replaces with real text. These
+//! helpers classify reference strings so the content metric can skip or
+//! relax them instead of reporting the real copy as a mismatch.
+
+/// How much of a reference string is placeholder copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Placeholder {
+    /// Real copy; compare normally.
+    None,
+    /// Entirely filler; skip it in the content comparison.
+    Full,
+    /// Literal text around template slots; holds the text with the slots removed.
+    Template(String),
+}
+
+/// Words that only appear in lorem-ipsum style filler.
+const LOREM_WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "labore",
+    "dolore",
+    "aliqua",
+];
+
+/// Stock filler phrases designers drop into text layers.
+const FILLER_PHRASES: &[&str] = &[
+    "placeholder",
+    "placeholder text",
+    "text here",
+    "your text here",
+    "insert text",
+    "insert text here",
+    "add text",
+    "tbd",
+    "tbc",
+    "todo",
+    "xxx",
+];
+
+pub(crate) fn classify(text: &str) -> Placeholder {
+    let lower = text.trim().to_lowercase();
+    if lower.is_empty() {
+        return Placeholder::None;
+    }
+    if is_lorem(&lower) || is_filler_phrase(&lower) {
+        return Placeholder::Full;
+    }
+
+    let (literal, had_slots) = strip_template_slots(text);
+    if !had_slots {
+        return Placeholder::None;
+    }
+    if literal.chars().any(char::is_alphanumeric) {
+        Placeholder::Template(literal)
+    } else {
+        Placeholder::Full
+    }
+}
+
+fn is_lorem(lower: &str) -> bool {
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.is_empty() {
+        return false;
+    }
+    if lower.contains("lorem ipsum") {
+        return true;
+    }
+    let filler = words.iter().filter(|w| LOREM_WORDS.contains(w)).count();
+    filler >= 2 && filler * 2 >= words.len()
+}
+
+fn is_filler_phrase(lower: &str) -> bool {
+    let collapsed = lower
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    FILLER_PHRASES.contains(&collapsed.as_str())
+}
+
+/// Remove `{{var}}`, `${var}`, `{var}`, `[Var]`, `[[var]]` and printf-style
+/// `%s`/`%d`/`%@`/`%1$s` slots. Returns the remaining text and whether any
+/// slot was found.
+fn strip_template_slots(text: &str) -> (String, bool) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut found = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let opener = match ch {
+            '$' if chars.get(i + 1) == Some(&'{') => Some((i + 1, '}')),
+            '{' => Some((i, '}')),
+            '[' => Some((i, ']')),
+            _ => None,
+        };
+        if let Some((open_at, closer)) = opener {
+            if let Some(close_at) = chars[open_at + 1..].iter().position(|c| *c == closer) {
+                let mut end = open_at + 1 + close_at + 1;
+                while chars.get(end) == Some(&closer) {
+                    end += 1;
+                }
+                let inner: String = chars[open_at..end]
+                    .iter()
+                    .filter(|c| !matches!(c, '{' | '}' | '[' | ']'))
+                    .collect();
+                if !inner.trim().is_empty() {
+                    found = true;
+                    out.push(' ');
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        if ch == '%' {
+            if let Some(len) = printf_slot_len(&chars[i + 1..]) {
+                found = true;
+                out.push(' ');
+                i += 1 + len;
+                continue;
+            }
+        }
+        out.push(ch);
+        i += 1;
+    }
+
+    (out.split_whitespace().collect::<Vec<_>>().join(" "), found)
+}
+
+/// Length of a printf conversion following `%` (`s`, `d`, `@`, `1$s`, ...).
+fn printf_slot_len(rest: &[char]) -> Option<usize> {
+    let positional = match (rest.first(), rest.get(1)) {
+        (Some(d), Some('$')) if d.is_ascii_digit() => 2,
+        _ => 0,
+    };
+    match rest.get(positional) {
+        Some('s' | 'd' | 'i' | 'f' | '@') => {
+            let next = rest.get(positional + 1);
+            (!next.is_some_and(|c| c.is_alphanumeric())).then_some(positional + 1)
+        }
+        _ => None,
+    }
+}
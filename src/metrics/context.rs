@@ -0,0 +1,241 @@
+//! Async metric API.
+//!
+//! [`Metric`] is synchronous and sees only the two views, so a metric that
+//! needs network, OCR or GPU work has to block its thread. [`MetricV2`] is
+//! async and receives a [`MetricContext`] instead: both views, their decoded
+//! screenshots (loaded once and shared between metrics), the run's
+//! [`Config`], an [`ArtifactSink`] for extra files and a cancellation token.
+//! [`MetricAdapter`] runs any existing [`Metric`] through the new API on
+//! Tokio's blocking pool.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use futures::future::{try_join_all, BoxFuture};
+use image::DynamicImage;
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::Config;
+use crate::error::DpcError;
+use crate::types::{MetricScores, NormalizedView};
+use crate::Result;
+
+use super::runner::{has_data_for, record_result, select_runnable};
+use super::stream::{CompareStreamOptions, SharedMetric};
+use super::{Metric, MetricKind, MetricResult};
+
+/// Async design parity metric.
+pub trait MetricV2: Send + Sync {
+    fn kind(&self) -> MetricKind;
+
+    /// Compare the views in `ctx`. Long-running metrics should stop early
+    /// once `ctx.cancel` is cancelled.
+    fn compute<'a>(&'a self, ctx: &'a MetricContext) -> BoxFuture<'a, Result<MetricResult>>;
+
+    /// Whether the views hold the data this metric needs; see
+    /// [`Metric::has_data`].
+    fn has_data(&self, ctx: &MetricContext) -> bool {
+        has_data_for(self.kind(), &ctx.reference, &ctx.implementation)
+    }
+}
+
+/// Everything a [`MetricV2`] can draw on during one comparison.
+pub struct MetricContext {
+    pub reference: Arc<NormalizedView>,
+    pub implementation: Arc<NormalizedView>,
+    pub config: Arc<Config>,
+    pub artifacts: ArtifactSink,
+    /// Cancelled when the comparison is abandoned (deadline, Ctrl-C).
+    pub cancel: CancellationToken,
+    reference_image: OnceCell<Arc<DynamicImage>>,
+    implementation_image: OnceCell<Arc<DynamicImage>>,
+}
+
+impl MetricContext {
+    /// Context with the default config, no artifact directory and a fresh
+    /// cancellation token.
+    pub fn new(reference: NormalizedView, implementation: NormalizedView) -> Self {
+        Self {
+            reference: Arc::new(reference),
+            implementation: Arc::new(implementation),
+            config: Arc::new(Config::default()),
+            artifacts: ArtifactSink::default(),
+            cancel: CancellationToken::new(),
+            reference_image: OnceCell::new(),
+            implementation_image: OnceCell::new(),
+        }
+    }
+
+    pub fn with_config(mut self, config: Arc<Config>) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_artifacts(mut self, artifacts: ArtifactSink) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// The reference screenshot, decoded on first use.
+    pub async fn reference_image(&self) -> Result<Arc<DynamicImage>> {
+        decode_once(&self.reference_image, &self.reference.screenshot_path).await
+    }
+
+    /// The implementation screenshot, decoded on first use.
+    pub async fn implementation_image(&self) -> Result<Arc<DynamicImage>> {
+        decode_once(
+            &self.implementation_image,
+            &self.implementation.screenshot_path,
+        )
+        .await
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(DpcError::metric("comparison cancelled"));
+        }
+        Ok(())
+    }
+}
+
+async fn decode_once(cell: &OnceCell<Arc<DynamicImage>>, path: &Path) -> Result<Arc<DynamicImage>> {
+    cell.get_or_try_init(|| async {
+        let path = path.to_path_buf();
+        let image = tokio::task::spawn_blocking(move || image::open(path))
+            .await
+            .map_err(|err| DpcError::Unknown(format!("image decode task failed: {err}")))??;
+        Ok(Arc::new(image))
+    })
+    .await
+    .cloned()
+}
+
+/// Where metrics write extra artifact files.
+///
+/// Disabled by default: [`ArtifactSink::path`] returns `None` and metrics
+/// skip their extra output.
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactSink {
+    dir: Option<PathBuf>,
+    written: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl ArtifactSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            written: Arc::default(),
+        }
+    }
+
+    /// Path for an artifact named `name`, recorded in [`Self::written`];
+    /// `None` when the sink is disabled.
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        let path = self.dir.as_ref()?.join(name);
+        self.written
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(path.clone());
+        Some(path)
+    }
+
+    /// Paths handed out so far, in request order.
+    pub fn written(&self) -> Vec<PathBuf> {
+        self.written
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+/// Runs a synchronous [`Metric`] as a [`MetricV2`] on Tokio's blocking pool.
+///
+/// Cancellation is checked before the metric starts and while it runs; a
+/// cancelled metric keeps running on its thread but its result is discarded.
+#[derive(Clone)]
+pub struct MetricAdapter(pub SharedMetric);
+
+impl MetricAdapter {
+    pub fn new(metric: impl Metric + Send + Sync + 'static) -> Self {
+        Self(Arc::new(metric))
+    }
+}
+
+impl MetricV2 for MetricAdapter {
+    fn kind(&self) -> MetricKind {
+        self.0.kind()
+    }
+
+    fn compute<'a>(&'a self, ctx: &'a MetricContext) -> BoxFuture<'a, Result<MetricResult>> {
+        Box::pin(async move {
+            ctx.check_cancelled()?;
+            let metric = Arc::clone(&self.0);
+            let reference = Arc::clone(&ctx.reference);
+            let implementation = Arc::clone(&ctx.implementation);
+            let task =
+                tokio::task::spawn_blocking(move || metric.compute(&reference, &implementation));
+            tokio::select! {
+                joined = task => joined
+                    .map_err(|err| DpcError::Unknown(format!("metric task failed: {err}")))?,
+                _ = ctx.cancel.cancelled() => Err(DpcError::metric("comparison cancelled")),
+            }
+        })
+    }
+
+    fn has_data(&self, ctx: &MetricContext) -> bool {
+        self.0.has_data(&ctx.reference, &ctx.implementation)
+    }
+}
+
+/// The built-in metrics behind [`MetricAdapter`]s.
+pub fn default_metrics_v2() -> Vec<Box<dyn MetricV2>> {
+    CompareStreamOptions::default()
+        .metrics
+        .into_iter()
+        .map(|metric| Box::new(MetricAdapter(metric)) as Box<dyn MetricV2>)
+        .collect()
+}
+
+/// Run the selected [`MetricV2`]s concurrently, like
+/// [`run_metrics`](super::run_metrics). Fails with the first metric error,
+/// or when `ctx.cancel` is cancelled before every metric finished.
+pub async fn run_metrics_v2(
+    metrics: &[Box<dyn MetricV2>],
+    selected: &[MetricKind],
+    ctx: &MetricContext,
+) -> Result<MetricScores> {
+    let available: Vec<(MetricKind, bool)> = metrics
+        .iter()
+        .map(|m| (m.kind(), m.has_data(ctx)))
+        .collect();
+    let to_run = select_runnable(&available, selected)?;
+    ctx.check_cancelled()?;
+
+    let results = try_join_all(
+        metrics
+            .iter()
+            .filter(|metric| to_run.contains(&metric.kind()))
+            .map(|metric| metric.compute(ctx)),
+    )
+    .await?;
+    ctx.check_cancelled()?;
+
+    let mut scores = MetricScores {
+        pixel: None,
+        layout: None,
+        typography: None,
+        color: None,
+        content: None,
+        semantic: None,
+    };
+    for result in results {
+        record_result(&mut scores, result);
+    }
+    Ok(scores)
+}
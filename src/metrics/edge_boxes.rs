@@ -0,0 +1,160 @@
+//! Element boxes detected from screenshot pixels.
+//!
+//! Two plain images carry no DOM or Figma tree for the layout matcher. Edges
+//! (Sobel gradient magnitude) outline buttons, cards, text lines and images;
+//! dilating them joins the strokes of one element, and each connected
+//! component becomes a box the layout matcher can pair up like a DOM node.
+
+use image::{DynamicImage, GrayImage};
+
+use crate::types::BoundingBox;
+
+/// Largest possible Sobel gradient magnitude on 8-bit luma (4 * 255).
+const SOBEL_FULL_SCALE: f32 = 1020.0;
+
+/// Settings for detecting element boxes in a screenshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeBoxDetector {
+    /// Gradient magnitude (0–1) above which a pixel counts as an edge.
+    pub edge_threshold: f32,
+    /// Edges this many pixels apart are merged into one box.
+    pub merge_radius: u32,
+    /// Boxes narrower or shorter than this many pixels are dropped as noise.
+    pub min_size: u32,
+    /// Only the largest boxes are kept, so textures don't flood the matcher.
+    pub max_boxes: usize,
+}
+
+impl Default for EdgeBoxDetector {
+    fn default() -> Self {
+        Self {
+            edge_threshold: 0.1,
+            merge_radius: 2,
+            min_size: 4,
+            max_boxes: 200,
+        }
+    }
+}
+
+impl EdgeBoxDetector {
+    /// Boxes in screenshot pixels, largest first.
+    pub fn detect(&self, image: &DynamicImage) -> Vec<BoundingBox> {
+        let gray = image.to_luma8();
+        let edges = edge_mask(&gray, self.edge_threshold);
+        let radius = self.merge_radius;
+        let merged = dilate(&edges, gray.width(), gray.height(), radius, radius);
+        let mut boxes = components(&merged, &edges, gray.width(), gray.height())
+            .into_iter()
+            .filter(|b| b.width >= self.min_size as f32 && b.height >= self.min_size as f32)
+            .collect::<Vec<_>>();
+        boxes.sort_by(|a, b| (b.width * b.height).total_cmp(&(a.width * a.height)));
+        boxes.truncate(self.max_boxes);
+        boxes
+    }
+}
+
+/// Pixels whose Sobel gradient magnitude (0–1) is above `threshold`.
+pub(super) fn edge_mask(gray: &GrayImage, threshold: f32) -> Vec<bool> {
+    let (w, h) = (gray.width() as i64, gray.height() as i64);
+    let px = |x: i64, y: i64| -> f32 {
+        gray.get_pixel(x.clamp(0, w - 1) as u32, y.clamp(0, h - 1) as u32)[0] as f32
+    };
+    let mut mask = vec![false; (w * h) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            let gx = px(x + 1, y - 1) + 2.0 * px(x + 1, y) + px(x + 1, y + 1)
+                - px(x - 1, y - 1)
+                - 2.0 * px(x - 1, y)
+                - px(x - 1, y + 1);
+            let gy = px(x - 1, y + 1) + 2.0 * px(x, y + 1) + px(x + 1, y + 1)
+                - px(x - 1, y - 1)
+                - 2.0 * px(x, y - 1)
+                - px(x + 1, y - 1);
+            let magnitude = (gx * gx + gy * gy).sqrt() / SOBEL_FULL_SCALE;
+            mask[(y * w + x) as usize] = magnitude > threshold;
+        }
+    }
+    mask
+}
+
+/// Rectangular dilation by `radius_x` and `radius_y`, done as a horizontal
+/// then a vertical pass.
+pub(super) fn dilate(
+    mask: &[bool],
+    width: u32,
+    height: u32,
+    radius_x: u32,
+    radius_y: u32,
+) -> Vec<bool> {
+    if radius_x == 0 && radius_y == 0 {
+        return mask.to_vec();
+    }
+    let (w, h) = (width as usize, height as usize);
+    let (r, ry) = (radius_x as usize, radius_y as usize);
+    let mut horizontal = vec![false; mask.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let row = &mask[y * w..(y + 1) * w];
+            horizontal[y * w + x] = row[x.saturating_sub(r)..(x + r + 1).min(w)]
+                .iter()
+                .any(|&edge| edge);
+        }
+    }
+    let mut out = vec![false; mask.len()];
+    for y in 0..h {
+        for x in 0..w {
+            out[y * w + x] =
+                (y.saturating_sub(ry)..(y + ry + 1).min(h)).any(|yy| horizontal[yy * w + x]);
+        }
+    }
+    out
+}
+
+/// Bounds of the 8-connected components of `merged`, measured on the
+/// original `edges` so dilation doesn't grow the boxes.
+pub(super) fn components(
+    merged: &[bool],
+    edges: &[bool],
+    width: u32,
+    height: u32,
+) -> Vec<BoundingBox> {
+    let (w, h) = (width as usize, height as usize);
+    let mut seen = vec![false; merged.len()];
+    let mut boxes = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..merged.len() {
+        if !merged[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % w, idx / w);
+            if edges[idx] {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+            }
+            for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                    let next = ny * w + nx;
+                    if merged[next] && !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        if min_x != usize::MAX {
+            boxes.push(BoundingBox {
+                x: min_x as f32,
+                y: min_y as f32,
+                width: (max_x - min_x + 1) as f32,
+                height: (max_y - min_y + 1) as f32,
+            });
+        }
+    }
+    boxes
+}
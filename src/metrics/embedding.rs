@@ -0,0 +1,229 @@
+//! Semantic similarity of whole screenshots (`--metrics semantic`).
+//!
+//! A CLIP-style image encoder in ONNX format maps each screenshot to an
+//! embedding of what it shows and how it is styled rather than its exact
+//! pixels, and the metric reports the cosine similarity of the two. Screens
+//! with different copy or stock photos but the same design language land
+//! close together, so the metric still says something when pixel alignment
+//! is hopeless.
+//!
+//! The metric is opt-in (it never runs unless selected), needs an encoder
+//! (`[metrics.semantic] model` or `--semantic-model`) and is built with the
+//! `embeddings` feature.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::DynamicImage;
+
+use super::{Metric, MetricKind, MetricResult};
+use crate::types::{NormalizedView, SemanticMetric};
+use crate::{DpcError, Result};
+
+/// Compares the embeddings of the two screenshots.
+#[derive(Debug, Clone)]
+pub struct SemanticSimilarity {
+    /// Cosine similarity that maps to a score of 0.0; unrelated screens
+    /// still share some content, so scores rise linearly from here to 1.0.
+    pub floor: f32,
+    /// Encoder producing the embeddings; selecting the metric without one is
+    /// a config error.
+    pub encoder: Option<Arc<ImageEncoder>>,
+}
+
+impl Default for SemanticSimilarity {
+    fn default() -> Self {
+        Self {
+            floor: 0.5,
+            encoder: None,
+        }
+    }
+}
+
+/// A CLIP-style image encoder in ONNX format, loaded on first use.
+///
+/// The model takes the screenshot as a `1x3xHxW` RGB tensor, scaled to cover
+/// the input and center-cropped, normalized with CLIP's mean and standard
+/// deviation. Its first output, flattened, is the embedding (e.g. `1x512`
+/// for the ViT-B/32 vision tower).
+pub struct ImageEncoder {
+    path: PathBuf,
+    #[cfg(feature = "embeddings")]
+    model: std::sync::OnceLock<std::result::Result<super::onnx::ImageModel, String>>,
+}
+
+impl fmt::Debug for ImageEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageEncoder")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "embeddings")]
+const CLIP: super::onnx::Preprocess = super::onnx::Preprocess {
+    mean: [0.481_454_66, 0.457_827_5, 0.408_210_73],
+    std: [0.268_629_54, 0.261_302_6, 0.275_777_1],
+    center_crop: true,
+};
+
+const NEEDS_FEATURE: &str =
+    "the semantic metric needs dpc built with the `embeddings` feature (cargo install dpc --features embeddings)";
+
+impl ImageEncoder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            #[cfg(feature = "embeddings")]
+            model: std::sync::OnceLock::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Unit-length embedding of `image`.
+    #[cfg(feature = "embeddings")]
+    pub fn embed(&self, image: &DynamicImage) -> Result<Vec<f32>> {
+        let model = self
+            .model
+            .get_or_init(|| {
+                super::onnx::ImageModel::load(&self.path, CLIP).map_err(|e| e.to_string())
+            })
+            .as_ref()
+            .map_err(|e| DpcError::Config(e.clone()))?;
+        let (_, mut embedding) = model.run(image)?;
+        let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 1e-12 {
+            embedding.iter_mut().for_each(|v| *v /= norm);
+        }
+        Ok(embedding)
+    }
+
+    #[cfg(not(feature = "embeddings"))]
+    pub fn embed(&self, _image: &DynamicImage) -> Result<Vec<f32>> {
+        Err(DpcError::Config(NEEDS_FEATURE.to_string()))
+    }
+}
+
+/// Cosine similarity of two unit-length embeddings (-1.0 - 1.0).
+fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32> {
+    if a.len() != b.len() {
+        return Err(DpcError::metric(format!(
+            "embeddings differ in length ({} vs {})",
+            a.len(),
+            b.len()
+        )));
+    }
+    Ok(a.iter()
+        .zip(b)
+        .map(|(x, y)| x * y)
+        .sum::<f32>()
+        .clamp(-1.0, 1.0))
+}
+
+impl SemanticSimilarity {
+    pub fn compute_metric(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<SemanticMetric> {
+        let Some(encoder) = &self.encoder else {
+            return Err(DpcError::Config(if cfg!(feature = "embeddings") {
+                "the semantic metric needs an ONNX image encoder; set `[metrics.semantic] model` or pass --semantic-model".to_string()
+            } else {
+                NEEDS_FEATURE.to_string()
+            }));
+        };
+        let ref_img = image::open(&reference.screenshot_path).map_err(DpcError::from)?;
+        let impl_img = image::open(&implementation.screenshot_path).map_err(DpcError::from)?;
+        let cosine_similarity =
+            cosine_similarity(&encoder.embed(&ref_img)?, &encoder.embed(&impl_img)?)?;
+        let floor = self.floor.clamp(-1.0, 0.99);
+        Ok(SemanticMetric {
+            score: ((cosine_similarity - floor) / (1.0 - floor)).clamp(0.0, 1.0),
+            cosine_similarity,
+        })
+    }
+}
+
+impl Metric for SemanticSimilarity {
+    fn kind(&self) -> MetricKind {
+        MetricKind::Semantic
+    }
+
+    fn compute(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<MetricResult> {
+        Ok(MetricResult::Semantic(
+            self.compute_metric(reference, implementation)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cosine_similarity_of_unit_vectors() {
+        assert!((cosine_similarity(&[0.6, 0.8], &[0.6, 0.8]).unwrap() - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).unwrap().abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0], &[1.0, 0.0]).is_err());
+    }
+
+    #[cfg(not(feature = "embeddings"))]
+    #[test]
+    fn embedding_without_the_feature_is_a_config_error() {
+        let encoder = ImageEncoder::new("clip.onnx");
+        let err = encoder.embed(&DynamicImage::new_rgb8(4, 4)).unwrap_err();
+        assert!(matches!(err, DpcError::Config(ref msg) if msg.contains("`embeddings` feature")));
+    }
+
+    #[cfg(feature = "embeddings")]
+    #[test]
+    fn onnx_encoders_embed_screenshots() {
+        use crate::metrics::onnx::test_models;
+        use image::{Rgb, RgbImage};
+
+        // Mean color per channel, at whatever size the encoder is fed.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("encoder.onnx");
+        test_models::write(
+            &path,
+            [Some(1), Some(3), None, None],
+            "GlobalAveragePool",
+            vec![],
+        );
+        let encoder = ImageEncoder::new(&path);
+
+        let page = |color: [u8; 3], stripe: u32| {
+            let mut img = RgbImage::from_pixel(96, 64, Rgb(color));
+            for x in (0..96).step_by(stripe as usize) {
+                img.put_pixel(x, 40, Rgb([90, 90, 90]));
+            }
+            DynamicImage::ImageRgb8(img)
+        };
+        let reference = encoder.embed(&page([200, 40, 40], 3)).unwrap();
+        let other_copy = encoder.embed(&page([200, 40, 40], 5)).unwrap();
+        let other_palette = encoder.embed(&page([40, 40, 200], 3)).unwrap();
+
+        assert_eq!(reference.len(), 3);
+        let norm = reference.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        let close = cosine_similarity(&reference, &other_copy).unwrap();
+        let far = cosine_similarity(&reference, &other_palette).unwrap();
+        assert!(close > 0.99, "close {close}");
+        assert!(far < 0.5, "far {far}");
+
+        let missing = ImageEncoder::new(dir.path().join("missing.onnx"));
+        assert!(matches!(
+            missing.embed(&page([0, 0, 0], 3)),
+            Err(DpcError::Config(_))
+        ));
+    }
+}
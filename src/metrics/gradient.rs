@@ -0,0 +1,595 @@
+//! Gradient fills: parsing CSS gradients and comparing the fills of matched
+//! elements.
+//!
+//! The flat palette comparison in [`super::color`] blends a gradient into a
+//! handful of averaged clusters. Here each element with a gradient fill (a
+//! Figma gradient paint or a CSS `background-image`) is matched to the element
+//! in the same place on the other side, and the two fills are compared by
+//! direction and by color along the gradient line.
+
+use palette::Lab;
+
+use super::color::{delta_e_2000, srgb_to_lab, PALETTE_MATCH_MAX_DELTA_E};
+use super::layout::iou;
+use crate::types::{
+    BoundingBox, ColorDiff, ColorDiffKind, FigmaPaintKind, Gradient, GradientKind, GradientStop,
+    NormalizedView,
+};
+
+/// Minimum box overlap for two fills to belong to the same element.
+const GRADIENT_MATCH_IOU: f32 = 0.5;
+/// Largest Delta E 2000 along the gradient line still treated as identical.
+const GRADIENT_DELTA_E_TOLERANCE: f32 = 2.0;
+/// Largest direction change, in degrees, still treated as identical.
+const GRADIENT_ANGLE_TOLERANCE: f32 = 10.0;
+/// Points sampled along each gradient when comparing colors.
+const GRADIENT_SAMPLES: usize = 11;
+
+/// Gradient findings and how similar the compared fills are overall.
+#[derive(Debug, Default)]
+pub(crate) struct GradientComparison {
+    pub diffs: Vec<ColorDiff>,
+    /// Mean similarity of the compared pairs; `None` when no element on
+    /// either side has a gradient fill with a counterpart.
+    pub similarity: Option<f32>,
+}
+
+/// An element's fill. Solid fills take part so that a gradient replaced by a
+/// flat color (or the reverse) is caught.
+#[derive(Debug, Clone)]
+struct FillElement {
+    id: String,
+    bbox: BoundingBox,
+    fill: Fill,
+}
+
+#[derive(Debug, Clone)]
+enum Fill {
+    Solid([f32; 3]),
+    Gradient(Gradient),
+}
+
+impl Fill {
+    fn is_gradient(&self) -> bool {
+        matches!(self, Fill::Gradient(_))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Fill::Solid(rgb) => rgb_to_hex(*rgb),
+            Fill::Gradient(gradient) => gradient.to_css(),
+        }
+    }
+
+    fn sample(&self, t: f32) -> [f32; 3] {
+        match self {
+            Fill::Solid(rgb) => *rgb,
+            Fill::Gradient(gradient) => sample_gradient(gradient, t),
+        }
+    }
+
+    fn angle(&self) -> Option<f32> {
+        match self {
+            Fill::Gradient(g) if g.kind == GradientKind::Linear => g.angle,
+            _ => None,
+        }
+    }
+}
+
+/// Compare the gradient fills of elements matched between both views.
+pub(crate) fn compare_gradients(
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+) -> GradientComparison {
+    let ref_fills = extract_fills(reference);
+    let impl_fills = extract_fills(implementation);
+    if !ref_fills
+        .iter()
+        .chain(&impl_fills)
+        .any(|f| f.fill.is_gradient())
+    {
+        return GradientComparison::default();
+    }
+
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (ref_idx, ref_el) in ref_fills.iter().enumerate() {
+        if !ref_el.fill.is_gradient() {
+            continue;
+        }
+        let taken: Vec<usize> = pairs.iter().map(|(_, i)| *i).collect();
+        if let Some(impl_idx) = best_match(&ref_el.bbox, &impl_fills, &taken) {
+            pairs.push((ref_idx, impl_idx));
+        }
+    }
+    for (impl_idx, impl_el) in impl_fills.iter().enumerate() {
+        if !impl_el.fill.is_gradient() || pairs.iter().any(|(_, i)| *i == impl_idx) {
+            continue;
+        }
+        let taken: Vec<usize> = pairs.iter().map(|(r, _)| *r).collect();
+        if let Some(ref_idx) = best_match(&impl_el.bbox, &ref_fills, &taken) {
+            pairs.push((ref_idx, impl_idx));
+        }
+    }
+    if pairs.is_empty() {
+        return GradientComparison::default();
+    }
+
+    let mut diffs = Vec::new();
+    let mut similarity = 0.0;
+    for (ref_idx, impl_idx) in &pairs {
+        let (ref_el, impl_el) = (&ref_fills[*ref_idx], &impl_fills[*impl_idx]);
+        let delta_e = max_delta_e(&ref_el.fill, &impl_el.fill);
+        let angle_delta = match (ref_el.fill.angle(), impl_el.fill.angle()) {
+            (Some(a), Some(b)) => Some(angle_difference(a, b)),
+            _ => None,
+        };
+        let kind_changed = match (&ref_el.fill, &impl_el.fill) {
+            (Fill::Gradient(a), Fill::Gradient(b)) => a.kind != b.kind,
+            _ => false,
+        };
+
+        let color_match = 1.0 - (delta_e / PALETTE_MATCH_MAX_DELTA_E).min(1.0);
+        let direction_match = 1.0 - 0.5 * angle_delta.unwrap_or(0.0) / 180.0;
+        let shape_match = if kind_changed { 0.5 } else { 1.0 };
+        similarity += color_match * direction_match * shape_match;
+
+        if delta_e > GRADIENT_DELTA_E_TOLERANCE
+            || angle_delta.is_some_and(|a| a > GRADIENT_ANGLE_TOLERANCE)
+            || kind_changed
+        {
+            diffs.push(ColorDiff {
+                fingerprint: None,
+                kind: ColorDiffKind::GradientShift,
+                ref_color: ref_el.fill.describe(),
+                impl_color: impl_el.fill.describe(),
+                delta_e: Some(delta_e),
+                element_id: Some(ref_el.id.clone()),
+                angle_delta,
+                style: None,
+            });
+        }
+    }
+
+    GradientComparison {
+        diffs,
+        similarity: Some(similarity / pairs.len() as f32),
+    }
+}
+
+fn extract_fills(view: &NormalizedView) -> Vec<FillElement> {
+    if let Some(dom) = &view.dom {
+        let fills: Vec<FillElement> = dom
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let style = node.computed_style.as_ref()?;
+                let fill = style
+                    .background_image
+                    .as_deref()
+                    .and_then(parse_css_gradient)
+                    .map(Fill::Gradient)
+                    .or_else(|| {
+                        let rgba = parse_css_color(style.background_color.as_deref()?)?;
+                        (rgba[3] >= 0.5).then_some(Fill::Solid([rgba[0], rgba[1], rgba[2]]))
+                    })?;
+                Some(FillElement {
+                    id: node.id.clone(),
+                    bbox: node.bounding_box,
+                    fill,
+                })
+            })
+            .collect();
+        if !fills.is_empty() {
+            return fills;
+        }
+    }
+
+    if let Some(figma) = &view.figma_tree {
+        return figma
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let fill = node
+                    .fills
+                    .iter()
+                    .find_map(|paint| paint.gradient.clone().map(Fill::Gradient))
+                    .or_else(|| {
+                        node.fills
+                            .iter()
+                            .filter(|paint| paint.kind == FigmaPaintKind::Solid)
+                            .find_map(|paint| {
+                                let rgba = parse_css_color(paint.color.as_deref()?)?;
+                                Some(Fill::Solid([rgba[0], rgba[1], rgba[2]]))
+                            })
+                    })?;
+                Some(FillElement {
+                    id: node.id.clone(),
+                    bbox: node.bounding_box,
+                    fill,
+                })
+            })
+            .collect();
+    }
+
+    Vec::new()
+}
+
+fn best_match(target: &BoundingBox, candidates: &[FillElement], taken: &[usize]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !taken.contains(idx))
+        .map(|(idx, cand)| (idx, iou(target, &cand.bbox)))
+        .filter(|(_, overlap)| *overlap >= GRADIENT_MATCH_IOU)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(idx, _)| idx)
+}
+
+/// Largest CIEDE2000 difference between the fills at evenly spaced points
+/// along the gradient line.
+fn max_delta_e(a: &Fill, b: &Fill) -> f32 {
+    (0..GRADIENT_SAMPLES)
+        .map(|i| {
+            let t = i as f32 / (GRADIENT_SAMPLES - 1) as f32;
+            let lab = |fill: &Fill| -> Lab { srgb_to_lab(fill.sample(t)) };
+            delta_e_2000(lab(a), lab(b))
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Color at `t` (0.0 - 1.0), interpolated in sRGB like CSS does.
+fn sample_gradient(gradient: &Gradient, t: f32) -> [f32; 3] {
+    let colors: Vec<(f32, [f32; 3])> = gradient
+        .stops
+        .iter()
+        .filter_map(|stop| {
+            let rgba = parse_css_color(&stop.color)?;
+            Some((stop.position, [rgba[0], rgba[1], rgba[2]]))
+        })
+        .collect();
+    let (Some(first), Some(last)) = (colors.first(), colors.last()) else {
+        return [0.0; 3];
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+    for pair in colors.windows(2) {
+        let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+        if t <= p1 {
+            let span = p1 - p0;
+            let f = if span <= f32::EPSILON {
+                1.0
+            } else {
+                (t - p0) / span
+            };
+            return [
+                c0[0] + (c1[0] - c0[0]) * f,
+                c0[1] + (c1[1] - c0[1]) * f,
+                c0[2] + (c1[2] - c0[2]) * f,
+            ];
+        }
+    }
+    last.1
+}
+
+fn angle_difference(a: f32, b: f32) -> f32 {
+    let diff = (a - b).rem_euclid(360.0);
+    diff.min(360.0 - diff)
+}
+
+fn rgb_to_hex(rgb: [f32; 3]) -> String {
+    let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02X}{:02X}{:02X}",
+        channel(rgb[0]),
+        channel(rgb[1]),
+        channel(rgb[2])
+    )
+}
+
+/// Parse a CSS gradient, as found in a computed `background-image`.
+///
+/// Handles `linear-`, `radial-` and `conic-gradient` (and their `repeating-`
+/// forms, compared as one repetition). With several background layers the
+/// first gradient layer is used. Stops without a position are spread evenly
+/// as in CSS; stops positioned in lengths rather than percentages are too.
+pub fn parse_css_gradient(value: &str) -> Option<Gradient> {
+    split_top_level(value).into_iter().find_map(|layer| {
+        let layer = layer.trim().to_ascii_lowercase();
+        let layer = layer.strip_prefix("repeating-").unwrap_or(&layer);
+        let (kind, rest) = [
+            ("linear-gradient(", GradientKind::Linear),
+            ("radial-gradient(", GradientKind::Radial),
+            ("conic-gradient(", GradientKind::Conic),
+        ]
+        .into_iter()
+        .find_map(|(prefix, kind)| Some((kind, layer.strip_prefix(prefix)?)))?;
+        let body = rest.trim_end().strip_suffix(')')?;
+        parse_gradient_body(kind, body)
+    })
+}
+
+fn parse_gradient_body(kind: GradientKind, body: &str) -> Option<Gradient> {
+    let mut args = split_top_level(body).into_iter().map(str::trim).peekable();
+    let mut angle = (kind == GradientKind::Linear).then_some(180.0);
+    let first = *args.peek()?;
+    if parse_stop(first).is_none() {
+        // Direction, shape or position prelude.
+        if kind == GradientKind::Linear {
+            angle = Some(parse_direction(first)?);
+        }
+        args.next();
+    }
+
+    let mut stops: Vec<(String, Option<f32>)> = Vec::new();
+    for arg in args {
+        // Color hints (a bare position between stops) are skipped.
+        if let Some(parsed) = parse_stop(arg) {
+            stops.extend(parsed);
+        }
+    }
+    if stops.is_empty() {
+        return None;
+    }
+    Some(Gradient {
+        kind,
+        angle,
+        stops: resolve_positions(stops),
+    })
+}
+
+/// `<color> [<position> [<position>]]`; two positions make two stops.
+fn parse_stop(arg: &str) -> Option<Vec<(String, Option<f32>)>> {
+    let arg = arg.trim();
+    let color_end = match arg.find('(') {
+        Some(open) if !arg[..open].contains(' ') => open + arg[open..].find(')')? + 1,
+        _ => arg.find(char::is_whitespace).unwrap_or(arg.len()),
+    };
+    let (color, rest) = arg.split_at(color_end);
+    let rgba = if color.eq_ignore_ascii_case("transparent") {
+        [0.0, 0.0, 0.0, 0.0]
+    } else {
+        parse_css_color(color)?
+    };
+    let hex = rgb_to_hex([rgba[0], rgba[1], rgba[2]]);
+    let positions: Vec<Option<f32>> = rest
+        .split_whitespace()
+        .map(|p| {
+            p.strip_suffix('%')
+                .and_then(|v| v.parse::<f32>().ok())
+                .map(|v| v / 100.0)
+        })
+        .collect();
+    Some(match positions.as_slice() {
+        [] => vec![(hex, None)],
+        [p] => vec![(hex, *p)],
+        [p1, p2, ..] => vec![(hex.clone(), *p1), (hex, *p2)],
+    })
+}
+
+/// `<angle>` or `to <side-or-corner>` as CSS degrees.
+fn parse_direction(arg: &str) -> Option<f32> {
+    if let Some(sides) = arg.strip_prefix("to ") {
+        let mut sides: Vec<&str> = sides.split_whitespace().collect();
+        sides.sort_unstable();
+        return match sides.as_slice() {
+            ["top"] => Some(0.0),
+            ["right"] => Some(90.0),
+            ["bottom"] => Some(180.0),
+            ["left"] => Some(270.0),
+            ["right", "top"] => Some(45.0),
+            ["bottom", "right"] => Some(135.0),
+            ["bottom", "left"] => Some(225.0),
+            ["left", "top"] => Some(315.0),
+            _ => None,
+        };
+    }
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    units.into_iter().find_map(|(unit, scale)| {
+        let value: f32 = arg.strip_suffix(unit)?.trim().parse().ok()?;
+        Some((value * scale).rem_euclid(360.0))
+    })
+}
+
+/// Fill in missing positions the way CSS does: the first and last stops
+/// default to the ends, the rest are spread evenly between known neighbors,
+/// and no stop may sit before the one preceding it.
+fn resolve_positions(stops: Vec<(String, Option<f32>)>) -> Vec<GradientStop> {
+    let n = stops.len();
+    let mut positions: Vec<Option<f32>> = stops.iter().map(|(_, p)| *p).collect();
+    if positions[0].is_none() {
+        positions[0] = Some(0.0);
+    }
+    if positions[n - 1].is_none() {
+        positions[n - 1] = Some(1.0);
+    }
+    let mut previous = 0.0f32;
+    for position in positions.iter_mut().flatten() {
+        *position = position.max(previous);
+        previous = *position;
+    }
+    let mut idx = 0;
+    while idx < n {
+        if positions[idx].is_some() {
+            idx += 1;
+            continue;
+        }
+        let start = idx - 1;
+        let end = (idx..n).find(|i| positions[*i].is_some()).unwrap_or(n - 1);
+        let (from, to) = (
+            positions[start].unwrap_or(0.0),
+            positions[end].unwrap_or(1.0),
+        );
+        for (step, position) in positions[idx..end].iter_mut().enumerate() {
+            *position = Some(from + (to - from) * (step + 1) as f32 / (end - start) as f32);
+        }
+        idx = end;
+    }
+    stops
+        .into_iter()
+        .zip(positions)
+        .map(|((color, _), position)| GradientStop {
+            color,
+            position: position.unwrap_or(0.0),
+        })
+        .collect()
+}
+
+/// Split on commas that are not nested inside parentheses.
+fn split_top_level(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (idx, ch) in value.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&value[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Parse a CSS color (`#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()`) into
+/// RGBA channels in 0.0 - 1.0. `transparent` and other keywords yield `None`.
+pub fn parse_css_color(value: &str) -> Option<[f32; 4]> {
+    let v = value.trim().to_ascii_lowercase();
+    if v == "transparent" {
+        return None;
+    }
+
+    if let Some(hex) = v.strip_prefix('#') {
+        let expanded = match hex.len() {
+            3 => hex
+                .chars()
+                .map(|c| format!("{c}{c}"))
+                .collect::<Vec<_>>()
+                .join(""),
+            6 | 8 => hex.to_string(),
+            _ => return None,
+        };
+        let r = u8::from_str_radix(&expanded[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&expanded[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&expanded[4..6], 16).ok()?;
+        let a = if expanded.len() == 8 {
+            u8::from_str_radix(&expanded[6..8], 16).ok()? as f32 / 255.0
+        } else {
+            1.0
+        };
+        return Some([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a]);
+    }
+
+    if let Some(body) = v.strip_prefix("rgba(").or_else(|| v.strip_prefix("rgb(")) {
+        let cleaned = body.trim_end_matches(')').replace('/', " ");
+        let parts: Vec<_> = cleaned
+            .split(&[',', ' '][..])
+            .filter(|p| !p.trim().is_empty())
+            .collect();
+        if parts.len() < 3 {
+            return None;
+        }
+        let r: f32 = parts.first()?.trim().parse::<f32>().ok()? / 255.0;
+        let g: f32 = parts.get(1)?.trim().parse::<f32>().ok()? / 255.0;
+        let b: f32 = parts.get(2)?.trim().parse::<f32>().ok()? / 255.0;
+        let a: f32 = if let Some(alpha) = parts.get(3) {
+            alpha
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .map(|v| v.clamp(0.0, 1.0))
+                .unwrap_or(1.0)
+        } else {
+            1.0
+        };
+        return Some([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), a]);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stops(gradient: &Gradient) -> Vec<(&str, f32)> {
+        gradient
+            .stops
+            .iter()
+            .map(|s| (s.color.as_str(), (s.position * 100.0).round()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_computed_linear_gradients() {
+        let g =
+            parse_css_gradient("linear-gradient(90deg, rgb(255, 0, 0) 0%, rgb(0, 0, 255) 100%)")
+                .unwrap();
+        assert_eq!(g.kind, GradientKind::Linear);
+        assert_eq!(g.angle, Some(90.0));
+        assert_eq!(stops(&g), vec![("#FF0000", 0.0), ("#0000FF", 100.0)]);
+
+        let g = parse_css_gradient(
+            "url(\"a.png\"), linear-gradient(to top right, #fff, rgba(0, 0, 0, 0.5), #000 80%)",
+        )
+        .unwrap();
+        assert_eq!(g.angle, Some(45.0));
+        assert_eq!(
+            stops(&g),
+            vec![("#FFFFFF", 0.0), ("#000000", 40.0), ("#000000", 80.0)]
+        );
+
+        let g = parse_css_gradient("linear-gradient(0.25turn, #f00 10%, #00ff00 20% 60%)").unwrap();
+        assert_eq!(g.angle, Some(90.0));
+        assert_eq!(
+            stops(&g),
+            vec![("#FF0000", 10.0), ("#00FF00", 20.0), ("#00FF00", 60.0)]
+        );
+    }
+
+    #[test]
+    fn parses_radial_and_conic_preludes_and_rejects_non_gradients() {
+        let g = parse_css_gradient(
+            "radial-gradient(circle at 50% 50%, rgb(255, 255, 255), rgb(0, 0, 0))",
+        )
+        .unwrap();
+        assert_eq!(g.kind, GradientKind::Radial);
+        assert_eq!(g.angle, None);
+        assert_eq!(stops(&g), vec![("#FFFFFF", 0.0), ("#000000", 100.0)]);
+
+        let g = parse_css_gradient("repeating-conic-gradient(from 45deg, #000, #fff)").unwrap();
+        assert_eq!(g.kind, GradientKind::Conic);
+
+        assert!(parse_css_gradient("none").is_none());
+        assert!(parse_css_gradient("url(\"hero.png\")").is_none());
+    }
+
+    #[test]
+    fn compares_sampled_colors_and_direction() {
+        let red_to_blue =
+            Fill::Gradient(parse_css_gradient("linear-gradient(90deg, #f00, #00f)").unwrap());
+        let rotated =
+            Fill::Gradient(parse_css_gradient("linear-gradient(270deg, #f00, #00f)").unwrap());
+        let flat = Fill::Solid([1.0, 0.0, 0.0]);
+
+        assert!(max_delta_e(&red_to_blue, &rotated) < 1e-3);
+        assert_eq!(
+            angle_difference(red_to_blue.angle().unwrap(), rotated.angle().unwrap()),
+            180.0
+        );
+        assert!(max_delta_e(&red_to_blue, &flat) > 20.0);
+        assert_eq!(angle_difference(350.0, 10.0), 20.0);
+    }
+}
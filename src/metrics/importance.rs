@@ -0,0 +1,158 @@
+//! Per-element importance from ARIA roles and tags.
+//!
+//! A missing primary button or page heading should cost more than a missing
+//! divider. Layout and content penalties are scaled by the importance of the
+//! reference element (or, for extra elements, the implementation element).
+
+use crate::types::{DomNode, FigmaNode};
+use crate::{DpcError, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Weights looked up by explicit ARIA `role`, then by tag name.
+///
+/// Figma layers have neither, so their names are split into words and each
+/// word is tried as a role, then as a tag (`"Primary Button"` counts as a
+/// button). Everything else weighs `default`. Weights only matter relative to
+/// each other: uniform importance leaves scores unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportanceModel {
+    pub roles: BTreeMap<String, f32>,
+    pub tags: BTreeMap<String, f32>,
+    pub default: f32,
+}
+
+impl Default for ImportanceModel {
+    fn default() -> Self {
+        let table = |entries: &[(&str, f32)]| {
+            entries
+                .iter()
+                .map(|(name, weight)| (name.to_string(), *weight))
+                .collect()
+        };
+        Self {
+            roles: table(&[
+                ("banner", 1.5),
+                ("button", 2.5),
+                ("contentinfo", 1.0),
+                ("form", 1.5),
+                ("heading", 2.0),
+                ("img", 1.2),
+                ("link", 1.5),
+                ("main", 1.5),
+                ("navigation", 2.0),
+                ("none", 0.3),
+                ("presentation", 0.3),
+                ("search", 1.5),
+                ("separator", 0.3),
+                ("textbox", 2.0),
+            ]),
+            tags: table(&[
+                ("a", 1.5),
+                ("button", 2.5),
+                ("footer", 1.0),
+                ("h1", 3.0),
+                ("h2", 2.0),
+                ("h3", 1.5),
+                ("h4", 1.2),
+                ("h5", 1.2),
+                ("h6", 1.2),
+                ("header", 1.5),
+                ("hr", 0.3),
+                ("img", 1.2),
+                ("input", 2.0),
+                ("label", 1.2),
+                ("main", 1.5),
+                ("nav", 2.0),
+                ("select", 2.0),
+                ("textarea", 2.0),
+            ]),
+            default: 1.0,
+        }
+    }
+}
+
+impl ImportanceModel {
+    /// Load `{"roles": {...}, "tags": {...}, "default": 1.0}`; entries are
+    /// merged over the built-in table, so a file only lists what it changes.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| DpcError::Config(format!("Failed to read importance model: {e}")))?;
+        let overrides: ImportanceOverrides = serde_json::from_str(&data).map_err(|e| {
+            DpcError::Config(format!(
+                "Invalid importance model JSON (expected {{\"roles\": {{...}}, \"tags\": {{...}}, \"default\": 1.0}}): {e}"
+            ))
+        })?;
+        let mut model = Self::default();
+        let lower = |map: BTreeMap<String, f32>| {
+            map.into_iter()
+                .map(|(name, weight)| (name.to_ascii_lowercase(), weight))
+                .collect::<Vec<_>>()
+        };
+        model.roles.extend(lower(overrides.roles));
+        model.tags.extend(lower(overrides.tags));
+        if let Some(default) = overrides.default {
+            model.default = default;
+        }
+        if let Some((name, weight)) = model
+            .roles
+            .iter()
+            .chain(&model.tags)
+            .find(|(_, weight)| weight.is_nan() || **weight < 0.0)
+        {
+            return Err(DpcError::Config(format!(
+                "importance of '{name}' must be non-negative, got {weight}"
+            )));
+        }
+        if model.default.is_nan() || model.default < 0.0 {
+            return Err(DpcError::Config(format!(
+                "default importance must be non-negative, got {}",
+                model.default
+            )));
+        }
+        Ok(model)
+    }
+
+    /// Importance of a DOM element: its first ARIA role, else its tag.
+    pub fn dom_importance(&self, node: &DomNode) -> f32 {
+        let role = node
+            .attributes
+            .get("role")
+            .and_then(|roles| roles.split_whitespace().next())
+            .map(str::to_ascii_lowercase);
+        role.and_then(|role| self.roles.get(&role))
+            .or_else(|| self.tags.get(&node.tag.to_ascii_lowercase()))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Importance of a Figma layer, guessed from the words of its name;
+    /// single letters are skipped so "A card" isn't read as a link.
+    pub fn figma_importance(&self, node: &FigmaNode) -> f32 {
+        let Some(name) = &node.name else {
+            return self.default;
+        };
+        let name = name.to_ascii_lowercase();
+        let words: Vec<&str> = name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| word.len() > 1)
+            .collect();
+        words
+            .iter()
+            .find_map(|word| self.roles.get(*word))
+            .or_else(|| words.iter().find_map(|word| self.tags.get(*word)))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImportanceOverrides {
+    #[serde(default)]
+    roles: BTreeMap<String, f32>,
+    #[serde(default)]
+    tags: BTreeMap<String, f32>,
+    default: Option<f32>,
+}
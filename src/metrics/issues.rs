@@ -1,3 +1,4 @@
+use super::severity::SeverityCalibration;
 use crate::types::{
     ColorDiffKind, ColorMetric, ContentMetric, DiffSeverity, LayoutDiffKind, LayoutMetric,
     MetricScores, PixelMetric, TypographyIssue, TypographyMetric,
@@ -36,9 +37,30 @@ impl RankedIssue {
     fn minor(priority_rank: u8, message: impl Into<String>) -> Self {
         Self::new(2, priority_rank, message)
     }
+
+    fn with_severity(
+        severity: DiffSeverity,
+        priority_rank: u8,
+        message: impl Into<String>,
+    ) -> Self {
+        match severity {
+            DiffSeverity::Major => Self::major(priority_rank, message),
+            DiffSeverity::Moderate => Self::moderate(priority_rank, message),
+            DiffSeverity::Minor => Self::minor(priority_rank, message),
+        }
+    }
 }
 
 pub fn generate_top_issues(scores: &MetricScores, max_issues: usize) -> Vec<String> {
+    generate_top_issues_with(scores, max_issues, &SeverityCalibration::default())
+}
+
+/// Like [`generate_top_issues`], ranking findings with `calibration`.
+pub fn generate_top_issues_with(
+    scores: &MetricScores,
+    max_issues: usize,
+    calibration: &SeverityCalibration,
+) -> Vec<String> {
     let mut issues: Vec<RankedIssue> = Vec::new();
 
     if let Some(ref pixel) = scores.pixel {
@@ -46,19 +68,19 @@ pub fn generate_top_issues(scores: &MetricScores, max_issues: usize) -> Vec<Stri
     }
 
     if let Some(ref layout) = scores.layout {
-        issues.extend(issues_from_layout(layout));
+        issues.extend(issues_from_layout(layout, calibration));
     }
 
     if let Some(ref typography) = scores.typography {
-        issues.extend(issues_from_typography(typography));
+        issues.extend(issues_from_typography(typography, calibration));
     }
 
     if let Some(ref color) = scores.color {
-        issues.extend(issues_from_color(color));
+        issues.extend(issues_from_color(color, calibration));
     }
 
     if let Some(ref content) = scores.content {
-        issues.extend(issues_from_content(content));
+        issues.extend(issues_from_content(content, calibration));
     }
 
     issues.sort_by(|a, b| {
@@ -77,18 +99,26 @@ pub fn generate_top_issues(scores: &MetricScores, max_issues: usize) -> Vec<Stri
 fn issues_from_pixel(metric: &PixelMetric) -> Vec<RankedIssue> {
     let mut issues = Vec::new();
 
+    // A global shift is realigned before diffing, so report it on its own.
+    if let Some(offset) = metric.offset {
+        issues.push(RankedIssue::moderate(
+            PRIORITY_PIXEL,
+            format!(
+                "Implementation is offset by {}px horizontally and {}px vertically from the reference.",
+                offset.x, offset.y
+            ),
+        ));
+    }
+
     // If semantic diffs are available, use those instead of generic pixel region counts
     if let Some(ref semantic_diffs) = metric.semantic_diffs {
         if !semantic_diffs.is_empty() {
             for diff in semantic_diffs {
-                let ranked = match diff.severity {
-                    DiffSeverity::Major => RankedIssue::major(PRIORITY_PIXEL, &diff.description),
-                    DiffSeverity::Moderate => {
-                        RankedIssue::moderate(PRIORITY_PIXEL, &diff.description)
-                    }
-                    DiffSeverity::Minor => RankedIssue::minor(PRIORITY_PIXEL, &diff.description),
-                };
-                issues.push(ranked);
+                issues.push(RankedIssue::with_severity(
+                    diff.severity,
+                    PRIORITY_PIXEL,
+                    &diff.description,
+                ));
             }
             return issues;
         }
@@ -145,7 +175,10 @@ fn issues_from_pixel(metric: &PixelMetric) -> Vec<RankedIssue> {
     issues
 }
 
-fn issues_from_layout(metric: &LayoutMetric) -> Vec<RankedIssue> {
+fn issues_from_layout(
+    metric: &LayoutMetric,
+    calibration: &SeverityCalibration,
+) -> Vec<RankedIssue> {
     let mut issues = Vec::new();
 
     for region in &metric.diff_regions {
@@ -155,6 +188,12 @@ fn issues_from_layout(metric: &LayoutMetric) -> Vec<RankedIssue> {
             .map(|l| format!("'{}'", l))
             .or_else(|| region.element_type.clone())
             .unwrap_or_else(|| "element".to_string());
+        let element_desc = match region.region_count {
+            Some(count) if count > 1 => {
+                format!("{element_desc} (with {} overlapping)", count - 1)
+            }
+            _ => element_desc,
+        };
 
         let msg = match region.kind {
             LayoutDiffKind::MissingElement => {
@@ -172,22 +211,35 @@ fn issues_from_layout(metric: &LayoutMetric) -> Vec<RankedIssue> {
             LayoutDiffKind::SizeChange => {
                 format!("{} has a different size than the reference.", element_desc)
             }
+            LayoutDiffKind::SpacingMismatch => format!(
+                "Spacing differs on {}: {}.",
+                element_desc,
+                region.detail.as_deref().unwrap_or("gap or padding changed")
+            ),
+            LayoutDiffKind::AlignmentBreak => format!(
+                "{} is {}.",
+                element_desc,
+                region
+                    .detail
+                    .as_deref()
+                    .unwrap_or("no longer aligned as in the design")
+            ),
         };
 
-        let ranked = match region.kind {
-            LayoutDiffKind::MissingElement => RankedIssue::major(PRIORITY_LAYOUT, msg),
-            LayoutDiffKind::ExtraElement => RankedIssue::moderate(PRIORITY_LAYOUT, msg),
-            LayoutDiffKind::PositionShift | LayoutDiffKind::SizeChange => {
-                RankedIssue::moderate(PRIORITY_LAYOUT, msg)
-            }
-        };
-        issues.push(ranked);
+        issues.push(RankedIssue::with_severity(
+            calibration.layout(region.kind),
+            PRIORITY_LAYOUT,
+            msg,
+        ));
     }
 
     issues
 }
 
-fn issues_from_typography(metric: &TypographyMetric) -> Vec<RankedIssue> {
+fn issues_from_typography(
+    metric: &TypographyMetric,
+    calibration: &SeverityCalibration,
+) -> Vec<RankedIssue> {
     let mut issues = Vec::new();
 
     for diff in &metric.diffs {
@@ -196,10 +248,11 @@ fn issues_from_typography(metric: &TypographyMetric) -> Vec<RankedIssue> {
         }
 
         let element_id = diff
-            .element_id_ref
+            .label
             .as_ref()
-            .or(diff.element_id_impl.as_ref())
-            .cloned()
+            .map(|l| format!("'{}'", l))
+            .or_else(|| diff.element_id_ref.clone())
+            .or_else(|| diff.element_id_impl.clone())
             .unwrap_or_else(|| "text element".to_string());
 
         let issue_names: Vec<&str> = diff
@@ -207,44 +260,99 @@ fn issues_from_typography(metric: &TypographyMetric) -> Vec<RankedIssue> {
             .iter()
             .map(|i| match i {
                 TypographyIssue::FontFamilyMismatch => "font family",
+                TypographyIssue::FontNotLoaded => "font (not loaded)",
                 TypographyIssue::FontSizeDiff => "font size",
                 TypographyIssue::FontWeightDiff => "font weight",
                 TypographyIssue::LineHeightDiff => "line height",
                 TypographyIssue::LetterSpacingDiff => "letter spacing",
+                TypographyIssue::LineWrapDiff => "line wrapping",
+                TypographyIssue::BaselineShift => "baseline position",
+                TypographyIssue::VerticalRhythmDiff => "vertical rhythm",
             })
             .collect();
 
-        let msg = if issue_names.len() == 1 {
+        let fallback = diff
+            .details
+            .as_ref()
+            .filter(|_| diff.issues.contains(&TypographyIssue::FontNotLoaded))
+            .and_then(|details| {
+                Some((
+                    details.get("requestedFont")?.as_str()?,
+                    details.get("fallbackFont")?.as_str()?,
+                ))
+            });
+
+        let wrap = diff
+            .details
+            .as_ref()
+            .filter(|_| diff.issues == [TypographyIssue::LineWrapDiff])
+            .and_then(|details| {
+                Some((
+                    details.get("refLines")?.as_u64()?,
+                    details.get("implLines")?.as_u64()?,
+                ))
+            });
+
+        let detail = |key: &str| diff.details.as_ref()?.get(key)?.as_f64();
+        let shift =
+            detail("baselineShift").filter(|_| diff.issues == [TypographyIssue::BaselineShift]);
+        let rhythm = (diff.issues == [TypographyIssue::VerticalRhythmDiff])
+            .then(|| Some((detail("refBaselineGap")?, detail("implBaselineGap")?)))
+            .flatten();
+
+        let msg = if let Some((requested, fallback)) = fallback {
             format!(
-                "{} has a different {} than the design.",
-                element_id, issue_names[0]
+                "{}: font \"{}\" not loaded, fell back to {}.",
+                element_id, requested, fallback
             )
-        } else {
+        } else if let Some((ref_lines, impl_lines)) = wrap {
+            format!(
+                "{} wraps into {} line(s) instead of {} as in the design.",
+                element_id, impl_lines, ref_lines
+            )
+        } else if let Some(shift) = shift {
             format!(
-                "{} has different {} than the design.",
+                "{} sits {}px {} in its container than in the design.",
                 element_id,
-                issue_names.join(", ")
+                shift.abs(),
+                if shift > 0.0 { "lower" } else { "higher" }
+            )
+        } else if let Some((ref_gap, impl_gap)) = rhythm {
+            format!(
+                "{} is {}px below the text above (baseline to baseline) instead of {}px as in the design.",
+                element_id, impl_gap, ref_gap
             )
-        };
-
-        let ranked = if diff.issues.contains(&TypographyIssue::FontFamilyMismatch) {
-            RankedIssue::major(PRIORITY_TYPOGRAPHY, msg)
-        } else if diff.issues.contains(&TypographyIssue::FontSizeDiff)
-            || diff.issues.contains(&TypographyIssue::FontWeightDiff)
-            || diff.issues.contains(&TypographyIssue::LetterSpacingDiff)
-        {
-            RankedIssue::moderate(PRIORITY_TYPOGRAPHY, msg)
         } else {
-            RankedIssue::minor(PRIORITY_TYPOGRAPHY, msg)
+            let design = match &diff.style {
+                Some(style) => format!("text style {style}"),
+                None => "the design".to_string(),
+            };
+            if issue_names.len() == 1 {
+                format!(
+                    "{} has a different {} than {}.",
+                    element_id, issue_names[0], design
+                )
+            } else {
+                format!(
+                    "{} has different {} than {}.",
+                    element_id,
+                    issue_names.join(", "),
+                    design
+                )
+            }
         };
 
-        issues.push(ranked);
+        issues.push(RankedIssue::with_severity(
+            calibration.typography(&diff.issues),
+            PRIORITY_TYPOGRAPHY,
+            msg,
+        ));
     }
 
     issues
 }
 
-fn issues_from_color(metric: &ColorMetric) -> Vec<RankedIssue> {
+fn issues_from_color(metric: &ColorMetric, calibration: &SeverityCalibration) -> Vec<RankedIssue> {
     let mut issues = Vec::new();
 
     for diff in &metric.diffs {
@@ -252,25 +360,47 @@ fn issues_from_color(metric: &ColorMetric) -> Vec<RankedIssue> {
             ColorDiffKind::PrimaryColorShift => "Primary color shift",
             ColorDiffKind::AccentColorShift => "Accent color shift",
             ColorDiffKind::BackgroundColorShift => "Background color shift",
+            ColorDiffKind::GradientShift => "Gradient fill",
         };
 
-        let msg = format!(
-            "{} differs: expected {}, got {}.",
-            kind_desc, diff.ref_color, diff.impl_color
-        );
-
-        let ranked = match diff.kind {
-            ColorDiffKind::PrimaryColorShift => RankedIssue::major(PRIORITY_COLOR, msg),
-            ColorDiffKind::AccentColorShift => RankedIssue::major(PRIORITY_COLOR, msg),
-            ColorDiffKind::BackgroundColorShift => RankedIssue::minor(PRIORITY_COLOR, msg),
+        let expected = match &diff.style {
+            Some(style) => format!("{} ({})", style, diff.ref_color),
+            None => diff.ref_color.clone(),
+        };
+        let msg = match &diff.element_id {
+            Some(element) => format!(
+                "{} of {} differs: expected {}, got {}.",
+                kind_desc, element, expected, diff.impl_color
+            ),
+            None => format!(
+                "{} differs: expected {}, got {}.",
+                kind_desc, expected, diff.impl_color
+            ),
         };
-        issues.push(ranked);
+
+        issues.push(RankedIssue::with_severity(
+            calibration.color(diff),
+            PRIORITY_COLOR,
+            msg,
+        ));
     }
 
     issues
 }
 
-fn issues_from_content(metric: &ContentMetric) -> Vec<RankedIssue> {
+/// ` in 'CTA / Primary'` when the element holding `text` has a name.
+fn text_location(metric: &ContentMetric, text: &str) -> String {
+    metric
+        .text_labels
+        .get(text)
+        .map(|label| format!(" in '{}'", label))
+        .unwrap_or_default()
+}
+
+fn issues_from_content(
+    metric: &ContentMetric,
+    calibration: &SeverityCalibration,
+) -> Vec<RankedIssue> {
     let mut issues = Vec::new();
 
     if !metric.missing_text.is_empty() {
@@ -282,13 +412,19 @@ fn issues_from_content(metric: &ContentMetric) -> Vec<RankedIssue> {
                 } else {
                     text.clone()
                 };
-                issues.push(RankedIssue::major(
+                issues.push(RankedIssue::with_severity(
+                    calibration.missing_text,
                     PRIORITY_CONTENT,
-                    format!("Text '{}' is missing in the implementation.", truncated),
+                    format!(
+                        "Text '{}'{} is missing in the implementation.",
+                        truncated,
+                        text_location(metric, text)
+                    ),
                 ));
             }
         } else {
-            issues.push(RankedIssue::major(
+            issues.push(RankedIssue::with_severity(
+                calibration.missing_text,
                 PRIORITY_CONTENT,
                 format!("{} text elements are missing in the implementation.", count),
             ));
@@ -304,16 +440,19 @@ fn issues_from_content(metric: &ContentMetric) -> Vec<RankedIssue> {
                 } else {
                     text.clone()
                 };
-                issues.push(RankedIssue::minor(
+                issues.push(RankedIssue::with_severity(
+                    calibration.extra_text,
                     PRIORITY_CONTENT,
                     format!(
-                        "Extra text '{}' appears in implementation but not in design.",
-                        truncated
+                        "Extra text '{}'{} appears in implementation but not in design.",
+                        truncated,
+                        text_location(metric, text)
                     ),
                 ));
             }
         } else {
-            issues.push(RankedIssue::minor(
+            issues.push(RankedIssue::with_severity(
+                calibration.extra_text,
                 PRIORITY_CONTENT,
                 format!(
                     "{} extra text elements appear in implementation but not in design.",
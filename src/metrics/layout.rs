@@ -1,13 +1,33 @@
 use crate::error::DpcError;
-use crate::types::{BoundingBox, LayoutDiffKind, LayoutDiffRegion, LayoutMetric, NormalizedView};
+use crate::types::{
+    BoundingBox, EdgeInsets, LayoutDiffKind, LayoutDiffRegion, LayoutMetric, NormalizedView,
+    RegionUnit,
+};
 use crate::Result;
-
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::edge_boxes::EdgeBoxDetector;
+use super::importance::ImportanceModel;
+use super::region_weights::RegionWeights;
+use super::runner::has_layout_data;
+use super::text_detection::{on_text_line, TextDetector};
 use super::{Metric, MetricKind, MetricResult};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct LayoutSimilarity {
     pub iou_threshold: f32,
     pub match_threshold: f32,
+    /// Weights element matches by where the reference element sits.
+    pub region_weights: Option<Arc<RegionWeights>>,
+    /// Weights element matches by role/tag; `None` counts every element alike.
+    pub importance: Option<Arc<ImportanceModel>>,
+    /// Detect element boxes from the screenshots when neither view has a DOM
+    /// or Figma tree; `None` skips layout for image-vs-image comparisons.
+    pub pixel_boxes: Option<EdgeBoxDetector>,
+    /// With `pixel_boxes`, detected boxes that lie on a text line count as
+    /// text elements.
+    pub pixel_text: Option<TextDetector>,
 }
 
 impl Default for LayoutSimilarity {
@@ -15,14 +35,77 @@ impl Default for LayoutSimilarity {
         Self {
             iou_threshold: 0.5,
             match_threshold: 0.1,
+            region_weights: None,
+            importance: Some(Arc::new(ImportanceModel::default())),
+            pixel_boxes: None,
+            pixel_text: None,
         }
     }
 }
 
+/// Share of a detected box a text line must cover for it to be text.
+const TEXT_LINE_COVERAGE: f32 = 0.6;
+/// Gap and padding differences below this many pixels are rounding noise.
+const SPACING_TOLERANCE_PX: f32 = 2.0;
+/// Edges within this many pixels count as aligned in the design.
+const ALIGNMENT_TOLERANCE_PX: f32 = 1.0;
+/// An alignment is broken once the implementation is off by more than this.
+const ALIGNMENT_BREAK_PX: f32 = 4.0;
+
 #[derive(Debug, Clone, Copy)]
-struct LayoutElement {
+struct LayoutElement<'a> {
     kind: ElementKind,
     bbox: BoundingBox,
+    /// Unit of `bbox`, reported with the findings about this element.
+    unit: RegionUnit,
+    /// Figma layer name or DOM `data-testid`/`aria-label`, for findings.
+    label: Option<&'a str>,
+    /// Index of the enclosing element in the same list; `None` for roots and
+    /// for boxes detected in screenshots.
+    parent: Option<usize>,
+    /// Container spacing from Figma auto layout or a flexbox.
+    spacing: Option<Spacing>,
+    /// Weight from the [`ImportanceModel`]; 1.0 without one.
+    importance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Spacing {
+    source: &'static str,
+    vertical: bool,
+    /// `None` when the main-axis gap is distributed (`space-between`).
+    gap: Option<f32>,
+    padding: EdgeInsets,
+}
+
+impl Spacing {
+    fn from_figma(layout: &crate::types::AutoLayout) -> Self {
+        Spacing {
+            source: "auto-layout",
+            vertical: layout.direction == crate::types::LayoutDirection::Vertical,
+            gap: (layout.primary_align != crate::types::AutoLayoutAlign::SpaceBetween)
+                .then_some(layout.gap),
+            padding: layout.padding,
+        }
+    }
+
+    fn from_dom(flex: &crate::types::FlexLayout) -> Self {
+        Spacing {
+            source: "flexbox",
+            vertical: flex.is_column(),
+            gap: (flex.justify_content.as_deref() != Some("space-between"))
+                .then(|| flex.main_gap()),
+            padding: flex.padding,
+        }
+    }
+
+    fn direction(&self) -> &'static str {
+        if self.vertical {
+            "vertical"
+        } else {
+            "horizontal"
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,14 +132,33 @@ impl ElementKind {
 }
 
 impl LayoutSimilarity {
-    fn extract_elements(view: &NormalizedView) -> Vec<LayoutElement> {
+    fn extract_elements<'a>(&self, view: &'a NormalizedView) -> Vec<LayoutElement<'a>> {
+        let model = self.importance.as_deref();
         if let Some(dom) = &view.dom {
+            let index: HashMap<&str, usize> = dom
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(idx, node)| (node.id.as_str(), idx))
+                .collect();
             let elements = dom
                 .nodes
                 .iter()
                 .map(|node| LayoutElement {
+                    parent: node
+                        .parent
+                        .as_deref()
+                        .and_then(|parent| index.get(parent).copied()),
                     kind: element_kind_from_dom(node),
                     bbox: node.bounding_box,
+                    unit: RegionUnit::Css,
+                    label: node.label(),
+                    spacing: node
+                        .computed_style
+                        .as_ref()
+                        .and_then(|style| style.flex.as_ref())
+                        .map(Spacing::from_dom),
+                    importance: model.map_or(1.0, |m| m.dom_importance(node)),
                 })
                 .collect::<Vec<_>>();
             if !elements.is_empty() {
@@ -65,12 +167,36 @@ impl LayoutSimilarity {
         }
 
         if let Some(figma) = &view.figma_tree {
+            let index: HashMap<&str, usize> = figma
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(idx, node)| (node.id.as_str(), idx))
+                .collect();
+            let mut parents = vec![None; figma.nodes.len()];
+            for (idx, node) in figma.nodes.iter().enumerate() {
+                for child in &node.children {
+                    if let Some(&child) = index.get(child.as_str()) {
+                        parents[child] = Some(idx);
+                    }
+                }
+            }
             let elements = figma
                 .nodes
                 .iter()
-                .map(|node| LayoutElement {
+                .zip(parents)
+                .map(|(node, parent)| LayoutElement {
+                    parent,
                     kind: element_kind_from_figma(node),
-                    bbox: node.bounding_box,
+                    bbox: node.layout_box(),
+                    unit: if is_normalized(&node.layout_box()) {
+                        RegionUnit::Normalized
+                    } else {
+                        RegionUnit::Pixel
+                    },
+                    label: node.label(),
+                    spacing: node.auto_layout.as_ref().map(Spacing::from_figma),
+                    importance: model.map_or(1.0, |m| m.figma_importance(node)),
                 })
                 .collect::<Vec<_>>();
             if !elements.is_empty() {
@@ -81,126 +207,317 @@ impl LayoutSimilarity {
         Vec::new()
     }
 
+    /// The detector to use when neither view has structure to match.
+    fn pixel_detector(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Option<&EdgeBoxDetector> {
+        self.pixel_boxes
+            .as_ref()
+            .filter(|_| !has_layout_data(reference) && !has_layout_data(implementation))
+    }
+
+    fn detect_elements(
+        &self,
+        detector: &EdgeBoxDetector,
+        view: &NormalizedView,
+    ) -> Result<Vec<LayoutElement<'static>>> {
+        let image = image::open(&view.screenshot_path).map_err(DpcError::from)?;
+        let importance = self.importance.as_deref().map_or(1.0, |m| m.default);
+        let lines = self
+            .pixel_text
+            .as_ref()
+            .map(|text| text.detect(&image))
+            .unwrap_or_default();
+        Ok(detector
+            .detect(&image)
+            .into_iter()
+            .map(|bbox| LayoutElement {
+                kind: if on_text_line(&lines, &bbox, TEXT_LINE_COVERAGE) {
+                    ElementKind::Text
+                } else {
+                    ElementKind::Other
+                },
+                bbox,
+                unit: RegionUnit::Pixel,
+                label: None,
+                parent: None,
+                spacing: None,
+                importance,
+            })
+            .collect())
+    }
+
     pub fn compute_metric(
         &self,
         reference: &NormalizedView,
         implementation: &NormalizedView,
     ) -> Result<LayoutMetric> {
-        let ref_elements = LayoutSimilarity::extract_elements(reference);
-        if ref_elements.is_empty() {
-            return Err(DpcError::Config(
-                "No layout elements available in reference view".to_string(),
-            ));
-        }
-        let mut impl_elements = LayoutSimilarity::extract_elements(implementation);
+        let (ref_elements, impl_elements) = match self.pixel_detector(reference, implementation) {
+            Some(detector) => {
+                let ref_elements = self.detect_elements(detector, reference)?;
+                let impl_elements = self.detect_elements(detector, implementation)?;
+                if ref_elements.is_empty() && impl_elements.is_empty() {
+                    return Ok(LayoutMetric {
+                        score: 1.0,
+                        diff_regions: Vec::new(),
+                    });
+                }
+                (ref_elements, impl_elements)
+            }
+            None => {
+                let ref_elements = self.extract_elements(reference);
+                if ref_elements.is_empty() {
+                    return Err(DpcError::Config(
+                        "No layout elements available in reference view".to_string(),
+                    ));
+                }
+                (ref_elements, self.extract_elements(implementation))
+            }
+        };
 
         if impl_elements.is_empty() {
             let diff_regions = ref_elements
                 .iter()
-                .map(|ref_el| LayoutDiffRegion {
-                    x: ref_el.bbox.x,
-                    y: ref_el.bbox.y,
-                    width: ref_el.bbox.width,
-                    height: ref_el.bbox.height,
-                    kind: LayoutDiffKind::MissingElement,
-                    element_type: Some(ref_el.kind.as_str().to_string()),
-                    label: None,
-                })
+                .map(|ref_el| element_region(ref_el, LayoutDiffKind::MissingElement))
                 .collect::<Vec<_>>();
 
             return Ok(LayoutMetric {
                 score: 0.0,
-                diff_regions,
+                diff_regions: merge_overlapping_regions(diff_regions),
             });
         }
 
-        let ref_count = ref_elements.len();
-        let impl_count = impl_elements.len();
-
-        let mut matches = Vec::new();
-
-        for ref_el in &ref_elements {
-            if let Some((idx, iou)) = best_match(ref_el, &impl_elements, self.match_threshold) {
-                matches.push((ref_el, impl_elements[idx], iou));
-                impl_elements.remove(idx);
-            }
-        }
+        let spacing_regions = self.spacing_mismatches(&ref_elements, &impl_elements);
+        let matching = self.match_hierarchically(&ref_elements, &impl_elements);
+        let matches: Vec<(&LayoutElement, &LayoutElement, f32)> = matching
+            .pairs
+            .iter()
+            .map(|pair| {
+                (
+                    &ref_elements[pair.ref_idx],
+                    &impl_elements[pair.impl_idx],
+                    pair.iou,
+                )
+            })
+            .collect();
+        let extras: Vec<&LayoutElement> = impl_elements
+            .iter()
+            .zip(&matching.impl_taken)
+            .filter(|(_, taken)| !**taken)
+            .map(|(el, _)| el)
+            .collect();
+
+        // Unweighted, every element counts 1.0 and these reduce to plain counts.
+        let (view_w, view_h) = (reference.width as f32, reference.height as f32);
+        let weight_of = |el: &LayoutElement| {
+            let region = self.region_weights.as_ref().map_or(1.0, |weights| {
+                element_weight(weights, el.bbox, view_w, view_h)
+            });
+            region * el.importance
+        };
 
-        let matched = matches.len() as f32;
-        let max_count = ref_count.max(impl_count) as f32;
-        let match_rate = if max_count == 0.0 {
+        let matched: f32 = matches.iter().map(|(r, _, _)| weight_of(r)).sum();
+        let ref_total: f32 = ref_elements.iter().map(weight_of).sum();
+        let extra_total: f32 = extras.iter().map(|el| weight_of(el)).sum();
+        let max_total = ref_total.max(matched + extra_total);
+        let match_rate = if max_total == 0.0 {
             1.0
         } else {
-            matched / max_count
+            matched / max_total
         };
 
-        let avg_iou = if matches.is_empty() {
-            0.0
+        let avg_iou = if matched > 0.0 {
+            matches
+                .iter()
+                .map(|(r, _, iou)| weight_of(r) * iou)
+                .sum::<f32>()
+                / matched
         } else {
-            matches.iter().map(|(_, _, iou)| *iou).sum::<f32>() / matches.len() as f32
+            0.0
         };
 
         let score = 0.5 * match_rate + 0.5 * avg_iou;
 
         let mut diff_regions = Vec::new();
 
-        for ref_el in &ref_elements {
-            if !matches.iter().any(|(r, _, _)| std::ptr::eq(*r, ref_el)) {
-                diff_regions.push(LayoutDiffRegion {
-                    x: ref_el.bbox.x,
-                    y: ref_el.bbox.y,
-                    width: ref_el.bbox.width,
-                    height: ref_el.bbox.height,
-                    kind: LayoutDiffKind::MissingElement,
-                    element_type: Some(ref_el.kind.as_str().to_string()),
-                    label: None,
-                });
+        for (ref_el, matched_to) in ref_elements.iter().zip(&matching.ref_matched) {
+            if matched_to.is_none() {
+                diff_regions.push(element_region(ref_el, LayoutDiffKind::MissingElement));
             }
         }
 
-        for extra in &impl_elements {
-            diff_regions.push(LayoutDiffRegion {
-                x: extra.bbox.x,
-                y: extra.bbox.y,
-                width: extra.bbox.width,
-                height: extra.bbox.height,
-                kind: LayoutDiffKind::ExtraElement,
-                element_type: Some(extra.kind.as_str().to_string()),
-                label: None,
-            });
+        for extra in &extras {
+            diff_regions.push(element_region(extra, LayoutDiffKind::ExtraElement));
         }
 
-        for (ref_el, impl_el, iou) in &matches {
-            let (pos_shift, size_change) = layout_deviations(ref_el.bbox, impl_el.bbox);
-            if *iou < self.iou_threshold || pos_shift {
-                diff_regions.push(LayoutDiffRegion {
-                    x: impl_el.bbox.x,
-                    y: impl_el.bbox.y,
-                    width: impl_el.bbox.width,
-                    height: impl_el.bbox.height,
-                    kind: LayoutDiffKind::PositionShift,
-                    element_type: Some(impl_el.kind.as_str().to_string()),
-                    label: None,
-                });
+        for pair in &matching.pairs {
+            let (ref_el, impl_el) = (&ref_elements[pair.ref_idx], &impl_elements[pair.impl_idx]);
+            // Judged against where the matched parent put it, so children
+            // that moved along with their section are not shifts of their own.
+            let expected = translate(ref_el.bbox, pair.parent_offset);
+            let (pos_shift, size_change) = layout_deviations(expected, impl_el.bbox);
+            if pair.iou < self.iou_threshold || pos_shift {
+                let nested = matching.matched_descendants(&ref_elements, pair.ref_idx);
+                let mut region = matched_region(ref_el, impl_el, LayoutDiffKind::PositionShift);
+                if nested > 0 {
+                    region.detail = Some(format!("section moved with {nested} nested element(s)"));
+                }
+                diff_regions.push(region);
             }
             if size_change {
-                diff_regions.push(LayoutDiffRegion {
-                    x: impl_el.bbox.x,
-                    y: impl_el.bbox.y,
-                    width: impl_el.bbox.width,
-                    height: impl_el.bbox.height,
-                    kind: LayoutDiffKind::SizeChange,
-                    element_type: Some(impl_el.kind.as_str().to_string()),
-                    label: None,
-                });
+                diff_regions.push(matched_region(ref_el, impl_el, LayoutDiffKind::SizeChange));
             }
         }
 
+        diff_regions.extend(alignment_breaks(
+            &ref_elements,
+            &impl_elements,
+            &matching.pairs,
+        ));
+        diff_regions.extend(spacing_regions);
+
         Ok(LayoutMetric {
             score,
-            diff_regions,
+            diff_regions: merge_overlapping_regions(diff_regions),
         })
     }
+
+    /// Match containers before their children: a reference element whose
+    /// parent matched is first looked for among the descendants of the
+    /// parent's match, with its box moved by the parent's offset, so a
+    /// section that moved as a whole keeps its children matched. Anything
+    /// not found that way is matched by absolute position.
+    fn match_hierarchically(
+        &self,
+        ref_elements: &[LayoutElement],
+        impl_elements: &[LayoutElement],
+    ) -> Matching {
+        let mut matching = Matching {
+            pairs: Vec::new(),
+            ref_matched: vec![None; ref_elements.len()],
+            impl_taken: vec![false; impl_elements.len()],
+        };
+        let depths: Vec<usize> = (0..ref_elements.len())
+            .map(|idx| ancestors(ref_elements, idx).count())
+            .collect();
+        let mut order: Vec<usize> = (0..ref_elements.len()).collect();
+        order.sort_by_key(|idx| depths[*idx]);
+
+        for ref_idx in order {
+            let ref_el = &ref_elements[ref_idx];
+            let parent_match = ref_el.parent.and_then(|parent| {
+                let pair = &matching.pairs[matching.ref_matched[parent]?];
+                Some((
+                    pair.impl_idx,
+                    offset(ref_elements[parent].bbox, impl_elements[pair.impl_idx].bbox),
+                ))
+            });
+            let scoped = parent_match.and_then(|(impl_parent, parent_offset)| {
+                let moved = LayoutElement {
+                    bbox: translate(ref_el.bbox, parent_offset),
+                    ..*ref_el
+                };
+                best_match(&moved, impl_elements, self.match_threshold, |idx| {
+                    !matching.impl_taken[idx]
+                        && ancestors(impl_elements, idx).any(|a| a == impl_parent)
+                })
+                .map(|(idx, iou)| (idx, iou, parent_offset))
+            });
+            let found = scoped.or_else(|| {
+                best_match(ref_el, impl_elements, self.match_threshold, |idx| {
+                    !matching.impl_taken[idx]
+                })
+                .map(|(idx, iou)| (idx, iou, parent_match.map_or((0.0, 0.0), |(_, o)| o)))
+            });
+            if let Some((impl_idx, iou, parent_offset)) = found {
+                matching.impl_taken[impl_idx] = true;
+                matching.ref_matched[ref_idx] = Some(matching.pairs.len());
+                matching.pairs.push(MatchedPair {
+                    ref_idx,
+                    impl_idx,
+                    iou,
+                    parent_offset,
+                });
+            }
+        }
+        // Report in reference order, as the flat matching did.
+        matching.pairs.sort_by_key(|pair| pair.ref_idx);
+        for (position, pair) in matching.pairs.iter().enumerate() {
+            matching.ref_matched[pair.ref_idx] = Some(position);
+        }
+        matching
+    }
+
+    /// Compare gap, padding and direction of containers that overlap well,
+    /// e.g. a Figma auto-layout frame and the flexbox implementing it.
+    /// Reported only, not scored.
+    fn spacing_mismatches(
+        &self,
+        ref_elements: &[LayoutElement],
+        impl_elements: &[LayoutElement],
+    ) -> Vec<LayoutDiffRegion> {
+        let mut candidates: Vec<(&LayoutElement, Spacing)> = impl_elements
+            .iter()
+            .filter_map(|el| el.spacing.map(|spacing| (el, spacing)))
+            .collect();
+        let mut regions = Vec::new();
+        for ref_el in ref_elements {
+            let Some(design) = ref_el.spacing else {
+                continue;
+            };
+            let best = candidates
+                .iter()
+                .enumerate()
+                .map(|(idx, (el, _))| (idx, iou(&ref_el.bbox, &el.bbox)))
+                .filter(|(_, overlap)| *overlap >= self.iou_threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((idx, _)) = best else {
+                continue;
+            };
+            let (impl_el, actual) = candidates.remove(idx);
+            let Some(detail) = describe_spacing_mismatch(&design, &actual) else {
+                continue;
+            };
+            regions.push(LayoutDiffRegion {
+                detail: Some(detail),
+                ..matched_region(ref_el, impl_el, LayoutDiffKind::SpacingMismatch)
+            });
+        }
+        regions
+    }
+}
+
+/// E.g. "design uses 16px gap auto-layout; implementation flexbox gap is 8px".
+fn describe_spacing_mismatch(design: &Spacing, actual: &Spacing) -> Option<String> {
+    let mut parts = Vec::new();
+    if design.vertical != actual.vertical {
+        parts.push(format!(
+            "design {} is {}; implementation {} is {}",
+            design.source,
+            design.direction(),
+            actual.source,
+            actual.direction()
+        ));
+    } else if let (Some(expected), Some(gap)) = (design.gap, actual.gap) {
+        if (expected - gap).abs() > SPACING_TOLERANCE_PX {
+            parts.push(format!(
+                "design uses {}px gap {}; implementation {} gap is {}px",
+                expected.round(),
+                design.source,
+                actual.source,
+                gap.round()
+            ));
+        }
+    }
+    if design.padding.max_difference(&actual.padding) > SPACING_TOLERANCE_PX {
+        parts.push(format!(
+            "design padding is {}; implementation padding is {}",
+            design.padding, actual.padding
+        ));
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
 }
 
 fn element_kind_from_dom(node: &crate::types::DomNode) -> ElementKind {
@@ -225,7 +542,24 @@ fn element_kind_from_figma(node: &crate::types::FigmaNode) -> ElementKind {
     }
 }
 
-fn iou(a: &BoundingBox, b: &BoundingBox) -> f32 {
+/// Whether `bbox` is in normalized (0.0 - 1.0) view coordinates.
+fn is_normalized(bbox: &BoundingBox) -> bool {
+    bbox.x >= 0.0
+        && bbox.y >= 0.0
+        && bbox.x + bbox.width <= 1.0 + f32::EPSILON
+        && bbox.y + bbox.height <= 1.0 + f32::EPSILON
+}
+
+/// Region weight at the element's center, in the view's pixel frame.
+fn element_weight(weights: &RegionWeights, bbox: BoundingBox, width: f32, height: f32) -> f32 {
+    let normalized = is_normalized(&bbox);
+    let scale = |v: f32, extent: f32| if normalized { v * extent } else { v };
+    let cx = scale(bbox.x + bbox.width / 2.0, width);
+    let cy = scale(bbox.y + bbox.height / 2.0, height);
+    weights.weight_at(cx, cy, width, height)
+}
+
+pub(super) fn iou(a: &BoundingBox, b: &BoundingBox) -> f32 {
     let ax2 = a.x + a.width;
     let ay2 = a.y + a.height;
     let bx2 = b.x + b.width;
@@ -252,14 +586,190 @@ fn iou(a: &BoundingBox, b: &BoundingBox) -> f32 {
     }
 }
 
+/// An edge or center line two elements can share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Top,
+    Right,
+    Bottom,
+    CenterX,
+    CenterY,
+}
+
+impl Alignment {
+    const ALL: [Alignment; 6] = [
+        Alignment::Left,
+        Alignment::Top,
+        Alignment::Right,
+        Alignment::Bottom,
+        Alignment::CenterX,
+        Alignment::CenterY,
+    ];
+
+    fn position(self, bbox: &BoundingBox) -> f32 {
+        match self {
+            Alignment::Left => bbox.x,
+            Alignment::Top => bbox.y,
+            Alignment::Right => bbox.x + bbox.width,
+            Alignment::Bottom => bbox.y + bbox.height,
+            Alignment::CenterX => bbox.x + bbox.width / 2.0,
+            Alignment::CenterY => bbox.y + bbox.height / 2.0,
+        }
+    }
+
+    /// E.g. "left-aligned with 'Title' in design but indented 12px in
+    /// implementation"; `forward` when the element moved right or down.
+    fn describe(self, partner: &str, delta: &str, forward: bool) -> String {
+        let pick = |yes: &str, no: &str| if forward { yes } else { no }.to_string();
+        let (relation, drift) = match self {
+            Alignment::Left => ("left-aligned", pick("indented", "outdented") + " " + delta),
+            Alignment::Right => (
+                "right-aligned",
+                format!("ends {delta} further {}", pick("right", "left")),
+            ),
+            Alignment::Top => (
+                "top-aligned",
+                format!("{delta} {}", pick("lower", "higher")),
+            ),
+            Alignment::Bottom => (
+                "bottom-aligned",
+                format!("{delta} {}", pick("lower", "higher")),
+            ),
+            Alignment::CenterX => (
+                "centered horizontally",
+                format!("{delta} {} of center", pick("right", "left")),
+            ),
+            Alignment::CenterY => (
+                "centered vertically",
+                format!("{delta} {} center", pick("below", "above")),
+            ),
+        };
+        format!("{relation} with {partner} in design but {drift} in implementation")
+    }
+}
+
+/// Alignments between matched siblings that the implementation broke: for
+/// every matched element, the first alignment it shared in the design with
+/// an earlier sibling (nearest first) that no longer holds. One finding per
+/// element at most, so a moved element is not reported against every
+/// neighbour it used to line up with. Reported only, not scored.
+fn alignment_breaks(
+    ref_elements: &[LayoutElement],
+    impl_elements: &[LayoutElement],
+    pairs: &[MatchedPair],
+) -> Vec<LayoutDiffRegion> {
+    let mut regions = Vec::new();
+    for (position, pair) in pairs.iter().enumerate() {
+        let (ref_el, impl_el) = (&ref_elements[pair.ref_idx], &impl_elements[pair.impl_idx]);
+        let normalized = is_normalized(&ref_el.bbox);
+        let scale = if normalized { 0.001 } else { 1.0 };
+        let break_at = ALIGNMENT_BREAK_PX * scale;
+        let siblings = pairs[..position]
+            .iter()
+            .rev()
+            .filter(|other| ref_elements[other.ref_idx].parent == ref_el.parent);
+        'siblings: for other in siblings {
+            let (ref_other, impl_other) =
+                (&ref_elements[other.ref_idx], &impl_elements[other.impl_idx]);
+            // Nested wrappers with the same box align on everything.
+            if iou(&ref_el.bbox, &ref_other.bbox) > 0.9 {
+                continue;
+            }
+            for alignment in Alignment::ALL {
+                let design = alignment.position(&ref_el.bbox) - alignment.position(&ref_other.bbox);
+                if design.abs() > ALIGNMENT_TOLERANCE_PX * scale {
+                    continue;
+                }
+                let actual =
+                    alignment.position(&impl_el.bbox) - alignment.position(&impl_other.bbox);
+                if (actual - design).abs() <= break_at {
+                    continue;
+                }
+                let delta = if normalized {
+                    format!("{:.1}%", (actual - design).abs() * 100.0)
+                } else {
+                    format!("{}px", (actual - design).abs().round())
+                };
+                let partner = match ref_other.label {
+                    Some(label) => format!("'{label}'"),
+                    None => format!("the {}", ref_other.kind.as_str()),
+                };
+                regions.push(LayoutDiffRegion {
+                    detail: Some(alignment.describe(&partner, &delta, actual > design)),
+                    ..matched_region(ref_el, impl_el, LayoutDiffKind::AlignmentBreak)
+                });
+                break 'siblings;
+            }
+        }
+    }
+    regions
+}
+
+/// A reference element and its implementation match.
+#[derive(Debug, Clone, Copy)]
+struct MatchedPair {
+    ref_idx: usize,
+    impl_idx: usize,
+    iou: f32,
+    /// How far the matched parent moved; `(0, 0)` for roots.
+    parent_offset: (f32, f32),
+}
+
+#[derive(Debug)]
+struct Matching {
+    pairs: Vec<MatchedPair>,
+    /// Index into `pairs` for every reference element.
+    ref_matched: Vec<Option<usize>>,
+    impl_taken: Vec<bool>,
+}
+
+impl Matching {
+    /// How many descendants of reference element `idx` were matched.
+    fn matched_descendants(&self, ref_elements: &[LayoutElement], idx: usize) -> usize {
+        (0..ref_elements.len())
+            .filter(|&other| {
+                self.ref_matched[other].is_some()
+                    && ancestors(ref_elements, other).any(|a| a == idx)
+            })
+            .count()
+    }
+}
+
+/// Indices of the ancestors of element `idx`, nearest first.
+fn ancestors<'e>(elements: &'e [LayoutElement], idx: usize) -> impl Iterator<Item = usize> + 'e {
+    let mut next = elements[idx].parent;
+    // A malformed tree with a cycle stops after visiting every element once.
+    let mut remaining = elements.len();
+    std::iter::from_fn(move || {
+        let current = next.filter(|_| remaining > 0)?;
+        remaining -= 1;
+        next = elements.get(current).and_then(|el| el.parent);
+        Some(current)
+    })
+}
+
+fn offset(from: BoundingBox, to: BoundingBox) -> (f32, f32) {
+    (to.x - from.x, to.y - from.y)
+}
+
+fn translate(bbox: BoundingBox, (dx, dy): (f32, f32)) -> BoundingBox {
+    BoundingBox {
+        x: bbox.x + dx,
+        y: bbox.y + dy,
+        ..bbox
+    }
+}
+
 fn best_match(
     target: &LayoutElement,
     candidates: &[LayoutElement],
     match_threshold: f32,
+    available: impl Fn(usize) -> bool,
 ) -> Option<(usize, f32)> {
     let mut best: Option<(usize, f32)> = None;
     for (idx, cand) in candidates.iter().enumerate() {
-        if cand.kind != target.kind {
+        if cand.kind != target.kind || !available(idx) {
             continue;
         }
         let overlap = iou(&target.bbox, &cand.bbox);
@@ -274,6 +784,81 @@ fn best_match(
     best
 }
 
+fn element_region(el: &LayoutElement, kind: LayoutDiffKind) -> LayoutDiffRegion {
+    LayoutDiffRegion {
+        fingerprint: None,
+        x: el.bbox.x,
+        y: el.bbox.y,
+        width: el.bbox.width,
+        height: el.bbox.height,
+        unit: Some(el.unit),
+        kind,
+        element_type: Some(el.kind.as_str().to_string()),
+        label: el.label.map(str::to_string),
+        detail: None,
+        region_count: None,
+        pixel_bounds: None,
+        css_bounds: None,
+    }
+}
+
+/// Region of a matched pair at the implementation's box, named after the
+/// design element when it has a name.
+fn matched_region(
+    ref_el: &LayoutElement,
+    impl_el: &LayoutElement,
+    kind: LayoutDiffKind,
+) -> LayoutDiffRegion {
+    LayoutDiffRegion {
+        label: ref_el.label.or(impl_el.label).map(str::to_string),
+        ..element_region(impl_el, kind)
+    }
+}
+
+/// Merge overlapping regions of the same kind (and detail) into their union,
+/// so a missing card and its missing children are reported once, with
+/// `region_count` saying how many differences the region stands for.
+fn merge_overlapping_regions(regions: Vec<LayoutDiffRegion>) -> Vec<LayoutDiffRegion> {
+    let mut merged: Vec<LayoutDiffRegion> = Vec::new();
+    for mut region in regions {
+        // The union grows with every merge, so keep absorbing until nothing
+        // merged so far overlaps it; it takes the earliest absorbed slot.
+        let mut slot = merged.len();
+        while let Some(idx) = merged.iter().position(|other| {
+            other.kind == region.kind && other.detail == region.detail && overlaps(other, &region)
+        }) {
+            region = merge_pair(region, merged.remove(idx));
+            slot = slot.min(idx);
+        }
+        merged.insert(slot.min(merged.len()), region);
+    }
+    merged
+}
+
+/// Union of two regions, keeping the element type and label of the larger.
+fn merge_pair(a: LayoutDiffRegion, b: LayoutDiffRegion) -> LayoutDiffRegion {
+    let count = a.region_count.unwrap_or(1) + b.region_count.unwrap_or(1);
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    let mut union = if b.width * b.height > a.width * a.height {
+        b
+    } else {
+        a
+    };
+    union.x = x;
+    union.y = y;
+    union.width = right - x;
+    union.height = bottom - y;
+    union.region_count = Some(count);
+    union
+}
+
+/// Whether two regions share a positive area.
+fn overlaps(a: &LayoutDiffRegion, b: &LayoutDiffRegion) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
 fn layout_deviations(reference: BoundingBox, implementation: BoundingBox) -> (bool, bool) {
     let ref_cx = reference.x + reference.width / 2.0;
     let ref_cy = reference.y + reference.height / 2.0;
@@ -306,4 +891,8 @@ impl Metric for LayoutSimilarity {
         let metric = self.compute_metric(reference, implementation)?;
         Ok(MetricResult::Layout(metric))
     }
+
+    fn has_data(&self, reference: &NormalizedView, implementation: &NormalizedView) -> bool {
+        has_layout_data(reference) || self.pixel_detector(reference, implementation).is_some()
+    }
 }
@@ -6,17 +6,39 @@
 //! - Typography similarity (font properties)
 //! - Color palette similarity (k-means clustering)
 //! - Content similarity (text matching)
+//! - Semantic similarity (screenshot embeddings, opt-in)
+//!
+//! Metrics implement the synchronous [`Metric`] trait or the async
+//! [`MetricV2`] trait, which receives a [`MetricContext`].
 
 // Submodules
 mod clustering;
 mod color;
+mod color_drift;
 mod content;
+mod content_formats;
+mod content_placeholders;
+mod context;
+mod edge_boxes;
+mod embedding;
+mod gradient;
+mod importance;
 mod issues;
 mod layout;
+#[cfg(any(feature = "saliency", feature = "embeddings"))]
+mod onnx;
+mod palette_sprawl;
 mod pixel;
+mod region_weights;
 mod runner;
+mod saliency;
+mod sampling;
 mod scoring;
 mod semantic;
+mod severity;
+mod stream;
+mod text_detection;
+mod transparency;
 mod typography;
 
 #[cfg(test)]
@@ -27,14 +49,38 @@ pub use clustering::{
     cluster_regions, cluster_regions_image_aware, ClusteredRegion, ClusteringConfig,
     ImageAwareClusteringConfig,
 };
+pub(crate) use color::srgb_delta_e;
 pub use color::ColorPaletteMetric;
-pub use content::ContentSimilarity;
-pub use issues::generate_top_issues;
+pub use color_drift::{element_color_drift, ElementColorDrift};
+pub use content::{ContentMatchMode, ContentSimilarity, SynonymTable};
+pub use content_formats::FormatNormalization;
+pub use context::{
+    default_metrics_v2, run_metrics_v2, ArtifactSink, MetricAdapter, MetricContext, MetricV2,
+};
+pub use edge_boxes::EdgeBoxDetector;
+pub use embedding::{ImageEncoder, SemanticSimilarity};
+pub use gradient::{parse_css_color, parse_css_gradient};
+pub use importance::ImportanceModel;
+pub use issues::{generate_top_issues, generate_top_issues_with};
 pub use layout::LayoutSimilarity;
-pub use pixel::{cluster_diff_regions, PixelDiffThresholds, PixelSimilarity};
+pub use palette_sprawl::{NearDuplicatePair, PaletteColor, PaletteReport, PaletteSprawl};
+pub use pixel::{
+    cluster_diff_regions, cluster_diff_regions_with_density, NoiseSuppression, PixelDiffSpace,
+    PixelDiffThresholds, PixelSimilarity,
+};
+pub use region_weights::{RegionWeights, WeightedRegion};
 pub use runner::{default_metrics, run_metrics, Metric, MetricKind, MetricResult};
-pub use scoring::{calculate_combined_score, ScoreWeights};
+pub use saliency::{SaliencyEstimator, SaliencyHeuristic, SaliencyMap, SaliencyModel};
+pub use sampling::{MetricInterval, SampleStats, ScoreInterval, UNSTABLE_STD_DEV};
+pub use scoring::{calculate_combined_score, metric_score, ScoreWeights};
 pub use semantic::{
     analyze_regions_batch, SemanticAnalyzer, SemanticAnalyzerConfig, SemanticDiff, SemanticDiffType,
 };
-pub use typography::TypographySimilarity;
+pub use severity::{DeltaEThresholds, SeverityCalibration, SeverityProfile};
+pub use stream::{
+    compare_stream, run_metrics_until, BudgetedScores, CompareEvent, CompareStreamOptions,
+    SharedMetric,
+};
+pub use text_detection::TextDetector;
+pub use transparency::TransparencyMode;
+pub use typography::{TypeSpecimenRow, TypographyReportRow, TypographySimilarity};
@@ -0,0 +1,204 @@
+//! Image models in ONNX format, run on the CPU with tract.
+//!
+//! Models take one RGB image as a `1x3xHxW` float tensor. A fixed height and
+//! width are read from the model; dynamic ones default to 224.
+
+use std::fmt;
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use tract_onnx::prelude::*;
+use tract_onnx::tract_hir::internal::{DimLike, Factoid};
+
+use crate::{DpcError, Result};
+
+/// Input side used when the model leaves its height or width open.
+const DEFAULT_SIDE: usize = 224;
+
+/// How a screenshot becomes the model input: RGB scaled to 0-1, then
+/// `(value - mean) / std` per channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Preprocess {
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+    /// Scale to cover the input and crop the center, keeping the aspect
+    /// ratio, instead of squashing the whole screenshot.
+    pub center_crop: bool,
+}
+
+/// A loaded, optimized ONNX image model.
+pub(super) struct ImageModel {
+    plan: TypedRunnableModel<TypedModel>,
+    width: u32,
+    height: u32,
+    preprocess: Preprocess,
+}
+
+impl fmt::Debug for ImageModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageModel")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("preprocess", &self.preprocess)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ImageModel {
+    pub(super) fn load(path: &Path, preprocess: Preprocess) -> Result<Self> {
+        let load_error = |e: TractError| {
+            DpcError::Config(format!(
+                "Failed to load ONNX model {}: {e:#}",
+                path.display()
+            ))
+        };
+        let model = tract_onnx::onnx()
+            .model_for_path(path)
+            .map_err(load_error)?;
+        let fact = model.input_fact(0).map_err(load_error)?;
+        let side = |axis: usize| {
+            fact.shape
+                .dim(axis)
+                .and_then(|dim| dim.concretize())
+                .and_then(|dim| dim.to_usize().ok())
+                .filter(|side| *side > 0)
+                .unwrap_or(DEFAULT_SIDE)
+        };
+        let (height, width) = (side(2), side(3));
+        let plan = model
+            .with_input_fact(0, f32::fact([1, 3, height, width]).into())
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(load_error)?;
+        Ok(Self {
+            plan,
+            width: width as u32,
+            height: height as u32,
+            preprocess,
+        })
+    }
+
+    /// Shape and values of the model's first output for `image`.
+    pub(super) fn run(&self, image: &DynamicImage) -> Result<(Vec<usize>, Vec<f32>)> {
+        let run_error = |e: TractError| DpcError::metric(format!("ONNX model failed: {e:#}"));
+        let outputs = self
+            .plan
+            .run(tvec!(self.input(image).into()))
+            .map_err(run_error)?;
+        let output = outputs
+            .first()
+            .ok_or_else(|| DpcError::metric("ONNX model returned no output"))?;
+        let values = output.to_array_view::<f32>().map_err(run_error)?;
+        Ok((values.shape().to_vec(), values.iter().copied().collect()))
+    }
+
+    fn input(&self, image: &DynamicImage) -> Tensor {
+        let (width, height) = (self.width, self.height);
+        let resized = if self.preprocess.center_crop {
+            image.resize_to_fill(width, height, FilterType::CatmullRom)
+        } else {
+            image.resize_exact(width, height, FilterType::Triangle)
+        };
+        let rgb = resized.to_rgb32f();
+        let Preprocess { mean, std, .. } = self.preprocess;
+        tract_ndarray::Array4::from_shape_fn(
+            (1, 3, height as usize, width as usize),
+            |(_, channel, y, x)| {
+                (rgb.get_pixel(x as u32, y as u32)[channel] - mean[channel]) / std[channel]
+            },
+        )
+        .into()
+    }
+}
+
+/// Tiny single-node ONNX models for tests.
+#[cfg(test)]
+pub(super) mod test_models {
+    use std::path::Path;
+
+    use prost::Message;
+    use tract_onnx::pb::attribute_proto::AttributeType;
+    use tract_onnx::pb::tensor_shape_proto::{dimension, Dimension};
+    use tract_onnx::pb::{
+        type_proto, AttributeProto, GraphProto, ModelProto, NodeProto, OperatorSetIdProto,
+        TensorShapeProto, TypeProto, ValueInfoProto,
+    };
+
+    /// Integer-list attribute, e.g. `axes`.
+    #[cfg_attr(not(feature = "saliency"), allow(dead_code))]
+    pub(crate) fn ints(name: &str, values: &[i64]) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            r#type: AttributeType::Ints as i32,
+            ints: values.to_vec(),
+            ..AttributeProto::default()
+        }
+    }
+
+    /// Integer attribute, e.g. `keepdims`.
+    #[cfg_attr(not(feature = "saliency"), allow(dead_code))]
+    pub(crate) fn int(name: &str, value: i64) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            r#type: AttributeType::Int as i32,
+            i: value,
+            ..AttributeProto::default()
+        }
+    }
+
+    /// Write a model applying `op_type` to a float `image` input of shape
+    /// `dims` (`None` marks a dynamic dimension).
+    pub(crate) fn write(
+        path: &Path,
+        dims: [Option<i64>; 4],
+        op_type: &str,
+        attribute: Vec<AttributeProto>,
+    ) {
+        let tensor = |dims: Option<[Option<i64>; 4]>| TypeProto {
+            value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                elem_type: 1,
+                shape: dims.map(|dims| TensorShapeProto {
+                    dim: dims
+                        .iter()
+                        .map(|dim| Dimension {
+                            value: Some(match dim {
+                                Some(size) => dimension::Value::DimValue(*size),
+                                None => dimension::Value::DimParam("n".to_string()),
+                            }),
+                            ..Dimension::default()
+                        })
+                        .collect(),
+                }),
+            })),
+            ..TypeProto::default()
+        };
+        let value = |name: &str, r#type| ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(r#type),
+            ..ValueInfoProto::default()
+        };
+        let model = ModelProto {
+            ir_version: 7,
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(GraphProto {
+                name: "test".to_string(),
+                node: vec![NodeProto {
+                    input: vec!["image".to_string()],
+                    output: vec!["output".to_string()],
+                    op_type: op_type.to_string(),
+                    attribute,
+                    ..NodeProto::default()
+                }],
+                input: vec![value("image", tensor(Some(dims)))],
+                output: vec![value("output", tensor(None))],
+                ..GraphProto::default()
+            }),
+            ..ModelProto::default()
+        };
+        std::fs::write(path, model.encode_to_vec()).unwrap();
+    }
+}
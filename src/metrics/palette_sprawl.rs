@@ -0,0 +1,168 @@
+//! Palette sprawl: how many distinct colors a single view uses.
+//!
+//! The quality command collects the solid text and background colors of a
+//! DOM snapshot (or the solid fills of a Figma tree), merges the ones that
+//! look the same, and lists the remaining pairs that are close enough to
+//! likely be one design token applied inconsistently.
+
+use super::color::{delta_e_2000, srgb_to_lab};
+use super::gradient::parse_css_color;
+use crate::types::{FigmaPaintKind, NormalizedView};
+
+/// Alpha above which a color counts as opaque; translucent colors depend on
+/// what is under them and are left out.
+const OPAQUE_ALPHA: f32 = 0.99;
+
+/// Settings of the palette sprawl check.
+#[derive(Debug, Clone)]
+pub struct PaletteSprawl {
+    /// Colors closer than this CIEDE2000 difference count as one color.
+    pub merge_delta_e: f32,
+    /// Distinct colors closer than this are reported as near duplicates.
+    pub near_duplicate_delta_e: f32,
+    /// Distinct colors a view may use before its palette sprawls.
+    pub budget: usize,
+}
+
+impl Default for PaletteSprawl {
+    fn default() -> Self {
+        Self {
+            merge_delta_e: 1.0,
+            near_duplicate_delta_e: 5.0,
+            budget: 16,
+        }
+    }
+}
+
+/// One distinct color and how many text/background uses it covers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteColor {
+    /// `#RRGGBB` of its most used variant.
+    pub hex: String,
+    pub uses: usize,
+}
+
+/// Two distinct colors that are likely meant to be the same token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearDuplicatePair {
+    pub first: String,
+    pub second: String,
+    pub delta_e: f32,
+}
+
+/// Distinct colors of a view, most used first.
+#[derive(Debug, Clone)]
+pub struct PaletteReport {
+    pub colors: Vec<PaletteColor>,
+    /// Closest pairs first.
+    pub near_duplicates: Vec<NearDuplicatePair>,
+    pub budget: usize,
+}
+
+impl PaletteReport {
+    pub fn over_budget(&self) -> bool {
+        self.colors.len() > self.budget
+    }
+}
+
+impl PaletteSprawl {
+    /// Palette of `view`, or `None` without DOM or Figma colors.
+    pub fn analyze(&self, view: &NormalizedView) -> Option<PaletteReport> {
+        let mut uses: Vec<([u8; 3], usize)> = Vec::new();
+        for rgb in view_colors(view) {
+            match uses.iter_mut().find(|(seen, _)| *seen == rgb) {
+                Some((_, count)) => *count += 1,
+                None => uses.push((rgb, 1)),
+            }
+        }
+        if uses.is_empty() {
+            return None;
+        }
+        uses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        // Each color joins the most used distinct color it looks the same as.
+        let lab = |rgb: [u8; 3]| srgb_to_lab(rgb.map(|channel| channel as f32 / 255.0));
+        let mut distinct: Vec<([u8; 3], usize)> = Vec::new();
+        for (rgb, count) in uses {
+            let same = distinct
+                .iter_mut()
+                .find(|(seen, _)| delta_e_2000(lab(*seen), lab(rgb)) < self.merge_delta_e);
+            match same {
+                Some((_, total)) => *total += count,
+                None => distinct.push((rgb, count)),
+            }
+        }
+        distinct.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut near_duplicates = Vec::new();
+        for (idx, (first, _)) in distinct.iter().enumerate() {
+            for (second, _) in &distinct[idx + 1..] {
+                let delta_e = delta_e_2000(lab(*first), lab(*second));
+                if delta_e < self.near_duplicate_delta_e {
+                    near_duplicates.push(NearDuplicatePair {
+                        first: hex(*first),
+                        second: hex(*second),
+                        delta_e,
+                    });
+                }
+            }
+        }
+        near_duplicates.sort_by(|a, b| a.delta_e.total_cmp(&b.delta_e));
+
+        Some(PaletteReport {
+            colors: distinct
+                .into_iter()
+                .map(|(rgb, uses)| PaletteColor {
+                    hex: hex(rgb),
+                    uses,
+                })
+                .collect(),
+            near_duplicates,
+            budget: self.budget,
+        })
+    }
+}
+
+/// Opaque text colors of elements with text and background colors of every
+/// element, or the solid fills of Figma nodes.
+fn view_colors(view: &NormalizedView) -> Vec<[u8; 3]> {
+    let to_rgb = |rgba: [f32; 4]| {
+        (rgba[3] >= OPAQUE_ALPHA)
+            .then(|| [rgba[0], rgba[1], rgba[2]].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+    };
+    if let Some(dom) = &view.dom {
+        let colors: Vec<[u8; 3]> = dom
+            .nodes
+            .iter()
+            .filter_map(|node| Some((node, node.computed_style.as_ref()?)))
+            .flat_map(|(node, style)| {
+                let has_text = node.text.as_deref().is_some_and(|t| !t.trim().is_empty());
+                let text = style.color.as_deref().filter(|_| has_text);
+                [text, style.background_color.as_deref()]
+            })
+            .flatten()
+            .filter_map(|value| to_rgb(parse_css_color(value)?))
+            .collect();
+        if !colors.is_empty() {
+            return colors;
+        }
+    }
+    let Some(figma) = &view.figma_tree else {
+        return Vec::new();
+    };
+    figma
+        .nodes
+        .iter()
+        .flat_map(|node| &node.fills)
+        .filter(|paint| paint.kind == FigmaPaintKind::Solid)
+        .filter_map(|paint| {
+            let mut rgba = parse_css_color(paint.color.as_deref()?)?;
+            rgba[3] *= paint.opacity.unwrap_or(1.0);
+            to_rgb(rgba)
+        })
+        .collect()
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
@@ -1,14 +1,23 @@
 use crate::error::DpcError;
 use crate::image_alignment::{align_implementation, ImageAlignmentOptions};
 use crate::image_loader::resize_to_match;
-use crate::types::{DiffSeverity, NormalizedView, PixelDiffReason, PixelDiffRegion, PixelMetric};
+use crate::types::{
+    BoundingBox, DiffSeverity, NormalizedView, PixelDiffReason, PixelDiffRegion, PixelMetric,
+    PixelOffset,
+};
 use crate::Result;
 use image::{DynamicImage, GenericImageView};
+use std::sync::Arc;
 
 use super::clustering::{cluster_regions, clustered_to_pixel_regions, ClusteringConfig};
+use super::color::{delta_e_2000, rgb_to_lab};
+use super::region_weights::RegionWeights;
+use super::runner::has_layout_data;
+use super::text_detection::{on_text_line, TextDetector};
+use super::transparency::TransparencyMode;
 use super::{Metric, MetricKind, MetricResult};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PixelDiffThresholds {
     pub minor: f32,
     pub moderate: f32,
@@ -25,15 +34,70 @@ impl Default for PixelDiffThresholds {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Noise suppression for pixel diff regions.
+///
+/// All limits default to zero, which reports every block that crosses the minor threshold.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoiseSuppression {
+    /// Drop clustered regions covering fewer than this many pixels.
+    pub min_region_pixels: u32,
+    /// Drop clustered regions covering less than this fraction (0.0-1.0) of the image.
+    pub min_region_area: f32,
+    /// Minimum fraction (0.0-1.0) of pixels inside a block that must exceed the minor
+    /// threshold before the block is reported.
+    pub min_block_density: f32,
+}
+
+impl NoiseSuppression {
+    fn keeps(&self, region: &PixelDiffRegion, width: u32, height: u32) -> bool {
+        let area = region.width * region.height;
+        let pixels = area * width as f32 * height as f32;
+        pixels + 0.5 >= self.min_region_pixels as f32 && area >= self.min_region_area
+    }
+}
+
+/// CIEDE2000 difference mapped to 1.0 in the diff map; black vs white is
+/// about 100, so gray steps land where the luma diff puts them.
+const DELTA_E_FULL_SCALE: f32 = 100.0;
+
+/// Share of a diff region a text line must cover for the diff to count as
+/// glyph rendering.
+const TEXT_LINE_COVERAGE: f32 = 0.8;
+
+/// Per-pixel difference that feeds region clustering and the coverage penalty.
+/// The SSIM part of the score always uses luma.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PixelDiffSpace {
+    /// Absolute luma difference.
+    #[default]
+    Luma,
+    /// CIEDE2000 difference in Lab, which also catches hue changes at equal
+    /// luminance (a blue button turned purple).
+    Ciede2000,
+}
+
+#[derive(Debug, Clone)]
 pub struct PixelSimilarity {
     pub block_size: u32,
     pub thresholds: PixelDiffThresholds,
     pub clustering: ClusteringConfig,
+    pub noise: NoiseSuppression,
     pub alignment: ImageAlignmentOptions,
     pub coverage_penalty_threshold: f32,
     pub coverage_penalty_scale: f32,
     pub coverage_penalty_max: f32,
+    /// Scales the score penalty by where the diffs fall.
+    pub region_weights: Option<Arc<RegionWeights>>,
+    /// How transparent pixels are compared.
+    pub transparency_mode: TransparencyMode,
+    pub diff_space: PixelDiffSpace,
+    /// Split flagged blocks into quadrants, down to this side in pixels, so
+    /// regions hug the changed pixels; `None` reports whole blocks.
+    pub refine_block_size: Option<u32>,
+    /// Detect text lines when neither view has a DOM or Figma tree; faint
+    /// diffs inside a line found in both screenshots are glyph rendering
+    /// (`anti_aliasing`, minor) rather than pixel changes.
+    pub text_regions: Option<TextDetector>,
 }
 
 impl Default for PixelSimilarity {
@@ -42,10 +106,16 @@ impl Default for PixelSimilarity {
             block_size: 32,
             thresholds: PixelDiffThresholds::default(),
             clustering: ClusteringConfig::default(),
+            noise: NoiseSuppression::default(),
             alignment: ImageAlignmentOptions::default(),
             coverage_penalty_threshold: 0.02,
             coverage_penalty_scale: 0.25,
             coverage_penalty_max: 0.30,
+            region_weights: None,
+            transparency_mode: TransparencyMode::default(),
+            diff_space: PixelDiffSpace::default(),
+            refine_block_size: None,
+            text_regions: None,
         }
     }
 }
@@ -63,43 +133,124 @@ impl PixelSimilarity {
             impl_img = resize_to_match(&impl_img, w, h);
         }
 
+        let mut offset = None;
         if self.alignment.enabled && self.alignment.max_shift > 0 {
-            let (aligned, _) = align_implementation(&ref_img, &impl_img, self.alignment);
+            let (aligned, shift) = align_implementation(&ref_img, &impl_img, self.alignment);
             impl_img = aligned;
+            offset = shift.map(|shift| PixelOffset {
+                x: -shift.dx,
+                y: -shift.dy,
+            });
         }
 
+        let mode = self.transparency_mode;
+        let ref_img = mode.prepare(ref_img);
+        let impl_img = mode.prepare(impl_img);
         let ref_luma = ref_img.to_luma8();
         let impl_luma = impl_img.to_luma8();
-
-        let diff_map = compute_diff_map(&ref_luma, &impl_luma);
-        let ssim = compute_ssim(&ref_luma, &impl_luma);
+        let (width, height) = (ref_luma.width(), ref_luma.height());
+
+        // Letterbox padding and (optionally) transparent pixels are not part
+        // of either design; score the remaining pixels only.
+        let content = shared_content_rect(reference, implementation, width, height);
+        let included = included_pixels(&ref_img, &impl_img, content, mode);
+        let mut diff_map = match self.diff_space {
+            PixelDiffSpace::Luma => compute_diff_map(&ref_luma, &impl_luma),
+            PixelDiffSpace::Ciede2000 => compute_delta_e_map(&ref_img, &impl_img),
+        };
+        let ssim = compute_ssim(&ref_luma, &impl_luma, included.as_deref());
+        if let Some(included) = &included {
+            for (diff, keep) in diff_map.iter_mut().zip(included) {
+                if !keep {
+                    *diff = 0.0;
+                }
+            }
+        }
+        let coverage_map: Vec<f32> = match &included {
+            Some(included) => diff_map
+                .iter()
+                .zip(included)
+                .filter(|(_, keep)| **keep)
+                .map(|(diff, _)| *diff)
+                .collect(),
+            None => diff_map.clone(),
+        };
         let coverage_penalty = compute_coverage_penalty(
-            &diff_map,
+            &coverage_map,
             self.coverage_penalty_threshold,
             self.coverage_penalty_scale,
             self.coverage_penalty_max,
         );
-        let score = (ssim - coverage_penalty).clamp(0.0, 1.0);
-        let raw_regions = cluster_diff_regions(
+        let mut score = (ssim - coverage_penalty).clamp(0.0, 1.0);
+        if let Some(weights) = &self.region_weights {
+            let factor = weights.diff_factor(&diff_map, width, height);
+            score = 1.0 - ((1.0 - score) * factor).min(1.0);
+        }
+        let raw_regions = scan_blocks(
             &diff_map,
-            ref_luma.width(),
-            ref_luma.height(),
+            width,
+            height,
             self.block_size,
             &self.thresholds,
+            self.noise.min_block_density,
+            self.refine_block_size,
         );
 
         // Cluster adjacent regions into larger bounding boxes
         let clustered = cluster_regions(&raw_regions, &self.clustering);
-        let diff_regions = clustered_to_pixel_regions(&clustered);
+        let mut diff_regions = clustered_to_pixel_regions(&clustered);
+        diff_regions.retain(|region| self.noise.keeps(region, width, height));
+        let image_only = !has_layout_data(reference) && !has_layout_data(implementation);
+        if let Some(detector) = self.text_regions.as_ref().filter(|_| image_only) {
+            let ref_lines = detector.detect(&ref_img);
+            let impl_lines = detector.detect(&impl_img);
+            mark_text_rendering(
+                &mut diff_regions,
+                &ref_lines,
+                &impl_lines,
+                (width, height),
+                self.thresholds.moderate,
+            );
+        }
 
         Ok(PixelMetric {
             score,
             diff_regions,
             semantic_diffs: None, // Populated by separate semantic analysis pass
+            offset,
         })
     }
 }
 
+/// Mark faint diff regions that sit inside a text line of both screenshots
+/// as glyph rendering differences. Text that moved, changed length or
+/// changed color is left alone: it leaves one of the lines or is not faint.
+fn mark_text_rendering(
+    regions: &mut [PixelDiffRegion],
+    ref_lines: &[BoundingBox],
+    impl_lines: &[BoundingBox],
+    (width, height): (u32, u32),
+    max_intensity: f32,
+) {
+    let (w, h) = (width as f32, height as f32);
+    for region in regions {
+        let bounds = BoundingBox {
+            x: region.x * w,
+            y: region.y * h,
+            width: region.width * w,
+            height: region.height * h,
+        };
+        let faint = region.intensity.is_some_and(|i| i < max_intensity);
+        if faint
+            && on_text_line(ref_lines, &bounds, TEXT_LINE_COVERAGE)
+            && on_text_line(impl_lines, &bounds, TEXT_LINE_COVERAGE)
+        {
+            region.reason = PixelDiffReason::AntiAliasing;
+            region.severity = DiffSeverity::Minor;
+        }
+    }
+}
+
 fn load_images(
     reference: &NormalizedView,
     implementation: &NormalizedView,
@@ -109,12 +260,63 @@ fn load_images(
     Ok((ref_img, impl_img))
 }
 
-fn compute_ssim(ref_luma: &image::GrayImage, impl_luma: &image::GrayImage) -> f32 {
+/// Area that is content (not letterbox padding) in both views, in pixels of
+/// a `width`x`height` image; `None` when neither view was letterboxed.
+fn shared_content_rect(
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    width: u32,
+    height: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let ref_rect = reference.content_rect(width, height);
+    let impl_rect = implementation.content_rect(width, height);
+    let (x0, y0, x1, y1) = [ref_rect, impl_rect].into_iter().flatten().fold(
+        None,
+        |acc: Option<(u32, u32, u32, u32)>, (x, y, w, h)| {
+            let (ax0, ay0, ax1, ay1) = acc.unwrap_or((0, 0, width, height));
+            Some((ax0.max(x), ay0.max(y), ax1.min(x + w), ay1.min(y + h)))
+        },
+    )?;
+    (x1 > x0 && y1 > y0).then_some((x0, y0, x1 - x0, y1 - y0))
+}
+
+/// Per-pixel flags for the pixels that count, or `None` when all of them do.
+fn included_pixels(
+    ref_img: &DynamicImage,
+    impl_img: &DynamicImage,
+    content: Option<(u32, u32, u32, u32)>,
+    mode: TransparencyMode,
+) -> Option<Vec<bool>> {
+    if content.is_none() && mode != TransparencyMode::Exclude {
+        return None;
+    }
+    let (width, height) = ref_img.dimensions();
+    let (x, y, w, h) = content.unwrap_or((0, 0, width, height));
+    let mut included = Vec::with_capacity((width * height) as usize);
+    for py in 0..height {
+        for px in 0..width {
+            let inside = px >= x && px < x + w && py >= y && py < y + h;
+            included.push(
+                inside
+                    && !mode.excludes(&ref_img.get_pixel(px, py))
+                    && !mode.excludes(&impl_img.get_pixel(px, py)),
+            );
+        }
+    }
+    Some(included)
+}
+
+fn compute_ssim(
+    ref_luma: &image::GrayImage,
+    impl_luma: &image::GrayImage,
+    included: Option<&[bool]>,
+) -> f32 {
     let ref_buf = ref_luma.as_raw();
     let impl_buf = impl_luma.as_raw();
 
     let len = ref_buf.len().min(impl_buf.len());
-    if len == 0 {
+    let count = included.map_or(len, |flags| flags.iter().take(len).filter(|f| **f).count());
+    if count == 0 {
         return 1.0;
     }
 
@@ -125,6 +327,9 @@ fn compute_ssim(ref_luma: &image::GrayImage, impl_luma: &image::GrayImage) -> f3
     let mut sum_xy = 0.0f64;
 
     for i in 0..len {
+        if included.is_some_and(|flags| !flags[i]) {
+            continue;
+        }
         let x = ref_buf[i] as f64;
         let y = impl_buf[i] as f64;
         sum_x += x;
@@ -134,7 +339,7 @@ fn compute_ssim(ref_luma: &image::GrayImage, impl_luma: &image::GrayImage) -> f3
         sum_xy += x * y;
     }
 
-    let n = len as f64;
+    let n = count as f64;
     let mu_x = sum_x / n;
     let mu_y = sum_y / n;
     let sigma_x = (sum_x2 / n) - mu_x * mu_x;
@@ -169,6 +374,23 @@ fn compute_diff_map(ref_luma: &image::GrayImage, impl_luma: &image::GrayImage) -
     diffs
 }
 
+/// CIEDE2000 difference per pixel, scaled by [`DELTA_E_FULL_SCALE`] to 0.0 - 1.0.
+fn compute_delta_e_map(ref_img: &DynamicImage, impl_img: &DynamicImage) -> Vec<f32> {
+    let ref_rgb = ref_img.to_rgb8();
+    let impl_rgb = impl_img.to_rgb8();
+    ref_rgb
+        .pixels()
+        .zip(impl_rgb.pixels())
+        .map(|(a, b)| {
+            if a == b {
+                0.0
+            } else {
+                (delta_e_2000(rgb_to_lab(a.0), rgb_to_lab(b.0)) / DELTA_E_FULL_SCALE).min(1.0)
+            }
+        })
+        .collect()
+}
+
 fn compute_coverage_penalty(diff_map: &[f32], threshold: f32, scale: f32, max_penalty: f32) -> f32 {
     if diff_map.is_empty() || scale <= 0.0 || max_penalty <= 0.0 {
         return 0.0;
@@ -190,51 +412,170 @@ pub fn cluster_diff_regions(
     height: u32,
     block_size: u32,
     thresholds: &PixelDiffThresholds,
+) -> Vec<PixelDiffRegion> {
+    cluster_diff_regions_with_density(diff_map, width, height, block_size, thresholds, 0.0)
+}
+
+/// Like [`cluster_diff_regions`], but skips blocks where fewer than `min_density`
+/// of the pixels exceed the minor threshold (isolated speckle noise).
+pub fn cluster_diff_regions_with_density(
+    diff_map: &[f32],
+    width: u32,
+    height: u32,
+    block_size: u32,
+    thresholds: &PixelDiffThresholds,
+    min_density: f32,
+) -> Vec<PixelDiffRegion> {
+    scan_blocks(
+        diff_map,
+        width,
+        height,
+        block_size,
+        thresholds,
+        min_density,
+        None,
+    )
+}
+
+/// Flag every `block_size` block whose average difference crosses the minor
+/// threshold, refining flagged blocks down to `refine_to` pixels when set.
+fn scan_blocks(
+    diff_map: &[f32],
+    width: u32,
+    height: u32,
+    block_size: u32,
+    thresholds: &PixelDiffThresholds,
+    min_density: f32,
+    refine_to: Option<u32>,
 ) -> Vec<PixelDiffRegion> {
     if width == 0 || height == 0 || block_size == 0 || diff_map.is_empty() {
         return vec![];
     }
 
-    let w = width as usize;
-    let h = height as usize;
+    let scan = BlockScan {
+        diff_map,
+        width: width as usize,
+        height: height as usize,
+        thresholds,
+        min_density,
+        refine_to: refine_to.map_or(usize::MAX, |size| size.max(1) as usize),
+    };
     let bs = block_size as usize;
     let mut regions = Vec::new();
+    for y in (0..scan.height).step_by(bs) {
+        for x in (0..scan.width).step_by(bs) {
+            let (block_w, block_h) = (bs.min(scan.width - x), bs.min(scan.height - y));
+            let stats = scan.stats(x, y, block_w, block_h);
+            if stats.density < scan.min_density {
+                continue;
+            }
+            if let Some(severity) = scan.severity(stats.avg) {
+                scan.refine(x, y, block_w, block_h, stats, severity, &mut regions);
+            }
+        }
+    }
 
-    for y in (0..h).step_by(bs) {
-        for x in (0..w).step_by(bs) {
-            let block_w = bs.min(w - x);
-            let block_h = bs.min(h - y);
-            let mut sum = 0.0f32;
-            for by in 0..block_h {
-                let start = (y + by) * w + x;
-                let end = start + block_w;
-                sum += diff_map[start..end].iter().copied().sum::<f32>();
+    regions
+}
+
+/// Difference statistics of one block.
+#[derive(Debug, Clone, Copy)]
+struct BlockStats {
+    avg: f32,
+    max: f32,
+    density: f32,
+}
+
+struct BlockScan<'a> {
+    diff_map: &'a [f32],
+    width: usize,
+    height: usize,
+    thresholds: &'a PixelDiffThresholds,
+    min_density: f32,
+    /// Smallest block side refinement may produce; `usize::MAX` disables it.
+    refine_to: usize,
+}
+
+impl BlockScan<'_> {
+    fn stats(&self, x: usize, y: usize, block_w: usize, block_h: usize) -> BlockStats {
+        let mut sum = 0.0f32;
+        let mut max = 0.0f32;
+        let mut changed = 0usize;
+        for by in 0..block_h {
+            let start = (y + by) * self.width + x;
+            for &diff in &self.diff_map[start..start + block_w] {
+                sum += diff;
+                max = max.max(diff);
+                changed += usize::from(diff >= self.thresholds.minor);
             }
+        }
+        let pixels = (block_w * block_h) as f32;
+        BlockStats {
+            avg: sum / pixels,
+            max,
+            density: changed as f32 / pixels,
+        }
+    }
 
-            let avg = sum / (block_w * block_h) as f32;
-            let severity = if avg >= thresholds.major {
-                DiffSeverity::Major
-            } else if avg >= thresholds.moderate {
-                DiffSeverity::Moderate
-            } else if avg >= thresholds.minor {
-                DiffSeverity::Minor
-            } else {
-                continue;
-            };
-
-            regions.push(PixelDiffRegion {
-                x: x as f32 / width as f32,
-                y: y as f32 / height as f32,
-                width: block_w as f32 / width as f32,
-                height: block_h as f32 / height as f32,
-                severity,
-                reason: PixelDiffReason::PixelChange,
-                intensity: Some(avg),
-            });
+    fn severity(&self, avg: f32) -> Option<DiffSeverity> {
+        if avg >= self.thresholds.major {
+            Some(DiffSeverity::Major)
+        } else if avg >= self.thresholds.moderate {
+            Some(DiffSeverity::Moderate)
+        } else if avg >= self.thresholds.minor {
+            Some(DiffSeverity::Minor)
+        } else {
+            None
         }
     }
 
-    regions
+    /// Quad-tree refinement of a flagged block: recurse into the quadrants
+    /// holding changed pixels and drop the empty ones, so the reported boxes
+    /// hug the change. A quadrant whose own average stays below the minor
+    /// threshold is still reported, as minor.
+    #[allow(clippy::too_many_arguments)]
+    fn refine(
+        &self,
+        x: usize,
+        y: usize,
+        block_w: usize,
+        block_h: usize,
+        stats: BlockStats,
+        severity: DiffSeverity,
+        out: &mut Vec<PixelDiffRegion>,
+    ) {
+        let (half_w, half_h) = (block_w / 2, block_h / 2);
+        if half_w >= self.refine_to && half_h >= self.refine_to {
+            let quadrants = [
+                (x, y, half_w, half_h),
+                (x + half_w, y, block_w - half_w, half_h),
+                (x, y + half_h, half_w, block_h - half_h),
+                (x + half_w, y + half_h, block_w - half_w, block_h - half_h),
+            ];
+            for (qx, qy, qw, qh) in quadrants {
+                let stats = self.stats(qx, qy, qw, qh);
+                if stats.density > 0.0 {
+                    let severity = self.severity(stats.avg).unwrap_or(DiffSeverity::Minor);
+                    self.refine(qx, qy, qw, qh, stats, severity, out);
+                }
+            }
+            return;
+        }
+
+        out.push(PixelDiffRegion {
+            fingerprint: None,
+            x: x as f32 / self.width as f32,
+            y: y as f32 / self.height as f32,
+            width: block_w as f32 / self.width as f32,
+            height: block_h as f32 / self.height as f32,
+            severity,
+            reason: PixelDiffReason::PixelChange,
+            intensity: Some(stats.avg),
+            max_intensity: Some(stats.max),
+            pixel_bounds: None,
+            css_bounds: None,
+        });
+    }
 }
 
 impl Metric for PixelSimilarity {
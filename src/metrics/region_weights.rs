@@ -0,0 +1,136 @@
+//! Region-of-interest weighting for pixel and layout penalties.
+//!
+//! Lets a hero banner count more than footer noise: discrepancies inside a
+//! heavily weighted rectangle (or near the top of the page, with
+//! `top_emphasis`) lower the score more than the same discrepancy elsewhere.
+
+use super::saliency::SaliencyMap;
+use crate::{DpcError, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A rectangle whose diffs are scaled by `weight`.
+///
+/// Coordinates are normalized (0.0-1.0) when every value fits that range,
+/// otherwise they are screenshot pixels — the same rule as ignore regions.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct WeightedRegion {
+    pub x: f32,
+    pub y: f32,
+    #[serde(alias = "w")]
+    pub width: f32,
+    #[serde(alias = "h")]
+    pub height: f32,
+    pub weight: f32,
+}
+
+impl WeightedRegion {
+    fn contains(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let normalized = self.x >= 0.0
+            && self.y >= 0.0
+            && self.x <= 1.0
+            && self.y <= 1.0
+            && self.width <= 1.0
+            && self.height <= 1.0;
+        let (rx, ry, rw, rh) = if normalized {
+            (
+                self.x * width,
+                self.y * height,
+                self.width * width,
+                self.height * height,
+            )
+        } else {
+            (self.x, self.y, self.width, self.height)
+        };
+        x >= rx && x < rx + rw && y >= ry && y < ry + rh
+    }
+}
+
+/// Spatial weights applied to pixel and layout penalties.
+///
+/// The weight at a point is the largest weight among the regions containing
+/// it (1.0 outside all regions), multiplied by the top-of-page curve
+/// `1 + top_emphasis * (1 - y)^2` where `y` runs from 0.0 (top) to 1.0 (bottom)
+/// and by the reference's estimated saliency there, when set.
+#[derive(Debug, Clone, Default)]
+pub struct RegionWeights {
+    pub regions: Vec<WeightedRegion>,
+    pub top_emphasis: f32,
+    pub saliency: Option<Arc<SaliencyMap>>,
+}
+
+impl RegionWeights {
+    /// Load a JSON array of `{x, y, width, height, weight}` rectangles.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| DpcError::Config(format!("Failed to read region-weights: {e}")))?;
+        let regions: Vec<WeightedRegion> = serde_json::from_str(&data).map_err(|e| {
+            DpcError::Config(format!(
+                "Invalid region-weights JSON (expected array of {{x,y,width,height,weight}}): {e}"
+            ))
+        })?;
+        if let Some(bad) = regions.iter().find(|r| r.weight.is_nan() || r.weight < 0.0) {
+            return Err(DpcError::Config(format!(
+                "region-weights weight must be non-negative, got {}",
+                bad.weight
+            )));
+        }
+        Ok(Self {
+            regions,
+            top_emphasis: 0.0,
+            saliency: None,
+        })
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        self.regions.is_empty() && self.top_emphasis == 0.0 && self.saliency.is_none()
+    }
+
+    /// Weight at `(x, y)` in a `width` x `height` pixel frame.
+    pub fn weight_at(&self, x: f32, y: f32, width: f32, height: f32) -> f32 {
+        let region_weight = self
+            .regions
+            .iter()
+            .filter(|region| region.contains(x, y, width, height))
+            .map(|region| region.weight)
+            .reduce(f32::max)
+            .unwrap_or(1.0);
+        let depth = if height > 0.0 {
+            (y / height).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let saliency = match (&self.saliency, width > 0.0) {
+            (Some(map), true) => map.weight_at(x / width, depth),
+            _ => 1.0,
+        };
+        region_weight * (1.0 + self.top_emphasis * (1.0 - depth).powi(2)) * saliency
+    }
+
+    /// How much a per-pixel diff map is amplified by the weights: the weighted
+    /// mean diff over the plain mean diff, with weights normalized to mean 1.
+    /// Uniformly spread diffs give 1.0; diffs concentrated in heavy areas give more.
+    pub(crate) fn diff_factor(&self, diff_map: &[f32], width: u32, height: u32) -> f32 {
+        if self.is_uniform() || diff_map.is_empty() || width == 0 {
+            return 1.0;
+        }
+        let (fw, fh) = (width as f32, height as f32);
+        let mut weight_sum = 0.0f64;
+        let mut weighted_diff = 0.0f64;
+        let mut diff_sum = 0.0f64;
+        for (idx, diff) in diff_map.iter().enumerate() {
+            let x = (idx as u32 % width) as f32 + 0.5;
+            let y = (idx as u32 / width) as f32 + 0.5;
+            let weight = self.weight_at(x, y, fw, fh) as f64;
+            weight_sum += weight;
+            weighted_diff += weight * *diff as f64;
+            diff_sum += *diff as f64;
+        }
+        if diff_sum <= f64::EPSILON || weight_sum <= f64::EPSILON {
+            return 1.0;
+        }
+        let n = diff_map.len() as f64;
+        ((weighted_diff / weight_sum) / (diff_sum / n)) as f32
+    }
+}
@@ -1,19 +1,22 @@
 use crate::error::DpcError;
 use crate::types::{
     ColorMetric, ContentMetric, LayoutMetric, MetricScores, NormalizedView, PixelMetric,
-    TypographyMetric,
+    SemanticMetric, TypographyMetric,
 };
 use crate::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use super::{
-    ColorPaletteMetric, ContentSimilarity, LayoutSimilarity, PixelSimilarity, TypographySimilarity,
+    ColorPaletteMetric, ContentSimilarity, LayoutSimilarity, PixelSimilarity, SemanticSimilarity,
+    TypographySimilarity,
 };
 
 /// The kind of metric being computed.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MetricKind {
     Pixel,
@@ -21,10 +24,24 @@ pub enum MetricKind {
     Typography,
     Color,
     Content,
+    /// Opt-in: only runs when selected explicitly.
+    Semantic,
 }
 
 impl MetricKind {
-    pub const fn all() -> [MetricKind; 5] {
+    pub const fn all() -> [MetricKind; 6] {
+        [
+            MetricKind::Pixel,
+            MetricKind::Layout,
+            MetricKind::Typography,
+            MetricKind::Color,
+            MetricKind::Content,
+            MetricKind::Semantic,
+        ]
+    }
+
+    /// The kinds run when no metrics are selected.
+    pub const fn defaults() -> [MetricKind; 5] {
         [
             MetricKind::Pixel,
             MetricKind::Layout,
@@ -46,6 +63,7 @@ impl fmt::Display for MetricKind {
                 MetricKind::Typography => "typography",
                 MetricKind::Color => "color",
                 MetricKind::Content => "content",
+                MetricKind::Semantic => "semantic",
             }
         )
     }
@@ -61,6 +79,7 @@ impl FromStr for MetricKind {
             "typography" => Ok(MetricKind::Typography),
             "color" => Ok(MetricKind::Color),
             "content" => Ok(MetricKind::Content),
+            "semantic" => Ok(MetricKind::Semantic),
             other => Err(format!("unknown metric kind: {}", other)),
         }
     }
@@ -74,6 +93,50 @@ pub trait Metric {
         reference: &NormalizedView,
         implementation: &NormalizedView,
     ) -> Result<MetricResult>;
+
+    /// Whether the views hold the data this metric needs; metrics without it
+    /// are skipped instead of failing the run.
+    fn has_data(&self, reference: &NormalizedView, implementation: &NormalizedView) -> bool {
+        has_data_for(self.kind(), reference, implementation)
+    }
+}
+
+/// Shared metrics (see [`SharedMetric`](super::SharedMetric)) can also run
+/// through [`run_metrics`].
+impl<M: Metric + ?Sized> Metric for Arc<M> {
+    fn kind(&self) -> MetricKind {
+        (**self).kind()
+    }
+
+    fn compute(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<MetricResult> {
+        (**self).compute(reference, implementation)
+    }
+
+    fn has_data(&self, reference: &NormalizedView, implementation: &NormalizedView) -> bool {
+        (**self).has_data(reference, implementation)
+    }
+}
+
+impl<M: Metric + ?Sized> Metric for Box<M> {
+    fn kind(&self) -> MetricKind {
+        (**self).kind()
+    }
+
+    fn compute(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<MetricResult> {
+        (**self).compute(reference, implementation)
+    }
+
+    fn has_data(&self, reference: &NormalizedView, implementation: &NormalizedView) -> bool {
+        (**self).has_data(reference, implementation)
+    }
 }
 
 /// Result of a metric computation, containing the specific metric data.
@@ -84,6 +147,7 @@ pub enum MetricResult {
     Typography(TypographyMetric),
     Color(ColorMetric),
     Content(ContentMetric),
+    Semantic(SemanticMetric),
 }
 
 impl MetricResult {
@@ -94,6 +158,7 @@ impl MetricResult {
             MetricResult::Typography(_) => MetricKind::Typography,
             MetricResult::Color(_) => MetricKind::Color,
             MetricResult::Content(_) => MetricKind::Content,
+            MetricResult::Semantic(_) => MetricKind::Semantic,
         }
     }
 
@@ -104,6 +169,7 @@ impl MetricResult {
             MetricResult::Typography(m) => m.score,
             MetricResult::Color(m) => m.score,
             MetricResult::Content(m) => m.score,
+            MetricResult::Semantic(m) => m.score,
         }
     }
 }
@@ -116,10 +182,28 @@ pub fn default_metrics() -> Vec<Box<dyn Metric>> {
         Box::new(TypographySimilarity::default()),
         Box::new(ColorPaletteMetric::default()),
         Box::new(ContentSimilarity::default()),
+        Box::new(SemanticSimilarity::default()),
     ]
 }
 
-fn has_layout_data(view: &NormalizedView) -> bool {
+/// Default [`Metric::has_data`]: layout needs reference structure,
+/// typography and content need styled text / text on both sides.
+pub(super) fn has_data_for(
+    kind: MetricKind,
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+) -> bool {
+    match kind {
+        MetricKind::Layout => has_layout_data(reference),
+        MetricKind::Typography => {
+            has_typography_data(reference) && has_typography_data(implementation)
+        }
+        MetricKind::Content => has_content_data(reference) && has_content_data(implementation),
+        MetricKind::Pixel | MetricKind::Color | MetricKind::Semantic => true,
+    }
+}
+
+pub(super) fn has_layout_data(view: &NormalizedView) -> bool {
     view.dom
         .as_ref()
         .map(|d| !d.nodes.is_empty())
@@ -182,28 +266,37 @@ fn has_content_data(view: &NormalizedView) -> bool {
     false
 }
 
-/// Run the specified metrics on the reference and implementation views.
-pub fn run_metrics(
-    metrics: &[Box<dyn Metric>],
+/// Kinds from `selected` (the defaults when empty) that have data to work with;
+/// errors when a selected kind has no entry in `metrics`.
+pub(crate) fn metrics_to_run<M: Metric>(
+    metrics: &[M],
     selected: &[MetricKind],
     reference: &NormalizedView,
     implementation: &NormalizedView,
-) -> Result<MetricScores> {
+) -> Result<Vec<MetricKind>> {
+    let available: Vec<(MetricKind, bool)> = metrics
+        .iter()
+        .map(|m| (m.kind(), m.has_data(reference, implementation)))
+        .collect();
+    select_runnable(&available, selected)
+}
+
+/// Kinds from `selected` (the defaults when empty) whose entry in `available` has
+/// data; errors when a selected kind has no entry at all.
+pub(crate) fn select_runnable(
+    available: &[(MetricKind, bool)],
+    selected: &[MetricKind],
+) -> Result<Vec<MetricKind>> {
     let desired: Vec<MetricKind> = if selected.is_empty() {
-        MetricKind::all().to_vec()
+        MetricKind::defaults().to_vec()
     } else {
         selected.to_vec()
     };
 
-    let layout_available = has_layout_data(reference);
-    let typography_available =
-        has_typography_data(reference) && has_typography_data(implementation);
-    let content_available = has_content_data(reference) && has_content_data(implementation);
-
     let missing: Vec<MetricKind> = desired
         .iter()
         .copied()
-        .filter(|kind| !metrics.iter().any(|m| m.kind() == *kind))
+        .filter(|kind| !available.iter().any(|(k, _)| k == kind))
         .collect();
 
     if !missing.is_empty() {
@@ -218,38 +311,47 @@ pub fn run_metrics(
         )));
     }
 
+    Ok(desired
+        .into_iter()
+        .filter(|kind| available.iter().any(|(k, has_data)| k == kind && *has_data))
+        .collect())
+}
+
+/// Store `result` in the matching slot of `scores`.
+pub(crate) fn record_result(scores: &mut MetricScores, result: MetricResult) {
+    match result {
+        MetricResult::Pixel(m) => scores.pixel = Some(m),
+        MetricResult::Layout(m) => scores.layout = Some(m),
+        MetricResult::Typography(m) => scores.typography = Some(m),
+        MetricResult::Color(m) => scores.color = Some(m),
+        MetricResult::Content(m) => scores.content = Some(m),
+        MetricResult::Semantic(m) => scores.semantic = Some(m),
+    }
+}
+
+/// Run the specified metrics on the reference and implementation views.
+pub fn run_metrics(
+    metrics: &[Box<dyn Metric>],
+    selected: &[MetricKind],
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+) -> Result<MetricScores> {
+    let to_run = metrics_to_run(metrics, selected, reference, implementation)?;
+
     let mut scores = MetricScores {
         pixel: None,
         layout: None,
         typography: None,
         color: None,
         content: None,
+        semantic: None,
     };
 
     for metric in metrics {
-        let kind = metric.kind();
-        if !desired.contains(&kind) {
+        if !to_run.contains(&metric.kind()) {
             continue;
         }
-
-        if matches!(kind, MetricKind::Layout) && !layout_available {
-            continue;
-        }
-        if matches!(kind, MetricKind::Typography) && !typography_available {
-            continue;
-        }
-        if matches!(kind, MetricKind::Content) && !content_available {
-            continue;
-        }
-
-        let result = metric.compute(reference, implementation)?;
-        match result {
-            MetricResult::Pixel(m) => scores.pixel = Some(m),
-            MetricResult::Layout(m) => scores.layout = Some(m),
-            MetricResult::Typography(m) => scores.typography = Some(m),
-            MetricResult::Color(m) => scores.color = Some(m),
-            MetricResult::Content(m) => scores.content = Some(m),
-        }
+        record_result(&mut scores, metric.compute(reference, implementation)?);
     }
 
     Ok(scores)
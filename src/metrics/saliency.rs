@@ -0,0 +1,412 @@
+//! Saliency weighting of pixel and layout penalties (`--saliency`).
+//!
+//! A saliency map says, cell by cell, how prominent each part of the
+//! reference screenshot is. It is folded into [`RegionWeights`], so a diff in
+//! a prominent element costs more than the same diff in the backdrop.
+//!
+//! Two estimators produce the map:
+//!
+//! - [`SaliencyModel`]: a trained saliency network in ONNX format
+//!   (`--saliency-model`), run on the CPU. Needs the `saliency` feature.
+//! - [`SaliencyHeuristic`]: the built-in fallback. A logistic function with
+//!   hand-tuned weights over per-cell contrast, color rarity, edge density
+//!   and closeness to the upper center (`--saliency-weights` replaces the
+//!   weights). It does not predict where real viewers look; contrasting,
+//!   detailed cells simply score high and flat background texture low.
+//!
+//! [`RegionWeights`]: super::RegionWeights
+
+use std::path::Path;
+use std::sync::Arc;
+
+use image::DynamicImage;
+use serde::Deserialize;
+
+use crate::{DpcError, Result};
+
+/// Hand-tuned logistic weighting of per-cell features into a saliency
+/// estimate.
+///
+/// Every feature is scaled to 0-1 over the screenshot before weighting:
+/// `contrast` is the luma standard deviation in the cell, `rarity` the
+/// distance of the cell's mean color from the page's, `edges` the mean luma
+/// gradient and `center` the closeness to the upper center, where eyes land
+/// first.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SaliencyHeuristic {
+    pub bias: f32,
+    pub contrast: f32,
+    pub rarity: f32,
+    pub edges: f32,
+    pub center: f32,
+}
+
+impl Default for SaliencyHeuristic {
+    fn default() -> Self {
+        Self {
+            bias: -3.0,
+            contrast: 2.5,
+            rarity: 2.0,
+            edges: 2.0,
+            center: 1.0,
+        }
+    }
+}
+
+/// Estimated saliency of a screenshot on a coarse grid, scaled to mean 1.0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaliencyMap {
+    pub columns: usize,
+    pub rows: usize,
+    /// Row-major cell weights.
+    pub values: Vec<f32>,
+}
+
+impl SaliencyMap {
+    /// Weight at the normalized point `(x, y)` (0.0-1.0 across the page).
+    pub fn weight_at(&self, x: f32, y: f32) -> f32 {
+        if self.values.is_empty() {
+            return 1.0;
+        }
+        let column = ((x.clamp(0.0, 1.0) * self.columns as f32) as usize).min(self.columns - 1);
+        let row = ((y.clamp(0.0, 1.0) * self.rows as f32) as usize).min(self.rows - 1);
+        self.values[row * self.columns + column]
+    }
+}
+
+impl SaliencyHeuristic {
+    /// Load `{"bias": .., "contrast": .., "rarity": .., "edges": .., "center": ..}`.
+    pub fn from_json_file(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| DpcError::Config(format!("Failed to read saliency weights: {e}")))?;
+        let heuristic: Self = serde_json::from_str(&data).map_err(|e| {
+            DpcError::Config(format!(
+                "Invalid saliency weights JSON (expected {{\"bias\", \"contrast\", \"rarity\", \"edges\", \"center\"}}): {e}"
+            ))
+        })?;
+        let coefficients = [
+            heuristic.bias,
+            heuristic.contrast,
+            heuristic.rarity,
+            heuristic.edges,
+            heuristic.center,
+        ];
+        if coefficients.iter().any(|c| !c.is_finite()) {
+            return Err(DpcError::Config(
+                "saliency weights must be finite numbers".to_string(),
+            ));
+        }
+        Ok(heuristic)
+    }
+
+    /// Estimate the saliency of `image`.
+    pub fn estimate(&self, image: &DynamicImage) -> SaliencyMap {
+        estimate::estimate(self, image)
+    }
+}
+
+/// A trained saliency model in ONNX format (`--saliency-model`).
+///
+/// The model takes the screenshot as a `1x3xHxW` RGB tensor normalized with
+/// the ImageNet mean and standard deviation, and returns an attention map
+/// whose last two dimensions are its rows and columns (e.g. `1x1xHxW`).
+/// Loading one needs dpc built with the `saliency` feature.
+#[derive(Debug)]
+pub struct SaliencyModel {
+    #[cfg(feature = "saliency")]
+    model: super::onnx::ImageModel,
+    #[cfg(not(feature = "saliency"))]
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "saliency")]
+const IMAGENET: super::onnx::Preprocess = super::onnx::Preprocess {
+    mean: [0.485, 0.456, 0.406],
+    std: [0.229, 0.224, 0.225],
+    center_crop: false,
+};
+
+impl SaliencyModel {
+    #[cfg(feature = "saliency")]
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self {
+            model: super::onnx::ImageModel::load(path, IMAGENET)?,
+        })
+    }
+
+    #[cfg(not(feature = "saliency"))]
+    pub fn load(_path: &Path) -> Result<Self> {
+        Err(DpcError::Config(
+            "--saliency-model needs dpc built with the `saliency` feature (cargo install dpc --features saliency)"
+                .to_string(),
+        ))
+    }
+
+    /// Predict the saliency of `image`. The map is shifted so its lowest
+    /// cell is 0 and scaled to mean 1.0, like the heuristic's.
+    #[cfg(feature = "saliency")]
+    pub fn predict(&self, image: &DynamicImage) -> Result<SaliencyMap> {
+        let (shape, values) = self.model.run(image)?;
+        let [.., rows, columns] = shape[..] else {
+            return Err(DpcError::metric(format!(
+                "saliency model output has shape {shape:?}; expected at least rows x columns"
+            )));
+        };
+        let mut values = values[..rows * columns].to_vec();
+        let low = values.iter().copied().fold(f32::INFINITY, f32::min);
+        values.iter_mut().for_each(|value| *value -= low);
+        let mean = values.iter().sum::<f32>() / values.len().max(1) as f32;
+        if mean > 0.0 && mean.is_finite() {
+            values.iter_mut().for_each(|value| *value /= mean);
+        } else {
+            values.iter_mut().for_each(|value| *value = 1.0);
+        }
+        Ok(SaliencyMap {
+            columns,
+            rows,
+            values,
+        })
+    }
+
+    #[cfg(not(feature = "saliency"))]
+    pub fn predict(&self, _image: &DynamicImage) -> Result<SaliencyMap> {
+        match self.never {}
+    }
+}
+
+/// Where the saliency map comes from.
+#[derive(Debug, Clone)]
+pub enum SaliencyEstimator {
+    Heuristic(SaliencyHeuristic),
+    Model(Arc<SaliencyModel>),
+}
+
+impl SaliencyEstimator {
+    pub fn estimate(&self, image: &DynamicImage) -> Result<SaliencyMap> {
+        match self {
+            SaliencyEstimator::Heuristic(heuristic) => Ok(heuristic.estimate(image)),
+            SaliencyEstimator::Model(model) => model.predict(image),
+        }
+    }
+}
+
+mod estimate {
+    use image::imageops::FilterType;
+    use image::DynamicImage;
+
+    use super::{SaliencyHeuristic, SaliencyMap};
+
+    /// Screenshots are shrunk to this width before scoring.
+    const ANALYSIS_WIDTH: u32 = 256;
+    /// Side of a grid cell, in analysis pixels.
+    const CELL: u32 = 8;
+    /// Where attention concentrates first, normalized.
+    const FOCUS: (f32, f32) = (0.5, 0.3);
+
+    pub(super) fn estimate(heuristic: &SaliencyHeuristic, image: &DynamicImage) -> SaliencyMap {
+        let image = if image.width() > ANALYSIS_WIDTH {
+            let height = (image.height() as u64 * ANALYSIS_WIDTH as u64 / image.width() as u64)
+                .max(1) as u32;
+            image.resize_exact(ANALYSIS_WIDTH, height, FilterType::Triangle)
+        } else {
+            image.clone()
+        };
+        let rgb = image.to_rgb32f();
+        let (width, height) = rgb.dimensions();
+        if width == 0 || height == 0 {
+            return SaliencyMap {
+                columns: 0,
+                rows: 0,
+                values: Vec::new(),
+            };
+        }
+        let luma: Vec<f32> = rgb
+            .pixels()
+            .map(|p| 0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2])
+            .collect();
+        let page_mean = mean_color(rgb.pixels().map(|p| [p[0], p[1], p[2]]));
+
+        let columns = width.div_ceil(CELL) as usize;
+        let rows = height.div_ceil(CELL) as usize;
+        let mut features = Vec::with_capacity(columns * rows);
+        for row in 0..rows as u32 {
+            for column in 0..columns as u32 {
+                let (x0, y0) = (column * CELL, row * CELL);
+                let (x1, y1) = ((x0 + CELL).min(width), (y0 + CELL).min(height));
+                let cell = || (y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)));
+                let lumas: Vec<f32> = cell()
+                    .map(|(x, y)| luma[(y * width + x) as usize])
+                    .collect();
+                let mean = lumas.iter().sum::<f32>() / lumas.len() as f32;
+                let contrast = (lumas.iter().map(|l| (l - mean).powi(2)).sum::<f32>()
+                    / lumas.len() as f32)
+                    .sqrt();
+                let color = mean_color(cell().map(|(x, y)| {
+                    let p = rgb.get_pixel(x, y);
+                    [p[0], p[1], p[2]]
+                }));
+                let rarity = color
+                    .iter()
+                    .zip(page_mean)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+                let edges = cell()
+                    .map(|(x, y)| {
+                        let at = |x: u32, y: u32| luma[(y * width + x) as usize];
+                        let dx = at((x + 1).min(width - 1), y) - at(x.saturating_sub(1), y);
+                        let dy = at(x, (y + 1).min(height - 1)) - at(x, y.saturating_sub(1));
+                        (dx * dx + dy * dy).sqrt()
+                    })
+                    .sum::<f32>()
+                    / lumas.len() as f32;
+                let center_x = (x0 + x1) as f32 / 2.0 / width as f32;
+                let center_y = (y0 + y1) as f32 / 2.0 / height as f32;
+                let distance = ((center_x - FOCUS.0).powi(2) + (center_y - FOCUS.1).powi(2)).sqrt();
+                let center = (1.0 - distance / 0.8).max(0.0);
+                features.push([contrast, rarity, edges, center]);
+            }
+        }
+
+        // Scale each feature to 0-1 over the page so the coefficients do not
+        // depend on the screenshot's overall contrast.
+        let mut peaks = [0.0f32; 4];
+        for cell in &features {
+            for (peak, value) in peaks.iter_mut().zip(cell) {
+                *peak = peak.max(*value);
+            }
+        }
+        let coefficients = [
+            heuristic.contrast,
+            heuristic.rarity,
+            heuristic.edges,
+            heuristic.center,
+        ];
+        let mut values: Vec<f32> = features
+            .iter()
+            .map(|cell| {
+                let z = cell
+                    .iter()
+                    .zip(peaks)
+                    .zip(coefficients)
+                    .map(|((value, peak), coefficient)| {
+                        let scaled = if peak > 0.0 { value / peak } else { 0.0 };
+                        scaled * coefficient
+                    })
+                    .sum::<f32>()
+                    + heuristic.bias;
+                1.0 / (1.0 + (-z).exp())
+            })
+            .collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        if mean > 0.0 {
+            values.iter_mut().for_each(|value| *value /= mean);
+        }
+        SaliencyMap {
+            columns,
+            rows,
+            values,
+        }
+    }
+
+    fn mean_color(colors: impl Iterator<Item = [f32; 3]>) -> [f32; 3] {
+        let (mut sum, mut count) = ([0.0f32; 3], 0usize);
+        for color in colors {
+            for (total, channel) in sum.iter_mut().zip(color) {
+                *total += channel;
+            }
+            count += 1;
+        }
+        sum.map(|total| total / count.max(1) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn weight_files_replace_the_coefficients() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saliency.json");
+        std::fs::write(
+            &path,
+            r#"{"bias": -2, "contrast": 1, "rarity": 3, "edges": 0.5, "center": 0}"#,
+        )
+        .unwrap();
+        let heuristic = SaliencyHeuristic::from_json_file(&path).unwrap();
+        assert_eq!(heuristic.rarity, 3.0);
+        assert_eq!(heuristic.center, 0.0);
+
+        std::fs::write(&path, r#"{"bias": -2, "contrast": 1}"#).unwrap();
+        assert!(SaliencyHeuristic::from_json_file(&path).is_err());
+    }
+
+    #[cfg(not(feature = "saliency"))]
+    #[test]
+    fn loading_a_model_without_the_feature_is_a_config_error() {
+        let err = SaliencyModel::load(Path::new("saliency.onnx")).unwrap_err();
+        assert!(matches!(err, DpcError::Config(ref msg) if msg.contains("`saliency` feature")));
+    }
+
+    #[cfg(feature = "saliency")]
+    #[test]
+    fn onnx_models_map_bright_areas_to_high_saliency() {
+        use crate::metrics::onnx::test_models;
+
+        // Channel mean of a fixed 16x16 input: brighter means more salient.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saliency.onnx");
+        test_models::write(
+            &path,
+            [Some(1), Some(3), Some(16), Some(16)],
+            "ReduceMean",
+            vec![
+                test_models::ints("axes", &[1]),
+                test_models::int("keepdims", 1),
+            ],
+        );
+        let model = SaliencyModel::load(&path).unwrap();
+
+        let mut img = RgbImage::from_pixel(64, 48, Rgb([20, 20, 20]));
+        for y in 0..12 {
+            for x in 0..16 {
+                img.put_pixel(x, y, Rgb([250, 250, 250]));
+            }
+        }
+        let map = model.predict(&DynamicImage::ImageRgb8(img)).unwrap();
+
+        assert_eq!((map.columns, map.rows), (16, 16));
+        let mean = map.values.iter().sum::<f32>() / map.values.len() as f32;
+        assert!((mean - 1.0).abs() < 1e-3);
+        assert!(map.weight_at(0.1, 0.1) > 4.0 * map.weight_at(0.9, 0.9).max(0.01));
+
+        std::fs::write(&path, b"not a model").unwrap();
+        assert!(matches!(
+            SaliencyModel::load(&path),
+            Err(DpcError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn busy_prominent_cells_outweigh_flat_background() {
+        // A gray page with a dark, detailed button in the upper center.
+        let mut img = RgbImage::from_pixel(128, 128, Rgb([240, 240, 240]));
+        for y in 32..48 {
+            for x in 48..80 {
+                let shade = if (x + y) % 4 < 2 { 20 } else { 200 };
+                img.put_pixel(x, y, Rgb([shade, 40, 160]));
+            }
+        }
+
+        let map = SaliencyHeuristic::default().estimate(&DynamicImage::ImageRgb8(img));
+
+        let mean = map.values.iter().sum::<f32>() / map.values.len() as f32;
+        assert!((mean - 1.0).abs() < 1e-3);
+        let button = map.weight_at(0.5, 0.3);
+        let corner = map.weight_at(0.05, 0.95);
+        assert!(button > 2.0 * corner, "button {button}, corner {corner}");
+    }
+}
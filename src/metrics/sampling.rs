@@ -0,0 +1,133 @@
+//! Score statistics over repeated captures (`--samples`).
+//!
+//! Animations, carousels and late-loading content make a single capture a
+//! lottery ticket. Scoring several captures of the same implementation shows
+//! how much the scores move, so an unreliable comparison is flagged instead
+//! of being reported as one precise-looking number.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::MetricScores;
+
+use super::scoring::{calculate_combined_score, metric_score, ScoreWeights};
+use super::MetricKind;
+
+/// Standard deviation above which a score counts as unstable.
+pub const UNSTABLE_STD_DEV: f32 = 0.01;
+
+/// Two-sided 95% Student's t quantiles for 1..=30 degrees of freedom.
+const T_95: [f32; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Spread of one score across samples.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreInterval {
+    pub mean: f32,
+    /// Sample standard deviation; 0 for a single sample.
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+    /// 95% confidence interval of the mean, clamped to 0–1.
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl ScoreInterval {
+    /// Statistics of `samples`; `None` when there are none.
+    pub fn from_samples(samples: &[f32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len() as f32;
+        let mean = samples.iter().sum::<f32>() / n;
+        let std_dev = if samples.len() > 1 {
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / (n - 1.0);
+            variance.sqrt()
+        } else {
+            0.0
+        };
+        let t = T_95
+            .get(samples.len().saturating_sub(2))
+            .copied()
+            .unwrap_or(1.96);
+        let margin = t * std_dev / n.sqrt();
+        Some(Self {
+            mean,
+            std_dev,
+            min: samples.iter().copied().fold(f32::INFINITY, f32::min),
+            max: samples.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            lower: (mean - margin).clamp(0.0, 1.0),
+            upper: (mean + margin).clamp(0.0, 1.0),
+        })
+    }
+
+    /// Whether the interval straddles `threshold`, i.e. a recapture could flip
+    /// pass/fail.
+    pub fn straddles(&self, threshold: f32) -> bool {
+        self.lower < threshold && self.upper >= threshold
+    }
+}
+
+/// Spread of one metric across samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricInterval {
+    pub metric: MetricKind,
+    #[serde(flatten)]
+    pub interval: ScoreInterval,
+}
+
+/// How the scores varied across repeated captures of the implementation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleStats {
+    /// Number of captures scored.
+    pub samples: usize,
+    /// Zero-based sample whose scores are reported: the one with the median
+    /// similarity.
+    pub reported_sample: usize,
+    pub similarity: ScoreInterval,
+    pub metrics: Vec<MetricInterval>,
+    /// The similarity or a metric varied by more than [`UNSTABLE_STD_DEV`].
+    pub unstable: bool,
+}
+
+impl SampleStats {
+    /// Statistics over the scores of each capture; `None` without samples.
+    pub fn from_scores(samples: &[MetricScores], weights: &ScoreWeights) -> Option<Self> {
+        let similarities: Vec<f32> = samples
+            .iter()
+            .map(|scores| calculate_combined_score(scores, weights))
+            .collect();
+        let similarity = ScoreInterval::from_samples(&similarities)?;
+        let metrics: Vec<MetricInterval> = MetricKind::all()
+            .into_iter()
+            .filter_map(|metric| {
+                let scores: Vec<f32> = samples
+                    .iter()
+                    .filter_map(|scores| metric_score(scores, metric))
+                    .collect();
+                ScoreInterval::from_samples(&scores)
+                    .map(|interval| MetricInterval { metric, interval })
+            })
+            .collect();
+        let unstable = std::iter::once(&similarity)
+            .chain(metrics.iter().map(|m| &m.interval))
+            .any(|interval| interval.std_dev > UNSTABLE_STD_DEV);
+
+        let mut order: Vec<usize> = (0..samples.len()).collect();
+        order.sort_by(|a, b| similarities[*a].total_cmp(&similarities[*b]));
+        Some(Self {
+            samples: samples.len(),
+            reported_sample: order[(order.len() - 1) / 2],
+            similarity,
+            metrics,
+            unstable,
+        })
+    }
+}
@@ -1,5 +1,7 @@
 use crate::types::MetricScores;
 
+use super::MetricKind;
+
 #[derive(Debug, Clone, Copy)]
 pub struct ScoreWeights {
     pub pixel: f32,
@@ -7,6 +9,7 @@ pub struct ScoreWeights {
     pub typography: f32,
     pub color: f32,
     pub content: f32,
+    pub semantic: f32,
 }
 
 impl Default for ScoreWeights {
@@ -17,13 +20,14 @@ impl Default for ScoreWeights {
             typography: 0.15,
             color: 0.15,
             content: 0.10,
+            semantic: 0.10,
         }
     }
 }
 
 impl ScoreWeights {
     pub fn sum(&self) -> f32 {
-        self.pixel + self.layout + self.typography + self.color + self.content
+        self.pixel + self.layout + self.typography + self.color + self.content + self.semantic
     }
 }
 
@@ -56,9 +60,26 @@ pub fn calculate_combined_score(scores: &MetricScores, weights: &ScoreWeights) -
         total_weight += weights.content;
     }
 
+    if let Some(ref m) = scores.semantic {
+        weighted_sum += weights.semantic * m.score;
+        total_weight += weights.semantic;
+    }
+
     if total_weight > 0.0 {
         weighted_sum / total_weight
     } else {
         0.0
     }
 }
+
+/// Score of `metric`, if it was computed.
+pub fn metric_score(scores: &MetricScores, metric: MetricKind) -> Option<f32> {
+    match metric {
+        MetricKind::Pixel => scores.pixel.as_ref().map(|m| m.score),
+        MetricKind::Layout => scores.layout.as_ref().map(|m| m.score),
+        MetricKind::Typography => scores.typography.as_ref().map(|m| m.score),
+        MetricKind::Color => scores.color.as_ref().map(|m| m.score),
+        MetricKind::Content => scores.content.as_ref().map(|m| m.score),
+        MetricKind::Semantic => scores.semantic.as_ref().map(|m| m.score),
+    }
+}
@@ -176,7 +176,7 @@ impl SemanticAnalyzer {
     pub fn new(config: SemanticAnalyzerConfig) -> Self {
         Self {
             config,
-            client: reqwest::Client::new(),
+            client: crate::network::http_client(),
         }
     }
 
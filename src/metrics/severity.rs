@@ -0,0 +1,268 @@
+//! Severity calibration: how raw metric deltas map to minor/moderate/major.
+//!
+//! Teams disagree on what counts as "major", so the mapping is data rather
+//! than code. A [`SeverityProfile`] provides a complete starting point and the
+//! `[severity]` config section overrides individual entries.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::pixel::PixelDiffThresholds;
+use crate::types::{ColorDiff, ColorDiffKind, DiffSeverity, LayoutDiffKind, TypographyIssue};
+
+/// Built-in calibration presets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityProfile {
+    /// The long-standing defaults.
+    #[default]
+    Default,
+    /// Flags smaller deltas and rates structural changes higher.
+    Strict,
+    /// Tolerates more rendering variance; only clear breakage is major.
+    Lenient,
+}
+
+impl SeverityProfile {
+    pub fn calibration(self) -> SeverityCalibration {
+        use DiffSeverity::{Major, Minor, Moderate};
+
+        let (pixel, layout, typography, color, missing_text, extra_text) = match self {
+            SeverityProfile::Default => (
+                PixelDiffThresholds::default(),
+                [Major, Moderate, Moderate, Moderate, Minor, Minor],
+                [
+                    Major, Major, Moderate, Moderate, Minor, Moderate, Moderate, Moderate, Minor,
+                ],
+                [Major, Major, Minor, Moderate],
+                Major,
+                Minor,
+            ),
+            SeverityProfile::Strict => (
+                PixelDiffThresholds {
+                    minor: 0.03,
+                    moderate: 0.08,
+                    major: 0.2,
+                },
+                [Major, Major, Major, Moderate, Moderate, Moderate],
+                [
+                    Major, Major, Major, Moderate, Moderate, Moderate, Moderate, Moderate, Moderate,
+                ],
+                [Major, Major, Moderate, Major],
+                Major,
+                Moderate,
+            ),
+            SeverityProfile::Lenient => (
+                PixelDiffThresholds {
+                    minor: 0.08,
+                    moderate: 0.2,
+                    major: 0.4,
+                },
+                [Major, Minor, Minor, Minor, Minor, Minor],
+                [
+                    Moderate, Major, Minor, Minor, Minor, Minor, Minor, Minor, Minor,
+                ],
+                [Moderate, Moderate, Minor, Minor],
+                Moderate,
+                Minor,
+            ),
+        };
+
+        let layout_kinds = [
+            LayoutDiffKind::MissingElement,
+            LayoutDiffKind::ExtraElement,
+            LayoutDiffKind::PositionShift,
+            LayoutDiffKind::SizeChange,
+            LayoutDiffKind::SpacingMismatch,
+            LayoutDiffKind::AlignmentBreak,
+        ];
+        let typography_issues = [
+            TypographyIssue::FontFamilyMismatch,
+            TypographyIssue::FontNotLoaded,
+            TypographyIssue::FontSizeDiff,
+            TypographyIssue::FontWeightDiff,
+            TypographyIssue::LineHeightDiff,
+            TypographyIssue::LetterSpacingDiff,
+            TypographyIssue::LineWrapDiff,
+            TypographyIssue::BaselineShift,
+            TypographyIssue::VerticalRhythmDiff,
+        ];
+        let color_kinds = [
+            ColorDiffKind::PrimaryColorShift,
+            ColorDiffKind::AccentColorShift,
+            ColorDiffKind::BackgroundColorShift,
+            ColorDiffKind::GradientShift,
+        ];
+        SeverityCalibration {
+            pixel,
+            layout: layout_kinds.into_iter().zip(layout).collect(),
+            typography: typography_issues.into_iter().zip(typography).collect(),
+            color: color_kinds.into_iter().zip(color).collect(),
+            color_delta_e: None,
+            missing_text,
+            extra_text,
+        }
+    }
+}
+
+impl FromStr for SeverityProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" => Ok(SeverityProfile::Default),
+            "strict" => Ok(SeverityProfile::Strict),
+            "lenient" => Ok(SeverityProfile::Lenient),
+            other => Err(format!(
+                "unknown severity profile '{other}': expected default, strict or lenient"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SeverityProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SeverityProfile::Default => "default",
+            SeverityProfile::Strict => "strict",
+            SeverityProfile::Lenient => "lenient",
+        })
+    }
+}
+
+/// Delta E cut-offs for color findings; below `moderate` is minor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeltaEThresholds {
+    pub moderate: f32,
+    pub major: f32,
+}
+
+/// Resolved severity mapping for every metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeverityCalibration {
+    /// Average per-block pixel difference at which a block becomes a
+    /// minor/moderate/major region.
+    pub pixel: PixelDiffThresholds,
+    pub layout: HashMap<LayoutDiffKind, DiffSeverity>,
+    /// Per issue; a typography finding takes its most severe issue.
+    pub typography: HashMap<TypographyIssue, DiffSeverity>,
+    pub color: HashMap<ColorDiffKind, DiffSeverity>,
+    /// When set, color findings with a Delta E are rated by it instead of by kind.
+    pub color_delta_e: Option<DeltaEThresholds>,
+    pub missing_text: DiffSeverity,
+    pub extra_text: DiffSeverity,
+}
+
+impl Default for SeverityCalibration {
+    fn default() -> Self {
+        SeverityProfile::Default.calibration()
+    }
+}
+
+impl SeverityCalibration {
+    pub fn layout(&self, kind: LayoutDiffKind) -> DiffSeverity {
+        self.layout
+            .get(&kind)
+            .copied()
+            .unwrap_or(DiffSeverity::Moderate)
+    }
+
+    pub fn typography(&self, issues: &[TypographyIssue]) -> DiffSeverity {
+        issues
+            .iter()
+            .map(|issue| {
+                self.typography
+                    .get(issue)
+                    .copied()
+                    .unwrap_or(DiffSeverity::Minor)
+            })
+            .max()
+            .unwrap_or(DiffSeverity::Minor)
+    }
+
+    pub fn color(&self, diff: &ColorDiff) -> DiffSeverity {
+        if let (Some(thresholds), Some(delta_e)) = (self.color_delta_e, diff.delta_e) {
+            return if delta_e >= thresholds.major {
+                DiffSeverity::Major
+            } else if delta_e >= thresholds.moderate {
+                DiffSeverity::Moderate
+            } else {
+                DiffSeverity::Minor
+            };
+        }
+        self.color
+            .get(&diff.kind)
+            .copied()
+            .unwrap_or(DiffSeverity::Moderate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color_diff(kind: ColorDiffKind, delta_e: Option<f32>) -> ColorDiff {
+        ColorDiff {
+            kind,
+            ref_color: "#112233".to_string(),
+            impl_color: "#112244".to_string(),
+            delta_e,
+            element_id: None,
+            angle_delta: None,
+            style: None,
+            fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn default_profile_matches_the_historical_mapping() {
+        let calibration = SeverityCalibration::default();
+        assert_eq!(calibration.pixel, PixelDiffThresholds::default());
+        assert_eq!(
+            calibration.layout(LayoutDiffKind::MissingElement),
+            DiffSeverity::Major
+        );
+        assert_eq!(
+            calibration.layout(LayoutDiffKind::PositionShift),
+            DiffSeverity::Moderate
+        );
+        assert_eq!(
+            calibration.typography(&[
+                TypographyIssue::LineHeightDiff,
+                TypographyIssue::FontWeightDiff
+            ]),
+            DiffSeverity::Moderate
+        );
+        assert_eq!(
+            calibration.color(&color_diff(ColorDiffKind::BackgroundColorShift, Some(30.0))),
+            DiffSeverity::Minor
+        );
+    }
+
+    #[test]
+    fn delta_e_thresholds_override_kind_for_colors() {
+        let calibration = SeverityCalibration {
+            color_delta_e: Some(DeltaEThresholds {
+                moderate: 3.0,
+                major: 10.0,
+            }),
+            ..SeverityProfile::Lenient.calibration()
+        };
+        let primary =
+            |delta_e| calibration.color(&color_diff(ColorDiffKind::PrimaryColorShift, delta_e));
+        assert_eq!(primary(Some(1.0)), DiffSeverity::Minor);
+        assert_eq!(primary(Some(5.0)), DiffSeverity::Moderate);
+        assert_eq!(primary(Some(12.0)), DiffSeverity::Major);
+        assert_eq!(primary(None), DiffSeverity::Moderate);
+    }
+
+    #[test]
+    fn parses_profiles() {
+        assert_eq!("Strict".parse(), Ok(SeverityProfile::Strict));
+        assert_eq!(SeverityProfile::Lenient.to_string(), "lenient");
+        assert!("harsh".parse::<SeverityProfile>().is_err());
+    }
+}
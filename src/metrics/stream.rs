@@ -0,0 +1,265 @@
+//! Progressive comparison results for embedding UIs.
+//!
+//! [`compare_stream`] runs the metrics concurrently and yields each one as it
+//! completes, so IDE plugins and web UIs can show scores and top issues
+//! before the slowest metric finishes. [`run_metrics_until`] uses the same
+//! machinery to stop at a deadline and keep whatever finished in time.
+
+use std::sync::Arc;
+
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::error::DpcError;
+use crate::types::{MetricScores, NormalizedView};
+use crate::Result;
+
+use super::issues::generate_top_issues_with;
+use super::runner::{metrics_to_run, record_result};
+use super::scoring::{calculate_combined_score, ScoreWeights};
+use super::severity::SeverityCalibration;
+use super::{
+    ColorPaletteMetric, ContentSimilarity, LayoutSimilarity, Metric, MetricKind, MetricResult,
+    PixelSimilarity, SemanticSimilarity, TypographySimilarity,
+};
+
+/// A metric that can run on a worker thread.
+pub type SharedMetric = Arc<dyn Metric + Send + Sync>;
+
+/// Settings for [`compare_stream`].
+#[derive(Clone)]
+pub struct CompareStreamOptions {
+    /// Metric implementations to run; defaults to every built-in metric.
+    pub metrics: Vec<SharedMetric>,
+    /// Metrics to run; empty means all that have data.
+    pub selected: Vec<MetricKind>,
+    pub weights: ScoreWeights,
+    pub calibration: SeverityCalibration,
+    /// Top issues reported with each event.
+    pub max_issues: usize,
+}
+
+impl Default for CompareStreamOptions {
+    fn default() -> Self {
+        Self {
+            metrics: vec![
+                Arc::new(PixelSimilarity::default()),
+                Arc::new(LayoutSimilarity::default()),
+                Arc::new(TypographySimilarity::default()),
+                Arc::new(ColorPaletteMetric::default()),
+                Arc::new(ContentSimilarity::default()),
+                Arc::new(SemanticSimilarity::default()),
+            ],
+            selected: Vec::new(),
+            weights: ScoreWeights::default(),
+            calibration: SeverityCalibration::default(),
+            max_issues: 5,
+        }
+    }
+}
+
+/// One step of a [`compare_stream`].
+#[derive(Debug, Clone)]
+pub enum CompareEvent {
+    /// A metric finished. `similarity` and `top_issues` cover the metrics
+    /// completed so far.
+    MetricCompleted {
+        result: MetricResult,
+        completed: usize,
+        total: usize,
+        similarity: f32,
+        top_issues: Vec<String>,
+    },
+    /// Every metric finished; always the last event of a successful stream.
+    Finished {
+        scores: MetricScores,
+        similarity: f32,
+        top_issues: Vec<String>,
+    },
+}
+
+enum StreamState {
+    Pending {
+        reference: Arc<NormalizedView>,
+        implementation: Arc<NormalizedView>,
+        options: CompareStreamOptions,
+    },
+    Running {
+        tasks: MetricTasks,
+        scores: Box<MetricScores>,
+        total: usize,
+        options: CompareStreamOptions,
+    },
+    Done,
+}
+
+/// Scores of a deadline-bounded run.
+#[derive(Debug, Clone)]
+pub struct BudgetedScores {
+    pub scores: MetricScores,
+    /// Metrics still running at the deadline, in run order.
+    pub skipped: Vec<MetricKind>,
+}
+
+/// Run the metrics concurrently like [`run_metrics`](super::run_metrics),
+/// keeping the results that finish before `deadline`.
+///
+/// Metrics still running at the deadline are reported as skipped; they keep
+/// running on Tokio's blocking pool but their results are discarded. The
+/// first metric error is returned as-is.
+pub async fn run_metrics_until(
+    metrics: &[SharedMetric],
+    selected: &[MetricKind],
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    deadline: Instant,
+) -> Result<BudgetedScores> {
+    let to_run = metrics_to_run(metrics, selected, reference, implementation)?;
+    let mut tasks = spawn_metrics(
+        Arc::new(reference.clone()),
+        Arc::new(implementation.clone()),
+        metrics,
+        &to_run,
+    );
+
+    let mut scores = empty_scores();
+    let mut finished = Vec::with_capacity(to_run.len());
+    while let Ok(Some(joined)) = tokio::time::timeout_at(deadline, tasks.next()).await {
+        let result =
+            joined.map_err(|err| DpcError::Unknown(format!("metric task failed: {err}")))??;
+        finished.push(result.kind());
+        record_result(&mut scores, result);
+    }
+    let skipped = to_run
+        .into_iter()
+        .filter(|kind| !finished.contains(kind))
+        .collect();
+    Ok(BudgetedScores { scores, skipped })
+}
+
+/// Compare two views, yielding each metric as soon as it completes and a
+/// final [`CompareEvent::Finished`] with the combined result.
+///
+/// Metrics run on Tokio's blocking pool, so the stream must be polled inside
+/// a Tokio runtime. The first error ends the stream; dropping the stream
+/// stops waiting for metrics still running.
+pub fn compare_stream(
+    reference: NormalizedView,
+    implementation: NormalizedView,
+    options: CompareStreamOptions,
+) -> impl Stream<Item = Result<CompareEvent>> + Send {
+    let state = StreamState::Pending {
+        reference: Arc::new(reference),
+        implementation: Arc::new(implementation),
+        options,
+    };
+    stream::unfold(state, |state| async move {
+        let (mut tasks, mut scores, total, options) = match state {
+            StreamState::Done => return None,
+            StreamState::Pending {
+                reference,
+                implementation,
+                options,
+            } => match start_metrics(reference, implementation, &options) {
+                Ok(tasks) => {
+                    let total = tasks.len();
+                    (tasks, Box::new(empty_scores()), total, options)
+                }
+                Err(err) => return Some((Err(err), StreamState::Done)),
+            },
+            StreamState::Running {
+                tasks,
+                scores,
+                total,
+                options,
+            } => (tasks, scores, total, options),
+        };
+
+        let Some(joined) = tasks.next().await else {
+            let event = CompareEvent::Finished {
+                similarity: calculate_combined_score(&scores, &options.weights),
+                top_issues: top_issues(&scores, &options),
+                scores: *scores,
+            };
+            return Some((Ok(event), StreamState::Done));
+        };
+        let result = match joined {
+            Ok(Ok(result)) => result,
+            Ok(Err(err)) => return Some((Err(err), StreamState::Done)),
+            Err(err) => {
+                let err = DpcError::Unknown(format!("metric task failed: {err}"));
+                return Some((Err(err), StreamState::Done));
+            }
+        };
+        record_result(&mut scores, result.clone());
+        let event = CompareEvent::MetricCompleted {
+            result,
+            completed: total - tasks.len(),
+            total,
+            similarity: calculate_combined_score(&scores, &options.weights),
+            top_issues: top_issues(&scores, &options),
+        };
+        let state = StreamState::Running {
+            tasks,
+            scores,
+            total,
+            options,
+        };
+        Some((Ok(event), state))
+    })
+}
+
+type MetricTasks = FuturesUnordered<JoinHandle<Result<MetricResult>>>;
+
+fn start_metrics(
+    reference: Arc<NormalizedView>,
+    implementation: Arc<NormalizedView>,
+    options: &CompareStreamOptions,
+) -> Result<MetricTasks> {
+    let to_run = metrics_to_run(
+        &options.metrics,
+        &options.selected,
+        &reference,
+        &implementation,
+    )?;
+    Ok(spawn_metrics(
+        reference,
+        implementation,
+        &options.metrics,
+        &to_run,
+    ))
+}
+
+fn spawn_metrics(
+    reference: Arc<NormalizedView>,
+    implementation: Arc<NormalizedView>,
+    metrics: &[SharedMetric],
+    to_run: &[MetricKind],
+) -> MetricTasks {
+    metrics
+        .iter()
+        .filter(|metric| to_run.contains(&metric.kind()))
+        .map(|metric| {
+            let metric = Arc::clone(metric);
+            let reference = Arc::clone(&reference);
+            let implementation = Arc::clone(&implementation);
+            tokio::task::spawn_blocking(move || metric.compute(&reference, &implementation))
+        })
+        .collect()
+}
+
+fn empty_scores() -> MetricScores {
+    MetricScores {
+        pixel: None,
+        layout: None,
+        typography: None,
+        color: None,
+        content: None,
+        semantic: None,
+    }
+}
+
+fn top_issues(scores: &MetricScores, options: &CompareStreamOptions) -> Vec<String> {
+    generate_top_issues_with(scores, options.max_issues, &options.calibration)
+}
@@ -1,9 +1,10 @@
 use super::*;
-use crate::image_alignment::ImageAlignmentOptions;
+use crate::image_alignment::{AlignmentMethod, ImageAlignmentOptions};
 use crate::types::{
     ColorDiff, ColorDiffKind, ColorMetric, ComputedStyle, ContentMetric, DiffSeverity,
     LayoutDiffKind, LayoutDiffRegion, LayoutMetric, PixelDiffReason, PixelDiffRegion, PixelMetric,
-    ResourceKind, TypographyDiff, TypographyIssue, TypographyMetric, TypographyStyle,
+    PixelOffset, RegionUnit, ResourceKind, SemanticMetric, TypographyDiff, TypographyIssue,
+    TypographyMetric, TypographyStyle,
 };
 use crate::{MetricScores, NormalizedView};
 use image::{ImageFormat, Rgba, RgbaImage};
@@ -117,6 +118,176 @@ fn run_metrics_skips_structural_when_no_structure() {
     assert!(scores.content.is_none());
 }
 
+#[tokio::test]
+async fn compare_stream_yields_each_metric_then_the_result() {
+    use futures::StreamExt;
+    let ref_img = solid_image([10, 20, 30, 255]);
+    let impl_img = solid_image([10, 20, 30, 255]);
+    let events: Vec<CompareEvent> = compare_stream(
+        view_from_file(ref_img.path(), 4, 4),
+        view_from_file(impl_img.path(), 4, 4),
+        CompareStreamOptions::default(),
+    )
+    .map(|event| event.expect("metrics should succeed"))
+    .collect()
+    .await;
+
+    // Pixel and color have data; the structural metrics are skipped.
+    assert_eq!(events.len(), 3);
+    let mut kinds = Vec::new();
+    for (idx, event) in events[..2].iter().enumerate() {
+        let CompareEvent::MetricCompleted {
+            result,
+            completed,
+            total,
+            ..
+        } = event
+        else {
+            panic!("expected a metric event, got {event:?}");
+        };
+        assert_eq!((*completed, *total), (idx + 1, 2));
+        kinds.push(result.kind());
+    }
+    kinds.sort_by_key(|kind| kind.to_string());
+    assert_eq!(kinds, vec![MetricKind::Color, MetricKind::Pixel]);
+    let CompareEvent::Finished {
+        scores, similarity, ..
+    } = &events[2]
+    else {
+        panic!("expected the final event");
+    };
+    assert!(scores.pixel.is_some() && scores.color.is_some());
+    assert!(*similarity > 0.99);
+}
+
+#[tokio::test]
+async fn compare_stream_reports_unavailable_metrics() {
+    use futures::StreamExt;
+    let options = CompareStreamOptions {
+        metrics: vec![],
+        selected: vec![MetricKind::Pixel],
+        ..CompareStreamOptions::default()
+    };
+    let events: Vec<_> = compare_stream(dummy_view(), dummy_view(), options)
+        .collect()
+        .await;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], Err(crate::DpcError::Config(_))));
+}
+
+#[tokio::test]
+async fn run_metrics_v2_adapts_sync_metrics() {
+    let ref_img = solid_image([10, 20, 30, 255]);
+    let impl_img = solid_image([200, 20, 30, 255]);
+    let ref_view = view_from_file(ref_img.path(), 4, 4);
+    let impl_view = view_from_file(impl_img.path(), 4, 4);
+    let expected = run_metrics(&default_metrics(), &[], &ref_view, &impl_view).unwrap();
+
+    let ctx = MetricContext::new(ref_view, impl_view);
+    let scores = run_metrics_v2(&default_metrics_v2(), &[], &ctx)
+        .await
+        .unwrap();
+    assert_eq!(
+        scores.pixel.map(|m| m.score),
+        expected.pixel.map(|m| m.score)
+    );
+    assert_eq!(
+        scores.color.map(|m| m.score),
+        expected.color.map(|m| m.score)
+    );
+    assert!(scores.layout.is_none() && scores.content.is_none());
+}
+
+#[tokio::test]
+async fn run_metrics_v2_passes_images_and_artifact_sink_to_native_metrics() {
+    struct WidthMetric;
+    impl MetricV2 for WidthMetric {
+        fn kind(&self) -> MetricKind {
+            MetricKind::Pixel
+        }
+        fn compute<'a>(
+            &'a self,
+            ctx: &'a MetricContext,
+        ) -> futures::future::BoxFuture<'a, crate::Result<MetricResult>> {
+            Box::pin(async move {
+                let image = ctx.implementation_image().await?;
+                let path = ctx.artifacts.path("width.txt").expect("sink enabled");
+                std::fs::write(path, image.width().to_string())?;
+                Ok(MetricResult::Pixel(PixelMetric {
+                    score: 1.0,
+                    diff_regions: vec![],
+                    semantic_diffs: None,
+                    offset: None,
+                }))
+            })
+        }
+    }
+
+    let img = solid_image([10, 20, 30, 255]);
+    let dir = tempfile::tempdir().unwrap();
+    let sink = ArtifactSink::new(dir.path());
+    let ctx = MetricContext::new(
+        view_from_file(img.path(), 8, 8),
+        view_from_file(img.path(), 8, 8),
+    )
+    .with_artifacts(sink.clone());
+    let metrics: Vec<Box<dyn MetricV2>> = vec![Box::new(WidthMetric)];
+    let scores = run_metrics_v2(&metrics, &[MetricKind::Pixel], &ctx)
+        .await
+        .unwrap();
+    assert_eq!(scores.pixel.unwrap().score, 1.0);
+    assert_eq!(sink.written(), vec![dir.path().join("width.txt")]);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("width.txt")).unwrap(),
+        "8"
+    );
+}
+
+#[tokio::test]
+async fn run_metrics_v2_stops_when_cancelled() {
+    let img = solid_image([10, 20, 30, 255]);
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let ctx = MetricContext::new(
+        view_from_file(img.path(), 4, 4),
+        view_from_file(img.path(), 4, 4),
+    )
+    .with_cancellation(cancel.clone());
+    cancel.cancel();
+    let err = run_metrics_v2(&default_metrics_v2(), &[], &ctx)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, crate::DpcError::Metric(_)));
+}
+
+#[tokio::test]
+async fn run_metrics_until_skips_metrics_past_the_deadline() {
+    struct SlowPixel;
+    impl Metric for SlowPixel {
+        fn kind(&self) -> MetricKind {
+            MetricKind::Pixel
+        }
+        fn compute(&self, r: &NormalizedView, i: &NormalizedView) -> crate::Result<MetricResult> {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            PixelSimilarity::default().compute(r, i)
+        }
+    }
+
+    let ref_img = solid_image([10, 20, 30, 255]);
+    let ref_view = view_from_file(ref_img.path(), 4, 4);
+    let metrics: Vec<SharedMetric> = vec![
+        std::sync::Arc::new(SlowPixel),
+        std::sync::Arc::new(ColorPaletteMetric::default()),
+    ];
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(100);
+    let selected = [MetricKind::Pixel, MetricKind::Color];
+    let budgeted = run_metrics_until(&metrics, &selected, &ref_view, &ref_view, deadline)
+        .await
+        .unwrap();
+    assert_eq!(budgeted.skipped, vec![MetricKind::Pixel]);
+    assert!(budgeted.scores.pixel.is_none());
+    assert!(budgeted.scores.color.is_some());
+}
+
 #[test]
 fn run_metrics_scores_layout_even_when_impl_is_empty() {
     let ref_view = view_with_dom(vec![("button", bbox(0.0, 0.0, 0.5, 0.5))]);
@@ -151,6 +322,7 @@ fn combined_score_rescales_to_present_metrics() {
         typography: 0.2,
         color: 0.1,
         content: 0.1,
+        semantic: 0.1,
     };
 
     let scores_pixel_only = MetricScores {
@@ -158,11 +330,13 @@ fn combined_score_rescales_to_present_metrics() {
             score: 0.4,
             diff_regions: vec![],
             semantic_diffs: None,
+            offset: None,
         }),
         layout: None,
         typography: None,
         color: None,
         content: None,
+        semantic: None,
     };
 
     let combined_pixel = calculate_combined_score(&scores_pixel_only, &weights);
@@ -185,6 +359,7 @@ fn combined_score_handles_zero_weights_and_missing_metrics() {
         typography: None,
         color: None,
         content: None,
+        semantic: None,
     };
     let zero_result = calculate_combined_score(&empty_scores, &ScoreWeights::default());
     assert_eq!(zero_result, 0.0);
@@ -194,6 +369,7 @@ fn combined_score_handles_zero_weights_and_missing_metrics() {
             score: 1.0,
             diff_regions: vec![],
             semantic_diffs: None,
+            offset: None,
         }),
         layout: Some(LayoutMetric {
             score: 0.25,
@@ -202,6 +378,7 @@ fn combined_score_handles_zero_weights_and_missing_metrics() {
         typography: None,
         color: None,
         content: None,
+        semantic: None,
     };
     let weights = ScoreWeights {
         pixel: 0.0,
@@ -209,6 +386,7 @@ fn combined_score_handles_zero_weights_and_missing_metrics() {
         typography: 0.0,
         color: 0.0,
         content: 0.0,
+        semantic: 0.0,
     };
     let combined = calculate_combined_score(&scores, &weights);
     assert!((combined - 0.25).abs() < 1e-6);
@@ -220,6 +398,7 @@ fn generate_top_issues_orders_by_severity_and_limits_count() {
         pixel: Some(PixelMetric {
             score: 0.4,
             diff_regions: vec![PixelDiffRegion {
+                fingerprint: None,
                 x: 0.0,
                 y: 0.0,
                 width: 0.2,
@@ -227,36 +406,55 @@ fn generate_top_issues_orders_by_severity_and_limits_count() {
                 severity: DiffSeverity::Minor,
                 reason: PixelDiffReason::PixelChange,
                 intensity: None,
+                max_intensity: None,
+                pixel_bounds: None,
+                css_bounds: None,
             }],
             semantic_diffs: None,
+            offset: None,
         }),
         layout: Some(LayoutMetric {
             score: 0.6,
             diff_regions: vec![LayoutDiffRegion {
+                fingerprint: None,
                 x: 0.1,
                 y: 0.1,
                 width: 0.2,
                 height: 0.2,
+                unit: None,
                 kind: LayoutDiffKind::ExtraElement,
                 element_type: Some("button".to_string()),
                 label: None,
+                detail: None,
+                region_count: None,
+                pixel_bounds: None,
+                css_bounds: None,
             }],
         }),
         typography: None,
         color: Some(ColorMetric {
             score: 0.5,
             diffs: vec![ColorDiff {
+                fingerprint: None,
                 kind: ColorDiffKind::PrimaryColorShift,
                 ref_color: "#FFFFFF".to_string(),
                 impl_color: "#000000".to_string(),
                 delta_e: Some(10.0),
+                element_id: None,
+                angle_delta: None,
+                style: None,
             }],
         }),
         content: Some(ContentMetric {
+            missing_text_fingerprints: Vec::new(),
+            extra_text_fingerprints: Vec::new(),
             score: 0.4,
             missing_text: vec!["Hero title".to_string()],
             extra_text: vec!["Extra banner".to_string()],
+            placeholder_text: vec![],
+            text_labels: Default::default(),
         }),
+        semantic: None,
     };
 
     let ordered = generate_top_issues(&scores, 10);
@@ -301,8 +499,11 @@ fn generate_top_issues_includes_color_and_typography_and_respects_limit() {
         typography: Some(TypographyMetric {
             score: 0.6,
             diffs: vec![TypographyDiff {
+                fingerprint: None,
                 element_id_ref: Some("title".into()),
                 element_id_impl: None,
+                label: None,
+                style: None,
                 issues: vec![TypographyIssue::FontFamilyMismatch],
                 details: None,
             }],
@@ -310,13 +511,18 @@ fn generate_top_issues_includes_color_and_typography_and_respects_limit() {
         color: Some(ColorMetric {
             score: 0.5,
             diffs: vec![ColorDiff {
+                fingerprint: None,
                 kind: ColorDiffKind::AccentColorShift,
                 ref_color: "#FFFFFF".to_string(),
                 impl_color: "#111111".to_string(),
                 delta_e: Some(8.0),
+                element_id: None,
+                angle_delta: None,
+                style: None,
             }],
         }),
         content: None,
+        semantic: None,
     };
 
     let issues = generate_top_issues(&scores, 1);
@@ -358,8 +564,11 @@ fn generate_top_issues_prioritizes_palette_over_typography() {
         typography: Some(TypographyMetric {
             score: 0.7,
             diffs: vec![TypographyDiff {
+                fingerprint: None,
                 element_id_ref: Some("caption".into()),
                 element_id_impl: Some("caption_impl".into()),
+                label: None,
+                style: None,
                 issues: vec![TypographyIssue::LineHeightDiff],
                 details: None,
             }],
@@ -367,13 +576,18 @@ fn generate_top_issues_prioritizes_palette_over_typography() {
         color: Some(ColorMetric {
             score: 0.6,
             diffs: vec![ColorDiff {
+                fingerprint: None,
                 kind: ColorDiffKind::BackgroundColorShift,
                 ref_color: "#111111".to_string(),
                 impl_color: "#222222".to_string(),
                 delta_e: Some(4.0),
+                element_id: None,
+                angle_delta: None,
+                style: None,
             }],
         }),
         content: None,
+        semantic: None,
     };
 
     let issues = generate_top_issues(&scores, 5);
@@ -461,6 +675,140 @@ fn pixel_metric_penalizes_large_localized_difference_on_shared_background() {
     );
 }
 
+#[test]
+fn pixel_metric_ciede2000_diff_space_catches_hue_shift_at_equal_luma() {
+    let blue = Rgba([59, 130, 246, 255]);
+    let purple = Rgba([147, 104, 246, 255]);
+    let ref_img = RgbaImage::from_pixel(64, 64, blue);
+    let mut impl_img = ref_img.clone();
+    for y in 0..32 {
+        for x in 32..64 {
+            impl_img.put_pixel(x, y, purple);
+        }
+    }
+    assert_eq!(
+        image::DynamicImage::ImageRgba8(ref_img.clone()).to_luma8(),
+        image::DynamicImage::ImageRgba8(impl_img.clone()).to_luma8(),
+        "fixture colors must share their luma"
+    );
+
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 64, 64);
+    let impl_view = view_from_file(impl_file.path(), 64, 64);
+    let run = |diff_space| match (PixelSimilarity {
+        diff_space,
+        ..PixelSimilarity::default()
+    })
+    .compute(&ref_view, &impl_view)
+    .unwrap()
+    {
+        MetricResult::Pixel(p) => p,
+        _ => unreachable!(),
+    };
+
+    let luma = run(PixelDiffSpace::Luma);
+    assert!(luma.diff_regions.is_empty());
+    assert!(luma.score > 0.99);
+
+    let perceptual = run(PixelDiffSpace::Ciede2000);
+    assert_eq!(perceptual.diff_regions.len(), 1);
+    let region = &perceptual.diff_regions[0];
+    assert!((region.x - 0.5).abs() < 0.01 && (region.width - 0.5).abs() < 0.01);
+    assert!(perceptual.score < luma.score);
+}
+
+#[test]
+fn pixel_and_color_metrics_ignore_letterbox_padding() {
+    let mut ref_img = RgbaImage::from_pixel(200, 120, Rgba([0, 0, 0, 0]));
+    let mut impl_img = RgbaImage::from_pixel(200, 120, Rgba([250, 250, 250, 255]));
+    for y in 0..120 {
+        for x in 20..180 {
+            let pixel = if (40..80).contains(&y) && (60..140).contains(&x) {
+                Rgba([30, 60, 200, 255])
+            } else {
+                Rgba([240, 240, 240, 255])
+            };
+            ref_img.put_pixel(x, y, pixel);
+            impl_img.put_pixel(x, y, pixel);
+        }
+    }
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let mut ref_view = view_from_file(ref_file.path(), 200, 120);
+    let impl_view = view_from_file(impl_file.path(), 200, 120);
+
+    let padded = PixelSimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!(padded.score < 0.95, "bars should count without geometry");
+
+    ref_view.content_area = Some(bbox(20.0, 0.0, 160.0, 120.0));
+    let pixel = PixelSimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!((pixel.score - 1.0).abs() < 1e-4, "{}", pixel.score);
+    assert!(pixel.diff_regions.is_empty(), "{:?}", pixel.diff_regions);
+
+    let impl_view = NormalizedView {
+        content_area: ref_view.content_area,
+        ..impl_view
+    };
+    let with_bars = NormalizedView {
+        content_area: None,
+        ..ref_view.clone()
+    };
+    let color = ColorPaletteMetric::default();
+    let padded = color.compute_metric(&with_bars, &impl_view).unwrap();
+    let content = color.compute_metric(&ref_view, &impl_view).unwrap();
+    assert!(content.score > padded.score);
+    assert!((content.score - 1.0).abs() < 1e-4, "{}", content.score);
+}
+
+#[test]
+fn transparency_mode_controls_how_transparent_pixels_compare() {
+    let mut ref_img = RgbaImage::from_pixel(64, 32, Rgba([0, 0, 0, 0]));
+    let mut impl_img = RgbaImage::from_pixel(64, 32, Rgba([255, 255, 255, 255]));
+    for y in 0..32 {
+        for x in 0..32 {
+            ref_img.put_pixel(x, y, Rgba([200, 40, 40, 255]));
+            impl_img.put_pixel(x, y, Rgba([200, 40, 40, 255]));
+        }
+    }
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 64, 32);
+    let impl_view = view_from_file(impl_file.path(), 64, 32);
+
+    let pixel_score = |transparency_mode| {
+        PixelSimilarity {
+            transparency_mode,
+            ..PixelSimilarity::default()
+        }
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap()
+        .score
+    };
+    assert!(pixel_score(TransparencyMode::Ignore) < 0.5);
+    assert!((pixel_score(TransparencyMode::Exclude) - 1.0).abs() < 1e-4);
+    assert!((pixel_score(TransparencyMode::Composite([255, 255, 255])) - 1.0).abs() < 1e-4);
+
+    let color_score = |transparency_mode| {
+        ColorPaletteMetric {
+            transparency_mode,
+            ..ColorPaletteMetric::default()
+        }
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap()
+        .score
+    };
+    assert!(color_score(TransparencyMode::Ignore) < 1.0);
+    assert!(
+        (color_score(TransparencyMode::Composite([255, 255, 255])) - 1.0).abs() < 1e-4,
+        "compositing over white should match the white implementation"
+    );
+}
+
 #[test]
 fn pixel_metric_keeps_tiny_localized_difference_high() {
     let ref_img = RgbaImage::from_pixel(200, 120, Rgba([240, 240, 240, 255]));
@@ -505,6 +853,7 @@ fn pixel_metric_alignment_handles_shifted_images() {
             enabled: true,
             max_shift: 4,
             downscale_max_dim: 64,
+            ..ImageAlignmentOptions::default()
         },
         ..PixelSimilarity::default()
     };
@@ -517,6 +866,57 @@ fn pixel_metric_alignment_handles_shifted_images() {
     assert!(score_align > score_no_align);
 }
 
+#[test]
+fn pixel_metric_reports_offset_found_by_phase_correlation() {
+    let ref_img = patterned_image();
+    let impl_img = shift_image(&ref_img, 2, -1);
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 20, 20);
+    let impl_view = view_from_file(impl_file.path(), 20, 20);
+
+    for method in [AlignmentMethod::Search, AlignmentMethod::PhaseCorrelation] {
+        let metric = PixelSimilarity {
+            alignment: ImageAlignmentOptions {
+                enabled: true,
+                max_shift: 4,
+                method,
+                ..ImageAlignmentOptions::default()
+            },
+            ..PixelSimilarity::default()
+        };
+        let pixel = metric.compute_metric(&ref_view, &impl_view).unwrap();
+        assert_eq!(
+            pixel.offset,
+            Some(PixelOffset { x: 2, y: -1 }),
+            "{method:?}"
+        );
+        assert!(pixel.score > 0.95);
+
+        let scores = MetricScores {
+            pixel: Some(pixel),
+            layout: None,
+            typography: None,
+            color: None,
+            content: None,
+            semantic: None,
+        };
+        let issues = generate_top_issues(&scores, 5);
+        assert!(issues[0].contains("offset by 2px horizontally and -1px vertically"));
+    }
+
+    let aligned = PixelSimilarity {
+        alignment: ImageAlignmentOptions {
+            enabled: true,
+            method: AlignmentMethod::PhaseCorrelation,
+            ..ImageAlignmentOptions::default()
+        },
+        ..PixelSimilarity::default()
+    };
+    let same = aligned.compute_metric(&ref_view, &ref_view).unwrap();
+    assert_eq!(same.offset, None);
+}
+
 #[test]
 fn layout_metric_partial_match_scores_between_zero_and_one() {
     let ref_view = view_with_dom(vec![
@@ -556,6 +956,336 @@ fn layout_metric_reports_extra_elements() {
         "extra elements should be reported"
     );
     assert!(layout.score < 1.0);
+    // DOM boxes are CSS pixels, whatever their size.
+    assert!(layout
+        .diff_regions
+        .iter()
+        .all(|d| d.unit == Some(RegionUnit::Css)));
+}
+
+#[test]
+fn layout_metric_reports_each_extra_once_and_merges_overlaps() {
+    let ref_view = view_with_dom(vec![("button", bbox(0.0, 0.0, 0.2, 0.2))]);
+    let impl_view = view_with_dom(vec![
+        ("button", bbox(0.0, 0.0, 0.2, 0.2)),
+        // A card and two children, none of them in the reference.
+        ("section", bbox(0.5, 0.5, 0.4, 0.3)),
+        ("img", bbox(0.55, 0.55, 0.1, 0.1)),
+        ("span", bbox(0.7, 0.6, 0.15, 0.05)),
+        // A separate extra element elsewhere.
+        ("input", bbox(0.0, 0.9, 0.1, 0.05)),
+    ]);
+    let layout = match LayoutSimilarity::default()
+        .compute(&ref_view, &impl_view)
+        .unwrap()
+    {
+        MetricResult::Layout(m) => m,
+        _ => unreachable!(),
+    };
+
+    let extras: Vec<_> = layout
+        .diff_regions
+        .iter()
+        .filter(|d| d.kind == LayoutDiffKind::ExtraElement)
+        .collect();
+    assert_eq!(extras.len(), 2, "{extras:?}");
+    let card = &extras[0];
+    assert_eq!(card.region_count, Some(3));
+    assert_eq!(card.element_type.as_deref(), Some("other"));
+    let bounds = [card.x, card.y, card.width, card.height];
+    for (got, want) in bounds.into_iter().zip([0.5, 0.5, 0.4, 0.3]) {
+        assert!((got - want).abs() < 1e-6, "{bounds:?}");
+    }
+    assert_eq!(extras[1].region_count, None);
+    assert_eq!(extras[1].element_type.as_deref(), Some("input"));
+
+    let issues = generate_top_issues(
+        &MetricScores {
+            pixel: None,
+            layout: Some(layout.clone()),
+            typography: None,
+            color: None,
+            content: None,
+            semantic: None,
+        },
+        10,
+    );
+    assert!(
+        issues
+            .iter()
+            .any(|i| i.contains("other (with 2 overlapping)")),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn findings_carry_element_names_into_regions_and_issues() {
+    let named = |mut view: NormalizedView, labels: &[(&str, &str, &str)]| {
+        let nodes = &mut view.dom.as_mut().unwrap().nodes;
+        for (id, attribute, value) in labels {
+            let node = nodes.iter_mut().find(|n| n.id == *id).unwrap();
+            node.attributes
+                .insert(attribute.to_string(), value.to_string());
+        }
+        view
+    };
+    let ref_view = named(
+        view_with_dom(vec![
+            ("button", bbox(0.0, 0.0, 0.2, 0.1)),
+            ("img", bbox(0.5, 0.5, 0.3, 0.3)),
+            ("p:Buy now", bbox(0.0, 0.8, 0.2, 0.05)),
+        ]),
+        &[
+            ("n0", "data-testid", "hero-cta"),
+            ("n1", "aria-label", "Hero image"),
+            ("n2", "aria-label", "Pricing note"),
+        ],
+    );
+    let impl_view = named(
+        view_with_dom(vec![("button", bbox(0.15, 0.0, 0.2, 0.1))]),
+        &[("n0", "data-testid", "cta")],
+    );
+
+    let layout = LayoutSimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    let label_of = |kind| {
+        layout
+            .diff_regions
+            .iter()
+            .find(|d| d.kind == kind)
+            .and_then(|d| d.label.as_deref())
+    };
+    // The design's name wins for matched pairs.
+    assert_eq!(label_of(LayoutDiffKind::PositionShift), Some("hero-cta"));
+    assert_eq!(label_of(LayoutDiffKind::MissingElement), Some("Hero image"));
+
+    let content = ContentSimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert_eq!(content.text_labels["Buy now"], "Pricing note");
+
+    let issues = generate_top_issues(
+        &MetricScores {
+            pixel: None,
+            layout: Some(layout),
+            typography: None,
+            color: None,
+            content: Some(content),
+            semantic: None,
+        },
+        10,
+    );
+    assert!(
+        issues.iter().any(|i| i.contains("'Hero image' is missing")),
+        "{issues:?}"
+    );
+    assert!(
+        issues
+            .iter()
+            .any(|i| i.contains("Text 'Buy now' in 'Pricing note' is missing")),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn figma_labels_skip_generated_layer_names() {
+    use crate::types::FigmaNode;
+    let layer = |name: &str| FigmaNode {
+        id: "1:2".to_string(),
+        name: Some(name.to_string()),
+        node_type: "FRAME".to_string(),
+        bounding_box: bbox(0.0, 0.0, 10.0, 10.0),
+        text: None,
+        typography: None,
+        line_count: None,
+        fills: vec![],
+        children: vec![],
+        auto_layout: None,
+        constraints: None,
+        responsive_box: None,
+        styles: std::collections::HashMap::new(),
+    };
+    assert_eq!(layer("CTA / Primary").label(), Some("CTA / Primary"));
+    assert_eq!(layer("Hero Title").label(), Some("Hero Title"));
+    assert_eq!(layer("Frame 12").label(), None);
+    assert_eq!(layer("Rectangle").label(), None);
+    assert_eq!(layer("  ").label(), None);
+}
+
+#[test]
+fn layout_metric_reports_a_moved_section_once_with_residual_child_shifts() {
+    let nested = |nodes: Vec<(&str, crate::types::BoundingBox)>| {
+        let mut view = view_with_dom(nodes);
+        for node in view.dom.as_mut().unwrap().nodes.iter_mut().skip(1) {
+            node.parent = Some("n0".to_string());
+        }
+        view
+    };
+    let ref_view = nested(vec![
+        ("section", bbox(100.0, 100.0, 400.0, 300.0)),
+        ("button", bbox(150.0, 150.0, 100.0, 50.0)),
+        ("img", bbox(300.0, 150.0, 150.0, 150.0)),
+        ("input", bbox(150.0, 300.0, 200.0, 50.0)),
+    ]);
+    // The whole section moved 250px right; the input moved 50px further.
+    let impl_view = nested(vec![
+        ("section", bbox(350.0, 100.0, 400.0, 300.0)),
+        ("button", bbox(400.0, 150.0, 100.0, 50.0)),
+        ("img", bbox(550.0, 150.0, 150.0, 150.0)),
+        ("input", bbox(450.0, 300.0, 200.0, 50.0)),
+    ]);
+
+    let layout = LayoutSimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    let kinds: Vec<_> = layout
+        .diff_regions
+        .iter()
+        .map(|d| (d.kind, d.element_type.as_deref(), d.detail.as_deref()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (
+                LayoutDiffKind::PositionShift,
+                Some("other"),
+                Some("section moved with 3 nested element(s)")
+            ),
+            (LayoutDiffKind::PositionShift, Some("input"), None),
+            (
+                LayoutDiffKind::AlignmentBreak,
+                Some("input"),
+                Some("left-aligned with the button in design but indented 50px in implementation")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn layout_metric_describes_broken_alignments_between_siblings() {
+    let card = |price_x: f32| {
+        let mut view = view_with_dom(vec![
+            ("section", bbox(0.0, 0.0, 600.0, 400.0)),
+            ("h2", bbox(20.0, 20.0, 300.0, 40.0)),
+            ("p", bbox(price_x, 80.0, 120.0, 30.0)),
+        ]);
+        let nodes = &mut view.dom.as_mut().unwrap().nodes;
+        for (node, label) in nodes.iter_mut().skip(1).zip(["Title", "Price"]) {
+            node.parent = Some("n0".to_string());
+            node.attributes
+                .insert("aria-label".to_string(), label.to_string());
+        }
+        view
+    };
+
+    let layout = LayoutSimilarity::default()
+        .compute_metric(&card(20.0), &card(32.0))
+        .unwrap();
+    assert_eq!(layout.diff_regions.len(), 1, "{:?}", layout.diff_regions);
+    let region = &layout.diff_regions[0];
+    assert_eq!(region.kind, LayoutDiffKind::AlignmentBreak);
+    assert_eq!(region.label.as_deref(), Some("Price"));
+    assert_eq!(region.x, 32.0);
+
+    let issues = generate_top_issues(
+        &MetricScores {
+            pixel: None,
+            layout: Some(layout),
+            typography: None,
+            color: None,
+            content: None,
+            semantic: None,
+        },
+        10,
+    );
+    assert_eq!(
+        issues,
+        ["'Price' is left-aligned with 'Title' in design but indented 12px in implementation."]
+    );
+
+    let unchanged = LayoutSimilarity::default()
+        .compute_metric(&card(20.0), &card(20.0))
+        .unwrap();
+    assert!(unchanged.diff_regions.is_empty());
+}
+
+#[test]
+fn layout_metric_reports_auto_layout_gap_mismatch() {
+    use crate::types::{
+        AutoLayout, AutoLayoutAlign, EdgeInsets, FigmaNode, FigmaSnapshot, FlexLayout,
+        LayoutDirection,
+    };
+    let padding = EdgeInsets {
+        top: 8.0,
+        right: 8.0,
+        bottom: 8.0,
+        left: 8.0,
+    };
+    let mut ref_view = dummy_view();
+    ref_view.kind = ResourceKind::Figma;
+    ref_view.figma_tree = Some(FigmaSnapshot {
+        schema_version: FigmaSnapshot::SCHEMA_VERSION,
+        file_key: "file".to_string(),
+        node_id: "1:1".to_string(),
+        name: None,
+        version: None,
+        frame_size: None,
+        export_scale: None,
+        nodes: vec![FigmaNode {
+            id: "1:2".to_string(),
+            name: Some("Row".to_string()),
+            node_type: "FRAME".to_string(),
+            bounding_box: bbox(10.0, 10.0, 80.0, 30.0),
+            text: None,
+            typography: None,
+            line_count: None,
+            fills: vec![],
+            children: vec![],
+            auto_layout: Some(AutoLayout {
+                direction: LayoutDirection::Horizontal,
+                gap: 16.0,
+                padding,
+                primary_align: AutoLayoutAlign::Min,
+                counter_align: AutoLayoutAlign::Center,
+                wrap: false,
+            }),
+            constraints: None,
+            responsive_box: None,
+            styles: std::collections::HashMap::new(),
+        }],
+        styles: Vec::new(),
+    });
+    let mut impl_view = view_with_dom(vec![("div", bbox(10.0, 11.0, 80.0, 30.0))]);
+    impl_view.dom.as_mut().unwrap().nodes[0].computed_style = Some(ComputedStyle {
+        flex: Some(FlexLayout {
+            direction: "row".to_string(),
+            row_gap: 0.0,
+            column_gap: 8.0,
+            padding,
+            justify_content: Some("flex-start".to_string()),
+            align_items: Some("center".to_string()),
+            wrap: false,
+        }),
+        ..Default::default()
+    });
+
+    let layout = match LayoutSimilarity::default()
+        .compute(&ref_view, &impl_view)
+        .unwrap()
+    {
+        MetricResult::Layout(m) => m,
+        _ => unreachable!(),
+    };
+    let mismatch = layout
+        .diff_regions
+        .iter()
+        .find(|d| matches!(d.kind, LayoutDiffKind::SpacingMismatch))
+        .expect("gap difference should be reported");
+    assert_eq!(
+        mismatch.detail.as_deref(),
+        Some("design uses 16px gap auto-layout; implementation flexbox gap is 8px")
+    );
 }
 
 #[test]
@@ -698,6 +1428,193 @@ fn typography_metric_font_family_mismatch_penalized() {
     assert!(score < 1.0);
 }
 
+#[test]
+fn typography_metric_reports_font_fallback_separately_from_family_mismatch() {
+    let style = TypographyStyle {
+        font_family: Some("Inter".into()),
+        font_size: Some(16.0),
+        font_weight: Some("400".into()),
+        line_height: Some(24.0),
+        letter_spacing: None,
+    };
+    let ref_view = view_with_text("Hello", style.clone());
+    let mut impl_view = view_with_text(
+        "Hello",
+        TypographyStyle {
+            font_family: Some("\"Inter\", Arial, sans-serif".into()),
+            ..style
+        },
+    );
+    let node_style = impl_view.dom.as_mut().unwrap().nodes[0]
+        .computed_style
+        .as_mut()
+        .unwrap();
+
+    node_style.rendered_font_family = Some("Inter".into());
+    let loaded = TypographySimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!(loaded.diffs.is_empty(), "{:?}", loaded.diffs);
+
+    let node_style = impl_view.dom.as_mut().unwrap().nodes[0]
+        .computed_style
+        .as_mut()
+        .unwrap();
+    node_style.rendered_font_family = Some("Arial".into());
+    let fell_back = TypographySimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!(fell_back.score < 1.0);
+    let diff = &fell_back.diffs[0];
+    assert_eq!(diff.issues, vec![TypographyIssue::FontNotLoaded]);
+    let details = diff.details.as_ref().unwrap();
+    assert_eq!(details["requestedFont"], "Inter");
+    assert_eq!(details["fallbackFont"], "Arial");
+
+    let scores = MetricScores {
+        pixel: None,
+        layout: None,
+        typography: Some(fell_back),
+        color: None,
+        content: None,
+        semantic: None,
+    };
+    let issues = generate_top_issues(&scores, 5);
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.contains("font \"Inter\" not loaded, fell back to Arial")),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn typography_metric_flags_text_that_wraps_differently() {
+    let style = TypographyStyle {
+        font_family: Some("Inter".into()),
+        font_size: Some(16.0),
+        font_weight: Some("400".into()),
+        line_height: Some(24.0),
+        letter_spacing: None,
+    };
+    let mut ref_view = view_with_text("Get started today", style.clone());
+    let mut impl_view = view_with_text("Get started today", style);
+    ref_view.dom.as_mut().unwrap().nodes[0].bounding_box = bbox(0.0, 0.0, 300.0, 24.0);
+    impl_view.dom.as_mut().unwrap().nodes[0].bounding_box = bbox(0.0, 0.0, 120.0, 70.0);
+
+    let metric = TypographySimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!(metric.score < 1.0);
+    let diff = &metric.diffs[0];
+    assert_eq!(diff.issues, vec![TypographyIssue::LineWrapDiff]);
+    let details = diff.details.as_ref().unwrap();
+    assert_eq!(details["refLines"], 1);
+    assert_eq!(details["implLines"], 3);
+
+    impl_view.dom.as_mut().unwrap().nodes[0].bounding_box = bbox(0.0, 0.0, 310.0, 26.0);
+    let same = TypographySimilarity::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+    assert!(same.diffs.is_empty(), "{:?}", same.diffs);
+}
+
+#[test]
+fn typography_metric_flags_label_sitting_lower_in_its_button() {
+    let style = TypographyStyle {
+        font_family: Some("Inter".into()),
+        font_size: Some(16.0),
+        font_weight: Some("500".into()),
+        line_height: Some(24.0),
+        letter_spacing: None,
+    };
+    let in_button = |label_y: f32| {
+        let mut view = view_with_text("Sign up", style.clone());
+        let nodes = &mut view.dom.as_mut().unwrap().nodes;
+        let mut button = nodes[0].clone();
+        button.id = "button".into();
+        button.tag = "button".into();
+        button.text = None;
+        button.bounding_box = bbox(0.0, 0.0, 120.0, 40.0);
+        nodes[0].parent = Some("button".into());
+        nodes[0].bounding_box = bbox(16.0, label_y, 88.0, 24.0);
+        nodes.insert(0, button);
+        view
+    };
+
+    let metric = TypographySimilarity::default()
+        .compute_metric(&in_button(8.0), &in_button(10.0))
+        .unwrap();
+    assert!(metric.score < 1.0);
+    assert_eq!(metric.diffs[0].issues, vec![TypographyIssue::BaselineShift]);
+    let details = metric.diffs[0].details.as_ref().unwrap();
+    assert_eq!(details["baselineShift"], 2.0);
+
+    let scores = MetricScores {
+        pixel: None,
+        layout: None,
+        typography: Some(metric),
+        color: None,
+        content: None,
+        semantic: None,
+    };
+    let issues = generate_top_issues(&scores, 5);
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.contains("sits 2px lower in its container")),
+        "{issues:?}"
+    );
+
+    let nudged = TypographySimilarity::default()
+        .compute_metric(&in_button(8.0), &in_button(8.5))
+        .unwrap();
+    assert!(nudged.diffs.is_empty(), "{:?}", nudged.diffs);
+}
+
+#[test]
+fn typography_metric_flags_changed_vertical_rhythm() {
+    let heading = TypographyStyle {
+        font_family: Some("Inter".into()),
+        font_size: Some(24.0),
+        font_weight: Some("700".into()),
+        line_height: Some(32.0),
+        letter_spacing: None,
+    };
+    let body = TypographyStyle {
+        font_family: Some("Inter".into()),
+        font_size: Some(16.0),
+        font_weight: Some("400".into()),
+        line_height: Some(24.0),
+        letter_spacing: None,
+    };
+    let stacked = |body_y: f32| {
+        let mut view = view_with_text("Pricing", heading.clone());
+        let mut text = view_with_text("Pick a plan", body.clone())
+            .dom
+            .unwrap()
+            .nodes[0]
+            .clone();
+        text.id = "t2".into();
+        text.bounding_box = bbox(0.0, body_y, 300.0, 24.0);
+        let nodes = &mut view.dom.as_mut().unwrap().nodes;
+        nodes[0].bounding_box = bbox(0.0, 0.0, 300.0, 32.0);
+        nodes.push(text);
+        view
+    };
+
+    let metric = TypographySimilarity::default()
+        .compute_metric(&stacked(40.0), &stacked(46.0))
+        .unwrap();
+    assert_eq!(metric.diffs.len(), 1, "{:?}", metric.diffs);
+    let diff = &metric.diffs[0];
+    assert_eq!(diff.element_id_ref.as_deref(), Some("t2"));
+    assert_eq!(diff.issues, vec![TypographyIssue::VerticalRhythmDiff]);
+    let details = diff.details.as_ref().unwrap();
+    assert_eq!(details["refBaselineGap"], 33.2);
+    assert_eq!(details["implBaselineGap"], 39.2);
+}
+
 #[test]
 fn typography_metric_line_height_mismatch_penalized() {
     let ref_view = view_with_text(
@@ -761,6 +1678,30 @@ fn typography_metric_small_size_difference_within_tolerance_scores_high() {
     assert!(score > 0.8, "score should remain high for small size diff");
 }
 
+#[test]
+fn typography_report_lists_matched_elements_with_penalty() {
+    let style = |family: &str, weight: &str| TypographyStyle {
+        font_family: Some(family.into()),
+        font_size: Some(16.0),
+        font_weight: Some(weight.into()),
+        line_height: Some(24.0),
+        letter_spacing: None,
+    };
+    let ref_view = view_with_text("Hello", style("Inter", "400"));
+    let impl_view = view_with_text("Hello", style("Inter", "700"));
+
+    let rows = TypographySimilarity::default()
+        .report(&ref_view, &impl_view)
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row.text, "Hello");
+    assert_eq!(row.ref_font_weight.as_deref(), Some("400"));
+    assert_eq!(row.impl_font_weight.as_deref(), Some("700"));
+    assert!(row.penalty > 0.0);
+    assert_eq!(row.issues, vec![TypographyIssue::FontWeightDiff]);
+}
+
 #[test]
 fn color_metric_identical_palettes_score_one() {
     let ref_img = solid_split_image(Rgba([10, 20, 30, 255]), Rgba([40, 50, 60, 255]));
@@ -803,19 +1744,430 @@ fn color_metric_detects_palette_shift() {
 }
 
 #[test]
-fn content_metric_missing_and_extra_text_affect_score() {
-    let ref_view = view_with_dom(vec![("p:Hello", bbox(0.0, 0.0, 0.5, 0.5))]);
-    let impl_view = view_with_dom(vec![
-        ("p:Hello", bbox(0.0, 0.0, 0.5, 0.5)),
-        ("h1:Extra", bbox(0.1, 0.1, 0.3, 0.3)),
-    ]);
-    let metric = ContentSimilarity::default();
-    let result = metric.compute(&ref_view, &impl_view).unwrap();
-    let content = match result {
-        MetricResult::Content(c) => c,
-        _ => unreachable!(),
-    };
-    assert!(content.score < 1.0);
+fn color_metric_compares_gradient_fills_of_matched_elements() {
+    let img = solid_split_image(Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255]));
+    let reference = view_with_gradient(
+        &img,
+        "linear-gradient(90deg, rgb(255, 0, 0) 0%, rgb(0, 0, 255) 100%)",
+    );
+    let same = view_with_gradient(&img, "linear-gradient(to right, #f00, #00f)");
+    let changed = view_with_gradient(&img, "linear-gradient(180deg, #f00, #0f0)");
+    let metric = ColorPaletteMetric::default();
+
+    let unchanged = metric.compute_metric(&reference, &same).unwrap();
+    assert!(
+        unchanged
+            .diffs
+            .iter()
+            .all(|d| d.kind != ColorDiffKind::GradientShift),
+        "{:?}",
+        unchanged.diffs
+    );
+
+    let shifted = metric.compute_metric(&reference, &changed).unwrap();
+    let diff = shifted
+        .diffs
+        .iter()
+        .find(|d| d.kind == ColorDiffKind::GradientShift)
+        .expect("gradient diff");
+    assert_eq!(diff.element_id.as_deref(), Some("hero"));
+    assert_eq!(diff.angle_delta, Some(90.0));
+    assert!(diff.delta_e.unwrap() > 20.0);
+    assert_eq!(
+        diff.impl_color,
+        "linear-gradient(180deg, #FF0000 0%, #00FF00 100%)"
+    );
+    assert!(shifted.score < unchanged.score);
+}
+
+#[test]
+fn element_color_drift_measures_matched_backgrounds_and_text() {
+    let reference = view_with_colors(&[
+        ("card", bbox(0.0, 0.0, 50.0, 50.0), Some("#ffffff"), None),
+        ("title", bbox(5.0, 5.0, 40.0, 10.0), None, Some("#111111")),
+        ("gone", bbox(60.0, 60.0, 30.0, 30.0), Some("#ff0000"), None),
+    ]);
+    let implementation = view_with_colors(&[
+        (
+            "card-impl",
+            bbox(1.0, 0.0, 50.0, 50.0),
+            Some("rgb(255, 255, 255)"),
+            None,
+        ),
+        (
+            "title-impl",
+            bbox(5.0, 6.0, 40.0, 10.0),
+            None,
+            Some("#3355ff"),
+        ),
+        ("new", bbox(60.0, 0.0, 30.0, 30.0), Some("#00ff00"), None),
+    ]);
+
+    let drifts = element_color_drift(&reference, &implementation);
+    let ids: Vec<(&str, &str)> = drifts
+        .iter()
+        .map(|d| (d.element_id.as_str(), d.ref_element_id.as_str()))
+        .collect();
+    assert_eq!(ids, [("card-impl", "card"), ("title-impl", "title")]);
+    assert_eq!(drifts[0].background_delta_e, Some(0.0));
+    assert_eq!(drifts[0].foreground_delta_e, None);
+    assert!(drifts[1].foreground_delta_e.unwrap() > 20.0);
+    assert_eq!(drifts[1].delta_e(), drifts[1].foreground_delta_e.unwrap());
+}
+
+#[test]
+fn palette_sprawl_merges_lookalikes_and_lists_near_duplicates() {
+    let view = view_with_colors(&[
+        ("a", bbox(0.0, 0.0, 10.0, 10.0), None, Some("#333333")),
+        ("b", bbox(0.0, 10.0, 10.0, 10.0), None, Some("#333333")),
+        ("c", bbox(0.0, 20.0, 10.0, 10.0), None, Some("#343434")),
+        ("d", bbox(0.0, 30.0, 10.0, 10.0), Some("#3d3d3d"), None),
+        ("e", bbox(0.0, 40.0, 10.0, 10.0), Some("#ff0000"), None),
+        (
+            "f",
+            bbox(0.0, 50.0, 10.0, 10.0),
+            Some("rgba(0, 0, 0, 0.5)"),
+            None,
+        ),
+    ]);
+    let sprawl = PaletteSprawl {
+        budget: 2,
+        ..PaletteSprawl::default()
+    };
+
+    let report = sprawl.analyze(&view).expect("palette report");
+    let colors: Vec<(&str, usize)> = report
+        .colors
+        .iter()
+        .map(|color| (color.hex.as_str(), color.uses))
+        .collect();
+    assert_eq!(colors, [("#333333", 3), ("#3D3D3D", 1), ("#FF0000", 1)]);
+    assert!(report.over_budget());
+    assert_eq!(report.near_duplicates.len(), 1);
+    let pair = &report.near_duplicates[0];
+    assert_eq!(
+        (pair.first.as_str(), pair.second.as_str()),
+        ("#333333", "#3D3D3D")
+    );
+    assert!(pair.delta_e >= 1.0 && pair.delta_e < 5.0);
+
+    assert!(sprawl.analyze(&dummy_view()).is_none());
+}
+
+#[test]
+fn type_specimen_counts_styles_per_side() {
+    let style = |family: &str, size: f32, weight: &str| TypographyStyle {
+        font_family: Some(family.to_string()),
+        font_size: Some(size),
+        font_weight: Some(weight.to_string()),
+        ..TypographyStyle::default()
+    };
+    let texts = |styles: &[(&str, TypographyStyle)]| {
+        let mut view = view_with_text(styles[0].0, styles[0].1.clone());
+        let dom = view.dom.as_mut().unwrap();
+        for (idx, (text, style)) in styles.iter().enumerate().skip(1) {
+            let mut node = dom.nodes[0].clone();
+            node.id = format!("t{}", idx + 1);
+            node.text = Some(text.to_string());
+            let computed = node.computed_style.as_mut().unwrap();
+            computed.font_family = style.font_family.clone();
+            computed.font_size = style.font_size;
+            computed.font_weight = style.font_weight.clone();
+            dom.nodes.push(node);
+        }
+        view
+    };
+    let reference = texts(&[
+        ("Welcome", style("Inter, sans-serif", 32.0, "bold")),
+        ("Body", style("Inter", 16.0, "400")),
+        ("More body", style("'Inter'", 16.0, "normal")),
+    ]);
+    let implementation = texts(&[
+        ("Welcome", style("Inter", 32.2, "700")),
+        ("Body", style("Inter", 16.0, "400")),
+        ("Rogue", style("Arial", 15.0, "500")),
+    ]);
+
+    let rows = TypographySimilarity::default().specimen(&reference, &implementation);
+    let summary: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            format!(
+                "{} {}px {} {}/{}",
+                r.font_family.as_deref().unwrap_or("-"),
+                r.font_size.unwrap_or_default(),
+                r.font_weight.as_deref().unwrap_or("-"),
+                r.ref_count,
+                r.impl_count
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            "Inter 32px 700 1/1",
+            "Inter 16px 400 2/1",
+            "Arial 15px 500 0/1"
+        ]
+    );
+    assert_eq!(rows[2].sample, "Rogue");
+}
+
+#[test]
+fn delta_e_2000_matches_reference_data() {
+    use palette::Lab;
+    // Pairs from Sharma, Wu & Dalal's CIEDE2000 test data.
+    let cases = [
+        ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+        ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+        ((50.0, 2.5, 0.0), (50.0, 3.2592, 0.335), 1.0),
+    ];
+    for (a, b, expected) in cases {
+        let delta = color::delta_e_2000(Lab::new(a.0, a.1, a.2), Lab::new(b.0, b.1, b.2));
+        assert!((delta - expected).abs() < 1e-3, "{delta} vs {expected}");
+    }
+}
+
+fn page_with_accent(accent: Rgba<u8>) -> RgbaImage {
+    let mut img = RgbaImage::from_pixel(80, 60, Rgba([255, 255, 255, 255]));
+    for y in 8..20 {
+        for x in 8..72 {
+            img.put_pixel(x, y, Rgba([51, 51, 51, 255]));
+        }
+    }
+    for y in 36..48 {
+        for x in 24..56 {
+            img.put_pixel(x, y, accent);
+        }
+    }
+    img
+}
+
+/// `(id, box, text, applied style IDs)` of a styled Figma node.
+type StyledNode<'a> = (
+    &'a str,
+    crate::types::BoundingBox,
+    Option<&'a str>,
+    &'a [(&'a str, &'a str)],
+);
+
+/// A Figma snapshot of `nodes` with the resolved `styles` of its file.
+fn styled_figma_tree(
+    nodes: &[StyledNode],
+    styles: Vec<crate::types::FigmaStyle>,
+) -> crate::types::FigmaSnapshot {
+    use crate::types::{FigmaNode, FigmaSnapshot};
+    FigmaSnapshot {
+        schema_version: FigmaSnapshot::SCHEMA_VERSION,
+        file_key: "file".to_string(),
+        node_id: "1:1".to_string(),
+        name: None,
+        version: None,
+        frame_size: None,
+        export_scale: None,
+        nodes: nodes
+            .iter()
+            .map(|(id, bounding_box, text, applied)| FigmaNode {
+                id: id.to_string(),
+                name: None,
+                node_type: if text.is_some() { "TEXT" } else { "FRAME" }.to_string(),
+                bounding_box: *bounding_box,
+                text: text.map(str::to_string),
+                typography: text.map(|_| TypographyStyle {
+                    font_family: Some("Inter".into()),
+                    font_size: Some(32.0),
+                    font_weight: Some("700".into()),
+                    line_height: None,
+                    letter_spacing: None,
+                }),
+                line_count: None,
+                fills: vec![],
+                children: vec![],
+                auto_layout: None,
+                constraints: None,
+                responsive_box: None,
+                styles: applied
+                    .iter()
+                    .map(|(property, id)| (property.to_string(), id.to_string()))
+                    .collect(),
+            })
+            .collect(),
+        styles,
+    }
+}
+
+fn figma_style(node_id: &str, name: &str, color: Option<&str>) -> crate::types::FigmaStyle {
+    use crate::types::{FigmaStyle, FigmaStyleKind};
+    FigmaStyle {
+        node_id: node_id.to_string(),
+        key: format!("key-{node_id}"),
+        name: name.to_string(),
+        kind: if color.is_some() {
+            FigmaStyleKind::Fill
+        } else {
+            FigmaStyleKind::Text
+        },
+        color: color.map(str::to_string),
+        typography: None,
+    }
+}
+
+#[test]
+fn color_metric_takes_reference_palette_from_figma_fill_styles() {
+    // The reference screenshot is ignored once its fill styles are known.
+    let ref_img = solid_image([200, 0, 200, 255]);
+    let impl_img = solid_split_image(Rgba([255, 255, 255, 255]), Rgba([0, 0, 0, 255]));
+    let mut ref_view = view_from_image(&ref_img);
+    ref_view.figma_tree = Some(styled_figma_tree(
+        &[
+            (
+                "page",
+                bbox(0.0, 0.0, 100.0, 100.0),
+                None,
+                &[("fill", "1:10")],
+            ),
+            (
+                "cta",
+                bbox(10.0, 10.0, 40.0, 20.0),
+                None,
+                &[("fill", "S:key-1:11,4:2")],
+            ),
+            (
+                "card",
+                bbox(10.0, 50.0, 20.0, 20.0),
+                None,
+                &[("fill", "9:99")],
+            ),
+        ],
+        vec![
+            figma_style("1:10", "Neutral/0", Some("#ffffff")),
+            figma_style("1:11", "Primary/600", Some("#1a73e8")),
+        ],
+    ));
+    let impl_view = view_from_image(&impl_img);
+
+    let color = ColorPaletteMetric::default()
+        .compute_metric(&ref_view, &impl_view)
+        .unwrap();
+
+    let styles: Vec<Option<&str>> = color.diffs.iter().map(|d| d.style.as_deref()).collect();
+    assert_eq!(styles, [Some("Neutral/0"), Some("Primary/600")]);
+    assert!(color.diffs[0].delta_e.unwrap() < 1.0);
+    assert!(color.diffs[1].delta_e.unwrap() > 20.0);
+    assert!(color.score < 0.9);
+
+    let scores = MetricScores {
+        pixel: None,
+        layout: None,
+        typography: None,
+        color: Some(color),
+        content: None,
+        semantic: None,
+    };
+    let issues = generate_top_issues(&scores, 5);
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.contains("expected Primary/600 (#")),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn typography_diffs_name_the_reference_text_style() {
+    let mut ref_view = dummy_view();
+    ref_view.figma_tree = Some(styled_figma_tree(
+        &[(
+            "title",
+            bbox(0.0, 0.0, 100.0, 40.0),
+            Some("Welcome"),
+            &[("text", "2:1")],
+        )],
+        vec![figma_style("2:1", "Heading/H1", None)],
+    ));
+    let impl_view = view_with_text(
+        "Welcome",
+        TypographyStyle {
+            font_family: Some("Inter".into()),
+            font_size: Some(24.0),
+            font_weight: Some("700".into()),
+            line_height: None,
+            letter_spacing: None,
+        },
+    );
+
+    let typography = match TypographySimilarity::default()
+        .compute(&ref_view, &impl_view)
+        .unwrap()
+    {
+        MetricResult::Typography(t) => t,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(typography.diffs.len(), 1);
+    assert_eq!(typography.diffs[0].style.as_deref(), Some("Heading/H1"));
+    let scores = MetricScores {
+        pixel: None,
+        layout: None,
+        typography: Some(typography),
+        color: None,
+        content: None,
+        semantic: None,
+    };
+    assert_eq!(
+        generate_top_issues(&scores, 5),
+        ["title has a different font size than text style Heading/H1."]
+    );
+}
+
+#[test]
+fn color_metric_palette_is_deterministic_and_finds_accent_shift_past_background() {
+    let ref_file = write_image(&page_with_accent(Rgba([32, 96, 224, 255])));
+    let impl_file = write_image(&page_with_accent(Rgba([224, 112, 32, 255])));
+    let ref_view = view_from_file(ref_file.path(), 80, 60);
+    let impl_view = view_from_file(impl_file.path(), 80, 60);
+    let metric = ColorPaletteMetric {
+        exclude_background: true,
+        ..ColorPaletteMetric::default()
+    };
+
+    let first = metric.compute_metric(&ref_view, &impl_view).unwrap();
+    let second = metric.compute_metric(&ref_view, &impl_view).unwrap();
+    assert_eq!(format!("{:?}", first.diffs), format!("{:?}", second.diffs));
+    assert!((first.score - second.score).abs() < f32::EPSILON);
+
+    assert!(
+        first.diffs.iter().all(|d| d.ref_color != "#FFFFFF"),
+        "white background should be excluded: {:?}",
+        first.diffs
+    );
+    assert!(
+        first.diffs.iter().any(|d| d.ref_color == "#2060E0"
+            && d.delta_e.unwrap_or(0.0) > 20.0
+            && matches!(
+                d.kind,
+                ColorDiffKind::PrimaryColorShift | ColorDiffKind::AccentColorShift
+            )),
+        "accent shift should be a primary/accent diff: {:?}",
+        first.diffs
+    );
+    assert!(first.score < 0.9);
+}
+
+#[test]
+fn content_metric_missing_and_extra_text_affect_score() {
+    let ref_view = view_with_dom(vec![("p:Hello", bbox(0.0, 0.0, 0.5, 0.5))]);
+    let impl_view = view_with_dom(vec![
+        ("p:Hello", bbox(0.0, 0.0, 0.5, 0.5)),
+        ("h1:Extra", bbox(0.1, 0.1, 0.3, 0.3)),
+    ]);
+    let metric = ContentSimilarity::default();
+    let result = metric.compute(&ref_view, &impl_view).unwrap();
+    let content = match result {
+        MetricResult::Content(c) => c,
+        _ => unreachable!(),
+    };
+    assert!(content.score < 1.0);
     assert!(
         content.extra_text.iter().any(|t| t.contains("extra")) || !content.extra_text.is_empty()
     );
@@ -889,6 +2241,219 @@ fn content_metric_completely_mismatched_text_penalizes_and_reports() {
     assert_eq!(content.extra_text.len(), 2);
 }
 
+fn content_of(
+    metric: &ContentSimilarity,
+    ref_view: &NormalizedView,
+    impl_view: &NormalizedView,
+) -> ContentMetric {
+    match metric.compute(ref_view, impl_view).unwrap() {
+        MetricResult::Content(c) => c,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn content_metric_levenshtein_and_diacritic_folding_tolerate_small_differences() {
+    let ref_view = view_with_dom(vec![("p:Café au lait", bbox(0.0, 0.0, 0.5, 0.1))]);
+    let impl_view = view_with_dom(vec![("p:Cafe au laît", bbox(0.0, 0.0, 0.5, 0.1))]);
+
+    let token = content_of(&ContentSimilarity::default(), &ref_view, &impl_view);
+    assert_eq!(
+        token.missing_text.len(),
+        1,
+        "token overlap misses accented words"
+    );
+
+    let folded = ContentSimilarity {
+        fold_diacritics: true,
+        ..ContentSimilarity::default()
+    };
+    assert!(content_of(&folded, &ref_view, &impl_view)
+        .missing_text
+        .is_empty());
+
+    let typo_ref = view_with_dom(vec![("p:Continue to checkout", bbox(0.0, 0.0, 0.5, 0.1))]);
+    let typo_impl = view_with_dom(vec![("p:Continue to chekout", bbox(0.0, 0.0, 0.5, 0.1))]);
+    let fuzzy = ContentSimilarity {
+        mode: ContentMatchMode::Levenshtein,
+        ..ContentSimilarity::default()
+    };
+    assert!(content_of(&fuzzy, &typo_ref, &typo_impl)
+        .missing_text
+        .is_empty());
+}
+
+#[test]
+fn content_metric_synonyms_map_equivalent_phrases() {
+    let ref_view = view_with_dom(vec![(
+        "button:Sign in to continue",
+        bbox(0.0, 0.0, 0.2, 0.1),
+    )]);
+    let impl_view = view_with_dom(vec![(
+        "button:Anmelden to continue",
+        bbox(0.0, 0.0, 0.2, 0.1),
+    )]);
+
+    let file = NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), r#"{"Sign in": ["Log in", "Anmelden"]}"#).unwrap();
+    let metric = ContentSimilarity {
+        synonyms: Some(std::sync::Arc::new(
+            SynonymTable::from_json_file(file.path()).unwrap(),
+        )),
+        ..ContentSimilarity::default()
+    };
+
+    let content = content_of(&metric, &ref_view, &impl_view);
+    assert!(content.missing_text.is_empty());
+    assert!(content.extra_text.is_empty());
+    assert!((content.score - 1.0).abs() < f32::EPSILON);
+}
+
+#[test]
+fn synonym_map_files_load_in_a_stable_order() {
+    let file = NamedTempFile::new().unwrap();
+    let map: serde_json::Map<String, serde_json::Value> = (b'a'..=b'p')
+        .map(|c| {
+            let key = format!("{}", c as char);
+            (
+                key.clone(),
+                serde_json::json!([format!("{key}{key}"), "shared"]),
+            )
+        })
+        .collect();
+    std::fs::write(file.path(), serde_json::to_string(&map).unwrap()).unwrap();
+
+    let first = format!("{:?}", SynonymTable::from_json_file(file.path()).unwrap());
+    for _ in 0..8 {
+        let again = format!("{:?}", SynonymTable::from_json_file(file.path()).unwrap());
+        assert_eq!(first, again);
+    }
+    assert!(first.find("\"a\"").unwrap() < first.find("\"p\"").unwrap());
+}
+
+#[test]
+fn format_normalization_canonicalizes_numbers_currency_and_dates() {
+    use super::content_formats::normalize_formats;
+    let canonical = |text| normalize_formats(text, FormatNormalization::Canonical);
+
+    assert_eq!(canonical("Only $1,299.00 today"), "Only usd 1299 today");
+    assert_eq!(canonical("Only 1299 USD today"), "Only usd 1299 today");
+    assert_eq!(canonical("1.299,50 €"), "eur 1299.5");
+    assert_eq!(canonical("Ships March 5, 2024"), "Ships 2024-03-05");
+    assert_eq!(canonical("Ships 05.03.2024"), "Ships 2024-03-05");
+    assert_eq!(canonical("Ships 03/05/2024"), "Ships 2024-03-05");
+    assert_eq!(
+        canonical("Version 1.2.3 in August"),
+        "Version 1.2.3 in August"
+    );
+
+    assert_eq!(
+        normalize_formats("Total: $42.10 on 2024-01-31", FormatNormalization::Mask),
+        "Total: usd num on date"
+    );
+    assert_eq!(
+        normalize_formats("$1,299.00", FormatNormalization::Off),
+        "$1,299.00"
+    );
+}
+
+#[test]
+fn format_normalization_leaves_overflowing_date_parts_alone() {
+    use super::content_formats::normalize_formats;
+
+    for formats in [FormatNormalization::Canonical, FormatNormalization::Mask] {
+        let normalized = normalize_formats("Ref 12345678901/01/2024", formats);
+        assert!(!normalized.contains("date"), "{normalized}");
+        assert!(normalized.starts_with("Ref "), "{normalized}");
+    }
+}
+
+#[test]
+fn content_metric_format_normalization_matches_localized_prices() {
+    let ref_view = view_with_dom(vec![("p:Pro plan $1,299.00", bbox(0.0, 0.0, 0.5, 0.1))]);
+    let impl_view = view_with_dom(vec![("p:Pro plan 1299 USD", bbox(0.0, 0.0, 0.5, 0.1))]);
+
+    let plain = content_of(&ContentSimilarity::default(), &ref_view, &impl_view);
+    assert!(!plain.missing_text.is_empty());
+
+    let canonical = ContentSimilarity {
+        formats: FormatNormalization::Canonical,
+        mode: ContentMatchMode::Levenshtein,
+        ..ContentSimilarity::default()
+    };
+    let content = content_of(&canonical, &ref_view, &impl_view);
+    assert!((content.score - 1.0).abs() < f32::EPSILON);
+
+    let live_impl = view_with_dom(vec![("p:Pro plan $1,349.00", bbox(0.0, 0.0, 0.5, 0.1))]);
+    let masked = ContentSimilarity {
+        formats: FormatNormalization::Mask,
+        mode: ContentMatchMode::Levenshtein,
+        ..ContentSimilarity::default()
+    };
+    assert!(content_of(&masked, &ref_view, &live_impl)
+        .missing_text
+        .is_empty());
+}
+
+#[test]
+fn placeholder_detection_classifies_filler_and_templates() {
+    use super::content_placeholders::{classify, Placeholder};
+
+    assert_eq!(
+        classify("Lorem ipsum dolor sit amet, consectetur"),
+        Placeholder::Full
+    );
+    assert_eq!(classify("{{user.name}}"), Placeholder::Full);
+    assert_eq!(classify("Your text here"), Placeholder::Full);
+    assert_eq!(
+        classify("Welcome back, {{firstName}}!"),
+        Placeholder::Template("Welcome back, !".to_string())
+    );
+    assert_eq!(
+        classify("%1$s items in [Cart name]"),
+        Placeholder::Template("items in".to_string())
+    );
+    assert_eq!(classify("Save 50% today"), Placeholder::None);
+    assert_eq!(classify("Dolor of the month"), Placeholder::None);
+}
+
+#[test]
+fn content_metric_treat_placeholders_skips_filler_copy() {
+    let ref_view = view_with_dom(vec![
+        ("h1:Welcome back, {{firstName}}", bbox(0.0, 0.0, 0.5, 0.1)),
+        (
+            "p:Lorem ipsum dolor sit amet, consectetur adipiscing elit",
+            bbox(0.0, 0.2, 0.8, 0.2),
+        ),
+        ("button:Continue", bbox(0.0, 0.5, 0.2, 0.1)),
+    ]);
+    let impl_view = view_with_dom(vec![
+        ("h1:Welcome back, Ada Lovelace", bbox(0.0, 0.0, 0.5, 0.1)),
+        (
+            "p:Your order ships tomorrow morning",
+            bbox(0.05, 0.22, 0.6, 0.1),
+        ),
+        ("button:Continue", bbox(0.0, 0.5, 0.2, 0.1)),
+    ]);
+
+    let strict = content_of(&ContentSimilarity::default(), &ref_view, &impl_view);
+    assert_eq!(strict.missing_text.len(), 2);
+    assert!(strict.placeholder_text.is_empty());
+
+    let relaxed = ContentSimilarity {
+        treat_placeholders: true,
+        ..ContentSimilarity::default()
+    };
+    let content = content_of(&relaxed, &ref_view, &impl_view);
+    assert!(content.missing_text.is_empty());
+    assert!(content.extra_text.is_empty(), "{:?}", content.extra_text);
+    assert_eq!(
+        content.placeholder_text,
+        vec!["Lorem ipsum dolor sit amet, consectetur adipiscing elit".to_string()]
+    );
+    assert!((content.score - 1.0).abs() < f32::EPSILON);
+}
+
 // Helpers for tests
 fn dummy_view() -> NormalizedView {
     NormalizedView {
@@ -899,6 +2464,7 @@ fn dummy_view() -> NormalizedView {
         dom: None,
         figma_tree: None,
         ocr_blocks: None,
+        content_area: None,
     }
 }
 
@@ -911,6 +2477,7 @@ fn view_from_file(path: &std::path::Path, width: u32, height: u32) -> Normalized
         dom: None,
         figma_tree: None,
         ocr_blocks: None,
+        content_area: None,
     }
 }
 
@@ -1007,6 +2574,8 @@ fn view_with_dom(nodes: Vec<(&str, crate::types::BoundingBox)>) -> NormalizedVie
                 attributes: std::collections::HashMap::new(),
                 text,
                 bounding_box: bbox,
+                text_baseline: None,
+                interaction_states: None,
                 computed_style: None,
             }
         })
@@ -1018,12 +2587,90 @@ fn view_with_dom(nodes: Vec<(&str, crate::types::BoundingBox)>) -> NormalizedVie
         width: 100,
         height: 100,
         dom: Some(DomSnapshot {
+            schema_version: DomSnapshot::SCHEMA_VERSION,
             url: None,
             title: None,
+            browser: None,
+            diagnostics: None,
+            custom_properties: None,
             nodes: dom_nodes,
         }),
         figma_tree: None,
         ocr_blocks: None,
+        content_area: None,
+    }
+}
+
+fn view_with_gradient(file: &NamedTempFile, background_image: &str) -> NormalizedView {
+    use crate::types::{DomNode, DomSnapshot};
+    NormalizedView {
+        dom: Some(DomSnapshot {
+            schema_version: DomSnapshot::SCHEMA_VERSION,
+            url: None,
+            title: None,
+            browser: None,
+            diagnostics: None,
+            custom_properties: None,
+            nodes: vec![DomNode {
+                id: "hero".into(),
+                tag: "div".into(),
+                children: vec![],
+                parent: None,
+                attributes: std::collections::HashMap::new(),
+                text: None,
+                bounding_box: bbox(0.0, 0.0, 4.0, 2.0),
+                text_baseline: None,
+                interaction_states: None,
+                computed_style: Some(ComputedStyle {
+                    background_image: Some(background_image.to_string()),
+                    ..ComputedStyle::default()
+                }),
+            }],
+        }),
+        ..view_from_image(file)
+    }
+}
+
+/// DOM nodes `(id, box, background-color, text color)`; nodes with a text
+/// color get some text.
+type ColoredNode<'a> = (
+    &'a str,
+    crate::types::BoundingBox,
+    Option<&'a str>,
+    Option<&'a str>,
+);
+
+fn view_with_colors(nodes: &[ColoredNode]) -> NormalizedView {
+    use crate::types::{DomNode, DomSnapshot};
+    NormalizedView {
+        dom: Some(DomSnapshot {
+            schema_version: DomSnapshot::SCHEMA_VERSION,
+            url: None,
+            title: None,
+            browser: None,
+            diagnostics: None,
+            custom_properties: None,
+            nodes: nodes
+                .iter()
+                .map(|(id, bounding_box, background, color)| DomNode {
+                    id: id.to_string(),
+                    tag: "div".into(),
+                    children: vec![],
+                    parent: None,
+                    attributes: std::collections::HashMap::new(),
+                    text: color.map(|_| "Hello".to_string()),
+                    bounding_box: *bounding_box,
+                    text_baseline: None,
+                    interaction_states: None,
+                    computed_style: Some(ComputedStyle {
+                        background_color: background.map(str::to_string),
+                        color: color.map(str::to_string),
+                        ..ComputedStyle::default()
+                    }),
+                })
+                .collect(),
+        }),
+        ..dummy_view()
     }
 }
 
@@ -1035,8 +2682,12 @@ fn view_with_text(text: &str, style: TypographyStyle) -> NormalizedView {
         width: 100,
         height: 100,
         dom: Some(DomSnapshot {
+            schema_version: DomSnapshot::SCHEMA_VERSION,
             url: None,
             title: None,
+            browser: None,
+            diagnostics: None,
+            custom_properties: None,
             nodes: vec![DomNode {
                 id: "t1".into(),
                 tag: "p".into(),
@@ -1045,22 +2696,28 @@ fn view_with_text(text: &str, style: TypographyStyle) -> NormalizedView {
                 attributes: std::collections::HashMap::new(),
                 text: Some(text.to_string()),
                 bounding_box: bbox(0.0, 0.0, 0.5, 0.1),
+                text_baseline: None,
+                interaction_states: None,
                 computed_style: Some(ComputedStyle {
                     font_family: style.font_family.clone(),
+                    rendered_font_family: None,
                     font_size: style.font_size,
                     font_weight: style.font_weight.clone(),
                     line_height: style.line_height,
                     letter_spacing: None,
                     color: None,
                     background_color: None,
+                    background_image: None,
                     display: None,
                     visibility: None,
                     opacity: None,
+                    flex: None,
                 }),
             }],
         }),
         figma_tree: None,
         ocr_blocks: None,
+        content_area: None,
     }
 }
 
@@ -1073,6 +2730,7 @@ fn view_from_image(file: &NamedTempFile) -> NormalizedView {
         dom: None,
         figma_tree: None,
         ocr_blocks: None,
+        content_area: None,
     }
 }
 
@@ -1105,6 +2763,7 @@ impl StubMetric {
                 score: self.score,
                 diff_regions: vec![],
                 semantic_diffs: None,
+                offset: None,
             }),
             MetricKind::Layout => MetricResult::Layout(LayoutMetric {
                 score: self.score,
@@ -1119,9 +2778,17 @@ impl StubMetric {
                 diffs: vec![],
             }),
             MetricKind::Content => MetricResult::Content(ContentMetric {
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
                 score: self.score,
                 missing_text: vec![],
                 extra_text: vec![],
+                placeholder_text: vec![],
+                text_labels: Default::default(),
+            }),
+            MetricKind::Semantic => MetricResult::Semantic(SemanticMetric {
+                score: self.score,
+                cosine_similarity: self.score,
             }),
         }
     }
@@ -1210,3 +2877,520 @@ fn cluster_diff_regions_returns_empty_for_zero_dimensions() {
     assert!(cluster_diff_regions(&diff_map, 8, 8, 0, &thresholds).is_empty());
     assert!(cluster_diff_regions(&[], 8, 8, 4, &thresholds).is_empty());
 }
+
+#[test]
+fn cluster_diff_regions_with_density_skips_sparse_blocks() {
+    // One block with a single very bright pixel (avg above minor), another fully changed.
+    let mut diff_map = vec![0.0; 64];
+    diff_map[0] = 1.0;
+    for row in 4..8 {
+        for col in 4..8 {
+            diff_map[row * 8 + col] = 0.2;
+        }
+    }
+    let thresholds = PixelDiffThresholds::default();
+    assert_eq!(
+        cluster_diff_regions(&diff_map, 8, 8, 4, &thresholds).len(),
+        2
+    );
+
+    let regions = cluster_diff_regions_with_density(&diff_map, 8, 8, 4, &thresholds, 0.5);
+    assert_eq!(regions.len(), 1, "sparse block should be dropped");
+    assert!((regions[0].x - 0.5).abs() < f32::EPSILON);
+}
+
+#[test]
+fn pixel_metric_noise_suppression_drops_small_regions() {
+    let ref_img = RgbaImage::from_pixel(128, 128, Rgba([240, 240, 240, 255]));
+    let mut impl_img = ref_img.clone();
+    // Small 8x8 blemish in one corner, large 64x64 change elsewhere.
+    for y in 0..8 {
+        for x in 0..8 {
+            impl_img.put_pixel(x, y, Rgba([20, 20, 20, 255]));
+        }
+    }
+    for y in 64..128 {
+        for x in 64..128 {
+            impl_img.put_pixel(x, y, Rgba([20, 20, 20, 255]));
+        }
+    }
+
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 128, 128);
+    let impl_view = view_from_file(impl_file.path(), 128, 128);
+
+    let regions_for = |metric: PixelSimilarity| match metric.compute(&ref_view, &impl_view).unwrap()
+    {
+        MetricResult::Pixel(p) => p.diff_regions,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(regions_for(PixelSimilarity::default()).len(), 2);
+
+    let by_pixels = regions_for(PixelSimilarity {
+        noise: NoiseSuppression {
+            min_region_pixels: 2048,
+            ..NoiseSuppression::default()
+        },
+        ..PixelSimilarity::default()
+    });
+    assert_eq!(by_pixels.len(), 1);
+
+    let by_area = regions_for(PixelSimilarity {
+        noise: NoiseSuppression {
+            min_region_area: 0.1,
+            ..NoiseSuppression::default()
+        },
+        ..PixelSimilarity::default()
+    });
+    assert_eq!(by_area.len(), 1);
+    assert!(by_area[0].width >= 0.5);
+}
+
+#[test]
+fn pixel_metric_refines_blocks_to_hug_the_change() {
+    let ref_img = RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+    let mut impl_img = ref_img.clone();
+    for y in 10..18 {
+        for x in 10..18 {
+            impl_img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+    }
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 64, 64);
+    let impl_view = view_from_file(impl_file.path(), 64, 64);
+    let regions_for = |metric: PixelSimilarity| match metric.compute(&ref_view, &impl_view).unwrap()
+    {
+        MetricResult::Pixel(p) => p.diff_regions,
+        _ => unreachable!(),
+    };
+
+    let whole = regions_for(PixelSimilarity::default());
+    assert_eq!(whole.len(), 1);
+    assert_eq!((whole[0].x, whole[0].width), (0.0, 0.5));
+    assert_eq!(whole[0].max_intensity, Some(1.0));
+
+    let refined = regions_for(PixelSimilarity {
+        refine_block_size: Some(4),
+        ..PixelSimilarity::default()
+    });
+    assert_eq!(refined.len(), 1);
+    let region = &refined[0];
+    // The 4px cells touching the 8x8 square span pixels 8..20.
+    assert_eq!((region.x, region.y), (8.0 / 64.0, 8.0 / 64.0));
+    assert_eq!((region.width, region.height), (12.0 / 64.0, 12.0 / 64.0));
+    assert_eq!(region.max_intensity, Some(1.0));
+    let intensity = region.intensity.unwrap();
+    assert!((intensity - 64.0 / 144.0).abs() < 1e-3, "{intensity}");
+}
+
+#[test]
+fn pixel_metric_top_emphasis_penalizes_hero_diffs_more() {
+    let ref_img = RgbaImage::from_fn(64, 64, |x, _| {
+        let v = 60 + (x as u8) * 2;
+        Rgba([v, v, v, 255])
+    });
+    let band = |rows: std::ops::Range<u32>| {
+        let mut img = ref_img.clone();
+        for y in rows {
+            for x in 0..64 {
+                let v = ref_img.get_pixel(x, y)[0] + 40;
+                img.put_pixel(x, y, Rgba([v, v, v, 255]));
+            }
+        }
+        write_image(&img)
+    };
+    let ref_file = write_image(&ref_img);
+    let top_file = band(0..4);
+    let bottom_file = band(60..64);
+    let ref_view = view_from_file(ref_file.path(), 64, 64);
+
+    let score_for = |metric: &PixelSimilarity, file: &NamedTempFile| match metric
+        .compute(&ref_view, &view_from_file(file.path(), 64, 64))
+        .unwrap()
+    {
+        MetricResult::Pixel(p) => p.score,
+        _ => unreachable!(),
+    };
+
+    let plain = PixelSimilarity::default();
+    assert!((score_for(&plain, &top_file) - score_for(&plain, &bottom_file)).abs() < 1e-4);
+
+    let weighted = PixelSimilarity {
+        region_weights: Some(std::sync::Arc::new(RegionWeights {
+            regions: vec![],
+            top_emphasis: 2.0,
+            saliency: None,
+        })),
+        ..PixelSimilarity::default()
+    };
+    let top = score_for(&weighted, &top_file);
+    let bottom = score_for(&weighted, &bottom_file);
+    assert!(top < score_for(&plain, &top_file));
+    assert!(bottom > score_for(&plain, &bottom_file));
+    assert!(top < bottom);
+
+    // A saliency map with a prominent left half and a flat right half.
+    let salient = PixelSimilarity {
+        region_weights: Some(std::sync::Arc::new(RegionWeights {
+            saliency: Some(std::sync::Arc::new(crate::metrics::SaliencyMap {
+                columns: 2,
+                rows: 1,
+                values: vec![1.8, 0.2],
+            })),
+            ..RegionWeights::default()
+        })),
+        ..PixelSimilarity::default()
+    };
+    let column = |columns: std::ops::Range<u32>| {
+        let mut img = ref_img.clone();
+        for y in 0..64 {
+            for x in columns.clone() {
+                let v = ref_img.get_pixel(x, y)[0] + 40;
+                img.put_pixel(x, y, Rgba([v, v, v, 255]));
+            }
+        }
+        write_image(&img)
+    };
+    let (left_file, right_file) = (column(0..4), column(60..64));
+    assert!(score_for(&salient, &left_file) < score_for(&plain, &left_file));
+    assert!(score_for(&salient, &right_file) > score_for(&plain, &right_file));
+}
+
+#[test]
+fn layout_metric_region_weights_scale_missing_elements() {
+    let hero = ("section", bbox(0.0, 0.0, 1.0, 0.2));
+    let body = ("p", bbox(0.0, 0.4, 1.0, 0.2));
+    let footer = ("footer", bbox(0.0, 0.9, 1.0, 0.1));
+    let ref_view = view_with_dom(vec![hero, body, footer]);
+    let no_hero = view_with_dom(vec![body, footer]);
+    let no_footer = view_with_dom(vec![hero, body]);
+
+    let weights_file = NamedTempFile::new().unwrap();
+    std::fs::write(
+        weights_file.path(),
+        r#"[{"x": 0, "y": 0, "w": 1, "h": 0.25, "weight": 4}]"#,
+    )
+    .unwrap();
+    let weights = RegionWeights::from_json_file(weights_file.path()).unwrap();
+
+    let score_for = |metric: &LayoutSimilarity, impl_view: &NormalizedView| {
+        metric.compute_metric(&ref_view, impl_view).unwrap().score
+    };
+    let plain = LayoutSimilarity::default();
+    assert!((score_for(&plain, &no_hero) - score_for(&plain, &no_footer)).abs() < 1e-6);
+
+    let weighted = LayoutSimilarity {
+        region_weights: Some(std::sync::Arc::new(weights)),
+        ..LayoutSimilarity::default()
+    };
+    assert!(score_for(&weighted, &no_hero) < score_for(&plain, &no_hero));
+    assert!(score_for(&weighted, &no_footer) > score_for(&plain, &no_footer));
+    assert!((score_for(&weighted, &ref_view) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn layout_metric_missing_button_costs_more_than_missing_divider() {
+    let heading = ("h2", bbox(0.0, 0.0, 1.0, 0.1));
+    let button = ("button", bbox(0.1, 0.3, 0.3, 0.1));
+    let divider = ("hr", bbox(0.0, 0.6, 1.0, 0.01));
+    let ref_view = view_with_dom(vec![heading, button, divider]);
+    let no_button = view_with_dom(vec![heading, divider]);
+    let no_divider = view_with_dom(vec![heading, button]);
+
+    let score_for = |metric: &LayoutSimilarity, impl_view: &NormalizedView| {
+        metric.compute_metric(&ref_view, impl_view).unwrap().score
+    };
+    let weighted = LayoutSimilarity::default();
+    assert!(score_for(&weighted, &no_button) < score_for(&weighted, &no_divider));
+
+    let unweighted = LayoutSimilarity {
+        importance: None,
+        ..LayoutSimilarity::default()
+    };
+    assert!(
+        (score_for(&unweighted, &no_button) - score_for(&unweighted, &no_divider)).abs() < 1e-6
+    );
+}
+
+fn image_with_boxes(boxes: &[(u32, u32, u32, u32)]) -> RgbaImage {
+    let mut img = RgbaImage::from_pixel(120, 80, Rgba([255, 255, 255, 255]));
+    for &(x0, y0, w, h) in boxes {
+        for y in y0..y0 + h {
+            for x in x0..x0 + w {
+                img.put_pixel(x, y, Rgba([30, 30, 30, 255]));
+            }
+        }
+    }
+    img
+}
+
+#[test]
+fn edge_box_detector_finds_separate_elements() {
+    let img = image_with_boxes(&[(10, 10, 40, 20), (70, 40, 30, 30)]);
+    let boxes = EdgeBoxDetector::default().detect(&image::DynamicImage::ImageRgba8(img));
+    assert_eq!(boxes.len(), 2);
+    // Largest first; Sobel marks one pixel on each side of a boundary.
+    assert!((boxes[0].x - 69.0).abs() <= 1.0 && (boxes[0].width - 32.0).abs() <= 1.0);
+    assert!((boxes[1].y - 9.0).abs() <= 1.0 && (boxes[1].height - 22.0).abs() <= 1.0);
+}
+
+#[test]
+fn layout_metric_matches_boxes_detected_in_screenshots() {
+    let ref_file = write_image(&image_with_boxes(&[(10, 10, 40, 20), (70, 40, 30, 30)]));
+    let impl_file = write_image(&image_with_boxes(&[(10, 18, 40, 20)]));
+    let ref_view = view_from_file(ref_file.path(), 120, 80);
+    let impl_view = view_from_file(impl_file.path(), 120, 80);
+
+    let plain = LayoutSimilarity::default();
+    assert!(!plain.has_data(&ref_view, &impl_view));
+    let from_pixels = LayoutSimilarity {
+        pixel_boxes: Some(EdgeBoxDetector::default()),
+        ..LayoutSimilarity::default()
+    };
+    assert!(from_pixels.has_data(&ref_view, &impl_view));
+
+    let same = from_pixels.compute_metric(&ref_view, &ref_view).unwrap();
+    assert!(same.diff_regions.is_empty());
+    assert!((same.score - 1.0).abs() < 1e-6);
+
+    let changed = from_pixels.compute_metric(&ref_view, &impl_view).unwrap();
+    let kinds: Vec<_> = changed.diff_regions.iter().map(|r| r.kind).collect();
+    assert!(kinds.contains(&LayoutDiffKind::MissingElement));
+    assert!(kinds.contains(&LayoutDiffKind::PositionShift));
+    assert!(changed.score < 0.5);
+    assert!(changed
+        .diff_regions
+        .iter()
+        .all(|r| r.unit == Some(RegionUnit::Pixel)));
+
+    // Structured views keep using their DOM.
+    let dom_view = view_with_dom(vec![("button", bbox(0.1, 0.1, 0.2, 0.1))]);
+    assert!(from_pixels.has_data(&dom_view, &impl_view));
+    assert!(!from_pixels.has_data(&ref_view, &dom_view));
+}
+
+/// Draws a row of glyph-like shapes (a 2 px stem with a crossbar) as a
+/// stand-in for a line of text.
+fn draw_text_line(img: &mut RgbaImage, x0: u32, y0: u32, glyphs: u32, shade: u8) {
+    for glyph in 0..glyphs {
+        let gx = x0 + glyph * 7;
+        for y in y0..y0 + 12 {
+            for x in gx..gx + 2 {
+                img.put_pixel(x, y, Rgba([shade, shade, shade, 255]));
+            }
+        }
+        for y in y0 + 5..y0 + 7 {
+            for x in gx..gx + 5 {
+                img.put_pixel(x, y, Rgba([shade, shade, shade, 255]));
+            }
+        }
+    }
+}
+
+#[test]
+fn text_detector_finds_text_lines_but_not_solid_boxes() {
+    let mut img = image_with_boxes(&[(10, 50, 40, 20)]);
+    draw_text_line(&mut img, 8, 10, 12, 30);
+    let lines = TextDetector::default().detect(&image::DynamicImage::ImageRgba8(img));
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    // Sobel marks one pixel on each side of the glyphs.
+    assert!((lines[0].y - 9.0).abs() <= 1.0 && (lines[0].height - 14.0).abs() <= 1.0);
+    assert!((lines[0].x - 7.0).abs() <= 1.0);
+}
+
+#[test]
+fn pixel_metric_reports_glyph_rendering_inside_text_as_anti_aliasing() {
+    let mut ref_img = RgbaImage::from_pixel(120, 40, Rgba([255, 255, 255, 255]));
+    draw_text_line(&mut ref_img, 8, 8, 14, 30);
+    let mut impl_img = RgbaImage::from_pixel(120, 40, Rgba([255, 255, 255, 255]));
+    draw_text_line(&mut impl_img, 8, 8, 14, 90);
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 120, 40);
+    let impl_view = view_from_file(impl_file.path(), 120, 40);
+
+    let metric = PixelSimilarity {
+        block_size: 8,
+        ..PixelSimilarity::default()
+    };
+    let plain = metric.compute_metric(&ref_view, &impl_view).unwrap();
+    assert!(!plain.diff_regions.is_empty());
+    assert!(plain
+        .diff_regions
+        .iter()
+        .all(|r| r.reason == PixelDiffReason::PixelChange));
+
+    let with_text = PixelSimilarity {
+        text_regions: Some(TextDetector::default()),
+        ..metric
+    };
+    let marked = with_text.compute_metric(&ref_view, &impl_view).unwrap();
+    assert_eq!(marked.diff_regions.len(), plain.diff_regions.len());
+    assert!(marked.diff_regions.iter().all(|r| {
+        r.reason == PixelDiffReason::AntiAliasing && r.severity == DiffSeverity::Minor
+    }));
+}
+
+#[test]
+fn layout_metric_types_boxes_on_text_lines_as_text() {
+    let mut ref_img = image_with_boxes(&[(70, 40, 30, 30)]);
+    draw_text_line(&mut ref_img, 8, 10, 6, 30);
+    let impl_img = image_with_boxes(&[(70, 40, 30, 30)]);
+    let ref_file = write_image(&ref_img);
+    let impl_file = write_image(&impl_img);
+    let ref_view = view_from_file(ref_file.path(), 120, 80);
+    let impl_view = view_from_file(impl_file.path(), 120, 80);
+
+    let metric = LayoutSimilarity {
+        pixel_boxes: Some(EdgeBoxDetector::default()),
+        pixel_text: Some(TextDetector::default()),
+        ..LayoutSimilarity::default()
+    };
+    let result = metric.compute_metric(&ref_view, &impl_view).unwrap();
+    let missing: Vec<_> = result
+        .diff_regions
+        .iter()
+        .filter(|r| r.kind == LayoutDiffKind::MissingElement)
+        .collect();
+    assert!(!missing.is_empty());
+    assert!(missing
+        .iter()
+        .all(|r| r.element_type.as_deref() == Some("text")));
+}
+
+#[test]
+fn content_metric_missing_heading_costs_more_than_missing_caption() {
+    let ref_view = view_with_dom(vec![
+        ("h1:Welcome back", bbox(0.0, 0.0, 1.0, 0.1)),
+        ("span:Updated daily", bbox(0.0, 0.5, 0.4, 0.05)),
+    ]);
+    let no_heading = view_with_dom(vec![("span:Updated daily", bbox(0.0, 0.5, 0.4, 0.05))]);
+    let no_caption = view_with_dom(vec![("h1:Welcome back", bbox(0.0, 0.0, 1.0, 0.1))]);
+
+    let metric = ContentSimilarity::default();
+    let missing_heading = content_of(&metric, &ref_view, &no_heading).score;
+    let missing_caption = content_of(&metric, &ref_view, &no_caption).score;
+    assert!(missing_heading < missing_caption);
+
+    let unweighted = ContentSimilarity {
+        importance: None,
+        ..ContentSimilarity::default()
+    };
+    let missing_heading = content_of(&unweighted, &ref_view, &no_heading).score;
+    let missing_caption = content_of(&unweighted, &ref_view, &no_caption).score;
+    assert!((missing_heading - missing_caption).abs() < 1e-6);
+}
+
+#[test]
+fn importance_model_reads_roles_tags_and_layer_names() {
+    use crate::types::{DomNode, FigmaNode};
+    let model = ImportanceModel::default();
+    let mut node = DomNode {
+        id: "n0".into(),
+        tag: "DIV".into(),
+        children: vec![],
+        parent: None,
+        attributes: std::collections::HashMap::new(),
+        text: None,
+        bounding_box: bbox(0.0, 0.0, 0.1, 0.1),
+        text_baseline: None,
+        interaction_states: None,
+        computed_style: None,
+    };
+    assert_eq!(model.dom_importance(&node), model.default);
+    node.attributes
+        .insert("role".into(), "Navigation menubar".into());
+    assert_eq!(model.dom_importance(&node), model.roles["navigation"]);
+    node.attributes.clear();
+    node.tag = "H1".into();
+    assert_eq!(model.dom_importance(&node), model.tags["h1"]);
+
+    let layer = |name: &str| FigmaNode {
+        id: "1:2".to_string(),
+        name: Some(name.to_string()),
+        node_type: "FRAME".to_string(),
+        bounding_box: bbox(0.0, 0.0, 10.0, 10.0),
+        text: None,
+        typography: None,
+        line_count: None,
+        fills: vec![],
+        children: vec![],
+        auto_layout: None,
+        constraints: None,
+        responsive_box: None,
+        styles: std::collections::HashMap::new(),
+    };
+    assert_eq!(
+        model.figma_importance(&layer("CTA / Primary Button")),
+        model.roles["button"]
+    );
+    assert_eq!(model.figma_importance(&layer("A card")), model.default);
+
+    let overrides = NamedTempFile::new().unwrap();
+    std::fs::write(overrides.path(), r#"{"tags": {"HR": 0.0}, "default": 0.5}"#).unwrap();
+    let custom = ImportanceModel::from_json_file(overrides.path()).unwrap();
+    assert_eq!(custom.tags["hr"], 0.0);
+    assert_eq!(custom.tags["h1"], model.tags["h1"]);
+    assert_eq!(custom.default, 0.5);
+
+    std::fs::write(overrides.path(), r#"{"roles": {"button": -1}}"#).unwrap();
+    assert!(matches!(
+        ImportanceModel::from_json_file(overrides.path()),
+        Err(crate::DpcError::Config(_))
+    ));
+}
+
+#[test]
+fn score_interval_reports_spread_and_confidence_bounds() {
+    let single = ScoreInterval::from_samples(&[0.9]).unwrap();
+    assert_eq!(
+        (single.std_dev, single.lower, single.upper),
+        (0.0, 0.9, 0.9)
+    );
+    assert!(ScoreInterval::from_samples(&[]).is_none());
+
+    let interval = ScoreInterval::from_samples(&[0.90, 0.92, 0.94]).unwrap();
+    assert!((interval.mean - 0.92).abs() < 1e-6);
+    assert!((interval.std_dev - 0.02).abs() < 1e-4);
+    // t(2) = 4.303, so the margin is 4.303 * 0.02 / sqrt(3).
+    assert!((interval.upper - interval.mean - 0.0497).abs() < 1e-3);
+    assert_eq!((interval.min, interval.max), (0.90, 0.94));
+    assert!(interval.straddles(0.95));
+    assert!(!interval.straddles(0.80));
+
+    // Wide intervals are clamped to the score range.
+    let wide = ScoreInterval::from_samples(&[0.2, 0.99]).unwrap();
+    assert_eq!((wide.lower, wide.upper), (0.0, 1.0));
+}
+
+#[test]
+fn sample_stats_reports_median_capture_and_flags_unstable_runs() {
+    let pixel = |score: f32| MetricScores {
+        pixel: Some(PixelMetric {
+            score,
+            diff_regions: Vec::new(),
+            semantic_diffs: None,
+            offset: None,
+        }),
+        layout: None,
+        typography: None,
+        color: None,
+        content: None,
+        semantic: None,
+    };
+    let weights = ScoreWeights::default();
+
+    let stats = SampleStats::from_scores(&[pixel(0.95), pixel(0.6), pixel(0.9)], &weights).unwrap();
+    assert_eq!(stats.samples, 3);
+    assert_eq!(stats.reported_sample, 2);
+    assert!(stats.unstable);
+    assert_eq!(stats.metrics.len(), 1);
+    assert_eq!(stats.metrics[0].metric, MetricKind::Pixel);
+
+    let steady = SampleStats::from_scores(&[pixel(0.95), pixel(0.951)], &weights).unwrap();
+    assert!(!steady.unstable);
+    assert!(SampleStats::from_scores(&[], &weights).is_none());
+}
@@ -0,0 +1,116 @@
+//! Text lines detected in screenshots without reading them.
+//!
+//! Image-only comparisons mostly need to know where text is, not what it
+//! says, and OCR is slow. Glyph edges are joined along the line into
+//! candidate boxes; a box is a text line when its height fits a line, it is
+//! wider than tall, and its ink is made of thin strokes of a similar width
+//! (a simplified stroke width transform). Solid shapes, photos and most
+//! icons fail one of these tests.
+
+use image::{DynamicImage, GrayImage};
+
+use super::edge_boxes::{components, dilate, edge_mask};
+use crate::types::BoundingBox;
+
+/// Settings for detecting text lines in a screenshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDetector {
+    /// Gradient magnitude (0–1) above which a pixel counts as an edge.
+    pub edge_threshold: f32,
+    /// Horizontal gap in pixels bridged between glyphs and words.
+    pub letter_gap: u32,
+    /// Line heights in pixels outside this range are not text.
+    pub min_height: u32,
+    pub max_height: u32,
+    /// Lines are at least this many times wider than tall.
+    pub min_aspect: f32,
+    /// Mean stroke width is at most this fraction of the line height.
+    pub max_stroke_ratio: f32,
+    /// Largest coefficient of variation of the stroke widths.
+    pub max_stroke_variation: f32,
+}
+
+impl Default for TextDetector {
+    fn default() -> Self {
+        Self {
+            edge_threshold: 0.1,
+            letter_gap: 6,
+            min_height: 6,
+            max_height: 80,
+            min_aspect: 1.5,
+            max_stroke_ratio: 0.35,
+            max_stroke_variation: 1.0,
+        }
+    }
+}
+
+/// Fewest horizontal ink runs a line needs to judge its strokes.
+const MIN_STROKES: usize = 4;
+
+impl TextDetector {
+    /// Text line boxes in screenshot pixels, top to bottom.
+    pub fn detect(&self, image: &DynamicImage) -> Vec<BoundingBox> {
+        let gray = image.to_luma8();
+        let (width, height) = (gray.width(), gray.height());
+        let edges = edge_mask(&gray, self.edge_threshold);
+        let merged = dilate(&edges, width, height, self.letter_gap, 1);
+        let mut lines: Vec<BoundingBox> = components(&merged, &edges, width, height)
+            .into_iter()
+            .filter(|b| {
+                b.height >= self.min_height as f32
+                    && b.height <= self.max_height as f32
+                    && b.width >= b.height * self.min_aspect
+            })
+            .filter(|b| self.has_text_strokes(&gray, b))
+            .collect();
+        lines.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+        lines
+    }
+
+    /// Whether the ink in `line` is thin strokes of a similar width. Ink is
+    /// the side of the midpoint luma with fewer pixels, so light text on a
+    /// dark background works too.
+    fn has_text_strokes(&self, gray: &GrayImage, line: &BoundingBox) -> bool {
+        let (x0, y0) = (line.x as u32, line.y as u32);
+        let (x1, y1) = (x0 + line.width as u32, y0 + line.height as u32);
+        let pixels = || (y0..y1).flat_map(move |y| (x0..x1).map(move |x| gray.get_pixel(x, y)[0]));
+        let (lo, hi) = pixels().fold((u8::MAX, u8::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let mid = (lo as u16 + hi as u16) / 2;
+        let dark = pixels().filter(|&v| (v as u16) < mid).count();
+        let ink_is_dark = dark * 2 <= ((x1 - x0) * (y1 - y0)) as usize;
+        let is_ink = |v: u8| ((v as u16) < mid) == ink_is_dark;
+
+        let mut strokes = Vec::new();
+        for y in y0..y1 {
+            let mut run = 0u32;
+            for x in x0..x1 {
+                if is_ink(gray.get_pixel(x, y)[0]) {
+                    run += 1;
+                } else if run > 0 {
+                    strokes.push(run as f32);
+                    run = 0;
+                }
+            }
+            if run > 0 {
+                strokes.push(run as f32);
+            }
+        }
+        if strokes.len() < MIN_STROKES {
+            return false;
+        }
+        let n = strokes.len() as f32;
+        let mean = strokes.iter().sum::<f32>() / n;
+        let variance = strokes.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / n;
+        mean <= line.height * self.max_stroke_ratio
+            && variance.sqrt() / mean <= self.max_stroke_variation
+    }
+}
+
+/// Whether one of `lines` covers at least `share` of `bounds`.
+pub(super) fn on_text_line(lines: &[BoundingBox], bounds: &BoundingBox, share: f32) -> bool {
+    lines.iter().any(|line| {
+        let w = (line.x + line.width).min(bounds.x + bounds.width) - line.x.max(bounds.x);
+        let h = (line.y + line.height).min(bounds.y + bounds.height) - line.y.max(bounds.y);
+        w.max(0.0) * h.max(0.0) >= bounds.width * bounds.height * share
+    })
+}
@@ -0,0 +1,123 @@
+//! Alpha handling for the pixel and color metrics.
+//!
+//! Transparent pixels come from Figma exports with transparent backgrounds,
+//! letterbox padding and ignore-region masks. Their color channels are
+//! arbitrary (usually black), so comparing them as stored skews scores.
+
+use std::fmt;
+use std::str::FromStr;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+/// How pixels with alpha < 255 are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransparencyMode {
+    /// Compare color channels as stored, ignoring alpha.
+    #[default]
+    Ignore,
+    /// Composite both images over a solid background color first.
+    Composite([u8; 3]),
+    /// Leave pixels that are fully transparent in either image out of the
+    /// statistics.
+    Exclude,
+}
+
+impl TransparencyMode {
+    /// Apply compositing; other modes return the image unchanged.
+    pub fn prepare(self, img: DynamicImage) -> DynamicImage {
+        match self {
+            TransparencyMode::Composite(background) => {
+                DynamicImage::ImageRgba8(composite_over(&img.to_rgba8(), background))
+            }
+            TransparencyMode::Ignore | TransparencyMode::Exclude => img,
+        }
+    }
+
+    /// Whether a pixel is left out of the statistics.
+    pub fn excludes(self, pixel: &Rgba<u8>) -> bool {
+        matches!(self, TransparencyMode::Exclude) && pixel.0[3] == 0
+    }
+}
+
+fn composite_over(img: &RgbaImage, background: [u8; 3]) -> RgbaImage {
+    let mut out = img.clone();
+    for pixel in out.pixels_mut() {
+        let alpha = pixel.0[3] as u32;
+        for (channel, bg) in pixel.0.iter_mut().take(3).zip(background) {
+            *channel = ((*channel as u32 * alpha + bg as u32 * (255 - alpha) + 127) / 255) as u8;
+        }
+        pixel.0[3] = 255;
+    }
+    out
+}
+
+impl FromStr for TransparencyMode {
+    type Err = String;
+
+    /// `ignore`, `exclude`, `composite` (over white) or `composite:#RRGGBB`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "ignore" => return Ok(TransparencyMode::Ignore),
+            "exclude" => return Ok(TransparencyMode::Exclude),
+            "composite" => return Ok(TransparencyMode::Composite([255, 255, 255])),
+            _ => {}
+        }
+        let invalid = || {
+            format!("invalid transparency mode '{s}': expected ignore, exclude, composite or composite:#RRGGBB")
+        };
+        let hex = lower
+            .strip_prefix("composite:")
+            .ok_or_else(invalid)?
+            .trim()
+            .trim_start_matches('#');
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        Ok(TransparencyMode::Composite([
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+        ]))
+    }
+}
+
+impl fmt::Display for TransparencyMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransparencyMode::Ignore => write!(f, "ignore"),
+            TransparencyMode::Exclude => write!(f, "exclude"),
+            TransparencyMode::Composite([r, g, b]) => {
+                write!(f, "composite:#{r:02x}{g:02x}{b:02x}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays_modes() {
+        assert_eq!("ignore".parse(), Ok(TransparencyMode::Ignore));
+        assert_eq!("Exclude".parse(), Ok(TransparencyMode::Exclude));
+        assert_eq!(
+            "composite".parse(),
+            Ok(TransparencyMode::Composite([255, 255, 255]))
+        );
+        let mode: TransparencyMode = "composite:#1A2b3c".parse().unwrap();
+        assert_eq!(mode, TransparencyMode::Composite([0x1a, 0x2b, 0x3c]));
+        assert_eq!(mode.to_string(), "composite:#1a2b3c");
+        assert!("composite:#12".parse::<TransparencyMode>().is_err());
+        assert!("blend".parse::<TransparencyMode>().is_err());
+    }
+
+    #[test]
+    fn composite_blends_by_alpha() {
+        let img = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 128]));
+        let out = composite_over(&img, [255, 255, 255]);
+        assert_eq!(out.get_pixel(0, 0).0, [127, 127, 127, 255]);
+    }
+}
@@ -1,15 +1,30 @@
 use crate::error::DpcError;
-use crate::types::{NormalizedView, TypographyDiff, TypographyIssue, TypographyMetric};
+use crate::types::{
+    BoundingBox, NormalizedView, TypographyDiff, TypographyIssue, TypographyMetric, TypographyStyle,
+};
 use crate::Result;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 
 use super::{Metric, MetricKind, MetricResult};
 
+/// Distance of a line's baseline below the middle of its line box, in font
+/// sizes: half the ascent minus the descent of common UI fonts.
+const BASELINE_BELOW_MIDDLE: f32 = 0.35;
+/// A container is at most this many times as tall as the text block in it;
+/// taller boxes are sections, whose text placement is layout's business.
+const MAX_CONTAINER_RATIO: f32 = 4.0;
+
 #[derive(Debug, Clone, Copy)]
 pub struct TypographySimilarity {
     pub size_tolerance: f32,
     pub line_height_tolerance: f32,
     pub letter_spacing_tolerance: f32,
+    /// Pixels the baseline may move within its container.
+    pub baseline_tolerance: f32,
+    /// Pixels the baseline distance to the text above may differ.
+    pub rhythm_tolerance: f32,
 }
 
 impl Default for TypographySimilarity {
@@ -18,36 +33,221 @@ impl Default for TypographySimilarity {
             size_tolerance: 0.03,
             line_height_tolerance: 0.05,
             letter_spacing_tolerance: 0.02,
+            baseline_tolerance: 1.0,
+            rhythm_tolerance: 2.0,
+        }
+    }
+}
+
+/// One matched text element in the typography report artifact.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypographyReportRow {
+    pub text: String,
+    pub ref_id: String,
+    pub impl_id: String,
+    pub ref_font_family: Option<String>,
+    pub impl_font_family: Option<String>,
+    pub ref_font_size: Option<f32>,
+    pub impl_font_size: Option<f32>,
+    pub ref_font_weight: Option<String>,
+    pub impl_font_weight: Option<String>,
+    pub ref_line_height: Option<f32>,
+    pub impl_line_height: Option<f32>,
+    pub ref_lines: Option<u32>,
+    pub impl_lines: Option<u32>,
+    /// Penalty contributed by this pair (0.0 = identical, 1.0 = worst)
+    pub penalty: f32,
+    pub issues: Vec<TypographyIssue>,
+}
+
+impl TypographyReportRow {
+    fn new(
+        reference: &TypographyElement,
+        implementation: &TypographyElement,
+        penalty: f32,
+        issues: Vec<TypographyIssue>,
+    ) -> Self {
+        Self {
+            text: reference.text.trim().to_string(),
+            ref_id: reference.id.clone(),
+            impl_id: implementation.id.clone(),
+            ref_font_family: reference.family.clone(),
+            impl_font_family: implementation.family.clone(),
+            ref_font_size: reference.size,
+            impl_font_size: implementation.size,
+            ref_font_weight: reference.weight.clone(),
+            impl_font_weight: implementation.weight.clone(),
+            ref_line_height: reference.line_height,
+            impl_line_height: implementation.line_height,
+            ref_lines: reference.line_count,
+            impl_lines: implementation.line_count,
+            penalty,
+            issues,
         }
     }
 }
 
+/// One distinct text style in the type specimen artifact.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeSpecimenRow {
+    /// First family of the stack, as first seen.
+    pub font_family: Option<String>,
+    /// Rounded to half pixels.
+    pub font_size: Option<f32>,
+    /// Numeric weight when known, otherwise as written.
+    pub font_weight: Option<String>,
+    /// Reference text elements set in this style.
+    pub ref_count: usize,
+    /// Implementation text elements set in this style.
+    pub impl_count: usize,
+    /// The first text seen in this style.
+    pub sample: String,
+}
+
 #[derive(Debug, Clone)]
 struct TypographyElement {
     id: String,
     text: String,
+    /// Figma layer name or DOM `data-testid`/`aria-label`.
+    label: Option<String>,
+    /// Published Figma text style of the node.
+    style: Option<String>,
     family: Option<String>,
+    /// Family that actually rendered (DOM captures only).
+    rendered_family: Option<String>,
     size: Option<f32>,
     weight: Option<String>,
     line_height: Option<f32>,
     letter_spacing: Option<f32>,
+    /// Lines the text block wraps into.
+    line_count: Option<u32>,
+    /// Y of the first line's baseline.
+    baseline: Option<f32>,
+    /// `baseline` relative to the middle of the text's container.
+    baseline_offset: Option<f32>,
+}
+
+impl TypographyElement {
+    /// `(requested, rendered)` when the first family of the stack did not
+    /// render and the browser fell back to another one.
+    fn font_fallback(&self) -> Option<(String, String)> {
+        let requested = primary_family(self.family.as_deref()?)?;
+        let rendered = self.rendered_family.as_deref()?.trim();
+        if rendered.is_empty() || rendered.eq_ignore_ascii_case(&requested) {
+            return None;
+        }
+        Some((requested, rendered.to_string()))
+    }
+
+    fn line_pitch(&self) -> Option<f32> {
+        line_pitch(self.size, self.line_height)
+    }
+
+    /// Height of the text's lines together.
+    fn block_height(&self) -> Option<f32> {
+        Some(self.line_pitch()? * self.line_count? as f32)
+    }
+}
+
+/// Line box height: the line height, else 1.2x the font size (`normal`).
+fn line_pitch(size: Option<f32>, line_height: Option<f32>) -> Option<f32> {
+    line_height
+        .filter(|lh| *lh > 0.0)
+        .or_else(|| size.filter(|fs| *fs > 0.0).map(|fs| fs * 1.2))
+}
+
+/// Baseline of a text's first line and its offset from the middle of the
+/// container. `boxes` are the text's own box followed by its ancestors';
+/// without a `measured` baseline, the lines are taken to be centered in the
+/// text's box. The container is the first box with room above and below
+/// the lines (the text's own box for a padded button with its label as
+/// direct text, else e.g. the button around a label layer).
+fn baseline_position(
+    mut boxes: impl Iterator<Item = BoundingBox>,
+    measured: Option<f32>,
+    size: Option<f32>,
+    line_height: Option<f32>,
+    lines: Option<u32>,
+) -> (Option<f32>, Option<f32>) {
+    let (Some(own), Some(size), Some(pitch), Some(lines)) =
+        (boxes.next(), size, line_pitch(size, line_height), lines)
+    else {
+        return (None, None);
+    };
+    let block = pitch * lines as f32;
+    let baseline = measured.unwrap_or_else(|| {
+        let middle = own.y + own.height / 2.0;
+        middle - block / 2.0 + pitch / 2.0 + BASELINE_BELOW_MIDDLE * size
+    });
+    let offset = std::iter::once(own)
+        .chain(boxes)
+        .find(|bbox| bbox.height > block + 1.0)
+        .filter(|container| container.height <= block * MAX_CONTAINER_RATIO)
+        .map(|container| baseline - (container.y + container.height / 2.0));
+    (Some(baseline), offset)
+}
+
+/// Baseline distances `(ref, impl)` from the text block above, for text
+/// stacked right below it in both views.
+fn rhythm_gaps(
+    reference: &TypographyElement,
+    implementation: &TypographyElement,
+    previous: Option<(&TypographyElement, &TypographyElement)>,
+) -> Option<(f32, f32)> {
+    let (prev_ref, prev_impl) = previous?;
+    let ref_gap = reference.baseline? - prev_ref.baseline?;
+    let impl_gap = implementation.baseline? - prev_impl.baseline?;
+    let reach = prev_ref.block_height()? + 2.0 * reference.line_pitch()?;
+    // A block above that wraps differently moves everything below it.
+    let rewrapped = prev_ref.line_count != prev_impl.line_count;
+    (ref_gap > 0.0 && ref_gap <= reach && impl_gap > 0.0 && !rewrapped)
+        .then_some((ref_gap, impl_gap))
 }
 
 impl TypographySimilarity {
     fn extract(view: &NormalizedView) -> Option<Vec<TypographyElement>> {
         if let Some(dom) = &view.dom {
+            let by_id: HashMap<&str, &crate::types::DomNode> = dom
+                .nodes
+                .iter()
+                .map(|node| (node.id.as_str(), node))
+                .collect();
             let mut elems = Vec::new();
             for node in &dom.nodes {
                 if let Some(text) = &node.text {
                     if let Some(style) = &node.computed_style {
+                        let line_count = TypographyStyle {
+                            font_size: style.font_size,
+                            line_height: style.line_height,
+                            ..Default::default()
+                        }
+                        .line_count(node.bounding_box.height);
+                        let ancestors = std::iter::successors(Some(node), |node| {
+                            by_id.get(node.parent.as_deref()?).copied()
+                        });
+                        let (baseline, baseline_offset) = baseline_position(
+                            ancestors.map(|node| node.bounding_box),
+                            node.text_baseline,
+                            style.font_size,
+                            style.line_height,
+                            line_count,
+                        );
                         elems.push(TypographyElement {
                             id: node.id.clone(),
                             text: text.clone(),
+                            label: node.label().map(str::to_string),
+                            style: None,
                             family: style.font_family.clone(),
+                            rendered_family: style.rendered_font_family.clone(),
                             size: style.font_size,
                             weight: style.font_weight.clone(),
                             line_height: style.line_height,
                             letter_spacing: style.letter_spacing,
+                            line_count,
+                            baseline,
+                            baseline_offset,
                         });
                     }
                 }
@@ -58,17 +258,42 @@ impl TypographySimilarity {
         }
 
         if let Some(figma) = &view.figma_tree {
+            let parents: HashMap<&str, &crate::types::FigmaNode> = figma
+                .nodes
+                .iter()
+                .flat_map(|node| {
+                    node.children
+                        .iter()
+                        .map(move |child| (child.as_str(), node))
+                })
+                .collect();
             let mut elems = Vec::new();
             for node in &figma.nodes {
                 if let (Some(text), Some(style)) = (&node.text, &node.typography) {
+                    let ancestors = std::iter::successors(Some(node), |node| {
+                        parents.get(node.id.as_str()).copied()
+                    });
+                    let (baseline, baseline_offset) = baseline_position(
+                        ancestors.map(|node| node.layout_box()),
+                        None,
+                        style.font_size,
+                        style.line_height,
+                        node.line_count,
+                    );
                     elems.push(TypographyElement {
                         id: node.id.clone(),
                         text: text.clone(),
+                        label: node.label().map(str::to_string),
+                        style: figma.style_of(node, "text").map(|style| style.name.clone()),
                         family: style.font_family.clone(),
+                        rendered_family: None,
                         size: style.font_size,
                         weight: style.font_weight.clone(),
                         line_height: style.line_height,
                         letter_spacing: style.letter_spacing,
+                        line_count: node.line_count,
+                        baseline,
+                        baseline_offset,
                     });
                 }
             }
@@ -85,6 +310,71 @@ impl TypographySimilarity {
         reference: &NormalizedView,
         implementation: &NormalizedView,
     ) -> Result<TypographyMetric> {
+        self.compare(reference, implementation)
+            .map(|(metric, _)| metric)
+    }
+
+    /// Ref vs impl styles for every text element matched between the views.
+    pub fn report(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<Vec<TypographyReportRow>> {
+        self.compare(reference, implementation)
+            .map(|(_, rows)| rows)
+    }
+
+    /// Every distinct (family, size, weight) used by either view, with how
+    /// many text elements of each side use it; largest sizes first.
+    pub fn specimen(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Vec<TypeSpecimenRow> {
+        let mut rows: Vec<TypeSpecimenRow> = Vec::new();
+        let mut index: HashMap<(String, Option<i32>, Option<String>), usize> = HashMap::new();
+        for (view, is_ref) in [(reference, true), (implementation, false)] {
+            for el in TypographySimilarity::extract(view).unwrap_or_default() {
+                let family = el.family.as_deref().and_then(primary_family);
+                // Half-pixel buckets absorb subpixel rounding between renderers.
+                let size = el.size.map(|size| (size * 2.0).round() as i32);
+                let weight = font_weight_category(el.weight.as_deref())
+                    .map(|w| w.to_string())
+                    .or_else(|| el.weight.as_deref().map(|w| w.trim().to_ascii_lowercase()));
+                let key = (canonical_family(family.as_deref()), size, weight.clone());
+                let idx = *index.entry(key).or_insert_with(|| {
+                    rows.push(TypeSpecimenRow {
+                        font_family: family,
+                        font_size: size.map(|s| s as f32 / 2.0),
+                        font_weight: weight,
+                        ref_count: 0,
+                        impl_count: 0,
+                        sample: el.text.trim().to_string(),
+                    });
+                    rows.len() - 1
+                });
+                if is_ref {
+                    rows[idx].ref_count += 1;
+                } else {
+                    rows[idx].impl_count += 1;
+                }
+            }
+        }
+        rows.sort_by(|a, b| {
+            let size = |row: &TypeSpecimenRow| row.font_size.unwrap_or(-1.0);
+            size(b)
+                .total_cmp(&size(a))
+                .then_with(|| a.font_family.cmp(&b.font_family))
+                .then_with(|| a.font_weight.cmp(&b.font_weight))
+        });
+        rows
+    }
+
+    fn compare(
+        &self,
+        reference: &NormalizedView,
+        implementation: &NormalizedView,
+    ) -> Result<(TypographyMetric, Vec<TypographyReportRow>)> {
         let ref_elems = TypographySimilarity::extract(reference).ok_or_else(|| {
             DpcError::Config("No typography elements available in reference view".to_string())
         })?;
@@ -102,9 +392,13 @@ impl TypographySimilarity {
         let mut total_penalty = 0.0f32;
         let mut comparisons = 0usize;
         let mut diffs: Vec<TypographyDiff> = Vec::new();
+        let mut rows = Vec::new();
+        // The last matched pair, while the reference texts keep matching.
+        let mut previous: Option<(&TypographyElement, TypographyElement)> = None;
 
         for ref_el in &ref_elems {
             comparisons += 1;
+            let above = previous.take();
             let Some(norm_text) = normalize_label(&ref_el.text) else {
                 continue;
             };
@@ -112,27 +406,36 @@ impl TypographySimilarity {
             let maybe_impl_list = impl_by_text.get_mut(&norm_text);
             if let Some(list) = maybe_impl_list {
                 if let Some(impl_el) = list.pop() {
-                    let (penalty, issues) = typography_penalty(
+                    let above = above.as_ref().map(|(r, i)| (*r, i));
+                    let (penalty, issues) = self.penalty(ref_el, &impl_el, above);
+                    total_penalty += penalty;
+                    rows.push(TypographyReportRow::new(
                         ref_el,
                         &impl_el,
-                        self.size_tolerance,
-                        self.line_height_tolerance,
-                        self.letter_spacing_tolerance,
-                    );
-                    total_penalty += penalty;
+                        penalty,
+                        issues.clone(),
+                    ));
                     if !issues.is_empty() {
+                        let details = typography_details(ref_el, &impl_el, above, &issues);
                         diffs.push(TypographyDiff {
+                            fingerprint: None,
                             element_id_ref: Some(ref_el.id.clone()),
                             element_id_impl: Some(impl_el.id.clone()),
+                            label: ref_el.label.clone().or_else(|| impl_el.label.clone()),
+                            style: ref_el.style.clone(),
                             issues,
-                            details: None,
+                            details,
                         });
                     }
+                    previous = Some((ref_el, impl_el));
                 } else {
                     total_penalty += 1.0;
                     diffs.push(TypographyDiff {
+                        fingerprint: None,
                         element_id_ref: Some(ref_el.id.clone()),
                         element_id_impl: None,
+                        label: ref_el.label.clone(),
+                        style: ref_el.style.clone(),
                         issues: vec![TypographyIssue::FontFamilyMismatch],
                         details: None,
                     });
@@ -143,8 +446,11 @@ impl TypographySimilarity {
             } else {
                 total_penalty += 1.0;
                 diffs.push(TypographyDiff {
+                    fingerprint: None,
                     element_id_ref: Some(ref_el.id.clone()),
                     element_id_impl: None,
+                    label: ref_el.label.clone(),
+                    style: ref_el.style.clone(),
                     issues: vec![TypographyIssue::FontFamilyMismatch],
                     details: None,
                 });
@@ -157,8 +463,11 @@ impl TypographySimilarity {
                 comparisons += 1;
                 total_penalty += 0.2;
                 diffs.push(TypographyDiff {
+                    fingerprint: None,
                     element_id_ref: None,
                     element_id_impl: Some(impl_el.id.clone()),
+                    label: impl_el.label.clone(),
+                    style: None,
                     issues: vec![TypographyIssue::FontFamilyMismatch],
                     details: None,
                 });
@@ -171,8 +480,55 @@ impl TypographySimilarity {
             (1.0 - (total_penalty / comparisons as f32)).clamp(0.0, 1.0)
         };
 
-        Ok(TypographyMetric { score, diffs })
+        Ok((TypographyMetric { score, diffs }, rows))
+    }
+}
+
+impl TypographySimilarity {
+    /// Penalty and issues of a matched pair; `above` is the pair matched
+    /// just before it, for the vertical rhythm.
+    fn penalty(
+        &self,
+        reference: &TypographyElement,
+        implementation: &TypographyElement,
+        above: Option<(&TypographyElement, &TypographyElement)>,
+    ) -> (f32, Vec<TypographyIssue>) {
+        const BASELINE_WEIGHT: f32 = 0.05;
+        const RHYTHM_WEIGHT: f32 = 0.05;
+
+        let (mut penalty, mut issues) = typography_penalty(
+            reference,
+            implementation,
+            self.size_tolerance,
+            self.line_height_tolerance,
+            self.letter_spacing_tolerance,
+        );
+        if let Some(shift) = baseline_shift(reference, implementation) {
+            if shift.abs() > self.baseline_tolerance {
+                penalty += BASELINE_WEIGHT;
+                issues.push(TypographyIssue::BaselineShift);
+            }
+        }
+        if let Some((ref_gap, impl_gap)) = rhythm_gaps(reference, implementation, above) {
+            if (impl_gap - ref_gap).abs() > self.rhythm_tolerance {
+                penalty += RHYTHM_WEIGHT;
+                issues.push(TypographyIssue::VerticalRhythmDiff);
+            }
+        }
+        (penalty.min(1.0), issues)
+    }
+}
+
+/// How much lower (positive) the implementation's baseline sits in its
+/// container. Only texts that keep their line count compare.
+fn baseline_shift(
+    reference: &TypographyElement,
+    implementation: &TypographyElement,
+) -> Option<f32> {
+    if reference.line_count != implementation.line_count {
+        return None;
     }
+    Some(implementation.baseline_offset? - reference.baseline_offset?)
 }
 
 fn typography_penalty(
@@ -187,6 +543,7 @@ fn typography_penalty(
     const WEIGHT_WEIGHT: f32 = 0.15;
     const LINE_WEIGHT: f32 = 0.05;
     const LETTER_SPACING_WEIGHT: f32 = 0.05;
+    const LINE_WRAP_WEIGHT: f32 = 0.1;
 
     let mut penalty = 0.0f32;
     let mut issues = Vec::new();
@@ -196,6 +553,10 @@ fn typography_penalty(
     if ref_family != impl_family {
         penalty += FAMILY_WEIGHT;
         issues.push(TypographyIssue::FontFamilyMismatch);
+    } else if implementation.font_fallback().is_some() {
+        // Right family requested, but what users see is the fallback.
+        penalty += FAMILY_WEIGHT;
+        issues.push(TypographyIssue::FontNotLoaded);
     }
 
     if let (Some(ref_size), Some(impl_size)) = (reference.size, implementation.size) {
@@ -235,7 +596,48 @@ fn typography_penalty(
         }
     }
 
-    (penalty, issues)
+    if let (Some(ref_lines), Some(impl_lines)) = (reference.line_count, implementation.line_count) {
+        if ref_lines != impl_lines {
+            penalty += LINE_WRAP_WEIGHT;
+            issues.push(TypographyIssue::LineWrapDiff);
+        }
+    }
+
+    (penalty.min(1.0), issues)
+}
+
+/// Extra context for a diff: the fallback font, the line counts and the
+/// baseline shift and distances (pixels, rounded to tenths).
+fn typography_details(
+    reference: &TypographyElement,
+    implementation: &TypographyElement,
+    above: Option<(&TypographyElement, &TypographyElement)>,
+    issues: &[TypographyIssue],
+) -> Option<Value> {
+    let px = |value: f32| (f64::from(value) * 10.0).round() / 10.0;
+    let mut details = Map::new();
+    if issues.contains(&TypographyIssue::FontNotLoaded) {
+        if let Some((requested, rendered)) = implementation.font_fallback() {
+            details.insert("requestedFont".into(), json!(requested));
+            details.insert("fallbackFont".into(), json!(rendered));
+        }
+    }
+    if issues.contains(&TypographyIssue::LineWrapDiff) {
+        details.insert("refLines".into(), json!(reference.line_count));
+        details.insert("implLines".into(), json!(implementation.line_count));
+    }
+    if issues.contains(&TypographyIssue::BaselineShift) {
+        if let Some(shift) = baseline_shift(reference, implementation) {
+            details.insert("baselineShift".into(), json!(px(shift)));
+        }
+    }
+    if issues.contains(&TypographyIssue::VerticalRhythmDiff) {
+        if let Some((ref_gap, impl_gap)) = rhythm_gaps(reference, implementation, above) {
+            details.insert("refBaselineGap".into(), json!(px(ref_gap)));
+            details.insert("implBaselineGap".into(), json!(px(impl_gap)));
+        }
+    }
+    (!details.is_empty()).then_some(Value::Object(details))
 }
 
 fn normalize_label(input: &str) -> Option<String> {
@@ -258,6 +660,17 @@ fn normalize_label(input: &str) -> Option<String> {
     }
 }
 
+/// First entry of a CSS `font-family` stack, unquoted.
+fn primary_family(stack: &str) -> Option<String> {
+    let first = stack
+        .split(',')
+        .next()?
+        .trim()
+        .trim_matches(['"', '\''])
+        .trim();
+    (!first.is_empty()).then(|| first.to_string())
+}
+
 fn canonical_family(family: Option<&str>) -> String {
     let Some(fam) = family else {
         return "unknown".to_string();
@@ -1,38 +1,51 @@
+use crate::browser::Emulation;
 use crate::error::ErrorPayload;
-use crate::types::{MetricScores, ResourceKind, Viewport};
+use crate::metrics::{MetricKind, SampleStats};
+use crate::result_diff::ResultDiff;
+use crate::suppression::SuppressedFinding;
+use crate::types::{
+    BrowserEngine, MetricScores, PageDiagnostics, RegionBounds, RegionUnit, ResourceKind, Viewport,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// Schema version for output payloads.
-pub const DPC_OUTPUT_VERSION: &str = "0.2.0";
+pub const DPC_OUTPUT_VERSION: &str = "0.3.0";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "mode", rename_all = "kebab-case")]
 #[allow(clippy::large_enum_variant)]
 pub enum DpcOutput {
     Compare(CompareOutput),
     GenerateCode(GenerateCodeOutput),
     Quality(QualityOutput),
+    DiffResults(DiffResultsOutput),
+    Flow(FlowOutput),
+    Audit(AuditOutput),
     Error(ErrorOutput),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum OutputMode {
     Compare,
     GenerateCode,
     Quality,
+    DiffResults,
+    Flow,
+    Audit,
     Error,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceDescriptor {
     pub kind: ResourceKind,
     pub value: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CompareOutput {
     pub version: String,
@@ -41,6 +54,14 @@ pub struct CompareOutput {
     #[serde(rename = "impl")]
     pub impl_resource: ResourceDescriptor,
     pub viewport: Viewport,
+    /// Engine that rendered the URL input(s); absent when nothing was rendered
+    /// in a browser.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserEngine>,
+    /// Locale and timezone the URL input(s) were rendered with (`--locale`,
+    /// `--timezone`); absent when neither was set or nothing was rendered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emulation: Option<Emulation>,
     pub similarity: f32,
     pub threshold: f32,
     pub passed: bool,
@@ -49,16 +70,229 @@ pub struct CompareOutput {
     pub summary: Option<Summary>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artifacts: Option<CompareArtifacts>,
+    /// All reference candidates that were scored, when more than one was supplied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_candidates: Option<Vec<ReferenceCandidate>>,
+    /// Conditions that make the result less trustworthy, e.g. a design frame
+    /// letterboxed into a viewport of a very different aspect ratio.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Findings left out because their fingerprint is listed in the
+    /// suppression file (`.dpc-ignore.json`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed: Vec<SuppressedFinding>,
+    /// Console errors and failed requests recorded while rendering URL inputs
+    /// (`--record-network`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_diagnostics: Option<ComparePageDiagnostics>,
+    /// Metrics left out of `metrics` and the similarity, e.g. because the
+    /// `--budget` ran out before they finished.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_metrics: Vec<SkippedMetric>,
+    /// Score spread over repeated implementation captures (`--samples`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SampleStats>,
+    /// How screenshots with different aspect ratios were reconciled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimension_mismatch: Option<DimensionMismatch>,
+    /// Scores of the named page sections (`--sections`), in file order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<SectionScore>,
+    /// Lower-resolution analysis forced by the `[resources]` guardrails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degradation: Option<Degradation>,
+    /// Comments left on the Figma reference (`--post-figma-comments`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub figma_comments: Vec<PostedFigmaComment>,
+    /// Figma variables compared with the implementation's CSS custom
+    /// properties (`--figma-variables`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable_drift: Option<VariableDrift>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What to do when the reference and implementation screenshots have
+/// different aspect ratios (`--dimension-mismatch`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DimensionMismatchPolicy {
+    /// Resize the implementation to the reference size, distorting it.
+    #[default]
+    Stretch,
+    /// Scale the implementation to fit the reference canvas, keeping its
+    /// aspect ratio; the padding is left out of the pixel comparison.
+    Letterbox,
+    /// Compare only the top-left area both screenshots cover, unscaled.
+    Crop,
+    /// Stop with an error explaining the mismatch.
+    Fail,
+}
+
+/// Screenshot sizes of a compare run whose aspect ratios differed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionMismatch {
+    pub policy: DimensionMismatchPolicy,
+    #[serde(rename = "ref")]
+    pub reference: ImageSize,
+    #[serde(rename = "impl")]
+    pub implementation: ImageSize,
+    /// Size of the screenshots the metrics compared.
+    pub compared: ImageSize,
+}
+
+/// How a compare run under memory or CPU pressure lowered its analysis
+/// resolution (`[resources]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Degradation {
+    /// The pressure that was detected, e.g. low available memory.
+    pub reason: String,
+    /// Screenshot size cap the inputs were downscaled to.
+    pub max_megapixels: f32,
+    /// Pixel metric block size used instead of the configured one.
+    pub block_size: u32,
+    /// Screenshots that were larger than the cap.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub downscaled: Vec<DownscaledScreenshot>,
+}
+
+/// A screenshot analyzed at a lower resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DownscaledScreenshot {
+    /// Capture the screenshot belongs to (`ref`, `ref2`, `impl`, `impl_sample2`, ...).
+    pub side: String,
+    pub original: ImageSize,
+    pub analyzed: ImageSize,
+}
+
+/// A finding commented on the Figma reference file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PostedFigmaComment {
+    /// Figma node the comment is pinned to.
+    pub node_id: String,
+    pub fingerprint: String,
+    pub message: String,
+    pub comment_id: String,
+    /// An earlier run already commented on this finding; nothing was posted.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub already_posted: bool,
+}
+
+/// Figma variables whose value differs from the CSS custom property they
+/// are published as.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableDrift {
+    /// Variable values that had a custom property to compare with.
+    pub compared: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<VariableMismatch>,
+}
+
+/// A variable value the implementation does not match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableMismatch {
+    /// Figma variable name (`color/primary/600`)
+    pub variable: String,
+    pub collection: String,
+    /// Mode whose value was compared (`Light`, `Dark`)
+    pub mode: String,
+    /// Custom property (`--color-primary-600`)
+    pub property: String,
+    /// Selector the property was read under: `:root` or a theme or
+    /// component rule
+    pub scope: String,
+    /// Value in Figma, in CSS notation
+    pub expected: String,
+    /// Value the browser resolved
+    pub actual: String,
+    /// CIEDE2000 difference, for color variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_e: Option<f32>,
+}
+
+impl VariableMismatch {
+    /// One-line description for reports.
+    pub fn describe(&self) -> String {
+        let delta = self
+            .delta_e
+            .map(|delta_e| format!(" (ΔE {delta_e:.1})"))
+            .unwrap_or_default();
+        format!(
+            "{} ({}): Figma {}, {} on {} is {}{delta}",
+            self.variable, self.mode, self.expected, self.property, self.scope, self.actual
+        )
+    }
+}
+
+/// Width and height in screenshot pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Scores of one named page section, compared on its own.
+///
+/// Findings inside `metrics` are relative to the section crop, not the page.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionScore {
+    pub name: String,
+    /// Section bounds in reference screenshot pixels.
+    pub bounds: RegionBounds,
+    pub similarity: f32,
+    /// Whether `similarity` reaches the run's threshold.
+    pub passed: bool,
+    pub metrics: MetricScores,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_issues: Vec<String>,
+}
+
+/// A metric that was requested but not computed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SkippedMetric {
+    pub metric: MetricKind,
+    /// Why it was skipped: `"budget"` when the `--budget` ran out.
+    pub reason: String,
+}
+
+/// Page diagnostics per side of a compare run; a side is absent when it was
+/// not rendered in a browser.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ComparePageDiagnostics {
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<PageDiagnostics>,
+    #[serde(rename = "impl", default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<PageDiagnostics>,
+}
+
+/// A reference candidate considered for best-match comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceCandidate {
+    pub resource: ResourceDescriptor,
+    /// Combined similarity of the implementation against this candidate.
+    pub similarity: f32,
+    /// Whether this candidate was chosen as the reported `ref`.
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub top_issues: Vec<String>,
+    /// Prioritized prose written by a summarizer (`--summarize`); shown in
+    /// place of `top_issues` in the pretty output and markdown report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narrative: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GenerateCodeOutput {
     pub version: String,
@@ -74,7 +308,7 @@ pub struct GenerateCodeOutput {
     pub summary: Option<Summary>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QualityOutput {
     pub version: String,
@@ -85,7 +319,105 @@ pub struct QualityOutput {
     pub findings: Vec<QualityFinding>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `dpc diff-results`: what changed between two saved compare results.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResultsOutput {
+    pub version: String,
+    /// Path of the earlier result.
+    pub old: PathBuf,
+    /// Path of the later result.
+    pub new: PathBuf,
+    #[serde(flatten)]
+    pub diff: ResultDiff,
+}
+
+/// `dpc flow`: every screen of a Figma prototype flow compared with its
+/// implementation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowOutput {
+    pub version: String,
+    /// The flow's start frame.
+    #[serde(rename = "ref")]
+    pub ref_resource: ResourceDescriptor,
+    pub viewport: Viewport,
+    /// Mean similarity of the compared screens.
+    pub similarity: f32,
+    pub threshold: f32,
+    /// Every screen was compared and passed.
+    pub passed: bool,
+    /// Screens in flow order.
+    pub screens: Vec<FlowScreen>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// One screen of a prototype flow and its comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowScreen {
+    /// Figma node id of the frame.
+    pub frame_id: String,
+    pub frame_name: String,
+    pub page: String,
+    /// Implementation paired with the frame; absent when too few were given.
+    #[serde(rename = "impl", default, skip_serializing_if = "Option::is_none")]
+    pub impl_resource: Option<ResourceDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricScores>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_issues: Vec<String>,
+    /// Why the screen could not be compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `dpc audit`: crawled implementation pages compared with the references
+/// their routes map to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditOutput {
+    pub version: String,
+    /// Where the crawl started: a sitemap or a page URL.
+    pub crawl: String,
+    pub viewport: Viewport,
+    /// Mean similarity of the compared pages.
+    pub similarity: f32,
+    pub threshold: f32,
+    /// Every page with a reference was compared and passed.
+    pub passed: bool,
+    /// Pages in crawl order.
+    pub pages: Vec<AuditPage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// One crawled page and its comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditPage {
+    pub url: String,
+    /// Reference the page's route maps to; absent when no rule matched, in
+    /// which case the page is not compared and does not count.
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub ref_resource: Option<ResourceDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricScores>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_issues: Vec<String>,
+    /// Why the page could not be compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QualityFinding {
     pub severity: FindingSeverity,
@@ -94,7 +426,7 @@ pub struct QualityFinding {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CompareArtifacts {
     pub directory: PathBuf,
@@ -106,6 +438,16 @@ pub struct CompareArtifacts {
     pub impl_screenshot: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub diff_image: Option<PathBuf>,
+    /// Implementation screenshot with each matched element tinted by how far
+    /// its colors drifted from the reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_drift_image: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_thumbnail: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impl_thumbnail: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff_thumbnail: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ref_dom_snapshot: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -114,9 +456,31 @@ pub struct CompareArtifacts {
     pub ref_figma_snapshot: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub impl_figma_snapshot: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typography_report: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typography_report_csv: Option<PathBuf>,
+    /// HTML type specimen: every text style of either side rendered side by
+    /// side with usage counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typography_specimen: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_manifest: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ref_network_har: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impl_network_har: Option<PathBuf>,
+    /// `overlay.json`: diff regions and finding element boxes as normalized
+    /// rectangles for review front-ends (see [`crate::overlay`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<PathBuf>,
+    /// `index.json` listing every file above with its type, size and the
+    /// findings it shows (see [`crate::artifact_index`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_index: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum FindingSeverity {
     Info,
@@ -124,16 +488,24 @@ pub enum FindingSeverity {
     Error,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QualityFindingType {
     AlignmentInconsistent,
     SpacingInconsistent,
     LowContrast,
     MissingHierarchy,
+    MissingFocusStyle,
+    MissingHoverStyle,
+    SkippedHeadingLevel,
+    EmptyHeading,
+    MissingAltText,
+    NonSemanticClickHandler,
+    SpacingOffScale,
+    PaletteSprawl,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorOutput {
     pub version: String,
@@ -143,9 +515,215 @@ pub struct ErrorOutput {
     pub error: ErrorPayload,
 }
 
+/// Which coordinate systems diff regions are reported in.
+///
+/// Normalized 0–1 coordinates are always emitted; the other spaces add
+/// `pixelBounds` and/or `cssBounds` alongside them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateSpace {
+    #[default]
+    Normalized,
+    Pixel,
+    Css,
+    All,
+}
+
+impl CoordinateSpace {
+    fn includes_pixel(self) -> bool {
+        matches!(self, CoordinateSpace::Pixel | CoordinateSpace::All)
+    }
+
+    fn includes_css(self) -> bool {
+        matches!(self, CoordinateSpace::Css | CoordinateSpace::All)
+    }
+}
+
+/// Dimensions of the image the diff regions were measured against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateFrame {
+    /// Screenshot width in pixels
+    pub width: u32,
+    /// Screenshot height in pixels
+    pub height: u32,
+    /// Screenshot pixels per CSS pixel
+    pub device_pixel_ratio: f32,
+}
+
+impl CoordinateFrame {
+    /// Build a frame for a screenshot captured at `viewport` (CSS px).
+    pub fn for_screenshot(width: u32, height: u32, viewport: &Viewport) -> Self {
+        let device_pixel_ratio = if viewport.width > 0 && width > 0 {
+            width as f32 / viewport.width as f32
+        } else {
+            1.0
+        };
+        Self {
+            width,
+            height,
+            device_pixel_ratio,
+        }
+    }
+
+    fn pixel_bounds(&self, x: f32, y: f32, width: f32, height: f32) -> RegionBounds {
+        let (fw, fh) = (self.width as f32, self.height as f32);
+        RegionBounds {
+            x: (x * fw).round(),
+            y: (y * fh).round(),
+            width: (width * fw).round(),
+            height: (height * fh).round(),
+        }
+    }
+
+    fn dpr(&self) -> f32 {
+        if self.device_pixel_ratio > 0.0 {
+            self.device_pixel_ratio
+        } else {
+            1.0
+        }
+    }
+
+    fn css_bounds(&self, pixel: RegionBounds) -> RegionBounds {
+        scale_bounds(pixel, 1.0 / self.dpr())
+    }
+
+    fn pixel_bounds_from_css(&self, css: RegionBounds) -> RegionBounds {
+        scale_bounds(css, self.dpr())
+    }
+}
+
+fn scale_bounds(bounds: RegionBounds, factor: f32) -> RegionBounds {
+    RegionBounds {
+        x: bounds.x * factor,
+        y: bounds.y * factor,
+        width: bounds.width * factor,
+        height: bounds.height * factor,
+    }
+}
+
+/// JSON Schema (draft 2020-12) of every [`DpcOutput`] payload, as printed by
+/// `dpc schema` and published as `docs/output_schema.json`.
+///
+/// Any change to the output types changes this schema; bump
+/// [`DPC_OUTPUT_VERSION`] along with it so consumers can tell payloads apart.
+pub fn output_schema() -> serde_json::Value {
+    let mut schema =
+        serde_json::to_value(schemars::schema_for!(DpcOutput)).expect("JSON Schema serializes");
+    schema["$comment"] = format!("dpc output version {DPC_OUTPUT_VERSION}").into();
+    schema
+}
+
+/// Populate absolute pixel / CSS pixel bounds on diff regions according to `space`.
+///
+/// Pixel diff regions are always normalized. Layout regions are converted from
+/// their recorded [`RegionUnit`]: normalized Figma boxes are scaled to the
+/// screenshot, DOM boxes are already CSS pixels and detected boxes screenshot
+/// pixels. Regions without a unit are treated as normalized when they fit in
+/// 0–1 and as screenshot pixels otherwise.
+pub fn apply_coordinate_space(
+    metrics: &mut MetricScores,
+    space: CoordinateSpace,
+    frame: &CoordinateFrame,
+) {
+    if space == CoordinateSpace::Normalized {
+        return;
+    }
+
+    let bounds_for = |x: f32, y: f32, width: f32, height: f32, unit: RegionUnit| {
+        let native = RegionBounds {
+            x,
+            y,
+            width,
+            height,
+        };
+        let (pixel, css) = match unit {
+            RegionUnit::Normalized => {
+                let pixel = frame.pixel_bounds(x, y, width, height);
+                (pixel, frame.css_bounds(pixel))
+            }
+            RegionUnit::Pixel => (native, frame.css_bounds(native)),
+            RegionUnit::Css => (frame.pixel_bounds_from_css(native), native),
+        };
+        (
+            space.includes_pixel().then_some(pixel),
+            space.includes_css().then_some(css),
+        )
+    };
+
+    if let Some(pixel) = metrics.pixel.as_mut() {
+        for region in &mut pixel.diff_regions {
+            let (px, css) = bounds_for(
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+                RegionUnit::Normalized,
+            );
+            region.pixel_bounds = px;
+            region.css_bounds = css;
+        }
+    }
+
+    if let Some(layout) = metrics.layout.as_mut() {
+        for region in &mut layout.diff_regions {
+            let unit = region.unit.unwrap_or_else(|| {
+                let normalized = region.x >= 0.0
+                    && region.y >= 0.0
+                    && region.x + region.width <= 1.0 + f32::EPSILON
+                    && region.y + region.height <= 1.0 + f32::EPSILON;
+                if normalized {
+                    RegionUnit::Normalized
+                } else {
+                    RegionUnit::Pixel
+                }
+            });
+            let (px, css) = bounds_for(region.x, region.y, region.width, region.height, unit);
+            region.pixel_bounds = px;
+            region.css_bounds = css;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// Every published output version with the SHA-256 of its schema (minus
+    /// the `$comment`), oldest first. A change to the output types fails
+    /// `published_schema_matches_the_output_types` until it gets a new entry
+    /// here under a bumped [`DPC_OUTPUT_VERSION`].
+    const SCHEMA_VERSIONS: &[(&str, &str)] = &[(
+        "0.3.0",
+        "141c255f03e9e2955855d83a223058950bf05bfe9c268e130368e0a5b2e5c1fa",
+    )];
+
+    #[test]
+    fn published_schema_matches_the_output_types() {
+        let published: serde_json::Value =
+            serde_json::from_str(include_str!("../docs/output_schema.json")).unwrap();
+        assert!(
+            published == output_schema(),
+            "docs/output_schema.json is stale; regenerate it with `dpc schema --output docs/output_schema.json`"
+        );
+
+        let mut schema = output_schema();
+        schema.as_object_mut().unwrap().remove("$comment");
+        let hash = hex::encode(Sha256::digest(serde_json::to_vec(&schema).unwrap()));
+        let versions: std::collections::HashSet<_> =
+            SCHEMA_VERSIONS.iter().map(|(version, _)| version).collect();
+        assert_eq!(
+            versions.len(),
+            SCHEMA_VERSIONS.len(),
+            "each output version must pin exactly one schema"
+        );
+        let (version, pinned) = SCHEMA_VERSIONS.last().unwrap();
+        assert!(
+            hash == *pinned && *version == DPC_OUTPUT_VERSION,
+            "the output schema (SHA-256 {hash}) no longer matches the one pinned for {version}; \
+             bump DPC_OUTPUT_VERSION and add it with this hash to SCHEMA_VERSIONS"
+        );
+    }
 
     #[test]
     fn compare_output_serializes() {
@@ -159,10 +737,9 @@ mod tests {
                 kind: ResourceKind::Url,
                 value: "https://example.com".to_string(),
             },
-            viewport: Viewport {
-                width: 1440,
-                height: 900,
-            },
+            viewport: Viewport::new(1440, 900),
+            browser: None,
+            emulation: None,
             similarity: 0.93,
             threshold: 0.9,
             passed: true,
@@ -172,11 +749,24 @@ mod tests {
                 typography: None,
                 color: None,
                 content: None,
+                semantic: None,
             },
             summary: Some(Summary {
                 top_issues: vec!["Minor color shift".into()],
+                narrative: None,
             }),
             artifacts: None,
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
         });
 
         let json = serde_json::to_string(&output).expect("serialize compare output");
@@ -192,10 +782,22 @@ mod tests {
             ref_screenshot: Some(PathBuf::from("/tmp/dpc-123/ref.png")),
             impl_screenshot: Some(PathBuf::from("/tmp/dpc-123/impl.png")),
             diff_image: Some(PathBuf::from("/tmp/dpc-123/diff.png")),
+            color_drift_image: None,
+            ref_thumbnail: None,
+            impl_thumbnail: None,
+            diff_thumbnail: None,
             ref_dom_snapshot: None,
             impl_dom_snapshot: Some(PathBuf::from("/tmp/dpc-123/impl_dom.json")),
             ref_figma_snapshot: None,
             impl_figma_snapshot: None,
+            typography_report: None,
+            typography_report_csv: None,
+            typography_specimen: None,
+            run_manifest: None,
+            ref_network_har: None,
+            impl_network_har: None,
+            overlay: None,
+            artifact_index: None,
         };
 
         let output = DpcOutput::Compare(CompareOutput {
@@ -208,10 +810,9 @@ mod tests {
                 kind: ResourceKind::Url,
                 value: "https://example.com".to_string(),
             },
-            viewport: Viewport {
-                width: 1440,
-                height: 900,
-            },
+            viewport: Viewport::new(1440, 900),
+            browser: None,
+            emulation: None,
             similarity: 0.93,
             threshold: 0.9,
             passed: true,
@@ -221,9 +822,21 @@ mod tests {
                 typography: None,
                 color: None,
                 content: None,
+                semantic: None,
             },
             summary: None,
             artifacts: Some(artifacts),
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
         });
 
         let json = serde_json::to_string(&output).expect("serialize compare output");
@@ -239,10 +852,7 @@ mod tests {
                 kind: ResourceKind::Figma,
                 value: "figma-file".to_string(),
             },
-            viewport: Some(Viewport {
-                width: 1280,
-                height: 720,
-            }),
+            viewport: Some(Viewport::new(1280, 720)),
             stack: Some("html+tailwind".to_string()),
             output_path: Some(PathBuf::from("output.html")),
             code: Some("<div>hi</div>".to_string()),
@@ -262,10 +872,7 @@ mod tests {
                 kind: ResourceKind::Url,
                 value: "https://example.com".to_string(),
             },
-            viewport: Viewport {
-                width: 1024,
-                height: 768,
-            },
+            viewport: Viewport::new(1024, 768),
             score: 0.82,
             findings: vec![QualityFinding {
                 severity: FindingSeverity::Warning,
@@ -298,4 +905,121 @@ mod tests {
         assert!(json.contains("\"missing ref\""));
         assert!(json.contains("\"message\""));
     }
+
+    fn region_scores() -> MetricScores {
+        use crate::types::{
+            DiffSeverity, LayoutDiffKind, LayoutDiffRegion, LayoutMetric, PixelDiffReason,
+            PixelDiffRegion, PixelMetric,
+        };
+        MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.8,
+                diff_regions: vec![PixelDiffRegion {
+                    fingerprint: None,
+                    x: 0.25,
+                    y: 0.5,
+                    width: 0.1,
+                    height: 0.2,
+                    severity: DiffSeverity::Moderate,
+                    reason: PixelDiffReason::PixelChange,
+                    intensity: None,
+                    max_intensity: None,
+                    pixel_bounds: None,
+                    css_bounds: None,
+                }],
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: Some(LayoutMetric {
+                score: 0.9,
+                diff_regions: vec![LayoutDiffRegion {
+                    fingerprint: None,
+                    x: 40.0,
+                    y: 80.0,
+                    width: 200.0,
+                    height: 50.0,
+                    unit: None,
+                    kind: LayoutDiffKind::PositionShift,
+                    element_type: None,
+                    label: None,
+                    detail: None,
+                    region_count: None,
+                    pixel_bounds: None,
+                    css_bounds: None,
+                }],
+            }),
+            typography: None,
+            color: None,
+            content: None,
+            semantic: None,
+        }
+    }
+
+    #[test]
+    fn normalized_coordinate_space_leaves_regions_untouched() {
+        let mut scores = region_scores();
+        let frame = CoordinateFrame::for_screenshot(2000, 1000, &Viewport::new(1000, 500));
+        apply_coordinate_space(&mut scores, CoordinateSpace::Normalized, &frame);
+
+        let json = serde_json::to_string(&scores).expect("serialize scores");
+        assert!(!json.contains("pixelBounds"));
+        assert!(!json.contains("cssBounds"));
+    }
+
+    #[test]
+    fn all_coordinate_space_reports_pixel_and_css_bounds() {
+        let mut scores = region_scores();
+        let frame = CoordinateFrame::for_screenshot(2000, 1000, &Viewport::new(1000, 500));
+        assert!((frame.device_pixel_ratio - 2.0).abs() < f32::EPSILON);
+        apply_coordinate_space(&mut scores, CoordinateSpace::All, &frame);
+
+        let region = &scores.pixel.as_ref().unwrap().diff_regions[0];
+        let px = region.pixel_bounds.expect("pixel bounds");
+        assert_eq!(
+            (px.x, px.y, px.width, px.height),
+            (500.0, 500.0, 200.0, 200.0)
+        );
+        let css = region.css_bounds.expect("css bounds");
+        assert_eq!(
+            (css.x, css.y, css.width, css.height),
+            (250.0, 250.0, 100.0, 100.0)
+        );
+
+        let layout = &scores.layout.as_ref().unwrap().diff_regions[0];
+        let px = layout.pixel_bounds.expect("layout pixel bounds");
+        assert_eq!((px.x, px.width), (40.0, 200.0));
+        assert_eq!(layout.css_bounds.unwrap().width, 100.0);
+    }
+
+    #[test]
+    fn dom_layout_regions_convert_from_css_pixels() {
+        let mut scores = region_scores();
+        let layout = &mut scores.layout.as_mut().unwrap().diff_regions[0];
+        layout.unit = Some(RegionUnit::Css);
+        let frame = CoordinateFrame::for_screenshot(2000, 1000, &Viewport::new(1000, 500));
+        apply_coordinate_space(&mut scores, CoordinateSpace::All, &frame);
+
+        let layout = &scores.layout.as_ref().unwrap().diff_regions[0];
+        let css = layout.css_bounds.expect("layout css bounds");
+        assert_eq!(
+            (css.x, css.y, css.width, css.height),
+            (40.0, 80.0, 200.0, 50.0)
+        );
+        let px = layout.pixel_bounds.expect("layout pixel bounds");
+        assert_eq!(
+            (px.x, px.y, px.width, px.height),
+            (80.0, 160.0, 400.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn pixel_coordinate_space_omits_css_bounds() {
+        let mut scores = region_scores();
+        let frame = CoordinateFrame::for_screenshot(1000, 500, &Viewport::new(1000, 500));
+        apply_coordinate_space(&mut scores, CoordinateSpace::Pixel, &frame);
+
+        let region = &scores.pixel.as_ref().unwrap().diff_regions[0];
+        assert!(region.pixel_bounds.is_some());
+        assert!(region.css_bounds.is_none());
+    }
 }
@@ -0,0 +1,495 @@
+//! `overlay.json`: the findings of a compare run as rectangles to draw over
+//! the screenshots.
+//!
+//! Review front-ends load the overlay instead of re-deriving boxes from the
+//! result: pixel and layout diff regions, plus the boxes of the elements a
+//! typography or color finding is about. The file is GeoJSON-like (a
+//! `FeatureCollection` of rectangle `Polygon`s with a `bbox`), but
+//! coordinates are normalized (0.0 - 1.0) to the screenshot named by each
+//! feature's `image`, with `y` pointing down. `z` is a draw order: larger
+//! rectangles come first so nested ones stay clickable. `finding` is the
+//! finding's fingerprint, as listed in `index.json` and `.dpc-ignore.json`.
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::artifact_index::relative_path;
+use crate::error::Result;
+use crate::metrics::{MetricKind, SeverityCalibration};
+use crate::output::{CompareArtifacts, DPC_OUTPUT_VERSION};
+use crate::types::{BoundingBox, DiffSeverity, LayoutDiffKind, MetricScores, NormalizedView};
+
+/// File name of the overlay inside the artifacts directory.
+pub const OVERLAY_FILE: &str = "overlay.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Overlay {
+    pub version: String,
+    /// Always `FeatureCollection`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub ref_image: OverlayImage,
+    pub impl_image: OverlayImage,
+    pub features: Vec<OverlayFeature>,
+}
+
+/// A screenshot the features are drawn on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayImage {
+    /// Path relative to the artifacts directory, as in `index.json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayFeature {
+    /// Always `Feature`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Unique within the overlay, e.g. `layout-2` or `typography-0-impl`.
+    pub id: String,
+    /// `[left, top, right, bottom]`, normalized.
+    pub bbox: [f32; 4],
+    pub geometry: OverlayGeometry,
+    pub properties: OverlayProperties,
+}
+
+/// A closed rectangle ring, GeoJSON `Polygon` style.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayGeometry {
+    /// Always `Polygon`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub coordinates: Vec<Vec<[f32; 2]>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayProperties {
+    pub metric: MetricKind,
+    /// Pixel diff reason, layout diff kind, typography issues (comma
+    /// separated) or color diff kind.
+    pub kind: String,
+    pub image: OverlaySide,
+    pub severity: DiffSeverity,
+    /// Draw order, starting at 0.
+    pub z: usize,
+    /// Fingerprint of the finding this rectangle shows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finding: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Average difference of a pixel region (0.0 - 1.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f32>,
+}
+
+/// Screenshot a feature belongs to; pixel regions apply to both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlaySide {
+    Ref,
+    Impl,
+    Both,
+}
+
+/// Build the overlay of the findings in `scores`, rated with `calibration`.
+/// Element boxes are looked up in the DOM or Figma tree of the two views.
+pub fn build_overlay(
+    artifacts: &CompareArtifacts,
+    ref_view: &NormalizedView,
+    impl_view: &NormalizedView,
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+) -> Overlay {
+    let mut features = Vec::new();
+
+    for (i, region) in scores
+        .pixel
+        .iter()
+        .flat_map(|m| &m.diff_regions)
+        .enumerate()
+    {
+        let bbox = BoundingBox {
+            x: region.x,
+            y: region.y,
+            width: region.width,
+            height: region.height,
+        };
+        features.push(feature(
+            format!("pixel-{i}"),
+            bbox,
+            OverlayProperties {
+                metric: MetricKind::Pixel,
+                kind: name_of(&region.reason),
+                image: OverlaySide::Both,
+                severity: region.severity,
+                z: 0,
+                finding: region.fingerprint.clone(),
+                label: None,
+                intensity: region.intensity,
+            },
+        ));
+    }
+
+    for (i, region) in scores
+        .layout
+        .iter()
+        .flat_map(|m| &m.diff_regions)
+        .enumerate()
+    {
+        let bbox = BoundingBox {
+            x: region.x,
+            y: region.y,
+            width: region.width,
+            height: region.height,
+        };
+        // Regions take the reference element's box, except for elements
+        // that only exist in the implementation.
+        let image = match region.kind {
+            LayoutDiffKind::ExtraElement => OverlaySide::Impl,
+            _ => OverlaySide::Ref,
+        };
+        let view = if image == OverlaySide::Impl {
+            impl_view
+        } else {
+            ref_view
+        };
+        features.push(feature(
+            format!("layout-{i}"),
+            normalized(bbox, view),
+            OverlayProperties {
+                metric: MetricKind::Layout,
+                kind: name_of(&region.kind),
+                image,
+                severity: calibration.layout(region.kind),
+                z: 0,
+                finding: region.fingerprint.clone(),
+                label: region.label.clone(),
+                intensity: None,
+            },
+        ));
+    }
+
+    for (i, diff) in scores.typography.iter().flat_map(|m| &m.diffs).enumerate() {
+        let kind = diff
+            .issues
+            .iter()
+            .map(name_of)
+            .collect::<Vec<_>>()
+            .join(",");
+        let sides = [
+            (diff.element_id_ref.as_deref(), ref_view, OverlaySide::Ref),
+            (
+                diff.element_id_impl.as_deref(),
+                impl_view,
+                OverlaySide::Impl,
+            ),
+        ];
+        for (id, view, image) in sides {
+            let Some((bbox, label)) = id.and_then(|id| element(view, id)) else {
+                continue;
+            };
+            features.push(feature(
+                format!("typography-{i}-{}", name_of(&image)),
+                normalized(bbox, view),
+                OverlayProperties {
+                    metric: MetricKind::Typography,
+                    kind: kind.clone(),
+                    image,
+                    severity: calibration.typography(&diff.issues),
+                    z: 0,
+                    finding: diff.fingerprint.clone(),
+                    label: diff.label.clone().or(label),
+                    intensity: None,
+                },
+            ));
+        }
+    }
+
+    for (i, diff) in scores.color.iter().flat_map(|m| &m.diffs).enumerate() {
+        let Some((bbox, label)) = diff
+            .element_id
+            .as_deref()
+            .and_then(|id| element(ref_view, id))
+        else {
+            continue;
+        };
+        features.push(feature(
+            format!("color-{i}"),
+            normalized(bbox, ref_view),
+            OverlayProperties {
+                metric: MetricKind::Color,
+                kind: name_of(&diff.kind),
+                image: OverlaySide::Ref,
+                severity: calibration.color(diff),
+                z: 0,
+                finding: diff.fingerprint.clone(),
+                label,
+                intensity: None,
+            },
+        ));
+    }
+
+    let area = |f: &OverlayFeature| (f.bbox[2] - f.bbox[0]) * (f.bbox[3] - f.bbox[1]);
+    features.sort_by(|a, b| area(b).total_cmp(&area(a)));
+    for (z, feature) in features.iter_mut().enumerate() {
+        feature.properties.z = z;
+    }
+
+    let image = |path: Option<&std::path::PathBuf>, view: &NormalizedView| OverlayImage {
+        path: path.map(|path| relative_path(&artifacts.directory, path)),
+        width: view.width,
+        height: view.height,
+    };
+    Overlay {
+        version: DPC_OUTPUT_VERSION.to_string(),
+        kind: "FeatureCollection".to_string(),
+        ref_image: image(artifacts.ref_screenshot.as_ref(), ref_view),
+        impl_image: image(artifacts.impl_screenshot.as_ref(), impl_view),
+        features,
+    }
+}
+
+/// Write [`OVERLAY_FILE`] into a kept artifacts directory and record it in
+/// `artifacts`. Does nothing when the artifacts are not kept.
+pub fn write_overlay(
+    artifacts: &mut CompareArtifacts,
+    ref_view: &NormalizedView,
+    impl_view: &NormalizedView,
+    scores: &MetricScores,
+    calibration: &SeverityCalibration,
+) -> Result<()> {
+    if !artifacts.kept {
+        return Ok(());
+    }
+    let overlay = build_overlay(artifacts, ref_view, impl_view, scores, calibration);
+    let path = artifacts.directory.join(OVERLAY_FILE);
+    fs::write(&path, serde_json::to_vec_pretty(&overlay)?)?;
+    artifacts.overlay = Some(path);
+    Ok(())
+}
+
+fn feature(id: String, bbox: BoundingBox, properties: OverlayProperties) -> OverlayFeature {
+    let (left, top) = (bbox.x, bbox.y);
+    let (right, bottom) = (bbox.x + bbox.width, bbox.y + bbox.height);
+    OverlayFeature {
+        kind: "Feature".to_string(),
+        id,
+        bbox: [left, top, right, bottom],
+        geometry: OverlayGeometry {
+            kind: "Polygon".to_string(),
+            coordinates: vec![vec![
+                [left, top],
+                [right, top],
+                [right, bottom],
+                [left, bottom],
+                [left, top],
+            ]],
+        },
+        properties,
+    }
+}
+
+/// Box and name of the DOM or Figma element `id` of `view`.
+fn element(view: &NormalizedView, id: &str) -> Option<(BoundingBox, Option<String>)> {
+    let dom = view.dom.iter().flat_map(|dom| &dom.nodes);
+    let figma = view.figma_tree.iter().flat_map(|tree| &tree.nodes);
+    dom.map(|node| (&node.id, node.bounding_box, node.label()))
+        .chain(figma.map(|node| (&node.id, node.bounding_box, node.label())))
+        .find(|(node_id, _, _)| node_id.as_str() == id)
+        .map(|(_, bbox, label)| (bbox, label.map(str::to_owned)))
+}
+
+/// `bbox` in normalized coordinates; boxes outside the unit square are taken
+/// to be screenshot pixels of `view`, as for `pixelBounds`.
+fn normalized(bbox: BoundingBox, view: &NormalizedView) -> BoundingBox {
+    let is_normalized = bbox.x >= 0.0
+        && bbox.y >= 0.0
+        && bbox.x + bbox.width <= 1.0 + f32::EPSILON
+        && bbox.y + bbox.height <= 1.0 + f32::EPSILON;
+    if is_normalized || view.width == 0 || view.height == 0 {
+        return bbox;
+    }
+    let (w, h) = (view.width as f32, view.height as f32);
+    BoundingBox {
+        x: bbox.x / w,
+        y: bbox.y / h,
+        width: bbox.width / w,
+        height: bbox.height / h,
+    }
+}
+
+/// The serialized name of a unit enum variant (`missing_element`, ...).
+fn name_of<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        DomNode, DomSnapshot, LayoutDiffRegion, LayoutMetric, PixelDiffReason, PixelDiffRegion,
+        PixelMetric, ResourceKind, TypographyDiff, TypographyIssue, TypographyMetric,
+    };
+
+    #[test]
+    fn overlay_draws_regions_and_finding_elements_smallest_last() {
+        let dir = tempfile::tempdir().unwrap();
+        let view = |nodes: Vec<DomNode>| NormalizedView {
+            kind: ResourceKind::Url,
+            screenshot_path: dir.path().join("ref.png"),
+            width: 200,
+            height: 100,
+            dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
+                url: None,
+                title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
+                nodes,
+            }),
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        };
+        let node = |id: &str, x: f32| DomNode {
+            id: id.to_string(),
+            tag: "h1".to_string(),
+            children: Vec::new(),
+            parent: None,
+            attributes: [("data-testid".to_string(), "title".to_string())].into(),
+            text: Some("Title".to_string()),
+            bounding_box: BoundingBox {
+                x,
+                y: 10.0,
+                width: 50.0,
+                height: 10.0,
+            },
+            text_baseline: None,
+            interaction_states: None,
+            computed_style: None,
+        };
+        let ref_view = view(vec![node("r1", 50.0)]);
+        let impl_view = view(vec![node("i1", 60.0)]);
+        let scores = MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.8,
+                diff_regions: vec![PixelDiffRegion {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 0.5,
+                    height: 0.5,
+                    severity: DiffSeverity::Moderate,
+                    reason: PixelDiffReason::PixelChange,
+                    intensity: Some(0.3),
+                    max_intensity: None,
+                    pixel_bounds: None,
+                    css_bounds: None,
+                    fingerprint: Some("px".to_string()),
+                }],
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: Some(LayoutMetric {
+                score: 0.9,
+                diff_regions: vec![LayoutDiffRegion {
+                    x: 100.0,
+                    y: 50.0,
+                    width: 20.0,
+                    height: 10.0,
+                    unit: None,
+                    kind: LayoutDiffKind::ExtraElement,
+                    element_type: Some("button".to_string()),
+                    label: Some("cta".to_string()),
+                    detail: None,
+                    region_count: None,
+                    pixel_bounds: None,
+                    css_bounds: None,
+                    fingerprint: Some("lay".to_string()),
+                }],
+            }),
+            typography: Some(TypographyMetric {
+                score: 0.9,
+                diffs: vec![TypographyDiff {
+                    element_id_ref: Some("r1".to_string()),
+                    element_id_impl: Some("i1".to_string()),
+                    label: None,
+                    style: None,
+                    issues: vec![TypographyIssue::FontSizeDiff],
+                    details: None,
+                    fingerprint: Some("typo".to_string()),
+                }],
+            }),
+            color: None,
+            content: None,
+            semantic: None,
+        };
+        let artifacts = CompareArtifacts {
+            directory: dir.path().to_path_buf(),
+            kept: true,
+            ref_screenshot: Some(dir.path().join("ref.png")),
+            impl_screenshot: None,
+            diff_image: None,
+            color_drift_image: None,
+            ref_thumbnail: None,
+            impl_thumbnail: None,
+            diff_thumbnail: None,
+            ref_dom_snapshot: None,
+            impl_dom_snapshot: None,
+            ref_figma_snapshot: None,
+            impl_figma_snapshot: None,
+            typography_report: None,
+            typography_report_csv: None,
+            typography_specimen: None,
+            run_manifest: None,
+            ref_network_har: None,
+            impl_network_har: None,
+            overlay: None,
+            artifact_index: None,
+        };
+
+        let overlay = build_overlay(
+            &artifacts,
+            &ref_view,
+            &impl_view,
+            &scores,
+            &SeverityCalibration::default(),
+        );
+
+        assert_eq!(overlay.kind, "FeatureCollection");
+        assert_eq!(overlay.ref_image.path.as_deref(), Some("ref.png"));
+        let ids: Vec<&str> = overlay.features.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "pixel-0",
+                "typography-0-ref",
+                "typography-0-impl",
+                "layout-0"
+            ]
+        );
+        let layout = &overlay.features[3];
+        assert_eq!(layout.bbox, [0.5, 0.5, 0.6, 0.6]);
+        assert_eq!(layout.properties.image, OverlaySide::Impl);
+        assert_eq!(layout.properties.kind, "extra_element");
+        assert_eq!(layout.properties.z, 3);
+        let typography = &overlay.features[2];
+        assert_eq!(typography.bbox, [0.3, 0.1, 0.55, 0.2]);
+        assert_eq!(typography.properties.finding.as_deref(), Some("typo"));
+        assert_eq!(typography.properties.label.as_deref(), Some("title"));
+        assert_eq!(typography.properties.kind, "font_size_diff");
+        assert_eq!(typography.geometry.coordinates[0].len(), 5);
+    }
+}
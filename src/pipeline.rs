@@ -6,12 +6,27 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use image::{imageops::FilterType, DynamicImage, GenericImageView, RgbaImage};
 use serde::{Deserialize, Serialize};
 
+use dpc_lib::dom_selector::{split_selector_list, DomIndex, SelectorList};
+use dpc_lib::figma::{
+    figma_json_to_normalized_view, include_hidden_from_env, resolve_frame_node_id,
+    FigmaOfflineOptions,
+};
 use dpc_lib::image_alignment::{align_implementation, ImageAlignmentOptions};
-use dpc_lib::types::{DomNode, MetricScores, ResourceKind, Viewport};
+use dpc_lib::image_loader::{
+    decode_data_uri, letterbox_content_area, read_image_stdin, resize_with_letterbox, ImageLimits,
+};
+use dpc_lib::types::{dom_snapshot_from_value, BoundingBox, MetricScores, ResourceKind, Viewport};
+use dpc_lib::video::video_frame_to_normalized_view;
 use dpc_lib::{
-    figma_to_normalized_view, generate_top_issues, image_to_normalized_view,
-    url_to_normalized_view, CompareArtifacts, DpcError, FigmaAuth, FigmaClient, FigmaRenderOptions,
-    ImageLoadOptions, NormalizedView, ParsedResource, Summary, UrlToViewOptions,
+    bytes_to_normalized_view, device_to_normalized_view, element_color_drift,
+    figma_to_normalized_view, generate_top_issues_with, image_to_normalized_view,
+    read_json_snapshot, remote_image_to_normalized_view, url_to_normalized_view,
+    write_json_snapshot, ArtifactStore, BrowserEngine, CaptureBackend, CompareArtifacts,
+    ComparePageDiagnostics, DeviceCaptureOptions, DeviceTarget, DimensionMismatch,
+    DimensionMismatchPolicy, DpcError, ElementColorDrift, Emulation, FigmaAuth, FigmaClient,
+    FigmaRenderOptions, ImageLoadOptions, ImageSize, NormalizedView, ParsedResource, RequestRules,
+    SeverityCalibration, SnapshotCompression, Summary, TypeSpecimenRow, TypographyReportRow,
+    TypographySimilarity, UrlToViewOptions,
 };
 
 /// Convert a parsed resource to a NormalizedView.
@@ -25,8 +40,17 @@ pub async fn resource_to_normalized_view(
     nav_timeout: u64,
     network_idle_timeout: u64,
     process_timeout: u64,
+    browser: BrowserEngine,
+    record_network: bool,
+    request_rules: &RequestRules,
+    init_scripts: &[PathBuf],
+    emulation: &Emulation,
+    capture_states: bool,
 ) -> Result<NormalizedView, Box<dyn std::error::Error + Send + Sync>> {
-    if matches!(resource.kind, ResourceKind::Url | ResourceKind::Figma) {
+    if matches!(
+        resource.kind,
+        ResourceKind::Url | ResourceKind::Figma | ResourceKind::Device | ResourceKind::Window
+    ) {
         if let Some(mock_path) = mock_render_image_path(prefix) {
             let screenshot_path = artifacts_dir.join(format!("{}_screenshot.png", prefix));
             let options = ImageLoadOptions {
@@ -34,12 +58,8 @@ pub async fn resource_to_normalized_view(
                 target_width: Some(viewport.width),
                 target_height: Some(viewport.height),
             };
-            let view = image_to_normalized_view(
-                mock_path.as_str(),
-                screenshot_path.to_string_lossy().as_ref(),
-                options,
-            )
-            .map_err(|e| format!("Mock rendering failed: {}", e))?;
+            let view = image_to_normalized_view(&mock_path, &screenshot_path, options)
+                .map_err(|e| format!("Mock rendering failed: {}", e))?;
             return Ok(view);
         }
     }
@@ -52,12 +72,83 @@ pub async fn resource_to_normalized_view(
                 target_width: Some(viewport.width),
                 target_height: Some(viewport.height),
             };
-            let view = image_to_normalized_view(
-                resource.value.as_str(),
-                &screenshot_path.to_string_lossy(),
+            if resource.is_remote_image() {
+                let view = remote_image_to_normalized_view(
+                    resource.value.as_str(),
+                    &screenshot_path,
+                    options,
+                )
+                .await
+                .map_err(|e| format!("Image download failed: {}", e))?;
+                return Ok(view);
+            }
+            if let Some(frame) = resource.video_frame() {
+                let view = video_frame_to_normalized_view(&frame, &screenshot_path, options)
+                    .map_err(|e| format!("Video frame loading failed: {}", e))?;
+                return Ok(view);
+            }
+            if resource.is_stdin() || resource.is_data_uri() {
+                let bytes = if resource.is_stdin() {
+                    read_image_stdin(&ImageLimits::current())
+                } else {
+                    decode_data_uri(&resource.value)
+                };
+                let view = bytes
+                    .and_then(|bytes| {
+                        bytes_to_normalized_view(
+                            &bytes,
+                            &resource.display_value(),
+                            &screenshot_path,
+                            options,
+                        )
+                    })
+                    .map_err(|e| format!("Image loading failed: {}", e))?;
+                return Ok(view);
+            }
+            let view =
+                image_to_normalized_view(Path::new(&resource.value), &screenshot_path, options)
+                    .map_err(|e| format!("Image loading failed: {}", e))?;
+            Ok(view)
+        }
+        ResourceKind::View => {
+            let dir = resource.view_bundle_dir().unwrap_or(Path::new(""));
+            let view = NormalizedView::load(dir)
+                .map_err(|e| format!("View bundle loading failed: {}", e))?;
+            if (view.width, view.height) != (viewport.width, viewport.height) {
+                return Err(DpcError::Config(format!(
+                    "View bundle {} was captured at {}x{}; use --viewport {}x{} to compare it",
+                    dir.display(),
+                    view.width,
+                    view.height,
+                    view.width,
+                    view.height
+                ))
+                .into());
+            }
+            Ok(view)
+        }
+        ResourceKind::Device | ResourceKind::Window => {
+            let target = DeviceTarget::parse(&resource.value).ok_or_else(|| {
+                DpcError::Config(format!("Invalid device resource '{}'", resource.value))
+            })?;
+            let options = ImageLoadOptions {
+                no_resize: false,
+                target_width: Some(viewport.width),
+                target_height: Some(viewport.height),
+            };
+            let capture_options = DeviceCaptureOptions {
+                timeout: Duration::from_secs(process_timeout),
+                ..DeviceCaptureOptions::default()
+            };
+            let view = device_to_normalized_view(
+                &target,
+                &artifacts_dir.join(format!("{}_device.png", prefix)),
+                &artifacts_dir.join(format!("{}_screenshot.png", prefix)),
                 options,
+                &capture_options,
             )
-            .map_err(|e| format!("Image loading failed: {}", e))?;
+            .await
+            .map_err(|e| format!("Device capture failed: {}", e))?;
             Ok(view)
         }
         ResourceKind::Url => {
@@ -68,6 +159,15 @@ pub async fn resource_to_normalized_view(
                 navigation_timeout: Duration::from_secs(nav_timeout),
                 network_idle_timeout: Duration::from_secs(network_idle_timeout),
                 process_timeout: Duration::from_secs(process_timeout),
+                backend: CaptureBackend::from_env()?,
+                engine: browser,
+                network: dpc_lib::network::options().clone(),
+                har_path: record_network
+                    .then(|| artifacts_dir.join(format!("{}_network.har", prefix))),
+                request_rules: request_rules.clone(),
+                init_scripts: init_scripts.to_vec(),
+                emulation: emulation.clone(),
+                capture_states,
                 ..UrlToViewOptions::default()
             };
             let view = url_to_normalized_view(resource.value.as_str(), &screenshot_path, options)
@@ -80,24 +180,51 @@ pub async fn resource_to_normalized_view(
                 .figma_info
                 .as_ref()
                 .ok_or_else(|| DpcError::Config("Missing Figma file key".to_string()))?;
-            let node_id = figma_info
-                .node_id
-                .clone()
-                .ok_or_else(|| DpcError::Config("Figma node-id is required".to_string()))?;
-            let auth = FigmaAuth::from_env().ok_or_else(|| {
+            let output_path = artifacts_dir.join(format!("{}_figma.png", prefix));
+            if let Some(json_path) = &figma_info.json_path {
+                let options = FigmaOfflineOptions {
+                    json_path: json_path.clone(),
+                    image_path: offline_frame_image(json_path, figma_info.node_id.as_deref()),
+                    file_key: figma_info.file_key.clone(),
+                    node_id: figma_info.node_id.clone(),
+                    frame_name: figma_info.frame_name.clone(),
+                    output_path,
+                    viewport: Some(*viewport),
+                    include_hidden: include_hidden_from_env(),
+                };
+                let view = figma_json_to_normalized_view(&options)
+                    .map_err(|e| format!("Figma JSON conversion failed: {}", e))?;
+                return Ok(view);
+            }
+            if figma_info.node_id.is_none() && figma_info.frame_name.is_none() {
+                return Err(DpcError::Config(
+                    "Figma node-id or frame-name is required".to_string(),
+                )
+                .into());
+            }
+            let auth = FigmaAuth::resolve().await?.ok_or_else(|| {
                 DpcError::Config(
-                    "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN".to_string(),
+                    "Figma token missing; set FIGMA_TOKEN or FIGMA_OAUTH_TOKEN, or run `dpc figma login`"
+                        .to_string(),
                 )
             })?;
+            auth.ensure_fresh().await?;
             let client =
                 FigmaClient::from_auth(auth).map_err(|e| format!("Figma client error: {}", e))?;
-            let output_path = artifacts_dir.join(format!("{}_figma.png", prefix));
+            let node_id = match &figma_info.node_id {
+                Some(node_id) => node_id.clone(),
+                None => {
+                    let frame_name = figma_info.frame_name.as_deref().unwrap_or_default();
+                    resolve_frame_node_id(&client, &figma_info.file_key, frame_name).await?
+                }
+            };
             let options = FigmaRenderOptions {
                 file_key: figma_info.file_key.clone(),
                 node_id,
                 output_path,
                 viewport: Some(*viewport),
-                scale: 1.0,
+                scale: None,
+                include_hidden: include_hidden_from_env(),
             };
             let view = figma_to_normalized_view(&client, &options)
                 .await
@@ -107,23 +234,137 @@ pub async fn resource_to_normalized_view(
     }
 }
 
+/// Exported PNG of a `figma-json:` frame: `<stem>.<node-id>.png` (with `:` as
+/// `-`) when the saved file holds several exported frames, else `<stem>.png`.
+fn offline_frame_image(json_path: &Path, node_id: Option<&str>) -> PathBuf {
+    node_id
+        .map(|id| json_path.with_extension(format!("{}.png", id.replace(':', "-"))))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| json_path.with_extension("png"))
+}
+
+/// Warn when a Figma frame was letterboxed into a viewport whose aspect ratio
+/// is far from its own, which leaves a large part of the render empty.
+pub fn aspect_ratio_warning(
+    label: &str,
+    view: &NormalizedView,
+    viewport: &Viewport,
+) -> Option<String> {
+    let (width, height) = view.figma_tree.as_ref()?.frame_size?;
+    let factor = viewport.aspect_mismatch(width, height)?;
+    Some(format!(
+        "{label}: Figma frame {width}x{height} was letterboxed into the {}x{} viewport \
+         (aspect ratios differ {factor:.1}x); use --viewport {width}x{height} to match the frame",
+        viewport.width, viewport.height
+    ))
+}
+
+/// Cap a captured DOM snapshot at `max_nodes` nodes (`[dom] max_nodes`),
+/// returning a warning when nodes were dropped.
+pub fn truncate_dom(label: &str, view: &mut NormalizedView, max_nodes: usize) -> Option<String> {
+    let dom = view.dom.as_mut()?;
+    let total = dom.nodes.len();
+    let dropped = dom.truncate_nodes(max_nodes);
+    (dropped > 0).then(|| {
+        format!(
+            "{label}: DOM snapshot had {total} nodes; kept the first {max_nodes} ([dom] max_nodes)"
+        )
+    })
+}
+
+/// Page diagnostics of both sides, without the HAR paths (those are listed
+/// under `artifacts`); `None` when neither side recorded any.
+pub fn compare_page_diagnostics(
+    ref_view: &NormalizedView,
+    impl_view: &NormalizedView,
+) -> Option<ComparePageDiagnostics> {
+    let diagnostics = |view: &NormalizedView| {
+        let mut diagnostics = view.dom.as_ref()?.diagnostics.clone()?;
+        diagnostics.har = None;
+        Some(diagnostics)
+    };
+    let report = ComparePageDiagnostics {
+        reference: diagnostics(ref_view),
+        implementation: diagnostics(impl_view),
+    };
+    (report.reference.is_some() || report.implementation.is_some()).then_some(report)
+}
+
+/// One warning per side whose page logged console errors or failed requests,
+/// since broken asset loads often explain visual diffs.
+pub fn page_diagnostics_warnings(report: &ComparePageDiagnostics) -> Vec<String> {
+    [("ref", &report.reference), ("impl", &report.implementation)]
+        .into_iter()
+        .filter_map(|(label, diagnostics)| {
+            let summary = diagnostics.as_ref()?.summary()?;
+            Some(format!("{label} page: {summary}"))
+        })
+        .collect()
+}
+
 /// Check for mock render image path from environment variables.
-fn mock_render_image_path(prefix: &str) -> Option<String> {
+///
+/// Read with `var_os` so mock paths need not be valid UTF-8.
+fn mock_render_image_path(prefix: &str) -> Option<PathBuf> {
     let env_key = format!("DPC_MOCK_RENDER_{}", prefix.to_ascii_uppercase());
-    if let Ok(path) = std::env::var(&env_key) {
-        if !path.trim().is_empty() {
-            return Some(path);
+    if let Some(path) = std::env::var_os(&env_key) {
+        if !path.to_string_lossy().trim().is_empty() {
+            return Some(PathBuf::from(path));
         }
     }
 
-    if let Ok(dir) = std::env::var("DPC_MOCK_RENDERERS_DIR") {
-        let candidate = std::path::Path::new(&dir).join(format!("{prefix}.png"));
+    if let Some(dir) = std::env::var_os("DPC_MOCK_RENDERERS_DIR") {
+        let candidate = Path::new(&dir).join(format!("{prefix}.png"));
         if candidate.exists() {
-            return Some(candidate.to_string_lossy().into_owned());
+            return Some(candidate);
         }
     }
 
-    None
+    // Extra `--samples` captures (`impl_sample2`) fall back to the mock of the first.
+    prefix
+        .split_once("_sample")
+        .and_then(|(side, _)| mock_render_image_path(side))
+}
+
+/// Load a previous run's capture from its artifacts directory as a view.
+///
+/// Uses `impl_screenshot.png` (+ `impl_dom.json`, or its `.gz`/`.zst`
+/// compressed form) and falls back to the `ref_` files, so both `compare` and
+/// `self-compare` runs can serve as baselines.
+pub fn load_baseline_view(dir: &Path) -> Result<NormalizedView, DpcError> {
+    let Some(prefix) = ["impl", "ref"]
+        .into_iter()
+        .find(|prefix| dir.join(format!("{prefix}_screenshot.png")).is_file())
+    else {
+        return Err(DpcError::Config(format!(
+            "Baseline {} has no impl_screenshot.png; create one by running with --artifacts-dir",
+            dir.display()
+        )));
+    };
+
+    let screenshot_path = dir.join(format!("{prefix}_screenshot.png"));
+    let (width, height) = image::image_dimensions(&screenshot_path).map_err(DpcError::from)?;
+    let dom = [
+        SnapshotCompression::None,
+        SnapshotCompression::Gzip,
+        SnapshotCompression::Zstd,
+    ]
+    .into_iter()
+    .map(|compression| dir.join(format!("{prefix}_dom.{}", compression.extension())))
+    .find(|path| path.is_file())
+    .map(|path| read_json_snapshot(&path).and_then(dom_snapshot_from_value))
+    .transpose()?;
+
+    Ok(NormalizedView {
+        kind: ResourceKind::Image,
+        screenshot_path,
+        width,
+        height,
+        dom,
+        figma_tree: None,
+        ocr_blocks: None,
+        content_area: None,
+    })
 }
 
 /// Ignore region for masking areas in images.
@@ -151,6 +392,17 @@ pub fn resolve_artifacts_dir(custom: Option<&Path>) -> (PathBuf, bool) {
     (dir, false)
 }
 
+/// Fail before rendering when an `--init-script` file is missing.
+pub fn check_init_scripts(scripts: &[PathBuf]) -> Result<(), DpcError> {
+    match scripts.iter().find(|script| !script.is_file()) {
+        Some(missing) => Err(DpcError::Config(format!(
+            "Init script not found: {}",
+            missing.display()
+        ))),
+        None => Ok(()),
+    }
+}
+
 /// Load ignore regions from a JSON file.
 pub fn load_ignore_regions(path: &Path) -> Result<Vec<IgnoreRegion>, DpcError> {
     let data = std::fs::read_to_string(path)
@@ -234,35 +486,193 @@ pub fn apply_ignore_regions(
     Ok(updated)
 }
 
+/// Aspect ratios within this fraction of each other count as equal; resizing
+/// is then a uniform scale (e.g. a 1x capture against a 2x one).
+const ASPECT_TOLERANCE: f32 = 0.01;
+
+/// Bring screenshots whose aspect ratios differ to a common size according
+/// to `policy`, writing adjusted images as `{ref_prefix}_fitted.png` and
+/// `{impl_prefix}_fitted.png`.
+/// The views come back unchanged, without a report, when the aspect ratios
+/// match.
+pub fn reconcile_dimensions(
+    reference: &NormalizedView,
+    implementation: &NormalizedView,
+    policy: DimensionMismatchPolicy,
+    artifacts_dir: &Path,
+    ref_prefix: &str,
+    impl_prefix: &str,
+) -> Result<(NormalizedView, NormalizedView, Option<DimensionMismatch>), DpcError> {
+    let size = |view: &NormalizedView| {
+        image::image_dimensions(&view.screenshot_path)
+            .map(|(width, height)| ImageSize { width, height })
+            .map_err(DpcError::from)
+    };
+    let (ref_size, impl_size) = (size(reference)?, size(implementation)?);
+    let aspect = |s: ImageSize| s.width as f32 / s.height.max(1) as f32;
+    let ratio = aspect(ref_size) / aspect(impl_size);
+    if ref_size == impl_size || (ratio.max(1.0 / ratio) - 1.0) <= ASPECT_TOLERANCE {
+        return Ok((reference.clone(), implementation.clone(), None));
+    }
+
+    let mut report = DimensionMismatch {
+        policy,
+        reference: ref_size,
+        implementation: impl_size,
+        compared: ref_size,
+    };
+    let (mut ref_view, mut impl_view) = (reference.clone(), implementation.clone());
+    match policy {
+        DimensionMismatchPolicy::Stretch => {}
+        DimensionMismatchPolicy::Fail => {
+            return Err(DpcError::Config(format!(
+                "--dimension-mismatch fail: the reference screenshot is {}x{} but the implementation is {}x{} \
+                 (aspect ratios differ {:.2}x); resizing would distort it. Match the capture sizes, \
+                 or pass --dimension-mismatch letterbox|crop|stretch",
+                ref_size.width,
+                ref_size.height,
+                impl_size.width,
+                impl_size.height,
+                ratio.max(1.0 / ratio)
+            )))
+        }
+        DimensionMismatchPolicy::Letterbox => {
+            let img = image::open(&implementation.screenshot_path).map_err(DpcError::from)?;
+            let fitted = resize_with_letterbox(&img, ref_size.width, ref_size.height);
+            let placed = letterbox_content_area(
+                impl_size.width,
+                impl_size.height,
+                ref_size.width,
+                ref_size.height,
+            )
+            .map_or((0, 0, ref_size.width, ref_size.height), |area| {
+                (
+                    area.x as u32,
+                    area.y as u32,
+                    area.width as u32,
+                    area.height as u32,
+                )
+            });
+            // Keep an existing letterbox of the implementation, moved along.
+            let content = implementation
+                .content_rect(impl_size.width, impl_size.height)
+                .map_or(placed, |(x, y, w, h)| {
+                    let sx = placed.2 as f32 / impl_size.width as f32;
+                    let sy = placed.3 as f32 / impl_size.height as f32;
+                    (
+                        placed.0 + (x as f32 * sx).round() as u32,
+                        placed.1 + (y as f32 * sy).round() as u32,
+                        (w as f32 * sx).round() as u32,
+                        (h as f32 * sy).round() as u32,
+                    )
+                });
+            impl_view.width = reference.width;
+            impl_view.height = reference.height;
+            save_fitted(&mut impl_view, &fitted, artifacts_dir, impl_prefix)?;
+            set_content_rect(&mut impl_view, content, ref_size);
+        }
+        DimensionMismatchPolicy::Crop => {
+            let compared = ImageSize {
+                width: ref_size.width.min(impl_size.width),
+                height: ref_size.height.min(impl_size.height),
+            };
+            for (view, original, prefix) in [
+                (&mut ref_view, ref_size, ref_prefix),
+                (&mut impl_view, impl_size, impl_prefix),
+            ] {
+                if original == compared {
+                    continue;
+                }
+                let img = image::open(&view.screenshot_path).map_err(DpcError::from)?;
+                let cropped = img.crop_imm(0, 0, compared.width, compared.height);
+                let content = view.content_rect(original.width, original.height);
+                view.width = scale_dim(view.width, compared.width, original.width);
+                view.height = scale_dim(view.height, compared.height, original.height);
+                save_fitted(view, &cropped, artifacts_dir, prefix)?;
+                view.content_area = None;
+                if let Some((x, y, w, h)) = content {
+                    let (x, y) = (x.min(compared.width), y.min(compared.height));
+                    let w = w.min(compared.width - x);
+                    let h = h.min(compared.height - y);
+                    set_content_rect(view, (x, y, w, h), compared);
+                }
+            }
+            report.compared = compared;
+        }
+    }
+    Ok((ref_view, impl_view, Some(report)))
+}
+
+fn scale_dim(value: u32, new_pixels: u32, old_pixels: u32) -> u32 {
+    ((value as u64 * new_pixels as u64) / old_pixels.max(1) as u64).max(1) as u32
+}
+
+fn save_fitted(
+    view: &mut NormalizedView,
+    image: &DynamicImage,
+    artifacts_dir: &Path,
+    prefix: &str,
+) -> Result<(), DpcError> {
+    let path = artifacts_dir.join(format!("{prefix}_fitted.png"));
+    image
+        .save(&path)
+        .map_err(|e| DpcError::Config(format!("Failed to save fitted screenshot: {e}")))?;
+    view.screenshot_path = path;
+    Ok(())
+}
+
+/// Record `rect` (pixels of an image of `size`) as the view's content area,
+/// which is kept in view units.
+fn set_content_rect(view: &mut NormalizedView, rect: (u32, u32, u32, u32), size: ImageSize) {
+    let sx = view.width as f32 / size.width.max(1) as f32;
+    let sy = view.height as f32 / size.height.max(1) as f32;
+    let (x, y, w, h) = rect;
+    view.content_area = Some(BoundingBox {
+        x: x as f32 * sx,
+        y: y as f32 * sy,
+        width: w as f32 * sx,
+        height: h as f32 * sy,
+    });
+}
+
 /// Parse ignore selectors from comma-separated string.
 pub fn parse_ignore_selectors(raw: Option<&str>) -> Vec<String> {
     raw.map(|s| {
-        s.split(',')
-            .filter_map(|part| {
-                let trimmed = part.trim().to_ascii_lowercase();
-                if trimmed.is_empty() {
-                    None
-                } else {
-                    Some(trimmed)
-                }
-            })
+        split_selector_list(s)
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_ascii_lowercase())
             .collect()
     })
     .unwrap_or_default()
 }
 
+/// Reject ignore selectors the selector engine cannot parse.
+pub fn validate_ignore_selectors(selectors: &[String]) -> Result<(), DpcError> {
+    selectors
+        .iter()
+        .try_for_each(|s| SelectorList::parse(s).map(drop))
+}
+
 /// Apply DOM ignores by filtering nodes matching selectors.
 pub fn apply_dom_ignores(view: &NormalizedView, selectors: &[String]) -> NormalizedView {
     if selectors.is_empty() {
         return view.clone();
     }
 
+    // Selectors are validated by `validate_ignore_selectors`; anything that
+    // still fails to parse matches nothing.
+    let compiled: Vec<SelectorList> = selectors
+        .iter()
+        .filter_map(|s| SelectorList::parse(s).ok())
+        .collect();
     let mut filtered = view.clone();
     if let Some(dom) = &view.dom {
+        let index = DomIndex::new(&dom.nodes);
         let nodes = dom
             .nodes
             .iter()
-            .filter(|n| !matches_any_selector(n, selectors))
+            .filter(|n| !compiled.iter().any(|sel| sel.matches(n, &index)))
             .cloned()
             .collect();
         let mut dom_filtered = dom.clone();
@@ -272,43 +682,12 @@ pub fn apply_dom_ignores(view: &NormalizedView, selectors: &[String]) -> Normali
     filtered
 }
 
-fn matches_any_selector(node: &DomNode, selectors: &[String]) -> bool {
-    selectors.iter().any(|sel| selector_matches(node, sel))
-}
-
-fn selector_matches(node: &DomNode, selector: &str) -> bool {
-    if let Some(id) = selector.strip_prefix('#') {
-        let id = id.to_ascii_lowercase();
-        let attr_id = node
-            .attributes
-            .get("id")
-            .map(|v| v.to_ascii_lowercase())
-            .unwrap_or_default();
-        let node_id = node.id.to_ascii_lowercase();
-        return attr_id == id || node_id == id;
-    }
-
-    if let Some(class) = selector.strip_prefix('.') {
-        let class = class.to_ascii_lowercase();
-        if let Some(attr) = node.attributes.get("class") {
-            let has = attr
-                .split_whitespace()
-                .any(|c| c.eq_ignore_ascii_case(&class));
-            if has {
-                return true;
-            }
-        }
-        return false;
-    }
-
-    node.tag.eq_ignore_ascii_case(selector)
-}
-
 /// Generate diff heatmap image from two screenshots.
 pub fn generate_diff_heatmap(
     ref_path: &Path,
     impl_path: &Path,
     output_path: &Path,
+    palette: HeatmapPalette,
 ) -> Result<(), DpcError> {
     let ref_img = image::open(ref_path).map_err(DpcError::from)?;
     let mut impl_img = image::open(impl_path).map_err(DpcError::from)?;
@@ -319,13 +698,21 @@ pub fn generate_diff_heatmap(
         impl_img = impl_img.resize_exact(ref_w, ref_h, FilterType::Lanczos3);
     }
 
-    generate_diff_heatmap_from_images(&ref_img, &impl_img, output_path)
+    generate_diff_heatmap_from_images(&ref_img, &impl_img, output_path, palette)
 }
 
+/// Fraction of the reference brightness kept underneath the heat overlay.
+const HEATMAP_BACKDROP_DIM: f32 = 0.35;
+/// Maximum opacity of the heat overlay for fully changed pixels.
+const HEATMAP_MAX_ALPHA: f32 = 0.85;
+
+/// Render the heatmap as an opaque image: the diff ratio is measured in linear
+/// light and the heat color is blended over a dimmed, desaturated reference.
 fn generate_diff_heatmap_from_images(
     ref_img: &DynamicImage,
     impl_img: &DynamicImage,
     output_path: &Path,
+    palette: HeatmapPalette,
 ) -> Result<(), DpcError> {
     let (ref_w, ref_h) = ref_img.dimensions();
     let ref_rgba = ref_img.to_rgba8();
@@ -334,25 +721,25 @@ fn generate_diff_heatmap_from_images(
 
     for y in 0..ref_h {
         for x in 0..ref_w {
-            let p_ref = ref_rgba.get_pixel(x, y);
-            let p_impl = impl_rgba.get_pixel(x, y);
-            let diff = (p_ref[0] as i16 - p_impl[0] as i16).abs()
-                + (p_ref[1] as i16 - p_impl[1] as i16).abs()
-                + (p_ref[2] as i16 - p_impl[2] as i16).abs();
-            let ratio = (diff as f32 / 765.0).clamp(0.0, 1.0);
-            let alpha = (ratio * 200.0).clamp(0.0, 200.0) as u8;
-
-            // Color coding: green (minor), yellow (moderate), red (major)
-            let pixel = if ratio < 0.33 {
-                let g = (100.0 + ratio / 0.33 * 100.0).clamp(0.0, 200.0) as u8;
-                image::Rgba([0, g, 0, alpha])
-            } else if ratio < 0.66 {
-                let g = 180u8;
-                let r = (150.0 + (ratio - 0.33) / 0.33 * 80.0).clamp(150.0, 230.0) as u8;
-                image::Rgba([r, g, 0, alpha])
+            let p_ref = linear_over_white(ref_rgba.get_pixel(x, y));
+            let p_impl = linear_over_white(impl_rgba.get_pixel(x, y));
+            let ratio = ((p_ref[0] - p_impl[0]).abs()
+                + (p_ref[1] - p_impl[1]).abs()
+                + (p_ref[2] - p_impl[2]).abs())
+                / 3.0;
+            let ratio = ratio.clamp(0.0, 1.0);
+
+            let luminance = 0.2126 * p_ref[0] + 0.7152 * p_ref[1] + 0.0722 * p_ref[2];
+            let backdrop = luminance * HEATMAP_BACKDROP_DIM;
+
+            let pixel = if ratio <= f32::EPSILON {
+                let v = linear_to_srgb(backdrop);
+                image::Rgba([v, v, v, 255])
             } else {
-                let r = (200.0 + (ratio - 0.66) / 0.34 * 55.0).clamp(200.0, 255.0) as u8;
-                image::Rgba([r, 0, 0, alpha])
+                let color = palette.sample(ratio);
+                let alpha = ratio.sqrt() * HEATMAP_MAX_ALPHA;
+                let blend = |c: f32| linear_to_srgb(c * alpha + backdrop * (1.0 - alpha));
+                image::Rgba([blend(color[0]), blend(color[1]), blend(color[2]), 255])
             };
             heat.put_pixel(x, y, pixel);
         }
@@ -364,6 +751,155 @@ fn generate_diff_heatmap_from_images(
     Ok(())
 }
 
+/// Delta E at which an element gets the hottest color of the drift heatmap.
+const COLOR_DRIFT_FULL_SCALE: f32 = 20.0;
+/// Overlay opacity of elements whose colors match the reference.
+const COLOR_DRIFT_MIN_ALPHA: f32 = 0.3;
+
+/// Tint every matched element of the implementation screenshot by the Delta E
+/// of its colors against the reference, over a dimmed copy of the
+/// screenshot. Smaller elements are drawn last so nested ones stay visible.
+pub fn generate_color_drift_heatmap(
+    impl_view: &NormalizedView,
+    drifts: &[ElementColorDrift],
+    output_path: &Path,
+    palette: HeatmapPalette,
+) -> Result<(), DpcError> {
+    let impl_img = image::open(&impl_view.screenshot_path).map_err(DpcError::from)?;
+    let (img_w, img_h) = impl_img.dimensions();
+    let rgba = impl_img.to_rgba8();
+    let mut backdrop = vec![0.0f32; (img_w * img_h) as usize];
+    let mut heat = RgbaImage::new(img_w, img_h);
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let p = linear_over_white(pixel);
+        let dimmed = (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]) * HEATMAP_BACKDROP_DIM;
+        backdrop[(y * img_w + x) as usize] = dimmed;
+        let v = linear_to_srgb(dimmed);
+        heat.put_pixel(x, y, image::Rgba([v, v, v, 255]));
+    }
+
+    let scale_x = img_w as f32 / impl_view.width.max(1) as f32;
+    let scale_y = img_h as f32 / impl_view.height.max(1) as f32;
+    let mut ordered: Vec<&ElementColorDrift> = drifts.iter().collect();
+    ordered
+        .sort_by(|a, b| (b.bbox.width * b.bbox.height).total_cmp(&(a.bbox.width * a.bbox.height)));
+    for drift in ordered {
+        let ratio = (drift.delta_e() / COLOR_DRIFT_FULL_SCALE).clamp(0.0, 1.0);
+        let color = palette.sample(ratio);
+        let alpha = COLOR_DRIFT_MIN_ALPHA + (HEATMAP_MAX_ALPHA - COLOR_DRIFT_MIN_ALPHA) * ratio;
+        let x0 = (drift.bbox.x * scale_x).max(0.0) as u32;
+        let y0 = (drift.bbox.y * scale_y).max(0.0) as u32;
+        let x1 = (((drift.bbox.x + drift.bbox.width) * scale_x)
+            .ceil()
+            .max(0.0) as u32)
+            .min(img_w);
+        let y1 = (((drift.bbox.y + drift.bbox.height) * scale_y)
+            .ceil()
+            .max(0.0) as u32)
+            .min(img_h);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let base = backdrop[(y * img_w + x) as usize];
+                let blend = |c: f32| linear_to_srgb(c * alpha + base * (1.0 - alpha));
+                heat.put_pixel(
+                    x,
+                    y,
+                    image::Rgba([blend(color[0]), blend(color[1]), blend(color[2]), 255]),
+                );
+            }
+        }
+    }
+
+    heat.save(output_path)
+        .map_err(|e| DpcError::Config(format!("Failed to save color drift heatmap: {e}")))?;
+
+    Ok(())
+}
+
+/// Color ramp used to render diff intensity in the heatmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeatmapPalette {
+    /// Green → yellow → red, matching minor/moderate/major severities.
+    #[default]
+    Severity,
+    /// Perceptually uniform, colorblind-safe blue → green → yellow ramp.
+    Viridis,
+    /// Perceptually uniform, colorblind-safe black → purple → cream ramp.
+    Magma,
+}
+
+impl HeatmapPalette {
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            HeatmapPalette::Severity => &[[0, 100, 0], [0, 200, 0], [230, 180, 0], [255, 0, 0]],
+            HeatmapPalette::Viridis => &[
+                [68, 1, 84],
+                [59, 82, 139],
+                [33, 145, 140],
+                [94, 201, 98],
+                [253, 231, 37],
+            ],
+            HeatmapPalette::Magma => &[
+                [0, 0, 4],
+                [81, 18, 124],
+                [183, 55, 121],
+                [252, 137, 97],
+                [252, 253, 191],
+            ],
+        }
+    }
+
+    /// Sample the ramp at `t` (0.0–1.0), interpolating in linear light.
+    fn sample(self, t: f32) -> [f32; 3] {
+        let stops = self.stops();
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let idx = (scaled.floor() as usize).min(stops.len() - 2);
+        let frac = scaled - idx as f32;
+        let (a, b) = (stops[idx], stops[idx + 1]);
+        let mut out = [0.0; 3];
+        for (c, value) in out.iter_mut().enumerate() {
+            let la = srgb_to_linear(a[c]);
+            let lb = srgb_to_linear(b[c]);
+            *value = la + (lb - la) * frac;
+        }
+        out
+    }
+}
+
+/// Convert an sRGBA pixel to linear RGB, compositing any transparency over white.
+fn linear_over_white(pixel: &image::Rgba<u8>) -> [f32; 3] {
+    let alpha = pixel[3] as f32 / 255.0;
+    let over = |c: u8| srgb_to_linear(c) * alpha + (1.0 - alpha);
+    [over(pixel[0]), over(pixel[1]), over(pixel[2])]
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let v = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (v * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// HAR recorded while rendering `view`, if any (`--record-network`).
+fn network_har(view: &NormalizedView) -> Option<PathBuf> {
+    view.dom
+        .as_ref()
+        .and_then(|dom| dom.diagnostics.as_ref())
+        .and_then(|diagnostics| diagnostics.har.clone())
+}
+
 /// Persist compare artifacts to disk.
 pub fn persist_compare_artifacts(
     artifacts_dir: &Path,
@@ -371,6 +907,8 @@ pub fn persist_compare_artifacts(
     impl_view: &NormalizedView,
     keep: bool,
     pixel_alignment: ImageAlignmentOptions,
+    heatmap_palette: HeatmapPalette,
+    dom_compression: SnapshotCompression,
 ) -> Result<CompareArtifacts, DpcError> {
     let mut artifacts = CompareArtifacts {
         directory: artifacts_dir.to_path_buf(),
@@ -378,10 +916,22 @@ pub fn persist_compare_artifacts(
         ref_screenshot: Some(ref_view.screenshot_path.clone()),
         impl_screenshot: Some(impl_view.screenshot_path.clone()),
         diff_image: None,
+        color_drift_image: None,
+        ref_thumbnail: None,
+        impl_thumbnail: None,
+        diff_thumbnail: None,
         ref_dom_snapshot: None,
         impl_dom_snapshot: None,
         ref_figma_snapshot: None,
         impl_figma_snapshot: None,
+        typography_report: None,
+        typography_report_csv: None,
+        typography_specimen: None,
+        run_manifest: None,
+        ref_network_har: network_har(ref_view),
+        impl_network_har: network_har(impl_view),
+        overlay: None,
+        artifact_index: None,
     };
 
     if keep {
@@ -391,25 +941,36 @@ pub fn persist_compare_artifacts(
             let ref_img = image::open(&ref_view.screenshot_path).map_err(DpcError::from)?;
             let impl_img = image::open(&impl_view.screenshot_path).map_err(DpcError::from)?;
             let (aligned_impl, _) = align_implementation(&ref_img, &impl_img, pixel_alignment);
-            generate_diff_heatmap_from_images(&ref_img, &aligned_impl, &diff_path)?;
+            generate_diff_heatmap_from_images(
+                &ref_img,
+                &aligned_impl,
+                &diff_path,
+                heatmap_palette,
+            )?;
         } else {
             generate_diff_heatmap(
                 &ref_view.screenshot_path,
                 &impl_view.screenshot_path,
                 &diff_path,
+                heatmap_palette,
             )?;
         }
         artifacts.diff_image = Some(diff_path);
 
+        let drifts = element_color_drift(ref_view, impl_view);
+        if !drifts.is_empty() {
+            let drift_path = artifacts_dir.join("color_drift.png");
+            generate_color_drift_heatmap(impl_view, &drifts, &drift_path, heatmap_palette)?;
+            artifacts.color_drift_image = Some(drift_path);
+        }
+
         if let Some(dom) = &ref_view.dom {
-            let path = artifacts_dir.join("ref_dom.json");
-            write_json_pretty(&path, dom)?;
+            let path = write_json_snapshot(artifacts_dir, "ref_dom", dom, dom_compression)?;
             artifacts.ref_dom_snapshot = Some(path);
         }
 
         if let Some(dom) = &impl_view.dom {
-            let path = artifacts_dir.join("impl_dom.json");
-            write_json_pretty(&path, dom)?;
+            let path = write_json_snapshot(artifacts_dir, "impl_dom", dom, dom_compression)?;
             artifacts.impl_dom_snapshot = Some(path);
         }
 
@@ -424,22 +985,237 @@ pub fn persist_compare_artifacts(
             write_json_pretty(&path, figma_tree)?;
             artifacts.impl_figma_snapshot = Some(path);
         }
+
+        // Replayable copies of both captures (`view:<dir>` inputs).
+        ref_view.save(artifacts_dir.join("ref_view"))?;
+        impl_view.save(artifacts_dir.join("impl_view"))?;
+
+        // Views without styled text have nothing to tabulate; skip quietly.
+        if let Ok(rows) = TypographySimilarity::default().report(ref_view, impl_view) {
+            if !rows.is_empty() {
+                let json_path = artifacts_dir.join("typography.json");
+                write_json_pretty(&json_path, &rows)?;
+                artifacts.typography_report = Some(json_path);
+
+                let csv_path = artifacts_dir.join("typography.csv");
+                std::fs::write(&csv_path, typography_report_csv(&rows))?;
+                artifacts.typography_report_csv = Some(csv_path);
+            }
+        }
+
+        let specimen = TypographySimilarity::default().specimen(ref_view, impl_view);
+        if !specimen.is_empty() {
+            let path = artifacts_dir.join("typography_specimen.html");
+            std::fs::write(&path, typography_specimen_html(&specimen))?;
+            artifacts.typography_specimen = Some(path);
+        }
     }
 
     Ok(artifacts)
 }
 
-fn write_json_pretty<T: Serialize>(path: &Path, value: &T) -> Result<(), DpcError> {
+/// Render typography report rows as CSV, one matched text element per line.
+fn typography_report_csv(rows: &[TypographyReportRow]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+    fn opt<T: ToString>(value: &Option<T>) -> String {
+        value.as_ref().map(ToString::to_string).unwrap_or_default()
+    }
+
+    let mut out = String::from(
+        "text,ref_id,impl_id,ref_font_family,impl_font_family,ref_font_size,impl_font_size,\
+         ref_font_weight,impl_font_weight,ref_line_height,impl_line_height,ref_lines,impl_lines,penalty,issues\n",
+    );
+    for row in rows {
+        let issues = row
+            .issues
+            .iter()
+            .filter_map(|issue| serde_json::to_value(issue).ok())
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect::<Vec<_>>()
+            .join(";");
+        let fields = [
+            field(&row.text),
+            field(&row.ref_id),
+            field(&row.impl_id),
+            field(&opt(&row.ref_font_family)),
+            field(&opt(&row.impl_font_family)),
+            opt(&row.ref_font_size),
+            opt(&row.impl_font_size),
+            field(&opt(&row.ref_font_weight)),
+            field(&opt(&row.impl_font_weight)),
+            opt(&row.ref_line_height),
+            opt(&row.impl_line_height),
+            opt(&row.ref_lines),
+            opt(&row.impl_lines),
+            format!("{:.3}", row.penalty),
+            issues,
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render the type specimen as a standalone HTML page: one row per style,
+/// set in that style, with styles used by only one side highlighted.
+fn typography_specimen_html(rows: &[TypeSpecimenRow]) -> String {
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#39;")
+    }
+
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Type specimen</title>\n<style>\n\
+         body { font-family: system-ui, sans-serif; margin: 24px; color: #222; }\n\
+         table { border-collapse: collapse; width: 100%; }\n\
+         th, td { border-bottom: 1px solid #ddd; padding: 8px 12px; text-align: left; }\n\
+         td.style, td.count { font-size: 13px; color: #555; white-space: nowrap; }\n\
+         tr.ref-only { background: #fff4e5; }\n\
+         tr.impl-only { background: #fde8e8; }\n\
+         .badge { padding: 1px 6px; border-radius: 4px; font-size: 11px; color: #fff; }\n\
+         tr.ref-only .badge { background: #d97706; }\n\
+         tr.impl-only .badge { background: #dc2626; }\n\
+         </style>\n</head>\n<body>\n<h1>Type specimen</h1>\n",
+    );
+    let ref_only = rows.iter().filter(|r| r.impl_count == 0).count();
+    let impl_only = rows.iter().filter(|r| r.ref_count == 0).count();
+    out.push_str(&format!(
+        "<p>{} styles; {ref_only} only in the reference, {impl_only} only in the implementation.</p>\n",
+        rows.len()
+    ));
+    out.push_str(
+        "<table>\n<tr><th>Style</th><th>Reference</th><th>Uses</th>\
+         <th>Implementation</th><th>Uses</th></tr>\n",
+    );
+    for row in rows {
+        let family = escape(row.font_family.as_deref().unwrap_or("inherit"));
+        let weight = escape(row.font_weight.as_deref().unwrap_or("inherit"));
+        let size = row
+            .font_size
+            .map(|s| format!("{s}px"))
+            .unwrap_or_else(|| "inherit".to_string());
+        let (class, badge) = match (row.ref_count, row.impl_count) {
+            (_, 0) => ("ref-only", " <span class=\"badge\">reference only</span>"),
+            (0, _) => (
+                "impl-only",
+                " <span class=\"badge\">implementation only</span>",
+            ),
+            _ => ("", ""),
+        };
+        let sample = |count: usize| {
+            if count == 0 {
+                "<td>&mdash;</td>".to_string()
+            } else {
+                format!(
+                    "<td style=\"font-family: &quot;{family}&quot;; font-size: {size}; \
+                     font-weight: {weight};\">{}</td>",
+                    escape(&row.sample)
+                )
+            }
+        };
+        out.push_str(&format!(
+            "<tr class=\"{class}\"><td class=\"style\">{family} {size} / {weight}{badge}</td>\
+             {}<td class=\"count\">{}</td>{}<td class=\"count\">{}</td></tr>\n",
+            sample(row.ref_count),
+            row.ref_count,
+            sample(row.impl_count),
+            row.impl_count,
+        ));
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+pub(crate) fn write_json_pretty<T: Serialize>(path: &Path, value: &T) -> Result<(), DpcError> {
     let file = File::create(path)?;
     let writer = BufWriter::new(file);
     serde_json::to_writer_pretty(writer, value)?;
     Ok(())
 }
 
-/// Generate summary of metric scores.
-pub fn generate_summary(scores: &MetricScores, similarity: f32, threshold: f32) -> Summary {
+/// Upload every artifact to `store` under `<run name>/` and replace the local
+/// paths with the returned durable locations.
+pub async fn upload_compare_artifacts(
+    store: &dyn ArtifactStore,
+    artifacts: &mut CompareArtifacts,
+) -> Result<(), DpcError> {
+    let run = artifacts
+        .directory
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "dpc".to_string());
+    // Screenshots may be the user's own input files, so key them by role.
+    let slots = [
+        (&mut artifacts.ref_screenshot, Some("ref_screenshot")),
+        (&mut artifacts.impl_screenshot, Some("impl_screenshot")),
+        (&mut artifacts.diff_image, None),
+        (&mut artifacts.color_drift_image, None),
+        (&mut artifacts.ref_thumbnail, None),
+        (&mut artifacts.impl_thumbnail, None),
+        (&mut artifacts.diff_thumbnail, None),
+        (&mut artifacts.ref_dom_snapshot, None),
+        (&mut artifacts.impl_dom_snapshot, None),
+        (&mut artifacts.ref_figma_snapshot, None),
+        (&mut artifacts.impl_figma_snapshot, None),
+        (&mut artifacts.typography_report, None),
+        (&mut artifacts.typography_report_csv, None),
+        (&mut artifacts.typography_specimen, None),
+        (&mut artifacts.run_manifest, None),
+        (&mut artifacts.ref_network_har, None),
+        (&mut artifacts.impl_network_har, None),
+        (&mut artifacts.overlay, None),
+        (&mut artifacts.artifact_index, None),
+    ];
+
+    let mut run_location = None;
+    for (slot, role) in slots {
+        let Some(path) = slot.as_ref() else {
+            continue;
+        };
+        let file_name = match (role, path.extension()) {
+            (Some(role), Some(ext)) => format!("{role}.{}", ext.to_string_lossy()),
+            (Some(role), None) => role.to_string(),
+            (None, _) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let location = store.put(&format!("{run}/{file_name}"), path).await?;
+        if run_location.is_none() {
+            run_location = location
+                .strip_suffix(&file_name)
+                .map(|base| base.trim_end_matches(['/', '\\']).to_string());
+        }
+        *slot = Some(PathBuf::from(location));
+    }
+
+    if let Some(location) = run_location {
+        artifacts.directory = PathBuf::from(location);
+    }
+    artifacts.kept = true;
+    Ok(())
+}
+
+/// Generate summary of metric scores, ranking issues with `calibration`.
+pub fn generate_summary(
+    scores: &MetricScores,
+    similarity: f32,
+    threshold: f32,
+    calibration: &SeverityCalibration,
+) -> Summary {
     const MAX_SUMMARY_ISSUES: usize = 5;
-    let mut top_issues = generate_top_issues(scores, MAX_SUMMARY_ISSUES);
+    let mut top_issues = generate_top_issues_with(scores, MAX_SUMMARY_ISSUES, calibration);
 
     // Add overall status
     let status = if similarity >= threshold {
@@ -457,16 +1233,19 @@ pub fn generate_summary(scores: &MetricScores, similarity: f32, threshold: f32)
     };
     top_issues.insert(0, status);
 
-    Summary { top_issues }
+    Summary {
+        top_issues,
+        narrative: None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use dpc_lib::types::{
-        BoundingBox, ColorDiff, ColorDiffKind, ColorMetric, DomSnapshot, LayoutDiffKind,
-        LayoutDiffRegion, LayoutMetric, MetricScores, ResourceKind, TypographyDiff,
-        TypographyIssue, TypographyMetric,
+        BoundingBox, ColorDiff, ColorDiffKind, ColorMetric, DomNode, DomSnapshot, FailedRequest,
+        LayoutDiffKind, LayoutDiffRegion, LayoutMetric, MetricScores, PageDiagnostics,
+        ResourceKind, TypographyDiff, TypographyIssue, TypographyMetric,
     };
     use std::collections::HashMap;
 
@@ -488,6 +1267,8 @@ mod tests {
                 width: 1.0,
                 height: 1.0,
             },
+            text_baseline: None,
+            interaction_states: None,
             computed_style: None,
         }
     }
@@ -499,15 +1280,70 @@ mod tests {
             width: 100,
             height: 100,
             dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
                 url: None,
                 title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
                 nodes,
             }),
             figma_tree: None,
             ocr_blocks: None,
+            content_area: None,
         }
     }
 
+    #[test]
+    fn page_diagnostics_report_drops_har_paths_and_warns_per_side() {
+        let ref_view = view_with_dom(vec![]);
+        let mut impl_view = view_with_dom(vec![]);
+        assert!(compare_page_diagnostics(&ref_view, &impl_view).is_none());
+
+        impl_view.dom.as_mut().unwrap().diagnostics = Some(PageDiagnostics {
+            console_errors: vec![],
+            failed_requests: vec![FailedRequest {
+                url: "https://cdn.example.com/hero.png".into(),
+                resource_type: Some("image".into()),
+                status: Some(404),
+                error: None,
+            }],
+            har: Some("impl_network.har".into()),
+        });
+        let report = compare_page_diagnostics(&ref_view, &impl_view).unwrap();
+        assert!(report.reference.is_none());
+        assert!(report.implementation.as_ref().unwrap().har.is_none());
+        assert_eq!(
+            page_diagnostics_warnings(&report),
+            vec![
+                "impl page: 1 failed request (1 image, e.g. https://cdn.example.com/hero.png 404)"
+            ]
+        );
+        assert_eq!(network_har(&impl_view), Some("impl_network.har".into()));
+    }
+
+    #[test]
+    fn truncate_dom_caps_nodes_and_prunes_dropped_children() {
+        let mut root = make_node("root", "body", None);
+        root.children = vec!["a".into(), "b".into(), "c".into()];
+        let nodes = vec![
+            root,
+            make_node("a", "div", None),
+            make_node("b", "div", None),
+            make_node("c", "div", None),
+        ];
+        let mut view = view_with_dom(nodes);
+
+        assert!(truncate_dom("impl", &mut view, 4).is_none());
+        assert_eq!(
+            truncate_dom("impl", &mut view, 2).as_deref(),
+            Some("impl: DOM snapshot had 4 nodes; kept the first 2 ([dom] max_nodes)")
+        );
+        let dom = view.dom.as_ref().unwrap();
+        assert_eq!(dom.nodes.len(), 2);
+        assert_eq!(dom.nodes[0].children, vec!["a".to_string()]);
+    }
+
     #[test]
     fn parse_ignore_selectors_normalizes_and_trims() {
         let parsed = parse_ignore_selectors(Some("  #Hero , .Ad ,p  ,, "));
@@ -535,6 +1371,103 @@ mod tests {
         assert!(kept.is_empty(), "all nodes should be ignored");
     }
 
+    #[test]
+    fn apply_dom_ignores_supports_compound_selectors() {
+        let mut header = make_node("header", "header", None);
+        header.children = vec!["logo".to_string()];
+        let mut logo = make_node("logo", "img", Some("logo"));
+        logo.parent = Some("header".to_string());
+        let footer_logo = make_node("footer-logo", "img", Some("logo"));
+        let mut ad = make_node("ad", "div", None);
+        ad.attributes
+            .insert("data-testid".to_string(), "ad".to_string());
+        let view = view_with_dom(vec![header, logo, footer_logo, ad]);
+
+        let selectors = parse_ignore_selectors(Some("header .logo, [data-testid=ad]"));
+        assert_eq!(selectors, vec!["header .logo", "[data-testid=ad]"]);
+        validate_ignore_selectors(&selectors).unwrap();
+        let kept: Vec<String> = apply_dom_ignores(&view, &selectors)
+            .dom
+            .unwrap()
+            .nodes
+            .iter()
+            .map(|n| n.id.clone())
+            .collect();
+        assert_eq!(kept, vec!["header", "footer-logo"]);
+
+        let err = validate_ignore_selectors(&["a:hover".to_string()]).unwrap_err();
+        assert!(matches!(err, DpcError::Config(_)));
+    }
+
+    #[test]
+    fn reconcile_dimensions_applies_the_mismatch_policy() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let shot = |name: &str, width: u32, height: u32| {
+            let path = tmp.path().join(name);
+            RgbaImage::from_pixel(width, height, image::Rgba([90, 90, 90, 255]))
+                .save(&path)
+                .unwrap();
+            let mut view = view_with_dom(Vec::new());
+            view.screenshot_path = path;
+            (view.width, view.height) = (width, height);
+            view
+        };
+        let reference = shot("ref.png", 100, 200);
+        let implementation = shot("impl.png", 100, 100);
+        let reconcile = |policy| {
+            reconcile_dimensions(
+                &reference,
+                &implementation,
+                policy,
+                tmp.path(),
+                "ref_vs_impl",
+                "impl_vs_ref",
+            )
+        };
+
+        let same = shot("same.png", 50, 100);
+        let (_, _, report) = reconcile_dimensions(
+            &reference,
+            &same,
+            DimensionMismatchPolicy::Fail,
+            tmp.path(),
+            "a",
+            "b",
+        )
+        .unwrap();
+        assert!(report.is_none(), "equal aspect ratios only need scaling");
+
+        let err = reconcile(DimensionMismatchPolicy::Fail).unwrap_err();
+        assert!(err.to_string().contains("100x200"));
+
+        let (ref_view, impl_view, report) = reconcile(DimensionMismatchPolicy::Stretch).unwrap();
+        assert_eq!(impl_view.screenshot_path, implementation.screenshot_path);
+        assert_eq!(ref_view.screenshot_path, reference.screenshot_path);
+        assert_eq!(report.unwrap().compared.height, 200);
+
+        let (_, impl_view, report) = reconcile(DimensionMismatchPolicy::Letterbox).unwrap();
+        assert_eq!(
+            image::image_dimensions(&impl_view.screenshot_path).unwrap(),
+            (100, 200)
+        );
+        let area = impl_view.content_area.unwrap();
+        assert_eq!((area.y, area.height), (50.0, 100.0));
+        assert_eq!(report.unwrap().policy, DimensionMismatchPolicy::Letterbox);
+
+        let (ref_view, impl_view, report) = reconcile(DimensionMismatchPolicy::Crop).unwrap();
+        assert_eq!(
+            image::image_dimensions(&ref_view.screenshot_path).unwrap(),
+            (100, 100)
+        );
+        assert_eq!(ref_view.height, 100);
+        assert_eq!(impl_view.screenshot_path, implementation.screenshot_path);
+        let report = report.unwrap();
+        assert_eq!(
+            (report.reference.height, report.compared.height),
+            (200, 100)
+        );
+    }
+
     #[test]
     fn generate_diff_heatmap_creates_file() {
         let tmp = tempfile::tempdir().expect("tempdir");
@@ -547,12 +1480,97 @@ mod tests {
         ref_img.save(&ref_path).unwrap();
         impl_img.save(&impl_path).unwrap();
 
-        generate_diff_heatmap(&ref_path, &impl_path, &out_path).unwrap();
+        generate_diff_heatmap(&ref_path, &impl_path, &out_path, HeatmapPalette::default()).unwrap();
         assert!(out_path.exists(), "heatmap file should be created");
         let meta = std::fs::metadata(&out_path).unwrap();
         assert!(meta.len() > 0, "heatmap should not be empty");
     }
 
+    #[test]
+    fn heatmap_is_opaque_and_dims_unchanged_reference() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let out_path = tmp.path().join("diff_heatmap.png");
+
+        let mut ref_img = RgbaImage::from_pixel(2, 1, image::Rgba([255, 255, 255, 255]));
+        ref_img.put_pixel(1, 0, image::Rgba([0, 0, 0, 0]));
+        let mut impl_img = ref_img.clone();
+        impl_img.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+
+        generate_diff_heatmap_from_images(
+            &DynamicImage::ImageRgba8(ref_img),
+            &DynamicImage::ImageRgba8(impl_img),
+            &out_path,
+            HeatmapPalette::Viridis,
+        )
+        .unwrap();
+
+        let heat = image::open(&out_path).unwrap().to_rgba8();
+        assert!(heat.pixels().all(|p| p[3] == 255), "heatmap must be opaque");
+        // Transparent reference pixels composite over white, then dim to a gray backdrop.
+        let backdrop = heat.get_pixel(1, 0);
+        assert_eq!(backdrop[0], backdrop[1]);
+        assert!(backdrop[0] > 100 && backdrop[0] < 200);
+        // Changed pixels pick up the palette's high end (viridis yellow).
+        let changed = heat.get_pixel(0, 0);
+        assert!(changed[0] > changed[2] && changed[1] > changed[2]);
+    }
+
+    #[test]
+    fn color_drift_heatmap_tints_elements_in_screenshot_pixels() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let screenshot = tmp.path().join("impl.png");
+        RgbaImage::from_pixel(8, 4, image::Rgba([255, 255, 255, 255]))
+            .save(&screenshot)
+            .unwrap();
+        let mut view = view_with_dom(vec![]);
+        (view.screenshot_path, view.width, view.height) = (screenshot, 4, 2);
+        let drift = |id: &str, x: f32, delta_e: f32| ElementColorDrift {
+            element_id: id.to_string(),
+            ref_element_id: id.to_string(),
+            bbox: BoundingBox {
+                x,
+                y: 0.0,
+                width: 1.0,
+                height: 1.0,
+            },
+            background_delta_e: Some(delta_e),
+            foreground_delta_e: None,
+        };
+        let out_path = tmp.path().join("color_drift.png");
+        generate_color_drift_heatmap(
+            &view,
+            &[drift("same", 0.0, 0.0), drift("drifted", 2.0, 30.0)],
+            &out_path,
+            HeatmapPalette::Severity,
+        )
+        .unwrap();
+
+        let heat = image::open(&out_path).unwrap().to_rgba8();
+        // Units map to 2x2 pixel blocks; untouched pixels keep the gray backdrop.
+        let untouched = heat.get_pixel(3, 3);
+        assert_eq!(untouched[0], untouched[1]);
+        let same = heat.get_pixel(1, 1);
+        assert!(same[1] > same[0], "matching colors are tinted green");
+        let drifted = heat.get_pixel(5, 1);
+        assert!(drifted[0] > drifted[1], "drifted colors are tinted red");
+        assert_eq!(heat.get_pixel(6, 1), untouched);
+    }
+
+    #[test]
+    fn heatmap_palettes_interpolate_endpoints() {
+        for palette in [
+            HeatmapPalette::Severity,
+            HeatmapPalette::Viridis,
+            HeatmapPalette::Magma,
+        ] {
+            let stops = palette.stops();
+            let first = palette.sample(0.0);
+            let last = palette.sample(1.0);
+            assert_eq!(linear_to_srgb(first[0]), stops[0][0]);
+            assert_eq!(linear_to_srgb(last[1]), stops[stops.len() - 1][1]);
+        }
+    }
+
     #[test]
     fn summary_orders_issues_by_severity_and_priority() {
         let scores = MetricScores {
@@ -561,8 +1579,11 @@ mod tests {
             typography: Some(TypographyMetric {
                 score: 0.7,
                 diffs: vec![TypographyDiff {
+                    fingerprint: None,
                     element_id_ref: Some("caption".into()),
                     element_id_impl: None,
+                    label: None,
+                    style: None,
                     issues: vec![TypographyIssue::LineHeightDiff],
                     details: None,
                 }],
@@ -570,16 +1591,21 @@ mod tests {
             color: Some(ColorMetric {
                 score: 0.6,
                 diffs: vec![ColorDiff {
+                    fingerprint: None,
                     kind: ColorDiffKind::AccentColorShift,
                     ref_color: "#111111".into(),
                     impl_color: "#222222".into(),
                     delta_e: Some(8.0),
+                    element_id: None,
+                    angle_delta: None,
+                    style: None,
                 }],
             }),
             content: None,
+            semantic: None,
         };
 
-        let summary = generate_summary(&scores, 0.4, 0.8);
+        let summary = generate_summary(&scores, 0.4, 0.8, &SeverityCalibration::default());
         assert!(
             summary
                 .top_issues
@@ -613,21 +1639,28 @@ mod tests {
             layout: Some(LayoutMetric {
                 score: 0.0,
                 diff_regions: vec![LayoutDiffRegion {
+                    fingerprint: None,
                     x: 0.0,
                     y: 0.0,
                     width: 1.0,
                     height: 1.0,
+                    unit: None,
                     kind: LayoutDiffKind::MissingElement,
                     element_type: Some("button".into()),
                     label: None,
+                    detail: None,
+                    region_count: None,
+                    pixel_bounds: None,
+                    css_bounds: None,
                 }],
             }),
             typography: None,
             color: None,
             content: None,
+            semantic: None,
         };
 
-        let summary = generate_summary(&scores, 0.0, 0.9);
+        let summary = generate_summary(&scores, 0.0, 0.9, &SeverityCalibration::default());
 
         assert!(
             summary
@@ -637,4 +1670,62 @@ mod tests {
             "layout issues should be surfaced even when implementation has no elements"
         );
     }
+
+    #[test]
+    fn typography_report_csv_quotes_text_and_joins_issues() {
+        let row = TypographyReportRow {
+            text: "Buy now, save \"big\"".into(),
+            ref_id: "r1".into(),
+            impl_id: "i1".into(),
+            ref_font_family: Some("Inter".into()),
+            impl_font_family: Some("Arial".into()),
+            ref_font_size: Some(16.0),
+            impl_font_size: Some(18.0),
+            ref_font_weight: Some("400".into()),
+            impl_font_weight: None,
+            ref_line_height: None,
+            impl_line_height: None,
+            ref_lines: Some(1),
+            impl_lines: Some(1),
+            penalty: 0.6,
+            issues: vec![
+                TypographyIssue::FontFamilyMismatch,
+                TypographyIssue::FontSizeDiff,
+            ],
+        };
+
+        let csv = typography_report_csv(&[row]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("text,ref_id,impl_id,ref_font_family"));
+        assert_eq!(
+            lines[1],
+            "\"Buy now, save \"\"big\"\"\",r1,i1,Inter,Arial,16,18,400,,,,1,1,0.600,font_family_mismatch;font_size_diff"
+        );
+    }
+
+    #[test]
+    fn typography_specimen_marks_one_sided_styles() {
+        let row = |family: &str, ref_count, impl_count, sample: &str| TypeSpecimenRow {
+            font_family: Some(family.to_string()),
+            font_size: Some(16.0),
+            font_weight: Some("400".to_string()),
+            ref_count,
+            impl_count,
+            sample: sample.to_string(),
+        };
+        let html = typography_specimen_html(&[
+            row("Inter", 3, 2, "Body"),
+            row("Arial", 0, 1, "<b>Rogue</b>"),
+        ]);
+        assert!(html.contains("2 styles; 0 only in the reference, 1 only in the implementation"));
+        let rogue = html
+            .lines()
+            .find(|line| line.contains("Arial 16px"))
+            .expect("row for the rogue style");
+        assert!(rogue.starts_with("<tr class=\"impl-only\">"));
+        assert!(rogue.contains("<td>&mdash;</td>"));
+        assert!(rogue.contains("&lt;b&gt;Rogue&lt;/b&gt;"));
+        assert!(html.contains("font-family: &quot;Inter&quot;; font-size: 16px; font-weight: 400;"));
+    }
 }
@@ -1,9 +1,12 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use url::Url;
 
+use crate::device::DeviceTarget;
 use crate::types::ResourceKind;
+use crate::video::VideoFrameRef;
+use crate::view_bundle::{VIEW_FILE, VIEW_SCHEME};
 
 #[derive(Debug, Clone)]
 pub struct ParsedResource {
@@ -12,10 +15,77 @@ pub struct ParsedResource {
     pub figma_info: Option<FigmaInfo>,
 }
 
+impl ParsedResource {
+    /// An image resource that is downloaded rather than read from disk
+    /// (`imgurl:` inputs, or an http(s) URL with `--*-type image`).
+    pub fn is_remote_image(&self) -> bool {
+        self.kind == ResourceKind::Image && is_http_url(&self.value)
+    }
+
+    /// An image piped in on stdin (`-`).
+    pub fn is_stdin(&self) -> bool {
+        self.kind == ResourceKind::Image && self.value == STDIN_RESOURCE
+    }
+
+    /// An image embedded as a `data:image/...;base64,` URI.
+    pub fn is_data_uri(&self) -> bool {
+        self.kind == ResourceKind::Image && self.value.starts_with(DATA_URI_SCHEME)
+    }
+
+    /// A frame of a local video (`clip.mp4@00:05.2`).
+    pub fn video_frame(&self) -> Option<VideoFrameRef<'_>> {
+        if self.kind != ResourceKind::Image {
+            return None;
+        }
+        VideoFrameRef::parse(&self.value).and_then(Result::ok)
+    }
+
+    /// Path of a local image (or video) file; `None` for every other kind of
+    /// input.
+    pub fn local_image_path(&self) -> Option<&Path> {
+        if let Some(frame) = self.video_frame() {
+            return Some(frame.path);
+        }
+        (self.kind == ResourceKind::Image
+            && !self.is_remote_image()
+            && !self.is_stdin()
+            && !self.is_data_uri())
+        .then(|| Path::new(&self.value))
+    }
+
+    /// Directory of a saved view bundle (`view:<dir>`).
+    pub fn view_bundle_dir(&self) -> Option<&Path> {
+        if self.kind != ResourceKind::View {
+            return None;
+        }
+        self.value.strip_prefix(VIEW_SCHEME).map(Path::new)
+    }
+
+    /// Value to echo in reports: stdin and data URIs are summarized instead
+    /// of repeating the payload.
+    pub fn display_value(&self) -> String {
+        if self.is_stdin() {
+            "stdin".to_string()
+        } else if self.is_data_uri() {
+            let header = self.value.split(',').next().unwrap_or_default();
+            format!("{header},… ({} bytes)", self.value.len())
+        } else {
+            self.value.clone()
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FigmaInfo {
+    /// Key the API is called with; the branch key for branch URLs.
     pub file_key: String,
+    /// Key of the main file when `file_key` is a branch.
+    pub main_file_key: Option<String>,
     pub node_id: Option<String>,
+    /// Frame name to resolve against the file tree when no node id is given.
+    pub frame_name: Option<String>,
+    /// Saved Figma REST response used instead of the API (`figma-json:` inputs).
+    pub json_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Error)]
@@ -24,8 +94,14 @@ pub enum ResourceParseError {
     InvalidUrl { value: String, message: String },
     #[error("Figma URL missing file key in '{url}'. Hint: use https://www.figma.com/file/<FILE_KEY>/... and node-id if needed.")]
     FigmaMissingFileKey { url: String },
+    #[error("Invalid Figma reference '{value}': {message}. Hint: use figma:<FILE_KEY>#node-id=1:2 or figma:<FILE_KEY>#frame-name=\"Page / Frame\".")]
+    InvalidFigmaReference { value: String, message: String },
     #[error("Local file not found: {path}. Hint: check the path relative to the current working directory or use an absolute path.")]
     FileNotFound { path: String },
+    #[error("Invalid video frame '{value}': {message}. Hint: use clip.mp4@00:05.2.")]
+    InvalidVideoFrame { value: String, message: String },
+    #[error("Invalid data URI: {message}. Hint: use data:image/png;base64,<DATA>.")]
+    InvalidDataUri { message: String },
     #[error("Unsupported file extension '{extension}'. Supported image extensions: {supported}.")]
     UnsupportedExtension {
         extension: String,
@@ -34,30 +110,153 @@ pub enum ResourceParseError {
 }
 
 const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif"];
+const FIGMA_SCHEME: &str = "figma:";
+const FIGMA_JSON_SCHEME: &str = "figma-json:";
+/// Path segments followed by the file key in Figma URLs.
+const FIGMA_FILE_PATHS: &[&str] = &["file", "design", "proto"];
+const IMAGE_URL_SCHEME: &str = "imgurl:";
+const DATA_URI_SCHEME: &str = "data:";
+/// Resource value that reads an image from stdin.
+pub const STDIN_RESOURCE: &str = "-";
 
 pub fn parse_resource(
     value: &str,
     override_type: Option<ResourceKind>,
 ) -> Result<ParsedResource, ResourceParseError> {
+    if value == STDIN_RESOURCE {
+        return Ok(ParsedResource {
+            kind: ResourceKind::Image,
+            value: value.to_string(),
+            figma_info: None,
+        });
+    }
+    if value.starts_with(DATA_URI_SCHEME) {
+        return parse_data_uri(value);
+    }
+    if let Some(dir) = value.strip_prefix(VIEW_SCHEME) {
+        return parse_view_bundle(value, dir);
+    }
+    if let Some(target) = DeviceTarget::parse(value) {
+        return Ok(ParsedResource {
+            kind: target.kind(),
+            value: value.to_string(),
+            figma_info: None,
+        });
+    }
+    if let Some(url) = value.strip_prefix(IMAGE_URL_SCHEME) {
+        return parse_image_url(url.trim());
+    }
+    if override_type == Some(ResourceKind::Image) && is_http_url(value) {
+        return parse_image_url(value);
+    }
     if let Some(kind) = override_type {
         return Ok(ParsedResource {
             kind,
             value: value.to_string(),
             figma_info: if kind == ResourceKind::Figma {
-                parse_figma_url(value).ok()
+                parse_figma_reference(value).ok()
             } else {
                 None
             },
         });
     }
 
-    if value.starts_with("http://") || value.starts_with("https://") {
+    if value.starts_with(FIGMA_SCHEME) || value.starts_with(FIGMA_JSON_SCHEME) {
+        Ok(ParsedResource {
+            kind: ResourceKind::Figma,
+            value: value.to_string(),
+            figma_info: Some(parse_figma_reference(value)?),
+        })
+    } else if is_http_url(value) {
         parse_url_resource(value)
+    } else if let Some(frame) = VideoFrameRef::parse(value) {
+        parse_video_frame(value, frame)
     } else {
         parse_local_resource(value)
     }
 }
 
+fn parse_video_frame(
+    value: &str,
+    frame: Result<VideoFrameRef<'_>, String>,
+) -> Result<ParsedResource, ResourceParseError> {
+    let frame = frame.map_err(|message| ResourceParseError::InvalidVideoFrame {
+        value: value.to_string(),
+        message,
+    })?;
+    if !frame.path.is_file() {
+        return Err(ResourceParseError::FileNotFound {
+            path: frame.path.to_string_lossy().into_owned(),
+        });
+    }
+    Ok(ParsedResource {
+        kind: ResourceKind::Image,
+        value: value.to_string(),
+        figma_info: None,
+    })
+}
+
+/// A directory written by [`NormalizedView::save`](crate::types::NormalizedView::save).
+fn parse_view_bundle(value: &str, dir: &str) -> Result<ParsedResource, ResourceParseError> {
+    let view_file = Path::new(dir).join(VIEW_FILE);
+    if !view_file.is_file() {
+        return Err(ResourceParseError::FileNotFound {
+            path: view_file.to_string_lossy().into_owned(),
+        });
+    }
+    Ok(ParsedResource {
+        kind: ResourceKind::View,
+        value: value.to_string(),
+        figma_info: None,
+    })
+}
+
+fn is_http_url(value: &str) -> bool {
+    value.starts_with("http://") || value.starts_with("https://")
+}
+
+/// A bitmap hosted at an http(s) URL, downloaded as-is instead of rendered.
+fn parse_image_url(value: &str) -> Result<ParsedResource, ResourceParseError> {
+    let invalid = |message: String| ResourceParseError::InvalidUrl {
+        value: value.to_string(),
+        message,
+    };
+    let url = Url::parse(value).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("image URLs must use http or https".to_string()));
+    }
+    Ok(ParsedResource {
+        kind: ResourceKind::Image,
+        value: value.to_string(),
+        figma_info: None,
+    })
+}
+
+/// Check the `data:image/<type>;base64,` header; the payload is decoded when
+/// the image is loaded.
+fn parse_data_uri(value: &str) -> Result<ParsedResource, ResourceParseError> {
+    let invalid = |message: &str| ResourceParseError::InvalidDataUri {
+        message: message.to_string(),
+    };
+    let (header, data) = value
+        .split_once(',')
+        .ok_or_else(|| invalid("missing ',' before the payload"))?;
+    let media = header[DATA_URI_SCHEME.len()..]
+        .strip_suffix(";base64")
+        .ok_or_else(|| invalid("only base64-encoded data URIs are supported"))?;
+    if !media.starts_with("image/") {
+        return Err(invalid("media type must be image/*"));
+    }
+    if data.trim().is_empty() {
+        return Err(invalid("payload is empty"));
+    }
+    Ok(ParsedResource {
+        kind: ResourceKind::Image,
+        value: value.to_string(),
+        figma_info: None,
+    })
+}
+
 fn parse_url_resource(value: &str) -> Result<ParsedResource, ResourceParseError> {
     let url = Url::parse(value).map_err(|e| ResourceParseError::InvalidUrl {
         value: value.to_string(),
@@ -89,21 +288,163 @@ fn parse_figma_url(value: &str) -> Result<FigmaInfo, ResourceParseError> {
 
     let path_segments: Vec<&str> = url.path_segments().map(|c| c.collect()).unwrap_or_default();
 
-    let file_key = path_segments
-        .iter()
-        .position(|&s| s == "file" || s == "design")
-        .and_then(|i| path_segments.get(i + 1))
-        .map(|s| s.to_string())
-        .ok_or_else(|| ResourceParseError::FigmaMissingFileKey {
+    // `/file/<KEY>/...`, `/design/<KEY>/...` or `/proto/<KEY>/...`, optionally
+    // followed by `/branch/<BRANCH_KEY>/...`.
+    let key_after = |marker: &[&str]| {
+        path_segments
+            .iter()
+            .position(|s| marker.contains(s))
+            .and_then(|i| path_segments.get(i + 1))
+            .filter(|key| !key.is_empty())
+            .map(|key| key.to_string())
+    };
+    let file_key =
+        key_after(FIGMA_FILE_PATHS).ok_or_else(|| ResourceParseError::FigmaMissingFileKey {
             url: value.to_string(),
         })?;
+    let (file_key, main_file_key) = match key_after(&["branch"]) {
+        Some(branch_key) => (branch_key, Some(file_key)),
+        None => (file_key, None),
+    };
 
     let node_id = url
         .query_pairs()
         .find(|(k, _)| k == "node-id")
-        .map(|(_, v)| v.replace('-', ":"));
+        .map(|(_, v)| normalize_node_id(&v))
+        .filter(|id| !id.is_empty());
+    let frame_name = url
+        .query_pairs()
+        .find(|(k, _)| k == "frame-name")
+        .map(|(_, v)| v.into_owned());
+
+    Ok(FigmaInfo {
+        file_key,
+        main_file_key,
+        node_id,
+        frame_name,
+        json_path: None,
+    })
+}
+
+/// Convert a node id from a URL or selector to the `1:23` form the Figma API
+/// expects.
+///
+/// Figma links write `1:23` as `1-23`, and pasted ids are often
+/// percent-encoded (`1%3A23`, or `I1%3A2%3B3%3A4` for a node inside an
+/// instance). Ids already in API form are returned unchanged.
+pub fn normalize_node_id(raw: &str) -> String {
+    percent_encoding::percent_decode_str(raw.trim())
+        .decode_utf8_lossy()
+        .trim()
+        .replace('-', ":")
+}
 
-    Ok(FigmaInfo { file_key, node_id })
+fn parse_figma_reference(value: &str) -> Result<FigmaInfo, ResourceParseError> {
+    if value.starts_with(FIGMA_JSON_SCHEME) {
+        parse_figma_json_reference(value)
+    } else if value.starts_with(FIGMA_SCHEME) {
+        parse_figma_shorthand(value)
+    } else {
+        parse_figma_url(value)
+    }
+}
+
+/// Parse `figma-json:<PATH>[#node-id=1:2 | #frame-name="..."]`.
+///
+/// The exported PNG is expected next to the JSON with the same stem (`frame.json` → `frame.png`).
+fn parse_figma_json_reference(value: &str) -> Result<FigmaInfo, ResourceParseError> {
+    let rest = &value[FIGMA_JSON_SCHEME.len()..];
+    let (path, selector) = match rest.split_once('#') {
+        Some((path, selector)) => (path.trim(), Some(selector.trim())),
+        None => (rest.trim(), None),
+    };
+    if path.is_empty() {
+        return Err(ResourceParseError::InvalidFigmaReference {
+            value: value.to_string(),
+            message: "missing JSON path".to_string(),
+        });
+    }
+
+    let json_path = PathBuf::from(path);
+    for required in [json_path.clone(), json_path.with_extension("png")] {
+        if !required.is_file() {
+            return Err(ResourceParseError::FileNotFound {
+                path: required.to_string_lossy().into_owned(),
+            });
+        }
+    }
+
+    let mut info = FigmaInfo {
+        file_key: json_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        main_file_key: None,
+        node_id: None,
+        frame_name: None,
+        json_path: Some(json_path),
+    };
+    if let Some(selector) = selector.filter(|s| !s.is_empty()) {
+        apply_figma_selector(value, selector, &mut info)?;
+    }
+    Ok(info)
+}
+
+/// Parse `figma:<FILE_KEY>[#node-id=1:2 | #frame-name="Page / Frame"]`.
+fn parse_figma_shorthand(value: &str) -> Result<FigmaInfo, ResourceParseError> {
+    let rest = &value[FIGMA_SCHEME.len()..];
+    let (file_key, selector) = match rest.split_once('#') {
+        Some((key, selector)) => (key.trim(), Some(selector.trim())),
+        None => (rest.trim(), None),
+    };
+    if file_key.is_empty() {
+        return Err(ResourceParseError::FigmaMissingFileKey {
+            url: value.to_string(),
+        });
+    }
+
+    let mut info = FigmaInfo {
+        file_key: file_key.to_string(),
+        main_file_key: None,
+        node_id: None,
+        frame_name: None,
+        json_path: None,
+    };
+    if let Some(selector) = selector.filter(|s| !s.is_empty()) {
+        apply_figma_selector(value, selector, &mut info)?;
+    }
+    Ok(info)
+}
+
+/// Apply a `node-id=...` or `frame-name=...` selector from a `figma:`/`figma-json:` value.
+fn apply_figma_selector(
+    value: &str,
+    selector: &str,
+    info: &mut FigmaInfo,
+) -> Result<(), ResourceParseError> {
+    let invalid = |message: &str| ResourceParseError::InvalidFigmaReference {
+        value: value.to_string(),
+        message: message.to_string(),
+    };
+    let (key, raw) = selector
+        .split_once('=')
+        .ok_or_else(|| invalid("expected key=value after '#'"))?;
+    let raw = raw.trim();
+    let unquoted = raw
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(raw)
+        .trim();
+    if unquoted.is_empty() {
+        return Err(invalid("selector value is empty"));
+    }
+
+    match key.trim() {
+        "node-id" => info.node_id = Some(normalize_node_id(unquoted)),
+        "frame-name" => info.frame_name = Some(unquoted.to_string()),
+        _ => return Err(invalid("supported selectors are node-id and frame-name")),
+    }
+    Ok(())
 }
 
 fn parse_local_resource(value: &str) -> Result<ParsedResource, ResourceParseError> {
@@ -205,6 +546,194 @@ mod tests {
         assert!(info.node_id.is_none());
     }
 
+    #[test]
+    fn test_figma_node_id_encodings() {
+        for raw in ["12-34", "12:34", "12%3A34", "12%3a34", " 12-34 "] {
+            assert_eq!(normalize_node_id(raw), "12:34", "{raw}");
+        }
+        assert_eq!(normalize_node_id("I1%3A2%3B3%3A4"), "I1:2;3:4");
+
+        for url in [
+            "https://www.figma.com/file/ABC123/My-Design?node-id=12%3A34",
+            "https://www.figma.com/proto/ABC123/My-Design?node-id=12-34&scaling=min-zoom",
+            "https://figma.com/design/ABC123?node-id=12%253A34",
+        ] {
+            let info = parse_resource(url, None).unwrap().figma_info.unwrap();
+            assert_eq!(info.file_key, "ABC123", "{url}");
+            assert_eq!(info.node_id.as_deref(), Some("12:34"), "{url}");
+        }
+
+        let info = parse_resource("figma:ABC123#node-id=12%3A34", None)
+            .unwrap()
+            .figma_info
+            .unwrap();
+        assert_eq!(info.node_id.as_deref(), Some("12:34"));
+    }
+
+    #[test]
+    fn test_parse_figma_branch_url() {
+        let url = "https://www.figma.com/design/MAIN123/branch/BRANCH456/My-Design?node-id=1-2";
+        let info = parse_resource(url, None).unwrap().figma_info.unwrap();
+        assert_eq!(info.file_key, "BRANCH456");
+        assert_eq!(info.main_file_key.as_deref(), Some("MAIN123"));
+        assert_eq!(info.node_id.as_deref(), Some("1:2"));
+
+        let info = parse_resource("https://www.figma.com/file/MAIN123/My-Design", None)
+            .unwrap()
+            .figma_info
+            .unwrap();
+        assert!(info.main_file_key.is_none());
+    }
+
+    #[test]
+    fn test_parse_figma_shorthand_frame_name() {
+        let res = parse_resource(r#"figma:ABC123#frame-name="Checkout / Desktop""#, None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Figma);
+        let info = res.figma_info.unwrap();
+        assert_eq!(info.file_key, "ABC123");
+        assert!(info.node_id.is_none());
+        assert_eq!(info.frame_name.as_deref(), Some("Checkout / Desktop"));
+    }
+
+    #[test]
+    fn test_parse_figma_shorthand_node_id() {
+        let res = parse_resource("figma:ABC123#node-id=12-34", None).unwrap();
+        let info = res.figma_info.unwrap();
+        assert_eq!(info.node_id, Some("12:34".to_string()));
+        assert!(info.frame_name.is_none());
+    }
+
+    #[test]
+    fn test_parse_figma_shorthand_rejects_unknown_selector() {
+        let res = parse_resource("figma:ABC123#page=Home", None);
+        assert!(matches!(
+            res,
+            Err(ResourceParseError::InvalidFigmaReference { .. })
+        ));
+        let res = parse_resource("figma:#frame-name=Home", None);
+        assert!(matches!(
+            res,
+            Err(ResourceParseError::FigmaMissingFileKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_figma_json_requires_sibling_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("checkout.json");
+        fs::write(&json, "{}").unwrap();
+        let value = format!("figma-json:{}#node-id=1-2", json.display());
+
+        let res = parse_resource(&value, None);
+        assert!(
+            matches!(res, Err(ResourceParseError::FileNotFound { path }) if path.ends_with("checkout.png"))
+        );
+
+        fs::write(dir.path().join("checkout.png"), b"png").unwrap();
+        let res = parse_resource(&value, None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Figma);
+        let info = res.figma_info.unwrap();
+        assert_eq!(info.file_key, "checkout");
+        assert_eq!(info.node_id.as_deref(), Some("1:2"));
+        assert_eq!(info.json_path.as_deref(), Some(json.as_path()));
+    }
+
+    #[test]
+    fn test_parse_remote_image_url() {
+        let res = parse_resource("imgurl:https://cdn.example.com/hero.png", None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Image);
+        assert_eq!(res.value, "https://cdn.example.com/hero.png");
+        assert!(res.is_remote_image());
+
+        let res = parse_resource(
+            "https://cdn.example.com/share?id=1",
+            Some(ResourceKind::Image),
+        )
+        .unwrap();
+        assert!(res.is_remote_image());
+
+        assert!(matches!(
+            parse_resource("imgurl:ftp://cdn.example.com/hero.png", None),
+            Err(ResourceParseError::InvalidUrl { .. })
+        ));
+        assert!(!parse_resource("https://example.com/page", None)
+            .unwrap()
+            .is_remote_image());
+    }
+
+    #[test]
+    fn test_parse_stdin_and_data_uri() {
+        let res = parse_resource("-", None).unwrap();
+        assert!(res.is_stdin());
+        assert!(res.local_image_path().is_none());
+        assert_eq!(res.display_value(), "stdin");
+
+        let res = parse_resource("data:image/png;base64,iVBORw0KGgo=", None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Image);
+        assert!(res.is_data_uri());
+        assert_eq!(res.display_value(), "data:image/png;base64,… (34 bytes)");
+
+        for bad in [
+            "data:image/png,rawbytes",
+            "data:text/plain;base64,aGk=",
+            "data:image/png;base64,",
+        ] {
+            assert!(matches!(
+                parse_resource(bad, None),
+                Err(ResourceParseError::InvalidDataUri { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_device_resources() {
+        for value in ["adb:", "adb:emulator-5554", "simctl:booted"] {
+            let res = parse_resource(value, None).unwrap();
+            assert_eq!(res.kind, ResourceKind::Device);
+            assert!(res.local_image_path().is_none());
+        }
+        let res = parse_resource("window:Acme Checkout", None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Window);
+    }
+
+    #[test]
+    fn test_parse_view_bundle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let value = format!("view:{}", dir.path().display());
+        assert!(matches!(
+            parse_resource(&value, None),
+            Err(ResourceParseError::FileNotFound { .. })
+        ));
+
+        std::fs::write(dir.path().join("view.json"), "{}").unwrap();
+        let res = parse_resource(&value, None).unwrap();
+        assert_eq!(res.kind, ResourceKind::View);
+        assert_eq!(res.view_bundle_dir(), Some(dir.path()));
+        assert!(res.local_image_path().is_none());
+    }
+
+    #[test]
+    fn test_parse_video_frame() {
+        let file = temp_file_with_extension("mp4");
+        let path = file.path().to_str().unwrap();
+        let res = parse_resource(&format!("{path}@00:05.2"), None).unwrap();
+        assert_eq!(res.kind, ResourceKind::Image);
+        assert_eq!(res.local_image_path(), Some(file.path()));
+        assert_eq!(
+            res.video_frame().unwrap().timestamp,
+            std::time::Duration::from_millis(5200)
+        );
+
+        assert!(matches!(
+            parse_resource(&format!("{path}@later"), None),
+            Err(ResourceParseError::InvalidVideoFrame { .. })
+        ));
+        assert!(matches!(
+            parse_resource("/tmp/missing-clip.mp4@1", None),
+            Err(ResourceParseError::FileNotFound { .. })
+        ));
+    }
+
     #[test]
     fn test_parse_local_png() {
         let file = temp_file_with_extension("png");
@@ -243,6 +772,26 @@ mod tests {
         assert!(matches!(res, Err(ResourceParseError::FileNotFound { .. })));
     }
 
+    #[test]
+    fn test_windows_paths_are_local_files() {
+        for value in [
+            r"C:\shots\ref.png",
+            r"\\?\C:\shots\ref.png",
+            r"\\build-server\share\shots\ref.png",
+        ] {
+            let res = parse_resource(value, None);
+            assert!(
+                matches!(&res, Err(ResourceParseError::FileNotFound { path }) if path == value),
+                "{value}: {res:?}"
+            );
+        }
+
+        let frame = VideoFrameRef::parse(r"\\build-server\share\clip.mp4@00:05")
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.path, Path::new(r"\\build-server\share\clip.mp4"));
+    }
+
     #[test]
     fn test_override_type() {
         let res = parse_resource("/some/path", Some(ResourceKind::Url)).unwrap();
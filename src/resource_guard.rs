@@ -0,0 +1,223 @@
+//! Compare-time resource guardrails (`[resources]`).
+//!
+//! Full-page screenshots decode to several bytes per pixel, and every metric
+//! keeps its own working copies, so a large comparison on a small CI runner
+//! can be OOM-killed without leaving any result behind. With guardrails
+//! configured, `compare` checks the memory available to the process (capped
+//! by the container's cgroup limit) and the CPU count once the inputs are
+//! captured. Under pressure it downscales the screenshots to at most
+//! `max_megapixels`, scores pixels in larger blocks, and reports the
+//! degradation in the output instead of failing.
+
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::GenericImageView;
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::error::{DpcError, Result};
+use crate::output::{DownscaledScreenshot, ImageSize};
+use crate::types::NormalizedView;
+
+const MIB: u64 = 1024 * 1024;
+
+/// When and how `compare` degrades its analysis (`[resources]`). Off unless
+/// `min_available_mb` or `min_cpus` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Degrade when less memory than this (MiB) is available.
+    pub min_available_mb: Option<u64>,
+    /// Degrade on machines with fewer CPUs than this.
+    pub min_cpus: Option<usize>,
+    /// Largest screenshot analyzed when degraded, in megapixels (default 4).
+    pub max_megapixels: Option<f32>,
+    /// Smallest pixel metric block size when degraded (default 64).
+    pub block_size: Option<u32>,
+}
+
+impl ResourceLimits {
+    pub const DEFAULT_MAX_MEGAPIXELS: f32 = 4.0;
+    pub const DEFAULT_BLOCK_SIZE: u32 = 64;
+
+    pub fn is_enabled(&self) -> bool {
+        self.min_available_mb.is_some() || self.min_cpus.is_some()
+    }
+
+    pub fn max_megapixels(&self) -> f32 {
+        self.max_megapixels.unwrap_or(Self::DEFAULT_MAX_MEGAPIXELS)
+    }
+
+    pub fn block_size(&self) -> u32 {
+        self.block_size.unwrap_or(Self::DEFAULT_BLOCK_SIZE)
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.min_cpus == Some(0) {
+            return Err("resources.min_cpus must be greater than zero".to_string());
+        }
+        if !(self.max_megapixels() > 0.0 && self.max_megapixels().is_finite()) {
+            return Err("resources.max_megapixels must be greater than zero".to_string());
+        }
+        if self.block_size() == 0 {
+            return Err("resources.block_size must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Why the comparison should degrade on this machine right now; `None`
+    /// when the guardrails are off or resources suffice.
+    pub fn pressure(&self) -> Option<String> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        self.pressure_with(available_memory(), cpus)
+    }
+
+    fn pressure_with(&self, available: Option<u64>, cpus: usize) -> Option<String> {
+        if let (Some(min), Some(available)) = (self.min_available_mb, available) {
+            if available < min.saturating_mul(MIB) {
+                return Some(format!(
+                    "{} MiB of memory available, below resources.min_available_mb ({min} MiB)",
+                    available / MIB
+                ));
+            }
+        }
+        match self.min_cpus {
+            Some(min) if cpus < min => Some(format!(
+                "{cpus} CPUs available, below resources.min_cpus ({min})"
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Memory available to this process in bytes: the system's available memory,
+/// capped by the free memory of the cgroup when running in a container.
+/// `None` on platforms that do not report it.
+pub fn available_memory() -> Option<u64> {
+    if !sysinfo::IS_SUPPORTED_SYSTEM {
+        return None;
+    }
+    let mut system = System::new();
+    system.refresh_memory();
+    let available = system.available_memory();
+    let limited = system
+        .cgroup_limits()
+        .map_or(available, |cgroup| cgroup.free_memory.min(available));
+    (limited > 0).then_some(limited)
+}
+
+/// Shrink the screenshot of `view` to at most `max_megapixels`, keeping its
+/// aspect ratio, and save it as `<prefix>_downscaled.png` in
+/// `artifacts_dir`. The view's CSS size is unchanged, so DOM boxes still line
+/// up. `None` when the screenshot is small enough already.
+pub fn downscale_view(
+    view: &NormalizedView,
+    max_megapixels: f32,
+    artifacts_dir: &Path,
+    prefix: &str,
+) -> Result<Option<(NormalizedView, DownscaledScreenshot)>> {
+    let (width, height) = image::image_dimensions(&view.screenshot_path)?;
+    let pixels = width as f64 * height as f64;
+    let max_pixels = (max_megapixels as f64 * 1_000_000.0).round();
+    if pixels <= max_pixels {
+        return Ok(None);
+    }
+    let scale = (max_pixels / pixels).sqrt();
+    let target = |side: u32| ((side as f64 * scale).floor() as u32).max(1);
+    let img = image::open(&view.screenshot_path)?;
+    let resized = img.resize_exact(target(width), target(height), FilterType::Triangle);
+    let path = artifacts_dir.join(format!("{prefix}_downscaled.png"));
+    resized
+        .save(&path)
+        .map_err(|err| DpcError::Config(format!("Failed to save {}: {err}", path.display())))?;
+
+    let (analyzed_width, analyzed_height) = resized.dimensions();
+    let mut downscaled = view.clone();
+    downscaled.screenshot_path = path;
+    Ok(Some((
+        downscaled,
+        DownscaledScreenshot {
+            side: prefix.to_string(),
+            original: ImageSize { width, height },
+            analyzed: ImageSize {
+                width: analyzed_width,
+                height: analyzed_height,
+            },
+        },
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResourceKind;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn pressure_reports_low_memory_and_cpu_count() {
+        let limits = ResourceLimits {
+            min_available_mb: Some(1024),
+            min_cpus: Some(2),
+            ..ResourceLimits::default()
+        };
+        assert_eq!(limits.pressure_with(Some(2048 * MIB), 4), None);
+        assert_eq!(
+            limits.pressure_with(Some(512 * MIB), 4).as_deref(),
+            Some("512 MiB of memory available, below resources.min_available_mb (1024 MiB)")
+        );
+        assert_eq!(
+            limits.pressure_with(None, 1).as_deref(),
+            Some("1 CPUs available, below resources.min_cpus (2)")
+        );
+        assert_eq!(ResourceLimits::default().pressure(), None);
+    }
+
+    #[test]
+    fn downscale_view_caps_the_screenshot_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.png");
+        RgbaImage::from_pixel(400, 100, Rgba([10, 20, 30, 255]))
+            .save(&path)
+            .unwrap();
+        let view = NormalizedView {
+            kind: ResourceKind::Image,
+            screenshot_path: path,
+            width: 400,
+            height: 100,
+            dom: None,
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        };
+
+        assert!(downscale_view(&view, 0.05, dir.path(), "ref")
+            .unwrap()
+            .is_none());
+        let (downscaled, report) = downscale_view(&view, 0.01, dir.path(), "ref")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            report.original,
+            ImageSize {
+                width: 400,
+                height: 100
+            }
+        );
+        assert_eq!(
+            report.analyzed,
+            ImageSize {
+                width: 200,
+                height: 50
+            }
+        );
+        assert_eq!(
+            image::image_dimensions(&downscaled.screenshot_path).unwrap(),
+            (200, 50)
+        );
+        assert_eq!((downscaled.width, downscaled.height), (400, 100));
+    }
+}
@@ -0,0 +1,353 @@
+//! Differences between two compare results.
+//!
+//! PR workflows usually care about what changed since the last run rather
+//! than absolute scores: which metrics improved or regressed, and which
+//! findings appeared or disappeared. Findings are matched by their
+//! fingerprints (see [`suppression`](crate::suppression)), so a diff that only
+//! moved by rendering noise counts as unchanged.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::accepted::Finding;
+use crate::metrics::{metric_score, MetricKind};
+use crate::output::CompareOutput;
+use crate::suppression::{
+    color_fingerprint, layout_fingerprint, pixel_fingerprint, text_fingerprint,
+    typography_fingerprint,
+};
+use crate::types::MetricScores;
+
+/// Score changes smaller than this count as unchanged.
+pub const SCORE_EPSILON: f32 = 0.001;
+
+/// What changed from an older compare result to a newer one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultDiff {
+    pub old_similarity: f32,
+    pub new_similarity: f32,
+    pub similarity_delta: f32,
+    pub old_passed: bool,
+    pub new_passed: bool,
+    /// Every metric present in either result, in the usual metric order.
+    pub metrics: Vec<MetricChange>,
+    /// Findings of the new result whose fingerprint the old one lacks.
+    pub appeared: Vec<ChangedFinding>,
+    /// Findings of the old result whose fingerprint the new one lacks.
+    pub disappeared: Vec<ChangedFinding>,
+    /// Findings present in both results.
+    pub unchanged_findings: usize,
+}
+
+/// Score of one metric in both results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricChange {
+    pub metric: MetricKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<f32>,
+    pub trend: MetricTrend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricTrend {
+    Improved,
+    Regressed,
+    Unchanged,
+    /// Only the new result has the metric.
+    Added,
+    /// Only the old result has the metric.
+    Removed,
+}
+
+/// A finding that appeared or disappeared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFinding {
+    pub fingerprint: String,
+    pub metric: MetricKind,
+    pub description: String,
+}
+
+impl ResultDiff {
+    /// Whether anything got worse: a metric regressed or was dropped, or a
+    /// finding appeared.
+    pub fn has_regressions(&self) -> bool {
+        !self.appeared.is_empty()
+            || self
+                .metrics
+                .iter()
+                .any(|m| matches!(m.trend, MetricTrend::Regressed | MetricTrend::Removed))
+    }
+}
+
+/// Compare two compare results, `old` being the earlier run.
+pub fn diff_results(old: &CompareOutput, new: &CompareOutput) -> ResultDiff {
+    let metrics = MetricKind::all()
+        .into_iter()
+        .filter_map(|metric| {
+            let (old, new) = (
+                metric_score(&old.metrics, metric),
+                metric_score(&new.metrics, metric),
+            );
+            let trend = match (old, new) {
+                (None, None) => return None,
+                (None, Some(_)) => MetricTrend::Added,
+                (Some(_), None) => MetricTrend::Removed,
+                (Some(a), Some(b)) if b - a >= SCORE_EPSILON => MetricTrend::Improved,
+                (Some(a), Some(b)) if a - b >= SCORE_EPSILON => MetricTrend::Regressed,
+                (Some(_), Some(_)) => MetricTrend::Unchanged,
+            };
+            Some(MetricChange {
+                metric,
+                old,
+                new,
+                trend,
+            })
+        })
+        .collect();
+
+    let old_findings = fingerprinted_findings(&old.metrics);
+    let new_findings = fingerprinted_findings(&new.metrics);
+    let old_prints: BTreeSet<&str> = old_findings
+        .iter()
+        .map(|f| f.fingerprint.as_str())
+        .collect();
+    let new_prints: BTreeSet<&str> = new_findings
+        .iter()
+        .map(|f| f.fingerprint.as_str())
+        .collect();
+    let unchanged_findings = old_prints.intersection(&new_prints).count();
+    let appeared = only_in(&new_findings, &old_prints);
+    let disappeared = only_in(&old_findings, &new_prints);
+
+    ResultDiff {
+        old_similarity: old.similarity,
+        new_similarity: new.similarity,
+        similarity_delta: new.similarity - old.similarity,
+        old_passed: old.passed,
+        new_passed: new.passed,
+        metrics,
+        appeared,
+        disappeared,
+        unchanged_findings,
+    }
+}
+
+/// Findings of `findings` whose fingerprint is not in `other`, once each.
+fn only_in(findings: &[ChangedFinding], other: &BTreeSet<&str>) -> Vec<ChangedFinding> {
+    let mut seen = BTreeSet::new();
+    findings
+        .iter()
+        .filter(|f| !other.contains(f.fingerprint.as_str()))
+        .filter(|f| seen.insert(f.fingerprint.clone()))
+        .cloned()
+        .collect()
+}
+
+/// Every finding with its fingerprint: the one stored in the result, or
+/// computed for results written before fingerprints were recorded.
+fn fingerprinted_findings(scores: &MetricScores) -> Vec<ChangedFinding> {
+    let mut prints = Vec::new();
+    if let Some(pixel) = &scores.pixel {
+        prints.extend(pixel.diff_regions.iter().map(|r| {
+            let print = r
+                .fingerprint
+                .clone()
+                .unwrap_or_else(|| pixel_fingerprint(r));
+            (MetricKind::Pixel, print)
+        }));
+    }
+    if let Some(layout) = &scores.layout {
+        prints.extend(layout.diff_regions.iter().map(|r| {
+            let print = r
+                .fingerprint
+                .clone()
+                .unwrap_or_else(|| layout_fingerprint(r));
+            (MetricKind::Layout, print)
+        }));
+    }
+    if let Some(typography) = &scores.typography {
+        prints.extend(typography.diffs.iter().map(|d| {
+            let print = d
+                .fingerprint
+                .clone()
+                .unwrap_or_else(|| typography_fingerprint(d));
+            (MetricKind::Typography, print)
+        }));
+    }
+    if let Some(color) = &scores.color {
+        prints.extend(color.diffs.iter().map(|d| {
+            let print = d
+                .fingerprint
+                .clone()
+                .unwrap_or_else(|| color_fingerprint(d));
+            (MetricKind::Color, print)
+        }));
+    }
+    if let Some(content) = &scores.content {
+        for (kind, texts, stored) in [
+            (
+                "missing_text",
+                &content.missing_text,
+                &content.missing_text_fingerprints,
+            ),
+            (
+                "extra_text",
+                &content.extra_text,
+                &content.extra_text_fingerprints,
+            ),
+        ] {
+            prints.extend(texts.iter().enumerate().map(|(idx, text)| {
+                let print = stored
+                    .get(idx)
+                    .cloned()
+                    .unwrap_or_else(|| text_fingerprint(kind, text));
+                (MetricKind::Content, print)
+            }));
+        }
+    }
+
+    // `Finding::collect` lists findings in the same order as above.
+    Finding::collect(scores)
+        .into_iter()
+        .zip(prints)
+        .map(|(finding, (metric, fingerprint))| ChangedFinding {
+            fingerprint,
+            metric,
+            description: finding.describe(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ResourceDescriptor;
+    use crate::types::{
+        ContentMetric, DiffSeverity, PixelDiffReason, PixelDiffRegion, PixelMetric, ResourceKind,
+        Viewport,
+    };
+
+    fn region(x: f32) -> PixelDiffRegion {
+        PixelDiffRegion {
+            x,
+            y: 0.5,
+            width: 0.1,
+            height: 0.1,
+            severity: DiffSeverity::Major,
+            reason: PixelDiffReason::PixelChange,
+            intensity: Some(0.4),
+            max_intensity: None,
+            pixel_bounds: None,
+            css_bounds: None,
+            fingerprint: None,
+        }
+    }
+
+    fn result(pixel: f32, regions: Vec<PixelDiffRegion>, content: Option<f32>) -> CompareOutput {
+        let descriptor = |value: &str| ResourceDescriptor {
+            kind: ResourceKind::Image,
+            value: value.to_string(),
+        };
+        CompareOutput {
+            version: crate::output::DPC_OUTPUT_VERSION.to_string(),
+            ref_resource: descriptor("ref.png"),
+            impl_resource: descriptor("impl.png"),
+            viewport: Viewport {
+                width: 100,
+                height: 100,
+                ..Viewport::default()
+            },
+            browser: None,
+            emulation: None,
+            similarity: pixel,
+            threshold: 0.95,
+            passed: pixel >= 0.95,
+            metrics: MetricScores {
+                pixel: Some(PixelMetric {
+                    score: pixel,
+                    diff_regions: regions,
+                    semantic_diffs: None,
+                    offset: None,
+                }),
+                layout: None,
+                typography: None,
+                color: None,
+                content: content.map(|score| ContentMetric {
+                    score,
+                    missing_text: vec!["Sign up".to_string()],
+                    extra_text: Vec::new(),
+                    placeholder_text: Vec::new(),
+                    text_labels: Default::default(),
+                    missing_text_fingerprints: Vec::new(),
+                    extra_text_fingerprints: Vec::new(),
+                }),
+                semantic: None,
+            },
+            summary: None,
+            artifacts: None,
+            reference_candidates: None,
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            page_diagnostics: None,
+            skipped_metrics: Vec::new(),
+            sampling: None,
+            dimension_mismatch: None,
+            sections: Vec::new(),
+            degradation: None,
+            figma_comments: Vec::new(),
+            variable_drift: None,
+        }
+    }
+
+    #[test]
+    fn diff_reports_metric_trends_and_finding_changes() {
+        let old = result(0.9, vec![region(0.1), region(0.5)], Some(0.5));
+        let mut new = result(0.95, vec![region(0.5), region(0.8)], None);
+        // A stored fingerprint wins over the recomputed one.
+        new.metrics.pixel.as_mut().unwrap().diff_regions[0].fingerprint =
+            Some(pixel_fingerprint(&region(0.5)));
+
+        let diff = diff_results(&old, &new);
+        assert!((diff.similarity_delta - 0.05).abs() < 1e-6);
+        assert!(!diff.old_passed && diff.new_passed);
+        let trends: Vec<_> = diff.metrics.iter().map(|m| (m.metric, m.trend)).collect();
+        assert_eq!(
+            trends,
+            vec![
+                (MetricKind::Pixel, MetricTrend::Improved),
+                (MetricKind::Content, MetricTrend::Removed),
+            ]
+        );
+        assert_eq!(diff.unchanged_findings, 1);
+        assert_eq!(diff.appeared.len(), 1);
+        assert_eq!(
+            diff.appeared[0].fingerprint,
+            pixel_fingerprint(&region(0.8))
+        );
+        assert!(diff.appeared[0].description.starts_with("pixel:"));
+        let gone: Vec<_> = diff.disappeared.iter().map(|f| f.metric).collect();
+        assert_eq!(gone, vec![MetricKind::Pixel, MetricKind::Content]);
+        assert!(diff.has_regressions());
+    }
+
+    #[test]
+    fn identical_results_have_no_changes() {
+        let old = result(0.97, vec![region(0.2)], Some(0.8));
+        let diff = diff_results(&old, &old.clone());
+        assert!(diff.appeared.is_empty() && diff.disappeared.is_empty());
+        assert_eq!(diff.unchanged_findings, 2);
+        assert!(diff
+            .metrics
+            .iter()
+            .all(|m| m.trend == MetricTrend::Unchanged));
+        assert!(!diff.has_regressions());
+    }
+}
@@ -0,0 +1,472 @@
+//! Named page sections scored on their own (`--sections`).
+//!
+//! One blended similarity hides where a page drifted. A sections file names
+//! parts of the page, by CSS selector or by rectangle; each is cropped out of
+//! both screenshots and scored separately, so a report can say the hero
+//! matches while the footer doesn't.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView};
+use serde::Deserialize;
+
+use crate::dom_selector::{DomIndex, SelectorList};
+use crate::output::CompareOutput;
+use crate::types::{BoundingBox, NormalizedView};
+use crate::{DpcError, Result};
+
+/// A named part of the page, located by `selector` or by `region`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Section {
+    pub name: String,
+    /// CSS selector; the section covers every matched element.
+    #[serde(default)]
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub region: Option<SectionRegion>,
+}
+
+/// A section rectangle.
+///
+/// Coordinates are normalized (0.0-1.0) when every value fits that range,
+/// otherwise they are reference screenshot pixels — the same rule as ignore
+/// regions.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SectionRegion {
+    pub x: f32,
+    pub y: f32,
+    #[serde(alias = "w")]
+    pub width: f32,
+    #[serde(alias = "h")]
+    pub height: f32,
+}
+
+impl SectionRegion {
+    fn is_normalized(&self) -> bool {
+        [self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|v| (0.0..=1.0).contains(v))
+    }
+}
+
+/// Load a JSON array of `{name, selector}` or `{name, region: {x,y,width,height}}`.
+pub fn load_sections(path: &Path) -> Result<Vec<Section>> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| DpcError::Config(format!("Failed to read sections: {e}")))?;
+    let sections: Vec<Section> = serde_json::from_str(&data).map_err(|e| {
+        DpcError::Config(format!(
+            "Invalid sections JSON (expected array of {{name, selector}} or {{name, region}}): {e}"
+        ))
+    })?;
+    validate_sections(&sections)?;
+    Ok(sections)
+}
+
+/// Reject unnamed or duplicate sections, sections without exactly one of
+/// `selector` and `region`, and selectors the selector engine cannot parse.
+pub fn validate_sections(sections: &[Section]) -> Result<()> {
+    let mut names = BTreeSet::new();
+    for section in sections {
+        let name = section.name.trim();
+        if name.is_empty() {
+            return Err(DpcError::Config("section name must not be empty".into()));
+        }
+        if !names.insert(name) {
+            return Err(DpcError::Config(format!("duplicate section '{name}'")));
+        }
+        match (&section.selector, &section.region) {
+            (Some(selector), None) => {
+                SelectorList::parse(selector)?;
+            }
+            (None, Some(region)) => {
+                if region.width <= 0.0 || region.height <= 0.0 {
+                    return Err(DpcError::Config(format!(
+                        "section '{name}' region must have a positive width and height"
+                    )));
+                }
+            }
+            _ => {
+                return Err(DpcError::Config(format!(
+                    "section '{name}' needs exactly one of \"selector\" or \"region\""
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Context kept around each pixel diff region by [`failing_regions`], as a
+/// fraction of the page.
+const REGION_MARGIN: f32 = 0.01;
+
+/// What failed in a previous compare result, as sections for a focused re-run
+/// (`--only-regions`): its failing sections under their own names, then each
+/// pixel diff region, slightly padded, as `diff-region-<n>`.
+pub fn failing_regions(result: &CompareOutput) -> Vec<Section> {
+    let sections = result
+        .sections
+        .iter()
+        .filter(|section| !section.passed)
+        .map(|section| Section {
+            name: section.name.clone(),
+            selector: None,
+            region: Some(SectionRegion {
+                x: section.bounds.x,
+                y: section.bounds.y,
+                width: section.bounds.width,
+                height: section.bounds.height,
+            }),
+        });
+    let diff_regions = result
+        .metrics
+        .pixel
+        .iter()
+        .flat_map(|pixel| &pixel.diff_regions)
+        .enumerate()
+        .map(|(idx, region)| {
+            let x = (region.x - REGION_MARGIN).max(0.0);
+            let y = (region.y - REGION_MARGIN).max(0.0);
+            Section {
+                name: format!("diff-region-{}", idx + 1),
+                selector: None,
+                region: Some(SectionRegion {
+                    x,
+                    y,
+                    width: (region.x + region.width + REGION_MARGIN).min(1.0) - x,
+                    height: (region.y + region.height + REGION_MARGIN).min(1.0) - y,
+                }),
+            }
+        });
+    sections.chain(diff_regions).collect()
+}
+
+/// Bounds of a section in a screenshot, in pixels `(x, y, width, height)`.
+pub type PixelRect = (u32, u32, u32, u32);
+
+impl Section {
+    /// Bounds of the section in the reference and implementation screenshots.
+    ///
+    /// A selector matched on only one side is mapped proportionally onto the
+    /// other screenshot; `None` when it matches nothing on either side.
+    pub fn bounds(
+        &self,
+        reference: &NormalizedView,
+        ref_size: (u32, u32),
+        implementation: &NormalizedView,
+        impl_size: (u32, u32),
+    ) -> Option<(PixelRect, PixelRect)> {
+        if let Some(region) = &self.region {
+            let ref_rect = region_rect(region, ref_size, ref_size)?;
+            let impl_rect = region_rect(region, ref_size, impl_size)?;
+            return Some((ref_rect, impl_rect));
+        }
+        let selector = SelectorList::parse(self.selector.as_deref()?).ok()?;
+        let ref_rect = selector_rect(&selector, reference, ref_size);
+        let impl_rect = selector_rect(&selector, implementation, impl_size);
+        match (ref_rect, impl_rect) {
+            (Some(r), Some(i)) => Some((r, i)),
+            (Some(r), None) => Some((r, rescale(r, ref_size, impl_size)?)),
+            (None, Some(i)) => Some((rescale(i, impl_size, ref_size)?, i)),
+            (None, None) => None,
+        }
+    }
+}
+
+fn region_rect(
+    region: &SectionRegion,
+    ref_size: (u32, u32),
+    size: (u32, u32),
+) -> Option<PixelRect> {
+    let (x, y, w, h) = if region.is_normalized() {
+        (region.x, region.y, region.width, region.height)
+    } else {
+        let (rw, rh) = (ref_size.0.max(1) as f32, ref_size.1.max(1) as f32);
+        (
+            region.x / rw,
+            region.y / rh,
+            region.width / rw,
+            region.height / rh,
+        )
+    };
+    clamp_rect(x, y, w, h, size)
+}
+
+/// Union of the boxes of every DOM node the selector matches, scaled from
+/// view units to the screenshot.
+fn selector_rect(
+    selector: &SelectorList,
+    view: &NormalizedView,
+    size: (u32, u32),
+) -> Option<PixelRect> {
+    let dom = view.dom.as_ref()?;
+    let index = DomIndex::new(&dom.nodes);
+    let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for node in dom.nodes.iter().filter(|n| selector.matches(n, &index)) {
+        let b = node.bounding_box;
+        if b.width <= 0.0 || b.height <= 0.0 {
+            continue;
+        }
+        x0 = x0.min(b.x);
+        y0 = y0.min(b.y);
+        x1 = x1.max(b.x + b.width);
+        y1 = y1.max(b.y + b.height);
+    }
+    if x0 > x1 || view.width == 0 || view.height == 0 {
+        return None;
+    }
+    let (vw, vh) = (view.width as f32, view.height as f32);
+    clamp_rect(x0 / vw, y0 / vh, (x1 - x0) / vw, (y1 - y0) / vh, size)
+}
+
+fn rescale(rect: PixelRect, from: (u32, u32), to: (u32, u32)) -> Option<PixelRect> {
+    let (fw, fh) = (from.0.max(1) as f32, from.1.max(1) as f32);
+    let (x, y, w, h) = rect;
+    clamp_rect(
+        x as f32 / fw,
+        y as f32 / fh,
+        w as f32 / fw,
+        h as f32 / fh,
+        to,
+    )
+}
+
+/// A normalized rectangle as pixels of an image of `size`, clipped to it.
+fn clamp_rect(x: f32, y: f32, w: f32, h: f32, size: (u32, u32)) -> Option<PixelRect> {
+    let (width, height) = size;
+    let left = ((x * width as f32).round().max(0.0) as u32).min(width);
+    let top = ((y * height as f32).round().max(0.0) as u32).min(height);
+    let right = (((x + w) * width as f32).round().max(0.0) as u32).min(width);
+    let bottom = (((y + h) * height as f32).round().max(0.0) as u32).min(height);
+    (right > left && bottom > top).then_some((left, top, right - left, bottom - top))
+}
+
+/// The part of `view` inside `rect` (pixels of `image`), with its screenshot
+/// saved to `path`.
+///
+/// DOM, Figma and OCR nodes whose center lies inside the section are kept and
+/// moved so the section's top-left corner is the origin.
+pub fn section_view(
+    view: &NormalizedView,
+    image: &DynamicImage,
+    rect: PixelRect,
+    path: &Path,
+) -> Result<NormalizedView> {
+    let (image_w, image_h) = image.dimensions();
+    let (x, y, w, h) = rect;
+    image
+        .crop_imm(x, y, w, h)
+        .save(path)
+        .map_err(|e| DpcError::Config(format!("Failed to save section screenshot: {e}")))?;
+
+    let sx = view.width as f32 / image_w.max(1) as f32;
+    let sy = view.height as f32 / image_h.max(1) as f32;
+    let bounds = BoundingBox {
+        x: x as f32 * sx,
+        y: y as f32 * sy,
+        width: w as f32 * sx,
+        height: h as f32 * sy,
+    };
+    let inside = |b: &BoundingBox| {
+        let (cx, cy) = (b.x + b.width / 2.0, b.y + b.height / 2.0);
+        cx >= bounds.x
+            && cx < bounds.x + bounds.width
+            && cy >= bounds.y
+            && cy < bounds.y + bounds.height
+    };
+    let shift = |b: &mut BoundingBox| {
+        b.x -= bounds.x;
+        b.y -= bounds.y;
+    };
+
+    let mut section = view.clone();
+    section.screenshot_path = path.to_path_buf();
+    section.width = bounds.width.round().max(1.0) as u32;
+    section.height = bounds.height.round().max(1.0) as u32;
+    if let Some(dom) = section.dom.as_mut() {
+        dom.nodes.retain(|n| inside(&n.bounding_box));
+        dom.nodes
+            .iter_mut()
+            .for_each(|n| shift(&mut n.bounding_box));
+    }
+    if let Some(tree) = section.figma_tree.as_mut() {
+        tree.nodes.retain(|n| inside(&n.layout_box()));
+        for node in &mut tree.nodes {
+            shift(&mut node.bounding_box);
+            if let Some(b) = node.responsive_box.as_mut() {
+                shift(b);
+            }
+        }
+    }
+    if let Some(blocks) = section.ocr_blocks.as_mut() {
+        blocks.retain(|b| inside(&b.bounding_box));
+        blocks.iter_mut().for_each(|b| shift(&mut b.bounding_box));
+    }
+    section.content_area = view.content_area.and_then(|area| {
+        let left = area.x.max(bounds.x);
+        let top = area.y.max(bounds.y);
+        let right = (area.x + area.width).min(bounds.x + bounds.width);
+        let bottom = (area.y + area.height).min(bounds.y + bounds.height);
+        (right > left && bottom > top).then_some(BoundingBox {
+            x: left - bounds.x,
+            y: top - bounds.y,
+            width: right - left,
+            height: bottom - top,
+        })
+    });
+    Ok(section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DomNode, DomSnapshot, ResourceKind};
+    use std::collections::HashMap;
+
+    fn node(id: &str, class: &str, bbox: (f32, f32, f32, f32)) -> DomNode {
+        DomNode {
+            id: id.to_string(),
+            tag: "div".to_string(),
+            children: Vec::new(),
+            parent: None,
+            attributes: HashMap::from([("class".to_string(), class.to_string())]),
+            text: None,
+            bounding_box: BoundingBox {
+                x: bbox.0,
+                y: bbox.1,
+                width: bbox.2,
+                height: bbox.3,
+            },
+            text_baseline: None,
+            interaction_states: None,
+            computed_style: None,
+        }
+    }
+
+    fn view(nodes: Vec<DomNode>) -> NormalizedView {
+        NormalizedView {
+            kind: ResourceKind::Url,
+            screenshot_path: "shot.png".into(),
+            width: 100,
+            height: 200,
+            dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
+                url: None,
+                title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
+                nodes,
+            }),
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        }
+    }
+
+    fn sections(json: &str) -> Result<Vec<Section>> {
+        let sections: Vec<Section> = serde_json::from_str(json).unwrap();
+        validate_sections(&sections).map(|_| sections)
+    }
+
+    #[test]
+    fn sections_need_unique_names_and_one_locator() {
+        assert!(sections(r#"[{"name": "hero", "selector": ".hero"}]"#).is_ok());
+        for bad in [
+            r#"[{"name": "hero", "selector": ".hero"}, {"name": "hero", "selector": "footer"}]"#,
+            r#"[{"name": " ", "selector": ".hero"}]"#,
+            r#"[{"name": "hero"}]"#,
+            r#"[{"name": "hero", "selector": ".hero", "region": {"x": 0, "y": 0, "w": 1, "h": 1}}]"#,
+            r#"[{"name": "hero", "selector": "div["}]"#,
+            r#"[{"name": "hero", "region": {"x": 0, "y": 0, "w": 0, "h": 1}}]"#,
+        ] {
+            assert!(sections(bad).is_err(), "accepted {bad}");
+        }
+    }
+
+    #[test]
+    fn section_bounds_follow_selectors_and_regions() {
+        let reference = view(vec![
+            node("1", "hero", (0.0, 0.0, 100.0, 50.0)),
+            node("2", "footer", (0.0, 180.0, 100.0, 20.0)),
+        ]);
+        let implementation = view(vec![node("1", "hero", (0.0, 10.0, 100.0, 50.0))]);
+        let all = sections(
+            r#"[
+                {"name": "hero", "selector": ".hero"},
+                {"name": "footer", "selector": ".footer"},
+                {"name": "nav", "selector": ".nav"},
+                {"name": "middle", "region": {"x": 0, "y": 0.5, "width": 1, "height": 0.25}},
+                {"name": "px", "region": {"x": 10, "y": 20, "width": 50, "height": 100}}
+            ]"#,
+        )
+        .unwrap();
+        // Screenshots at 2x the view's units.
+        let bounds: Vec<_> = all
+            .iter()
+            .map(|s| s.bounds(&reference, (200, 400), &implementation, (100, 200)))
+            .collect();
+        assert_eq!(bounds[0], Some(((0, 0, 200, 100), (0, 10, 100, 50))));
+        // Missing on the implementation: mapped proportionally.
+        assert_eq!(bounds[1], Some(((0, 360, 200, 40), (0, 180, 100, 20))));
+        assert_eq!(bounds[2], None);
+        assert_eq!(bounds[3], Some(((0, 200, 200, 100), (0, 100, 100, 50))));
+        assert_eq!(bounds[4], Some(((10, 20, 50, 100), (5, 10, 25, 50))));
+    }
+
+    #[test]
+    fn section_view_keeps_and_shifts_nodes_inside() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = view(vec![
+            node("1", "hero", (0.0, 0.0, 100.0, 50.0)),
+            node("2", "card", (10.0, 110.0, 40.0, 20.0)),
+        ]);
+        let image = DynamicImage::new_rgb8(200, 400);
+        let path = dir.path().join("section.png");
+        let section = section_view(&source, &image, (0, 200, 200, 100), &path).unwrap();
+        assert_eq!((section.width, section.height), (100, 50));
+        assert_eq!(image::open(&path).unwrap().dimensions(), (200, 100));
+        let nodes = &section.dom.unwrap().nodes;
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id, "2");
+        assert_eq!(
+            (nodes[0].bounding_box.x, nodes[0].bounding_box.y),
+            (10.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn failing_regions_take_failed_sections_and_padded_diff_regions() {
+        let result: CompareOutput = serde_json::from_value(serde_json::json!({
+            "version": "1",
+            "ref": {"kind": "image", "value": "ref.png"},
+            "impl": {"kind": "image", "value": "impl.png"},
+            "viewport": {"width": 100, "height": 200},
+            "similarity": 0.8,
+            "threshold": 0.95,
+            "passed": false,
+            "metrics": {"pixel": {"score": 0.8, "diffRegions": [
+                {"x": 0.5, "y": 0.0, "width": 0.2, "height": 0.1,
+                 "severity": "major", "reason": "pixel_change"}
+            ]}},
+            "sections": [
+                {"name": "hero", "bounds": {"x": 0, "y": 0, "width": 100, "height": 50},
+                 "similarity": 0.99, "passed": true, "metrics": {}},
+                {"name": "footer", "bounds": {"x": 0, "y": 180, "width": 100, "height": 20},
+                 "similarity": 0.7, "passed": false, "metrics": {}}
+            ]
+        }))
+        .unwrap();
+
+        let regions = failing_regions(&result);
+        let names: Vec<&str> = regions.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["footer", "diff-region-1"]);
+        assert!(validate_sections(&regions).is_ok());
+        let bounds: Vec<_> = regions
+            .iter()
+            .map(|s| s.bounds(&view(vec![]), (100, 200), &view(vec![]), (100, 200)))
+            .collect();
+        assert_eq!(bounds[0], Some(((0, 180, 100, 20), (0, 180, 100, 20))));
+        assert_eq!(bounds[1], Some(((49, 0, 22, 22), (49, 0, 22, 22))));
+    }
+}
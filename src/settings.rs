@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use dpc_lib::image_alignment::ImageAlignmentOptions;
+use dpc_lib::image_alignment::{AlignmentMethod, ImageAlignmentOptions};
 use dpc_lib::types::Viewport;
 use dpc_lib::{Config, DpcError, ScoreWeights};
 
@@ -55,6 +55,7 @@ pub fn resolve_compare_settings(
     cli_pixel_align: Option<bool>,
     cli_pixel_align_max_shift: Option<u32>,
     cli_pixel_align_downscale: Option<u32>,
+    cli_pixel_align_method: Option<AlignmentMethod>,
     config: &Config,
     flags: &CompareFlagSources,
 ) -> ResolvedCompareSettings {
@@ -64,6 +65,7 @@ pub fn resolve_compare_settings(
         typography: config.metric_weights.typography,
         color: config.metric_weights.color,
         content: config.metric_weights.content,
+        semantic: config.metric_weights.semantic,
     };
 
     let pixel_alignment = ImageAlignmentOptions {
@@ -71,6 +73,7 @@ pub fn resolve_compare_settings(
         max_shift: cli_pixel_align_max_shift.unwrap_or(config.pixel_alignment.max_shift),
         downscale_max_dim: cli_pixel_align_downscale
             .unwrap_or(config.pixel_alignment.downscale_max_dim),
+        method: cli_pixel_align_method.unwrap_or(config.pixel_alignment.method),
     };
 
     ResolvedCompareSettings {
@@ -140,7 +143,7 @@ pub fn log_effective_config(
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|| "defaults/built-in".to_string());
     eprintln!(
-        "Effective config (source: {}): viewport {}x{}, threshold {:.2}, timeouts nav {}s / idle {}s / process {}s, weights pixel {:.2}, layout {:.2}, typography {:.2}, color {:.2}, content {:.2}, pixel_align {} (max_shift {}, downscale {})",
+        "Effective config (source: {}): viewport {}x{}, threshold {:.2}, timeouts nav {}s / idle {}s / process {}s, weights pixel {:.2}, layout {:.2}, typography {:.2}, color {:.2}, content {:.2}, semantic {:.2}, pixel_align {} (max_shift {}, downscale {}, method {})",
         config_source,
         viewport.width,
         viewport.height,
@@ -153,9 +156,11 @@ pub fn log_effective_config(
         weights.typography,
         weights.color,
         weights.content,
+        weights.semantic,
         pixel_alignment.enabled,
         pixel_alignment.max_shift,
-        pixel_alignment.downscale_max_dim
+        pixel_alignment.downscale_max_dim,
+        pixel_alignment.method.as_str()
     );
 }
 
@@ -175,7 +180,7 @@ pub fn format_effective_config(
         .map(|p| p.display().to_string())
         .unwrap_or_else(|| "defaults".to_string());
     format!(
-        "Effective config [{source}]: viewport={}x{}, threshold={:.2}, timeouts: nav={}s, network-idle={}s, process={}s, weights: pixel={:.2}, layout={:.2}, typography={:.2}, color={:.2}, content={:.2}, pixel_align={} (max_shift {}, downscale {})",
+        "Effective config [{source}]: viewport={}x{}, threshold={:.2}, timeouts: nav={}s, network-idle={}s, process={}s, weights: pixel={:.2}, layout={:.2}, typography={:.2}, color={:.2}, content={:.2}, semantic={:.2}, pixel_align={} (max_shift {}, downscale {}, method {})",
         viewport.width,
         viewport.height,
         threshold,
@@ -187,25 +192,28 @@ pub fn format_effective_config(
         weights.typography,
         weights.color,
         weights.content,
+        weights.semantic,
         pixel_alignment.enabled,
         pixel_alignment.max_shift,
-        pixel_alignment.downscale_max_dim
+        pixel_alignment.downscale_max_dim,
+        pixel_alignment.method.as_str()
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dpc_lib::config::{MetricWeights, PixelAlignmentConfig, SemanticConfig, Timeouts};
+    use dpc_lib::config::{
+        DomConfig, FigmaConfig, MetricWeights, MetricsConfig, PixelAlignmentConfig, QualityConfig,
+        SemanticConfig, SeverityConfig, SummarizerConfig, Timeouts,
+    };
+    use dpc_lib::{ArtifactEncoding, RequestRules, ResourceLimits};
     use std::time::Duration;
 
     #[test]
     fn resolve_compare_settings_prefers_config_when_flags_absent() {
         let cfg = Config {
-            viewport: Viewport {
-                width: 111,
-                height: 222,
-            },
+            viewport: Viewport::new(111, 222),
             threshold: 0.5,
             metric_weights: MetricWeights {
                 pixel: 1.0,
@@ -213,6 +221,7 @@ mod tests {
                 typography: 3.0,
                 color: 4.0,
                 content: 5.0,
+                semantic: 6.0,
             },
             timeouts: Timeouts {
                 navigation: Duration::from_secs(5),
@@ -220,14 +229,20 @@ mod tests {
                 process: Duration::from_secs(7),
             },
             semantic: SemanticConfig::default(),
+            summarizer: SummarizerConfig::default(),
             pixel_alignment: PixelAlignmentConfig::default(),
+            severity: SeverityConfig::default(),
+            artifacts: ArtifactEncoding::default(),
+            requests: RequestRules::default(),
+            dom: DomConfig::default(),
+            metrics: MetricsConfig::default(),
+            resources: ResourceLimits::default(),
+            figma: FigmaConfig::default(),
+            quality: QualityConfig::default(),
         };
         let flags = CompareFlagSources::default();
         let resolved = resolve_compare_settings(
-            Viewport {
-                width: 999,
-                height: 999,
-            },
+            Viewport::new(999, 999),
             0.9,
             30,
             10,
@@ -235,6 +250,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             &cfg,
             &flags,
         );
@@ -247,6 +263,7 @@ mod tests {
         assert_eq!(resolved.process_timeout, 7);
         assert!((resolved.weights.pixel - 1.0).abs() < f32::EPSILON);
         assert!((resolved.weights.content - 5.0).abs() < f32::EPSILON);
+        assert!((resolved.weights.semantic - 6.0).abs() < f32::EPSILON);
         assert!(!resolved.pixel_alignment.enabled);
     }
 
@@ -261,10 +278,7 @@ mod tests {
             process_timeout: true,
         };
         let resolved = resolve_compare_settings(
-            Viewport {
-                width: 10,
-                height: 20,
-            },
+            Viewport::new(10, 20),
             0.9,
             50,
             60,
@@ -272,6 +286,7 @@ mod tests {
             Some(true),
             Some(12),
             Some(128),
+            Some(AlignmentMethod::PhaseCorrelation),
             &cfg,
             &flags,
         );
@@ -285,15 +300,16 @@ mod tests {
         assert!(resolved.pixel_alignment.enabled);
         assert_eq!(resolved.pixel_alignment.max_shift, 12);
         assert_eq!(resolved.pixel_alignment.downscale_max_dim, 128);
+        assert_eq!(
+            resolved.pixel_alignment.method,
+            AlignmentMethod::PhaseCorrelation
+        );
     }
 
     #[test]
     fn format_effective_config_includes_all_fields() {
         let summary = format_effective_config(
-            &Viewport {
-                width: 1280,
-                height: 720,
-            },
+            &Viewport::new(1280, 720),
             0.9,
             12,
             8,
@@ -304,6 +320,7 @@ mod tests {
                 typography: 0.2,
                 color: 0.15,
                 content: 0.1,
+                semantic: 0.1,
             },
             &ImageAlignmentOptions::default(),
             Some(Path::new("dpc.toml")),
@@ -318,6 +335,7 @@ mod tests {
         assert!(summary.contains("typography=0.20"));
         assert!(summary.contains("color=0.15"));
         assert!(summary.contains("content=0.10"));
+        assert!(summary.contains("method search"));
         assert!(summary.contains("dpc.toml"));
     }
 }
@@ -0,0 +1,295 @@
+//! Narrative summaries of compare findings.
+//!
+//! The default summary is deterministic: a status line followed by the top
+//! findings, worded by templates. A [`Summarizer`] turns the same structured
+//! findings into a short prioritized narrative instead, which `dpc compare
+//! --summarize` stores as `summary.narrative` and shows in place of the
+//! template lines in the pretty output and the markdown report.
+//! [`ApiSummarizer`] asks an OpenAI-compatible chat completions API.
+
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::config::SummarizerConfig;
+use crate::error::{DpcError, Result};
+use crate::metrics::{generate_top_issues_with, metric_score, MetricKind, SeverityCalibration};
+use crate::types::MetricScores;
+
+const DEFAULT_ENDPOINT: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_MAX_FINDINGS: usize = 20;
+
+/// Everything a summarizer gets to see about one comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryRequest {
+    pub passed: bool,
+    pub similarity: f32,
+    pub threshold: f32,
+    /// Scores of the metrics that ran.
+    pub metrics: Vec<(MetricKind, f32)>,
+    /// Findings as the template summary words them, most severe first.
+    pub findings: Vec<String>,
+    pub warnings: Vec<String>,
+    /// What the pages show (`--context`).
+    pub context: Option<String>,
+}
+
+impl SummaryRequest {
+    /// The request for `scores`, listing at most `max_findings` findings
+    /// ranked with `calibration`.
+    pub fn new(
+        scores: &MetricScores,
+        similarity: f32,
+        threshold: f32,
+        passed: bool,
+        calibration: &SeverityCalibration,
+        max_findings: usize,
+    ) -> Self {
+        Self {
+            passed,
+            similarity,
+            threshold,
+            metrics: MetricKind::all()
+                .into_iter()
+                .filter_map(|kind| metric_score(scores, kind).map(|score| (kind, score)))
+                .collect(),
+            findings: generate_top_issues_with(scores, max_findings, calibration),
+            warnings: Vec::new(),
+            context: None,
+        }
+    }
+
+    /// The prompt sent to a language model.
+    pub fn prompt(&self) -> String {
+        let mut prompt = String::from(
+            "You are a design QA expert. Summarize this comparison of a design reference \
+             against its implementation for the developer who has to fix it. Write at most \
+             4 short sentences of plain text, no lists or headings. Lead with whether it \
+             passed, then name the most important problems first, grouping related findings. \
+             Do not invent findings that are not listed.\n\n",
+        );
+        prompt.push_str(&format!(
+            "RESULT: {} ({:.1}% similarity, threshold {:.1}%)\n",
+            if self.passed { "passed" } else { "failed" },
+            self.similarity * 100.0,
+            self.threshold * 100.0
+        ));
+        if let Some(context) = &self.context {
+            prompt.push_str(&format!("CONTEXT: {context}\n"));
+        }
+        if !self.metrics.is_empty() {
+            let scores: Vec<String> = self
+                .metrics
+                .iter()
+                .map(|(kind, score)| format!("{kind} {:.1}%", score * 100.0))
+                .collect();
+            prompt.push_str(&format!("METRIC SCORES: {}\n", scores.join(", ")));
+        }
+        prompt.push_str("FINDINGS (most severe first):\n");
+        if self.findings.is_empty() {
+            prompt.push_str("- none\n");
+        }
+        for finding in &self.findings {
+            prompt.push_str(&format!("- {finding}\n"));
+        }
+        if !self.warnings.is_empty() {
+            prompt.push_str("WARNINGS:\n");
+            for warning in &self.warnings {
+                prompt.push_str(&format!("- {warning}\n"));
+            }
+        }
+        prompt
+    }
+}
+
+/// Turns the findings of a comparison into a short human narrative.
+pub trait Summarizer: Send + Sync {
+    fn summarize<'a>(&'a self, request: &'a SummaryRequest) -> BoxFuture<'a, Result<String>>;
+}
+
+/// [`Summarizer`] backed by an OpenAI-compatible chat completions API.
+#[derive(Debug, Clone)]
+pub struct ApiSummarizer {
+    api_endpoint: String,
+    api_key: String,
+    model: String,
+    /// Findings passed to the model; the rest are left out.
+    pub max_findings: usize,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+impl ApiSummarizer {
+    pub fn new(
+        api_endpoint: impl Into<String>,
+        api_key: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_endpoint: api_endpoint.into(),
+            api_key: api_key.into(),
+            model: model.into(),
+            max_findings: DEFAULT_MAX_FINDINGS,
+            client: crate::network::http_client(),
+        }
+    }
+
+    /// Summarizer from the `[summarizer]` config section, falling back to
+    /// `DPC_SUMMARY_API_KEY` / `OPENAI_API_KEY`, `DPC_SUMMARY_API_ENDPOINT` and
+    /// `DPC_SUMMARY_MODEL`. None when no API key is available.
+    pub fn from_config(config: &SummarizerConfig) -> Option<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("DPC_SUMMARY_API_KEY").ok())
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())?;
+        let api_endpoint = config
+            .api_endpoint
+            .clone()
+            .or_else(|| std::env::var("DPC_SUMMARY_API_ENDPOINT").ok())
+            .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
+        let model = config
+            .model
+            .clone()
+            .or_else(|| std::env::var("DPC_SUMMARY_MODEL").ok())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let mut summarizer = Self::new(api_endpoint, api_key, model);
+        if let Some(max_findings) = config.max_findings {
+            summarizer.max_findings = max_findings;
+        }
+        Some(summarizer)
+    }
+
+    async fn call_api(&self, request: &SummaryRequest) -> Result<String> {
+        let payload = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": request.prompt() }],
+            "max_tokens": 300,
+        });
+        let response = self
+            .client
+            .post(&self.api_endpoint)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| DpcError::metric(format!("Summary API request failed: {e}")))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(DpcError::metric(format!(
+                "Summary API returned {status}: {body}"
+            )));
+        }
+        let body: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| DpcError::metric(format!("Failed to parse summary API response: {e}")))?;
+        narrative_from(body)
+    }
+}
+
+impl Summarizer for ApiSummarizer {
+    fn summarize<'a>(&'a self, request: &'a SummaryRequest) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let mut request = request.clone();
+            request.findings.truncate(self.max_findings);
+            self.call_api(&request).await
+        })
+    }
+}
+
+fn narrative_from(response: ChatResponse) -> Result<String> {
+    response
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.message.content)
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+        .ok_or_else(|| DpcError::metric("Empty summary API response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ContentMetric, PixelMetric};
+
+    fn scores() -> MetricScores {
+        MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.82,
+                diff_regions: Vec::new(),
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: None,
+            typography: None,
+            color: None,
+            content: Some(ContentMetric {
+                score: 0.5,
+                missing_text: vec!["Buy now".into()],
+                extra_text: Vec::new(),
+                placeholder_text: Vec::new(),
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
+                text_labels: Default::default(),
+            }),
+            semantic: None,
+        }
+    }
+
+    #[test]
+    fn prompt_lists_result_scores_and_findings() {
+        let mut request = SummaryRequest::new(
+            &scores(),
+            0.74,
+            0.95,
+            false,
+            &SeverityCalibration::default(),
+            10,
+        );
+        request.context = Some("Pricing page".into());
+        assert_eq!(
+            request.metrics,
+            [(MetricKind::Pixel, 0.82), (MetricKind::Content, 0.5)]
+        );
+
+        let prompt = request.prompt();
+        assert!(prompt.contains("RESULT: failed (74.0% similarity, threshold 95.0%)"));
+        assert!(prompt.contains("CONTEXT: Pricing page"));
+        assert!(prompt.contains("METRIC SCORES: pixel 82.0%, content 50.0%"));
+        assert!(prompt.contains("Buy now"), "{prompt}");
+        assert!(!prompt.contains("WARNINGS"));
+    }
+
+    #[test]
+    fn narrative_is_the_trimmed_first_choice() {
+        let response: ChatResponse = serde_json::from_str(
+            r#"{"choices": [{"message": {"content": "  Failed: the CTA is missing.\n"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            narrative_from(response).unwrap(),
+            "Failed: the CTA is missing."
+        );
+
+        let empty: ChatResponse =
+            serde_json::from_str(r#"{"choices": [{"message": {"content": " "}}]}"#).unwrap();
+        assert!(narrative_from(empty).is_err());
+    }
+}
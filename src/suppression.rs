@@ -0,0 +1,410 @@
+//! Stable finding fingerprints and the `.dpc-ignore.json` suppression file.
+//!
+//! A fingerprint hashes what identifies a finding across runs: the element it
+//! concerns (DOM/Figma label or id), the issue kind and its geometry rounded to
+//! a coarse grid, so re-rendering noise does not change it. Listing a
+//! fingerprint in the suppression file keeps that one deviation (e.g. legal
+//! copy that differs on purpose) from failing builds while new findings still
+//! surface.
+
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{DpcError, Result};
+use crate::metrics::MetricKind;
+use crate::types::{ColorDiff, LayoutDiffRegion, MetricScores, PixelDiffRegion, TypographyDiff};
+
+/// Suppression file picked up from the working directory when present.
+pub const DEFAULT_SUPPRESSION_FILE: &str = ".dpc-ignore.json";
+
+/// Hex digits kept from the SHA-256 of a finding's identity.
+const FINGERPRINT_LEN: usize = 16;
+
+/// Fill in the `fingerprint` of every finding in `scores`.
+pub fn assign_fingerprints(scores: &mut MetricScores) {
+    if let Some(pixel) = scores.pixel.as_mut() {
+        for region in &mut pixel.diff_regions {
+            region.fingerprint = Some(pixel_fingerprint(region));
+        }
+    }
+    if let Some(layout) = scores.layout.as_mut() {
+        for region in &mut layout.diff_regions {
+            region.fingerprint = Some(layout_fingerprint(region));
+        }
+    }
+    if let Some(typography) = scores.typography.as_mut() {
+        for diff in &mut typography.diffs {
+            diff.fingerprint = Some(typography_fingerprint(diff));
+        }
+    }
+    if let Some(color) = scores.color.as_mut() {
+        for diff in &mut color.diffs {
+            diff.fingerprint = Some(color_fingerprint(diff));
+        }
+    }
+    if let Some(content) = scores.content.as_mut() {
+        content.missing_text_fingerprints = content
+            .missing_text
+            .iter()
+            .map(|text| text_fingerprint("missing_text", text))
+            .collect();
+        content.extra_text_fingerprints = content
+            .extra_text
+            .iter()
+            .map(|text| text_fingerprint("extra_text", text))
+            .collect();
+    }
+}
+
+pub fn pixel_fingerprint(region: &PixelDiffRegion) -> String {
+    fingerprint(&[
+        "pixel",
+        &enum_name(&region.reason),
+        &geometry(region.x, region.y, region.width, region.height),
+    ])
+}
+
+pub fn layout_fingerprint(region: &LayoutDiffRegion) -> String {
+    let path = format!(
+        "{}/{}",
+        region.element_type.as_deref().unwrap_or(""),
+        region.label.as_deref().unwrap_or("")
+    );
+    fingerprint(&[
+        "layout",
+        &path,
+        &enum_name(&region.kind),
+        &geometry(region.x, region.y, region.width, region.height),
+    ])
+}
+
+pub fn typography_fingerprint(diff: &TypographyDiff) -> String {
+    let path = format!(
+        "{}>{}",
+        diff.element_id_ref.as_deref().unwrap_or(""),
+        diff.element_id_impl.as_deref().unwrap_or("")
+    );
+    let mut issues: Vec<String> = diff.issues.iter().map(enum_name).collect();
+    issues.sort();
+    fingerprint(&["typography", &path, &issues.join(",")])
+}
+
+pub fn color_fingerprint(diff: &ColorDiff) -> String {
+    fingerprint(&[
+        "color",
+        &enum_name(&diff.kind),
+        &diff.ref_color.to_ascii_lowercase(),
+        &diff.impl_color.to_ascii_lowercase(),
+    ])
+}
+
+/// `kind` is `missing_text` or `extra_text`.
+pub fn text_fingerprint(kind: &str, text: &str) -> String {
+    fingerprint(&["content", kind, text.trim()])
+}
+
+fn fingerprint(parts: &[&str]) -> String {
+    let digest = Sha256::digest(parts.join("\u{1f}").as_bytes());
+    hex::encode(digest)[..FINGERPRINT_LEN].to_string()
+}
+
+/// Normalized coordinates snap to 2% of the view, absolute ones (DOM/Figma
+/// layout regions) to 8 px.
+fn geometry(x: f32, y: f32, width: f32, height: f32) -> String {
+    let normalized = x >= 0.0 && y >= 0.0 && x + width <= 1.0 + f32::EPSILON;
+    let step = if normalized { 0.02 } else { 8.0 };
+    [x, y, width, height]
+        .iter()
+        .map(|v| ((v / step).round() as i64).to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn enum_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// One entry of the suppression file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suppression {
+    pub fingerprint: String,
+    /// Why the deviation is accepted, for reviewers of the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A finding left out of the result because its fingerprint is suppressed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressedFinding {
+    pub fingerprint: String,
+    pub metric: MetricKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Contents of `.dpc-ignore.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SuppressionFile {
+    pub suppressions: Vec<Suppression>,
+}
+
+impl SuppressionFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path).map_err(|err| {
+            DpcError::Config(format!(
+                "Failed to read suppression file {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        serde_json::from_str(&data).map_err(|err| {
+            DpcError::Config(format!(
+                "Invalid suppression file {} (expected {{\"suppressions\": [{{\"fingerprint\": ...}}]}}): {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    fn lookup(&self, fingerprint: &str) -> Option<&Suppression> {
+        self.suppressions
+            .iter()
+            .find(|entry| entry.fingerprint == fingerprint)
+    }
+
+    /// Remove suppressed findings from `scores`, whose fingerprints must have
+    /// been assigned, and report what was removed.
+    pub fn apply(&self, scores: &mut MetricScores) -> Vec<SuppressedFinding> {
+        let mut suppressed = Vec::new();
+        let mut keep = |fingerprint: Option<&String>, metric: MetricKind| {
+            let entry = fingerprint.and_then(|fp| self.lookup(fp));
+            if let Some(entry) = entry {
+                suppressed.push(SuppressedFinding {
+                    fingerprint: entry.fingerprint.clone(),
+                    metric,
+                    reason: entry.reason.clone(),
+                });
+            }
+            entry.is_none()
+        };
+        if let Some(pixel) = scores.pixel.as_mut() {
+            pixel
+                .diff_regions
+                .retain(|r| keep(r.fingerprint.as_ref(), MetricKind::Pixel));
+        }
+        if let Some(layout) = scores.layout.as_mut() {
+            layout
+                .diff_regions
+                .retain(|r| keep(r.fingerprint.as_ref(), MetricKind::Layout));
+        }
+        if let Some(typography) = scores.typography.as_mut() {
+            typography
+                .diffs
+                .retain(|d| keep(d.fingerprint.as_ref(), MetricKind::Typography));
+        }
+        if let Some(color) = scores.color.as_mut() {
+            color
+                .diffs
+                .retain(|d| keep(d.fingerprint.as_ref(), MetricKind::Color));
+        }
+        if let Some(content) = scores.content.as_mut() {
+            retain_texts(
+                &mut content.missing_text,
+                &mut content.missing_text_fingerprints,
+                |fp| keep(fp, MetricKind::Content),
+            );
+            retain_texts(
+                &mut content.extra_text,
+                &mut content.extra_text_fingerprints,
+                |fp| keep(fp, MetricKind::Content),
+            );
+        }
+        suppressed
+    }
+
+    /// Add every fingerprint in `scores` not yet listed, so the current
+    /// findings become the accepted baseline. Returns how many were added.
+    pub fn extend_from(&mut self, scores: &MetricScores, reason: &str) -> usize {
+        let before = self.suppressions.len();
+        for fingerprint in fingerprints(scores) {
+            if self.lookup(&fingerprint).is_none() {
+                self.suppressions.push(Suppression {
+                    fingerprint,
+                    reason: Some(reason.to_string()),
+                });
+            }
+        }
+        self.suppressions.len() - before
+    }
+}
+
+/// Filter `texts` and their parallel `fingerprints` together.
+fn retain_texts(
+    texts: &mut Vec<String>,
+    fingerprints: &mut Vec<String>,
+    mut keep: impl FnMut(Option<&String>) -> bool,
+) {
+    let decisions: Vec<bool> = (0..texts.len())
+        .map(|idx| keep(fingerprints.get(idx)))
+        .collect();
+    let mut idx = 0;
+    texts.retain(|_| {
+        idx += 1;
+        decisions[idx - 1]
+    });
+    let mut idx = 0;
+    fingerprints.retain(|_| {
+        idx += 1;
+        decisions.get(idx - 1).copied().unwrap_or(true)
+    });
+}
+
+/// Every fingerprint assigned in `scores`.
+pub fn fingerprints(scores: &MetricScores) -> Vec<String> {
+    let mut all = Vec::new();
+    if let Some(pixel) = &scores.pixel {
+        all.extend(
+            pixel
+                .diff_regions
+                .iter()
+                .filter_map(|r| r.fingerprint.clone()),
+        );
+    }
+    if let Some(layout) = &scores.layout {
+        all.extend(
+            layout
+                .diff_regions
+                .iter()
+                .filter_map(|r| r.fingerprint.clone()),
+        );
+    }
+    if let Some(typography) = &scores.typography {
+        all.extend(
+            typography
+                .diffs
+                .iter()
+                .filter_map(|d| d.fingerprint.clone()),
+        );
+    }
+    if let Some(color) = &scores.color {
+        all.extend(color.diffs.iter().filter_map(|d| d.fingerprint.clone()));
+    }
+    if let Some(content) = &scores.content {
+        all.extend(content.missing_text_fingerprints.iter().cloned());
+        all.extend(content.extra_text_fingerprints.iter().cloned());
+    }
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ContentMetric, DiffSeverity, PixelDiffReason, PixelMetric};
+
+    fn region(x: f32) -> PixelDiffRegion {
+        PixelDiffRegion {
+            x,
+            y: 0.5,
+            width: 0.1,
+            height: 0.1,
+            severity: DiffSeverity::Major,
+            reason: PixelDiffReason::PixelChange,
+            intensity: Some(0.4),
+            max_intensity: None,
+            pixel_bounds: None,
+            css_bounds: None,
+            fingerprint: None,
+        }
+    }
+
+    fn scores() -> MetricScores {
+        MetricScores {
+            pixel: Some(PixelMetric {
+                score: 0.9,
+                diff_regions: vec![region(0.1), region(0.7)],
+                semantic_diffs: None,
+                offset: None,
+            }),
+            layout: None,
+            typography: None,
+            color: None,
+            content: Some(ContentMetric {
+                score: 0.5,
+                missing_text: vec!["© 2024 Acme Inc.".to_string(), "Sign up".to_string()],
+                extra_text: Vec::new(),
+                placeholder_text: Vec::new(),
+                text_labels: Default::default(),
+                missing_text_fingerprints: Vec::new(),
+                extra_text_fingerprints: Vec::new(),
+            }),
+            semantic: None,
+        }
+    }
+
+    #[test]
+    fn fingerprints_ignore_small_geometry_jitter() {
+        assert_eq!(
+            pixel_fingerprint(&region(0.1)),
+            pixel_fingerprint(&region(0.101))
+        );
+        assert_ne!(
+            pixel_fingerprint(&region(0.1)),
+            pixel_fingerprint(&region(0.3))
+        );
+        assert_eq!(pixel_fingerprint(&region(0.1)).len(), FINGERPRINT_LEN);
+    }
+
+    #[test]
+    fn suppressed_findings_are_removed_with_their_fingerprints() {
+        let mut scores = scores();
+        assign_fingerprints(&mut scores);
+        let legal = text_fingerprint("missing_text", "© 2024 Acme Inc.");
+        let file = SuppressionFile {
+            suppressions: vec![
+                Suppression {
+                    fingerprint: legal.clone(),
+                    reason: Some("legal copy differs per market".to_string()),
+                },
+                Suppression {
+                    fingerprint: pixel_fingerprint(&region(0.7)),
+                    reason: None,
+                },
+            ],
+        };
+
+        let suppressed = file.apply(&mut scores);
+        assert_eq!(suppressed.len(), 2);
+        assert_eq!(suppressed[1].fingerprint, legal);
+        assert_eq!(suppressed[1].metric, MetricKind::Content);
+        let content = scores.content.as_ref().unwrap();
+        assert_eq!(content.missing_text, vec!["Sign up".to_string()]);
+        assert_eq!(
+            content.missing_text_fingerprints,
+            vec![text_fingerprint("missing_text", "Sign up")]
+        );
+        assert_eq!(scores.pixel.as_ref().unwrap().diff_regions.len(), 1);
+    }
+
+    #[test]
+    fn extend_from_adds_only_new_fingerprints() {
+        let mut scores = scores();
+        assign_fingerprints(&mut scores);
+        let mut file = SuppressionFile::default();
+        assert_eq!(file.extend_from(&scores, "baseline"), 4);
+        assert_eq!(file.extend_from(&scores, "baseline"), 0);
+        assert!(file.apply(&mut scores).len() == 4);
+        assert!(fingerprints(&scores).is_empty());
+    }
+}
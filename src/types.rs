@@ -5,24 +5,42 @@
 //! - [`dom`] - DOM snapshot types for browser captures
 //! - [`figma`] - Figma design snapshot types
 //! - [`metric_results`] - Metric result types for comparison output
+//! - [`schema`] - Snapshot schema versions and migration of older snapshots
 
 pub mod core;
 pub mod dom;
 pub mod figma;
 pub mod metric_results;
+pub mod schema;
 
 // Re-export core types at module level for convenience
-pub use core::{BoundingBox, NormalizedView, OcrBlock, ResourceKind, TypographyStyle, Viewport};
+pub use core::{
+    BoundingBox, EdgeInsets, Gradient, GradientKind, GradientStop, NormalizedView, OcrBlock,
+    ResourceKind, TypographyStyle, Viewport,
+};
 
 // Re-export DOM types
-pub use dom::{ComputedStyle, DomNode, DomSnapshot};
+pub use dom::{
+    BrowserEngine, ComputedStyle, CustomProperties, DomNode, DomSnapshot, FailedRequest,
+    FlexLayout, InteractionStates, PageDiagnostics, ScopedCustomProperties,
+};
 
 // Re-export Figma types
-pub use figma::{FigmaNode, FigmaPaint, FigmaPaintKind, FigmaSnapshot};
+pub use figma::{
+    AutoLayout, AutoLayoutAlign, Constraint, FigmaNode, FigmaPaint, FigmaPaintKind, FigmaSnapshot,
+    FigmaStyle, FigmaStyleKind, LayoutConstraints, LayoutDirection,
+};
 
 // Re-export metric types
 pub use metric_results::{
     ColorDiff, ColorDiffKind, ColorMetric, ContentMetric, DiffSeverity, LayoutDiffKind,
     LayoutDiffRegion, LayoutMetric, MetricScores, PixelDiffReason, PixelDiffRegion, PixelMetric,
-    SemanticDiff, SemanticDiffType, TypographyDiff, TypographyIssue, TypographyMetric,
+    PixelOffset, RegionBounds, RegionUnit, SemanticDiff, SemanticDiffType, SemanticMetric,
+    TypographyDiff, TypographyIssue, TypographyMetric,
+};
+
+// Re-export snapshot schema migration
+pub use schema::{
+    dom_snapshot_from_value, figma_snapshot_from_value, migrate_dom_snapshot,
+    migrate_figma_snapshot, migrate_view,
 };
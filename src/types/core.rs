@@ -4,9 +4,11 @@
 //! - [`ResourceKind`] - Input type classification
 //! - [`NormalizedView`] - Unified view representation
 //! - [`BoundingBox`] - Element positioning
+//! - [`Gradient`] - Gradient fills from Figma paints and CSS backgrounds
 //! - [`TypographyStyle`] - Font properties
 //! - [`OcrBlock`] - OCR-extracted text blocks
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -16,7 +18,7 @@ use super::dom::DomSnapshot;
 use super::figma::FigmaSnapshot;
 
 /// Classification of input resource type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ResourceKind {
     /// Web URL to be rendered via Playwright
@@ -25,6 +27,12 @@ pub enum ResourceKind {
     Image,
     /// Figma design reference
     Figma,
+    /// Live screenshot from an Android device (`adb:`) or iOS simulator (`simctl:`)
+    Device,
+    /// Native desktop window (`window:`)
+    Window,
+    /// View saved with [`NormalizedView::save`] (`view:<dir>`)
+    View,
 }
 
 /// A normalized representation of a design view.
@@ -51,6 +59,28 @@ pub struct NormalizedView {
     /// OCR-extracted text blocks (for image inputs without DOM/Figma)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ocr_blocks: Option<Vec<OcrBlock>>,
+    /// Part of the screenshot (pixels) showing the source when it was
+    /// letterboxed into the viewport; everything outside is padding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_area: Option<BoundingBox>,
+}
+
+impl NormalizedView {
+    /// [`content_area`](Self::content_area) scaled to an image of
+    /// `width`x`height`, as a pixel rect `(x, y, width, height)`.
+    pub fn content_rect(&self, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+        let area = self.content_area?;
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let sx = width as f32 / self.width as f32;
+        let sy = height as f32 / self.height as f32;
+        let x = ((area.x * sx).round() as u32).min(width);
+        let y = ((area.y * sy).round() as u32).min(height);
+        let w = ((area.width * sx).round() as u32).min(width - x);
+        let h = ((area.height * sy).round() as u32).min(height - y);
+        (w > 0 && h > 0).then_some((x, y, w, h))
+    }
 }
 
 /// Rectangle bounds for an element.
@@ -63,6 +93,104 @@ pub struct BoundingBox {
     pub height: f32,
 }
 
+/// Space on each side of a box, in CSS order (e.g. padding).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeInsets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl EdgeInsets {
+    /// Largest per-side difference to `other`.
+    pub fn max_difference(&self, other: &EdgeInsets) -> f32 {
+        (self.top - other.top)
+            .abs()
+            .max((self.right - other.right).abs())
+            .max((self.bottom - other.bottom).abs())
+            .max((self.left - other.left).abs())
+    }
+}
+
+impl std::fmt::Display for EdgeInsets {
+    /// CSS shorthand, e.g. `24px 16px` or `8px 16px 12px 16px`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let px = |value: f32| format!("{}px", value.round());
+        if self.top == self.bottom && self.left == self.right {
+            if self.top == self.left {
+                write!(f, "{}", px(self.top))
+            } else {
+                write!(f, "{} {}", px(self.top), px(self.right))
+            }
+        } else {
+            write!(
+                f,
+                "{} {} {} {}",
+                px(self.top),
+                px(self.right),
+                px(self.bottom),
+                px(self.left)
+            )
+        }
+    }
+}
+
+/// A gradient fill, described in CSS terms so Figma and DOM fills compare
+/// directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// Direction in CSS degrees (0 = towards the top, clockwise); linear
+    /// gradients only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f32>,
+    pub stops: Vec<GradientStop>,
+}
+
+/// Gradient shape. Figma's angular fills are conic; diamond fills compare
+/// as radial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientKind {
+    Linear,
+    Radial,
+    Conic,
+}
+
+/// A color stop along a gradient.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GradientStop {
+    /// Color in hex format
+    pub color: String,
+    /// Position along the gradient line (0.0 - 1.0)
+    pub position: f32,
+}
+
+impl Gradient {
+    /// CSS notation, e.g. `linear-gradient(90deg, #FF0000 0%, #0000FF 100%)`.
+    pub fn to_css(&self) -> String {
+        let mut parts = Vec::with_capacity(self.stops.len() + 1);
+        if let Some(angle) = self.angle.filter(|_| self.kind == GradientKind::Linear) {
+            parts.push(format!("{}deg", angle.round()));
+        }
+        parts.extend(
+            self.stops
+                .iter()
+                .map(|stop| format!("{} {}%", stop.color, (stop.position * 100.0).round())),
+        );
+        let function = match self.kind {
+            GradientKind::Linear => "linear-gradient",
+            GradientKind::Radial => "radial-gradient",
+            GradientKind::Conic => "conic-gradient",
+        };
+        format!("{}({})", function, parts.join(", "))
+    }
+}
+
 /// Typography style properties.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -74,6 +202,21 @@ pub struct TypographyStyle {
     pub letter_spacing: Option<f32>,
 }
 
+impl TypographyStyle {
+    /// Number of lines a text box of `height` holds at this style's line
+    /// height (1.2x the font size, the browser `normal`, when unset).
+    pub fn line_count(&self, height: f32) -> Option<u32> {
+        let line_height = self
+            .line_height
+            .filter(|lh| *lh > 0.0)
+            .or_else(|| self.font_size.filter(|fs| *fs > 0.0).map(|fs| fs * 1.2))?;
+        if height <= 0.0 {
+            return None;
+        }
+        Some(((height / line_height).round() as u32).max(1))
+    }
+}
+
 /// A text block extracted via OCR.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
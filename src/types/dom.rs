@@ -3,22 +3,228 @@
 //! These types represent the DOM structure extracted from web pages
 //! via Playwright for structural comparison.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-use super::core::BoundingBox;
+use super::core::{BoundingBox, EdgeInsets};
 
 /// A snapshot of a web page's DOM structure.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DomSnapshot {
+    /// Schema version the snapshot was written with (see [`super::schema`])
+    #[serde(default = "super::schema::legacy_schema_version")]
+    pub schema_version: u32,
     /// The URL of the captured page
     pub url: Option<String>,
     /// The page title
     pub title: Option<String>,
+    /// Browser engine that rendered the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserEngine>,
+    /// Console errors and failed requests, when recorded (`--record-network`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<PageDiagnostics>,
     /// Flattened list of DOM nodes
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub nodes: Vec<DomNode>,
+    /// Resolved `--*` custom properties declared by the page's stylesheets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_properties: Option<CustomProperties>,
+}
+
+impl DomSnapshot {
+    /// Schema version written by this release.
+    pub const SCHEMA_VERSION: u32 = 2;
+
+    /// Keep the first `max_nodes` nodes (document order) and drop the rest,
+    /// along with child references to them. Returns how many were dropped.
+    pub fn truncate_nodes(&mut self, max_nodes: usize) -> usize {
+        if self.nodes.len() <= max_nodes {
+            return 0;
+        }
+        let dropped = self.nodes.len() - max_nodes;
+        self.nodes.truncate(max_nodes);
+        let kept: std::collections::HashSet<String> =
+            self.nodes.iter().map(|n| n.id.clone()).collect();
+        for node in &mut self.nodes {
+            node.children.retain(|child| kept.contains(child));
+        }
+        dropped
+    }
+}
+
+/// CSS custom properties (`--*`) of a captured page, as the browser resolved
+/// them (`var()` references substituted).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomProperties {
+    /// Values on `:root`, by property name (`--color-primary`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub root: BTreeMap<String, String>,
+    /// Values set by rules with other selectors (themes, components), in
+    /// stylesheet order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scoped: Vec<ScopedCustomProperties>,
+}
+
+impl CustomProperties {
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty() && self.scoped.is_empty()
+    }
+}
+
+/// Custom properties set by the rules of one selector, resolved on the
+/// first element it matches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopedCustomProperties {
+    pub selector: String,
+    pub values: BTreeMap<String, String>,
+}
+
+/// Console errors and failed network requests seen while capturing a page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PageDiagnostics {
+    /// `console.error` messages and uncaught page exceptions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub console_errors: Vec<String>,
+    /// Requests that failed or returned HTTP 4xx/5xx
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_requests: Vec<FailedRequest>,
+    /// HAR of all network traffic, when written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub har: Option<PathBuf>,
+}
+
+impl PageDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.console_errors.is_empty() && self.failed_requests.is_empty()
+    }
+
+    /// One-line summary such as `2 failed requests (2 images, e.g. https://cdn/x.png 404); 1 console error`.
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(first) = self.failed_requests.first() {
+            let images = self
+                .failed_requests
+                .iter()
+                .filter(|request| request.is_image())
+                .count();
+            let example = self
+                .failed_requests
+                .iter()
+                .find(|request| request.is_image())
+                .unwrap_or(first);
+            let kinds = if images > 0 {
+                format!("{images} {}, ", plural(images, "image", "images"))
+            } else {
+                String::new()
+            };
+            parts.push(format!(
+                "{} failed {} ({kinds}e.g. {})",
+                self.failed_requests.len(),
+                plural(self.failed_requests.len(), "request", "requests"),
+                example
+            ));
+        }
+        if !self.console_errors.is_empty() {
+            parts.push(format!(
+                "{} console {}",
+                self.console_errors.len(),
+                plural(self.console_errors.len(), "error", "errors")
+            ));
+        }
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+/// A request that failed to load while capturing a page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedRequest {
+    pub url: String,
+    /// Playwright resource type (`image`, `font`, `stylesheet`, `script`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    /// HTTP status for 4xx/5xx responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Network error for requests that got no response (e.g. `net::ERR_NAME_NOT_RESOLVED`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FailedRequest {
+    pub fn is_image(&self) -> bool {
+        self.resource_type.as_deref() == Some("image")
+    }
+}
+
+impl fmt::Display for FailedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)?;
+        match (self.status, &self.error) {
+            (Some(status), _) => write!(f, " {status}"),
+            (None, Some(error)) => write!(f, " {error}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Browser engine used to render URL inputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserEngine {
+    #[default]
+    Chromium,
+    Firefox,
+    Webkit,
+}
+
+impl BrowserEngine {
+    /// Name of the matching Playwright browser type (`chromium`, `firefox`, `webkit`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BrowserEngine::Chromium => "chromium",
+            BrowserEngine::Firefox => "firefox",
+            BrowserEngine::Webkit => "webkit",
+        }
+    }
+}
+
+impl fmt::Display for BrowserEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BrowserEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "chromium" | "chrome" => Ok(BrowserEngine::Chromium),
+            "firefox" => Ok(BrowserEngine::Firefox),
+            "webkit" | "safari" => Ok(BrowserEngine::Webkit),
+            _ => Err(format!(
+                "invalid browser '{s}': expected chromium, firefox or webkit"
+            )),
+        }
+    }
 }
 
 /// A single DOM element with its properties.
@@ -41,23 +247,98 @@ pub struct DomNode {
     pub text: Option<String>,
     /// Position and size on screen
     pub bounding_box: BoundingBox,
+    /// Y of the baseline of the first line of `text`, as rendered: the top
+    /// of the text's content area plus the font's ascent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_baseline: Option<f32>,
+    /// How an interactive element restyles under forced `:hover` and
+    /// `:focus-visible`, when captured (`dpc quality` on Chromium)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interaction_states: Option<InteractionStates>,
     /// CSS computed styles
     #[serde(skip_serializing_if = "Option::is_none")]
     pub computed_style: Option<ComputedStyle>,
 }
 
+/// Computed style properties of an interactive element or its descendants
+/// that change when a state is forced; empty when the state renders the same
+/// as the default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractionStates {
+    #[serde(default)]
+    pub hover: Vec<String>,
+    #[serde(default)]
+    pub focus_visible: Vec<String>,
+}
+
+impl DomNode {
+    /// Name to show for this element in findings: its `data-testid`, else
+    /// its `aria-label`.
+    pub fn label(&self) -> Option<&str> {
+        ["data-testid", "aria-label"]
+            .iter()
+            .filter_map(|name| self.attributes.get(*name))
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+    }
+}
+
 /// Computed CSS styles for a DOM element.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ComputedStyle {
     pub font_family: Option<String>,
+    /// Family from the `font-family` stack that actually rendered at capture
+    /// time; differs from the first entry when a font failed to load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_font_family: Option<String>,
     pub font_size: Option<f32>,
     pub font_weight: Option<String>,
     pub line_height: Option<f32>,
     pub letter_spacing: Option<f32>,
     pub color: Option<String>,
     pub background_color: Option<String>,
+    /// Computed `background-image`, e.g. a `linear-gradient(...)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<String>,
     pub display: Option<String>,
     pub visibility: Option<String>,
     pub opacity: Option<f32>,
+    /// Flexbox settings (for `display: flex` / `inline-flex` containers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flex: Option<FlexLayout>,
+}
+
+/// Computed flexbox settings of a flex container.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlexLayout {
+    /// `flex-direction`, e.g. `row` or `column-reverse`
+    pub direction: String,
+    pub row_gap: f32,
+    pub column_gap: f32,
+    pub padding: EdgeInsets,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justify_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub align_items: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wrap: bool,
+}
+
+impl FlexLayout {
+    /// Whether the main axis runs vertically (`column` / `column-reverse`).
+    pub fn is_column(&self) -> bool {
+        self.direction.starts_with("column")
+    }
+
+    /// Gap between items along the main axis.
+    pub fn main_gap(&self) -> f32 {
+        if self.is_column() {
+            self.row_gap
+        } else {
+            self.column_gap
+        }
+    }
 }
@@ -3,23 +3,90 @@
 //! These types represent the Figma node structure extracted from
 //! Figma designs via the Figma API for structural comparison.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use super::core::{BoundingBox, TypographyStyle};
+use super::core::{BoundingBox, EdgeInsets, Gradient, TypographyStyle};
 
 /// A snapshot of a Figma design frame/component.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FigmaSnapshot {
+    /// Schema version the snapshot was written with (see [`super::schema`])
+    #[serde(default = "super::schema::legacy_schema_version")]
+    pub schema_version: u32,
     /// The Figma file key
     pub file_key: String,
     /// The node ID within the file
     pub node_id: String,
     /// The node name
     pub name: Option<String>,
+    /// Figma file version the node was read from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Size of the exported frame image before it was fitted to the viewport
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_size: Option<(u32, u32)>,
+    /// Scale the frame was exported at from the Figma API, when exported live
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_scale: Option<f32>,
     /// Flattened list of Figma nodes
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub nodes: Vec<FigmaNode>,
+    /// Published color and text styles of the file, with their values, when
+    /// they were fetched (`compare --figma-styles`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub styles: Vec<FigmaStyle>,
+}
+
+impl FigmaSnapshot {
+    /// Schema version written by this release.
+    pub const SCHEMA_VERSION: u32 = 2;
+
+    /// The published style `node` applies to `property` (`fill`, `text`),
+    /// when it is one of [`styles`](Self::styles).
+    pub fn style_of(&self, node: &FigmaNode, property: &str) -> Option<&FigmaStyle> {
+        let id = node.styles.get(property)?;
+        self.styles.iter().find(|style| style.matches_id(id))
+    }
+}
+
+/// A published style of a Figma file and the value it resolves to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigmaStyle {
+    /// Node holding the style; nodes of the same file refer to it by this ID
+    pub node_id: String,
+    /// Library key; nodes of other files refer to it as `S:<key>,...`
+    pub key: String,
+    /// Style name, e.g. `Primary/600`
+    pub name: String,
+    pub kind: FigmaStyleKind,
+    /// Color of a solid fill style, `#RRGGBB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Typography of a text style
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typography: Option<TypographyStyle>,
+}
+
+impl FigmaStyle {
+    /// Whether a style ID found on a node refers to this style.
+    pub fn matches_id(&self, id: &str) -> bool {
+        id == self.node_id
+            || id
+                .strip_prefix("S:")
+                .is_some_and(|rest| rest.split(',').next() == Some(self.key.as_str()))
+    }
+}
+
+/// Kinds of published styles the metrics use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FigmaStyleKind {
+    Fill,
+    Text,
 }
 
 /// A single Figma design node.
@@ -40,12 +107,223 @@ pub struct FigmaNode {
     /// Typography properties (for TEXT nodes)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub typography: Option<TypographyStyle>,
+    /// Number of lines the text wraps into (for TEXT nodes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_count: Option<u32>,
     /// Fill paints applied to this node
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub fills: Vec<FigmaPaint>,
     /// IDs of child nodes
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<String>,
+    /// Auto-layout settings (for frames using auto layout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_layout: Option<AutoLayout>,
+    /// How the node follows its parent when the parent is resized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<LayoutConstraints>,
+    /// Where the constraints place the node once the frame is resized to the
+    /// viewport, in view coordinates; set when the viewport differs from the
+    /// frame's native size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub responsive_box: Option<BoundingBox>,
+    /// IDs of the styles applied to the node, by property (`fill`, `text`,
+    /// `stroke`, ...)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub styles: HashMap<String, String>,
+}
+
+impl FigmaNode {
+    /// The box layout comparisons use: the constraint-based prediction when
+    /// there is one, else the scaled design box.
+    pub fn layout_box(&self) -> BoundingBox {
+        self.responsive_box.unwrap_or(self.bounding_box)
+    }
+
+    /// Name to show for this node in findings: the layer name, unless it is
+    /// one Figma generates (`Frame 12`, `Rectangle 3`) and says nothing the
+    /// node type doesn't.
+    pub fn label(&self) -> Option<&str> {
+        let name = self.name.as_deref()?.trim();
+        let base = name
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .trim_end();
+        let generated = GENERATED_LAYER_NAMES
+            .iter()
+            .any(|generated| base.eq_ignore_ascii_case(generated));
+        (!name.is_empty() && !generated).then_some(name)
+    }
+}
+
+/// Default layer names Figma gives new nodes, before the number suffix.
+const GENERATED_LAYER_NAMES: &[&str] = &[
+    "Frame",
+    "Group",
+    "Rectangle",
+    "Ellipse",
+    "Vector",
+    "Line",
+    "Polygon",
+    "Star",
+    "Image",
+    "Section",
+    "Component",
+    "Instance",
+    "Union",
+    "Subtract",
+    "Intersect",
+    "Exclude",
+];
+
+/// Figma auto layout of a frame, the design-side counterpart of a flexbox.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoLayout {
+    pub direction: LayoutDirection,
+    /// Space between children along `direction` (`itemSpacing`)
+    pub gap: f32,
+    pub padding: EdgeInsets,
+    /// Distribution along `direction` (`primaryAxisAlignItems`)
+    pub primary_align: AutoLayoutAlign,
+    /// Alignment across `direction` (`counterAxisAlignItems`)
+    pub counter_align: AutoLayoutAlign,
+    /// Whether children wrap onto new lines (`layoutWrap`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wrap: bool,
+}
+
+impl AutoLayout {
+    /// The layout with gap and padding scaled like the node's bounding box.
+    pub fn scaled(&self, scale_x: f32, scale_y: f32) -> AutoLayout {
+        let gap_scale = match self.direction {
+            LayoutDirection::Horizontal => scale_x,
+            LayoutDirection::Vertical => scale_y,
+        };
+        AutoLayout {
+            gap: self.gap * gap_scale,
+            padding: EdgeInsets {
+                top: self.padding.top * scale_y,
+                right: self.padding.right * scale_x,
+                bottom: self.padding.bottom * scale_y,
+                left: self.padding.left * scale_x,
+            },
+            ..*self
+        }
+    }
+
+    /// Tailwind classes that reproduce this layout, e.g. `flex flex-col gap-4 px-6 py-4`.
+    pub fn tailwind_classes(&self) -> String {
+        let mut classes = vec!["flex".to_string()];
+        if self.direction == LayoutDirection::Vertical {
+            classes.push("flex-col".to_string());
+        }
+        if self.wrap {
+            classes.push("flex-wrap".to_string());
+        }
+        let justify = match self.primary_align {
+            AutoLayoutAlign::Min | AutoLayoutAlign::Baseline => None,
+            AutoLayoutAlign::Center => Some("justify-center"),
+            AutoLayoutAlign::Max => Some("justify-end"),
+            AutoLayoutAlign::SpaceBetween => Some("justify-between"),
+        };
+        let items = match self.counter_align {
+            AutoLayoutAlign::Min | AutoLayoutAlign::SpaceBetween => "items-start",
+            AutoLayoutAlign::Center => "items-center",
+            AutoLayoutAlign::Max => "items-end",
+            AutoLayoutAlign::Baseline => "items-baseline",
+        };
+        classes.extend(justify.map(str::to_string));
+        classes.push(items.to_string());
+        if self.gap > 0.0 {
+            classes.push(tailwind_spacing("gap", self.gap));
+        }
+        let p = self.padding;
+        if p.top == p.bottom && p.left == p.right && p.top == p.left {
+            if p.top > 0.0 {
+                classes.push(tailwind_spacing("p", p.top));
+            }
+        } else if p.top == p.bottom && p.left == p.right {
+            for (prefix, value) in [("py", p.top), ("px", p.left)] {
+                if value > 0.0 {
+                    classes.push(tailwind_spacing(prefix, value));
+                }
+            }
+        } else {
+            for (prefix, value) in [
+                ("pt", p.top),
+                ("pr", p.right),
+                ("pb", p.bottom),
+                ("pl", p.left),
+            ] {
+                if value > 0.0 {
+                    classes.push(tailwind_spacing(prefix, value));
+                }
+            }
+        }
+        classes.join(" ")
+    }
+}
+
+/// `gap-4` for multiples of Tailwind's 4px step, else `gap-[14px]`.
+fn tailwind_spacing(prefix: &str, px: f32) -> String {
+    let px = px.round();
+    if px % 4.0 == 0.0 {
+        format!("{prefix}-{}", px / 4.0)
+    } else {
+        format!("{prefix}-[{px}px]")
+    }
+}
+
+/// Main axis of an auto-layout frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutDirection {
+    Horizontal,
+    Vertical,
+}
+
+impl std::fmt::Display for LayoutDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutDirection::Horizontal => write!(f, "horizontal"),
+            LayoutDirection::Vertical => write!(f, "vertical"),
+        }
+    }
+}
+
+/// Child alignment on one auto-layout axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoLayoutAlign {
+    Min,
+    Center,
+    Max,
+    SpaceBetween,
+    Baseline,
+}
+
+/// Figma resizing constraints of a layer relative to its parent frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutConstraints {
+    pub horizontal: Constraint,
+    pub vertical: Constraint,
+}
+
+/// Resizing behaviour on one axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Constraint {
+    /// Keeps its distance to the left/top edge (`LEFT` / `TOP`)
+    Min,
+    /// Keeps its distance to the right/bottom edge (`RIGHT` / `BOTTOM`)
+    Max,
+    /// Keeps its offset from the parent's center (`CENTER`)
+    Center,
+    /// Keeps both distances and stretches (`LEFT_RIGHT` / `TOP_BOTTOM`)
+    Stretch,
+    /// Keeps its position and size as fractions of the parent (`SCALE`)
+    Scale,
 }
 
 /// A Figma paint/fill.
@@ -58,6 +336,9 @@ pub struct FigmaPaint {
     pub color: Option<String>,
     /// Opacity (0.0 - 1.0)
     pub opacity: Option<f32>,
+    /// Direction and stops (for gradient fills)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gradient: Option<Gradient>,
 }
 
 /// Types of Figma paint fills.
@@ -6,12 +6,15 @@
 //! - Typography comparison (font properties)
 //! - Color palette comparison
 //! - Content comparison (text matching)
+//! - Semantic comparison (image embeddings)
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 /// Container for all metric scores.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricScores {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -24,6 +27,8 @@ pub struct MetricScores {
     pub color: Option<ColorMetric>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<ContentMetric>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<SemanticMetric>,
 }
 
 // ============================================================================
@@ -31,7 +36,7 @@ pub struct MetricScores {
 // ============================================================================
 
 /// Result of pixel/perceptual similarity comparison.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PixelMetric {
     /// Similarity score (0.0 - 1.0)
@@ -42,10 +47,22 @@ pub struct PixelMetric {
     /// Semantic analysis of diff regions (when vision model is enabled)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub semantic_diffs: Option<Vec<SemanticDiff>>,
+    /// Global offset of the implementation found by pixel alignment; the
+    /// score and regions are measured after undoing it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<PixelOffset>,
+}
+
+/// How far the implementation content sits from the reference, in
+/// screenshot pixels (positive: right / down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PixelOffset {
+    pub x: i32,
+    pub y: i32,
 }
 
 /// A semantically analyzed diff region.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SemanticDiff {
     /// Bounding box x (normalized 0.0-1.0)
@@ -68,7 +85,7 @@ pub struct SemanticDiff {
 }
 
 /// Type of semantic difference detected by vision analysis.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SemanticDiffType {
     TextContent,
@@ -85,7 +102,7 @@ pub enum SemanticDiffType {
 }
 
 /// A region of pixel differences.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PixelDiffRegion {
     /// X position (normalized 0.0 - 1.0)
@@ -104,10 +121,32 @@ pub struct PixelDiffRegion {
     /// Higher values indicate more significant visual differences.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub intensity: Option<f32>,
+    /// Largest single-pixel difference (0.0 - 1.0) in this region.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_intensity: Option<f32>,
+    /// Bounds in screenshot pixels (emitted when the coordinate space requests it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pixel_bounds: Option<RegionBounds>,
+    /// Bounds in CSS pixels, i.e. screenshot pixels divided by the device pixel ratio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub css_bounds: Option<RegionBounds>,
+    /// Stable identifier for suppressing this finding (see `.dpc-ignore.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+/// Absolute bounds of a diff region in a pixel coordinate space.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
 }
 
 /// Severity level of a difference.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DiffSeverity {
     Minor,
@@ -116,7 +155,7 @@ pub enum DiffSeverity {
 }
 
 /// Reason for a pixel difference.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PixelDiffReason {
     PixelChange,
@@ -129,7 +168,7 @@ pub enum PixelDiffReason {
 // ============================================================================
 
 /// Result of layout/structure comparison.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LayoutMetric {
     /// Similarity score (0.0 - 1.0)
@@ -140,29 +179,67 @@ pub struct LayoutMetric {
 }
 
 /// A layout difference region.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LayoutDiffRegion {
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Unit of `x`, `y`, `width` and `height`; omitted by results written
+    /// before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<RegionUnit>,
     /// Type of layout difference
     pub kind: LayoutDiffKind,
     /// Element type (e.g., "div", "TEXT")
     pub element_type: Option<String>,
     /// Human-readable label
     pub label: Option<String>,
+    /// What differs, e.g. the gaps of a spacing mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Number of overlapping differences of this kind merged into this
+    /// region (e.g. a missing card and its children); omitted for one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_count: Option<usize>,
+    /// Bounds in screenshot pixels (emitted when the coordinate space requests it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pixel_bounds: Option<RegionBounds>,
+    /// Bounds in CSS pixels, i.e. screenshot pixels divided by the device pixel ratio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub css_bounds: Option<RegionBounds>,
+    /// Stable identifier for suppressing this finding (see `.dpc-ignore.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+/// Unit of a layout region's coordinates, after the view it was measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionUnit {
+    /// 0–1 fractions of the view (Figma layers)
+    Normalized,
+    /// CSS pixels (DOM snapshots)
+    Css,
+    /// Screenshot pixels (boxes detected in the image)
+    Pixel,
 }
 
 /// Type of layout difference.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum LayoutDiffKind {
     MissingElement,
     ExtraElement,
     PositionShift,
     SizeChange,
+    /// Gap, padding or direction of a container differs (Figma auto layout
+    /// vs flexbox)
+    SpacingMismatch,
+    /// Two sibling elements share an edge or center in the design but not
+    /// in the implementation
+    AlignmentBreak,
 }
 
 // ============================================================================
@@ -170,7 +247,7 @@ pub enum LayoutDiffKind {
 // ============================================================================
 
 /// Result of typography comparison.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TypographyMetric {
     /// Similarity score (0.0 - 1.0)
@@ -181,29 +258,50 @@ pub struct TypographyMetric {
 }
 
 /// A typography difference between elements.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TypographyDiff {
     /// Element ID in reference
     pub element_id_ref: Option<String>,
     /// Element ID in implementation
     pub element_id_impl: Option<String>,
+    /// Name of the text element: the Figma layer name, else the DOM
+    /// `data-testid` or `aria-label`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Published Figma text style of the reference element, e.g.
+    /// `Heading/H1` (`--figma-styles`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
     /// List of typography issues
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub issues: Vec<TypographyIssue>,
     /// Additional details (ref/impl values)
     pub details: Option<Value>,
+    /// Stable identifier for suppressing this finding (see `.dpc-ignore.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 /// Type of typography issue.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TypographyIssue {
     FontFamilyMismatch,
+    /// The implementation requests the design's family, but it did not load
+    /// and the browser rendered a fallback instead.
+    FontNotLoaded,
     FontSizeDiff,
     FontWeightDiff,
     LineHeightDiff,
     LetterSpacingDiff,
+    /// The text block wraps into a different number of lines.
+    LineWrapDiff,
+    /// The text sits higher or lower in its container (a button, a chip)
+    /// than in the design.
+    BaselineShift,
+    /// The baseline-to-baseline distance to the text block above differs.
+    VerticalRhythmDiff,
 }
 
 // ============================================================================
@@ -211,7 +309,7 @@ pub enum TypographyIssue {
 // ============================================================================
 
 /// Result of color palette comparison.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ColorMetric {
     /// Similarity score (0.0 - 1.0)
@@ -222,7 +320,7 @@ pub struct ColorMetric {
 }
 
 /// A color difference between palettes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ColorDiff {
     /// Type of color shift
@@ -233,15 +331,31 @@ pub struct ColorDiff {
     pub impl_color: String,
     /// Delta E (perceptual difference)
     pub delta_e: Option<f32>,
+    /// Reference element whose fill differs (gradient shifts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<String>,
+    /// Difference in gradient direction, in degrees (gradient shifts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle_delta: Option<f32>,
+    /// Published Figma fill style the reference color comes from, e.g.
+    /// `Primary/600` (`--figma-styles`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Stable identifier for suppressing this finding (see `.dpc-ignore.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 /// Type of color difference.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ColorDiffKind {
     PrimaryColorShift,
     AccentColorShift,
     BackgroundColorShift,
+    /// A matched element's gradient fill differs in direction or stops, or
+    /// became/stopped being a gradient.
+    GradientShift,
 }
 
 // ============================================================================
@@ -249,7 +363,7 @@ pub enum ColorDiffKind {
 // ============================================================================
 
 /// Result of content/text comparison.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ContentMetric {
     /// Similarity score (0.0 - 1.0)
@@ -260,4 +374,31 @@ pub struct ContentMetric {
     /// Text present in implementation but not in reference
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_text: Vec<String>,
+    /// Reference text recognized as placeholder copy and left out of the comparison
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub placeholder_text: Vec<String>,
+    /// Fingerprints of `missing_text`, index for index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_text_fingerprints: Vec<String>,
+    /// Fingerprints of `extra_text`, index for index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_text_fingerprints: Vec<String>,
+    /// Name of the element holding a missing or extra text (Figma layer
+    /// name, else DOM `data-testid` or `aria-label`), keyed by the text
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub text_labels: BTreeMap<String, String>,
+}
+
+// ============================================================================
+// Semantic Metric Types
+// ============================================================================
+
+/// Result of comparing whole-screenshot embeddings.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticMetric {
+    /// Similarity score (0.0 - 1.0)
+    pub score: f32,
+    /// Cosine similarity of the reference and implementation embeddings (-1.0 - 1.0)
+    pub cosine_similarity: f32,
 }
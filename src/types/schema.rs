@@ -0,0 +1,168 @@
+//! Schema versions of stored DOM and Figma snapshots.
+//!
+//! Snapshots outlive the binary that wrote them: `self-compare` baselines,
+//! `view:` bundles and kept artifacts are read back by later releases. Every
+//! snapshot records the `schemaVersion` it was written with, and the
+//! `migrate_*` functions upgrade older JSON step by step to the current
+//! version before it is deserialized. Snapshots from a newer release are
+//! rejected with an error instead of being misread.
+//!
+//! Version history (both snapshot kinds):
+//! - 1: unversioned snapshots written before `schemaVersion` existed
+//! - 2: adds `schemaVersion`
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{DpcError, Result};
+
+use super::{DomSnapshot, FigmaSnapshot};
+
+/// Version assumed for snapshots without a `schemaVersion` field.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "schemaVersion";
+
+/// One upgrade step, from the version at its index + 1 to the next.
+type Migration = fn(&mut serde_json::Map<String, Value>);
+
+/// DOM snapshot upgrades; entry `n` upgrades version `n + 1`.
+const DOM_MIGRATIONS: &[Migration] = &[add_schema_version];
+/// Figma snapshot upgrades; entry `n` upgrades version `n + 1`.
+const FIGMA_MIGRATIONS: &[Migration] = &[add_schema_version];
+
+/// 1 -> 2: only the version key is new, and [`migrate`] writes it after the
+/// last step.
+fn add_schema_version(_: &mut serde_json::Map<String, Value>) {}
+
+pub(crate) fn legacy_schema_version() -> u32 {
+    LEGACY_SCHEMA_VERSION
+}
+
+/// Upgrade DOM snapshot JSON in place to [`DomSnapshot::SCHEMA_VERSION`].
+pub fn migrate_dom_snapshot(value: &mut Value) -> Result<()> {
+    migrate("DOM", value, DOM_MIGRATIONS, DomSnapshot::SCHEMA_VERSION)
+}
+
+/// Upgrade Figma snapshot JSON in place to [`FigmaSnapshot::SCHEMA_VERSION`].
+pub fn migrate_figma_snapshot(value: &mut Value) -> Result<()> {
+    migrate(
+        "Figma",
+        value,
+        FIGMA_MIGRATIONS,
+        FigmaSnapshot::SCHEMA_VERSION,
+    )
+}
+
+/// Upgrade the `dom` and `figmaTree` snapshots of a serialized
+/// [`NormalizedView`](super::NormalizedView) in place.
+pub fn migrate_view(value: &mut Value) -> Result<()> {
+    if let Some(dom) = value.get_mut("dom").filter(|dom| !dom.is_null()) {
+        migrate_dom_snapshot(dom)?;
+    }
+    if let Some(figma) = value.get_mut("figmaTree").filter(|tree| !tree.is_null()) {
+        migrate_figma_snapshot(figma)?;
+    }
+    Ok(())
+}
+
+/// Migrate DOM snapshot JSON and deserialize it.
+pub fn dom_snapshot_from_value(mut value: Value) -> Result<DomSnapshot> {
+    migrate_dom_snapshot(&mut value)?;
+    deserialize("DOM", value)
+}
+
+/// Migrate Figma snapshot JSON and deserialize it.
+pub fn figma_snapshot_from_value(mut value: Value) -> Result<FigmaSnapshot> {
+    migrate_figma_snapshot(&mut value)?;
+    deserialize("Figma", value)
+}
+
+fn migrate(kind: &str, value: &mut Value, migrations: &[Migration], current: u32) -> Result<()> {
+    let Some(object) = value.as_object_mut() else {
+        return Err(DpcError::Config(format!(
+            "{kind} snapshot must be a JSON object"
+        )));
+    };
+    let version = match object.get(VERSION_KEY) {
+        None => LEGACY_SCHEMA_VERSION,
+        Some(raw) => raw
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= LEGACY_SCHEMA_VERSION)
+            .ok_or_else(|| {
+                DpcError::Config(format!(
+                    "{kind} snapshot has an invalid {VERSION_KEY} {raw}"
+                ))
+            })?,
+    };
+    if version > current {
+        return Err(DpcError::Config(format!(
+            "{kind} snapshot schema version {version} is newer than this dpc supports \
+             ({current}); upgrade dpc to read it"
+        )));
+    }
+    for step in &migrations[(version - LEGACY_SCHEMA_VERSION) as usize..] {
+        step(object);
+    }
+    object.insert(VERSION_KEY.to_string(), Value::from(current));
+    Ok(())
+}
+
+fn deserialize<T: DeserializeOwned>(kind: &str, value: Value) -> Result<T> {
+    serde_json::from_value(value)
+        .map_err(|e| DpcError::Config(format!("Invalid {kind} snapshot: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_snapshots_upgrade_to_the_current_version() {
+        let dom = dom_snapshot_from_value(json!({
+            "url": "https://example.com/",
+            "title": null,
+            "nodes": []
+        }))
+        .unwrap();
+        assert_eq!(dom.schema_version, DomSnapshot::SCHEMA_VERSION);
+
+        let mut view = json!({
+            "kind": "figma",
+            "screenshotPath": "screenshot.png",
+            "width": 4,
+            "height": 4,
+            "figmaTree": { "fileKey": "abc", "nodeId": "1:2", "name": null }
+        });
+        migrate_view(&mut view).unwrap();
+        assert_eq!(
+            view["figmaTree"]["schemaVersion"],
+            json!(FigmaSnapshot::SCHEMA_VERSION)
+        );
+        let written = serde_json::to_value(&dom).unwrap();
+        assert_eq!(written["schemaVersion"], json!(DomSnapshot::SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn every_version_has_a_migration_step() {
+        let steps = |current: u32| (current - LEGACY_SCHEMA_VERSION) as usize;
+        assert_eq!(DOM_MIGRATIONS.len(), steps(DomSnapshot::SCHEMA_VERSION));
+        assert_eq!(FIGMA_MIGRATIONS.len(), steps(FigmaSnapshot::SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn newer_and_invalid_versions_are_rejected() {
+        let err = dom_snapshot_from_value(json!({
+            "schemaVersion": DomSnapshot::SCHEMA_VERSION + 1,
+            "url": null,
+            "title": null
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("newer than this dpc supports"));
+
+        let err = migrate_figma_snapshot(&mut json!({ "schemaVersion": "two" })).unwrap_err();
+        assert!(err.to_string().contains("invalid schemaVersion"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Single frames from video files, for comparing key moments of motion designs.
+//!
+//! A resource like `flow.mp4@00:05.2` is an image resource whose bitmap is the
+//! frame shown at that timestamp. Decoding uses FFmpeg and needs the `video`
+//! feature.
+
+use std::path::Path;
+use std::time::Duration;
+
+use image::DynamicImage;
+
+use crate::error::{DpcError, Result};
+use crate::image_loader::{normalize_image, ImageLimits, ImageLoadOptions};
+use crate::types::NormalizedView;
+
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "webm", "mkv", "avi"];
+
+/// A `<video>@<timestamp>` resource value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrameRef<'a> {
+    pub path: &'a Path,
+    pub timestamp: Duration,
+}
+
+impl<'a> VideoFrameRef<'a> {
+    /// Split `value` into a video path and timestamp.
+    ///
+    /// Returns `None` when the part before the last `@` is not a video file
+    /// name, and `Some(Err(_))` when it is but the timestamp is malformed.
+    pub fn parse(value: &'a str) -> Option<std::result::Result<Self, String>> {
+        let (path, timestamp) = value.rsplit_once('@')?;
+        let path = Path::new(path);
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        if !VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+            return None;
+        }
+        Some(
+            parse_timestamp(timestamp)
+                .map(|timestamp| VideoFrameRef { path, timestamp })
+                .ok_or_else(|| {
+                    format!(
+                        "invalid timestamp '{}'; use SS(.s), MM:SS(.s) or HH:MM:SS(.s)",
+                        timestamp
+                    )
+                }),
+        )
+    }
+}
+
+/// Parse `SS`, `MM:SS` or `HH:MM:SS`, each with optional fractional seconds.
+pub fn parse_timestamp(value: &str) -> Option<Duration> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    let (seconds, whole) = parts.split_last()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    if !seconds.is_finite() || seconds < 0.0 || (!whole.is_empty() && seconds >= 60.0) {
+        return None;
+    }
+    let mut total = 0u64;
+    for (idx, part) in whole.iter().enumerate() {
+        let unit: u64 = part.parse().ok()?;
+        // Minutes after hours must stay below 60.
+        if idx > 0 && unit >= 60 {
+            return None;
+        }
+        total = total * 60 + unit;
+    }
+    Some(Duration::from_secs(total * 60) + Duration::from_secs_f64(seconds))
+}
+
+/// Decode the frame of `frame.path` displayed at `frame.timestamp` and
+/// normalize it like an image input.
+pub fn video_frame_to_normalized_view(
+    frame: &VideoFrameRef<'_>,
+    output_path: &Path,
+    options: ImageLoadOptions,
+) -> Result<NormalizedView> {
+    if !frame.path.is_file() {
+        return Err(DpcError::Config(format!(
+            "Video file not found: {}",
+            frame.path.display()
+        )));
+    }
+    let img = decode_frame(frame.path, frame.timestamp)?;
+    let (width, height) = (img.width(), img.height());
+    ImageLimits::current().check_dimensions(width, height, &frame.path.display().to_string())?;
+    Ok(normalize_image(img, output_path, options)?)
+}
+
+#[cfg(feature = "video")]
+fn decode_frame(path: &Path, at: Duration) -> Result<DynamicImage> {
+    use ffmpeg::format::Pixel;
+    use ffmpeg::media::Type;
+    use ffmpeg::software::scaling::{context::Context as Scaler, flag::Flags};
+    use ffmpeg::util::frame::video::Video;
+    use ffmpeg_next as ffmpeg;
+
+    let video_error = |err: ffmpeg::Error| {
+        DpcError::Config(format!(
+            "Video decoding failed for {}: {}",
+            path.display(),
+            err
+        ))
+    };
+
+    ffmpeg::init().map_err(video_error)?;
+    let mut input = ffmpeg::format::input(&path).map_err(video_error)?;
+    let stream = input
+        .streams()
+        .best(Type::Video)
+        .ok_or_else(|| DpcError::Config(format!("{} has no video stream", path.display())))?;
+    let stream_index = stream.index();
+    let time_base = f64::from(stream.time_base());
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        .and_then(|context| context.decoder().video())
+        .map_err(video_error)?;
+
+    // Seek to the keyframe before the timestamp, then decode forward.
+    let target = at.as_secs_f64();
+    let seek_ts = (target / f64::from(ffmpeg::rescale::TIME_BASE)) as i64;
+    input.seek(seek_ts, ..seek_ts).map_err(video_error)?;
+
+    let mut scaler = Scaler::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        Pixel::RGBA,
+        decoder.width(),
+        decoder.height(),
+        Flags::BILINEAR,
+    )
+    .map_err(video_error)?;
+
+    let mut last: Option<Video> = None;
+    let mut decoded = Video::empty();
+    let mut packets = input.packets();
+    loop {
+        let packet = packets.next();
+        match &packet {
+            Some((stream, packet)) if stream.index() == stream_index => {
+                decoder.send_packet(packet).map_err(video_error)?;
+            }
+            Some(_) => continue,
+            None => decoder.send_eof().map_err(video_error)?,
+        }
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            let seconds = decoded
+                .timestamp()
+                .map(|ts| ts as f64 * time_base)
+                .unwrap_or(f64::MAX);
+            // Keep the latest frame at or before the target; stop once past it.
+            if seconds > target {
+                if let Some(previous) = &last {
+                    return video_to_image(previous, &mut scaler, video_error);
+                }
+            }
+            last = Some(decoded.clone());
+        }
+        if packet.is_none() {
+            break;
+        }
+    }
+    match last {
+        Some(frame) => video_to_image(&frame, &mut scaler, video_error),
+        None => Err(DpcError::Config(format!(
+            "No frame found at {:?} in {}",
+            at,
+            path.display()
+        ))),
+    }
+}
+
+#[cfg(feature = "video")]
+fn video_to_image(
+    frame: &ffmpeg_next::util::frame::video::Video,
+    scaler: &mut ffmpeg_next::software::scaling::context::Context,
+    video_error: impl Fn(ffmpeg_next::Error) -> DpcError,
+) -> Result<DynamicImage> {
+    let mut rgba = ffmpeg_next::util::frame::video::Video::empty();
+    scaler.run(frame, &mut rgba).map_err(video_error)?;
+    let (width, height) = (rgba.width(), rgba.height());
+    let stride = rgba.stride(0);
+    let row_bytes = width as usize * 4;
+    // Rows are padded to the stride; copy only the visible pixels.
+    let mut pixels = Vec::with_capacity(row_bytes * height as usize);
+    for row in rgba.data(0).chunks(stride).take(height as usize) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    image::RgbaImage::from_raw(width, height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| DpcError::Unknown("Decoded video frame has a malformed buffer".to_string()))
+}
+
+#[cfg(not(feature = "video"))]
+fn decode_frame(_path: &Path, _at: Duration) -> Result<DynamicImage> {
+    Err(DpcError::Config(
+        "video frame inputs need dpc built with the `video` feature (cargo install dpc --features video)"
+            .to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("5"), Some(Duration::from_secs(5)));
+        assert_eq!(
+            parse_timestamp("00:05.2"),
+            Some(Duration::from_millis(5200))
+        );
+        assert_eq!(parse_timestamp("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_timestamp("00:75"), None);
+        assert_eq!(parse_timestamp("1:60:00"), None);
+        assert_eq!(parse_timestamp("abc"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn parses_video_frame_references() {
+        let frame = VideoFrameRef::parse("runs/flow.MP4@00:05.2")
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.path, Path::new("runs/flow.MP4"));
+        assert_eq!(frame.timestamp, Duration::from_millis(5200));
+
+        assert!(VideoFrameRef::parse("flow.mp4@soon").unwrap().is_err());
+        assert!(VideoFrameRef::parse("shot@2x.png").is_none());
+        assert!(VideoFrameRef::parse("flow.mp4").is_none());
+    }
+
+    #[cfg(not(feature = "video"))]
+    #[test]
+    fn decoding_without_the_video_feature_is_a_config_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let clip = dir.path().join("clip.mp4");
+        std::fs::write(&clip, b"not really a video").unwrap();
+        let value = format!("{}@1", clip.display());
+        let frame = VideoFrameRef::parse(&value).unwrap().unwrap();
+        let err = video_frame_to_normalized_view(
+            &frame,
+            &dir.path().join("out.png"),
+            ImageLoadOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, DpcError::Config(msg) if msg.contains("`video` feature")));
+    }
+}
@@ -0,0 +1,184 @@
+//! Portable bundles of a captured [`NormalizedView`].
+//!
+//! A bundle is a directory holding the screenshot and a `view.json` with the
+//! DOM, Figma tree and OCR blocks, so a view captured on a machine with a
+//! browser or Figma access can be compared elsewhere as `view:<dir>`. Bundles
+//! saved by older releases are migrated to the current snapshot schema on load.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{DpcError, Result};
+use crate::types::{migrate_view, NormalizedView};
+
+/// Resource prefix that loads a saved bundle (`view:captures/home`).
+pub const VIEW_SCHEME: &str = "view:";
+/// Name of the view description inside a bundle.
+pub const VIEW_FILE: &str = "view.json";
+/// Screenshot file name inside a bundle, without extension.
+const SCREENSHOT_STEM: &str = "screenshot";
+
+impl NormalizedView {
+    /// Write the view to `dir` (created if missing) as `view.json` plus a copy
+    /// of the screenshot; the saved screenshot path is relative to `dir`.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let extension = self
+            .screenshot_path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "png".to_string());
+        let screenshot_name = format!("{SCREENSHOT_STEM}.{extension}");
+        let target = dir.join(&screenshot_name);
+        if !is_same_file(&self.screenshot_path, &target) {
+            fs::copy(&self.screenshot_path, &target)?;
+        }
+
+        let view = NormalizedView {
+            screenshot_path: PathBuf::from(screenshot_name),
+            ..self.clone()
+        };
+        fs::write(dir.join(VIEW_FILE), serde_json::to_vec_pretty(&view)?)?;
+        Ok(())
+    }
+
+    /// Read a bundle written by [`save`](Self::save); the screenshot path is
+    /// resolved against `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let view_path = dir.join(VIEW_FILE);
+        if !view_path.is_file() {
+            return Err(DpcError::Config(format!(
+                "{} is not a view bundle (missing {VIEW_FILE})",
+                dir.display()
+            )));
+        }
+        let mut value: serde_json::Value = serde_json::from_slice(&fs::read(&view_path)?)?;
+        migrate_view(&mut value)?;
+        let mut view: NormalizedView = serde_json::from_value(value)?;
+        if view.screenshot_path.is_relative() {
+            view.screenshot_path = dir.join(&view.screenshot_path);
+        }
+        if !view.screenshot_path.is_file() {
+            return Err(DpcError::Config(format!(
+                "View bundle {} is missing its screenshot {}",
+                dir.display(),
+                view.screenshot_path.display()
+            )));
+        }
+        Ok(view)
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BoundingBox, DomNode, DomSnapshot, ResourceKind};
+    use image::RgbaImage;
+    use tempfile::TempDir;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let capture = TempDir::new().unwrap();
+        let screenshot = capture.path().join("impl_screenshot.png");
+        RgbaImage::new(4, 3).save(&screenshot).unwrap();
+        let view = NormalizedView {
+            kind: ResourceKind::Url,
+            screenshot_path: screenshot,
+            width: 4,
+            height: 3,
+            dom: Some(DomSnapshot {
+                schema_version: DomSnapshot::SCHEMA_VERSION,
+                url: Some("https://example.com/".to_string()),
+                title: None,
+                browser: None,
+                diagnostics: None,
+                custom_properties: None,
+                nodes: vec![DomNode {
+                    id: "n0".to_string(),
+                    tag: "h1".to_string(),
+                    children: vec![],
+                    parent: None,
+                    attributes: Default::default(),
+                    text: Some("Hello".to_string()),
+                    bounding_box: BoundingBox {
+                        x: 0.0,
+                        y: 0.0,
+                        width: 4.0,
+                        height: 1.0,
+                    },
+                    text_baseline: None,
+                    interaction_states: None,
+                    computed_style: None,
+                }],
+            }),
+            figma_tree: None,
+            ocr_blocks: None,
+            content_area: None,
+        };
+
+        let bundle = TempDir::new().unwrap();
+        view.save(bundle.path()).unwrap();
+        drop(capture);
+        let loaded = NormalizedView::load(bundle.path()).unwrap();
+
+        assert_eq!(loaded.kind, ResourceKind::Url);
+        assert_eq!(loaded.screenshot_path, bundle.path().join("screenshot.png"));
+        assert!(loaded.screenshot_path.is_file());
+        let dom = loaded.dom.unwrap();
+        assert_eq!(dom.url.as_deref(), Some("https://example.com/"));
+        assert_eq!(dom.nodes[0].text.as_deref(), Some("Hello"));
+
+        // Saving a loaded view back into its own bundle keeps the screenshot.
+        let reloaded = NormalizedView::load(bundle.path()).unwrap();
+        reloaded.save(bundle.path()).unwrap();
+        assert!(NormalizedView::load(bundle.path()).is_ok());
+    }
+
+    #[test]
+    fn load_migrates_unversioned_snapshots_and_rejects_newer_ones() {
+        let bundle = TempDir::new().unwrap();
+        RgbaImage::new(2, 2)
+            .save(bundle.path().join("screenshot.png"))
+            .unwrap();
+        let write_view = |dom_version: Option<u32>| {
+            let mut dom = serde_json::json!({ "url": null, "title": "Home", "nodes": [] });
+            if let Some(version) = dom_version {
+                dom["schemaVersion"] = version.into();
+            }
+            let view = serde_json::json!({
+                "kind": "url",
+                "screenshotPath": "screenshot.png",
+                "width": 2,
+                "height": 2,
+                "dom": dom
+            });
+            fs::write(bundle.path().join(VIEW_FILE), view.to_string()).unwrap();
+        };
+
+        write_view(None);
+        let loaded = NormalizedView::load(bundle.path()).unwrap();
+        let dom = loaded.dom.unwrap();
+        assert_eq!(dom.schema_version, DomSnapshot::SCHEMA_VERSION);
+        assert_eq!(dom.title.as_deref(), Some("Home"));
+
+        write_view(Some(DomSnapshot::SCHEMA_VERSION + 1));
+        let err = NormalizedView::load(bundle.path()).unwrap_err();
+        assert!(err.to_string().contains("upgrade dpc"), "{err}");
+    }
+
+    #[test]
+    fn load_rejects_directories_without_a_view() {
+        let dir = TempDir::new().unwrap();
+        let err = NormalizedView::load(dir.path()).unwrap_err();
+        assert!(matches!(err, DpcError::Config(_)), "{err}");
+    }
+}
@@ -1,26 +1,76 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Named viewport sizes accepted in place of `WIDTHxHEIGHT`.
+pub const VIEWPORT_PRESETS: &[(&str, u32, u32)] = &[
+    ("desktop", 1440, 900),
+    ("laptop", 1280, 800),
+    ("tablet", 768, 1024),
+    ("mobile", 375, 812),
+];
+
+/// Aspect ratios further apart than this factor leave a large part of a
+/// letterboxed render empty (20% or more of the viewport).
+pub const ASPECT_MISMATCH_FACTOR: f32 = 1.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Viewport {
     pub width: u32,
     pub height: u32,
+    /// Device pixel ratio for browser captures (screenshot pixels per CSS px).
+    #[serde(
+        default = "default_device_scale_factor",
+        skip_serializing_if = "is_default_device_scale_factor"
+    )]
+    pub device_scale_factor: f32,
+}
+
+fn default_device_scale_factor() -> f32 {
+    1.0
+}
+
+fn is_default_device_scale_factor(value: &f32) -> bool {
+    *value == 1.0
 }
 
 impl Default for Viewport {
     fn default() -> Self {
+        Self::new(1440, 900)
+    }
+}
+
+impl Viewport {
+    /// A viewport at device pixel ratio 1.
+    pub fn new(width: u32, height: u32) -> Self {
         Self {
-            width: 1440,
-            height: 900,
+            width,
+            height,
+            device_scale_factor: default_device_scale_factor(),
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// How far `width`x`height` is from this viewport's aspect ratio, as a
+    /// factor >= 1.0, when it exceeds [`ASPECT_MISMATCH_FACTOR`].
+    pub fn aspect_mismatch(&self, width: u32, height: u32) -> Option<f32> {
+        if width == 0 || height == 0 || self.width == 0 || self.height == 0 {
+            return None;
         }
+        let ratio = (width as f32 / height as f32) / self.aspect_ratio();
+        let factor = ratio.max(1.0 / ratio);
+        (factor > ASPECT_MISMATCH_FACTOR).then_some(factor)
     }
 }
 
 #[derive(Debug, Error)]
 pub enum ViewportParseError {
-    #[error("Invalid viewport format: expected WIDTHxHEIGHT (e.g., 1440x900)")]
+    #[error("Invalid viewport format: expected WIDTHxHEIGHT[@SCALEx] (e.g., 1440x900, 1440x900@2x) or a preset (desktop, laptop, tablet, mobile)")]
     InvalidFormat,
     #[error("Invalid width: {0}")]
     InvalidWidth(String),
@@ -30,41 +80,78 @@ pub enum ViewportParseError {
     ZeroWidth,
     #[error("Height must be positive")]
     ZeroHeight,
+    #[error("Invalid device scale factor: {0} (expected e.g. @2x)")]
+    InvalidScale(String),
 }
 
 impl FromStr for Viewport {
     type Err = ViewportParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split('x').collect();
-        if parts.len() != 2 {
-            return Err(ViewportParseError::InvalidFormat);
+        let (size, scale) = match s.split_once('@') {
+            Some((size, scale)) => (size, Some(scale)),
+            None => (s, None),
+        };
+
+        let mut viewport = match VIEWPORT_PRESETS
+            .iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(size.trim()))
+        {
+            Some(&(_, width, height)) => Viewport::new(width, height),
+            None => parse_size(size)?,
+        };
+
+        if let Some(scale) = scale {
+            let trimmed = scale.trim();
+            let factor: f32 = trimmed
+                .strip_suffix(['x', 'X'])
+                .unwrap_or(trimmed)
+                .trim()
+                .parse()
+                .map_err(|_| ViewportParseError::InvalidScale(scale.to_string()))?;
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(ViewportParseError::InvalidScale(scale.to_string()));
+            }
+            viewport.device_scale_factor = factor;
         }
 
-        let width: u32 = parts[0]
-            .trim()
-            .parse()
-            .map_err(|_| ViewportParseError::InvalidWidth(parts[0].to_string()))?;
+        Ok(viewport)
+    }
+}
+
+fn parse_size(s: &str) -> Result<Viewport, ViewportParseError> {
+    let parts: Vec<&str> = s.split('x').collect();
+    if parts.len() != 2 {
+        return Err(ViewportParseError::InvalidFormat);
+    }
 
-        let height: u32 = parts[1]
-            .trim()
-            .parse()
-            .map_err(|_| ViewportParseError::InvalidHeight(parts[1].to_string()))?;
+    let width: u32 = parts[0]
+        .trim()
+        .parse()
+        .map_err(|_| ViewportParseError::InvalidWidth(parts[0].to_string()))?;
 
-        if width == 0 {
-            return Err(ViewportParseError::ZeroWidth);
-        }
-        if height == 0 {
-            return Err(ViewportParseError::ZeroHeight);
-        }
+    let height: u32 = parts[1]
+        .trim()
+        .parse()
+        .map_err(|_| ViewportParseError::InvalidHeight(parts[1].to_string()))?;
 
-        Ok(Viewport { width, height })
+    if width == 0 {
+        return Err(ViewportParseError::ZeroWidth);
+    }
+    if height == 0 {
+        return Err(ViewportParseError::ZeroHeight);
     }
+
+    Ok(Viewport::new(width, height))
 }
 
 impl std::fmt::Display for Viewport {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}x{}", self.width, self.height)
+        write!(f, "{}x{}", self.width, self.height)?;
+        if !is_default_device_scale_factor(&self.device_scale_factor) {
+            write!(f, "@{}x", self.device_scale_factor)?;
+        }
+        Ok(())
     }
 }
 
@@ -114,10 +201,54 @@ mod tests {
 
     #[test]
     fn test_display() {
-        let vp = Viewport {
-            width: 1920,
-            height: 1080,
-        };
+        let vp = Viewport::new(1920, 1080);
         assert_eq!(format!("{}", vp), "1920x1080");
+        let retina: Viewport = "1440x900@2x".parse().unwrap();
+        assert_eq!(format!("{}", retina), "1440x900@2x");
+    }
+
+    #[test]
+    fn test_parse_device_scale_factor() {
+        let vp: Viewport = "1440x900@2x".parse().unwrap();
+        assert_eq!((vp.width, vp.height), (1440, 900));
+        assert_eq!(vp.device_scale_factor, 2.0);
+        assert_eq!(
+            "390x844@1.5"
+                .parse::<Viewport>()
+                .unwrap()
+                .device_scale_factor,
+            1.5
+        );
+        assert!("1440x900@0x".parse::<Viewport>().is_err());
+        assert!("1440x900@abc".parse::<Viewport>().is_err());
+    }
+
+    #[test]
+    fn test_parse_presets() {
+        assert_eq!(
+            "desktop".parse::<Viewport>().unwrap(),
+            Viewport::new(1440, 900)
+        );
+        let mobile: Viewport = "Mobile@3x".parse().unwrap();
+        assert_eq!((mobile.width, mobile.height), (375, 812));
+        assert_eq!(mobile.device_scale_factor, 3.0);
+        assert!("watch".parse::<Viewport>().is_err());
+    }
+
+    #[test]
+    fn test_serde_omits_default_scale() {
+        let json = serde_json::to_string(&Viewport::new(800, 600)).unwrap();
+        assert_eq!(json, r#"{"width":800,"height":600}"#);
+        let back: Viewport = serde_json::from_str(r#"{"width":800,"height":600}"#).unwrap();
+        assert_eq!(back.device_scale_factor, 1.0);
+    }
+
+    #[test]
+    fn test_aspect_mismatch() {
+        let vp = Viewport::new(1440, 900);
+        assert!(vp.aspect_mismatch(2880, 1800).is_none());
+        assert!(vp.aspect_mismatch(1440, 1000).is_none());
+        let factor = vp.aspect_mismatch(1440, 4000).unwrap();
+        assert!((factor - 4.444).abs() < 0.01);
     }
 }
@@ -32,6 +32,49 @@ fn compare_exit_code_passes_for_matching_images() {
     assert_eq!(status.code(), Some(0));
 }
 
+#[test]
+fn compare_replays_saved_view_bundles() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    let artifacts = dir.path().join("artifacts");
+    write_image(&ref_path, [10, 20, 30, 255]);
+    write_image(&impl_path, [10, 20, 30, 255]);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            impl_path.to_str().unwrap(),
+            "--viewport",
+            "4x4",
+            "--artifacts-dir",
+            artifacts.to_str().unwrap(),
+        ])
+        .status()
+        .expect("run dpc");
+    assert_eq!(status.code(), Some(0));
+    assert!(artifacts.join("ref_view").join("view.json").is_file());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "compare",
+            "--ref",
+            &format!("view:{}", artifacts.join("ref_view").display()),
+            "--impl",
+            &format!("view:{}", artifacts.join("impl_view").display()),
+            "--viewport",
+            "4x4",
+        ])
+        .output()
+        .expect("run dpc");
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json output");
+    assert_eq!(json["ref"]["kind"], "view");
+}
+
 #[test]
 fn compare_accepts_config_flag_and_still_passes() {
     let dir = TempDir::new().expect("tempdir");
@@ -273,6 +316,129 @@ fn compare_exit_code_returns_fatal_for_invalid_input() {
     assert_eq!(status.code(), Some(2));
 }
 
+#[test]
+fn compare_reports_missing_init_script() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    write_image(&ref_path, [10, 20, 30, 255]);
+    let script = dir.path().join("seed.js");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            ref_path.to_str().unwrap(),
+            "--init-script",
+            script.to_str().unwrap(),
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("run dpc");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Init script not found"), "{stdout}");
+}
+
+#[test]
+fn compare_rejects_invalid_emulation_flags() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    write_image(&ref_path, [10, 20, 30, 255]);
+
+    for (flag, value) in [
+        ("--locale", "german"),
+        ("--timezone", "Berlin time"),
+        ("--grant-permissions", "notifications,telepathy"),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+            .args([
+                "compare",
+                "--ref",
+                ref_path.to_str().unwrap(),
+                "--impl",
+                ref_path.to_str().unwrap(),
+                flag,
+                value,
+                "--format",
+                "json",
+            ])
+            .output()
+            .expect("run dpc");
+        assert_eq!(output.status.code(), Some(2));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(flag), "{stdout}");
+    }
+}
+
+#[test]
+fn post_figma_comments_requires_a_figma_reference() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    write_image(&ref_path, [10, 20, 30, 255]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            ref_path.to_str().unwrap(),
+            "--post-figma-comments",
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("run dpc");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--post-figma-comments"), "{stdout}");
+}
+
+#[test]
+fn publish_github_without_a_target_is_a_config_error() {
+    let dir = TempDir::new().expect("tempdir");
+    let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args(["publish-github", dir.path().to_str().unwrap()])
+        .env("GITHUB_TOKEN", "token")
+        .env("GITHUB_REPOSITORY", "owner/name")
+        .output()
+        .expect("run dpc");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stderr.contains("--pr") || stdout.contains("--pr"),
+        "{stdout}{stderr}"
+    );
+}
+
+#[test]
+fn open_issue_without_credentials_is_a_config_error() {
+    let dir = TempDir::new().expect("tempdir");
+    let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "open-issue",
+            dir.path().to_str().unwrap(),
+            "--tracker",
+            "linear",
+            "--project",
+            "WEB",
+        ])
+        .env_remove("LINEAR_API_KEY")
+        .output()
+        .expect("run dpc");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stderr.contains("LINEAR_API_KEY") || stdout.contains("LINEAR_API_KEY"),
+        "{stdout}{stderr}"
+    );
+}
+
 fn run_compare_pretty(args: &[&str]) -> std::process::Output {
     Command::new(env!("CARGO_BIN_EXE_dpc"))
         .args(args)
@@ -722,3 +888,111 @@ fn compare_pretty_exit_code_returns_fatal_for_invalid_input() {
         .expect("run dpc");
     assert_eq!(status.code(), Some(2));
 }
+
+#[test]
+fn diff_results_reports_regressions_between_compare_runs() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let same_path = dir.path().join("same.png");
+    let changed_path = dir.path().join("changed.png");
+    write_image(&ref_path, [10, 20, 30, 255]);
+    write_image(&same_path, [10, 20, 30, 255]);
+    write_image(&changed_path, [240, 240, 240, 255]);
+
+    let compare = |impl_path: &std::path::Path, output: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_dpc"))
+            .args([
+                "compare",
+                "--ref",
+                ref_path.to_str().unwrap(),
+                "--impl",
+                impl_path.to_str().unwrap(),
+                "--metrics",
+                "pixel",
+                "--output",
+                output.to_str().unwrap(),
+            ])
+            .status()
+            .expect("run dpc")
+    };
+    let old = dir.path().join("old.json");
+    let new = dir.path().join("new.json");
+    assert_eq!(compare(&same_path, &old).code(), Some(0));
+    assert_eq!(compare(&changed_path, &new).code(), Some(1));
+
+    let run = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args([
+            "diff-results",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--fail-on-regression",
+        ])
+        .output()
+        .expect("run dpc");
+    assert_eq!(run.status.code(), Some(1));
+    let parsed: DpcOutput = serde_json::from_slice(&run.stdout).expect("parse json");
+    match parsed {
+        DpcOutput::DiffResults(out) => {
+            assert!(out.diff.similarity_delta < 0.0);
+            assert_eq!(out.diff.metrics.len(), 1);
+            assert_eq!(out.diff.metrics[0].trend, dpc_lib::MetricTrend::Regressed);
+            assert!(!out.diff.appeared.is_empty());
+            assert!(out.diff.disappeared.is_empty());
+        }
+        other => panic!("unexpected output: {other:?}"),
+    }
+
+    // Without --fail-on-regression the report alone exits zero.
+    let status = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .args(["diff-results", new.to_str().unwrap(), old.to_str().unwrap()])
+        .status()
+        .expect("run dpc");
+    assert_eq!(status.code(), Some(0));
+}
+
+#[test]
+fn json_outputs_validate_against_the_published_schema() {
+    let dir = TempDir::new().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    write_image(&ref_path, [10, 20, 30, 255]);
+    write_image(&impl_path, [200, 20, 30, 255]);
+    let artifacts = dir.path().join("artifacts");
+    let (ref_arg, impl_arg) = (ref_path.to_str().unwrap(), impl_path.to_str().unwrap());
+
+    let schema = Command::new(env!("CARGO_BIN_EXE_dpc"))
+        .arg("schema")
+        .output()
+        .expect("run dpc schema");
+    assert_eq!(schema.status.code(), Some(0));
+    let schema: serde_json::Value = serde_json::from_slice(&schema.stdout).expect("schema JSON");
+    let validator = jsonschema::validator_for(&schema).expect("valid JSON Schema");
+
+    let runs: [&[&str]; 3] = [
+        &[
+            "compare",
+            "--ref",
+            ref_arg,
+            "--impl",
+            impl_arg,
+            "--artifacts-dir",
+            artifacts.to_str().unwrap(),
+        ],
+        &["compare", "--ref", "missing.png", "--impl", impl_arg],
+        &["quality", "--input", impl_arg, "--viewport", "640x480"],
+    ];
+    for args in runs {
+        let output = Command::new(env!("CARGO_BIN_EXE_dpc"))
+            .args(args)
+            .args(["--format", "json"])
+            .output()
+            .expect("run dpc");
+        let body: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("output should be JSON");
+        let errors: Vec<String> = validator
+            .iter_errors(&body)
+            .map(|err| format!("{}: {err}", err.instance_path))
+            .collect();
+        assert!(errors.is_empty(), "{args:?} output: {errors:#?}");
+    }
+}
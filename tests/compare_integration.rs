@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
 use dpc_lib::error::ErrorCategory;
-use dpc_lib::{DpcOutput, ResourceKind};
+use dpc_lib::{AcceptedFindings, DpcOutput, Finding, ResourceKind};
 use image::{ImageBuffer, Rgba};
 use serde_json::Value;
 use tempfile::tempdir;
@@ -103,6 +103,17 @@ fn compare_emits_artifacts_when_keep_artifacts_is_enabled() {
             assert!(impl_shot.exists(), "impl screenshot should exist on disk");
             let diff_heatmap = artifacts.diff_image.expect("diff heatmap path");
             assert!(diff_heatmap.exists(), "diff heatmap should exist on disk");
+
+            let manifest_path = artifacts.run_manifest.expect("run manifest path");
+            let manifest: Value =
+                serde_json::from_slice(&std::fs::read(manifest_path).unwrap()).unwrap();
+            assert_eq!(manifest["command"], "compare");
+            assert_eq!(manifest["options"]["threshold"], 0.9);
+            let ref_hash = manifest["inputs"]["ref"]["sourceSha256"]
+                .as_str()
+                .expect("ref source hash");
+            assert_eq!(ref_hash.len(), 64);
+            assert!(manifest["inputs"]["impl"]["screenshotSha256"].is_string());
         }
         other => panic!("expected compare output, got {:?}", other),
     }
@@ -171,6 +182,105 @@ fn compare_reports_artifacts_even_when_not_kept() {
     }
 }
 
+#[test]
+fn artifact_store_publishes_artifacts_and_reports_their_locations() {
+    let dir = tempdir().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    let store_dir = dir.path().join("store");
+
+    let ref_img: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let impl_img: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    ref_img.save(&ref_path).unwrap();
+    impl_img.save(&impl_path).unwrap();
+
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            impl_path.to_str().unwrap(),
+            "--format",
+            "json",
+            "--artifact-store",
+            store_dir.to_str().unwrap(),
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(0));
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            let artifacts = out.artifacts.expect("artifacts block");
+            assert!(artifacts.kept, "published artifacts should be kept");
+            let store_dir = store_dir.canonicalize().unwrap();
+            assert!(
+                artifacts.directory.starts_with(&store_dir),
+                "directory should point into the store, got {}",
+                artifacts.directory.display()
+            );
+            for path in [
+                artifacts.ref_screenshot.expect("ref screenshot"),
+                artifacts.impl_screenshot.expect("impl screenshot"),
+                artifacts.diff_image.expect("diff heatmap"),
+            ] {
+                assert!(path.starts_with(&artifacts.directory));
+                assert!(path.exists(), "{} should exist", path.display());
+            }
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
+#[test]
+fn cache_dir_reuses_scores_until_an_input_changes() {
+    let dir = tempdir().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    let cache_dir = dir.path().join("cache");
+
+    let ref_img: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    ref_img.save(&ref_path).unwrap();
+    ref_img.save(&impl_path).unwrap();
+
+    let run = || {
+        let output = run_compare(
+            &[
+                "--verbose",
+                "compare",
+                "--ref",
+                ref_path.to_str().unwrap(),
+                "--impl",
+                impl_path.to_str().unwrap(),
+                "--format",
+                "json",
+                "--cache-dir",
+                cache_dir.to_str().unwrap(),
+            ],
+            &[],
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let similarity = match parse_output(&output.stdout) {
+            DpcOutput::Compare(out) => out.similarity,
+            other => panic!("expected compare output, got {:?}", other),
+        };
+        (stderr.contains("reusing cached metric scores"), similarity)
+    };
+
+    let (cached, first) = run();
+    assert!(!cached, "first run has nothing cached");
+    let (cached, second) = run();
+    assert!(cached, "unchanged inputs should hit the cache");
+    assert_eq!(first, second);
+
+    let changed: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_pixel(4, 4, Rgba([200, 20, 30, 255]));
+    changed.save(&impl_path).unwrap();
+    let (cached, third) = run();
+    assert!(!cached, "a changed implementation must be recomputed");
+    assert!(third < second);
+}
+
 #[test]
 fn ignore_regions_masks_pixel_differences() {
     let dir = tempdir().expect("tempdir");
@@ -226,6 +336,150 @@ fn ignore_regions_masks_pixel_differences() {
     }
 }
 
+#[test]
+fn accepted_findings_from_a_kept_run_are_suppressed() {
+    let dir = tempdir().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    let artifacts = dir.path().join("artifacts");
+    let accepted_path = dir.path().join("accepted.json");
+
+    let ref_img: ImageBuffer<Rgba<u8>, _> =
+        ImageBuffer::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+    let mut impl_img = ref_img.clone();
+    for y in 16..32 {
+        for x in 16..32 {
+            impl_img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+    }
+    ref_img.save(&ref_path).unwrap();
+    impl_img.save(&impl_path).unwrap();
+
+    let compare = |extra: &[&str]| {
+        let mut args = vec![
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            impl_path.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--threshold",
+            "0.95",
+        ];
+        args.extend_from_slice(extra);
+        run_compare(&args, &[])
+    };
+
+    let first = compare(&["--artifacts-dir", artifacts.to_str().unwrap()]);
+    assert_eq!(first.status.code(), Some(1));
+    let saved: DpcOutput =
+        serde_json::from_slice(&std::fs::read(artifacts.join("result.json")).unwrap()).unwrap();
+    let DpcOutput::Compare(saved) = saved else {
+        panic!("result.json should hold the compare output");
+    };
+    assert!(!saved.passed);
+    let findings = Finding::collect(&saved.metrics);
+    assert!(
+        !findings.is_empty(),
+        "expected the black square to be reported"
+    );
+
+    // What `dpc review` writes after accepting every finding.
+    AcceptedFindings { accepted: findings }
+        .save(&accepted_path)
+        .unwrap();
+    let second = compare(&["--accepted", accepted_path.to_str().unwrap()]);
+    assert_eq!(
+        second.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&second.stderr)
+    );
+    match parse_output(&second.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(out.passed);
+            assert!(out.similarity > saved.similarity);
+            assert!(out.metrics.pixel.unwrap().diff_regions.is_empty());
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
+#[test]
+fn suppression_file_baselines_findings_but_new_ones_still_fail() {
+    let dir = tempdir().expect("tempdir");
+    let ref_path = dir.path().join("ref.png");
+    let impl_path = dir.path().join("impl.png");
+    let ignore_path = dir.path().join(".dpc-ignore.json");
+
+    let ref_img: ImageBuffer<Rgba<u8>, _> =
+        ImageBuffer::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+    let mut impl_img = ref_img.clone();
+    let paint = |img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, x0: u32, y0: u32| {
+        for y in y0..y0 + 12 {
+            for x in x0..x0 + 12 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+    };
+    paint(&mut impl_img, 4, 4);
+    ref_img.save(&ref_path).unwrap();
+    impl_img.save(&impl_path).unwrap();
+
+    let compare = |extra: &[&str]| {
+        let mut args = vec![
+            "compare",
+            "--ref",
+            ref_path.to_str().unwrap(),
+            "--impl",
+            impl_path.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--threshold",
+            "0.999",
+            "--ignore-file",
+            ignore_path.to_str().unwrap(),
+        ];
+        args.extend_from_slice(extra);
+        run_compare(&args, &[])
+    };
+
+    let baseline = compare(&["--update-ignore-file"]);
+    assert_eq!(
+        baseline.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&baseline.stderr)
+    );
+    let file: Value = serde_json::from_slice(&std::fs::read(&ignore_path).unwrap()).unwrap();
+    let listed = file["suppressions"].as_array().unwrap().len();
+    assert!(listed > 0);
+    match parse_output(&baseline.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(out.passed);
+            assert_eq!(out.suppressed.len(), listed);
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+
+    // A new deviation elsewhere is not covered by the baseline.
+    paint(&mut impl_img, 44, 44);
+    impl_img.save(&impl_path).unwrap();
+    let regressed = compare(&[]);
+    assert_eq!(regressed.status.code(), Some(1));
+    match parse_output(&regressed.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(!out.passed);
+            assert!(!out.suppressed.is_empty());
+            let pixel = out.metrics.pixel.unwrap();
+            assert!(!pixel.diff_regions.is_empty());
+            assert!(pixel.diff_regions.iter().all(|r| r.fingerprint.is_some()));
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
 #[test]
 fn ignore_regions_accepts_width_height_keys_and_normalized_values() {
     let dir = tempdir().expect("tempdir");
@@ -317,6 +571,518 @@ fn image_inputs_emit_json_and_pass() {
     }
 }
 
+#[test]
+fn layout_from_pixels_scores_layout_for_image_inputs() {
+    let (reference, implementation) = (asset("ref.png"), asset("impl_different.png"));
+    let compare = |extra: &[&str]| {
+        let mut args = vec![
+            "compare",
+            "--ref",
+            reference.to_str().unwrap(),
+            "--impl",
+            implementation.to_str().unwrap(),
+            "--format",
+            "json",
+            "--threshold",
+            "0",
+        ];
+        args.extend_from_slice(extra);
+        let output = run_compare(&args, &[]);
+        assert!(output.status.success(), "{:?}", output.status.code());
+        match parse_output(&output.stdout) {
+            DpcOutput::Compare(out) => out,
+            other => panic!("expected compare output, got {:?}", other),
+        }
+    };
+
+    assert!(compare(&[]).metrics.layout.is_none());
+    let out = compare(&["--layout-from-pixels"]);
+    let layout = out
+        .metrics
+        .layout
+        .expect("layout metric from detected boxes");
+    assert!((0.0..=1.0).contains(&layout.score));
+}
+
+#[test]
+fn resource_pressure_downscales_screenshots_and_reports_it() {
+    let dir = tempdir().expect("tempdir");
+    let config = dir.path().join("dpc.toml");
+    std::fs::write(
+        &config,
+        "[resources]\nmin_cpus = 100000\nmax_megapixels = 0.0025\n",
+    )
+    .expect("write config");
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            asset("ref.png").to_str().unwrap(),
+            "--impl",
+            asset("impl_different.png").to_str().unwrap(),
+            "--config",
+            config.to_str().unwrap(),
+            "--format",
+            "json",
+            "--threshold",
+            "0",
+        ],
+        &[],
+    );
+    assert!(output.status.success(), "{:?}", output.status.code());
+
+    let DpcOutput::Compare(out) = parse_output(&output.stdout) else {
+        panic!("expected compare output");
+    };
+    let degradation = out.degradation.expect("degradation reported");
+    assert!(degradation.reason.contains("resources.min_cpus"));
+    assert_eq!(degradation.block_size, 64);
+    let sides: Vec<&str> = degradation
+        .downscaled
+        .iter()
+        .map(|d| d.side.as_str())
+        .collect();
+    assert_eq!(sides, ["ref", "impl"]);
+    for screenshot in &degradation.downscaled {
+        assert!(screenshot.analyzed.width * screenshot.analyzed.height <= 2500);
+        assert!(screenshot.analyzed.width < screenshot.original.width);
+    }
+    assert!(out.warnings.iter().any(|w| w.contains("[resources]")));
+    assert!(out.metrics.pixel.is_some());
+}
+
+#[test]
+fn sections_are_scored_separately() {
+    let dir = tempfile::tempdir().unwrap();
+    let sections = dir.path().join("sections.json");
+    std::fs::write(
+        &sections,
+        r#"[
+            {"name": "top", "region": {"x": 0, "y": 0, "width": 1, "height": 0.5}},
+            {"name": "nav", "selector": "nav"},
+            {"name": "bottom", "region": {"x": 0, "y": 0.5, "width": 1, "height": 0.5}}
+        ]"#,
+    )
+    .unwrap();
+    let (reference, implementation) = (asset("ref.png"), asset("impl_identical.png"));
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            reference.to_str().unwrap(),
+            "--impl",
+            implementation.to_str().unwrap(),
+            "--format",
+            "json",
+            "--sections",
+            sections.to_str().unwrap(),
+        ],
+        &[],
+    );
+    assert!(output.status.success(), "{:?}", output.status.code());
+    let DpcOutput::Compare(out) = parse_output(&output.stdout) else {
+        panic!("expected compare output");
+    };
+    let names: Vec<&str> = out.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["top", "bottom"]);
+    for section in &out.sections {
+        assert!(section.passed && section.similarity > 0.99, "{section:?}");
+        assert!(section.metrics.pixel.is_some());
+    }
+    assert!(out.warnings.iter().any(|w| w.contains("nav")));
+}
+
+#[test]
+fn only_regions_rescores_failed_regions_from_saved_captures() {
+    let dir = tempfile::tempdir().unwrap();
+    let (artifacts, prior) = (dir.path().join("artifacts"), dir.path().join("prior.json"));
+    let (reference, different) = (asset("ref.png"), asset("impl_different.png"));
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            reference.to_str().unwrap(),
+            "--impl",
+            different.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--format",
+            "json",
+            "--artifacts-dir",
+            artifacts.to_str().unwrap(),
+            "--output",
+            prior.to_str().unwrap(),
+        ],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let DpcOutput::Compare(first) = parse_output(&std::fs::read(&prior).unwrap()) else {
+        panic!("expected compare output");
+    };
+    let regions = first.metrics.pixel.as_ref().unwrap().diff_regions.len();
+    assert!(regions > 0);
+
+    // Both inputs come from the saved captures; only the regions are scored.
+    let output = run_compare(
+        &[
+            "compare",
+            "--only-regions",
+            prior.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--format",
+            "json",
+        ],
+        &[],
+    );
+    let DpcOutput::Compare(out) = parse_output(&output.stdout) else {
+        panic!("expected compare output, got {:?}", output);
+    };
+    assert!(out.ref_resource.value.starts_with("view:"));
+    assert_eq!(out.sections.len(), regions);
+    assert_eq!(out.sections[0].name, "diff-region-1");
+    assert!(out.sections.iter().all(|s| s.metrics.pixel.is_some()));
+
+    // Without kept artifacts there is nothing to re-score from.
+    std::fs::remove_dir_all(&artifacts).unwrap();
+    let output = run_compare(&["compare", "--only-regions", prior.to_str().unwrap()], &[]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn only_regions_keeps_fine_blocks_under_resource_pressure() {
+    let dir = tempfile::tempdir().unwrap();
+    let (artifacts, prior) = (dir.path().join("artifacts"), dir.path().join("prior.json"));
+    let config = dir.path().join("dpc.toml");
+    std::fs::write(&config, "[resources]\nmin_cpus = 100000\n").unwrap();
+    let (reference, different) = (asset("ref.png"), asset("impl_different.png"));
+    run_compare(
+        &[
+            "compare",
+            "--ref",
+            reference.to_str().unwrap(),
+            "--impl",
+            different.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--format",
+            "json",
+            "--artifacts-dir",
+            artifacts.to_str().unwrap(),
+            "--output",
+            prior.to_str().unwrap(),
+        ],
+        &[],
+    );
+
+    let output = run_compare(
+        &[
+            "compare",
+            "--only-regions",
+            prior.to_str().unwrap(),
+            "--config",
+            config.to_str().unwrap(),
+            "--metrics",
+            "pixel",
+            "--format",
+            "json",
+        ],
+        &[],
+    );
+    let DpcOutput::Compare(out) = parse_output(&output.stdout) else {
+        panic!("expected compare output, got {:?}", output);
+    };
+    let degradation = out.degradation.expect("degradation reported");
+    assert_eq!(degradation.block_size, 8);
+    assert!(out
+        .warnings
+        .iter()
+        .any(|w| w.contains("in 8px pixel blocks")));
+}
+
+#[test]
+fn flow_compares_each_prototype_screen_in_order() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("flow.json");
+    std::fs::write(
+        &file,
+        serde_json::json!({
+            "name": "Shop",
+            "lastModified": "2026-01-01T00:00:00Z",
+            "version": "1",
+            "document": {
+                "id": "0:0",
+                "name": "Document",
+                "type": "DOCUMENT",
+                "children": [{
+                    "id": "0:1",
+                    "name": "Flows",
+                    "type": "CANVAS",
+                    "children": [
+                        {"id": "1:1", "name": "Cart", "type": "FRAME", "transitionNodeID": "1:2"},
+                        {"id": "1:2", "name": "Payment", "type": "FRAME", "transitionNodeID": "1:3"},
+                        {"id": "1:3", "name": "Done", "type": "FRAME"}
+                    ]
+                }]
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::copy(asset("ref.png"), file.with_extension("png")).unwrap();
+    let start = format!("figma-json:{}#node-id=1:1", file.display());
+    let (reference, different) = (asset("ref.png"), asset("impl_different.png"));
+    let reference = reference.to_str().unwrap();
+    let output = run_compare(
+        &[
+            "flow",
+            "--ref",
+            &start,
+            "--impl",
+            reference,
+            "--impl",
+            different.to_str().unwrap(),
+            "--format",
+            "json",
+        ],
+        &[
+            ("DPC_MOCK_RENDER_REF_STEP1", reference),
+            ("DPC_MOCK_RENDER_REF_STEP2", reference),
+            ("DPC_MOCK_RENDER_REF_STEP3", reference),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let DpcOutput::Flow(out) = parse_output(&output.stdout) else {
+        panic!("expected flow output");
+    };
+    let names: Vec<&str> = out.screens.iter().map(|s| s.frame_name.as_str()).collect();
+    assert_eq!(names, ["Cart", "Payment", "Done"]);
+    let similarity = |step: usize| out.screens[step].similarity.unwrap();
+    assert!(out.screens[0].passed && similarity(0) > 0.99);
+    assert!(similarity(1) < similarity(0));
+    assert!(out.screens[2].impl_resource.is_none() && !out.screens[2].passed);
+    assert!(!out.passed);
+    assert!(out.warnings[0].contains("3 screens"));
+}
+
+#[test]
+fn flow_captures_a_repeated_impl_url_once() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("flow.json");
+    std::fs::write(
+        &file,
+        serde_json::json!({
+            "name": "Shop",
+            "lastModified": "2026-01-01T00:00:00Z",
+            "version": "1",
+            "document": {
+                "id": "0:0",
+                "name": "Document",
+                "type": "DOCUMENT",
+                "children": [{
+                    "id": "0:1",
+                    "name": "Flows",
+                    "type": "CANVAS",
+                    "children": [
+                        {"id": "1:1", "name": "Menu closed", "type": "FRAME", "transitionNodeID": "1:2"},
+                        {"id": "1:2", "name": "Menu open", "type": "FRAME"}
+                    ]
+                }]
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::copy(asset("ref.png"), file.with_extension("png")).unwrap();
+    let start = format!("figma-json:{}#node-id=1:1", file.display());
+    let reference = asset("ref.png");
+    let reference = reference.to_str().unwrap();
+    // Only the first step has a mock capture; the second must reuse it.
+    let output = run_compare(
+        &[
+            "flow",
+            "--ref",
+            &start,
+            "--impl",
+            "https://shop.example.test/",
+            "--impl",
+            "https://shop.example.test/",
+            "--format",
+            "json",
+        ],
+        &[
+            ("DPC_MOCK_RENDER_REF_STEP1", reference),
+            ("DPC_MOCK_RENDER_REF_STEP2", reference),
+            ("DPC_MOCK_RENDER_IMPL_STEP1", reference),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let DpcOutput::Flow(out) = parse_output(&output.stdout) else {
+        panic!("expected flow output");
+    };
+    assert!(out.screens.iter().all(|s| s.error.is_none() && s.passed));
+}
+
+#[test]
+fn audit_compares_sitemap_pages_with_routed_references() {
+    let dir = tempdir().unwrap();
+    let sitemap = dir.path().join("sitemap.xml");
+    std::fs::write(
+        &sitemap,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://shop.example.test/</loc></url>
+  <url><loc>https://shop.example.test/products/shoes</loc></url>
+  <url><loc>https://shop.example.test/blog</loc></url>
+</urlset>"#,
+    )
+    .unwrap();
+    std::fs::copy(asset("impl_different.png"), dir.path().join("shoes.png")).unwrap();
+    let reference = asset("ref.png");
+    let reference = reference.to_str().unwrap();
+    let home_rule = format!("/={reference}");
+    let product_rule = format!("/products/*={}/{{1}}.png", dir.path().display());
+    let output = run_compare(
+        &[
+            "audit",
+            "--crawl",
+            sitemap.to_str().unwrap(),
+            "--route",
+            &home_rule,
+            "--route",
+            &product_rule,
+            "--format",
+            "json",
+        ],
+        &[
+            ("DPC_MOCK_RENDER_IMPL_STEP1", reference),
+            ("DPC_MOCK_RENDER_IMPL_STEP2", reference),
+        ],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let DpcOutput::Audit(out) = parse_output(&output.stdout) else {
+        panic!("expected audit output");
+    };
+    let urls: Vec<&str> = out.pages.iter().map(|p| p.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://shop.example.test/",
+            "https://shop.example.test/products/shoes",
+            "https://shop.example.test/blog",
+        ]
+    );
+    let similarity = |idx: usize| out.pages[idx].similarity.unwrap();
+    assert!(out.pages[0].passed && similarity(0) > 0.99);
+    assert!(out.pages[1]
+        .ref_resource
+        .as_ref()
+        .unwrap()
+        .value
+        .ends_with("shoes.png"));
+    assert!(similarity(1) < similarity(0));
+    assert!(out.pages[2].ref_resource.is_none() && out.pages[2].similarity.is_none());
+    assert!(!out.passed);
+    assert!(out.warnings.iter().any(|w| w.contains("/blog")));
+}
+
+#[test]
+fn stdin_and_data_uri_inputs_compare_without_temp_files() {
+    use base64::Engine;
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let png = std::fs::read(asset("ref.png")).unwrap();
+    let data_uri = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&png)
+    );
+    let mut child = Command::new(bin_path())
+        .args([
+            "compare",
+            "--ref",
+            data_uri.as_str(),
+            "--impl",
+            "-",
+            "--format",
+            "json",
+            "--threshold",
+            "0.9",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn compare");
+    child.stdin.take().unwrap().write_all(&png).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "expected success exit, got {:?}: {}",
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(out.passed);
+            assert_eq!(out.impl_resource.value, "stdin");
+            assert!(out
+                .ref_resource
+                .value
+                .starts_with("data:image/png;base64,…"));
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+
+    let output = run_compare(
+        &["compare", "--ref", "-", "--impl", "-", "--format", "json"],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn alt_refs_pick_best_matching_reference() {
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            asset("impl_different.png").to_str().unwrap(),
+            "--alt-ref",
+            asset("ref.png").to_str().unwrap(),
+            "--impl",
+            asset("impl_identical.png").to_str().unwrap(),
+            "--format",
+            "json",
+            "--threshold",
+            "0.90",
+        ],
+        &[],
+    );
+
+    assert!(
+        output.status.success(),
+        "expected success exit, got {:?}",
+        output.status.code()
+    );
+
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(out.passed, "best candidate should pass");
+            assert!(out.ref_resource.value.ends_with("ref.png"));
+            let candidates = out
+                .reference_candidates
+                .expect("candidates should be reported when --alt-ref is used");
+            assert_eq!(candidates.len(), 2);
+            assert!(!candidates[0].selected);
+            assert!(candidates[1].selected);
+            assert!(candidates[1].similarity > candidates[0].similarity);
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
 #[test]
 fn image_inputs_fail_when_below_threshold() {
     let output = run_compare(
@@ -399,6 +1165,121 @@ fn url_inputs_use_mock_renderer() {
     }
 }
 
+#[test]
+fn samples_report_score_spread_and_flag_flaky_captures() {
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            "https://example.com/design",
+            "--impl",
+            "https://example.com/build",
+            "--format",
+            "json",
+            "--threshold",
+            "0.9",
+            "--samples",
+            "3",
+        ],
+        &[
+            ("DPC_MOCK_RENDER_REF", asset("ref.png").to_str().unwrap()),
+            (
+                "DPC_MOCK_RENDER_IMPL",
+                asset("impl_identical.png").to_str().unwrap(),
+            ),
+            // The second capture catches a different frame; the third falls
+            // back to DPC_MOCK_RENDER_IMPL.
+            (
+                "DPC_MOCK_RENDER_IMPL_SAMPLE2",
+                asset("impl_different.png").to_str().unwrap(),
+            ),
+        ],
+    );
+
+    assert_eq!(output.status.code(), Some(0), "median capture should pass");
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            let sampling = out.sampling.expect("sampling stats");
+            assert_eq!(sampling.samples, 3);
+            assert_eq!(sampling.reported_sample, 0);
+            assert!(sampling.unstable);
+            assert!(sampling.similarity.min < sampling.similarity.max);
+            assert!((out.similarity - sampling.similarity.max).abs() < 1e-6);
+            assert!(out
+                .warnings
+                .iter()
+                .any(|w| w.contains("3 implementation captures")));
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
+#[cfg(not(feature = "embeddings"))]
+#[test]
+fn semantic_metric_needs_the_embeddings_feature() {
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            asset("ref.png").to_str().unwrap(),
+            "--impl",
+            asset("impl_identical.png").to_str().unwrap(),
+            "--format",
+            "json",
+            "--metrics",
+            "pixel,semantic",
+            "--semantic-model",
+            "clip-vision.onnx",
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(2));
+    match parse_error(&output.stdout) {
+        DpcOutput::Error(err) => {
+            assert_eq!(err.error.category, ErrorCategory::Config);
+            assert!(
+                err.error.message.contains("`embeddings` feature"),
+                "{}",
+                err.error.message
+            );
+        }
+        other => panic!("expected error payload, got {:?}", other),
+    }
+}
+
+#[test]
+fn samples_reject_static_implementations() {
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            asset("ref.png").to_str().unwrap(),
+            "--impl",
+            "-",
+            "--format",
+            "json",
+            "--samples",
+            "3",
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(2));
+    match parse_error(&output.stdout) {
+        DpcOutput::Error(err) => {
+            assert_eq!(err.error.category, ErrorCategory::Config);
+            assert!(
+                err.error.message.contains("--samples"),
+                "{}",
+                err.error.message
+            );
+            assert!(err.error.message.contains("stdin"), "{}", err.error.message);
+        }
+        other => panic!("expected error payload, got {:?}", other),
+    }
+}
+
 #[test]
 fn figma_inputs_use_mock_renderer() {
     let output = run_compare(
@@ -442,6 +1323,182 @@ fn figma_inputs_use_mock_renderer() {
     }
 }
 
+#[test]
+fn figma_json_inputs_run_offline() {
+    let dir = tempdir().expect("tempdir");
+    let json_path = dir.path().join("frame.json");
+    let png_path = dir.path().join("frame.png");
+    let impl_path = dir.path().join("impl.png");
+
+    let frame = serde_json::json!({
+        "nodes": {
+            "1:2": {
+                "document": {
+                    "id": "1:2",
+                    "name": "Checkout",
+                    "type": "FRAME",
+                    "absoluteBoundingBox": {"x": 0, "y": 0, "width": 8, "height": 8},
+                    "children": [{
+                        "id": "1:3",
+                        "name": "Title",
+                        "type": "TEXT",
+                        "characters": "Pay now",
+                        "absoluteBoundingBox": {"x": 1, "y": 1, "width": 6, "height": 2}
+                    }]
+                }
+            }
+        }
+    });
+    std::fs::write(&json_path, frame.to_string()).unwrap();
+    let img: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_pixel(8, 8, Rgba([200, 50, 50, 255]));
+    img.save(&png_path).unwrap();
+    img.save(&impl_path).unwrap();
+
+    let ref_arg = format!("figma-json:{}", json_path.display());
+    let output = run_compare(
+        &[
+            "compare",
+            "--ref",
+            &ref_arg,
+            "--impl",
+            impl_path.to_str().unwrap(),
+            "--viewport",
+            "8x8",
+            "--format",
+            "json",
+        ],
+        &[("FIGMA_TOKEN", ""), ("FIGMA_OAUTH_TOKEN", "")],
+    );
+
+    assert!(
+        output.status.success(),
+        "offline figma run should pass: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(matches!(out.ref_resource.kind, ResourceKind::Figma));
+            assert!(out.similarity > 0.99);
+            assert!(out.warnings.is_empty(), "{:?}", out.warnings);
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+}
+
+#[test]
+fn figma_frame_with_mismatched_aspect_warns_or_fails_when_strict() {
+    let dir = tempdir().expect("tempdir");
+    let json_path = dir.path().join("frame.json");
+    let png_path = dir.path().join("frame.png");
+    let impl_path = dir.path().join("impl.png");
+
+    let frame = serde_json::json!({
+        "nodes": {
+            "1:2": {
+                "document": {
+                    "id": "1:2",
+                    "name": "Landing",
+                    "type": "FRAME",
+                    "absoluteBoundingBox": {"x": 0, "y": 0, "width": 8, "height": 32},
+                    "children": []
+                }
+            }
+        }
+    });
+    std::fs::write(&json_path, frame.to_string()).unwrap();
+    ImageBuffer::<Rgba<u8>, _>::from_pixel(8, 32, Rgba([200, 50, 50, 255]))
+        .save(&png_path)
+        .unwrap();
+    ImageBuffer::<Rgba<u8>, _>::from_pixel(8, 8, Rgba([200, 50, 50, 255]))
+        .save(&impl_path)
+        .unwrap();
+
+    let ref_arg = format!("figma-json:{}", json_path.display());
+    let args = [
+        "compare",
+        "--ref",
+        &ref_arg,
+        "--impl",
+        impl_path.to_str().unwrap(),
+        "--viewport",
+        "8x8",
+        "--format",
+        "json",
+    ];
+    let envs = [("FIGMA_TOKEN", ""), ("FIGMA_OAUTH_TOKEN", "")];
+
+    let output = run_compare(&args, &envs);
+    match parse_output(&output.stdout) {
+        DpcOutput::Compare(out) => {
+            assert_eq!(out.warnings.len(), 1, "{:?}", out.warnings);
+            assert!(out.warnings[0].contains("8x32"), "{}", out.warnings[0]);
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+
+    let mut strict = args.to_vec();
+    strict.push("--strict-aspect");
+    let output = run_compare(&strict, &envs);
+    assert_eq!(output.status.code(), Some(2));
+    match parse_error(&output.stdout) {
+        DpcOutput::Error(err) => assert!(err.error.message.contains("letterboxed")),
+        other => panic!("expected error output, got {:?}", other),
+    }
+}
+
+#[test]
+fn self_compare_detects_drift_against_previous_run() {
+    let dir = tempdir().expect("tempdir");
+    let baseline = dir.path().join("baseline");
+    let run1 = dir.path().join("run1");
+    std::fs::create_dir_all(&baseline).unwrap();
+    std::fs::copy(asset("ref.png"), baseline.join("impl_screenshot.png")).unwrap();
+
+    let self_compare = |baseline: &Path, artifacts: &Path, capture: &str| {
+        run_compare(
+            &[
+                "self-compare",
+                "https://example.com/build",
+                "--baseline",
+                baseline.to_str().unwrap(),
+                "--artifacts-dir",
+                artifacts.to_str().unwrap(),
+                "--viewport",
+                "100x100",
+                "--format",
+                "json",
+            ],
+            &[("DPC_MOCK_RENDER_IMPL", asset(capture).to_str().unwrap())],
+        )
+    };
+
+    let unchanged = self_compare(&baseline, &run1, "ref.png");
+    assert!(
+        unchanged.status.success(),
+        "identical capture should pass: {}",
+        String::from_utf8_lossy(&unchanged.stdout)
+    );
+    match parse_output(&unchanged.stdout) {
+        DpcOutput::Compare(out) => {
+            assert!(matches!(out.impl_resource.kind, ResourceKind::Url));
+            assert!(out.similarity > 0.99);
+        }
+        other => panic!("expected compare output, got {:?}", other),
+    }
+    assert!(run1.join("impl_screenshot.png").exists());
+
+    // The previous run's artifacts become the next baseline.
+    let drifted = self_compare(&run1, &dir.path().join("run2"), "impl_different.png");
+    assert_eq!(
+        drifted.status.code(),
+        Some(1),
+        "drift should fail threshold"
+    );
+
+    let clobber = self_compare(&run1, &run1, "ref.png");
+    assert_eq!(clobber.status.code(), Some(2));
+}
+
 #[test]
 fn invalid_input_exits_with_fatal_code() {
     let missing = asset("missing.png");